@@ -1,22 +1,560 @@
 import mimetypes
-from fast_c2pa_core import read_c2pa_from_bytes, load_c2pa_settings, convert_to_gray_keep_c2pa
+from fast_c2pa_core import (
+    read_c2pa_from_bytes,
+    read_c2pa_from_file as _read_c2pa_from_path,
+    read_c2pa_from_stream,
+    read_c2pa_fragmented,
+    read_c2pa_json_from_bytes,
+    read_c2pa_encoded_from_bytes,
+    validate_c2pa_from_bytes,
+    verify_manifest_bytes,
+    revalidate,
+    extract_manifest_store,
+    read_c2pa_merged,
+    validate_manifest_definition,
+    get_manifest_definition_schema,
+    get_c2pa_constants,
+    Reader,
+    C2paReader,
+    read_c2pa_batch,
+    read_c2pa_from_zip,
+    read_c2pa_from_email,
+    read_c2pa_items,
+    read_c2pa_digest,
+    scan_to_file,
+    get_generator_icons,
+    read_c2pa_resources,
+    get_claim_thumbnail_fast,
+    set_resource_limits,
+    get_hash_coverage,
+    get_asset_info,
+    get_manifest_signers,
+    read_signing_info,
+    get_assertion_metadata,
+    describe_actions,
+    set_action_translations,
+    set_soft_binding_resolver,
+    set_remote_manifest_fetcher,
+    detect_mime_from_bytes,
+    extract_jumbf,
+    embed_jumbf,
+    strip_c2pa,
+    remove_c2pa,
+    relocate_manifest,
+    register_assertion_decoder,
+    set_resource_resolver,
+    set_transparency_log_verifier,
+    set_trust_anchors,
+    set_allowed_list,
+    enable_trust_checks,
+    refresh_trust_list,
+    set_signature_verifier,
+    set_audit_callback,
+    set_signer_reputation,
+    set_version_limits,
+    to_protobuf,
+    to_sarif,
+    flatten_ingredients,
+    enable_shared_cache,
+    disable_shared_cache,
+    clear_shared_cache,
+    get_cache_stats,
+    set_ocsp_cache,
+    clear_ocsp_cache,
+    get_ocsp_cache_stats,
+    get_shared_cache_stats,
+    shutdown,
+    capabilities,
+    TimeBudgetExceeded,
+    set_max_abandoned_parses,
+    get_abandoned_parses,
+    C2paError,
+    UnsupportedFormatError,
+    ManifestNotFoundError,
+    ValidationError,
+    SigningError,
+    IoError,
+    can_embed,
+    can_sidecar,
+    supported_mime_types,
+    load_c2pa_settings,
+    set_gil_release_threshold,
+    get_gil_release_threshold,
+    configure_http,
+    get_http_config,
+    fetch_url,
+    list_jpeg_segments,
+    read_xmp,
+    read_xmp_provenance,
+    set_remote_reference,
+    repair_c2pa,
+)
+from .constants import (
+    SUCCESS_CODES,
+    Action,
+    AssertionLabel,
+    Relationship,
+    ValidationCode,
+    ValidationState,
+)
+import errno
 import json
+import os
+import sys
+from concurrent.futures import FIRST_COMPLETED, ThreadPoolExecutor, wait
 from pathlib import Path
 
-__all__ = ["read_c2pa_from_file", "read_c2pa_from_bytes", "get_mime_type", "setup_trust_verification", "convert_to_gray_keep_c2pa"]
+try:
+    from fast_c2pa_core import sign_and_embed
+except ImportError:
+    # Slim build without the "signing" cargo feature
+    def sign_and_embed(data, mime_type, manifest_json, signer_config, allow_threads=None,
+                       resources=None, deterministic=False):
+        """Unavailable: this build does not include signing"""
+        raise NotImplementedError(
+            "sign_and_embed is not available in this build; "
+            "rebuild with the 'signing' cargo feature"
+        )
+
+try:
+    from fast_c2pa_core import create_ingredient_from_bytes
+except ImportError:
+    # Slim build without the "signing" cargo feature
+    def create_ingredient_from_bytes(data, mime_type, title=None, relationship="componentOf",
+                                     allow_threads=None):
+        """Unavailable: this build does not include signing"""
+        raise NotImplementedError(
+            "create_ingredient_from_bytes is not available in this build; "
+            "rebuild with the 'signing' cargo feature"
+        )
+
+try:
+    from fast_c2pa_core import add_ingredient_from_url
+except ImportError:
+    # Slim build without the "signing" cargo feature
+    def add_ingredient_from_url(manifest_json, url, relationship="componentOf", title=None,
+                                mime_type=None, download=True):
+        """Unavailable: this build does not include signing"""
+        raise NotImplementedError(
+            "add_ingredient_from_url is not available in this build; "
+            "rebuild with the 'signing' cargo feature"
+        )
+
+try:
+    from fast_c2pa_core import sign_with_manifest_file
+except ImportError:
+    # Slim build without the "signing" cargo feature
+    def sign_with_manifest_file(data, mime_type, manifest_path, signer_config=None, parent=None,
+                                allow_threads=None):
+        """Unavailable: this build does not include signing"""
+        raise NotImplementedError(
+            "sign_with_manifest_file is not available in this build; "
+            "rebuild with the 'signing' cargo feature"
+        )
+
+try:
+    from fast_c2pa_core import CallbackSigner
+except ImportError:
+    # Slim build without the "signing" cargo feature
+    class CallbackSigner:
+        """Unavailable: this build does not include signing"""
+
+        def __init__(self, callback, alg, sign_cert, ta_url=None):
+            raise NotImplementedError(
+                "CallbackSigner is not available in this build; "
+                "rebuild with the 'signing' cargo feature"
+            )
+
+try:
+    from fast_c2pa_core import make_signed_test_asset
+except ImportError:
+    # Slim build without the "signing" cargo feature
+    def make_signed_test_asset(format, options=None):
+        """Unavailable: this build does not include signing"""
+        raise NotImplementedError(
+            "make_signed_test_asset is not available in this build; "
+            "rebuild with the 'signing' cargo feature"
+        )
+
+try:
+    from fast_c2pa_core import set_receipt_signer
+except ImportError:
+    # Slim build without the "signing" cargo feature
+    def set_receipt_signer(signer_config):
+        """Unavailable: this build does not include processing receipts"""
+        raise NotImplementedError(
+            "set_receipt_signer is not available in this build; "
+            "rebuild with the 'signing' cargo feature"
+        )
+
+try:
+    from fast_c2pa_core import verify_receipt
+except ImportError:
+    # Slim build without the "signing" cargo feature
+    def verify_receipt(receipt):
+        """Unavailable: this build does not include processing receipts"""
+        raise NotImplementedError(
+            "verify_receipt is not available in this build; "
+            "rebuild with the 'signing' cargo feature"
+        )
+
+try:
+    from fast_c2pa_core import convert_to_gray_keep_c2pa
+except ImportError:
+    # Slim build without the "transforms" cargo feature
+    def convert_to_gray_keep_c2pa(input_path, output_path, format):
+        """Unavailable: this build does not include image transforms"""
+        raise NotImplementedError(
+            "convert_to_gray_keep_c2pa is not available in this build; "
+            "rebuild with the 'transforms' cargo feature"
+        )
+
+try:
+    from fast_c2pa_core import transform_keep_c2pa
+except ImportError:
+    # Slim build without the "transforms" cargo feature
+    def transform_keep_c2pa(input, output, ops, mime_type=None, allow_color_conversion=False,
+                            with_actions=False, allow_threads=None):
+        """Unavailable: this build does not include image transforms"""
+        raise NotImplementedError(
+            "transform_keep_c2pa is not available in this build; "
+            "rebuild with the 'transforms' cargo feature"
+        )
+
+try:
+    from fast_c2pa_core import get_thumbnail_array
+except ImportError:
+    # Slim build without the "transforms" cargo feature
+    def get_thumbnail_array(data, mime_type, allow_threads=None):
+        """Unavailable: this build does not include image decoding"""
+        raise NotImplementedError(
+            "get_thumbnail_array is not available in this build; "
+            "rebuild with the 'transforms' cargo feature"
+        )
+
+try:
+    from fast_c2pa_core import compare_ingredient_thumbnails
+except ImportError:
+    # Slim build without the "transforms" cargo feature
+    def compare_ingredient_thumbnails(data, mime_type, allow_threads=None):
+        """Unavailable: this build does not include image decoding"""
+        raise NotImplementedError(
+            "compare_ingredient_thumbnails is not available in this build; "
+            "rebuild with the 'transforms' cargo feature"
+        )
+
+try:
+    from fast_c2pa_core import read_c2pa_from_bytes_async
+except ImportError:
+    # Slim build without the "asyncio" cargo feature
+    def read_c2pa_from_bytes_async(data, mime_type, lenient=False, max_ingredient_depth=None,
+                                   ingredient_thumbnails=True, parse_datetimes=False,
+                                   schema_version=None):
+        """Unavailable: this build does not include the tokio runtime"""
+        raise NotImplementedError(
+            "read_c2pa_from_bytes_async is not available in this build; "
+            "rebuild with the 'asyncio' cargo feature"
+        )
+
+try:
+    from fast_c2pa_core import read_c2pa_from_pdf_images
+except ImportError:
+    # Slim build without the "pdf" cargo feature
+    def read_c2pa_from_pdf_images(data, allow_threads=None):
+        """Unavailable: this build does not include PDF support"""
+        raise NotImplementedError(
+            "read_c2pa_from_pdf_images is not available in this build; "
+            "rebuild with the 'pdf' cargo feature"
+        )
+
+try:
+    from fast_c2pa_core import measure_memory
+except ImportError:
+    # Built without the "memory-stats" cargo feature
+    def measure_memory(func, *args, **kwargs):
+        """Unavailable: this build does not count allocations"""
+        raise NotImplementedError(
+            "measure_memory is not available in this build; "
+            "rebuild with the 'memory-stats' cargo feature"
+        )
+
+__all__ = [
+    "read_c2pa_from_file",
+    "read_c2pa_from_stream",
+    "read_c2pa_fragmented",
+    "read_c2pa_from_bytes_async",
+    "read_c2pa_many",
+    "read_c2pa_from_bytes",
+    "read_c2pa_json_from_bytes",
+    "read_c2pa_encoded_from_bytes",
+    "validate_c2pa_from_bytes",
+    "verify_manifest_bytes",
+    "revalidate",
+    "extract_manifest_store",
+    "read_c2pa_merged",
+    "sign_and_embed",
+    "create_ingredient_from_bytes",
+    "add_ingredient_from_url",
+    "sign_with_manifest_file",
+    "CallbackSigner",
+    "make_signed_test_asset",
+    "validate_manifest_definition",
+    "get_manifest_definition_schema",
+    "get_c2pa_constants",
+    "AssertionLabel",
+    "Action",
+    "Relationship",
+    "ValidationCode",
+    "ValidationState",
+    "SUCCESS_CODES",
+    "Reader",
+    "C2paReader",
+    "read_c2pa_batch",
+    "read_c2pa_from_zip",
+    "read_c2pa_from_email",
+    "read_c2pa_items",
+    "read_c2pa_digest",
+    "scan_to_file",
+    "read_c2pa_from_pdf_images",
+    "get_generator_icons",
+    "read_c2pa_resources",
+    "get_claim_thumbnail_fast",
+    "set_resource_limits",
+    "get_thumbnail_array",
+    "compare_ingredient_thumbnails",
+    "get_hash_coverage",
+    "get_asset_info",
+    "get_manifest_signers",
+    "read_signing_info",
+    "get_assertion_metadata",
+    "describe_actions",
+    "set_action_translations",
+    "set_soft_binding_resolver",
+    "set_remote_manifest_fetcher",
+    "register_assertion_decoder",
+    "set_resource_resolver",
+    "set_transparency_log_verifier",
+    "set_trust_anchors",
+    "set_allowed_list",
+    "enable_trust_checks",
+    "refresh_trust_list",
+    "set_signature_verifier",
+    "set_audit_callback",
+    "set_receipt_signer",
+    "verify_receipt",
+    "set_signer_reputation",
+    "set_version_limits",
+    "to_protobuf",
+    "to_sarif",
+    "flatten_ingredients",
+    "enable_shared_cache",
+    "disable_shared_cache",
+    "clear_shared_cache",
+    "get_cache_stats",
+    "set_ocsp_cache",
+    "clear_ocsp_cache",
+    "get_ocsp_cache_stats",
+    "get_shared_cache_stats",
+    "shutdown",
+    "measure_memory",
+    "capabilities",
+    "TimeBudgetExceeded",
+    "set_max_abandoned_parses",
+    "get_abandoned_parses",
+    "C2paError",
+    "UnsupportedFormatError",
+    "ManifestNotFoundError",
+    "ValidationError",
+    "SigningError",
+    "IoError",
+    "can_embed",
+    "can_sidecar",
+    "supported_mime_types",
+    "get_mime_type",
+    "detect_mime_from_bytes",
+    "extract_jumbf",
+    "embed_jumbf",
+    "strip_c2pa",
+    "remove_c2pa",
+    "relocate_manifest",
+    "setup_trust_verification",
+    "set_gil_release_threshold",
+    "get_gil_release_threshold",
+    "configure_http",
+    "get_http_config",
+    "fetch_url",
+    "convert_to_gray_keep_c2pa",
+    "transform_keep_c2pa",
+    "list_jpeg_segments",
+    "read_xmp_provenance",
+    "read_xmp",
+    "set_remote_reference",
+    "repair_c2pa",
+]
+
+# MIME types of the formats c2pa-rs reads, for extensions the mimetypes
+# database of the platform may not know
+_MIME_TYPES = {
+    ".avif": "image/avif",
+    ".heic": "image/heic",
+    ".heif": "image/heif",
+    ".dng": "image/x-adobe-dng",
+    ".arw": "image/x-sony-arw",
+    ".nef": "image/x-nikon-nef",
+    ".m4a": "audio/mp4",
+    ".wav": "audio/wav",
+    ".pdf": "application/pdf",
+    ".c2pa": "application/c2pa",
+}
 
 def get_mime_type(file_path):
     """Get MIME type of file"""
     mime_type, _ = mimetypes.guess_type(file_path)
+    if mime_type is None:
+        mime_type = _MIME_TYPES.get(os.path.splitext(file_path)[1].lower())
     return mime_type or "application/octet-stream"
 
-def read_c2pa_from_file(file_path, mime_type=None, allow_threads=True):
-    """Read C2PA data from file using Rust core"""
-    # Determine MIME type if not provided
-    effective_mime_type = mime_type if mime_type else get_mime_type(file_path)
-    
-    with open(file_path, 'rb') as f:
-        return read_c2pa_from_bytes(f.read(), effective_mime_type, allow_threads)
+def _read_fd(fd):
+    """Read everything from file descriptor fd without closing it
+
+    Regular files are read from the start without moving the descriptor's offset,
+    so the host process can keep using it; pipes and sockets are read to the end.
+    """
+    chunks = []
+    if hasattr(os, "pread"):
+        try:
+            offset = 0
+            while chunk := os.pread(fd, 1 << 20, offset):
+                chunks.append(chunk)
+                offset += len(chunk)
+            return b"".join(chunks)
+        except OSError as e:
+            if e.errno != errno.ESPIPE:
+                raise
+    while chunk := os.read(fd, 1 << 20):
+        chunks.append(chunk)
+    return b"".join(chunks)
+
+def _fileno(source):
+    """The file descriptor of source, an int or an object with a working fileno(), else None
+
+    In-memory streams such as io.BytesIO, and wrappers without an OS file, have
+    a fileno() that raises io.UnsupportedOperation.
+    """
+    if isinstance(source, int):
+        return source
+    try:
+        return source.fileno()
+    except (AttributeError, OSError):
+        return None
+
+def _read_stream(stream):
+    """Read everything from a binary file object without moving its position, if seekable"""
+    if not stream.seekable():
+        return stream.read()
+    position = stream.tell()
+    try:
+        stream.seek(0)
+        return stream.read()
+    finally:
+        stream.seek(position)
+
+def read_c2pa_from_file(file_path, mime_type=None, allow_threads=None, lenient=False,
+                        max_ingredient_depth=None, ingredient_thumbnails=True,
+                        parse_datetimes=False, schema_version=None,
+                        fetch_remote_manifests=False, remote_manifest_url=None,
+                        remote_timeout_ms=None, deadline_ms=None, detailed=False,
+                        receipt=False, verify_hash_bindings=False):
+    """Read C2PA data from file using Rust core
+
+    file_path can also be an open file descriptor (an int) or an object with a
+    fileno() method, such as a file or socket received from elsewhere, in which
+    case mime_type is required. The descriptor is not closed. Binary file objects
+    without a descriptor, such as io.BytesIO, are read from the start into bytes,
+    detecting their MIME type when none is given. "-" reads the asset from
+    standard input, detecting its MIME type when none is given.
+    """
+    if file_path == "-":
+        return read_c2pa_from_bytes(sys.stdin.buffer.read(), mime_type or None, allow_threads,
+                                    lenient, max_ingredient_depth, ingredient_thumbnails,
+                                    parse_datetimes, schema_version, fetch_remote_manifests,
+                                    remote_manifest_url, remote_timeout_ms, deadline_ms,
+                                    detailed, receipt, verify_hash_bindings)
+    fd = None if isinstance(file_path, (str, os.PathLike)) else _fileno(file_path)
+    if fd is not None:
+        if not mime_type:
+            raise ValueError("mime_type is required when reading from a file descriptor")
+        return read_c2pa_from_bytes(_read_fd(fd), mime_type, allow_threads, lenient,
+                                    max_ingredient_depth, ingredient_thumbnails, parse_datetimes,
+                                    schema_version, fetch_remote_manifests, remote_manifest_url,
+                                    remote_timeout_ms, deadline_ms, detailed, receipt,
+                                    verify_hash_bindings)
+    if hasattr(file_path, "read"):
+        return read_c2pa_from_bytes(_read_stream(file_path), mime_type or None, allow_threads,
+                                    lenient, max_ingredient_depth, ingredient_thumbnails,
+                                    parse_datetimes, schema_version, fetch_remote_manifests,
+                                    remote_manifest_url, remote_timeout_ms, deadline_ms,
+                                    detailed, receipt, verify_hash_bindings)
+
+    # Memory-mapped in Rust, without reading the file into bytes; without a
+    # MIME type, it is detected from the content, else from the extension
+    return _read_c2pa_from_path(file_path, mime_type or None, allow_threads, lenient,
+                                max_ingredient_depth, ingredient_thumbnails, parse_datetimes,
+                                schema_version, fetch_remote_manifests, remote_manifest_url,
+                                remote_timeout_ms, deadline_ms, detailed, receipt,
+                                verify_hash_bindings)
+
+def _read_item(item, mime_type, options):
+    """Read one (id, source[, mime_type]) item of read_c2pa_many"""
+    source = item[1]
+    item_mime_type = item[2] if len(item) > 2 else mime_type
+    if isinstance(source, (str, os.PathLike)) or _fileno(source) is not None or hasattr(source, "read"):
+        return read_c2pa_from_file(source, item_mime_type, **options)
+    return read_c2pa_from_bytes(source, item_mime_type, **options)
+
+def read_c2pa_many(items, mime_type=None, max_in_flight=8, **options):
+    """Read C2PA data for a stream of assets, yielding results as they complete
+
+    items is any iterable of (id, source) or (id, source, mime_type) tuples, where
+    source is bytes, a path, a file descriptor or a binary file object, as taken by
+    read_c2pa_from_file. It is consumed lazily: at most max_in_flight assets
+    are read at once, and the next item is only taken when one of them is done, so
+    memory stays bounded however long the stream is. Items given as bytes, paths or
+    file objects without a MIME type get it from their content, else the extension
+    of the path; file descriptor items use mime_type.
+
+    Other keyword arguments are passed to read_c2pa_from_bytes. Yields
+    (id, result, error) tuples in completion order, where error is None or the
+    message of the exception raised for that item.
+    """
+    if max_in_flight < 1:
+        raise ValueError("max_in_flight must be at least 1")
+    items = iter(items)
+    executor = ThreadPoolExecutor(max_workers=max_in_flight)
+    pending = {}
+    try:
+        exhausted = False
+        while True:
+            while not exhausted and len(pending) < max_in_flight:
+                try:
+                    item = next(items)
+                except StopIteration:
+                    exhausted = True
+                    break
+                future = executor.submit(_read_item, item, mime_type, options)
+                pending[future] = item[0]
+            if not pending:
+                return
+            done, _ = wait(pending, return_when=FIRST_COMPLETED)
+            for future in done:
+                item_id = pending.pop(future)
+                error = future.exception()
+                if error is None:
+                    yield item_id, future.result(), None
+                else:
+                    yield item_id, None, str(error)
+    finally:
+        executor.shutdown(wait=True, cancel_futures=True)
 
 def build_trust_settings_from_files(anchors_path, allowed_path, config_path):
     """Build trust settings from three config files"""
@@ -40,7 +578,7 @@ def build_trust_settings_from_files(anchors_path, allowed_path, config_path):
     except FileNotFoundError as e:
         raise FileNotFoundError(f"Trust config file not found: {e}")
     except Exception as e:
-        raise RuntimeError(f"Error building trust settings: {e}")
+        raise C2paError(f"Error building trust settings: {e}")
     
 def setup_trust_verification(anchors_path, allowed_path, config_path):
     """Setup global C2PA trust settings"""
@@ -0,0 +1,150 @@
+"""Command line interface, run as fast-c2pa or python -m fast_c2pa_python
+
+Every command reads its asset from a path or, given "-" or no path, from standard
+input, and writes its output to standard output unless -o is given, so it can sit
+in a shell pipeline with other media tools:
+
+    curl -s https://example.com/photo.jpg | fast-c2pa strip | convert - -resize 50% small.jpg
+
+Output is binary-safe. The exit status is 0 on success, 1 when the asset has no
+manifest store for read and extract, and 2 on errors.
+"""
+
+import argparse
+import os
+import sys
+from pathlib import Path
+
+from . import (
+    detect_mime_from_bytes,
+    embed_jumbf,
+    extract_jumbf,
+    get_mime_type,
+    read_c2pa_json_from_bytes,
+    sign_with_manifest_file,
+    strip_c2pa,
+)
+
+class CliError(Exception):
+    """An error reported to the user without a traceback"""
+
+def _read_input(source):
+    """Read the asset at source, or from standard input for "-" """
+    if source == "-":
+        return sys.stdin.buffer.read()
+    return Path(source).read_bytes()
+
+def _mime_type(args, data):
+    """The MIME type given, else detected from the content, else from the extension"""
+    mime_type = args.mime_type or detect_mime_from_bytes(data)
+    if mime_type is None and args.input != "-":
+        mime_type = get_mime_type(args.input)
+    if mime_type is None:
+        raise CliError("cannot detect the MIME type of the input, pass --mime-type")
+    return mime_type
+
+def _write_output(data, target, binary=True):
+    """Write data to target, or to standard output for "-" """
+    if target != "-":
+        Path(target).write_bytes(data)
+        return
+    if binary and sys.stdout.isatty():
+        raise CliError("refusing to write binary data to a terminal, pass -o or redirect it")
+    sys.stdout.buffer.write(data)
+    sys.stdout.buffer.flush()
+
+def _read(args, data, mime_type):
+    json = read_c2pa_json_from_bytes(data, mime_type, canonical=args.canonical)
+    if json is None:
+        return 1
+    _write_output(json.encode() + b"\n", args.output, binary=False)
+    return 0
+
+def _sign(args, data, mime_type):
+    signer_config = None
+    if args.cert or args.key:
+        if not (args.cert and args.key):
+            raise CliError("pass both --cert and --key, or neither to use those of the manifest")
+        signer_config = {
+            "alg": args.alg,
+            "sign_cert": Path(args.cert).read_bytes(),
+            "private_key": Path(args.key).read_bytes(),
+        }
+        if args.ta_url:
+            signer_config["ta_url"] = args.ta_url
+    signed = sign_with_manifest_file(data, mime_type, args.manifest, signer_config,
+                                     parent=args.parent)
+    _write_output(signed, args.output)
+    return 0
+
+def _strip(args, data, mime_type):
+    _write_output(strip_c2pa(data, mime_type), args.output)
+    return 0
+
+def _extract(args, data, mime_type):
+    jumbf = extract_jumbf(data, mime_type)
+    if jumbf is None:
+        return 1
+    _write_output(jumbf, args.output)
+    return 0
+
+def _embed(args, data, mime_type):
+    jumbf = Path(args.jumbf).read_bytes()
+    _write_output(embed_jumbf(data, mime_type, jumbf), args.output)
+    return 0
+
+def _parser():
+    parser = argparse.ArgumentParser(
+        prog="fast-c2pa", description="Read, sign and strip C2PA manifest stores"
+    )
+    commands = parser.add_subparsers(dest="command", required=True)
+
+    def command(name, run, help):
+        sub = commands.add_parser(name, help=help, description=help)
+        sub.add_argument("input", nargs="?", default="-",
+                         help='path of the asset, "-" for standard input (default)')
+        sub.add_argument("-o", "--output", default="-",
+                         help='path to write to, "-" for standard output (default)')
+        sub.add_argument("-m", "--mime-type",
+                         help="MIME type of the asset (default: detected from its content)")
+        sub.set_defaults(run=run)
+        return sub
+
+    read = command("read", _read, "Print the manifest store as JSON")
+    read.add_argument("--canonical", action="store_true",
+                      help="deterministically ordered, compact JSON")
+
+    sign = command("sign", _sign, "Sign a manifest and embed it in the asset")
+    sign.add_argument("--manifest", required=True,
+                      help="manifest definition JSON file, c2patool ones included")
+    sign.add_argument("--cert", help="PEM certificate chain of the signer "
+                      "(default: the sign_cert of the manifest)")
+    sign.add_argument("--key", help="PEM private key of the signer "
+                      "(default: the private_key of the manifest)")
+    sign.add_argument("--alg", default="es256",
+                      help="signing algorithm with --cert and --key (default: es256)")
+    sign.add_argument("--ta-url", help="RFC 3161 time stamp authority with --cert and --key")
+    sign.add_argument("--parent", help="asset the input was edited from, as its parent ingredient")
+
+    command("strip", _strip, "Remove the manifest store of the asset")
+    command("extract", _extract, "Write the raw JUMBF manifest store of the asset")
+
+    embed = command("embed", _embed, "Embed a raw JUMBF manifest store in the asset")
+    embed.add_argument("--jumbf", required=True, help="manifest store file, e.g. from extract")
+    return parser
+
+def main(argv=None):
+    """Run the command line interface with argv (default: sys.argv[1:])"""
+    args = _parser().parse_args(argv)
+    try:
+        data = _read_input(args.input)
+        return args.run(args, data, _mime_type(args, data))
+    except BrokenPipeError:
+        # The reading end of the pipeline closed early, e.g. head: stop quietly,
+        # without a second error when Python flushes stdout on exit
+        devnull = os.open(os.devnull, os.O_WRONLY)
+        os.dup2(devnull, sys.stdout.fileno())
+        return 1
+    except (CliError, OSError, ValueError, TypeError, RuntimeError) as e:
+        print(f"fast-c2pa: error: {e}", file=sys.stderr)
+        return 2
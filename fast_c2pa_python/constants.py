@@ -0,0 +1,47 @@
+"""Enums of the standard strings of C2PA manifests
+
+The members are taken from c2pa-rs, so they follow the specification version the
+installed build implements. They are str enums: members compare equal to the
+strings of results, and can be passed wherever a string is expected.
+
+    from fast_c2pa_python import AssertionLabel, ValidationCode, ValidationState
+
+    if result["validation_state"] == ValidationState.TRUSTED:
+        ...
+    codes = {status["code"] for status in result.get("validation_status", [])}
+    if ValidationCode.SIGNING_CREDENTIAL_EXPIRED in codes:
+        ...
+"""
+
+from enum import StrEnum
+
+from fast_c2pa_core import get_c2pa_constants
+
+_CONSTANTS = get_c2pa_constants()
+
+AssertionLabel = StrEnum("AssertionLabel", _CONSTANTS["assertion_labels"])
+AssertionLabel.__doc__ = "Labels of the standard assertions, such as DATA_HASH for c2pa.hash.data"
+
+Action = StrEnum("Action", _CONSTANTS["actions"])
+Action.__doc__ = "Actions of the specification, such as CROPPED for c2pa.cropped"
+
+Relationship = StrEnum("Relationship", _CONSTANTS["relationships"])
+Relationship.__doc__ = "Relationships of an ingredient to the asset it went into"
+
+ValidationCode = StrEnum("ValidationCode", _CONSTANTS["validation_codes"])
+ValidationCode.__doc__ = "Codes of validation_status and validation_results entries"
+
+ValidationState = StrEnum("ValidationState", _CONSTANTS["validation_states"])
+ValidationState.__doc__ = "The validation_state of a result"
+
+#: The validation codes that report a check passing
+SUCCESS_CODES = frozenset(ValidationCode[name] for name in _CONSTANTS["success_codes"])
+
+__all__ = [
+    "AssertionLabel",
+    "Action",
+    "Relationship",
+    "ValidationCode",
+    "ValidationState",
+    "SUCCESS_CODES",
+]
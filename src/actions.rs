@@ -0,0 +1,264 @@
+//! Human-readable descriptions of c2pa actions
+//!
+//! Product UIs tell what was done to an asset in words, not as c2pa.cropped
+//! or c2pa.color_adjustments. The descriptions of the actions the
+//! specification defines are built in for English; other locales, and custom
+//! actions, come from translation tables registered at runtime.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::{json, Value};
+
+use crate::assertion_metadata::decode;
+use crate::c2pa_reader::{embedded_store, value_to_py};
+use crate::gil;
+use crate::jumbf::active_manifest_assertions;
+
+/// The actions of the C2PA specification, with its descriptions
+pub(crate) const ACTIONS: &[(&str, &str)] = &[
+    (
+        "c2pa.color_adjustments",
+        "Changes to tone, saturation, etc.",
+    ),
+    ("c2pa.converted", "The format of the asset was changed."),
+    ("c2pa.created", "The asset was first created."),
+    (
+        "c2pa.cropped",
+        "Areas of the asset's content were cropped out.",
+    ),
+    ("c2pa.deleted", "Areas of the asset's content were deleted."),
+    (
+        "c2pa.drawing",
+        "Changes using drawing tools including brushes or eraser.",
+    ),
+    (
+        "c2pa.dubbed",
+        "Changes were made to the audio, usually one or more tracks.",
+    ),
+    ("c2pa.edited", "Editorial transformations of the content."),
+    (
+        "c2pa.edited.metadata",
+        "Modifications to the asset's metadata.",
+    ),
+    (
+        "c2pa.enhanced",
+        "Enhancements like noise reduction or sharpening were applied.",
+    ),
+    (
+        "c2pa.filtered",
+        "Changes to appearance with applied filters, styles, etc.",
+    ),
+    ("c2pa.opened", "An existing asset was opened."),
+    (
+        "c2pa.orientation",
+        "Changes to the direction and position of content.",
+    ),
+    (
+        "c2pa.placed",
+        "An ingredient was added to the asset's content.",
+    ),
+    (
+        "c2pa.published",
+        "The asset was released to a wider audience.",
+    ),
+    (
+        "c2pa.redacted",
+        "One or more assertions or content were redacted.",
+    ),
+    ("c2pa.removed", "An ingredient was removed."),
+    (
+        "c2pa.repackaged",
+        "The content was moved to another container format without transcoding.",
+    ),
+    (
+        "c2pa.resized",
+        "Changes to content dimensions and/or file size.",
+    ),
+    (
+        "c2pa.transcoded",
+        "The content was converted from one encoding to another.",
+    ),
+    ("c2pa.translated", "Changes to the language of the content."),
+    ("c2pa.trimmed", "A time range of the content was removed."),
+    (
+        "c2pa.unknown",
+        "Something was done to the asset, but the tool cannot say what.",
+    ),
+    ("c2pa.watermarked", "An invisible watermark was inserted."),
+    (
+        "c2pa.watermarked.bound",
+        "An invisible watermark bound to this manifest was inserted.",
+    ),
+];
+
+/// Registered translation tables, by normalized locale
+static TRANSLATIONS: LazyLock<Mutex<HashMap<String, HashMap<String, String>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// "en_US" and "en-us" alike become "en-us"
+fn normalize(locale: &str) -> String {
+    locale.replace('_', "-").to_ascii_lowercase()
+}
+
+/// The locales to look an action up in: the locale, its parent languages, then English
+fn fallbacks(locale: &str) -> Vec<String> {
+    let mut locales = Vec::new();
+    let mut locale = normalize(locale);
+    loop {
+        locales.push(locale.clone());
+        match locale.rfind('-') {
+            Some(end) => locale.truncate(end),
+            None => break,
+        }
+    }
+    if !locales.iter().any(|l| l == "en") {
+        locales.push("en".to_owned());
+    }
+    locales
+}
+
+/// The description of `action` in the first of `locales` that has one
+fn describe(
+    translations: &HashMap<String, HashMap<String, String>>,
+    locales: &[String],
+    action: &str,
+) -> Option<String> {
+    locales.iter().find_map(|locale| {
+        let translated = translations.get(locale).and_then(|table| table.get(action));
+        match translated {
+            Some(description) => Some(description.clone()),
+            None if locale == "en" => ACTIONS
+                .iter()
+                .find(|(code, _)| *code == action)
+                .map(|(_, description)| (*description).to_owned()),
+            None => None,
+        }
+    })
+}
+
+fn is_actions(label: &str) -> bool {
+    let base = label.split("__").next().unwrap_or(label);
+    base == "c2pa.actions" || base == "c2pa.actions.v2"
+}
+
+/// Describe the actions of the active manifest in `store`
+fn actions(store: &[u8], locale: &str) -> Vec<Value> {
+    let locales = fallbacks(locale);
+    let translations = TRANSLATIONS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut described = Vec::new();
+    for (_, label, content) in active_manifest_assertions(store) {
+        if !is_actions(label) {
+            continue;
+        }
+        let Some(Value::Array(list)) = decode(content).and_then(|mut data| data.remove("actions"))
+        else {
+            continue;
+        };
+        for action in list {
+            let Some(code) = action.get("action").and_then(Value::as_str) else {
+                continue;
+            };
+            // The tool's own wording for actions no table knows
+            let description = describe(&translations, &locales, code).or_else(|| {
+                action
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .map(str::to_owned)
+            });
+            described.push(json!({
+                "action": code,
+                "description": description,
+                "assertion": label,
+                "data": action,
+            }));
+        }
+    }
+    described
+}
+
+/// Describe the actions of the active manifest in words
+///
+/// Each action code is looked up in the translation table of `locale`, then of
+/// its parent languages ("pt-br", then "pt"), then in English, where the
+/// actions of the C2PA specification are built in. Actions no table knows keep
+/// the description the tool recorded, if any. The actions are only read, not
+/// validated: use read_c2pa_from_bytes for the validation results.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     locale: Locale of the descriptions, e.g. "fr" or "pt_BR" (default: "en")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     None if no manifest store is embedded, else a list of dicts in manifest
+///     order with "action" (the code), "description" (None when unknown),
+///     "assertion" (the label of the actions assertion) and "data" (the action
+///     as recorded)
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, locale="en", allow_threads=None))]
+pub fn describe_actions(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    locale: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<Option<PyObject>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let read = || {
+        let store = embedded_store(data, mime_type)?;
+        Some(Value::Array(actions(&store, locale)))
+    };
+    let actions = if allow_threads {
+        py.allow_threads(read)
+    } else {
+        read()
+    };
+    actions.map(|actions| value_to_py(py, &actions)).transpose()
+}
+
+/// Set the translation table of a locale used by describe_actions
+///
+/// A table for "en" takes precedence over the built-in descriptions, so it can
+/// reword them or describe custom actions; tables for other locales need not be
+/// complete, missing actions fall back to the parent language and to English.
+///
+/// Args:
+///     locale: Locale of the table, e.g. "fr" or "pt-BR" (case and "_" or "-" do not matter)
+///     translations: A dict of action code to description, or None to remove the table
+///
+/// Raises:
+///     TypeError: If translations is not a dict of str to str
+#[pyfunction]
+#[pyo3(signature = (locale, translations))]
+pub fn set_action_translations(
+    locale: &str,
+    translations: Option<&Bound<'_, PyDict>>,
+) -> PyResult<()> {
+    let table = match translations {
+        None => None,
+        Some(translations) => {
+            let mut table = HashMap::new();
+            for (action, description) in translations.iter() {
+                let (Ok(action), Ok(description)) =
+                    (action.extract::<String>(), description.extract::<String>())
+                else {
+                    return Err(PyTypeError::new_err("translations must map str to str"));
+                };
+                table.insert(action, description);
+            }
+            Some(table)
+        }
+    };
+    let mut translations = TRANSLATIONS.lock().unwrap_or_else(|e| e.into_inner());
+    match table {
+        Some(table) => translations.insert(normalize(locale), table),
+        None => translations.remove(&normalize(locale)),
+    };
+    Ok(())
+}
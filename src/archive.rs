@@ -0,0 +1,163 @@
+//! Reading manifests from assets inside ZIP archives
+//!
+//! Bulk upload bundles and ZIP-based containers (EPUB, DOCX and the like) hold
+//! many assets. The archive is walked in Rust and each supported member is read
+//! in turn, without a round trip through Python per member.
+
+use std::fs::File;
+use std::io::{Cursor, Read, Seek};
+use std::path::PathBuf;
+
+use c2pa::format_from_path;
+use c2pa::jumbf_io::get_supported_types;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use serde_json::Value;
+use zip::ZipArchive;
+
+use crate::c2pa_reader::{read_contained, set_contained_result};
+use crate::errors::C2paError;
+use crate::gil;
+
+/// Outcome of reading one archive member
+struct Member {
+    name: String,
+    mime_type: String,
+    size: u64,
+    result: Result<Option<Value>, String>,
+}
+
+/// Where the archive is read from
+enum Source<'a> {
+    Bytes(&'a [u8]),
+    File(File),
+}
+
+/// Match `name` against a glob `pattern`, where `*` matches any run of
+/// characters (including `/`) and `?` matches a single character
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn scan_archive<R: Read + Seek>(reader: R, pattern: Option<&str>) -> Result<Vec<Member>, String> {
+    let mut archive =
+        ZipArchive::new(reader).map_err(|e| format!("Error reading ZIP archive: {e}"))?;
+    let supported = get_supported_types();
+
+    let mut members = Vec::new();
+    for index in 0..archive.len() {
+        let mut file = archive
+            .by_index(index)
+            .map_err(|e| format!("Error reading ZIP archive: {e}"))?;
+        if file.is_dir() {
+            continue;
+        }
+        let name = file.name().to_owned();
+        if pattern.is_some_and(|pattern| !wildcard_match(pattern, &name)) {
+            continue;
+        }
+        let Some(mime_type) = format_from_path(&name).filter(|m| supported.contains(m)) else {
+            continue;
+        };
+
+        let mut data = Vec::new();
+        let result = match file.read_to_end(&mut data) {
+            Ok(_) => read_contained(&data, &mime_type),
+            Err(e) => Err(format!("Error reading ZIP member: {e}")),
+        };
+        members.push(Member {
+            name,
+            mime_type,
+            size: file.size(),
+            result,
+        });
+    }
+    Ok(members)
+}
+
+/// Read C2PA metadata from every supported asset in a ZIP archive
+///
+/// Members are matched to a media type by their file extension; members of
+/// unsupported types are skipped. A member that fails to parse does not stop the
+/// scan, its error is reported in its entry instead.
+///
+/// Args:
+///     archive: Path to the ZIP file, or its contents as bytes
+///     member_pattern: Only read members whose name matches this glob pattern,
+///         e.g. "images/*.jpg" (default: None, all members)
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A list with one dict per member read, in archive order, with "name",
+///     "mime_type", "size" (uncompressed), "result" (the C2PA data as returned
+///     by read_c2pa_from_bytes, or None) and "error" (a message, or None)
+///
+/// Raises:
+///     OSError: If the archive file cannot be opened
+///     C2paError: If the data is not a readable ZIP archive
+#[pyfunction]
+#[pyo3(signature = (archive, member_pattern=None, allow_threads=None))]
+pub fn read_c2pa_from_zip(
+    py: Python,
+    archive: &Bound<'_, PyAny>,
+    member_pattern: Option<&str>,
+    allow_threads: Option<bool>,
+) -> PyResult<Vec<PyObject>> {
+    let scan = |source: Source| match source {
+        Source::Bytes(data) => scan_archive(Cursor::new(data), member_pattern),
+        Source::File(file) => scan_archive(file, member_pattern),
+    };
+
+    let source = match archive.downcast::<PyBytes>() {
+        Ok(bytes) => Source::Bytes(bytes.as_bytes()),
+        Err(_) => Source::File(File::open(archive.extract::<PathBuf>()?)?),
+    };
+    let len = match &source {
+        Source::Bytes(data) => data.len(),
+        Source::File(file) => file.metadata().map_or(usize::MAX, |m| m.len() as usize),
+    };
+    let allow_threads = gil::release(allow_threads, len);
+    let members = if allow_threads {
+        py.allow_threads(|| scan(source))
+    } else {
+        scan(source)
+    }
+    .map_err(C2paError::new_err)?;
+
+    members
+        .into_iter()
+        .map(|member| {
+            let entry = PyDict::new(py);
+            entry.set_item("name", member.name)?;
+            entry.set_item("mime_type", member.mime_type)?;
+            entry.set_item("size", member.size)?;
+            set_contained_result(py, &entry, member.result)?;
+            Ok(entry.into_any().unbind())
+        })
+        .collect()
+}
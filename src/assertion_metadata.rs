@@ -0,0 +1,124 @@
+//! Metadata about individual assertions
+//!
+//! An assertion can carry a "metadata" map (reviews and ratings, when it was
+//! made, its data source), or be described by a separate
+//! c2pa.assertion.metadata assertion referencing it. c2pa-rs keeps the first
+//! form inside each assertion's data, drops it for assertions it does not list
+//! (hashes, thumbnails) and leaves the second as an unrelated assertion, so
+//! both are collected here per assertion.
+
+use std::collections::BTreeMap;
+
+use coset::cbor::Value as Cbor;
+use pyo3::prelude::*;
+use serde_json::{json, Map, Value};
+
+use crate::c2pa_reader::{embedded_store, value_to_py};
+use crate::gil;
+use crate::jumbf::manifest_assertions;
+
+const METADATA_LABEL: &str = "c2pa.assertion.metadata";
+
+/// Decode an assertion's content box, CBOR or JSON, as a JSON object
+pub(crate) fn decode(content: &[u8]) -> Option<Map<String, Value>> {
+    let value = if content.first() == Some(&b'{') {
+        serde_json::from_slice(content).ok()?
+    } else {
+        let cbor: Cbor = coset::cbor::de::from_reader(content).ok()?;
+        serde_json::to_value(cbor).ok()?
+    };
+    match value {
+        Value::Object(map) => Some(map),
+        _ => None,
+    }
+}
+
+/// The assertion label a hashed URI points at, e.g.
+/// "self#jumbf=c2pa.assertions/c2pa.actions" -> "c2pa.actions"
+fn referenced_label(reference: &Value) -> Option<&str> {
+    let url = reference.get("url")?.as_str()?;
+    url.rsplit('/').next().filter(|label| !label.is_empty())
+}
+
+fn is_metadata_assertion(label: &str) -> bool {
+    label == METADATA_LABEL || label.starts_with("c2pa.assertion.metadata__")
+}
+
+/// The assertion metadata of each manifest in `store`
+fn collect(store: &[u8]) -> (Option<&str>, BTreeMap<&str, Vec<Value>>) {
+    let mut manifests = BTreeMap::new();
+    let mut active = None;
+    for (manifest, label, content) in manifest_assertions(store) {
+        active = Some(manifest);
+        let entries: &mut Vec<Value> = manifests.entry(manifest).or_default();
+        let Some(mut data) = decode(content) else {
+            continue;
+        };
+        if is_metadata_assertion(label) {
+            let assertion = data
+                .get("reference")
+                .and_then(referenced_label)
+                .map(str::to_owned);
+            data.remove("reference");
+            entries.push(json!({
+                "assertion": assertion,
+                "stored_in": label,
+                "metadata": data,
+            }));
+        } else if let Some(metadata @ Value::Object(_)) = data.remove("metadata") {
+            entries.push(json!({
+                "assertion": label,
+                "stored_in": label,
+                "metadata": metadata,
+            }));
+        }
+    }
+    (active, manifests)
+}
+
+/// Get the metadata attached to individual assertions of each manifest
+///
+/// Collects both forms the specification allows: the "metadata" map inside an
+/// assertion, and separate c2pa.assertion.metadata assertions, matched to the
+/// assertion they reference. The metadata is only read, not validated: use
+/// read_c2pa_from_bytes for the validation results.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     None if no manifest store is embedded, else a dict with "active_manifest"
+///     and "manifests", mapping each manifest label to a list of dicts with
+///     "assertion" (the label of the assertion described, None when a metadata
+///     assertion references none), "stored_in" (the label of the assertion the
+///     metadata was found in) and "metadata" (e.g. "dateTime", "reviewRatings",
+///     "dataSource")
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, allow_threads=None))]
+pub fn get_assertion_metadata(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<Option<PyObject>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let read = || {
+        let store = embedded_store(data, mime_type)?;
+        let (active, manifests) = collect(&store);
+        Some(json!({
+            "active_manifest": active,
+            "manifests": manifests,
+        }))
+    };
+    let metadata = if allow_threads {
+        py.allow_threads(read)
+    } else {
+        read()
+    };
+    metadata
+        .map(|metadata| value_to_py(py, &metadata))
+        .transpose()
+}
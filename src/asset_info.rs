@@ -0,0 +1,659 @@
+//! Basic facts about the asset
+//!
+//! Callers often need the dimensions, orientation or duration of an asset
+//! along with its Content Credentials, and decoding it a second time just for
+//! those costs more than reading the manifest store. Read results carry them
+//! as "asset_info", taken from the headers while the asset is read: the image
+//! and media data are skipped, never decoded.
+
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+
+use crate::c2pa_reader::value_to_py;
+use crate::mime;
+
+/// Bodies of headers, chunks and boxes read, at most
+const MAX_BODY: usize = 1 << 20;
+
+/// What the headers of an asset tell
+#[derive(Default)]
+struct AssetInfo {
+    width: Option<u64>,
+    height: Option<u64>,
+    orientation: Option<u16>,
+    color_space: Option<&'static str>,
+    duration: Option<f64>,
+}
+
+impl AssetInfo {
+    fn to_value(&self) -> Value {
+        json!({
+            "width": self.width,
+            "height": self.height,
+            "orientation": self.orientation,
+            "color_space": self.color_space,
+            "duration": self.duration,
+        })
+    }
+}
+
+/// An asset read by offset
+pub(crate) struct Source<R> {
+    inner: R,
+    pub(crate) len: u64,
+}
+
+impl<R: Read + Seek> Source<R> {
+    pub(crate) fn new(mut inner: R) -> Option<Self> {
+        let len = inner.seek(SeekFrom::End(0)).ok()?;
+        Some(Source { inner, len })
+    }
+
+    /// The `len` bytes at `offset`, None past the end of the asset
+    pub(crate) fn read(&mut self, offset: u64, len: usize) -> Option<Vec<u8>> {
+        if len > MAX_BODY || offset.checked_add(len as u64)? > self.len {
+            return None;
+        }
+        self.inner.seek(SeekFrom::Start(offset)).ok()?;
+        let mut buf = vec![0; len];
+        self.inner.read_exact(&mut buf).ok()?;
+        Some(buf)
+    }
+}
+
+pub(crate) fn be_u16(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(pos..pos + 2)?.try_into().ok()?))
+}
+
+pub(crate) fn be_u32(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+fn be_u64(data: &[u8], pos: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(pos..pos + 8)?.try_into().ok()?))
+}
+
+fn le_u16(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(pos..pos + 2)?.try_into().ok()?))
+}
+
+fn le_u24(data: &[u8], pos: usize) -> Option<u32> {
+    let bytes = data.get(pos..pos + 3)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]))
+}
+
+fn le_u32(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+/// The Exif orientation of a clockwise rotation by `quarter_turns` quarter
+/// turns followed, if `flip`, by a horizontal flip
+fn orientation(quarter_turns: u8, flip: bool) -> u16 {
+    match (quarter_turns % 4, flip) {
+        (0, false) => 1,
+        (0, true) => 2,
+        (2, false) => 3,
+        (2, true) => 4,
+        (1, true) => 5,
+        (1, false) => 6,
+        (3, true) => 7,
+        _ => 8,
+    }
+}
+
+/// Byte order of a TIFF structure
+#[derive(Clone, Copy)]
+pub(crate) enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    pub(crate) fn u16(self, data: &[u8], pos: usize) -> Option<u16> {
+        match self {
+            Endian::Little => le_u16(data, pos),
+            Endian::Big => be_u16(data, pos),
+        }
+    }
+
+    pub(crate) fn u32(self, data: &[u8], pos: usize) -> Option<u32> {
+        match self {
+            Endian::Little => le_u32(data, pos),
+            Endian::Big => be_u32(data, pos),
+        }
+    }
+}
+
+/// The tags of a TIFF image file directory with a SHORT or LONG value
+fn tiff_ifd<R: Read + Seek>(
+    source: &mut Source<R>,
+    endian: Endian,
+    offset: u32,
+) -> Option<Vec<(u16, u32)>> {
+    let count = endian.u16(&source.read(offset.into(), 2)?, 0)?;
+    let entries = source.read(u64::from(offset) + 2, usize::from(count) * 12)?;
+    let tags = entries
+        .chunks_exact(12)
+        .filter_map(|entry| {
+            let value = match endian.u16(entry, 2)? {
+                3 => endian.u16(entry, 8)?.into(),
+                4 => endian.u32(entry, 8)?,
+                _ => return None,
+            };
+            Some((endian.u16(entry, 0)?, value))
+        })
+        .collect();
+    Some(tags)
+}
+
+/// The byte order and first directory offset of a TIFF header
+pub(crate) fn tiff_header(head: &[u8]) -> Option<(Endian, u32)> {
+    let endian = match head.get(..4)? {
+        b"II*\0" => Endian::Little,
+        b"MM\0*" => Endian::Big,
+        _ => return None,
+    };
+    Some((endian, endian.u32(head, 4)?))
+}
+
+/// The orientation tag of Exif data, with or without its "Exif\0\0" header
+fn exif_orientation(exif: &[u8]) -> Option<u16> {
+    let tiff = exif.strip_prefix(b"Exif\0\0").unwrap_or(exif);
+    let (endian, offset) = tiff_header(tiff)?;
+    let mut source = Source::new(Cursor::new(tiff))?;
+    let tags = tiff_ifd(&mut source, endian, offset)?;
+    let (_, value) = tags.into_iter().find(|(tag, _)| *tag == 274)?;
+    u16::try_from(value)
+        .ok()
+        .filter(|value| (1..=8).contains(value))
+}
+
+fn tiff<R: Read + Seek>(source: &mut Source<R>, info: &mut AssetInfo) -> Option<()> {
+    let (endian, offset) = tiff_header(&source.read(0, 8)?)?;
+    let mut tags = tiff_ifd(source, endian, offset)?;
+    let tag = |tags: &[(u16, u32)], id: u16| tags.iter().find(|(tag, _)| *tag == id).map(|t| t.1);
+    info.orientation = tag(&tags, 274).and_then(|value| u16::try_from(value).ok());
+    // DNG and other raw files start with a preview, the image is a SubIFD
+    if tag(&tags, 254).is_some_and(|kind| kind & 1 == 1) {
+        if let Some(sub) = tag(&tags, 330) {
+            if let Some(sub) = tiff_ifd(source, endian, sub) {
+                if tag(&sub, 254) == Some(0) {
+                    tags = sub;
+                }
+            }
+        }
+    }
+    info.width = tag(&tags, 256).map(u64::from);
+    info.height = tag(&tags, 257).map(u64::from);
+    info.color_space = match tag(&tags, 262)? {
+        0 | 1 => Some("Gray"),
+        2 | 3 | 6 | 32803 | 34892 => Some("RGB"),
+        5 => Some("CMYK"),
+        8..=10 => Some("Lab"),
+        _ => None,
+    };
+    Some(())
+}
+
+fn jpeg<R: Read + Seek>(source: &mut Source<R>, info: &mut AssetInfo) -> Option<()> {
+    let mut pos = 2;
+    loop {
+        let header = source.read(pos, 4).or_else(|| source.read(pos, 2))?;
+        if header[0] != 0xff {
+            return None;
+        }
+        let marker = header[1];
+        match marker {
+            0xff => {
+                pos += 1;
+                continue;
+            }
+            0x01 | 0xd0..=0xd7 => {
+                pos += 2;
+                continue;
+            }
+            0xd9 | 0xda => return Some(()),
+            _ => {}
+        }
+        let length = be_u16(&header, 2)?;
+        let body = pos + 4;
+        match marker {
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                let frame = source.read(body, 6)?;
+                info.height = Some(be_u16(&frame, 1)?.into());
+                info.width = Some(be_u16(&frame, 3)?.into());
+                info.color_space = match frame[5] {
+                    1 => Some("Gray"),
+                    3 => Some("RGB"),
+                    4 => Some("CMYK"),
+                    _ => None,
+                };
+                return Some(());
+            }
+            0xe1 if info.orientation.is_none() => {
+                let segment = source.read(body, usize::from(length).saturating_sub(2))?;
+                if segment.starts_with(b"Exif\0\0") {
+                    info.orientation = exif_orientation(&segment);
+                }
+            }
+            _ => {}
+        }
+        pos += 2 + u64::from(length);
+    }
+}
+
+fn png<R: Read + Seek>(source: &mut Source<R>, info: &mut AssetInfo) -> Option<()> {
+    let mut pos = 8;
+    while let Some(header) = source.read(pos, 8) {
+        let len = be_u32(&header, 0)?;
+        let body = pos + 8;
+        match &header[4..8] {
+            b"IHDR" => {
+                let ihdr = source.read(body, 13)?;
+                info.width = Some(be_u32(&ihdr, 0)?.into());
+                info.height = Some(be_u32(&ihdr, 4)?.into());
+                info.color_space = match ihdr[9] {
+                    0 | 4 => Some("Gray"),
+                    2 | 3 | 6 => Some("RGB"),
+                    _ => None,
+                };
+            }
+            b"eXIf" => {
+                if let Some(exif) = source.read(body, len as usize) {
+                    info.orientation = exif_orientation(&exif);
+                }
+            }
+            b"IEND" => break,
+            _ => {}
+        }
+        pos = body + u64::from(len) + 4;
+    }
+    Some(())
+}
+
+fn gif<R: Read + Seek>(source: &mut Source<R>, info: &mut AssetInfo) -> Option<()> {
+    let screen = source.read(6, 4)?;
+    info.width = Some(le_u16(&screen, 0)?.into());
+    info.height = Some(le_u16(&screen, 2)?.into());
+    info.color_space = Some("RGB");
+    Some(())
+}
+
+/// The chunks of a RIFF file (WebP, WAV, AVI) from `pos` to `end`, with the
+/// offsets of their bodies
+pub(crate) fn riff_chunks<R: Read + Seek>(
+    source: &mut Source<R>,
+    mut pos: u64,
+    end: u64,
+) -> Vec<([u8; 4], u64, u32)> {
+    let mut chunks = Vec::new();
+    while pos + 8 <= end {
+        let Some(header) = source.read(pos, 8) else {
+            break;
+        };
+        let (Ok(id), Some(size)) = (<[u8; 4]>::try_from(&header[..4]), le_u32(&header, 4)) else {
+            break;
+        };
+        let body = pos + 8;
+        chunks.push((id, body, size));
+        pos = body + u64::from(size) + u64::from(size & 1);
+    }
+    chunks
+}
+
+fn webp<R: Read + Seek>(source: &mut Source<R>, info: &mut AssetInfo) -> Option<()> {
+    let mut frames_ms = None::<u64>;
+    let len = source.len;
+    for (id, body, size) in riff_chunks(source, 12, len) {
+        let dimensions = match &id {
+            b"VP8X" => source
+                .read(body, 10)
+                .and_then(|vp8x| Some((le_u24(&vp8x, 4)? + 1, le_u24(&vp8x, 7)? + 1))),
+            b"VP8 " => source.read(body, 10).and_then(|vp8| {
+                Some((
+                    u32::from(le_u16(&vp8, 6)? & 0x3fff),
+                    u32::from(le_u16(&vp8, 8)? & 0x3fff),
+                ))
+            }),
+            b"VP8L" => source.read(body, 5).and_then(|vp8l| {
+                let bits = le_u32(&vp8l, 1)?;
+                Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+            }),
+            b"ANMF" => {
+                if let Some(ms) = source.read(body, 16).and_then(|frame| le_u24(&frame, 12)) {
+                    *frames_ms.get_or_insert(0) += u64::from(ms);
+                }
+                None
+            }
+            b"EXIF" => {
+                if let Some(exif) = source.read(body, size as usize) {
+                    info.orientation = exif_orientation(&exif);
+                }
+                None
+            }
+            _ => None,
+        };
+        if let (Some((width, height)), None) = (dimensions, info.width) {
+            info.width = Some(width.into());
+            info.height = Some(height.into());
+        }
+    }
+    info.color_space = Some("RGB");
+    info.duration = frames_ms.map(|ms| ms as f64 / 1000.0);
+    Some(())
+}
+
+fn wav<R: Read + Seek>(source: &mut Source<R>, info: &mut AssetInfo) -> Option<()> {
+    let (mut byte_rate, mut data_size) = (None, None);
+    let len = source.len;
+    for (id, body, size) in riff_chunks(source, 12, len) {
+        match &id {
+            b"fmt " => byte_rate = source.read(body, 12).and_then(|fmt| le_u32(&fmt, 8)),
+            b"data" => data_size = Some(size),
+            _ => {}
+        }
+    }
+    let byte_rate = byte_rate.filter(|rate| *rate > 0)?;
+    info.duration = Some(f64::from(data_size?) / f64::from(byte_rate));
+    Some(())
+}
+
+fn avi<R: Read + Seek>(source: &mut Source<R>, info: &mut AssetInfo) -> Option<()> {
+    let len = source.len;
+    let hdrl = riff_chunks(source, 12, len)
+        .into_iter()
+        .find(|&(id, body, _)| &id == b"LIST" && source.read(body, 4).as_deref() == Some(b"hdrl"));
+    let (_, body, size) = hdrl?;
+    let header = riff_chunks(source, body + 4, body + u64::from(size));
+    let (_, body, _) = header.into_iter().find(|(id, _, _)| id == b"avih")?;
+    let avih = source.read(body, 40)?;
+    let frame_us = le_u32(&avih, 0)?;
+    let frames = le_u32(&avih, 16)?;
+    info.width = Some(le_u32(&avih, 32)?.into());
+    info.height = Some(le_u32(&avih, 36)?.into());
+    info.duration = Some(f64::from(frames) * f64::from(frame_us) / 1_000_000.0);
+    Some(())
+}
+
+/// The boxes of an ISO base media file from `pos` to `end`, with the offsets
+/// of their bodies and ends, clamped to `end`
+///
+/// The one box walker of the crate: xmp, embed and items read boxes with it.
+pub(crate) fn bmff_boxes<R: Read + Seek>(
+    source: &mut Source<R>,
+    mut pos: u64,
+    end: u64,
+) -> Vec<([u8; 4], u64, u64)> {
+    let mut boxes = Vec::new();
+    while pos + 8 <= end {
+        let Some(header) = source.read(pos, 8) else {
+            break;
+        };
+        let (Some(size), Ok(kind)) = (be_u32(&header, 0), <[u8; 4]>::try_from(&header[4..8]))
+        else {
+            break;
+        };
+        let (body, box_end) = match size {
+            0 => (pos + 8, end),
+            1 => match source.read(pos + 8, 8).and_then(|large| be_u64(&large, 0)) {
+                Some(large) if large >= 16 => (pos + 16, pos.saturating_add(large)),
+                _ => break,
+            },
+            size if size >= 8 => (pos + 8, pos + u64::from(size)),
+            _ => break,
+        };
+        let box_end = box_end.min(end);
+        if body > box_end {
+            break;
+        }
+        boxes.push((kind, body, box_end));
+        pos = box_end;
+    }
+    boxes
+}
+
+/// The body of the first box of `kind` among `boxes`
+fn find_box(boxes: &[([u8; 4], u64, u64)], kind: &[u8; 4]) -> Option<(u64, u64)> {
+    boxes
+        .iter()
+        .find(|(found, _, _)| found == kind)
+        .map(|(_, body, end)| (*body, *end))
+}
+
+/// The duration of a movie header box
+fn mvhd_duration(mvhd: &[u8]) -> Option<f64> {
+    let (timescale, duration) = match mvhd.first()? {
+        1 => (be_u32(mvhd, 20)?, be_u64(mvhd, 24)?),
+        _ => (be_u32(mvhd, 12)?, be_u32(mvhd, 16)?.into()),
+    };
+    if timescale == 0 || duration == u64::MAX || duration == u64::from(u32::MAX) {
+        return None;
+    }
+    Some(duration as f64 / f64::from(timescale))
+}
+
+/// The dimensions and orientation of a video track header box
+fn tkhd_video(tkhd: &[u8]) -> Option<(u64, u64, u16)> {
+    let matrix = if tkhd.first()? == &1 { 52 } else { 40 };
+    let fixed = |index: usize| be_u32(tkhd, matrix + 4 * index).map(|v| v as i32 >> 16);
+    let orientation = match (fixed(0)?, fixed(1)?, fixed(3)?, fixed(4)?) {
+        (0, 1, -1, 0) => 6,
+        (-1, 0, 0, -1) => 3,
+        (0, -1, 1, 0) => 8,
+        _ => 1,
+    };
+    let width = be_u32(tkhd, matrix + 36)? >> 16;
+    let height = be_u32(tkhd, matrix + 40)? >> 16;
+    Some((width.into(), height.into(), orientation))
+}
+
+fn movie<R: Read + Seek>(
+    source: &mut Source<R>,
+    moov: (u64, u64),
+    info: &mut AssetInfo,
+) -> Option<()> {
+    let boxes = bmff_boxes(source, moov.0, moov.1);
+    if let Some((body, end)) = find_box(&boxes, b"mvhd") {
+        let mvhd = source.read(body, end.saturating_sub(body).min(32) as usize)?;
+        info.duration = mvhd_duration(&mvhd);
+    }
+    for (_, body, end) in boxes.iter().filter(|(kind, _, _)| kind == b"trak") {
+        let track = bmff_boxes(source, *body, *end);
+        let handler = find_box(&track, b"mdia")
+            .map(|(body, end)| bmff_boxes(source, body, end))
+            .and_then(|media| find_box(&media, b"hdlr"))
+            .and_then(|(body, _)| source.read(body + 8, 4));
+        if handler.as_deref() != Some(b"vide") {
+            continue;
+        }
+        let (body, end) = find_box(&track, b"tkhd")?;
+        let tkhd = source.read(body, end.saturating_sub(body).min(96) as usize)?;
+        if let Some((width, height, orientation)) = tkhd_video(&tkhd) {
+            info.width = Some(width);
+            info.height = Some(height);
+            info.orientation = Some(orientation);
+            break;
+        }
+    }
+    Some(())
+}
+
+/// The color space of a colr box, from the header of its ICC profile
+fn colr_space(colr: &[u8]) -> Option<&'static str> {
+    match colr.get(..4)? {
+        b"prof" | b"rICC" => match colr.get(20..24)? {
+            b"RGB " => Some("RGB"),
+            b"GRAY" => Some("Gray"),
+            b"CMYK" => Some("CMYK"),
+            _ => None,
+        },
+        b"nclx" => Some("RGB"),
+        _ => None,
+    }
+}
+
+/// The property indices, 1-based, associated with `item` by an ipma box
+fn item_properties(ipma: &[u8], item: u32) -> Option<Vec<usize>> {
+    let version = *ipma.first()?;
+    let wide_index = ipma.get(3)? & 1 == 1;
+    let mut pos = 8;
+    for _ in 0..be_u32(ipma, 4)? {
+        let id = if version < 1 {
+            pos += 2;
+            u32::from(be_u16(ipma, pos - 2)?)
+        } else {
+            pos += 4;
+            be_u32(ipma, pos - 4)?
+        };
+        let count = usize::from(*ipma.get(pos)?);
+        pos += 1;
+        let mut indices = Vec::with_capacity(count);
+        for _ in 0..count {
+            if wide_index {
+                indices.push(usize::from(be_u16(ipma, pos)? & 0x7fff));
+                pos += 2;
+            } else {
+                indices.push(usize::from(ipma.get(pos)? & 0x7f));
+                pos += 1;
+            }
+        }
+        if id == item {
+            return Some(indices);
+        }
+    }
+    None
+}
+
+fn heif<R: Read + Seek>(
+    source: &mut Source<R>,
+    meta: (u64, u64),
+    info: &mut AssetInfo,
+) -> Option<()> {
+    // meta is a full box: version and flags come before its children
+    let boxes = bmff_boxes(source, meta.0 + 4, meta.1);
+    let (body, _) = find_box(&boxes, b"pitm")?;
+    let pitm = source.read(body, 8).or_else(|| source.read(body, 6))?;
+    let primary = match pitm[0] {
+        0 => u32::from(be_u16(&pitm, 4)?),
+        _ => be_u32(&pitm, 4)?,
+    };
+    let (body, end) = find_box(&boxes, b"iprp")?;
+    let iprp = bmff_boxes(source, body, end);
+    let (body, end) = find_box(&iprp, b"ipco")?;
+    let properties = bmff_boxes(source, body, end);
+    let (body, end) = find_box(&iprp, b"ipma")?;
+    let ipma = source.read(body, end.saturating_sub(body) as usize)?;
+
+    let (mut turns, mut flip) = (0u8, false);
+    let mut channels = None;
+    for index in item_properties(&ipma, primary)? {
+        let Some(&(kind, body, end)) = properties.get(index.wrapping_sub(1)) else {
+            continue;
+        };
+        let Some(property) = source.read(body, end.saturating_sub(body).min(64) as usize) else {
+            continue;
+        };
+        match &kind {
+            b"ispe" => {
+                info.width = be_u32(&property, 4).map(u64::from);
+                info.height = be_u32(&property, 8).map(u64::from);
+            }
+            // Counterclockwise quarter turns, then mirroring
+            b"irot" => {
+                let ccw = property.first().map_or(0, |angle| angle & 3);
+                turns = if flip { turns + ccw } else { turns + 4 - ccw } % 4;
+            }
+            b"imir" => {
+                let vertical_axis = property.first().is_some_and(|axis| axis & 1 == 0);
+                if !vertical_axis {
+                    turns = (turns + 2) % 4;
+                }
+                flip = !flip;
+            }
+            b"pixi" => channels = property.get(4).copied(),
+            b"colr" if info.color_space.is_none() => info.color_space = colr_space(&property),
+            _ => {}
+        }
+    }
+    info.orientation = Some(orientation(turns, flip));
+    if info.color_space.is_none() {
+        info.color_space = match channels {
+            Some(1) => Some("Gray"),
+            _ => Some("RGB"),
+        };
+    }
+    Some(())
+}
+
+fn bmff<R: Read + Seek>(source: &mut Source<R>, info: &mut AssetInfo) -> Option<()> {
+    let len = source.len;
+    let boxes = bmff_boxes(source, 0, len);
+    if let Some(moov) = find_box(&boxes, b"moov") {
+        movie(source, moov, info);
+    }
+    if info.width.is_none() {
+        if let Some(meta) = find_box(&boxes, b"meta") {
+            heif(source, meta, info);
+        }
+    }
+    Some(())
+}
+
+/// The asset info of an asset read from `reader`, with all keys None for
+/// formats and files the headers tell nothing about
+pub(crate) fn read_asset_info(reader: impl Read + Seek, mime_type: &str) -> Value {
+    let mut info = AssetInfo::default();
+    if let Some(mut source) = Source::new(reader) {
+        let head = source.read(0, 16).unwrap_or_default();
+        let format = mime::sniff(&head).unwrap_or(mime_type);
+        match format {
+            "image/jpeg" => jpeg(&mut source, &mut info),
+            "image/png" => png(&mut source, &mut info),
+            "image/gif" => gif(&mut source, &mut info),
+            "image/webp" => webp(&mut source, &mut info),
+            "image/tiff" => tiff(&mut source, &mut info),
+            "audio/wav" => wav(&mut source, &mut info),
+            "video/avi" => avi(&mut source, &mut info),
+            "image/heic" | "image/heif" | "image/avif" | "video/mp4" | "video/quicktime"
+            | "audio/mp4" => bmff(&mut source, &mut info),
+            _ => None,
+        };
+    }
+    info.to_value()
+}
+
+/// Add the asset info of the asset read from `reader` to a read result
+pub(crate) fn add_asset_info(value: &mut Value, reader: impl Read + Seek, mime_type: &str) {
+    if let Some(map) = value.as_object_mut() {
+        map.insert("asset_info".to_owned(), read_asset_info(reader, mime_type));
+    }
+}
+
+/// Get the dimensions, orientation, color space and duration of an asset
+///
+/// The same "asset_info" as in read results, for assets without a manifest
+/// store. Only the headers are read.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data, or None to detect it from the data
+///         (default: None)
+///
+/// Returns:
+///     A dict with "width" and "height" in pixels as stored, before
+///     "orientation" (the Exif orientation, 1 to 8, from the Exif data of
+///     images, the rotation of HEIF images or the matrix of video tracks) is
+///     applied, "color_space" ("RGB", "Gray", "CMYK" or "Lab") and "duration"
+///     in seconds for video, audio and animated WebP. Keys the headers do not
+///     tell are None.
+///
+/// Raises:
+///     ValueError: If mime_type is None and the format of the data is not recognized
+#[pyfunction]
+#[pyo3(signature = (data, mime_type=None))]
+pub fn get_asset_info(py: Python, data: &[u8], mime_type: Option<&str>) -> PyResult<PyObject> {
+    let mime_type = mime::resolve(mime_type, &[data])?;
+    value_to_py(py, &read_asset_info(Cursor::new(data), &mime_type))
+}
@@ -0,0 +1,115 @@
+//! Awaitable reads for asyncio
+//!
+//! asyncio services otherwise wrap every read in run_in_executor and size a
+//! thread pool for it. Here the read runs on the blocking threads of one
+//! tokio runtime, started on the first call and kept for the life of the
+//! process, and the event loop only awaits its result.
+
+use pyo3::prelude::*;
+
+use crate::c2pa_reader::{bytes_chunks, read_chunks_result};
+use crate::deadline;
+use crate::errors::C2paError;
+use crate::lifecycle::Worker;
+use crate::mime;
+use crate::remote::RemoteOptions;
+
+/// Read C2PA metadata from a byte array without blocking the event loop
+///
+/// The same read as read_c2pa_from_bytes, returning an awaitable. The asset is
+/// parsed on a thread of the module's tokio runtime with the GIL released, so
+/// other tasks keep running meanwhile; registered hooks (assertion decoders,
+/// verifiers, audit callbacks) run on that thread under the GIL. Cancelling
+/// the awaiting task does not stop a read already started.
+///
+/// Args:
+///     data: As for read_c2pa_from_bytes: bytes-like, or a list or tuple of
+///         bytes-like chunks. The buffers are held until the read completes and
+///         must not be modified meanwhile.
+///     mime_type: MIME type of the data (e.g., "image/jpeg"), or None to detect it
+///         from the data (default: None)
+///     lenient: As for read_c2pa_from_bytes (default: False)
+///     max_ingredient_depth: As for read_c2pa_from_bytes (default: None)
+///     ingredient_thumbnails: As for read_c2pa_from_bytes (default: True)
+///     parse_datetimes: As for read_c2pa_from_bytes (default: False)
+///     schema_version: As for read_c2pa_from_bytes (default: None)
+///     fetch_remote_manifests: As for read_c2pa_from_bytes (default: False). The
+///         remote manifest store is downloaded on the runtime's thread as well.
+///     remote_manifest_url: As for read_c2pa_from_bytes (default: None)
+///     remote_timeout_ms: As for read_c2pa_from_bytes (default: None)
+///     deadline_ms: As for read_c2pa_from_bytes (default: None)
+///     detailed: As for read_c2pa_from_bytes (default: False)
+///     receipt: As for read_c2pa_from_bytes (default: False)
+///     verify_hash_bindings: As for read_c2pa_from_bytes (default: False)
+///
+/// Returns:
+///     An awaitable resolving to what read_c2pa_from_bytes returns: a dict, or
+///     None if no C2PA metadata is present
+///
+/// Raises:
+///     TypeError: If data is neither bytes nor a list or tuple of bytes
+///     ValueError: If mime_type is None and the format of the data is not recognized
+///     C2paError: When awaited, if there is an error reading or parsing the C2PA data
+#[pyfunction]
+#[pyo3(signature = (
+    data, mime_type=None, lenient=false, max_ingredient_depth=None, ingredient_thumbnails=true,
+    parse_datetimes=false, schema_version=None, fetch_remote_manifests=false,
+    remote_manifest_url=None, remote_timeout_ms=None, deadline_ms=None, detailed=false,
+    receipt=false, verify_hash_bindings=false
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn read_c2pa_from_bytes_async<'py>(
+    py: Python<'py>,
+    data: &Bound<'py, PyAny>,
+    mime_type: Option<&str>,
+    lenient: bool,
+    max_ingredient_depth: Option<usize>,
+    ingredient_thumbnails: bool,
+    parse_datetimes: bool,
+    schema_version: Option<u32>,
+    fetch_remote_manifests: bool,
+    remote_manifest_url: Option<String>,
+    remote_timeout_ms: Option<u64>,
+    deadline_ms: Option<u64>,
+    detailed: bool,
+    receipt: bool,
+    verify_hash_bindings: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    let remote = RemoteOptions::new(
+        fetch_remote_manifests,
+        remote_manifest_url,
+        remote_timeout_ms,
+    );
+    let buffers = bytes_chunks(data)?;
+    let chunks: Vec<&[u8]> = buffers.iter().map(|buffer| (**buffer).as_ref()).collect();
+    let mime_type = mime::resolve(mime_type, &chunks)?;
+    let worker = Worker::start();
+    let read = move || {
+        let _worker = worker;
+        Python::with_gil(|py| {
+            let chunks: Vec<&[u8]> = buffers.iter().map(|buffer| (**buffer).as_ref()).collect();
+            let _inputs = deadline::Inputs::share(&buffers);
+            read_chunks_result(
+                py,
+                &chunks,
+                &mime_type,
+                Some(true),
+                lenient,
+                max_ingredient_depth,
+                ingredient_thumbnails,
+                parse_datetimes,
+                schema_version,
+                remote,
+                deadline_ms,
+                detailed,
+                receipt,
+                verify_hash_bindings,
+            )
+        })
+    };
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        tokio::task::spawn_blocking(read)
+            .await
+            .map_err(|e| C2paError::new_err(format!("Error reading C2PA data: {e}")))?
+    })
+}
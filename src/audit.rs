@@ -0,0 +1,121 @@
+//! Audit events of validation decisions
+//!
+//! Compliance environments have to record why content was trusted or not,
+//! not only the verdict. In audit mode every validation step of a read, each
+//! hash, certificate and trust check c2pa-rs reports and each check of the
+//! library itself, is handed to a Python callback as a structured event,
+//! followed by the decision it led to.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+
+use crate::c2pa_reader::value_to_py;
+use crate::sarif::findings;
+
+static AUDITOR: Mutex<Option<PyObject>> = Mutex::new(None);
+
+/// Numbers the audited reads, so their events can be grouped
+static READ_IDS: AtomicU64 = AtomicU64::new(0);
+
+/// The events of a result: a "validation.step" per validation status, then
+/// the "validation.decision"
+fn events(value: &Value, operation: &str, read_id: u64) -> Vec<Value> {
+    let findings = findings(value, true);
+    let failures = findings
+        .iter()
+        .filter(|finding| finding.outcome == "failure")
+        .count();
+    let mut events: Vec<Value> = findings
+        .iter()
+        .map(|finding| {
+            let status = finding.status;
+            let code = status.get("code").and_then(Value::as_str);
+            json!({
+                "event": "validation.step",
+                "read_id": read_id,
+                "operation": operation,
+                // The family of the check, e.g. "assertion" or "signingCredential"
+                "step": code.map(|code| code.split('.').next().unwrap_or(code)),
+                "code": code,
+                "outcome": finding.outcome,
+                "manifest": finding.manifest(),
+                "ingredient": finding.ingredient,
+                "url": status.get("url"),
+                "explanation": status.get("explanation"),
+            })
+        })
+        .collect();
+    events.push(json!({
+        "event": "validation.decision",
+        "read_id": read_id,
+        "operation": operation,
+        "active_manifest": value.get("active_manifest"),
+        "validation_state": value.get("validation_state"),
+        "failures": failures,
+    }));
+    events
+}
+
+/// Hand the events of a result to the registered callback, if any
+///
+/// `operation` is "read" for results read from an asset and "verify" for
+/// manifest stores verified against one.
+pub(crate) fn audit(py: Python, value: &Value, operation: &str) -> PyResult<()> {
+    let Some(auditor) = AUDITOR
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|auditor| auditor.clone_ref(py))
+    else {
+        return Ok(());
+    };
+    let read_id = READ_IDS.fetch_add(1, Ordering::Relaxed) + 1;
+    for event in events(value, operation, read_id) {
+        auditor.call1(py, (value_to_py(py, &event)?,))?;
+    }
+    Ok(())
+}
+
+/// Register a callback receiving an audit event for each validation step
+///
+/// For each result of read_c2pa_from_file, read_c2pa_from_bytes,
+/// read_c2pa_json_from_bytes, read_c2pa_encoded_from_bytes,
+/// verify_manifest_bytes and Reader.from_bytes, `callback(event)` is called
+/// with one dict per validation status, active manifest and ingredients
+/// alike, then one for the decision:
+///
+/// - {"event": "validation.step", "read_id", "operation", "step", "code",
+///   "outcome", "manifest", "ingredient", "url", "explanation"}, "outcome"
+///   being "success", "informational" or "failure" as in the validation
+///   results, or "warning" for the library's own checks (version limits,
+///   transparency log, truncated assets), and "step" the family of the code,
+///   such as "assertion" (hashes) or "signingCredential" (certificate and trust)
+/// - {"event": "validation.decision", "read_id", "operation",
+///   "active_manifest", "validation_state", "failures"}
+///
+/// "read_id" numbers the results, the same for all the events of one, and
+/// "operation" is "read" or "verify". The events are JSON serializable, ready
+/// for json.dumps. Results served from the shared cache are audited as well.
+/// Exceptions raised by the callback propagate to the caller of the read
+/// function, so a broken audit log does not let reads through unrecorded.
+///
+/// Args:
+///     callback: A callable, or None to turn audit mode off
+///
+/// Raises:
+///     TypeError: If callback is not callable
+#[pyfunction]
+#[pyo3(signature = (callback))]
+pub fn set_audit_callback(callback: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+    if let Some(callback) = &callback {
+        if !callback.is_callable() {
+            return Err(PyTypeError::new_err("audit callback must be callable"));
+        }
+    }
+    *AUDITOR.lock().unwrap_or_else(|e| e.into_inner()) = callback.map(Bound::unbind);
+    Ok(())
+}
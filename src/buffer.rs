@@ -0,0 +1,53 @@
+//! Reading assets from any object with the buffer protocol
+//!
+//! PyO3 only extracts `&[u8]` from bytes, so a memoryview, a bytearray, a
+//! numpy array or an mmap would have to be copied into bytes first. Holding a
+//! buffer export instead gives the parser the object's own memory. The export
+//! lasts as long as the [`Buffer`], which keeps the memory in place while the
+//! GIL is released: Python refuses to resize a bytearray or close an mmap
+//! that is exported.
+
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+
+/// The C-contiguous bytes of a Python object with the buffer protocol
+pub(crate) struct Buffer(PyBuffer<u8>);
+
+impl Buffer {
+    /// Export the buffer of the argument `what`, raising TypeError for objects
+    /// without the buffer protocol, with items other than unsigned bytes, or
+    /// whose memory is not contiguous
+    pub(crate) fn new(data: &Bound<'_, PyAny>, what: &str) -> PyResult<Self> {
+        let Ok(buffer) = PyBuffer::<u8>::get(data) else {
+            return Err(PyTypeError::new_err(format!(
+                "{what} must be bytes-like, not {}",
+                data.get_type().name()?
+            )));
+        };
+        if !buffer.is_c_contiguous() {
+            return Err(PyTypeError::new_err(format!("{what} must be C-contiguous")));
+        }
+        Ok(Buffer(buffer))
+    }
+
+    /// The bytes of the buffer, without copying them
+    ///
+    /// Writable buffers are read in place, so they must not be modified
+    /// while a call reads them.
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        let len = self.0.len_bytes();
+        if len == 0 {
+            return &[];
+        }
+        // Safety: the buffer is C-contiguous with items of one byte, so its
+        // memory is `len` bytes from buf_ptr, exported for the lifetime of self
+        unsafe { std::slice::from_raw_parts(self.0.buf_ptr() as *const u8, len) }
+    }
+}
+
+impl AsRef<[u8]> for Buffer {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
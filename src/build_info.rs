@@ -0,0 +1,174 @@
+//! What the installed build of the module supports
+//!
+//! Wheels can be built with different cargo features, so applications ask at
+//! runtime instead of assuming.
+
+use c2pa::jumbf_io::get_supported_types;
+use c2pa::SigningAlg;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::errors::UnsupportedFormatError;
+use crate::schema::SCHEMA_VERSIONS;
+
+/// Signature algorithms c2pa-rs validates
+const SIGNING_ALGORITHMS: [SigningAlg; 7] = [
+    SigningAlg::Es256,
+    SigningAlg::Es384,
+    SigningAlg::Es512,
+    SigningAlg::Ps256,
+    SigningAlg::Ps384,
+    SigningAlg::Ps512,
+    SigningAlg::Ed25519,
+];
+
+/// Hash algorithms of hashed URIs and hard bindings
+const HASH_ALGORITHMS: [&str; 3] = ["sha256", "sha384", "sha512"];
+
+/// Formats c2pa-rs reads manifests from but has no writer for
+const READ_ONLY_FORMATS: [&str; 6] = [
+    "pdf",
+    "application/pdf",
+    "arw",
+    "image/x-sony-arw",
+    "nef",
+    "image/x-nikon-nef",
+];
+
+/// Manifest store files, which hold a manifest store rather than an asset
+const STORE_FORMATS: [&str; 3] = [
+    "c2pa",
+    "application/c2pa",
+    "application/x-c2pa-manifest-store",
+];
+
+/// Whether c2pa-rs has a writer for assets of `mime_type` and this build signs
+fn can_write(mime_type: &str) -> bool {
+    let mime_type = mime_type.to_ascii_lowercase();
+    let mime_type = mime_type.as_str();
+    cfg!(feature = "signing")
+        && get_supported_types()
+            .iter()
+            .any(|format| format == mime_type)
+        && !READ_ONLY_FORMATS.contains(&mime_type)
+        && !STORE_FORMATS.contains(&mime_type)
+}
+
+/// Aliases the manifest store scanners read besides the formats of c2pa-rs
+const READ_ALIASES: [&str; 2] = ["image/jpg", "audio/mp3"];
+
+/// Whether manifest stores can be read from assets of `mime_type`
+pub(crate) fn can_read(mime_type: &str) -> bool {
+    let mime_type = mime_type.to_ascii_lowercase();
+    READ_ALIASES.contains(&mime_type.as_str())
+        || get_supported_types().contains(&mime_type)
+}
+
+/// The MIME types, without the extensions, c2pa-rs reads manifest stores from
+fn read_mime_types() -> Vec<String> {
+    let mut mime_types: Vec<String> = get_supported_types()
+        .into_iter()
+        .filter(|format| format.contains('/'))
+        .collect();
+    mime_types.sort();
+    mime_types.dedup();
+    mime_types
+}
+
+/// The UnsupportedFormatError for reading assets of `mime_type`, listing the
+/// MIME types that can be read
+pub(crate) fn unsupported_read(mime_type: &str) -> PyErr {
+    UnsupportedFormatError::new_err(format!(
+        "Unsupported format {mime_type:?}, C2PA data is read from {}",
+        read_mime_types().join(", ")
+    ))
+}
+
+/// Report the versions, features and formats of this build
+///
+/// Returns:
+///     A dict with "version" (of this module), "c2pa_version" (of the linked
+///     c2pa-rs), "features" (a dict of feature name to whether it is built in:
+///     "file_io", "remote_fetch", "signing", "transforms", "pdf", "asyncio"
+///     and "memory_stats"),
+///     "signing_algorithms", "hash_algorithms", "formats" (the MIME types
+///     and extensions assets can be read from) and "schema_versions" (the
+///     values schema_version accepts)
+#[pyfunction]
+pub fn capabilities(py: Python) -> PyResult<PyObject> {
+    let features = PyDict::new(py);
+    features.set_item("file_io", true)?;
+    features.set_item("remote_fetch", true)?;
+    features.set_item("signing", cfg!(feature = "signing"))?;
+    features.set_item("transforms", cfg!(feature = "transforms"))?;
+    features.set_item("pdf", cfg!(feature = "pdf"))?;
+    features.set_item("asyncio", cfg!(feature = "asyncio"))?;
+    features.set_item("memory_stats", cfg!(feature = "memory-stats"))?;
+
+    let signing_algorithms: Vec<String> =
+        SIGNING_ALGORITHMS.iter().map(ToString::to_string).collect();
+
+    let capabilities = PyDict::new(py);
+    capabilities.set_item("version", env!("CARGO_PKG_VERSION"))?;
+    capabilities.set_item("c2pa_version", c2pa::VERSION)?;
+    capabilities.set_item("features", features)?;
+    capabilities.set_item("signing_algorithms", signing_algorithms)?;
+    capabilities.set_item("hash_algorithms", HASH_ALGORITHMS.to_vec())?;
+    capabilities.set_item("formats", get_supported_types())?;
+    capabilities.set_item("schema_versions", SCHEMA_VERSIONS.to_vec())?;
+    Ok(capabilities.into_any().unbind())
+}
+
+/// Whether a manifest store can be signed into assets of a format
+///
+/// Args:
+///     mime_type: MIME type (e.g., "image/jpeg") or file extension (e.g., "jpg")
+///
+/// Returns:
+///     True if sign_and_embed can embed a manifest store in such assets. PDF and
+///     camera raw formats other than DNG can only be read, and builds without
+///     the "signing" cargo feature embed none.
+#[pyfunction]
+#[pyo3(signature = (mime_type))]
+pub fn can_embed(mime_type: &str) -> bool {
+    can_write(mime_type)
+}
+
+/// Whether a sidecar manifest store can be signed for assets of a format
+///
+/// The asset is left without a manifest store, the store being published as a
+/// .c2pa file or at a remote URL. c2pa-rs strips any store already in the asset
+/// first, which takes the same writer as embedding, so the formats are those of
+/// can_embed.
+///
+/// Args:
+///     mime_type: MIME type (e.g., "image/jpeg") or file extension (e.g., "jpg")
+///
+/// Returns:
+///     True if a sidecar manifest store can be signed for such assets
+#[pyfunction]
+#[pyo3(signature = (mime_type))]
+pub fn can_sidecar(mime_type: &str) -> bool {
+    can_write(mime_type)
+}
+
+/// List the MIME types the bundled c2pa-rs supports
+///
+/// The lists come from c2pa-rs itself, so they follow upgrades of the SDK.
+/// Several MIME types can name one format, such as "audio/wav" and
+/// "audio/x-wav"; all of them are listed.
+///
+/// Returns:
+///     A dict with "read" (the MIME types of assets manifest stores can be
+///     read from, including manifest store files) and "embed" (those
+///     sign_and_embed can embed a manifest store in, empty without the
+///     "signing" cargo feature), both sorted
+#[pyfunction]
+pub fn supported_mime_types(py: Python) -> PyResult<PyObject> {
+    let read = read_mime_types();
+    let embed: Vec<&String> = read.iter().filter(|format| can_write(format)).collect();
+    let mime_types = PyDict::new(py);
+    mime_types.set_item("embed", embed)?;
+    mime_types.set_item("read", &read)?;
+    Ok(mime_types.into_any().unbind())
+}
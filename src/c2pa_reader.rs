@@ -1,67 +1,1069 @@
 use pyo3::prelude::*;
-use std::io::Cursor;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use c2pa::{
     Reader,
     jumbf_io::load_jumbf_from_stream,
 };
 use log::debug;
-use pyo3::exceptions::PyRuntimeError;
+use memmap2::Mmap;
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::types::{PyBool, PyBytes, PyDict, PyList, PyString, PyTuple};
+use serde_json::{json, Value};
+
+use crate::asset_info::{add_asset_info, read_asset_info};
+use crate::audit::audit;
+use crate::buffer::Buffer;
+use crate::build_info::{can_read, unsupported_read};
+use crate::cache;
+use crate::chunks::{join, read_chunks_value};
+use crate::datetimes;
+use crate::deadline::{self, Owner};
+use crate::detailed::detailed_report;
+use crate::errors::{c2pa_error, C2paError};
+use crate::decoders::decode_assertions;
+use crate::external::resolve_external;
+use crate::gil;
+use crate::ingredients::limit_ingredients;
+use crate::mime;
+use crate::jumbf::{is_complete_store, salvage_manifest_store, scan_manifest_store};
+use crate::remote::{read_remote, RemoteOptions};
+#[cfg(feature = "signing")]
+use crate::receipt::{add_receipt, receipt_signer, Started};
+use crate::reputation::{annotate_signers, has_reputation};
+use crate::revocation::staple;
+use crate::schema;
+use crate::serialize::{encode, sort_keys, to_canonical_json};
+use crate::settings;
+use crate::signature_verifier::{has_signature_verifier, verify_signatures};
+use crate::soft_binding::resolve_soft_binding;
+use crate::stream::{PyReader, PyWriter};
+use crate::transparency::{check_transparency_log, has_verifier};
+use crate::validation::hash_bindings;
+use crate::versions::{check_versions, has_limits};
+use crate::xmp::remote_reference;
+
+/// The raw manifest store embedded in `data`, falling back to the tolerant
+/// scanner when c2pa-rs finds none
+pub(crate) fn embedded_store(data: &[u8], mime_type: &str) -> Option<Vec<u8>> {
+    load_jumbf_from_stream(mime_type, &mut Cursor::new(data))
+        .ok()
+        .or_else(|| scan_manifest_store(data, mime_type).filter(|store| is_complete_store(store)))
+}
+
+/// Parse the manifest store embedded in `data`
+///
+/// Returns `None` when the data carries no JUMBF, so callers can tell "no C2PA"
+/// apart from a malformed manifest store. Does not touch Python state, so it
+/// can run with the GIL released.
+pub(crate) fn parse_reader(data: &[u8], mime_type: &str) -> Option<c2pa::Result<Reader>> {
+    settings::sync();
+    let embedded = load_jumbf_from_stream(mime_type, &mut Cursor::new(data)).ok();
+    let result = embedded
+        .as_ref()
+        .map(|store| {
+            Reader::from_manifest_data_and_stream(&staple(store), mime_type, Cursor::new(data))
+        });
+    if let Some(Ok(reader)) = result {
+        return Some(Ok(reader));
+    }
+
+    // c2pa-rs found no store or could not use the one it assembled, which
+    // happens with stray or repeated JPEG XT packets. Retry with the
+    // tolerant scanner if it assembles a different, complete store.
+    let fallback = scan_manifest_store(data, mime_type)
+        .filter(|store| is_complete_store(store) && Some(store) != embedded.as_ref());
+    match (fallback, result) {
+        (Some(store), result) => {
+            let stapled = staple(&store);
+            match Reader::from_manifest_data_and_stream(&stapled, mime_type, Cursor::new(data)) {
+                Ok(reader) => Some(Ok(reader)),
+                Err(e) => Some(result.unwrap_or(Err(e))),
+            }
+        }
+        (None, result) => result,
+    }
+}
+
+/// Parse the manifest store embedded in `data`, optionally releasing the GIL
+pub(crate) fn read_reader(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: bool,
+) -> PyResult<Option<Reader>> {
+    let result = if allow_threads {
+        py.allow_threads(|| parse_reader(data, mime_type))
+    } else {
+        parse_reader(data, mime_type)
+    };
+
+    match result {
+        Some(Ok(reader)) => Ok(Some(reader)),
+        Some(Err(e)) => Err(c2pa_error("Error reading C2PA data", &e)),
+        None => {
+            // No JUMBF data found
+            debug!("No JUMBF data found in the provided data");
+            Ok(None)
+        }
+    }
+}
+
+/// Convert a parsed manifest store into the JSON value returned to callers
+pub(crate) fn reader_to_value(reader: Reader) -> PyResult<Value> {
+    Value::try_from(reader)
+        .map_err(|e| C2paError::new_err(format!("Error serializing C2PA data: {e}")))
+}
+
+/// Record how the manifest store was obtained, and the asset info of `data`
+///
+/// `is_embedded` tells whether the manifest store came from inside the asset;
+/// `remote_url` is the remote manifest reference found in the asset's XMP, if
+/// any, whether or not it was used.
+pub(crate) fn add_source(value: &mut Value, data: &[u8], mime_type: &str, is_embedded: bool) {
+    let remote_url = remote_reference(data, mime_type, is_embedded);
+    set_source(value, is_embedded, remote_url);
+    add_asset_info(value, Cursor::new(data), mime_type);
+}
+
+/// Record how the manifest store was obtained, with the remote manifest
+/// reference already looked up
+pub(crate) fn set_source(value: &mut Value, is_embedded: bool, remote_url: Option<String>) {
+    if let Some(map) = value.as_object_mut() {
+        map.insert("is_embedded".to_owned(), json!(is_embedded));
+        map.insert("remote_url".to_owned(), json!(remote_url));
+    }
+}
+
+/// Read the embedded manifest store as the JSON value returned to callers,
+/// through the shared cache if enabled
+///
+/// Does not touch Python state, so it can run with the GIL released.
+pub(crate) fn parse_store(data: &[u8], mime_type: &str) -> PyResult<Option<Value>> {
+    let key = cache::key([data], mime_type);
+    if let Some(value) = key.as_ref().and_then(cache::get) {
+        return Ok(Some(value));
+    }
+
+    let reader = match parse_reader(data, mime_type) {
+        Some(Ok(reader)) => reader,
+        Some(Err(e)) => return Err(c2pa_error("Error reading C2PA data", &e)),
+        None => {
+            debug!("No JUMBF data found in the provided data");
+            return Ok(None);
+        }
+    };
+    let mut value = reader_to_value(reader)?;
+    add_source(&mut value, data, mime_type, true);
+    if let Some(key) = &key {
+        cache::put(key, &value);
+    }
+    Ok(Some(value))
+}
+
+/// Read the embedded manifest store, through the shared cache if enabled
+///
+/// Without an embedded manifest store, the registered soft binding resolver
+/// (if any) gets a chance to recover one.
+pub(crate) fn read_store(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: bool,
+) -> PyResult<Option<Value>> {
+    let value = if deadline::is_set() {
+        let (data, mime_type) = (deadline::share(data), mime_type.to_owned());
+        deadline::bounded(py, "parsing", allow_threads, move || {
+            parse_store(data.bytes(), &mime_type)
+        })?
+    } else if allow_threads {
+        py.allow_threads(|| parse_store(data, mime_type))
+    } else {
+        parse_store(data, mime_type)
+    }?;
+    match value {
+        Some(value) => Ok(Some(value)),
+        None => {
+            deadline::check(py, "the soft binding resolver", None)?;
+            resolve_soft_binding(py, data, mime_type, allow_threads)
+        }
+    }
+}
+
+/// Read the manifest store of an asset as the JSON value returned to callers,
+/// with custom assertions decoded, external references resolved, signers
+/// annotated with their reputation and signatures checked against the
+/// transparency log
+pub(crate) fn read_value(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: bool,
+) -> PyResult<Option<Value>> {
+    let mut value = read_store(py, data, mime_type, allow_threads)?;
+    if let Some(value) = &mut value {
+        run_hooks(py, value, "read", allow_threads, || embedded_store(data, mime_type))?;
+    }
+    Ok(value)
+}
+
+/// Decode custom assertions, resolve external references, check the version
+/// limits, annotate signers and check the transparency log of a result, then
+/// audit it as `operation`
+///
+/// `store` gives the manifest store, only called when a hook needs it.
+pub(crate) fn run_hooks(
+    py: Python,
+    value: &mut Value,
+    operation: &str,
+    allow_threads: bool,
+    store: impl FnOnce() -> Option<Vec<u8>> + Send,
+) -> PyResult<()> {
+    deadline::check(py, "the assertion decoders", Some(value))?;
+    decode_assertions(py, value)?;
+    deadline::check(py, "the resource resolver", Some(value))?;
+    resolve_external(py, value)?;
+    let needs_store =
+        has_signature_verifier() || has_verifier() || has_reputation() || has_limits();
+    if needs_store && value.get("soft_binding").is_none() {
+        deadline::check(py, "the signature checks", Some(value))?;
+        let store = if allow_threads {
+            py.allow_threads(store)
+        } else {
+            store()
+        };
+        if let Some(store) = store {
+            verify_signatures(py, value, &store)?;
+            check_versions(value, &store);
+            annotate_signers(value, &store);
+            check_transparency_log(py, value, &store)?;
+        }
+    }
+    audit(py, value, operation)
+}
+
+/// Validate a manifest store kept apart from the asset `data`
+pub(crate) fn verify_value(
+    py: Python,
+    manifest_bytes: &[u8],
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: bool,
+) -> PyResult<Value> {
+    let verify = |manifest_bytes: &[u8], data: &[u8], mime_type: &str| {
+        settings::sync();
+        let stapled = staple(manifest_bytes);
+        Reader::from_manifest_data_and_stream(&stapled, mime_type, Cursor::new(data))
+    };
+    let reader = if deadline::is_set() {
+        let shared = (
+            deadline::share(manifest_bytes),
+            deadline::share(data),
+            mime_type.to_owned(),
+        );
+        deadline::bounded(py, "verifying", allow_threads, move || {
+            let (manifest_bytes, data, mime_type) = shared;
+            verify(manifest_bytes.bytes(), data.bytes(), &mime_type)
+        })?
+    } else if allow_threads {
+        py.allow_threads(|| verify(manifest_bytes, data, mime_type))
+    } else {
+        verify(manifest_bytes, data, mime_type)
+    }
+    .map_err(|e| c2pa_error("Error verifying manifest", &e))?;
+
+    let mut value = reader_to_value(reader)?;
+    add_source(&mut value, data, mime_type, false);
+    Ok(value)
+}
+
+/// Convert a JSON value into the equivalent Python object
+///
+/// The objects are built directly, giving what json.loads would return for
+/// the serialized value without serializing it.
+pub(crate) fn value_to_py(py: Python, value: &Value) -> PyResult<PyObject> {
+    let object = match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => PyBool::new(py, *b).to_owned().into_any().unbind(),
+        Value::Number(n) => {
+            if let Some(n) = n.as_i64() {
+                n.into_pyobject(py)?.into_any().unbind()
+            } else if let Some(n) = n.as_u64() {
+                n.into_pyobject(py)?.into_any().unbind()
+            } else {
+                n.as_f64().unwrap_or(f64::NAN).into_pyobject(py)?.into_any().unbind()
+            }
+        }
+        Value::String(s) => PyString::new(py, s).into_any().unbind(),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(value_to_py(py, item)?)?;
+            }
+            list.into_any().unbind()
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, item) in map {
+                dict.set_item(key, value_to_py(py, item)?)?;
+            }
+            dict.into_any().unbind()
+        }
+    };
+    Ok(object)
+}
+
+/// Convert a Python result dict back into a JSON value
+pub(crate) fn py_to_value(py: Python, result: &Bound<'_, PyAny>) -> PyResult<Value> {
+    let json = py.import("json")?.call_method1("dumps", (result,))?;
+    serde_json::from_str(json.extract::<&str>()?)
+        .map_err(|e| PyValueError::new_err(format!("Invalid result: {e}")))
+}
+
+/// Read an asset found inside a container, such as an archive member
+///
+/// Errors are returned as messages rather than raised, so that one bad asset
+/// does not stop the scan of the others.
+pub(crate) fn read_contained(data: &[u8], mime_type: &str) -> Result<Option<Value>, String> {
+    match parse_reader(data, mime_type) {
+        None => Ok(None),
+        Some(Err(e)) => Err(format!("Error reading C2PA data: {e}")),
+        Some(Ok(reader)) => {
+            let mut value =
+                Value::try_from(reader).map_err(|e| format!("Error serializing C2PA data: {e}"))?;
+            add_source(&mut value, data, mime_type, true);
+            Ok(Some(value))
+        }
+    }
+}
+
+/// Store the outcome of `read_contained` as the "result" and "error" items
+pub(crate) fn set_contained_result(
+    py: Python,
+    entry: &Bound<'_, PyDict>,
+    result: Result<Option<Value>, String>,
+) -> PyResult<()> {
+    match result {
+        Ok(value) => {
+            let value = value.map(|v| value_to_py(py, &v)).transpose()?;
+            entry.set_item("result", value)?;
+            entry.set_item("error", py.None())
+        }
+        Err(e) => {
+            entry.set_item("result", py.None())?;
+            entry.set_item("error", e)
+        }
+    }
+}
+
+/// Recover what we can from an asset cut off inside its manifest store
+///
+/// Returns the result with the salvaged manifest store, if a manifest could be
+/// recovered, or `None` when the asset does not look truncated, so the caller
+/// can fall back to its normal result.
+fn read_truncated(data: &[u8], mime_type: &str) -> PyResult<Option<(Value, Option<Vec<u8>>)>> {
+    let Some(store) = scan_manifest_store(data, mime_type) else {
+        return Ok(None);
+    };
+    let Some(salvaged) = salvage_manifest_store(&store) else {
+        return Ok(None);
+    };
+
+    let recovered = if salvaged.kept > 0 {
+        settings::sync();
+        Reader::from_manifest_data_and_stream(&salvaged.store, mime_type, Cursor::new(data)).ok()
+    } else {
+        None
+    };
+    let (mut value, kept, store) = match recovered {
+        Some(reader) => (reader_to_value(reader)?, salvaged.kept, Some(salvaged.store)),
+        None => (json!({ "manifests": {} }), 0, None),
+    };
+
+    if let Some(map) = value.as_object_mut() {
+        map.insert("validation_state".to_owned(), json!("Invalid"));
+        map.insert(
+            "warnings".to_owned(),
+            json!([{
+                "code": "asset.truncated",
+                "explanation": format!(
+                    "asset is truncated inside the manifest store, recovered {kept} complete manifest(s)"
+                ),
+            }]),
+        );
+    }
+    Ok(Some((value, store)))
+}
+
+/// The buffers of `data`: itself, or the items of a list or tuple
+///
+/// They are shared, so that a parse with a budget can keep them.
+pub(crate) fn bytes_chunks(data: &Bound<'_, PyAny>) -> PyResult<Vec<Owner>> {
+    let items: Vec<Bound<'_, PyAny>> = if let Ok(list) = data.downcast::<PyList>() {
+        list.iter().collect()
+    } else if let Ok(tuple) = data.downcast::<PyTuple>() {
+        tuple.iter().collect()
+    } else {
+        return Ok(vec![Arc::new(Buffer::new(data, "data")?)]);
+    };
+    items
+        .iter()
+        .map(|item| Ok(Arc::new(Buffer::new(item, "data chunks")?) as Owner))
+        .collect()
+}
 
 /// Read C2PA metadata from a byte array
-/// 
-/// This function parses binary data to extract C2PA metadata, returning a Python 
+///
+/// This function parses binary data to extract C2PA metadata, returning a Python
 /// dictionary if found.
 ///
 /// Args:
-///     data: Binary data of the file (bytes-like object)
-///     mime_type: MIME type of the data (e.g., "image/jpeg")
-///     allow_threads: Whether to release the Python GIL during processing (default: True)
+///     data: Binary data of the file: bytes or any other object with the buffer
+///         protocol (bytearray, memoryview, mmap, a uint8 numpy array), read in
+///         place without copying, or a list or tuple of such chunks, such as those
+///         of a streamed upload, parsed without joining them. Writable buffers must
+///         not be modified during the call.
+///     mime_type: MIME type of the data (e.g., "image/jpeg"), or None to detect it
+///         from the data with detect_mime_from_bytes (default: None)
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///     lenient: Recover from assets truncated inside the manifest store (default: False).
+///         The manifests that are fully present are returned, with validation_state
+///         "Invalid" and an "asset.truncated" entry in "warnings".
+///     max_ingredient_depth: Only include the manifests within this many ingredient
+///         levels of the active manifest, 0 for the active manifest alone (default: None,
+///         all). Omitted manifests are reported by an "ingredients.depthLimited" entry in
+///         "warnings".
+///     ingredient_thumbnails: Include the thumbnail references of ingredients (default: True)
+///     parse_datetimes: Return the signing times ("signature_info" "time") and action
+///         "when" timestamps as timezone-aware datetime objects in UTC instead of
+///         strings (default: False)
+///     schema_version: Return the result in this fixed layout version, which stays
+///         the same across c2pa-rs releases (default: None, the layout of the linked
+///         c2pa-rs). Supported: 1
+///     fetch_remote_manifests: For assets without an embedded manifest store, fetch
+///         the remote one their XMP points to, or remote_manifest_url, and validate
+///         it against the asset (default: False). Downloaded with the fetcher of
+///         set_remote_manifest_fetcher if one is registered, else with the settings
+///         of configure_http, recording the attempts in "fetch_attempts".
+///     remote_manifest_url: URL of the remote manifest store to fetch instead of the
+///         XMP reference, such as the X-C2PA-Manifest header of the response the
+///         asset came with (default: None)
+///     remote_timeout_ms: Timeout of each request for the remote manifest store in
+///         milliseconds (default: None, the timeout of configure_http)
+///     deadline_ms: Processing time budget in milliseconds (default: None, no
+///         budget). When it runs out, the read stops and TimeBudgetExceeded is
+///         raised; a parse still running is left to finish in the background,
+///         sharing the input rather than copying it. While
+///         set_max_abandoned_parses of them are running, reads with a budget
+///         fail at once. Python callbacks already started are not interrupted.
+///     detailed: Add "detailed", the low-level report of the embedded manifest
+///         store printed by c2patool --detailed (default: False): the claims as
+///         stored, the full contents of the assertion stores, hashes such as
+///         those of c2pa.hash.boxes in base64, and each claim signature with its
+///         raw COSE_Sign1 bytes in base64 as "cose_sign1". The store is parsed a
+///         second time for it. None for remote manifest stores.
+///     receipt: Add "receipt", a processing receipt signed with the key of
+///         set_receipt_signer (default: False): the hash of the input, a digest
+///         of the result, the library versions and the timing of the read, to
+///         archive as evidence of the check and verify with verify_receipt
+///     verify_hash_bindings: Add "hash_bindings", whether the data, box and BMFF
+///         hashes of the active manifest match the bytes read (default: False):
+///         a dict with "is_intact", False when a hash does not match or the
+///         manifest has no hard binding, and "failures", the labels of the hash
+///         assertions that did not match, such as "c2pa.hash.data"
 ///
 /// Returns:
-///     A dictionary containing the C2PA data if found, or None if no 
-///     C2PA metadata is present
+///     A dictionary containing the C2PA data if found, or None if no
+///     C2PA metadata is present. "is_embedded" tells whether the manifest store
+///     was read from inside the asset and "remote_url" holds the remote manifest
+///     reference from the asset's XMP, or None. For fetched remote stores,
+///     "is_embedded" is False and "remote_url" the URL fetched. "asset_info"
+///     holds the dimensions, orientation, color space and duration of the
+///     asset, as returned by get_asset_info.
 ///
 /// Raises:
-///     RuntimeError: If there is an error reading or parsing the C2PA data
+///     TypeError: If data is not bytes-like, or not C-contiguous
+///     ValueError: If mime_type is None and the format of the data is not
+///         recognized, or receipt is True without set_receipt_signer
+///     C2paError: If there is an error reading or parsing the C2PA data, or
+///         downloading or validating the remote manifest store
+///     PermissionError: If the remote manifest URL is not allowed by configure_http
+///     TimeBudgetExceeded: If deadline_ms runs out. Its "partial" attribute is the
+///         result as far as it got, with a "deadline.exceeded" entry in "warnings",
+///         when the manifest store was parsed in time, else None
+///     NotImplementedError: If receipt is True in a build without the "signing"
+///         cargo feature
 #[pyfunction]
-#[pyo3(signature = (data, mime_type, allow_threads=true))]
+#[pyo3(signature = (
+    data, mime_type=None, allow_threads=None, lenient=false, max_ingredient_depth=None,
+    ingredient_thumbnails=true, parse_datetimes=false, schema_version=None,
+    fetch_remote_manifests=false, remote_manifest_url=None, remote_timeout_ms=None,
+    deadline_ms=None, detailed=false, receipt=false, verify_hash_bindings=false
+))]
+#[allow(clippy::too_many_arguments)]
 pub fn read_c2pa_from_bytes(
     py: Python,
-    data: &[u8],
+    data: &Bound<'_, PyAny>,
+    mime_type: Option<&str>,
+    allow_threads: Option<bool>,
+    lenient: bool,
+    max_ingredient_depth: Option<usize>,
+    ingredient_thumbnails: bool,
+    parse_datetimes: bool,
+    schema_version: Option<u32>,
+    fetch_remote_manifests: bool,
+    remote_manifest_url: Option<String>,
+    remote_timeout_ms: Option<u64>,
+    deadline_ms: Option<u64>,
+    detailed: bool,
+    receipt: bool,
+    verify_hash_bindings: bool,
+) -> PyResult<Option<PyObject>> {
+    let buffers = bytes_chunks(data)?;
+    let chunks: Vec<&[u8]> = buffers.iter().map(|buffer| (**buffer).as_ref()).collect();
+    let mime_type = mime::resolve(mime_type, &chunks)?;
+    let _inputs = deadline::Inputs::share(&buffers);
+    read_chunks_result(
+        py,
+        &chunks,
+        &mime_type,
+        allow_threads,
+        lenient,
+        max_ingredient_depth,
+        ingredient_thumbnails,
+        parse_datetimes,
+        schema_version,
+        RemoteOptions::new(fetch_remote_manifests, remote_manifest_url, remote_timeout_ms),
+        deadline_ms,
+        detailed,
+        receipt,
+        verify_hash_bindings,
+    )
+}
+
+/// The result of read_c2pa_from_bytes for an asset given as `chunks`
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn read_chunks_result(
+    py: Python,
+    chunks: &[&[u8]],
     mime_type: &str,
-    allow_threads: bool,
+    allow_threads: Option<bool>,
+    lenient: bool,
+    max_ingredient_depth: Option<usize>,
+    ingredient_thumbnails: bool,
+    parse_datetimes: bool,
+    schema_version: Option<u32>,
+    remote: Option<RemoteOptions>,
+    deadline_ms: Option<u64>,
+    detailed: bool,
+    receipt: bool,
+    verify_hash_bindings: bool,
 ) -> PyResult<Option<PyObject>> {
-    // First check if JUMBF data exists before trying to create a Reader
-    let has_jumbf = {
-        let mut cursor = Cursor::new(data);
-        load_jumbf_from_stream(mime_type, &mut cursor).is_ok()
+    #[cfg(feature = "signing")]
+    let started = Started::now();
+    let len = chunks.iter().map(|chunk| chunk.len()).sum();
+    let allow_threads = gil::release(allow_threads, len);
+    schema::check_version(schema_version)?;
+    #[cfg(feature = "signing")]
+    let receipt_signer = if receipt { Some(receipt_signer()?) } else { None };
+    #[cfg(not(feature = "signing"))]
+    if receipt {
+        return Err(pyo3::exceptions::PyNotImplementedError::new_err(
+            "receipt needs the 'signing' cargo feature",
+        ));
+    }
+    let _deadline = deadline::Scope::new(deadline_ms);
+    let result = match chunks {
+        [data] => read_value(py, data, mime_type, allow_threads),
+        // The parse is given to a thread of its own to be bounded anyway
+        chunks if deadline::is_set() => {
+            let joined: Owner = Arc::new(chunks.concat());
+            let _inputs = deadline::Inputs::share([&joined]);
+            read_value(py, (*joined).as_ref(), mime_type, allow_threads)
+        }
+        chunks => read_chunks_value(py, chunks, mime_type, allow_threads),
+    };
+    let result = match (result, &remote) {
+        (Ok(None), Some(remote)) => {
+            read_remote(py, &join(chunks), mime_type, remote, allow_threads)
+        }
+        (result, _) => result,
+    };
+    let mut value = match result {
+        Ok(Some(value)) => value,
+        result => {
+            let data = if lenient { Some(join(chunks)) } else { None };
+            let truncated = match &data {
+                Some(data) => read_truncated(data, mime_type)?,
+                None => None,
+            };
+            match (truncated, result) {
+                (Some((mut value, store)), _) => {
+                    add_source(&mut value, data.as_deref().unwrap_or_default(), mime_type, true);
+                    run_hooks(py, &mut value, "read", allow_threads, || store)?;
+                    value
+                }
+                (None, Err(e)) => return Err(e),
+                (None, Ok(_)) if !can_read(mime_type) => return Err(unsupported_read(mime_type)),
+                (None, Ok(_)) => return Ok(None),
+            }
+        }
     };
+    limit_ingredients(&mut value, max_ingredient_depth, ingredient_thumbnails);
+    schema::translate(&mut value, schema_version);
+    if verify_hash_bindings {
+        value["hash_bindings"] = hash_bindings(&value);
+    }
+    if detailed {
+        let report = match detailed_report(py, &join(chunks), mime_type, allow_threads) {
+            Ok(report) => report,
+            Err(_) if lenient => None,
+            Err(e) => return Err(e),
+        };
+        value["detailed"] = report.unwrap_or(Value::Null);
+    }
+    #[cfg(feature = "signing")]
+    if let Some(signer) = receipt_signer {
+        add_receipt(py, &mut value, chunks, mime_type, &signer, &started, allow_threads)?;
+    }
 
-    if !has_jumbf {
-        // No JUMBF data found
-        debug!("No JUMBF data found in the provided data");
-        return Ok(None);
+    let result = value_to_py(py, &value)?;
+    if parse_datetimes {
+        datetimes::parse_datetimes(result.bind(py))?;
     }
+    Ok(Some(result))
+}
+
+/// An OSError naming the file, as open() raises it, so it becomes the
+/// FileNotFoundError or PermissionError subclass matching its errno
+pub(crate) fn os_error(e: std::io::Error, path: &Path) -> PyErr {
+    let Some(errno) = e.raw_os_error() else {
+        return e.into();
+    };
+    let message = e.to_string();
+    let message = match message.rfind(" (os error ") {
+        Some(end) => message[..end].to_owned(),
+        None => message,
+    };
+    PyOSError::new_err((errno, message, path.to_string_lossy().into_owned()))
+}
+
+/// Memory-map the file at `path` to read it
+pub(crate) fn map_file(path: &Path) -> PyResult<Mmap> {
+    let file = File::open(path).map_err(|e| os_error(e, path))?;
+    // SAFETY: the map is only read, and callers are told not to change the
+    // file while it is; a concurrent writer gives a wrong result, as with any read
+    unsafe { Mmap::map(&file) }.map_err(|e| os_error(e, path))
+}
 
-    // JUMBF data exists, proceed with Reader creation
-    let reader = if allow_threads {
-        let cursor = Cursor::new(data);
-        py.allow_threads(|| Reader::from_stream(mime_type, cursor))
+/// The MIME type of the file at `path` from its content `data`, else from its
+/// extension
+pub(crate) fn file_mime_type(data: &[u8], path: &Path) -> String {
+    mime::sniff(data)
+        .map(str::to_owned)
+        .or_else(|| c2pa::format_from_path(path))
+        .unwrap_or_else(|| "application/octet-stream".to_owned())
+}
+
+/// Read C2PA metadata from a file, memory-mapped rather than read into bytes
+///
+/// The file is mapped and parsed in place, so large TIFF, MP4 or PDF assets
+/// are neither copied into a Python bytes object nor across the FFI boundary.
+/// The file must not be modified or truncated while it is read.
+///
+/// Args:
+///     path: Path of the file (str or os.PathLike)
+///     mime_type: MIME type of the file (default: None, detected from its content,
+///         else from its extension)
+///     allow_threads, lenient, max_ingredient_depth, ingredient_thumbnails,
+///         parse_datetimes, schema_version, fetch_remote_manifests,
+///         remote_manifest_url, remote_timeout_ms, deadline_ms, detailed, receipt,
+///         verify_hash_bindings: As for read_c2pa_from_bytes
+///
+/// Returns:
+///     The same as read_c2pa_from_bytes
+///
+/// Raises:
+///     OSError: If the file cannot be opened or mapped
+///     C2paError: If there is an error reading or parsing the C2PA data
+#[pyfunction]
+#[pyo3(signature = (
+    path, mime_type=None, allow_threads=None, lenient=false, max_ingredient_depth=None,
+    ingredient_thumbnails=true, parse_datetimes=false, schema_version=None,
+    fetch_remote_manifests=false, remote_manifest_url=None, remote_timeout_ms=None,
+    deadline_ms=None, detailed=false, receipt=false, verify_hash_bindings=false
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn read_c2pa_from_file(
+    py: Python,
+    path: PathBuf,
+    mime_type: Option<&str>,
+    allow_threads: Option<bool>,
+    lenient: bool,
+    max_ingredient_depth: Option<usize>,
+    ingredient_thumbnails: bool,
+    parse_datetimes: bool,
+    schema_version: Option<u32>,
+    fetch_remote_manifests: bool,
+    remote_manifest_url: Option<String>,
+    remote_timeout_ms: Option<u64>,
+    deadline_ms: Option<u64>,
+    detailed: bool,
+    receipt: bool,
+    verify_hash_bindings: bool,
+) -> PyResult<Option<PyObject>> {
+    let map: Owner = Arc::new(map_file(&path)?);
+    let map_bytes = (*map).as_ref();
+    let mime_type = match mime_type {
+        Some(mime_type) => mime_type.to_owned(),
+        None => file_mime_type(map_bytes, &path),
+    };
+    let _inputs = deadline::Inputs::share([&map]);
+    read_chunks_result(
+        py,
+        &[map_bytes],
+        &mime_type,
+        allow_threads,
+        lenient,
+        max_ingredient_depth,
+        ingredient_thumbnails,
+        parse_datetimes,
+        schema_version,
+        RemoteOptions::new(fetch_remote_manifests, remote_manifest_url, remote_timeout_ms),
+        deadline_ms,
+        detailed,
+        receipt,
+        verify_hash_bindings,
+    )
+}
+
+/// Parse the manifest store embedded in the asset read by `reader`, with the
+/// store itself
+fn parse_stream(
+    reader: &mut PyReader,
+    mime_type: &str,
+) -> Option<c2pa::Result<(Reader, Vec<u8>)>> {
+    settings::sync();
+    let store = load_jumbf_from_stream(mime_type, reader).ok()?;
+    let result = reader.rewind().map_err(c2pa::Error::from).and_then(|_| {
+        Reader::from_manifest_data_and_stream(&staple(&store), mime_type, &mut *reader)
+    });
+    Some(result.map(|parsed| (parsed, store)))
+}
+
+/// The MIME type of the asset read by `reader` from its first bytes, else
+/// from the extension of the name attribute of `file_obj`
+fn stream_mime_type(reader: &mut PyReader, file_obj: &Bound<'_, PyAny>) -> PyResult<String> {
+    let mut head = Vec::with_capacity(mime::HEAD_LEN);
+    let result = reader.by_ref().take(mime::HEAD_LEN as u64).read_to_end(&mut head);
+    reader.check(result)?;
+    let result = reader.rewind();
+    reader.check(result)?;
+    let name = file_obj.getattr("name").ok().and_then(|name| name.extract::<PathBuf>().ok());
+    mime::sniff(&head)
+        .map(str::to_owned)
+        .or_else(|| name.and_then(|name| c2pa::format_from_path(&name)))
+        .ok_or_else(|| {
+            PyValueError::new_err("Cannot detect the MIME type of the data, pass mime_type")
+        })
+}
+
+/// Read C2PA metadata from a binary file object
+///
+/// The asset is read through the object's read() and seek() as c2pa-rs needs
+/// it, so streaming bodies, temporary files and mmap objects are parsed
+/// without being loaded into a bytes object first. The asset is read from the
+/// start of the stream, and its position is restored afterwards.
+///
+/// Args:
+///     file_obj: A seekable binary file object with read(size) and
+///         seek(offset, whence)
+///     mime_type: MIME type of the asset (default: None, detected from its first
+///         bytes, else from the extension of file_obj.name)
+///     allow_threads: Whether to release the Python GIL during processing, taking
+///         it back for every read (default: None, only for assets of at least
+///         get_gil_release_threshold() bytes)
+///     max_ingredient_depth, ingredient_thumbnails, parse_datetimes,
+///         schema_version: As for read_c2pa_from_bytes
+///
+/// Returns:
+///     The same as read_c2pa_from_bytes, with "remote_url" None: the XMP of the
+///     asset is not searched for a remote manifest reference
+///
+/// Raises:
+///     TypeError: If file_obj is not a seekable binary file object
+///     ValueError: If mime_type is None and the format of the asset is not recognized
+///     C2paError: If there is an error reading or parsing the C2PA data
+///     Exceptions raised by file_obj.read() and file_obj.seek()
+#[pyfunction]
+#[pyo3(signature = (
+    file_obj, mime_type=None, allow_threads=None, max_ingredient_depth=None,
+    ingredient_thumbnails=true, parse_datetimes=false, schema_version=None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn read_c2pa_from_stream(
+    py: Python,
+    file_obj: &Bound<'_, PyAny>,
+    mime_type: Option<&str>,
+    allow_threads: Option<bool>,
+    max_ingredient_depth: Option<usize>,
+    ingredient_thumbnails: bool,
+    parse_datetimes: bool,
+    schema_version: Option<u32>,
+) -> PyResult<Option<PyObject>> {
+    schema::check_version(schema_version)?;
+    let mut reader = PyReader::new(file_obj)?;
+    let result = reader.stream_position();
+    let start = reader.check(result)?;
+    let result = reader.seek(SeekFrom::End(0));
+    let len = reader.check(result)?;
+    let result = reader.rewind();
+    reader.check(result)?;
+    let mime_type = match mime_type {
+        Some(mime_type) => mime_type.to_owned(),
+        None => stream_mime_type(&mut reader, file_obj)?,
+    };
+
+    let allow_threads = gil::release(allow_threads, len as usize);
+    let parse = |reader: &mut PyReader| {
+        let parsed = parse_stream(reader, &mime_type)?;
+        Some(parsed.map(|parsed| (parsed, read_asset_info(reader, &mime_type))))
+    };
+    let parsed = if allow_threads {
+        py.allow_threads(|| parse(&mut reader))
     } else {
-        let cursor = Cursor::new(data);
-        Reader::from_stream(mime_type, cursor)
-    };
-    
-    match reader {
-        Ok(reader) => {
-            let json_str = reader.json();
-            let json_module = PyModule::import(py, "json")?;
-            let py_json = json_module.getattr("loads")?.call1((json_str,))?;
-            
-            Ok(Some(py_json.into_py(py)))
-        },
-        Err(e) => {
-            Err(PyRuntimeError::new_err(format!("Error reading C2PA data: {}", e)))
+        parse(&mut reader)
+    };
+    let result = reader.seek(SeekFrom::Start(start));
+    reader.check(result)?;
+
+    let ((parsed, store), asset_info) = match parsed {
+        Some(Ok(parsed)) => parsed,
+        Some(Err(e)) => return Err(c2pa_error("Error reading C2PA data", &e)),
+        None => return Ok(None),
+    };
+    let mut value = reader_to_value(parsed)?;
+    set_source(&mut value, true, None);
+    value["asset_info"] = asset_info;
+    run_hooks(py, &mut value, "read", allow_threads, || Some(store))?;
+    limit_ingredients(&mut value, max_ingredient_depth, ingredient_thumbnails);
+    schema::translate(&mut value, schema_version);
+
+    let result = value_to_py(py, &value)?;
+    if parse_datetimes {
+        datetimes::parse_datetimes(result.bind(py))?;
+    }
+    Ok(Some(result))
+}
+
+/// Read C2PA metadata from a byte array as a JSON string
+///
+/// With `canonical=True` the output is compact JSON with object keys sorted at
+/// every level, so the same manifest store always serializes to the same bytes
+/// and can be diffed or hashed across runs and library versions.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     canonical: Produce deterministically ordered, compact JSON (default: False)
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///     max_ingredient_depth: Only include the manifests within this many ingredient
+///         levels of the active manifest, 0 for the active manifest alone (default: None,
+///         all). Omitted manifests are reported by an "ingredients.depthLimited" entry in
+///         "warnings".
+///     ingredient_thumbnails: Include the thumbnail references of ingredients (default: True)
+///     schema_version: Return the result in this fixed layout version, which stays
+///         the same across c2pa-rs releases (default: None, the layout of the linked
+///         c2pa-rs). Supported: 1
+///     output: A writable file object to stream the JSON to instead of returning it,
+///         in chunks of about chunk_size bytes: str chunks for text files
+///         (io.TextIOBase), bytes for anything else (default: None)
+///     chunk_size: Size of the chunks written to output (default: 65536)
+///
+/// Returns:
+///     The manifest store as a JSON string, or None if no C2PA metadata is present.
+///     With output, the number of bytes of JSON written instead of the string.
+///
+/// Raises:
+///     C2paError: If there is an error reading or parsing the C2PA data
+///     Exceptions raised by output.write()
+#[pyfunction]
+#[pyo3(signature = (
+    data, mime_type, canonical=false, allow_threads=None, max_ingredient_depth=None,
+    ingredient_thumbnails=true, schema_version=None, output=None, chunk_size=65536
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn read_c2pa_json_from_bytes(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    canonical: bool,
+    allow_threads: Option<bool>,
+    max_ingredient_depth: Option<usize>,
+    ingredient_thumbnails: bool,
+    schema_version: Option<u32>,
+    output: Option<Bound<'_, PyAny>>,
+    chunk_size: usize,
+) -> PyResult<Option<PyObject>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    schema::check_version(schema_version)?;
+    let Some(mut value) = read_value(py, data, mime_type, allow_threads)? else {
+        return Ok(None);
+    };
+    limit_ingredients(&mut value, max_ingredient_depth, ingredient_thumbnails);
+    schema::translate(&mut value, schema_version);
+    let serialize_error =
+        |e: serde_json::Error| C2paError::new_err(format!("Error serializing C2PA data: {e}"));
+
+    if let Some(output) = output {
+        let mut writer = PyWriter::new(output, chunk_size)?;
+        let written = if canonical {
+            serde_json::to_writer(&mut writer, &sort_keys(&value))
+        } else {
+            serde_json::to_writer_pretty(&mut writer, &value)
+        };
+        // A failed write() is reported by finish, with the exception it raised
+        if let Err(e) = written.map_err(serialize_error) {
+            return Err(writer.finish().err().unwrap_or(e));
         }
+        let written = writer.finish()?;
+        return Ok(Some(written.into_pyobject(py)?.into_any().unbind()));
     }
-}
\ No newline at end of file
+
+    let json = if canonical {
+        to_canonical_json(&value)
+    } else {
+        serde_json::to_string_pretty(&value)
+    };
+    let json = json.map_err(serialize_error)?;
+    Ok(Some(PyString::new(py, &json).into_any().unbind()))
+}
+
+/// Read C2PA metadata from a byte array as encoded bytes
+///
+/// The manifest store is encoded in Rust, so services relaying manifest data
+/// over the network can skip a Python re-serialization step.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     format: Output encoding, "msgpack", "json" or "protobuf" (default: "msgpack")
+///     compress: Compression applied to the encoded output, None or "zstd" (default: None)
+///     canonical: Sort object keys at every level before encoding (default: False)
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///     max_ingredient_depth: Only include the manifests within this many ingredient
+///         levels of the active manifest, 0 for the active manifest alone (default: None,
+///         all). Omitted manifests are reported by an "ingredients.depthLimited" entry in
+///         "warnings".
+///     ingredient_thumbnails: Include the thumbnail references of ingredients (default: True)
+///     schema_version: Return the result in this fixed layout version, which stays
+///         the same across c2pa-rs releases (default: None, the layout of the linked
+///         c2pa-rs). Supported: 1
+///
+/// Returns:
+///     The encoded manifest store as bytes, or None if no C2PA metadata is present
+///
+/// Raises:
+///     ValueError: If the format or compression is not supported
+///     C2paError: If there is an error reading or parsing the C2PA data
+#[pyfunction]
+#[pyo3(signature = (
+    data, mime_type, format="msgpack", compress=None, canonical=false, allow_threads=None,
+    max_ingredient_depth=None, ingredient_thumbnails=true, schema_version=None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn read_c2pa_encoded_from_bytes(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    format: &str,
+    compress: Option<&str>,
+    canonical: bool,
+    allow_threads: Option<bool>,
+    max_ingredient_depth: Option<usize>,
+    ingredient_thumbnails: bool,
+    schema_version: Option<u32>,
+) -> PyResult<Option<Py<PyBytes>>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    schema::check_version(schema_version)?;
+    let Some(mut value) = read_value(py, data, mime_type, allow_threads)? else {
+        return Ok(None);
+    };
+    limit_ingredients(&mut value, max_ingredient_depth, ingredient_thumbnails);
+    schema::translate(&mut value, schema_version);
+
+    let encoded = encode(&value, format, compress, canonical).map_err(PyValueError::new_err)?;
+
+    Ok(Some(PyBytes::new(py, &encoded).unbind()))
+}
+
+/// Validate a manifest store kept apart from its asset
+///
+/// For architectures where manifests live in a database or object store rather
+/// than inside the asset: the manifest store bytes are validated against the
+/// asset exactly as if they were embedded in it.
+///
+/// Args:
+///     manifest_bytes: The manifest store (JUMBF), e.g. a .c2pa sidecar
+///     asset: The asset as a bytes-like object or a binary file-like object
+///     mime_type: MIME type of the asset (e.g., "image/jpeg")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///     deadline_ms: Processing time budget in milliseconds, as for
+///         read_c2pa_from_bytes (default: None, no budget)
+///
+/// Returns:
+///     A dictionary containing the C2PA data, in the same form as read_c2pa_from_bytes
+///
+/// Raises:
+///     C2paError: If the manifest store cannot be parsed or validated
+///     TimeBudgetExceeded: If deadline_ms runs out, as for read_c2pa_from_bytes
+#[pyfunction]
+#[pyo3(signature = (manifest_bytes, asset, mime_type, allow_threads=None, deadline_ms=None))]
+pub fn verify_manifest_bytes(
+    py: Python,
+    manifest_bytes: &[u8],
+    asset: &Bound<'_, PyAny>,
+    mime_type: &str,
+    allow_threads: Option<bool>,
+    deadline_ms: Option<u64>,
+) -> PyResult<PyObject> {
+    let asset: Owner = if asset.downcast::<PyBytes>().is_ok() {
+        Arc::new(Buffer::new(asset, "asset")?)
+    } else if asset.hasattr("read")? {
+        Arc::new(asset.call_method0("read")?.extract::<Vec<u8>>()?)
+    } else {
+        Arc::new(asset.extract::<Vec<u8>>()?)
+    };
+    let data = (*asset).as_ref();
+    let allow_threads = gil::release(allow_threads, manifest_bytes.len() + data.len());
+    let _deadline = deadline::Scope::new(deadline_ms);
+    let _inputs = deadline::Inputs::share([&asset]);
+
+    let mut value = verify_value(py, manifest_bytes, data, mime_type, allow_threads)?;
+    run_hooks(py, &mut value, "verify", allow_threads, || Some(manifest_bytes.to_vec()))?;
+    value_to_py(py, &value)
+}
+
+/// Extract the manifest store embedded in an asset
+///
+/// The bytes can be published as a .c2pa sidecar or at a remote URL, and
+/// validated later with verify_manifest_bytes. The asset itself is left as is;
+/// relocate_manifest also moves the store out of it where the hard binding
+/// allows.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///
+/// Returns:
+///     The manifest store (JUMBF) as bytes, or None if no store is embedded
+#[pyfunction]
+pub fn extract_manifest_store(py: Python, data: &[u8], mime_type: &str) -> Option<Py<PyBytes>> {
+    embedded_store(data, mime_type).map(|store| PyBytes::new(py, &store).unbind())
+}
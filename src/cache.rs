@@ -0,0 +1,423 @@
+//! Result cache shared between processes
+//!
+//! Pre-fork servers (gunicorn, uWSGI) run several worker processes, and the
+//! same upload often reaches more than one of them. The cache is a
+//! memory-mapped file all workers open, holding validated results keyed by a
+//! hash of the content, so an asset is only validated once.
+//!
+//! The file is a fixed table of slots, each guarded by a sequence lock: the
+//! writer makes the sequence number odd while it fills the slot, and readers
+//! discard what they copied if the number changed meanwhile. Writers never
+//! wait for each other; a write that finds the slot busy is dropped.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use memmap2::MmapMut;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::revocation::get_ocsp_cache_stats;
+use crate::settings;
+
+const MAGIC: &[u8; 8] = b"FC2PACHE";
+const FORMAT_VERSION: u64 = 1;
+const HEADER_LEN: usize = 64;
+// Header fields
+const SLOT_COUNT: usize = 16;
+const SLOT_LEN: usize = 24;
+const GENERATION: usize = 32;
+// Slot fields
+const SEQUENCE: usize = 0;
+const SLOT_GENERATION: usize = 8;
+const CREATED: usize = 16;
+const LENGTH: usize = 24;
+const KEY: usize = 32;
+const PAYLOAD: usize = 64;
+
+pub type Key = [u8; 32];
+
+static CACHE: LazyLock<RwLock<Option<Arc<SharedCache>>>> = LazyLock::new(|| RwLock::new(None));
+
+struct SharedCache {
+    path: PathBuf,
+    /// Start of `map`, taken once so slots can be written through a shared
+    /// reference
+    base: *mut u8,
+    map: MmapMut,
+    slots: usize,
+    slot_len: usize,
+    ttl: u64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    stores: AtomicU64,
+    evictions: AtomicU64,
+}
+
+// SAFETY: `base` points into the mapping owned by the same struct, and all
+// access through it is synchronized with atomics
+unsafe impl Send for SharedCache {}
+unsafe impl Sync for SharedCache {}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Whether the cache file at `path` exists with the given layout, from its
+/// length and header alone
+fn has_layout(path: &Path, size: usize, slots: usize, slot_len: usize) -> bool {
+    let Ok(mut file) = File::open(path) else {
+        return false;
+    };
+    let mut header = [0u8; HEADER_LEN];
+    if !file.metadata().is_ok_and(|m| m.len() == size as u64)
+        || file.read_exact(&mut header).is_err()
+    {
+        return false;
+    }
+    let field = |at: usize| u64::from_le_bytes(header[at..at + 8].try_into().unwrap());
+    header.starts_with(MAGIC)
+        && field(8) == FORMAT_VERSION
+        && field(SLOT_COUNT) == slots as u64
+        && field(SLOT_LEN) == slot_len as u64
+}
+
+/// An exclusive lock on the lock file next to the cache file at `path`, held
+/// until the returned file is dropped
+///
+/// The lock file is never removed: the operating system releases the lock of
+/// a process that exits, so a crash cannot leave it locked.
+#[cfg(unix)]
+fn lock_for_init(path: &Path) -> std::io::Result<File> {
+    use std::os::fd::AsRawFd;
+
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path)?;
+    // SAFETY: flock only reads the descriptor, which `file` keeps open
+    while unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+        let error = std::io::Error::last_os_error();
+        if error.kind() != std::io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn lock_for_init(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+impl SharedCache {
+    /// Open the cache file at `path`, creating it when it is missing or was
+    /// created with a different layout
+    fn open(path: &Path, slots: usize, slot_len: usize, ttl: u64) -> std::io::Result<Self> {
+        let size = HEADER_LEN + slots * slot_len;
+        // Processes opening the cache at once would otherwise each create a
+        // file and map a different one
+        let _lock = lock_for_init(path)?;
+        if !has_layout(path, size, slots, slot_len) {
+            // Build the new file aside and move it into place, so other
+            // processes never open a half-initialized one
+            let mut header = vec![0u8; HEADER_LEN];
+            header[..8].copy_from_slice(MAGIC);
+            header[8..16].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+            header[SLOT_COUNT..SLOT_COUNT + 8].copy_from_slice(&(slots as u64).to_le_bytes());
+            header[SLOT_LEN..SLOT_LEN + 8].copy_from_slice(&(slot_len as u64).to_le_bytes());
+            let staging = path.with_extension(format!("tmp{}", std::process::id()));
+            let mut file = File::create(&staging)?;
+            file.write_all(&header)?;
+            file.set_len(size as u64)?;
+            fs::rename(&staging, path)?;
+        }
+
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        // SAFETY: the file is only modified through atomics and sequence locked
+        // slots, by this module in every process sharing it
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+        Ok(SharedCache {
+            path: path.to_owned(),
+            base: map.as_mut_ptr(),
+            map,
+            slots,
+            slot_len,
+            ttl,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            stores: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        })
+    }
+
+    fn atomic(&self, offset: usize) -> &AtomicU64 {
+        debug_assert!(offset % 8 == 0 && offset + 8 <= self.map.len());
+        // SAFETY: in bounds and 8-byte aligned (mappings are page aligned and
+        // every offset used is a multiple of 8)
+        unsafe { &*(self.base.add(offset) as *const AtomicU64) }
+    }
+
+    fn slot(&self, key: &Key) -> usize {
+        let index = u64::from_le_bytes(key[..8].try_into().unwrap()) as usize % self.slots;
+        HEADER_LEN + index * self.slot_len
+    }
+
+    fn get(&self, key: &Key) -> Option<Value> {
+        let slot = self.slot(key);
+        let sequence = self.atomic(slot + SEQUENCE);
+        let before = sequence.load(Ordering::Acquire);
+        if before % 2 == 1 {
+            return None;
+        }
+        let generation = self.atomic(slot + SLOT_GENERATION).load(Ordering::Relaxed);
+        let created = self.atomic(slot + CREATED).load(Ordering::Relaxed);
+        let len = self.atomic(slot + LENGTH).load(Ordering::Relaxed) as usize;
+        if len == 0 || len > self.slot_len - PAYLOAD {
+            return None;
+        }
+        let mut stored = [0u8; 32];
+        let mut payload = vec![0u8; len];
+        // SAFETY: in bounds; a concurrent write makes the copy torn, which the
+        // sequence check below detects
+        unsafe {
+            let base = self.base.add(slot);
+            std::ptr::copy_nonoverlapping(base.add(KEY), stored.as_mut_ptr(), 32);
+            std::ptr::copy_nonoverlapping(base.add(PAYLOAD), payload.as_mut_ptr(), len);
+        }
+        fence(Ordering::Acquire);
+        if sequence.load(Ordering::Relaxed) != before
+            || &stored != key
+            || generation != self.atomic(GENERATION).load(Ordering::Acquire)
+            || now().saturating_sub(created) >= self.ttl
+        {
+            return None;
+        }
+        serde_json::from_slice(&payload).ok()
+    }
+
+    fn put(&self, key: &Key, value: &Value) {
+        let Ok(payload) = serde_json::to_vec(value) else {
+            return;
+        };
+        if payload.len() > self.slot_len - PAYLOAD {
+            return;
+        }
+        let slot = self.slot(key);
+        let sequence = self.atomic(slot + SEQUENCE);
+        let before = sequence.load(Ordering::Relaxed);
+        if before % 2 == 1
+            || sequence
+                .compare_exchange(before, before + 1, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+        {
+            return;
+        }
+        fence(Ordering::Release);
+        let generation = self.atomic(GENERATION).load(Ordering::Acquire);
+        if self.live_entry_of_other_key(slot, key, generation) {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+        self.atomic(slot + SLOT_GENERATION)
+            .store(generation, Ordering::Relaxed);
+        self.atomic(slot + CREATED).store(now(), Ordering::Relaxed);
+        self.atomic(slot + LENGTH)
+            .store(payload.len() as u64, Ordering::Relaxed);
+        // SAFETY: in bounds, and this process holds the slot's sequence lock
+        unsafe {
+            let base = self.base.add(slot);
+            std::ptr::copy_nonoverlapping(key.as_ptr(), base.add(KEY), 32);
+            std::ptr::copy_nonoverlapping(payload.as_ptr(), base.add(PAYLOAD), payload.len());
+        }
+        sequence.store(before + 2, Ordering::Release);
+        self.stores.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Whether the slot at `slot`, sequence locked by the caller, holds an
+    /// unexpired result for a key other than `key`
+    fn live_entry_of_other_key(&self, slot: usize, key: &Key, generation: u64) -> bool {
+        let len = self.atomic(slot + LENGTH).load(Ordering::Relaxed);
+        let created = self.atomic(slot + CREATED).load(Ordering::Relaxed);
+        if len == 0
+            || self.atomic(slot + SLOT_GENERATION).load(Ordering::Relaxed) != generation
+            || now().saturating_sub(created) >= self.ttl
+        {
+            return false;
+        }
+        let mut stored = [0u8; 32];
+        // SAFETY: in bounds, and the caller holds the slot's sequence lock
+        unsafe {
+            std::ptr::copy_nonoverlapping(self.base.add(slot + KEY), stored.as_mut_ptr(), 32);
+        }
+        &stored != key
+    }
+}
+
+fn current() -> Option<Arc<SharedCache>> {
+    CACHE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Cache key of an asset, given as its bytes in one or more chunks, or `None`
+/// when no cache is enabled
+pub fn key<'a>(chunks: impl IntoIterator<Item = &'a [u8]>, mime_type: &str) -> Option<Key> {
+    current()?;
+    let settings = settings::digest();
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    hasher.update(settings);
+    hasher.update(mime_type);
+    hasher.update([0]);
+    for chunk in chunks {
+        hasher.update(chunk);
+    }
+    Some(hasher.finalize().into())
+}
+
+/// Look up the result stored for `key`
+pub fn get(key: &Key) -> Option<Value> {
+    let cache = current()?;
+    let value = cache.get(key);
+    let counter = if value.is_some() {
+        &cache.hits
+    } else {
+        &cache.misses
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+    value
+}
+
+/// Store the result for `key`, unless it does not fit in a slot
+pub fn put(key: &Key, value: &Value) {
+    if let Some(cache) = current() {
+        cache.put(key, value);
+    }
+}
+
+/// Share validated results between processes through a memory-mapped file
+///
+/// Results of read_c2pa_from_bytes, read_c2pa_json_from_bytes,
+/// read_c2pa_encoded_from_bytes and read_c2pa_from_file are stored under a
+/// hash of the asset, its MIME type and the loaded C2PA settings, so identical
+/// uploads reaching different worker processes are only validated once.
+///
+/// Call this in the server's master process before it forks, or in every
+/// worker with the same arguments. The file is created, or recreated if it was
+/// made with different sizes, under a lock on the file path + ".lock" so
+/// workers starting at once agree on one file.
+///
+/// Args:
+///     path: Path of the cache file, e.g. on /dev/shm
+///     size_mb: Size of the cache file in MiB (default: 64)
+///     max_entry_kb: Largest result stored, in KiB of JSON; larger results are
+///         not cached (default: 256)
+///     ttl_seconds: How long a stored result is used before the asset is
+///         validated again, e.g. to notice expired certificates (default: 3600)
+///
+/// Raises:
+///     ValueError: If the sizes do not leave room for at least one entry
+///     OSError: If the cache file cannot be created or mapped
+#[pyfunction]
+#[pyo3(signature = (path, size_mb=64, max_entry_kb=256, ttl_seconds=3600))]
+pub fn enable_shared_cache(
+    path: PathBuf,
+    size_mb: usize,
+    max_entry_kb: usize,
+    ttl_seconds: u64,
+) -> PyResult<()> {
+    let slot_len = (PAYLOAD + max_entry_kb * 1024).next_multiple_of(8);
+    let slots = (size_mb * 1024 * 1024).saturating_sub(HEADER_LEN) / slot_len;
+    if max_entry_kb == 0 || slots == 0 {
+        return Err(PyValueError::new_err(
+            "size_mb must leave room for at least one entry of max_entry_kb",
+        ));
+    }
+    let cache = SharedCache::open(&path, slots, slot_len, ttl_seconds)?;
+    *CACHE.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(cache));
+    Ok(())
+}
+
+/// Stop using the shared result cache in this process
+///
+/// The cache file is left in place for other processes.
+#[pyfunction]
+pub fn disable_shared_cache() {
+    *CACHE.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Write the shared cache to its file and stop using it in this process
+pub fn close() -> std::io::Result<()> {
+    let cache = CACHE.write().unwrap_or_else(|e| e.into_inner()).take();
+    match cache {
+        Some(cache) => cache.map.flush(),
+        None => Ok(()),
+    }
+}
+
+/// Drop every result in the shared cache, for all processes using it
+#[pyfunction]
+pub fn clear_shared_cache() {
+    if let Some(cache) = current() {
+        cache.atomic(GENERATION).fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// Get the state of the shared result cache
+///
+/// Returns:
+///     None when no cache is enabled, otherwise a dict with "path", "slots",
+///     "max_entry_bytes", "ttl_seconds" and this process's "hits", "misses",
+///     "stores" and "evictions" counts, evictions being stores that replaced
+///     the unexpired result of another asset sharing the slot
+#[pyfunction]
+pub fn get_shared_cache_stats(py: Python) -> PyResult<Option<PyObject>> {
+    let Some(cache) = current() else {
+        return Ok(None);
+    };
+    let stats = PyDict::new(py);
+    stats.set_item("path", &cache.path)?;
+    stats.set_item("slots", cache.slots)?;
+    stats.set_item("max_entry_bytes", cache.slot_len - PAYLOAD)?;
+    stats.set_item("ttl_seconds", cache.ttl)?;
+    stats.set_item("hits", cache.hits.load(Ordering::Relaxed))?;
+    stats.set_item("misses", cache.misses.load(Ordering::Relaxed))?;
+    stats.set_item("stores", cache.stores.load(Ordering::Relaxed))?;
+    stats.set_item("evictions", cache.evictions.load(Ordering::Relaxed))?;
+    Ok(Some(stats.into_any().unbind()))
+}
+
+/// Get the hit, miss and eviction counts of every cache, to size them under
+/// production load
+///
+/// Returns:
+///     A dict with "results", the shared result cache as returned by
+///     get_shared_cache_stats (None when no cache is enabled), "ocsp", the OCSP
+///     response cache as returned by get_ocsp_cache_stats, and "settings", the
+///     per-thread copies of the settings loaded with load_c2pa_settings:
+///     "hits" counts reads on a thread whose copy was current, "misses" reads
+///     that applied the settings to their thread first, and "evictions" loads
+///     that made every thread's copy stale
+#[pyfunction]
+pub fn get_cache_stats(py: Python) -> PyResult<PyObject> {
+    let stats = PyDict::new(py);
+    stats.set_item("results", get_shared_cache_stats(py)?)?;
+    stats.set_item("ocsp", get_ocsp_cache_stats(py)?)?;
+    let (hits, misses, evictions) = settings::stats();
+    let settings = PyDict::new(py);
+    settings.set_item("hits", hits)?;
+    settings.set_item("misses", misses)?;
+    settings.set_item("evictions", evictions)?;
+    stats.set_item("settings", settings)?;
+    Ok(stats.into_any().unbind())
+}
@@ -0,0 +1,206 @@
+//! Reading assets held as a list of buffers
+//!
+//! Proxies that stream uploads hold the asset as the chunks they received.
+//! Joining them copies the whole asset once per request; a chained reader lets
+//! c2pa-rs parse the chunks where they are. Only the slower paths that search
+//! raw bytes, such as the tolerant manifest scanner, still join them.
+
+use std::borrow::Cow;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use c2pa::jumbf_io::load_jumbf_from_stream;
+use c2pa::Reader;
+use memchr::memmem;
+use pyo3::prelude::*;
+use serde_json::Value;
+
+use crate::asset_info::read_asset_info;
+use crate::c2pa_reader::{read_value, reader_to_value, run_hooks, set_source};
+use crate::cache;
+use crate::revocation::staple;
+use crate::settings;
+use crate::soft_binding::has_resolver;
+use crate::xmp::{container_xmp, find_provenance};
+
+/// A `Read + Seek` view of buffers laid end to end
+pub struct Chunks<'a> {
+    chunks: &'a [&'a [u8]],
+    /// Offset of each chunk in the asset
+    starts: Vec<u64>,
+    len: u64,
+    pos: u64,
+}
+
+impl<'a> Chunks<'a> {
+    pub fn new(chunks: &'a [&'a [u8]]) -> Self {
+        let mut starts = Vec::with_capacity(chunks.len());
+        let mut len = 0u64;
+        for chunk in chunks {
+            starts.push(len);
+            len += chunk.len() as u64;
+        }
+        Chunks {
+            chunks,
+            starts,
+            len,
+            pos: 0,
+        }
+    }
+}
+
+impl Read for Chunks<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        // The last chunk starting at or before pos; empty chunks share their
+        // start with the next one, so skip forward to one holding pos
+        let mut index = self.starts.partition_point(|&start| start <= self.pos) - 1;
+        while self.pos - self.starts[index] >= self.chunks[index].len() as u64 {
+            index += 1;
+        }
+        let chunk = &self.chunks[index][(self.pos - self.starts[index]) as usize..];
+        let n = chunk.len().min(buf.len());
+        buf[..n].copy_from_slice(&chunk[..n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for Chunks<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+        self.pos = pos.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek before the start of the asset",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
+
+/// The chunks as one buffer, copied only when there is more than one
+pub(crate) fn join<'a>(chunks: &[&'a [u8]]) -> Cow<'a, [u8]> {
+    match chunks {
+        [] => Cow::Borrowed(&[]),
+        [chunk] => Cow::Borrowed(chunk),
+        chunks => Cow::Owned(chunks.concat()),
+    }
+}
+
+/// The offsets of every occurrence of `needle` in the chunks, including those
+/// spanning chunk boundaries
+fn find_all(chunks: &[&[u8]], needle: &[u8]) -> Vec<u64> {
+    let finder = memmem::Finder::new(needle);
+    let mut found = Vec::new();
+    // The last bytes before the current chunk, too few to hold the needle
+    let mut carry: Vec<u8> = Vec::new();
+    let mut start = 0u64;
+    for chunk in chunks {
+        if !carry.is_empty() {
+            let head = &chunk[..chunk.len().min(needle.len() - 1)];
+            let joined = [carry.as_slice(), head].concat();
+            let spanning = finder
+                .find_iter(&joined)
+                .filter(|&pos| pos < carry.len() && pos + needle.len() > carry.len());
+            found.extend(spanning.map(|pos| start - (carry.len() - pos) as u64));
+        }
+        found.extend(finder.find_iter(chunk).map(|pos| start + pos as u64));
+        carry.extend_from_slice(&chunk[chunk.len().saturating_sub(needle.len() - 1)..]);
+        carry.drain(..carry.len().saturating_sub(needle.len() - 1));
+        start += chunk.len() as u64;
+    }
+    found
+}
+
+/// The remote manifest reference in the XMP of the chunks, read from where
+/// the format stores it
+fn remote_url(chunks: &[&[u8]], mime_type: &str) -> Option<String> {
+    container_xmp(Chunks::new(chunks), mime_type)
+        .flatten()
+        .as_deref()
+        .and_then(find_provenance)
+}
+
+enum Parsed {
+    Cached(Value),
+    Read(Box<Reader>, Vec<u8>),
+    /// c2pa-rs found no manifest store, or none it could read
+    Missing,
+}
+
+/// Read the manifest store of an asset held as `chunks`, like read_value
+///
+/// The chunks are parsed through a chained reader. When c2pa-rs finds no
+/// usable manifest store but the bytes may still hold one for the tolerant
+/// scanner, or a soft binding resolver is registered, they are joined and
+/// read as one buffer.
+pub(crate) fn read_chunks_value(
+    py: Python,
+    chunks: &[&[u8]],
+    mime_type: &str,
+    allow_threads: bool,
+) -> PyResult<Option<Value>> {
+    let key = if allow_threads {
+        py.allow_threads(|| cache::key(chunks.iter().copied(), mime_type))
+    } else {
+        cache::key(chunks.iter().copied(), mime_type)
+    };
+    let parse = || {
+        if let Some(value) = key.as_ref().and_then(cache::get) {
+            return Parsed::Cached(value);
+        }
+        settings::sync();
+        let Ok(store) = load_jumbf_from_stream(mime_type, &mut Chunks::new(chunks)) else {
+            return Parsed::Missing;
+        };
+        let stapled = staple(&store);
+        match Reader::from_manifest_data_and_stream(&stapled, mime_type, Chunks::new(chunks)) {
+            Ok(reader) => Parsed::Read(Box::new(reader), store),
+            Err(_) => Parsed::Missing,
+        }
+    };
+    let parsed = if allow_threads {
+        py.allow_threads(parse)
+    } else {
+        parse()
+    };
+
+    let (mut value, store) = match parsed {
+        Parsed::Cached(value) => (value, None),
+        Parsed::Read(reader, store) => {
+            let mut value = reader_to_value(*reader)?;
+            let source = || {
+                let asset_info = read_asset_info(Chunks::new(chunks), mime_type);
+                (remote_url(chunks, mime_type), asset_info)
+            };
+            let (url, asset_info) = if allow_threads {
+                py.allow_threads(source)
+            } else {
+                source()
+            };
+            set_source(&mut value, true, url);
+            value["asset_info"] = asset_info;
+            if let Some(key) = &key {
+                cache::put(key, &value);
+            }
+            (value, Some(store))
+        }
+        Parsed::Missing => {
+            if !has_resolver() && find_all(chunks, b"c2pa").is_empty() {
+                return Ok(None);
+            }
+            return read_value(py, &join(chunks), mime_type, allow_threads);
+        }
+    };
+
+    run_hooks(py, &mut value, "read", allow_threads, || {
+        store.or_else(|| load_jumbf_from_stream(mime_type, &mut Chunks::new(chunks)).ok())
+    })?;
+    Ok(Some(value))
+}
@@ -0,0 +1,173 @@
+//! The standard strings of C2PA manifests
+//!
+//! Assertion labels, relationships, actions and validation status codes are
+//! strings in every result, and hard-coding them in callers lets them drift
+//! from the specification version the SDK implements. They are taken here
+//! from c2pa-rs itself, by name, for the enums of fast_c2pa_python.constants.
+
+use c2pa::assertions::labels;
+use c2pa::{validation_status, Relationship, ValidationState};
+use pyo3::prelude::*;
+use serde_json::{json, Map, Value};
+
+use crate::actions::ACTIONS;
+use crate::c2pa_reader::value_to_py;
+
+/// Constants of a c2pa-rs module, paired with their names
+macro_rules! named {
+    ($module:ident: $($name:ident),* $(,)?) => {
+        &[$((stringify!($name), $module::$name)),*]
+    };
+}
+
+const ASSERTION_LABELS: &[(&str, &str)] = named!(labels:
+    CLAIM,
+    ASSERTION_METADATA,
+    DATA_HASH,
+    BOX_HASH,
+    BMFF_HASH,
+    SOFT_BINDING,
+    CLOUD_DATA,
+    THUMBNAIL,
+    CLAIM_THUMBNAIL,
+    INGREDIENT_THUMBNAIL,
+    JPEG_CLAIM_THUMBNAIL,
+    JPEG_INGREDIENT_THUMBNAIL,
+    PNG_CLAIM_THUMBNAIL,
+    PNG_INGREDIENT_THUMBNAIL,
+    ACTIONS,
+    INGREDIENT,
+    DEPTHMAP,
+    ASSET_TYPE,
+    DEPTHMAP_GDEPTH,
+    EXIF,
+    IPTC_PHOTO_METADATA,
+    SCHEMA_ORG,
+    CLAIM_REVIEW,
+    CREATIVE_WORK,
+);
+
+/// The validation codes of checks that passed
+const SUCCESS_CODES: &[(&str, &str)] = named!(validation_status:
+    CLAIM_SIGNATURE_VALIDATED,
+    CLAIM_SIGNATURE_INSIDE_VALIDITY,
+    SIGNING_CREDENTIAL_TRUSTED,
+    SIGNING_CREDENTIAL_NOT_REVOKED,
+    TIMESTAMP_TRUSTED,
+    ASSERTION_HASHEDURI_MATCH,
+    ASSERTION_DATAHASH_MATCH,
+    ASSERTION_BMFFHASH_MATCH,
+    ASSERTION_BOXHASH_MATCH,
+    ASSERTION_ACCESSIBLE,
+);
+
+const FAILURE_CODES: &[(&str, &str)] = named!(validation_status:
+    CLAIM_MISSING,
+    CLAIM_MULTIPLE,
+    HARD_BINDINGS_MISSING,
+    HARD_BINDINGS_MULTIPLE,
+    CLAIM_REQUIRED_MISSING,
+    CLAIM_CBOR_INVALID,
+    INGREDIENT_HASHEDURI_MISMATCH,
+    CLAIM_SIGNATURE_MISSING,
+    CLAIM_SIGNATURE_MISMATCH,
+    MANIFEST_INACCESSIBLE,
+    MANIFEST_MULTIPLE_PARENTS,
+    MANIFEST_UPDATE_INVALID,
+    MANIFEST_UPDATE_WRONG_PARENTS,
+    SIGNING_CREDENTIAL_UNTRUSTED,
+    SIGNING_CREDENTIAL_INVALID,
+    SIGNING_CREDENTIAL_REVOKED,
+    SIGNING_CREDENTIAL_EXPIRED,
+    TIMESTAMP_MISMATCH,
+    TIMESTAMP_UNTRUSTED,
+    TIMESTAMP_OUTSIDE_VALIDITY,
+    TIMESTAMP_MALFORMED,
+    ASSERTION_HASHEDURI_MISMATCH,
+    ASSERTION_MISSING,
+    ASSERTION_UNDECLARED,
+    ASSERTION_INACCESSIBLE,
+    ASSERTION_NOT_REDACTED,
+    ASSERTION_SELF_REDACTED,
+    ASSERTION_REQUIRED_MISSING,
+    ASSERTION_JSON_INVALID,
+    ASSERTION_CBOR_INVALID,
+    ACTION_ASSERTION_INGREDIENT_MISMATCH,
+    ACTION_ASSERTION_REDACTED,
+    ASSERTION_DATAHASH_MISMATCH,
+    ASSERTION_BMFFHASH_MISMATCH,
+    ASSERTION_BOXHASH_MISMATCH,
+    ASSERTION_BOXHASH_UNKNOWN,
+    ASSERTION_CLOUD_DATA_HARD_BINDING,
+    ASSERTION_CLOUD_DATA_ACTIONS,
+    ALGORITHM_UNSUPPORTED,
+    GENERAL_ERROR,
+);
+
+const RELATIONSHIPS: &[(&str, Relationship)] = &[
+    ("PARENT_OF", Relationship::ParentOf),
+    ("COMPONENT_OF", Relationship::ComponentOf),
+    ("INPUT_TO", Relationship::InputTo),
+];
+
+const VALIDATION_STATES: &[(&str, ValidationState)] = &[
+    ("INVALID", ValidationState::Invalid),
+    ("VALID", ValidationState::Valid),
+    ("TRUSTED", ValidationState::Trusted),
+];
+
+fn table<'a>(entries: impl IntoIterator<Item = (String, &'a str)>) -> Value {
+    Value::Object(
+        entries
+            .into_iter()
+            .map(|(name, value)| (name, value.into()))
+            .collect(),
+    )
+}
+
+/// The standard strings of C2PA manifests, by name
+///
+/// The enums of fast_c2pa_python.constants are built from this; use them
+/// rather than the dict.
+///
+/// Returns:
+///     A dict with "assertion_labels", "actions" (the actions of the
+///     specification, named without their "c2pa." prefix), "relationships",
+///     "validation_codes", "success_codes" (the names of the validation codes
+///     that report a check passing) and "validation_states", each but
+///     "success_codes" a dict mapping names such as "DATA_HASH" to strings such
+///     as "c2pa.hash.data"
+#[pyfunction]
+pub fn get_c2pa_constants(py: Python) -> PyResult<PyObject> {
+    let names = |entries: &[&[(&str, &'static str)]]| {
+        table(
+            entries
+                .iter()
+                .copied()
+                .flatten()
+                .map(|(name, value)| ((*name).to_owned(), *value)),
+        )
+    };
+    let actions = ACTIONS.iter().map(|(action, _)| {
+        let name = action.trim_start_matches("c2pa.").replace('.', "_");
+        (name.to_ascii_uppercase(), *action)
+    });
+    let success_codes: Vec<&str> = SUCCESS_CODES.iter().map(|(name, _)| *name).collect();
+    let relationships: Map<_, _> = RELATIONSHIPS
+        .iter()
+        .map(|(name, relationship)| ((*name).to_owned(), json!(relationship)))
+        .collect();
+    let validation_states: Map<_, _> = VALIDATION_STATES
+        .iter()
+        .map(|(name, state)| ((*name).to_owned(), json!(state)))
+        .collect();
+    let constants = json!({
+        "assertion_labels": names(&[ASSERTION_LABELS]),
+        "actions": table(actions),
+        "relationships": relationships,
+        "validation_codes": names(&[SUCCESS_CODES, FAILURE_CODES]),
+        "success_codes": success_codes,
+        "validation_states": validation_states,
+    });
+    value_to_py(py, &constants)
+}
@@ -0,0 +1,144 @@
+//! The byte ranges a data hash binding covers
+//!
+//! A data hash assertion hashes the whole asset except the ranges it excludes,
+//! usually the bytes of the manifest store itself. Knowing the exact ranges
+//! tells what a third party may still change, and why trailing metadata added
+//! after signing breaks the binding.
+
+use coset::cbor::Value as Cbor;
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+
+use crate::c2pa_reader::{embedded_store, value_to_py};
+use crate::gil;
+use crate::jumbf::active_manifest_assertions;
+
+fn is_data_hash(label: &str) -> bool {
+    label == "c2pa.hash.data" || label.starts_with("c2pa.hash.data__")
+}
+
+fn field<'a>(map: &'a [(Cbor, Cbor)], key: &str) -> Option<&'a Cbor> {
+    map.iter()
+        .find(|(k, _)| k.as_text() == Some(key))
+        .map(|(_, v)| v)
+}
+
+fn uint(value: Option<&Cbor>) -> Option<u64> {
+    value?.as_integer().and_then(|n| u64::try_from(n).ok())
+}
+
+/// The exclusions of a data hash assertion, as (start, length) pairs sorted by start
+fn exclusions(map: &[(Cbor, Cbor)]) -> Vec<(u64, u64)> {
+    let mut exclusions: Vec<(u64, u64)> = field(map, "exclusions")
+        .and_then(Cbor::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|exclusion| {
+            let exclusion = exclusion.as_map()?;
+            Some((
+                uint(field(exclusion, "start"))?,
+                uint(field(exclusion, "length"))?,
+            ))
+        })
+        .collect();
+    exclusions.sort_unstable();
+    exclusions
+}
+
+/// The ranges of `0..size` outside the exclusions
+fn covered(exclusions: &[(u64, u64)], size: u64) -> Vec<(u64, u64)> {
+    let mut ranges = Vec::new();
+    let mut pos = 0u64;
+    for &(start, length) in exclusions {
+        let start = start.min(size);
+        if start > pos {
+            ranges.push((pos, start - pos));
+        }
+        pos = pos.max(start.saturating_add(length).min(size));
+    }
+    if pos < size {
+        ranges.push((pos, size - pos));
+    }
+    ranges
+}
+
+fn ranges_json(ranges: &[(u64, u64)]) -> Value {
+    json!(ranges
+        .iter()
+        .map(|&(start, length)| json!({ "start": start, "length": length }))
+        .collect::<Vec<_>>())
+}
+
+/// The data hash bindings of the active manifest in `store`
+fn bindings(store: &[u8], size: u64) -> (Option<&str>, Vec<Value>) {
+    let mut manifest = None;
+    let mut bindings = Vec::new();
+    for (manifest_label, label, content) in active_manifest_assertions(store) {
+        manifest = Some(manifest_label);
+        if !is_data_hash(label) {
+            continue;
+        }
+        let Ok(Cbor::Map(map)) = coset::cbor::de::from_reader::<Cbor, _>(content) else {
+            continue;
+        };
+        let exclusions = exclusions(&map);
+        let covered = covered(&exclusions, size);
+        bindings.push(json!({
+            "label": label,
+            "name": field(&map, "name").and_then(Cbor::as_text),
+            "alg": field(&map, "alg").and_then(Cbor::as_text),
+            "exclusions": ranges_json(&exclusions),
+            "covered": ranges_json(&covered),
+            "covered_bytes": covered.iter().map(|&(_, length)| length).sum::<u64>(),
+        }));
+    }
+    (manifest, bindings)
+}
+
+/// Get the byte ranges of an asset covered by its data hash binding
+///
+/// Reads the data hash assertions (c2pa.hash.data) of the active manifest, the
+/// hard binding of formats like JPEG, PNG, GIF, TIFF, WebP, RIFF and MP3. BMFF
+/// based formats (MP4, HEIF, AVIF) bind by box instead and have no data hash
+/// assertion. The ranges are only read, not validated: use read_c2pa_from_bytes
+/// to learn whether the hash matches.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     None if no manifest store is embedded, else a dict with "active_manifest"
+///     (its label), "size" (of the data) and "bindings": one dict per data hash
+///     assertion with "label", "name", "alg" (None when the claim's algorithm
+///     applies), "exclusions" and "covered" (lists of {"start", "length"} byte
+///     ranges, sorted) and "covered_bytes"
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, allow_threads=None))]
+pub fn get_hash_coverage(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<Option<PyObject>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let coverage = || {
+        let store = embedded_store(data, mime_type)?;
+        let (manifest, bindings) = bindings(&store, data.len() as u64);
+        Some(json!({
+            "active_manifest": manifest,
+            "size": data.len(),
+            "bindings": bindings,
+        }))
+    };
+    let coverage = if allow_threads {
+        py.allow_threads(coverage)
+    } else {
+        coverage()
+    };
+    coverage
+        .map(|coverage| value_to_py(py, &coverage))
+        .transpose()
+}
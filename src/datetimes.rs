@@ -0,0 +1,67 @@
+//! Timestamps as Python datetimes
+//!
+//! Results carry timestamps as RFC 3339 strings, which callers otherwise parse
+//! themselves, each with its own handling of offsets. With parse_datetimes the
+//! known timestamp fields are turned into timezone-aware datetimes in UTC.
+
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+/// Replace `dict[key]` by a UTC datetime if it holds an RFC 3339 timestamp
+fn parse_item(dict: &Bound<'_, PyDict>, key: &str) -> PyResult<()> {
+    let Some(item) = dict.get_item(key)? else {
+        return Ok(());
+    };
+    let Ok(text) = item.extract::<&str>() else {
+        return Ok(());
+    };
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        dict.set_item(key, time.with_timezone(&Utc))?;
+    }
+    Ok(())
+}
+
+fn dict_item<'py>(dict: &Bound<'py, PyDict>, key: &str) -> Option<Bound<'py, PyDict>> {
+    dict.get_item(key).ok()??.downcast_into().ok()
+}
+
+fn list_item<'py>(dict: &Bound<'py, PyDict>, key: &str) -> Option<Bound<'py, PyList>> {
+    dict.get_item(key).ok()??.downcast_into().ok()
+}
+
+/// Parse the timestamps of a result dict in place: the signing time of each
+/// manifest ("signature_info" "time") and the "when" of its actions
+pub(crate) fn parse_datetimes(result: &Bound<'_, PyAny>) -> PyResult<()> {
+    let Some(manifests) = result
+        .downcast::<PyDict>()
+        .ok()
+        .and_then(|result| dict_item(result, "manifests"))
+    else {
+        return Ok(());
+    };
+    for (_, manifest) in manifests.iter() {
+        let Ok(manifest) = manifest.downcast::<PyDict>() else {
+            continue;
+        };
+        if let Some(signature_info) = dict_item(manifest, "signature_info") {
+            parse_item(&signature_info, "time")?;
+        }
+        for assertion in list_item(manifest, "assertions").iter().flat_map(|a| a.iter()) {
+            let Ok(assertion) = assertion.downcast::<PyDict>() else {
+                continue;
+            };
+            let is_actions = assertion
+                .get_item("label")?
+                .and_then(|label| label.extract::<String>().ok())
+                .is_some_and(|label| label.starts_with("c2pa.actions"));
+            let actions = dict_item(assertion, "data").and_then(|data| list_item(&data, "actions"));
+            for action in actions.iter().filter(|_| is_actions).flat_map(|a| a.iter()) {
+                if let Ok(action) = action.downcast::<PyDict>() {
+                    parse_item(action, "when")?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,287 @@
+//! Processing time budgets
+//!
+//! A read given deadline_ms must return or fail within it, however large or
+//! hostile the asset. c2pa-rs cannot be interrupted, so its parse runs on a
+//! thread of its own that is abandoned when the budget runs out; the
+//! library's own stages (decoders, resolvers, verifiers, downloads) check
+//! the remaining time before they start.
+//!
+//! The deadline of the read in progress is kept per thread, so the stages
+//! deep inside a read see it without every helper taking it. So are the
+//! buffers the read was given: a bounded parse shares the buffer, or memory
+//! map, holding its bytes instead of copying them, and keeps it alive when
+//! abandoned. Only a limited number of abandoned parses may run at once;
+//! past it, reads with a budget fail at once rather than pile up threads.
+
+use std::cell::{Cell, RefCell};
+use std::ops::Range;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyTimeoutError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::{json, Value};
+
+use crate::c2pa_reader::value_to_py;
+use crate::errors::C2paError;
+use crate::lifecycle::Worker;
+
+create_exception!(
+    fast_c2pa_core,
+    TimeBudgetExceeded,
+    PyTimeoutError,
+    "A read or validation ran out of its deadline_ms budget.\n\n\
+     The partial attribute holds the result as far as it got, when the\n\
+     manifest store was parsed before the budget ran out, else None."
+);
+
+/// Bytes a bounded parse can keep after its read gave up on it
+pub(crate) type Owner = Arc<dyn AsRef<[u8]> + Send + Sync>;
+
+thread_local! {
+    static DEADLINE: Cell<Option<(Instant, Duration)>> = const { Cell::new(None) };
+    static INPUTS: RefCell<Vec<Owner>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Abandoned parses allowed to keep running at once, by default
+const DEFAULT_MAX_ABANDONED: usize = 16;
+static MAX_ABANDONED: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_ABANDONED);
+/// Parses abandoned by their read and still running
+static ABANDONED: AtomicUsize = AtomicUsize::new(0);
+
+/// States of a bounded parse, as seen by the read waiting for it
+const RUNNING: u8 = 0;
+const GIVEN_UP: u8 = 1;
+const FINISHED: u8 = 2;
+
+/// The deadline of a read while it is in progress, restoring the enclosing
+/// one when dropped
+pub(crate) struct Scope {
+    previous: Option<(Instant, Duration)>,
+}
+
+impl Scope {
+    /// Start a budget of `deadline_ms` for the read on this thread, if given
+    pub(crate) fn new(deadline_ms: Option<u64>) -> Scope {
+        let previous = DEADLINE.get();
+        if let Some(ms) = deadline_ms {
+            let budget = Duration::from_millis(ms);
+            DEADLINE.set(Some((Instant::now() + budget, budget)));
+        }
+        Scope { previous }
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        DEADLINE.set(self.previous);
+    }
+}
+
+/// The buffers of the read on this thread, shared by its bounded parses
+/// until dropped
+pub(crate) struct Inputs {
+    previous: usize,
+}
+
+impl Inputs {
+    pub(crate) fn share<'a>(owners: impl IntoIterator<Item = &'a Owner>) -> Inputs {
+        INPUTS.with_borrow_mut(|inputs| {
+            let previous = inputs.len();
+            inputs.extend(owners.into_iter().cloned());
+            Inputs { previous }
+        })
+    }
+}
+
+impl Drop for Inputs {
+    fn drop(&mut self) {
+        INPUTS.with_borrow_mut(|inputs| inputs.truncate(self.previous));
+    }
+}
+
+/// Bytes handed to a bounded parse
+pub(crate) struct Shared {
+    owner: Owner,
+    range: Range<usize>,
+}
+
+impl Shared {
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &(*self.owner).as_ref()[self.range.clone()]
+    }
+}
+
+/// `data` as bytes a bounded parse can keep: a share of the buffer of the
+/// read on this thread holding it, else a copy
+pub(crate) fn share(data: &[u8]) -> Shared {
+    let (start, end) = (data.as_ptr() as usize, data.as_ptr() as usize + data.len());
+    let found = INPUTS.with_borrow(|inputs| {
+        inputs.iter().find_map(|owner| {
+            let bytes = (**owner).as_ref();
+            let base = bytes.as_ptr() as usize;
+            (base <= start && end <= base + bytes.len())
+                .then(|| (owner.clone(), start - base..end - base))
+        })
+    });
+    match found {
+        Some((owner, range)) => Shared { owner, range },
+        None => Shared {
+            owner: Arc::new(data.to_vec()),
+            range: 0..data.len(),
+        },
+    }
+}
+
+/// Whether the read on this thread has a budget
+pub(crate) fn is_set() -> bool {
+    DEADLINE.get().is_some()
+}
+
+/// The time left of the budget of the read on this thread, if it has one
+pub(crate) fn remaining() -> Option<Duration> {
+    DEADLINE
+        .get()
+        .map(|(deadline, _)| deadline.saturating_duration_since(Instant::now()))
+}
+
+/// The error for a read stopped before `stage`, with the result so far
+fn exceeded(py: Python, stage: &str, partial: Option<&Value>) -> PyResult<PyErr> {
+    let budget = DEADLINE.get().map_or(0, |(_, budget)| budget.as_millis());
+    let explanation = format!("processing time budget of {budget} ms exceeded before {stage}");
+    stopped(py, explanation, partial)
+}
+
+/// TimeBudgetExceeded with `explanation`, with the result so far
+fn stopped(py: Python, explanation: String, partial: Option<&Value>) -> PyResult<PyErr> {
+    let partial = match partial {
+        Some(value) => {
+            let mut value = value.clone();
+            let warning = json!({ "code": "deadline.exceeded", "explanation": explanation });
+            if let Some(map) = value.as_object_mut() {
+                match map.get_mut("warnings").and_then(Value::as_array_mut) {
+                    Some(warnings) => warnings.push(warning),
+                    None => {
+                        map.insert("warnings".to_owned(), json!([warning]));
+                    }
+                }
+            }
+            value_to_py(py, &value)?
+        }
+        None => py.None(),
+    };
+    let err = TimeBudgetExceeded::new_err(explanation);
+    err.value(py).setattr("partial", partial)?;
+    Ok(err)
+}
+
+/// Fail with TimeBudgetExceeded if the budget of the read on this thread has
+/// run out before `stage`, with `partial` as its result so far
+pub(crate) fn check(py: Python, stage: &str, partial: Option<&Value>) -> PyResult<()> {
+    match remaining() {
+        Some(left) if left.is_zero() => Err(exceeded(py, stage, partial)?),
+        _ => Ok(()),
+    }
+}
+
+/// Run `work` within the budget of the read on this thread
+///
+/// Without a budget `work` runs here. With one it runs on a thread of its
+/// own, and when the budget runs out first that thread is left to finish
+/// in the background and its result dropped. While the most abandoned
+/// threads allowed are still running, the read fails before starting one.
+pub(crate) fn bounded<T: Send + 'static>(
+    py: Python,
+    stage: &str,
+    allow_threads: bool,
+    work: impl FnOnce() -> T + Send + 'static,
+) -> PyResult<T> {
+    let Some(left) = remaining() else {
+        return Ok(if allow_threads {
+            py.allow_threads(work)
+        } else {
+            work()
+        });
+    };
+    let abandoned = ABANDONED.load(Ordering::Acquire);
+    if abandoned >= MAX_ABANDONED.load(Ordering::Relaxed) {
+        let explanation = format!(
+            "processing time budget not started for {stage}: {abandoned} parses abandoned \
+             by earlier reads are still running"
+        );
+        return Err(stopped(py, explanation, None)?);
+    }
+    let (sender, receiver) = mpsc::channel();
+    let state = Arc::new(AtomicU8::new(RUNNING));
+    let worker = Worker::start();
+    let worker_state = state.clone();
+    #[cfg(feature = "memory-stats")]
+    let measured = crate::memory::Measured::current();
+    std::thread::spawn(move || {
+        #[cfg(feature = "memory-stats")]
+        let _measured = measured.enter();
+        let _worker = worker;
+        let result = work();
+        if worker_state.swap(FINISHED, Ordering::AcqRel) == GIVEN_UP {
+            ABANDONED.fetch_sub(1, Ordering::AcqRel);
+        }
+        // The receiver is gone when the budget ran out
+        let _ = sender.send(result);
+    });
+    let received = if allow_threads {
+        py.allow_threads(move || receiver.recv_timeout(left))
+    } else {
+        receiver.recv_timeout(left)
+    };
+    match received {
+        Ok(result) => Ok(result),
+        Err(RecvTimeoutError::Timeout) => {
+            // Counted before the worker can see it given up and uncount it
+            ABANDONED.fetch_add(1, Ordering::AcqRel);
+            if state
+                .compare_exchange(RUNNING, GIVEN_UP, Ordering::AcqRel, Ordering::Acquire)
+                .is_err()
+            {
+                ABANDONED.fetch_sub(1, Ordering::AcqRel);
+            }
+            Err(exceeded(py, stage, None)?)
+        }
+        Err(RecvTimeoutError::Disconnected) => Err(C2paError::new_err(format!(
+            "Error {stage}: the worker thread panicked"
+        ))),
+    }
+}
+
+/// Set how many parses abandoned by deadline_ms may keep running at once
+///
+/// c2pa-rs cannot be interrupted, so a parse whose budget ran out keeps its
+/// thread and its input until it finishes. Once this many are still running,
+/// reads with a deadline_ms raise TimeBudgetExceeded at once, with "partial"
+/// None, instead of starting another parse; reads without a budget are not
+/// affected.
+///
+/// Args:
+///     limit: The number of parses, 0 to fail every read with a budget (default
+///         limit: 16)
+#[pyfunction]
+#[pyo3(signature = (limit))]
+pub fn set_max_abandoned_parses(limit: usize) {
+    MAX_ABANDONED.store(limit, Ordering::Relaxed);
+}
+
+/// Get how many parses abandoned by deadline_ms may keep running at once
+///
+/// Returns:
+///     A dict with "limit", set with set_max_abandoned_parses, and "running",
+///     the number of abandoned parses still running
+#[pyfunction]
+pub fn get_abandoned_parses(py: Python) -> PyResult<PyObject> {
+    let result = PyDict::new(py);
+    result.set_item("limit", MAX_ABANDONED.load(Ordering::Relaxed))?;
+    result.set_item("running", ABANDONED.load(Ordering::Acquire))?;
+    Ok(result.into_any().unbind())
+}
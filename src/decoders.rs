@@ -0,0 +1,90 @@
+//! Custom decoders for assertions
+//!
+//! c2pa-rs decodes the standard assertions; others come out as the raw CBOR or
+//! JSON data their producer wrote. Python callbacks registered per assertion
+//! label turn that data into something structured, so ecosystem-specific
+//! assertions can be understood without changes to this crate.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use serde_json::Value;
+
+use crate::c2pa_reader::{py_to_value, value_to_py};
+
+static DECODERS: LazyLock<Mutex<HashMap<String, PyObject>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Run the registered decoders over the assertions of every manifest
+///
+/// The decoded result of an assertion is stored in its "decoded" item.
+pub(crate) fn decode_assertions(py: Python, value: &mut Value) -> PyResult<()> {
+    let decoders: HashMap<String, PyObject> = {
+        let decoders = DECODERS.lock().unwrap_or_else(|e| e.into_inner());
+        if decoders.is_empty() {
+            return Ok(());
+        }
+        decoders
+            .iter()
+            .map(|(label, decoder)| (label.clone(), decoder.clone_ref(py)))
+            .collect()
+    };
+
+    let manifests = value.get_mut("manifests").and_then(Value::as_object_mut);
+    for manifest in manifests.into_iter().flat_map(|m| m.values_mut()) {
+        let assertions = manifest.get_mut("assertions").and_then(Value::as_array_mut);
+        for assertion in assertions.into_iter().flatten() {
+            let Some(decoder) = assertion
+                .get("label")
+                .and_then(Value::as_str)
+                .and_then(|label| decoders.get(label))
+            else {
+                continue;
+            };
+            let data = value_to_py(py, assertion.get("data").unwrap_or(&Value::Null))?;
+            let decoded = decoder.call1(py, (data,))?;
+            if !decoded.is_none(py) {
+                assertion["decoded"] = py_to_value(py, decoded.bind(py))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Register a decoder for the assertions with a given label
+///
+/// Whenever read_c2pa_from_file, read_c2pa_from_bytes, read_c2pa_json_from_bytes,
+/// read_c2pa_encoded_from_bytes, verify_manifest_bytes or Reader.from_bytes
+/// return a manifest with an assertion of that label, `decoder(data)` is called
+/// with the assertion's "data", and what it returns is added to the assertion as
+/// "decoded" unless it is None. The result must be JSON serializable.
+///
+/// Exceptions raised by the decoder propagate to the caller of the read
+/// function.
+///
+/// Args:
+///     label: The assertion label, e.g. "com.example.review"
+///     decoder: A callable, or None to remove the decoder registered for label
+///
+/// Raises:
+///     TypeError: If decoder is not callable
+#[pyfunction]
+#[pyo3(signature = (label, decoder))]
+pub fn register_assertion_decoder(label: &str, decoder: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+    let mut decoders = DECODERS.lock().unwrap_or_else(|e| e.into_inner());
+    match decoder {
+        Some(decoder) if !decoder.is_callable() => {
+            Err(PyTypeError::new_err("assertion decoder must be callable"))
+        }
+        Some(decoder) => {
+            decoders.insert(label.to_owned(), decoder.unbind());
+            Ok(())
+        }
+        None => {
+            decoders.remove(label);
+            Ok(())
+        }
+    }
+}
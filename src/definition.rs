@@ -0,0 +1,244 @@
+//! Linting manifest definitions
+//!
+//! c2pa-rs's Builder reports a malformed manifest definition with the first
+//! serde error it hits, or not at all when a misspelled key is silently
+//! ignored. Checking a template against the bundled JSON Schema lists every
+//! problem, with where it is, before anything is signed.
+//!
+//! The validator covers the JSON Schema keywords the bundled schema uses:
+//! type, enum, properties, required, additionalProperties, items, minItems,
+//! minLength, minimum, maximum and local $ref.
+
+use std::sync::OnceLock;
+
+use c2pa::Builder;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyString};
+use serde_json::{Map, Value};
+
+use crate::c2pa_reader::{py_to_value, value_to_py};
+
+/// The JSON Schema of manifest definitions
+const SCHEMA: &str = include_str!("manifest_definition.schema.json");
+
+fn schema() -> &'static Value {
+    static SCHEMA_VALUE: OnceLock<Value> = OnceLock::new();
+    SCHEMA_VALUE.get_or_init(|| serde_json::from_str(SCHEMA).unwrap_or_default())
+}
+
+/// A problem found in a manifest definition: a JSON Pointer to the value and
+/// what is wrong with it
+struct Problem {
+    path: String,
+    message: String,
+}
+
+/// The JSON Schema type name of `value`
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Whether `value` is of the JSON Schema type `expected`
+fn is_type(value: &Value, expected: &str) -> bool {
+    let actual = type_name(value);
+    actual == expected || (expected == "number" && actual == "integer")
+}
+
+/// `path` extended with the JSON Pointer token `key`
+fn child(path: &str, key: &str) -> String {
+    format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Check `value` at `path` against `schema`, adding what does not match to
+/// `problems`
+fn check(value: &Value, schema: &Value, path: &str, problems: &mut Vec<Problem>) {
+    let mut problem = |message: String| {
+        problems.push(Problem {
+            path: path.to_owned(),
+            message,
+        })
+    };
+    if let Some(target) = schema.get("$ref").and_then(Value::as_str) {
+        let resolved = target
+            .strip_prefix('#')
+            .and_then(|pointer| self::schema().pointer(pointer));
+        match resolved {
+            Some(resolved) => check(value, resolved, path, problems),
+            None => problem(format!("unresolved schema reference {target:?}")),
+        }
+        return;
+    }
+
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        if !is_type(value, expected) {
+            problem(format!("expected {expected}, not {}", type_name(value)));
+            return;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            problem(format!("{value} is not one of {}", allowed.join(", ")));
+        }
+    }
+    match value {
+        Value::Object(map) => check_object(map, schema, path, problems),
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if (items.len() as u64) < min {
+                    problem(format!(
+                        "expected at least {min} item(s), found {}",
+                        items.len()
+                    ));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    check(
+                        item,
+                        item_schema,
+                        &child(path, &index.to_string()),
+                        problems,
+                    );
+                }
+            }
+        }
+        Value::String(text) => {
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if (text.chars().count() as u64) < min {
+                    problem(format!("expected at least {min} character(s)"));
+                }
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < min {
+                    problem(format!("{number} is less than the minimum of {min}"));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > max {
+                    problem(format!("{number} is more than the maximum of {max}"));
+                }
+            }
+        }
+        Value::Null | Value::Bool(_) => {}
+    }
+}
+
+/// Check the keys of the object `map` at `path` against `schema`
+fn check_object(map: &Map<String, Value>, schema: &Value, path: &str, problems: &mut Vec<Problem>) {
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for required in schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let Some(key) = required.as_str() else {
+            continue;
+        };
+        if !map.contains_key(key) {
+            problems.push(Problem {
+                path: path.to_owned(),
+                message: format!("missing required key {key:?}"),
+            });
+        }
+    }
+    let closed = schema.get("additionalProperties") == Some(&Value::Bool(false));
+    for (key, item) in map {
+        match properties.and_then(|properties| properties.get(key)) {
+            Some(item_schema) => check(item, item_schema, &child(path, key), problems),
+            None if closed => problems.push(Problem {
+                path: child(path, key),
+                message: format!("unknown key {key:?}"),
+            }),
+            None => {}
+        }
+    }
+}
+
+/// The problems of the manifest definition `definition`, against the schema,
+/// then against the Builder of c2pa-rs
+fn lint(definition: &Value) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    check(definition, schema(), "", &mut problems);
+    if problems.is_empty() {
+        if let Err(e) = Builder::from_json(&definition.to_string()) {
+            problems.push(Problem {
+                path: String::new(),
+                message: format!("rejected by c2pa-rs: {e}"),
+            });
+        }
+    }
+    problems
+}
+
+/// Check a manifest definition against the manifest definition JSON Schema
+///
+/// Lints a template before it is signed: wrong types, missing required keys,
+/// unknown keys (usually misspelled ones, which the Builder silently ignores)
+/// and unknown ingredient relationships are all reported, not only the first.
+/// A definition the schema accepts is also parsed by c2pa-rs's Builder.
+///
+/// Args:
+///     manifest_json: The manifest definition as a JSON string, or as a dict
+///
+/// Returns:
+///     A list of dicts with "path" (a JSON Pointer to the offending value, "" for
+///     the definition itself) and "message", empty if the definition is valid
+///
+/// Raises:
+///     TypeError: If manifest_json is neither str nor dict
+#[pyfunction]
+#[pyo3(signature = (manifest_json))]
+pub fn validate_manifest_definition(
+    py: Python,
+    manifest_json: &Bound<'_, PyAny>,
+) -> PyResult<PyObject> {
+    let definition = if let Ok(text) = manifest_json.downcast::<PyString>() {
+        serde_json::from_str(text.to_str()?)
+    } else if manifest_json.is_instance_of::<PyDict>() {
+        Ok(py_to_value(py, manifest_json)?)
+    } else {
+        return Err(PyTypeError::new_err(format!(
+            "manifest_json must be str or dict, not {}",
+            manifest_json.get_type().name()?
+        )));
+    };
+    let problems = match definition {
+        Ok(definition) => lint(&definition),
+        Err(e) => vec![Problem {
+            path: String::new(),
+            message: format!("invalid JSON: {e}"),
+        }],
+    };
+
+    let list = PyList::empty(py);
+    for problem in problems {
+        let item = PyDict::new(py);
+        item.set_item("path", problem.path)?;
+        item.set_item("message", problem.message)?;
+        list.append(item)?;
+    }
+    Ok(list.into_any().unbind())
+}
+
+/// Get the JSON Schema manifest definitions are checked against
+///
+/// Returns:
+///     The schema as a dict, for editors and other JSON Schema tools
+#[pyfunction]
+pub fn get_manifest_definition_schema(py: Python) -> PyResult<PyObject> {
+    value_to_py(py, schema())
+}
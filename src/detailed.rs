@@ -0,0 +1,75 @@
+//! Detailed manifest store reports
+//!
+//! The detailed report is the low-level view c2patool prints with --detailed:
+//! each claim as stored, every assertion of the assertion store decoded in
+//! full, hashes such as those of c2pa.hash.boxes in base64, and the claim
+//! signatures. c2pa-rs only renders it through the Debug output of its reader.
+
+use std::fmt::Write;
+use std::io::Cursor;
+
+use base64::Engine;
+use c2pa::Reader;
+use pyo3::prelude::*;
+use serde_json::Value;
+
+use crate::c2pa_reader::embedded_store;
+use crate::errors::c2pa_error;
+use crate::jumbf::manifest_signatures;
+use crate::settings;
+
+/// The detailed report of the manifest store embedded in `data`, or `None`
+/// when there is none
+///
+/// Does not touch Python state, so it can run with the GIL released.
+fn parse_report(data: &[u8], mime_type: &str) -> Option<c2pa::Result<Value>> {
+    settings::sync();
+    let store = embedded_store(data, mime_type)?;
+    let result = Reader::from_manifest_data_and_stream(&store, mime_type, Cursor::new(data))
+        .and_then(|reader| {
+            let mut report = String::new();
+            write!(report, "{reader:?}")
+                .map_err(|_| c2pa::Error::BadParam("detailed report".to_owned()))?;
+            serde_json::from_str::<Value>(&report).map_err(c2pa::Error::JsonError)
+        })
+        .map(|mut report| {
+            add_signatures(&mut report, &store);
+            report
+        });
+    Some(result)
+}
+
+/// Add the raw COSE_Sign1 bytes of each claim signature of `store`, base64
+/// encoded, as "cose_sign1" of the "signature" of its manifest in `report`
+fn add_signatures(report: &mut Value, store: &[u8]) {
+    let Some(manifests) = report["manifests"].as_object_mut() else {
+        return;
+    };
+    for (label, signature) in manifest_signatures(store) {
+        if let Some(signature_report) = manifests
+            .get_mut(label)
+            .and_then(|manifest| manifest["signature"].as_object_mut())
+        {
+            let encoded = base64::engine::general_purpose::STANDARD.encode(signature);
+            signature_report.insert("cose_sign1".to_owned(), encoded.into());
+        }
+    }
+}
+
+/// The detailed report of the manifest store embedded in `data`, optionally
+/// releasing the GIL, or `None` when there is none
+pub(crate) fn detailed_report(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: bool,
+) -> PyResult<Option<Value>> {
+    let result = if allow_threads {
+        py.allow_threads(|| parse_report(data, mime_type))
+    } else {
+        parse_report(data, mime_type)
+    };
+    result
+        .transpose()
+        .map_err(|e| c2pa_error("Error reading the detailed C2PA report", &e))
+}
@@ -0,0 +1,178 @@
+//! Digests of a manifest store
+//!
+//! Most callers keep a few columns per asset (who signed it, what produced it,
+//! whether it is AI generated) and throw the rest of the result away. A digest
+//! computes just those answers in Rust and returns them as scalars, skipping
+//! the conversion of the whole manifest store to Python objects.
+
+use std::collections::HashSet;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::{json, Value};
+
+use crate::c2pa_reader::read_store;
+use crate::gil;
+use crate::schema::claim_generator;
+
+/// The fields a digest can hold
+const FIELDS: [&str; 7] = [
+    "signer",
+    "generator",
+    "ai_flag",
+    "validation_state",
+    "active_manifest",
+    "signed_at",
+    "has_c2pa",
+];
+
+const DEFAULT_FIELDS: [&str; 3] = ["signer", "generator", "ai_flag"];
+
+/// IPTC digital source types of generative AI content
+const AI_SOURCE_TYPES: [&str; 2] = [
+    "trainedAlgorithmicMedia",
+    "compositeWithTrainedAlgorithmicMedia",
+];
+
+/// Whether the actions of `manifest` declare generative AI content
+fn declares_ai(manifest: &Value) -> bool {
+    let assertions = manifest.get("assertions").and_then(Value::as_array);
+    assertions
+        .into_iter()
+        .flatten()
+        .filter(|assertion| {
+            assertion
+                .get("label")
+                .and_then(Value::as_str)
+                .is_some_and(|label| label.starts_with("c2pa.actions"))
+        })
+        .filter_map(|assertion| assertion.pointer("/data/actions")?.as_array())
+        .flatten()
+        .filter_map(|action| action.get("digitalSourceType")?.as_str())
+        .any(|source_type| {
+            let name = source_type.rsplit('/').next().unwrap_or(source_type);
+            AI_SOURCE_TYPES.contains(&name)
+        })
+}
+
+/// Whether the manifest `label` or the manifests of its ingredients, at any
+/// depth, declare generative AI content
+fn ai_flag(value: &Value, label: &str) -> bool {
+    let manifests = value.get("manifests");
+    let mut pending = vec![label];
+    let mut visited = HashSet::new();
+    while let Some(label) = pending.pop() {
+        if !visited.insert(label) {
+            continue;
+        }
+        let Some(manifest) = manifests.and_then(|m| m.get(label)) else {
+            continue;
+        };
+        if declares_ai(manifest) {
+            return true;
+        }
+        let ingredients = manifest.get("ingredients").and_then(Value::as_array);
+        pending.extend(
+            ingredients
+                .into_iter()
+                .flatten()
+                .filter_map(|ingredient| ingredient.get("active_manifest")?.as_str()),
+        );
+    }
+    false
+}
+
+/// The answer for `field`, from the result `value` (None without C2PA data)
+pub(crate) fn field_json(field: &str, value: Option<&Value>) -> Value {
+    let active = value.and_then(|value| {
+        let label = value.get("active_manifest")?.as_str()?;
+        value.get("manifests")?.get(label)
+    });
+    let text = |pointer: &str| active.and_then(|m| m.pointer(pointer)?.as_str());
+    match field {
+        "signer" => json!(text("/signature_info/issuer")),
+        "generator" => json!(active.and_then(|m| {
+            match m.get("claim_generator").and_then(Value::as_str) {
+                Some(generator) => Some(generator.to_owned()),
+                None => claim_generator(m.get("claim_generator_info")?),
+            }
+        })),
+        "ai_flag" => {
+            json!(value.and_then(|v| Some(ai_flag(v, v.get("active_manifest")?.as_str()?))))
+        }
+        "validation_state" => json!(value.and_then(|v| v.get("validation_state")?.as_str())),
+        "active_manifest" => json!(value.and_then(|v| v.get("active_manifest")?.as_str())),
+        "signed_at" => json!(text("/signature_info/time")),
+        "has_c2pa" => json!(value.is_some()),
+        _ => Value::Null,
+    }
+}
+
+/// The answer for `field` as a str, bool or None
+fn field_value(py: Python, field: &str, value: Option<&Value>) -> PyResult<PyObject> {
+    let object = match field_json(field, value) {
+        Value::String(text) => text.into_pyobject(py)?.into_any(),
+        Value::Bool(flag) => flag.into_pyobject(py)?.to_owned().into_any(),
+        _ => py.None().into_bound(py),
+    };
+    Ok(object.unbind())
+}
+
+/// The requested digest fields, or the default ones
+pub(crate) fn digest_fields(fields: Option<Vec<String>>) -> PyResult<Vec<String>> {
+    let fields = fields.unwrap_or_else(|| DEFAULT_FIELDS.iter().map(|&f| f.to_owned()).collect());
+    if let Some(field) = fields.iter().find(|f| !FIELDS.contains(&f.as_str())) {
+        return Err(PyValueError::new_err(format!(
+            "Unknown digest field {field}, expected one of {FIELDS:?}"
+        )));
+    }
+    Ok(fields)
+}
+
+/// Read a few scalar answers about the C2PA data of an asset
+///
+/// Faster than read_c2pa_from_bytes when only these answers are kept, since
+/// the manifest store is never converted to Python objects. Answers are about
+/// the active manifest. The shared cache is used when enabled; assertion
+/// decoders, resource resolvers and the other result hooks are not run.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     fields: The answers to compute, from "signer" (the issuer of the signing
+///         certificate), "generator" (the claim generator), "ai_flag" (whether an
+///         action of the active manifest or of an ingredient's manifest, at any depth,
+///         declares a generative AI digital source type), "validation_state",
+///         "active_manifest" (its label), "signed_at" (the signing time, as an
+///         RFC 3339 string) and "has_c2pa" (default: None, for "signer",
+///         "generator" and "ai_flag")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A dict of field to str, bool or None. Without C2PA data every field is
+///     None except "has_c2pa", which is False.
+///
+/// Raises:
+///     ValueError: If a field is not one of the above
+///     C2paError: If there is an error reading or parsing the C2PA data
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, fields=None, allow_threads=None))]
+pub fn read_c2pa_digest(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    fields: Option<Vec<String>>,
+    allow_threads: Option<bool>,
+) -> PyResult<PyObject> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let fields = digest_fields(fields)?;
+    let value = read_store(py, data, mime_type, allow_threads)?;
+
+    let digest = PyDict::new(py);
+    for field in &fields {
+        digest.set_item(field, field_value(py, field, value.as_ref())?)?;
+    }
+    Ok(digest.into_any().unbind())
+}
@@ -0,0 +1,460 @@
+//! Reading manifests from the attachments of email messages
+//!
+//! Abuse desks process reported messages as raw MIME, such as .eml files. The
+//! message is walked in Rust, through nested multiparts and attached messages,
+//! and each media attachment is decoded and read in turn.
+
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+use c2pa::format_from_path;
+use c2pa::jumbf_io::get_supported_types;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::Value;
+
+use crate::buffer::Buffer;
+use crate::c2pa_reader::{read_contained, set_contained_result};
+use crate::gil;
+use crate::mime::sniff;
+
+/// Deepest nesting of multiparts and attached messages walked, to bound the
+/// work on hostile messages
+const MAX_DEPTH: usize = 32;
+
+/// Base64 as written by mail clients, with or without padding
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// A media attachment found in a message
+struct Attachment {
+    part: String,
+    filename: Option<String>,
+    content_type: String,
+    mime_type: String,
+    size: usize,
+    result: Result<Option<Value>, String>,
+}
+
+/// The header block and the body of a message or body part
+fn split_headers(data: &[u8]) -> (&[u8], &[u8]) {
+    let mut start = 0;
+    while start < data.len() {
+        let end = data[start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(data.len(), |at| start + at + 1);
+        let line = &data[start..end];
+        if line == b"\n" || line == b"\r\n" {
+            return (&data[..start], &data[end..]);
+        }
+        start = end;
+    }
+    (data, &[])
+}
+
+/// Header fields with lowercased names and folded lines joined
+fn parse_headers(block: &[u8]) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in String::from_utf8_lossy(block).lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_owned()));
+        }
+    }
+    headers
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(field, _)| field == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Split a structured header field into its lowercased value and its
+/// parameters, with lowercased names and unquoted values
+fn parse_structured(field: &str) -> (String, Vec<(String, String)>) {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut escaped = false;
+    for c in field.chars() {
+        match c {
+            _ if escaped => {
+                current.push(c);
+                escaped = false;
+            }
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    items.push(current);
+
+    let value = items[0].trim().to_ascii_lowercase();
+    let params = items[1..]
+        .iter()
+        .filter_map(|item| item.split_once('='))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_owned()))
+        .collect();
+    (value, params)
+}
+
+fn percent_decode(value: &str) -> Vec<u8> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Text in `charset`, for the charsets filenames come in; others are read as
+/// UTF-8
+fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    match charset.to_ascii_lowercase().as_str() {
+        "iso-8859-1" | "latin1" | "us-ascii" => bytes.iter().map(|&b| char::from(b)).collect(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// The parameter `name`, from its RFC 2231 form (`name*`, or continued in
+/// `name*0`, `name*1*`, ...) if present, else from RFC 2047 encoded words
+fn param(params: &[(String, String)], name: &str) -> Option<String> {
+    let mut sections: Vec<(usize, bool, &str)> = params
+        .iter()
+        .filter_map(|(key, value)| {
+            let rest = key.strip_prefix(name)?.strip_prefix('*')?;
+            let (index, encoded) = match rest.strip_suffix('*') {
+                Some(index) => (index, true),
+                None if rest.is_empty() => ("", true),
+                None => (rest, false),
+            };
+            let index = if index.is_empty() {
+                0
+            } else {
+                index.parse().ok()?
+            };
+            Some((index, encoded, value.as_str()))
+        })
+        .collect();
+    if sections.is_empty() {
+        return params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| decode_encoded_words(value));
+    }
+    sections.sort_by_key(|(index, _, _)| *index);
+
+    let mut charset = "utf-8";
+    let mut bytes = Vec::new();
+    for (position, (_, encoded, value)) in sections.into_iter().enumerate() {
+        if !encoded {
+            bytes.extend_from_slice(value.as_bytes());
+            continue;
+        }
+        let mut value = value;
+        if position == 0 {
+            if let [declared, _language, rest] = value.splitn(3, '\'').collect::<Vec<_>>()[..] {
+                if !declared.is_empty() {
+                    charset = declared;
+                }
+                value = rest;
+            }
+        }
+        bytes.extend(percent_decode(value));
+    }
+    Some(decode_charset(&bytes, charset))
+}
+
+/// Decode the RFC 2047 encoded words (`=?charset?B?...?=`) of a header value
+fn decode_encoded_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..].splitn(4, '?').collect::<Vec<_>>();
+        let word = match decoded[..] {
+            [charset, encoding, text, tail] if tail.starts_with('=') => {
+                let bytes = match encoding.to_ascii_uppercase().as_str() {
+                    "B" => BASE64.decode(text).ok(),
+                    "Q" => Some(decode_quoted_printable(text.replace('_', " ").as_bytes())),
+                    _ => None,
+                };
+                bytes.map(|bytes| {
+                    let len = 2 + charset.len() + encoding.len() + text.len() + 4;
+                    (
+                        decode_charset(&bytes, charset.split('*').next().unwrap_or(charset)),
+                        len,
+                    )
+                })
+            }
+            _ => None,
+        };
+        let Some((word, len)) = word else {
+            out.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            after_word = false;
+            continue;
+        };
+        // Whitespace between adjacent encoded words is not part of the text
+        let between = &rest[..start];
+        if !(after_word && between.trim().is_empty()) {
+            out.push_str(between);
+        }
+        out.push_str(&word);
+        rest = &rest[start + len..];
+        after_word = true;
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_quoted_printable(body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len());
+    let mut i = 0;
+    while i < body.len() {
+        if body[i] != b'=' {
+            out.push(body[i]);
+            i += 1;
+            continue;
+        }
+        match &body[i + 1..] {
+            [b'\r', b'\n', ..] => i += 3,
+            [b'\n', ..] => i += 2,
+            [high, low, ..] => match std::str::from_utf8(&[*high, *low])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    out.push(byte);
+                    i += 3;
+                }
+                None => {
+                    out.push(b'=');
+                    i += 1;
+                }
+            },
+            _ => {
+                out.push(b'=');
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// The body of a part decoded from its Content-Transfer-Encoding
+fn decode_body(encoding: Option<&str>, body: &[u8]) -> Vec<u8> {
+    match encoding.map(|e| e.trim().to_ascii_lowercase()).as_deref() {
+        Some("base64") => {
+            let mut text: Vec<u8> = body
+                .iter()
+                .copied()
+                .filter(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/'))
+                .collect();
+            // A stray character that cannot end a base64 group
+            if text.len() % 4 == 1 {
+                text.pop();
+            }
+            BASE64.decode(&text).unwrap_or_default()
+        }
+        Some("quoted-printable") => decode_quoted_printable(body),
+        _ => body.to_vec(),
+    }
+}
+
+/// The body parts of a multipart body delimited by `boundary`
+fn multipart_parts<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{boundary}");
+    let mut parts = Vec::new();
+    let mut part_start = None;
+    let mut line_start = 0;
+    while line_start < body.len() {
+        let line_end = body[line_start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(body.len(), |at| line_start + at + 1);
+        let line = body[line_start..line_end].trim_ascii_end();
+        let is_close = line.strip_suffix(b"--") == Some(delimiter.as_bytes());
+        if line == delimiter.as_bytes() || is_close {
+            if let Some(start) = part_start {
+                // The line break before the delimiter belongs to it
+                let mut end = line_start.max(start);
+                if end > start && body[end - 1] == b'\n' {
+                    end -= 1;
+                    if end > start && body[end - 1] == b'\r' {
+                        end -= 1;
+                    }
+                }
+                parts.push(&body[start..end]);
+            }
+            if is_close {
+                return parts;
+            }
+            part_start = Some(line_end);
+        }
+        line_start = line_end;
+    }
+    // A message cut off before its close delimiter keeps its last part
+    if let Some(start) = part_start {
+        parts.push(&body[start..]);
+    }
+    parts
+}
+
+/// The MIME type to read an attachment as: the format of its bytes, else its
+/// declared type, else the type of its file extension, if c2pa-rs reads it
+fn attachment_type(
+    data: &[u8],
+    content_type: &str,
+    filename: Option<&str>,
+    supported: &[String],
+) -> Option<String> {
+    let is_supported = |mime_type: &String| supported.contains(mime_type);
+    sniff(data)
+        .map(str::to_owned)
+        .filter(is_supported)
+        .or_else(|| Some(content_type.to_owned()).filter(is_supported))
+        .or_else(|| filename.and_then(format_from_path).filter(is_supported))
+}
+
+fn walk(
+    data: &[u8],
+    path: &str,
+    depth: usize,
+    supported: &[String],
+    attachments: &mut Vec<Attachment>,
+) {
+    let (block, body) = split_headers(data);
+    let headers = parse_headers(block);
+    let (content_type, type_params) =
+        parse_structured(header(&headers, "content-type").unwrap_or("text/plain"));
+    let child = |index: usize| match path {
+        "" => index.to_string(),
+        path => format!("{path}.{index}"),
+    };
+
+    if content_type.starts_with("multipart/") && depth < MAX_DEPTH {
+        if let Some(boundary) = param(&type_params, "boundary") {
+            for (index, part) in multipart_parts(body, &boundary).into_iter().enumerate() {
+                walk(part, &child(index + 1), depth + 1, supported, attachments);
+            }
+            return;
+        }
+    }
+    let encoding = header(&headers, "content-transfer-encoding");
+    if content_type == "message/rfc822" && depth < MAX_DEPTH {
+        walk(
+            &decode_body(encoding, body),
+            path,
+            depth + 1,
+            supported,
+            attachments,
+        );
+        return;
+    }
+
+    let (_, disposition_params) =
+        parse_structured(header(&headers, "content-disposition").unwrap_or(""));
+    let filename = param(&disposition_params, "filename").or_else(|| param(&type_params, "name"));
+    let decoded = decode_body(encoding, body);
+    let Some(mime_type) = attachment_type(&decoded, &content_type, filename.as_deref(), supported)
+    else {
+        return;
+    };
+    attachments.push(Attachment {
+        part: if path.is_empty() {
+            "1".to_owned()
+        } else {
+            path.to_owned()
+        },
+        filename,
+        content_type,
+        size: decoded.len(),
+        result: read_contained(&decoded, &mime_type),
+        mime_type,
+    });
+}
+
+/// Read C2PA metadata from every media attachment of an email message
+///
+/// The raw MIME message is walked through nested multiparts and attached
+/// (forwarded) messages; base64 and quoted-printable parts are decoded. Parts
+/// are read when their bytes, their declared content type or the extension of
+/// their filename is a format c2pa-rs reads, so text bodies are skipped and
+/// mislabeled attachments are still read. An attachment that fails to parse
+/// does not stop the walk, its error is reported in its entry instead.
+///
+/// Args:
+///     message: The raw message, such as the contents of an .eml file
+///         (bytes-like object)
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A list with one dict per attachment read, in message order, with "part"
+///     (the IMAP section number, e.g. "2.1"), "filename" (or None),
+///     "content_type" (as declared), "mime_type" (as read), "size" (decoded),
+///     "result" (the C2PA data as returned by read_c2pa_from_bytes, or None) and
+///     "error" (a message, or None)
+///
+/// Raises:
+///     TypeError: If message is not bytes-like, or not C-contiguous
+#[pyfunction]
+#[pyo3(signature = (message, allow_threads=None))]
+pub fn read_c2pa_from_email(
+    py: Python,
+    message: &Bound<'_, PyAny>,
+    allow_threads: Option<bool>,
+) -> PyResult<Vec<PyObject>> {
+    let buffer = Buffer::new(message, "message")?;
+    let data = buffer.as_bytes();
+    let scan = || {
+        let supported = get_supported_types();
+        let mut attachments = Vec::new();
+        walk(data, "", 0, &supported, &mut attachments);
+        attachments
+    };
+    let attachments = if gil::release(allow_threads, data.len()) {
+        py.allow_threads(scan)
+    } else {
+        scan()
+    };
+
+    attachments
+        .into_iter()
+        .map(|attachment| {
+            let entry = PyDict::new(py);
+            entry.set_item("part", attachment.part)?;
+            entry.set_item("filename", attachment.filename)?;
+            entry.set_item("content_type", attachment.content_type)?;
+            entry.set_item("mime_type", attachment.mime_type)?;
+            entry.set_item("size", attachment.size)?;
+            set_contained_result(py, &entry, attachment.result)?;
+            Ok(entry.into_any().unbind())
+        })
+        .collect()
+}
@@ -0,0 +1,517 @@
+//! Carrying manifest stores across transforms done elsewhere
+//!
+//! Pipelines that resize or re-encode assets with other tools lose the
+//! manifest store on the way. Taking the raw JUMBF out before the transform
+//! and putting it back after keeps the provenance with the asset, without a
+//! round trip through files. Signed assets can also have their store moved
+//! between the asset, a sidecar and a remote URL, where the hard binding
+//! allows it.
+
+use std::fs;
+use std::io::Cursor;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use c2pa::jumbf_io::{
+    get_supported_types, load_jumbf_from_stream, remove_jumbf_from_file, save_jumbf_to_memory,
+};
+use c2pa::{format_from_path, Error, Reader};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use serde_json::Value;
+
+use crate::asset_info::{bmff_boxes, Source};
+use crate::errors::c2pa_error;
+use crate::gil;
+use crate::jumbf::parse_boxes;
+use crate::settings;
+use crate::validation::hash_bindings;
+use crate::xmp::{remote_reference, with_remote_reference, without_provenance, Container};
+
+/// User type of the BMFF uuid box holding C2PA data
+const C2PA_UUID: [u8; 16] = [
+    0xD8, 0xFE, 0xC3, 0xD6, 0x1B, 0x0E, 0x48, 0x3C, 0x92, 0x97, 0x58, 0x28, 0x87, 0x7E, 0xC4, 0x81,
+];
+/// Purpose of the BMFF C2PA box holding the manifest store
+const MANIFEST_PURPOSE: &[u8] = b"manifest\0";
+/// Purpose of the BMFF C2PA box a manifest store was moved out of; readers
+/// skip boxes with purposes they do not know
+const RELOCATED_PURPOSE: &[u8] = b"relocated\0";
+
+/// The error raised for a format c2pa-rs has no handler for, else the
+/// exception for `e` with `context`
+fn unsupported(e: Error, mime_type: &str, context: &str) -> PyErr {
+    match e {
+        Error::UnsupportedType => {
+            PyValueError::new_err(format!("Unsupported format for JUMBF: {mime_type}"))
+        }
+        e => c2pa_error(context, &e),
+    }
+}
+
+/// Extract the raw JUMBF manifest store of an asset
+///
+/// Unlike extract_manifest_store, which also recovers stores c2pa-rs cannot
+/// locate, the store is read as c2pa-rs reads it and errors are raised.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     The manifest store (JUMBF) as bytes, or None if the asset has none
+///
+/// Raises:
+///     ValueError: If c2pa-rs does not support the format
+///     C2paError: If the asset cannot be parsed
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, allow_threads=None))]
+pub fn extract_jumbf(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<Option<Py<PyBytes>>> {
+    let extract = || load_jumbf_from_stream(mime_type, &mut Cursor::new(data));
+    let result = if gil::release(allow_threads, data.len()) {
+        py.allow_threads(extract)
+    } else {
+        extract()
+    };
+    match result {
+        Ok(jumbf) => Ok(Some(PyBytes::new(py, &jumbf).unbind())),
+        Err(Error::JumbfNotFound) => Ok(None),
+        Err(e) => Err(unsupported(e, mime_type, "Error extracting JUMBF")),
+    }
+}
+
+/// Embed a raw JUMBF manifest store in an asset
+///
+/// Any manifest store the asset has is replaced. The store is embedded as
+/// is: its data hash still describes the asset it was signed for, so after a
+/// transform the result reads with a hash mismatch until it is signed again,
+/// with the embedded manifest as an ingredient.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     jumbf: The manifest store, e.g. from extract_jumbf
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     The asset with the manifest store as bytes
+///
+/// Raises:
+///     ValueError: If jumbf is not a JUMBF superbox, or c2pa-rs cannot write the
+///         format (see can_embed)
+///     C2paError: If the asset cannot be parsed
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, jumbf, allow_threads=None))]
+pub fn embed_jumbf(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    jumbf: &[u8],
+    allow_threads: Option<bool>,
+) -> PyResult<Py<PyBytes>> {
+    if !matches!(parse_boxes(jumbf).first(), Some(b) if &b.box_type == b"jumb") {
+        return Err(PyValueError::new_err("jumbf is not a JUMBF superbox"));
+    }
+    let embed = || save_jumbf_to_memory(mime_type, data, jumbf);
+    let result = if gil::release(allow_threads, data.len()) {
+        py.allow_threads(embed)
+    } else {
+        embed()
+    };
+    let embedded = result.map_err(|e| unsupported(e, mime_type, "Error embedding JUMBF"))?;
+    Ok(PyBytes::new(py, &embedded).unbind())
+}
+
+/// Staging files of strip_c2pa made by this process so far
+static STAGED: AtomicU64 = AtomicU64::new(0);
+
+/// The file extension c2pa-rs reads `mime_type` assets by, if it has one
+fn extension(mime_type: &str) -> Option<String> {
+    let mime_type = mime_type.to_ascii_lowercase();
+    get_supported_types()
+        .into_iter()
+        .filter(|t| !t.contains('/'))
+        .find(|ext| {
+            *ext == mime_type
+                || format_from_path(Path::new(&format!("asset.{ext}"))).as_deref()
+                    == Some(mime_type.as_str())
+        })
+}
+
+/// A staging file removed when dropped
+struct Staged(PathBuf);
+
+impl Drop for Staged {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// `data` without its manifest store, staged in the temporary directory since
+/// c2pa-rs only removes stores from files
+fn strip(data: &[u8], mime_type: &str) -> c2pa::Result<Vec<u8>> {
+    let ext = extension(mime_type).ok_or(Error::UnsupportedType)?;
+    let name = format!(
+        "fast_c2pa_strip_{}_{}.{ext}",
+        std::process::id(),
+        STAGED.fetch_add(1, Ordering::Relaxed)
+    );
+    let staged = Staged(std::env::temp_dir().join(name));
+    fs::write(&staged.0, data)?;
+    match remove_jumbf_from_file(&staged.0) {
+        Ok(()) | Err(Error::JumbfNotFound) => Ok(fs::read(&staged.0)?),
+        Err(e) => Err(e),
+    }
+}
+
+/// `data` with the provenance pointer removed from each of its XMP packets
+fn without_remote_references(mut data: Vec<u8>, container: Container) -> Result<Vec<u8>, String> {
+    let Some((slots, _)) = container.slots(&data) else {
+        return Ok(data);
+    };
+    // From the end, so the ranges of the earlier slots stay valid
+    for slot in slots.iter().rev() {
+        let Some(xmp) = slot.xmp.as_deref().and_then(without_provenance) else {
+            continue;
+        };
+        data.splice(slot.range.clone(), container.encode(&xmp)?);
+    }
+    Ok(data)
+}
+
+/// Remove the manifest store of an asset
+///
+/// c2pa-rs only removes stores from files, so the asset is staged in the
+/// temporary directory while it is stripped. Assets without a manifest store
+/// are returned unchanged.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     The asset without its manifest store as bytes
+///
+/// Raises:
+///     ValueError: If c2pa-rs cannot write the format (see can_embed)
+///     C2paError: If the asset cannot be parsed
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, allow_threads=None))]
+pub fn strip_c2pa(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<Py<PyBytes>> {
+    let result = if gil::release(allow_threads, data.len()) {
+        py.allow_threads(|| strip(data, mime_type))
+    } else {
+        strip(data, mime_type)
+    };
+    let stripped = result.map_err(|e| unsupported(e, mime_type, "Error stripping C2PA data"))?;
+    Ok(PyBytes::new(py, &stripped).unbind())
+}
+
+/// Remove the C2PA provenance of an asset before redistributing it
+///
+/// The embedded manifest store is removed as by strip_c2pa and, for JPEG and
+/// PNG, the `dcterms:provenance` pointer to a remote manifest is removed from
+/// the XMP as well, so the asset no longer leads to its credentials. The rest
+/// of the XMP is kept. Assets without provenance are returned unchanged.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     remove_remote_reference: Also remove the XMP pointer to a remote
+///         manifest (default: True)
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     The asset without its manifest store as bytes
+///
+/// Raises:
+///     ValueError: If c2pa-rs cannot write the format (see can_embed), or the
+///         XMP cannot be rewritten
+///     C2paError: If the asset cannot be parsed
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, remove_remote_reference=true, allow_threads=None))]
+pub fn remove_c2pa(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    remove_remote_reference: bool,
+    allow_threads: Option<bool>,
+) -> PyResult<Py<PyBytes>> {
+    let container = Container::from_mime_type(&mime_type.to_ascii_lowercase())
+        .filter(|_| remove_remote_reference);
+    let remove = || {
+        let stripped = strip(data, mime_type)
+            .map_err(|e| unsupported(e, mime_type, "Error removing C2PA data"))?;
+        match container {
+            Some(container) => without_remote_references(stripped, container).map_err(|e| {
+                PyValueError::new_err(format!("Cannot remove the remote reference: {e}"))
+            }),
+            None => Ok(stripped),
+        }
+    };
+    let removed = if gil::release(allow_threads, data.len()) {
+        py.allow_threads(remove)
+    } else {
+        remove()
+    }?;
+    Ok(PyBytes::new(py, &removed).unbind())
+}
+
+/// Where a manifest store is kept
+#[derive(Clone, Copy, PartialEq)]
+enum Location {
+    Embedded,
+    Sidecar,
+    Remote,
+}
+
+/// The contents, after the UUID, of the top-level C2PA box of a BMFF asset
+/// with `purpose`
+fn bmff_c2pa_box(data: &[u8], purpose: &[u8]) -> Option<Range<usize>> {
+    if data.get(4..8) != Some(b"ftyp") {
+        return None;
+    }
+    let mut source = Source::new(Cursor::new(data))?;
+    let len = source.len;
+    bmff_boxes(&mut source, 0, len)
+        .into_iter()
+        .find_map(|(kind, body, end)| {
+            let contents = body as usize + 16..end as usize;
+            let is_c2pa = &kind == b"uuid"
+                && data.get(body as usize..contents.start) == Some(&C2PA_UUID[..])
+                && data
+                    .get(contents.start + 4..contents.end)?
+                    .starts_with(purpose);
+            is_c2pa.then_some(contents)
+        })
+}
+
+/// `data` with the manifest store of its C2PA box cleared in place
+///
+/// The BMFF hash excludes C2PA boxes by their UUID but covers the offsets of
+/// the boxes around them, so the box keeps its size and position.
+fn bmff_vacate(data: &[u8]) -> Option<Vec<u8>> {
+    let contents = bmff_c2pa_box(data, MANIFEST_PURPOSE)?;
+    let mut vacated = data.to_vec();
+    let box_data = &mut vacated[contents];
+    box_data.fill(0);
+    box_data
+        .get_mut(4..4 + RELOCATED_PURPOSE.len())?
+        .copy_from_slice(RELOCATED_PURPOSE);
+    Some(vacated)
+}
+
+/// `data` with `store` written back into the C2PA box bmff_vacate cleared, if
+/// it fits the box exactly
+fn bmff_restore(data: &[u8], store: &[u8]) -> Option<Vec<u8>> {
+    let contents = bmff_c2pa_box(data, RELOCATED_PURPOSE)?;
+    // Version and flags, purpose, offset of the first auxiliary box
+    let header = 4 + MANIFEST_PURPOSE.len() + 8;
+    if contents.len() != header + store.len() {
+        return None;
+    }
+    let mut restored = data.to_vec();
+    let box_data = &mut restored[contents];
+    box_data.fill(0);
+    box_data[4..4 + MANIFEST_PURPOSE.len()].copy_from_slice(MANIFEST_PURPOSE);
+    box_data[header..].copy_from_slice(store);
+    Some(restored)
+}
+
+/// Fail unless the hard binding of `store` still matches the moved `asset`
+fn check_binding(store: &[u8], asset: &[u8], mime_type: &str) -> PyResult<()> {
+    settings::sync();
+    let reader = Reader::from_manifest_data_and_stream(store, mime_type, Cursor::new(asset))
+        .map_err(|e| c2pa_error("Error verifying the moved manifest store", &e))?;
+    let value = Value::try_from(reader)
+        .map_err(|e| c2pa_error("Error verifying the moved manifest store", &e))?;
+    let bindings = hash_bindings(&value);
+    if bindings["is_intact"] == true {
+        return Ok(());
+    }
+    let failures: Vec<&str> = bindings["failures"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    if failures
+        .iter()
+        .any(|label| label.starts_with("c2pa.hash.data"))
+    {
+        return Err(PyValueError::new_err(
+            "Cannot move the manifest store: its data hash (c2pa.hash.data) covers the asset \
+             bytes the move changes, so the asset must be signed again",
+        ));
+    }
+    let failed = if failures.is_empty() {
+        "the hard binding does not match".to_owned()
+    } else {
+        format!("{} no longer matches", failures.join(", "))
+    };
+    Err(PyValueError::new_err(format!(
+        "Cannot move the manifest store without signing again: {failed}"
+    )))
+}
+
+/// The asset and manifest store of a move, and the remote reference the
+/// asset keeps
+type Moved = (Vec<u8>, Vec<u8>, Option<String>);
+
+fn relocate(
+    data: &[u8],
+    mime_type: &str,
+    location: Location,
+    url: Option<&str>,
+    manifest_store: Option<&[u8]>,
+) -> PyResult<Moved> {
+    let mime = mime_type.to_ascii_lowercase();
+    let embedded = load_jumbf_from_stream(&mime, &mut Cursor::new(data)).ok();
+    let store = match (&embedded, manifest_store) {
+        (Some(store), _) => store.clone(),
+        (None, Some(store)) => store.to_vec(),
+        (None, None) => {
+            return Err(PyValueError::new_err(
+                "The asset has no embedded manifest store: pass its sidecar or remote store \
+                 as manifest_store",
+            ))
+        }
+    };
+    let pointer = remote_reference(data, &mime, false);
+
+    let moved = match (location, &embedded) {
+        (Location::Embedded, Some(_)) | (Location::Sidecar | Location::Remote, None) => {
+            data.to_vec()
+        }
+        (Location::Embedded, None) => match bmff_restore(data, &store) {
+            Some(restored) => restored,
+            None => save_jumbf_to_memory(&mime, data, &store)
+                .map_err(|e| unsupported(e, mime_type, "Error embedding the manifest store"))?,
+        },
+        (Location::Sidecar | Location::Remote, Some(_)) => match bmff_vacate(data) {
+            Some(vacated) => vacated,
+            None => strip(data, &mime)
+                .map_err(|e| unsupported(e, mime_type, "Error removing the manifest store"))?,
+        },
+    };
+    let moved =
+        match url.filter(|url| location == Location::Remote && pointer.as_deref() != Some(url)) {
+            Some(url) => {
+                let container = Container::from_mime_type(&mime).ok_or_else(|| {
+                    PyValueError::new_err(format!(
+                        "Cannot write XMP to {mime_type} assets, only JPEG and PNG are supported"
+                    ))
+                })?;
+                with_remote_reference(&moved, container, mime_type, url).map_err(|e| {
+                    PyValueError::new_err(format!("Cannot set the remote reference: {e}"))
+                })?
+            }
+            None => moved,
+        };
+
+    if moved != data {
+        check_binding(&store, &moved, &mime)?;
+    }
+    let remote_url = match location {
+        Location::Remote => url.map(str::to_owned),
+        _ => pointer,
+    };
+    Ok((moved, store, remote_url))
+}
+
+/// Move the manifest store of a signed asset, without signing it again
+///
+/// The store can be embedded in the asset, published as a .c2pa sidecar, or
+/// served from a remote URL the asset points to with the `dcterms:provenance`
+/// property of its XMP. A move that changes the asset keeps the hard binding
+/// only where the binding excludes what changed:
+///
+/// - BMFF assets (MP4, HEIC, AVIF...) move the store between embedded and
+///   sidecar in place: the C2PA box keeps its size and position and only its
+///   contents change, which the BMFF hash excludes. The pointer to a remote
+///   store cannot be written to them.
+/// - Moves between sidecar and remote leave the asset as is when it already
+///   points to `url`; moving to a sidecar keeps any pointer the asset has.
+/// - Data hashes cover every byte but the embedded store at its signed
+///   position, so removing the store or writing the pointer breaks them.
+///
+/// The moved asset is checked against the store, and the move fails if its
+/// hard binding no longer matches.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "video/mp4")
+///     to: Where to move the store: "embedded", "sidecar" or "remote"
+///     url: URL the store is served from, required to move it to "remote"
+///     manifest_store: The sidecar or remote store, for assets without an
+///         embedded one
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A dict with "asset" (the moved asset as bytes), "manifest_store" (the
+///     store as bytes, to publish as a sidecar or at the URL) and
+///     "remote_url" (the pointer the asset has, or None)
+///
+/// Raises:
+///     ValueError: If to is unknown, an argument it needs is missing, the
+///         move breaks the hard binding (always with a data hash), or the
+///         pointer cannot be written to the format
+///     C2paError: If the asset or store cannot be parsed
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, to, url=None, manifest_store=None, allow_threads=None))]
+pub fn relocate_manifest(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    to: &str,
+    url: Option<&str>,
+    manifest_store: Option<&[u8]>,
+    allow_threads: Option<bool>,
+) -> PyResult<PyObject> {
+    let location = match to {
+        "embedded" => Location::Embedded,
+        "sidecar" => Location::Sidecar,
+        "remote" => Location::Remote,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "to must be \"embedded\", \"sidecar\" or \"remote\", not {to:?}"
+            )))
+        }
+    };
+    if location == Location::Remote && url.is_none() {
+        return Err(PyValueError::new_err(
+            "url is required to move the manifest store to a remote URL",
+        ));
+    }
+    let mv = || relocate(data, mime_type, location, url, manifest_store);
+    let (asset, store, remote_url) = if gil::release(allow_threads, data.len()) {
+        py.allow_threads(mv)
+    } else {
+        mv()
+    }?;
+
+    let result = PyDict::new(py);
+    result.set_item("asset", PyBytes::new(py, &asset))?;
+    result.set_item("manifest_store", PyBytes::new(py, &store))?;
+    result.set_item("remote_url", remote_url)?;
+    Ok(result.into_any().unbind())
+}
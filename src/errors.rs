@@ -0,0 +1,131 @@
+//! The exceptions raised for failures
+//!
+//! Every failure of c2pa-rs or of the library itself is raised as a
+//! C2paError, subclassed by what went wrong, so callers can tell an asset
+//! they cannot handle from a credential that does not check out. C2paError
+//! is a RuntimeError, as everything was before the hierarchy existed.
+//! Invalid arguments still raise ValueError or TypeError.
+
+use c2pa::Error;
+use pyo3::create_exception;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+
+create_exception!(
+    fast_c2pa_core,
+    C2paError,
+    PyRuntimeError,
+    "Base class of the errors raised reading, validating, signing or writing C2PA data."
+);
+create_exception!(
+    fast_c2pa_core,
+    UnsupportedFormatError,
+    C2paError,
+    "The format of the asset is not one c2pa-rs can handle for the operation."
+);
+create_exception!(
+    fast_c2pa_core,
+    ManifestNotFoundError,
+    C2paError,
+    "A manifest store, manifest, claim or ingredient the operation needs is missing."
+);
+create_exception!(
+    fast_c2pa_core,
+    ValidationError,
+    C2paError,
+    "The manifest store is malformed or its claims, hashes or signatures do not check out."
+);
+create_exception!(
+    fast_c2pa_core,
+    SigningError,
+    C2paError,
+    "A manifest could not be signed or embedded."
+);
+create_exception!(
+    fast_c2pa_core,
+    IoError,
+    C2paError,
+    "An asset, sidecar or remote manifest could not be read or written."
+);
+
+/// The exception for `error` of c2pa-rs, its message prefixed with `context`
+pub(crate) fn c2pa_error(context: &str, error: &Error) -> PyErr {
+    let message = format!("{context}: {error}");
+    match error {
+        Error::UnsupportedType | Error::XmpNotSupported => {
+            UnsupportedFormatError::new_err(message)
+        }
+        Error::JumbfNotFound
+        | Error::JumbfBoxNotFound
+        | Error::ProvenanceMissing
+        | Error::ClaimMissing { .. }
+        | Error::NotFound
+        | Error::IngredientNotFound
+        | Error::RemoteManifestUrl(_) => ManifestNotFoundError::new_err(message),
+        Error::IoError(_) | Error::FileNotFound(_) | Error::RemoteManifestFetch(_) => {
+            IoError::new_err(message)
+        }
+        Error::ClaimAlreadySigned
+        | Error::CoseSignatureAlgorithmNotSupported
+        | Error::CoseMissingKey
+        | Error::CoseTimeStampGeneration
+        | Error::CoseTimeStampAuthority
+        | Error::CoseSigboxTooSmall
+        | Error::InvalidSigningKey
+        | Error::UnknownAlgorithm
+        | Error::EmbeddingError
+        | Error::RawSignerError(_) => SigningError::new_err(message),
+        Error::AssertionMissing { .. }
+        | Error::AssertionDecoding(_)
+        | Error::AssertionInvalidRedaction
+        | Error::ClaimDecoding
+        | Error::ClaimMissingSignatureBox
+        | Error::ClaimMissingHardBinding
+        | Error::ClaimMultipleHardBinding
+        | Error::ClaimInvalidContent
+        | Error::ClaimSelfRedact
+        | Error::ClaimDisallowedRedaction
+        | Error::InvalidCoseSignature { .. }
+        | Error::CoseX5ChainMissing
+        | Error::CoseInvalidCert
+        | Error::CoseSignature
+        | Error::CoseVerifier
+        | Error::CoseCertExpiration
+        | Error::CoseCertRevoked
+        | Error::CoseCertUntrusted
+        | Error::CoseInvalidTimeStamp
+        | Error::CoseTimeStampValidity
+        | Error::CoseTimeStampMismatch
+        | Error::CoseNoCerts
+        | Error::HashMismatch(_)
+        | Error::ClaimVerification(_)
+        | Error::InvalidClaim(_)
+        | Error::JumbfParseError(_)
+        | Error::InvalidEcdsaSignature
+        | Error::VerifiableCredentialInvalid
+        | Error::PrereleaseError
+        | Error::RawSignatureValidationError(_)
+        | Error::CertificateProfileError(_)
+        | Error::CertificateTrustError(_)
+        | Error::InvalidCertificateError(_) => ValidationError::new_err(message),
+        _ => C2paError::new_err(message),
+    }
+}
+
+/// The exception for `error` of c2pa-rs while signing: a SigningError unless
+/// the format or I/O was at fault, caused by the exception of the signing
+/// callback if one failed
+#[cfg(feature = "signing")]
+pub(crate) fn signing_error(context: &str, error: &Error) -> PyErr {
+    let err = match error {
+        Error::UnsupportedType | Error::IoError(_) | Error::FileNotFound(_) => {
+            c2pa_error(context, error)
+        }
+        _ => SigningError::new_err(format!("{context}: {error}")),
+    };
+    if let Some(cause) = crate::signing::take_callback_error() {
+        Python::with_gil(|py| err.set_cause(py, Some(cause)));
+    }
+    err
+}
+
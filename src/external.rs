@@ -0,0 +1,187 @@
+//! Resolving references to data kept outside the asset
+//!
+//! Hashed URIs normally point into the manifest store itself, but cloud-stored
+//! ingredients and remote data boxes point elsewhere, typically over HTTPS.
+//! Fetching them is left to a Python callback, which can use the caller's own
+//! storage clients; the library checks what it returns against the hash
+//! recorded in the manifest.
+
+use std::sync::Mutex;
+
+use base64::Engine;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+static RESOLVER: Mutex<Option<PyObject>> = Mutex::new(None);
+
+const MATCH: &str = "assertion.hashedURI.match";
+const MISMATCH: &str = "assertion.hashedURI.mismatch";
+const UNSUPPORTED: &str = "algorithm.unsupported";
+
+/// The hash recorded in a hashed URI, serialized either as base64 or, for
+/// CBOR byte strings, as an array of numbers
+fn recorded_hash(hash: &Value) -> Option<Vec<u8>> {
+    match hash {
+        Value::String(encoded) => base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .ok(),
+        Value::Array(bytes) => bytes
+            .iter()
+            .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+            .collect(),
+        _ => None,
+    }
+}
+
+fn digest(alg: &str, data: &[u8]) -> Option<Vec<u8>> {
+    match alg {
+        "sha256" => Some(Sha256::digest(data).to_vec()),
+        "sha384" => Some(Sha384::digest(data).to_vec()),
+        "sha512" => Some(Sha512::digest(data).to_vec()),
+        _ => None,
+    }
+}
+
+/// A hashed URI pointing outside the manifest store, as (url, alg, hash)
+fn external_reference(
+    object: &serde_json::Map<String, Value>,
+) -> Option<(String, String, Vec<u8>)> {
+    let url = object.get("url")?.as_str()?;
+    if url.starts_with("self#jumbf=") || !url.contains("://") {
+        return None;
+    }
+    let hash = recorded_hash(object.get("hash")?)?;
+    let alg = object
+        .get("alg")
+        .and_then(Value::as_str)
+        .unwrap_or("sha256");
+    Some((url.to_owned(), alg.to_owned(), hash))
+}
+
+fn call_resolver(py: Python, resolver: &PyObject, url: &str) -> PyResult<Option<Vec<u8>>> {
+    let found = resolver.call1(py, (url,))?;
+    let found = found.bind(py);
+    if found.is_none() {
+        Ok(None)
+    } else if let Ok(data) = found.extract::<Vec<u8>>() {
+        Ok(Some(data))
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "resource resolver must return bytes or None, not {}",
+            found.get_type().name()?
+        )))
+    }
+}
+
+/// Resolve the external references below `node`, returning the status code
+/// and URL of each one resolved
+fn resolve_node(
+    py: Python,
+    resolver: &PyObject,
+    node: &mut Value,
+) -> PyResult<Vec<(&'static str, String)>> {
+    let mut resolved = Vec::new();
+    match node {
+        Value::Object(object) => {
+            if let Some((url, alg, hash)) = external_reference(object) {
+                if let Some(data) = call_resolver(py, resolver, &url)? {
+                    let code = match digest(&alg, &data) {
+                        Some(computed) if computed == hash => MATCH,
+                        Some(_) => MISMATCH,
+                        None => UNSUPPORTED,
+                    };
+                    object.insert(
+                        "resolved".to_owned(),
+                        json!({ "size": data.len(), "code": code }),
+                    );
+                    resolved.push((code, url));
+                }
+            }
+            for child in object.values_mut() {
+                resolved.extend(resolve_node(py, resolver, child)?);
+            }
+        }
+        Value::Array(items) => {
+            for child in items {
+                resolved.extend(resolve_node(py, resolver, child)?);
+            }
+        }
+        _ => {}
+    }
+    Ok(resolved)
+}
+
+/// Fetch and check the external references of every manifest
+///
+/// Each resolved reference gets a "resolved" item with the "size" of the data
+/// and the status "code". Data not matching its hash is also reported in
+/// "warnings".
+pub(crate) fn resolve_external(py: Python, value: &mut Value) -> PyResult<()> {
+    let Some(resolver) = RESOLVER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|resolver| resolver.clone_ref(py))
+    else {
+        return Ok(());
+    };
+
+    let mut warnings = Vec::new();
+    let manifests = value.get_mut("manifests").and_then(Value::as_object_mut);
+    for (label, manifest) in manifests.into_iter().flatten() {
+        for (code, url) in resolve_node(py, &resolver, manifest)? {
+            let explanation = match code {
+                MISMATCH => format!("data at {url} referenced by {label} does not match its hash"),
+                UNSUPPORTED => {
+                    format!("hash algorithm of {url} referenced by {label} is not supported")
+                }
+                _ => continue,
+            };
+            warnings.push(json!({ "code": code, "url": url, "explanation": explanation }));
+        }
+    }
+    if !warnings.is_empty() {
+        match value.get_mut("warnings").and_then(Value::as_array_mut) {
+            Some(existing) => existing.extend(warnings),
+            None => value["warnings"] = json!(warnings),
+        }
+    }
+    Ok(())
+}
+
+/// Register a callback that fetches data referenced from outside the asset
+///
+/// Manifests can reference data by a URL and its hash, e.g. cloud-stored
+/// ingredients and remote data boxes. When a result returned by
+/// read_c2pa_from_file, read_c2pa_from_bytes, read_c2pa_json_from_bytes,
+/// read_c2pa_encoded_from_bytes, verify_manifest_bytes or Reader.from_bytes has
+/// such references, `resolver(url)` is called for each and returns the data as
+/// bytes, or None to leave it unresolved.
+///
+/// The data is hashed with the recorded algorithm and compared with the
+/// recorded hash. The reference gets a "resolved" dict with the "size" of the
+/// data and a "code": "assertion.hashedURI.match",
+/// "assertion.hashedURI.mismatch" or "algorithm.unsupported"; the last two are
+/// also listed in the result's "warnings".
+///
+/// Exceptions raised by the resolver propagate to the caller of the read
+/// function.
+///
+/// Args:
+///     resolver: A callable, or None to remove the registered resolver
+///
+/// Raises:
+///     TypeError: If resolver is not callable
+#[pyfunction]
+#[pyo3(signature = (resolver))]
+pub fn set_resource_resolver(resolver: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+    if let Some(resolver) = &resolver {
+        if !resolver.is_callable() {
+            return Err(PyTypeError::new_err("resource resolver must be callable"));
+        }
+    }
+    *RESOLVER.lock().unwrap_or_else(|e| e.into_inner()) = resolver.map(Bound::unbind);
+    Ok(())
+}
@@ -0,0 +1,152 @@
+//! Fragmented BMFF (DASH, HLS)
+//!
+//! Live streams publish an init segment carrying the manifest store and media
+//! fragments as separate files, each fragment covered by a leaf of the Merkle
+//! tree in the init segment's BMFF hash. Each fragment is validated on its own
+//! against the init segment, so a pipeline can check segments as they arrive.
+
+use std::io::Cursor;
+
+use c2pa::jumbf_io::load_jumbf_from_stream;
+use c2pa::Reader;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+
+use crate::buffer::Buffer;
+use crate::c2pa_reader::{add_source, embedded_store, reader_to_value, run_hooks, value_to_py};
+use crate::errors::c2pa_error;
+use crate::gil;
+use crate::settings;
+
+const VALIDATION_KEYS: [&str; 3] = [
+    "validation_state",
+    "validation_status",
+    "validation_results",
+];
+
+/// Validate each of `fragments` against the manifest store of `init`, or
+/// `None` when the init segment carries no manifest store
+///
+/// Does not touch Python state, so it can run with the GIL released.
+fn parse_fragments(
+    init: &[u8],
+    fragments: &[&[u8]],
+    mime_type: &str,
+) -> Option<Vec<c2pa::Result<Reader>>> {
+    settings::sync();
+    load_jumbf_from_stream(mime_type, &mut Cursor::new(init)).ok()?;
+    let readers = fragments
+        .iter()
+        .map(|fragment| Reader::from_fragment(mime_type, Cursor::new(init), Cursor::new(fragment)))
+        .collect();
+    Some(readers)
+}
+
+/// The least trusted of the validation states of the fragments
+fn overall_state<'a>(states: impl Iterator<Item = Option<&'a str>>) -> Option<&'a str> {
+    let rank = |state: &str| match state {
+        "Invalid" => 0,
+        "Valid" => 1,
+        _ => 2,
+    };
+    states.flatten().min_by_key(|state| rank(state))
+}
+
+/// Read and validate fragmented MP4, as streamed with DASH or HLS, where the
+/// init segment and the media fragments are separate files
+///
+/// The manifest store is read from the init segment, and each fragment is
+/// validated against it: its hash must match the leaf of the init segment's
+/// Merkle tree for that fragment.
+///
+/// Args:
+///     init_segment: Binary data of the init segment (bytes-like object)
+///     fragments: Binary data of the media fragments (iterable of bytes-like
+///         objects), such as .m4s segments
+///     mime_type: MIME type of the segments (default: "video/mp4")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     None if the init segment has no manifest store, else the result of
+///     read_c2pa_from_bytes for the init segment, with "validation_state" the
+///     least trusted state of the fragments and, instead of the validation
+///     codes, "fragments": one dict per fragment, in order, with its "index",
+///     "validation_state", "validation_status" and "validation_results"
+///
+/// Raises:
+///     TypeError: If a segment is not bytes-like, or not C-contiguous
+///     ValueError: If no fragments are given
+///     C2paError: If the manifest store cannot be parsed, or a fragment cannot
+///         be validated at all
+#[pyfunction]
+#[pyo3(signature = (init_segment, fragments, mime_type="video/mp4", allow_threads=None))]
+pub fn read_c2pa_fragmented(
+    py: Python,
+    init_segment: &Bound<'_, PyAny>,
+    fragments: &Bound<'_, PyAny>,
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<Option<PyObject>> {
+    let init = Buffer::new(init_segment, "init_segment")?;
+    let buffers = fragments
+        .try_iter()?
+        .map(|fragment| Buffer::new(&fragment?, "fragments items"))
+        .collect::<PyResult<Vec<_>>>()?;
+    if buffers.is_empty() {
+        return Err(PyValueError::new_err("fragments must not be empty"));
+    }
+    let init = init.as_bytes();
+    let fragments: Vec<&[u8]> = buffers.iter().map(Buffer::as_bytes).collect();
+    let len = init.len()
+        + fragments
+            .iter()
+            .map(|fragment| fragment.len())
+            .sum::<usize>();
+    let allow_threads = gil::release(allow_threads, len);
+
+    let readers = if allow_threads {
+        py.allow_threads(|| parse_fragments(init, &fragments, mime_type))
+    } else {
+        parse_fragments(init, &fragments, mime_type)
+    };
+    let Some(readers) = readers else {
+        return Ok(None);
+    };
+
+    let mut value = Value::Null;
+    let mut results = Vec::with_capacity(readers.len());
+    for (index, reader) in readers.into_iter().enumerate() {
+        let reader =
+            reader.map_err(|e| c2pa_error(&format!("Error validating fragment {index}"), &e))?;
+        let mut fragment_value = reader_to_value(reader)?;
+        let mut result = json!({"index": index});
+        for key in VALIDATION_KEYS {
+            let validation = fragment_value
+                .as_object_mut()
+                .and_then(|map| map.remove(key))
+                .unwrap_or(Value::Null);
+            result[key] = validation;
+        }
+        if value.is_null() {
+            value = fragment_value;
+        }
+        results.push(result);
+    }
+
+    let state = overall_state(
+        results
+            .iter()
+            .map(|result| result["validation_state"].as_str()),
+    );
+    if let Some(map) = value.as_object_mut() {
+        map.insert("validation_state".to_owned(), json!(state));
+        map.insert("fragments".to_owned(), Value::Array(results));
+    }
+    add_source(&mut value, init, mime_type, true);
+    run_hooks(py, &mut value, "read", allow_threads, || {
+        embedded_store(init, mime_type)
+    })?;
+    value_to_py(py, &value).map(Some)
+}
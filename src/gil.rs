@@ -0,0 +1,43 @@
+//! When to release the GIL
+//!
+//! Releasing the GIL lets other Python threads run while an asset is parsed,
+//! but handing it back and forth costs more than parsing a small thumbnail.
+//! By default the GIL is only released for payloads of at least a configurable
+//! size.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use pyo3::prelude::*;
+
+/// Payloads of at least this many bytes are parsed with the GIL released
+static THRESHOLD: AtomicUsize = AtomicUsize::new(64 * 1024);
+
+/// Whether to release the GIL for a payload of `len` bytes, given the
+/// allow_threads argument of a call: None releases it above the threshold
+pub fn release(allow_threads: Option<bool>, len: usize) -> bool {
+    allow_threads.unwrap_or_else(|| len >= THRESHOLD.load(Ordering::Relaxed))
+}
+
+/// Set the payload size from which the GIL is released automatically
+///
+/// Applies to calls that leave allow_threads at its default of None. Calls
+/// passing allow_threads=True or False keep releasing the GIL or not, whatever
+/// the size of their payload.
+///
+/// Args:
+///     threshold_bytes: The size in bytes, 0 to always release the GIL (default
+///         threshold: 65536)
+#[pyfunction]
+#[pyo3(signature = (threshold_bytes))]
+pub fn set_gil_release_threshold(threshold_bytes: usize) {
+    THRESHOLD.store(threshold_bytes, Ordering::Relaxed);
+}
+
+/// Get the payload size from which the GIL is released automatically
+///
+/// Returns:
+///     The size in bytes set with set_gil_release_threshold
+#[pyfunction]
+pub fn get_gil_release_threshold() -> usize {
+    THRESHOLD.load(Ordering::Relaxed)
+}
@@ -0,0 +1,234 @@
+//! Tolerant ID3v2 scanning for MP3 files
+//!
+//! c2pa-rs looks for the manifest store in a GEOB frame of the first ID3v2 tag
+//! only. Podcast and audio tools also stack tags, e.g. an ID3v2.3 tag written
+//! for older players followed by an ID3v2.4 one, append tags at the end of the
+//! file, or store the manifest store in a PRIV frame. The functions here walk
+//! every tag and both frame types, undoing the v2.3 and v2.4 encodings.
+
+use std::borrow::Cow;
+
+use crate::jumbf::parse_boxes;
+
+/// MIME type of GEOB frames holding a manifest store
+const GEOB_MIME_TYPE: &[u8] = b"application/x-c2pa-manifest-store";
+const HEADER_LEN: usize = 10;
+const ID3V1_LEN: usize = 128;
+
+// Tag header flags
+const TAG_UNSYNCHRONISED: u8 = 0x80;
+const TAG_EXTENDED_HEADER: u8 = 0x40;
+const TAG_FOOTER: u8 = 0x10;
+
+fn syncsafe(bytes: &[u8]) -> usize {
+    bytes.iter().fold(0, |n, &b| (n << 7) | (b & 0x7f) as usize)
+}
+
+/// Reverse the unsynchronisation scheme, which inserts a zero after every 0xFF
+fn resync(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut previous = 0u8;
+    for &b in data {
+        if !(previous == 0xff && b == 0) {
+            out.push(b);
+        }
+        previous = b;
+    }
+    out
+}
+
+/// An ID3v2 tag header found at `start`
+struct Tag {
+    start: usize,
+    /// Major version, 3 or 4
+    version: u8,
+    flags: u8,
+    /// Frames and padding, excluding header and footer
+    size: usize,
+}
+
+impl Tag {
+    fn parse(data: &[u8], start: usize) -> Option<Tag> {
+        let header = data.get(start..start + HEADER_LEN)?;
+        if &header[..3] != b"ID3" || !(3..=4).contains(&header[3]) {
+            return None;
+        }
+        if header[6..].iter().any(|&b| b & 0x80 != 0) {
+            return None;
+        }
+        Some(Tag {
+            start,
+            version: header[3],
+            flags: header[5],
+            size: syncsafe(&header[6..]),
+        })
+    }
+
+    fn end(&self) -> usize {
+        let footer = if self.version == 4 && self.flags & TAG_FOOTER != 0 {
+            HEADER_LEN
+        } else {
+            0
+        };
+        self.start + HEADER_LEN + self.size + footer
+    }
+}
+
+/// The ID3v2 tags of `data`: those stacked at the start of the file and one
+/// appended at the end, located from its footer
+fn tags(data: &[u8]) -> Vec<Tag> {
+    let mut tags = Vec::new();
+    let mut pos = 0;
+    while let Some(tag) = Tag::parse(data, pos) {
+        pos = tag.end();
+        tags.push(tag);
+    }
+
+    let end = match data.len().checked_sub(ID3V1_LEN) {
+        Some(v1) if data[v1..].starts_with(b"TAG") => v1,
+        _ => data.len(),
+    };
+    if let Some(footer) = end.checked_sub(HEADER_LEN).and_then(|f| data.get(f..end)) {
+        if footer.starts_with(b"3DI") {
+            let start = end
+                .checked_sub(2 * HEADER_LEN + syncsafe(&footer[6..]))
+                .and_then(|start| Tag::parse(data, start));
+            if let Some(tag) = start.filter(|tag| tags.iter().all(|t| t.start != tag.start)) {
+                tags.push(tag);
+            }
+        }
+    }
+    tags
+}
+
+/// The frames of `tag` with one of the given IDs, as (frame ID, decoded
+/// content) pairs
+///
+/// Compressed and encrypted frames are skipped; a manifest store is never
+/// stored that way.
+fn frames(data: &[u8], tag: &Tag, ids: &[&[u8; 4]]) -> Vec<([u8; 4], Vec<u8>)> {
+    let body = &data[(tag.start + HEADER_LEN).min(data.len())
+        ..(tag.start + HEADER_LEN + tag.size).min(data.len())];
+    // v2.3 unsynchronises the whole tag, v2.4 each frame
+    let body: Cow<[u8]> = if tag.version == 3 && tag.flags & TAG_UNSYNCHRONISED != 0 {
+        Cow::Owned(resync(body))
+    } else {
+        Cow::Borrowed(body)
+    };
+
+    let mut pos = 0;
+    if tag.flags & TAG_EXTENDED_HEADER != 0 {
+        let Some(size) = body.get(..4) else {
+            return Vec::new();
+        };
+        pos = match tag.version {
+            3 => 4 + u32::from_be_bytes([size[0], size[1], size[2], size[3]]) as usize,
+            _ => syncsafe(size),
+        };
+    }
+
+    let mut frames = Vec::new();
+    while let Some(header) = body.get(pos..pos + HEADER_LEN) {
+        // Padding
+        if header[0] == 0 {
+            break;
+        }
+        let id = [header[0], header[1], header[2], header[3]];
+        let size = if tag.version == 4 && header[4..8].iter().all(|&b| b & 0x80 == 0) {
+            syncsafe(&header[4..8])
+        } else {
+            // v2.3, or a v2.4 frame written with a plain size, as some tools do
+            u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize
+        };
+        let format = header[9];
+        let start = pos + HEADER_LEN;
+        let Some(content) = body.get(start..start.saturating_add(size)) else {
+            break;
+        };
+        pos = start + size;
+        if !ids.contains(&&id) {
+            continue;
+        }
+
+        let content = match tag.version {
+            3 => {
+                // Compressed or encrypted
+                if format & 0xc0 != 0 {
+                    continue;
+                }
+                let grouping = usize::from(format & 0x20 != 0);
+                content.get(grouping..).unwrap_or_default().to_vec()
+            }
+            _ => {
+                if format & 0x0c != 0 {
+                    continue;
+                }
+                let skip = usize::from(format & 0x40 != 0) + 4 * usize::from(format & 0x01 != 0);
+                let content = content.get(skip..).unwrap_or_default();
+                if format & 0x02 != 0 || tag.flags & TAG_UNSYNCHRONISED != 0 {
+                    resync(content)
+                } else {
+                    content.to_vec()
+                }
+            }
+        };
+        frames.push((id, content));
+    }
+    frames
+}
+
+/// Split off a string terminated as its text encoding requires
+fn split_terminated(data: &[u8], encoding: u8) -> Option<(&[u8], &[u8])> {
+    match encoding {
+        // UTF-16, terminated by two zero bytes at an even offset
+        1 | 2 => {
+            let end = data.chunks(2).position(|unit| unit == [0, 0])? * 2;
+            Some((&data[..end], &data[end + 2..]))
+        }
+        _ => {
+            let end = data.iter().position(|&b| b == 0)?;
+            Some((&data[..end], &data[end + 1..]))
+        }
+    }
+}
+
+/// The manifest store in a GEOB (general encapsulated object) frame
+fn geob_store(content: &[u8]) -> Option<&[u8]> {
+    let (&encoding, rest) = content.split_first()?;
+    // The MIME type is always Latin-1
+    let (mime_type, rest) = split_terminated(rest, 0)?;
+    if mime_type != GEOB_MIME_TYPE {
+        return None;
+    }
+    let (_filename, rest) = split_terminated(rest, encoding)?;
+    let (_description, store) = split_terminated(rest, encoding)?;
+    Some(store)
+}
+
+/// The manifest store in a PRIV (private) frame, whatever its owner
+fn priv_store(content: &[u8]) -> Option<&[u8]> {
+    let (_owner, data) = split_terminated(content, 0)?;
+    parse_boxes(data)
+        .first()
+        .is_some_and(|outer| outer.is_c2pa())
+        .then_some(data)
+}
+
+/// Locate the manifest store in the ID3v2 tags of an MP3 file
+///
+/// The first GEOB or PRIV frame holding a manifest store wins, in file order.
+pub fn scan_mp3(data: &[u8]) -> Option<Vec<u8>> {
+    for tag in tags(data) {
+        for (id, content) in frames(data, &tag, &[b"GEOB", b"PRIV"]) {
+            let store = match &id {
+                b"GEOB" => geob_store(&content),
+                b"PRIV" => priv_store(&content),
+                _ => None,
+            };
+            if let Some(store) = store {
+                return Some(store.to_vec());
+            }
+        }
+    }
+    None
+}
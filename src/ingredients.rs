@@ -0,0 +1,154 @@
+//! Shaping the ingredient tree of a manifest store
+//!
+//! Each ingredient of a manifest can carry its own manifest, which has
+//! ingredients in turn. Compositions built from many generations of edits can
+//! nest deeply, so the results can be cut down to what callers need.
+
+use std::collections::{HashSet, VecDeque};
+
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+
+use crate::c2pa_reader::{py_to_value, value_to_py};
+
+/// Labels of the manifests of the ingredients of `manifest`
+fn ingredient_labels(manifest: &Value) -> impl Iterator<Item = &str> {
+    manifest
+        .get("ingredients")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|ingredient| ingredient.get("active_manifest").and_then(Value::as_str))
+}
+
+/// Labels of the manifests at most `max_depth` ingredient levels below the
+/// active manifest
+fn labels_within(value: &Value, max_depth: usize) -> HashSet<String> {
+    let mut labels = HashSet::new();
+    let mut queue = VecDeque::new();
+    if let Some(active) = value.get("active_manifest").and_then(Value::as_str) {
+        queue.push_back((active, 0));
+    }
+    while let Some((label, depth)) = queue.pop_front() {
+        if !labels.insert(label.to_owned()) || depth == max_depth {
+            continue;
+        }
+        if let Some(manifest) = value.get("manifests").and_then(|m| m.get(label)) {
+            queue.extend(ingredient_labels(manifest).map(|label| (label, depth + 1)));
+        }
+    }
+    labels
+}
+
+/// Limit the ingredient tree of a manifest store
+///
+/// With `max_depth`, only the manifests within that many ingredient levels of
+/// the active manifest are kept (0 keeps the active manifest alone), and an
+/// "ingredients.depthLimited" warning is added if any were dropped. Without
+/// `thumbnails`, ingredient thumbnail references are removed.
+pub(crate) fn limit_ingredients(value: &mut Value, max_depth: Option<usize>, thumbnails: bool) {
+    if let Some(max_depth) = max_depth {
+        let keep = labels_within(value, max_depth);
+        if let Some(manifests) = value.get_mut("manifests").and_then(Value::as_object_mut) {
+            let before = manifests.len();
+            manifests.retain(|label, _| keep.contains(label));
+            let dropped = before - manifests.len();
+            if dropped > 0 {
+                let warning = json!({
+                    "code": "ingredients.depthLimited",
+                    "explanation": format!(
+                        "{dropped} manifest(s) not within {max_depth} ingredient level(s) of the active manifest were omitted"
+                    ),
+                });
+                match value.get_mut("warnings").and_then(Value::as_array_mut) {
+                    Some(warnings) => warnings.push(warning),
+                    None => value["warnings"] = json!([warning]),
+                }
+            }
+        }
+    }
+
+    if !thumbnails {
+        let manifests = value.get_mut("manifests").and_then(Value::as_object_mut);
+        for manifest in manifests.into_iter().flat_map(|m| m.values_mut()) {
+            let ingredients = manifest
+                .get_mut("ingredients")
+                .and_then(Value::as_array_mut);
+            for ingredient in ingredients.into_iter().flatten() {
+                if let Some(ingredient) = ingredient.as_object_mut() {
+                    ingredient.remove("thumbnail");
+                }
+            }
+        }
+    }
+}
+
+/// List the ingredients of the manifest tree with their position in it
+///
+/// Manifests are visited breadth first from the active manifest, each one
+/// once, so an ingredient manifest shared by several manifests is expanded a
+/// single time.
+fn flatten(value: &Value) -> Vec<Value> {
+    let mut flat = Vec::new();
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+    if let Some(active) = value.get("active_manifest").and_then(Value::as_str) {
+        queue.push_back((active, 1));
+    }
+    while let Some((label, depth)) = queue.pop_front() {
+        if !visited.insert(label) {
+            continue;
+        }
+        let Some(manifest) = value.get("manifests").and_then(|m| m.get(label)) else {
+            continue;
+        };
+        let ingredients = manifest.get("ingredients").and_then(Value::as_array);
+        for (index, ingredient) in ingredients.into_iter().flatten().enumerate() {
+            let mut entry = json!({
+                "parent_manifest": label,
+                "depth": depth,
+                "index": index,
+            });
+            if let (Some(entry), Some(fields)) = (entry.as_object_mut(), ingredient.as_object()) {
+                for (key, field) in fields {
+                    entry.entry(key.as_str()).or_insert_with(|| field.clone());
+                }
+            }
+            flat.push(entry);
+            if let Some(child) = ingredient.get("active_manifest").and_then(Value::as_str) {
+                queue.push_back((child, depth + 1));
+            }
+        }
+    }
+    flat
+}
+
+/// List the ingredients of a result as a flat table
+///
+/// Every ingredient of every manifest reachable from the active manifest gets
+/// one entry, for loading into SQL or analytics tables. Entries link to their
+/// manifest through "parent_manifest", and to the ingredient's own manifest, if
+/// it has one, through "active_manifest".
+///
+/// Args:
+///     result: Dict returned by read_c2pa_from_bytes, or None
+///
+/// Returns:
+///     A list of dicts with the ingredient's fields plus "parent_manifest" (the
+///     label of the manifest listing it), "depth" (1 for the ingredients of the
+///     active manifest) and "index" (its position in the parent's ingredients),
+///     ordered by depth
+///
+/// Raises:
+///     TypeError: If the result contains values that are not JSON serializable
+#[pyfunction]
+pub fn flatten_ingredients(py: Python, result: &Bound<'_, PyAny>) -> PyResult<Vec<PyObject>> {
+    if result.is_none() {
+        return Ok(Vec::new());
+    }
+    let value = py_to_value(py, result)?;
+    flatten(&value)
+        .iter()
+        .map(|entry| value_to_py(py, entry))
+        .collect()
+}
@@ -0,0 +1,337 @@
+//! The items of multi-image assets
+//!
+//! A multi-page TIFF or a HEIF file with several images (bursts, image
+//! sequences, depth maps) holds one manifest store for the whole file, yet
+//! moderation often works per page or per image. The functions here list the
+//! pages of a TIFF and the items and tracks of a HEIF file, with where the
+//! manifest store sits and whether the hard binding covering them holds.
+
+use std::collections::HashSet;
+use std::io::Cursor;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+
+use crate::asset_info::{self, Source};
+use crate::c2pa_reader::{read_value, value_to_py};
+use crate::gil;
+
+/// TIFF tag holding the manifest store
+const TIFF_C2PA_TAG: u16 = 0xcd41;
+const TIFF_NEW_SUBFILE_TYPE: u16 = 254;
+const TIFF_IMAGE_WIDTH: u16 = 256;
+const TIFF_IMAGE_LENGTH: u16 = 257;
+/// Prefixes of the status codes of hard binding checks
+const HARD_BINDING_CODES: [&str; 3] = [
+    "assertion.dataHash.",
+    "assertion.bmffHash.",
+    "assertion.boxesHash.",
+];
+/// Bound on the pages walked, against IFD chains that never end
+const MAX_PAGES: usize = 10_000;
+
+struct Tiff<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+    big: bool,
+}
+
+impl Tiff<'_> {
+    fn uint(&self, at: usize, len: usize) -> Option<u64> {
+        let bytes = self.data.get(at..at.checked_add(len)?)?;
+        let fold = |n: u64, &b: &u8| (n << 8) | u64::from(b);
+        Some(if self.little_endian {
+            bytes.iter().rev().fold(0, fold)
+        } else {
+            bytes.iter().fold(0, fold)
+        })
+    }
+
+    fn offset_len(&self) -> usize {
+        if self.big {
+            8
+        } else {
+            4
+        }
+    }
+
+    /// The value of a SHORT, LONG or LONG8 entry with a count of 1
+    fn entry_value(&self, entry: usize) -> Option<u64> {
+        let (kind, count) = (
+            self.uint(entry + 2, 2)?,
+            self.uint(entry + 4, self.offset_len())?,
+        );
+        let value = entry + 4 + self.offset_len();
+        match (kind, count) {
+            (3, 1) => self.uint(value, 2),
+            (4, 1) => self.uint(value, 4),
+            (16, 1) => self.uint(value, 8),
+            _ => None,
+        }
+    }
+}
+
+/// The pages of a TIFF file, one per IFD in the main chain
+fn tiff_pages(data: &[u8]) -> Vec<Value> {
+    let little_endian = match data.get(..2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => return Vec::new(),
+    };
+    let mut tiff = Tiff {
+        data,
+        little_endian,
+        big: false,
+    };
+    let mut next = match tiff.uint(2, 2) {
+        Some(42) => tiff.uint(4, 4),
+        Some(43) => {
+            tiff.big = true;
+            tiff.uint(8, 8)
+        }
+        _ => None,
+    };
+
+    let (count_len, entry_len) = if tiff.big { (8, 20) } else { (2, 12) };
+    let mut pages = Vec::new();
+    let mut visited = HashSet::new();
+    while let Some(offset) = next
+        .and_then(|o| usize::try_from(o).ok())
+        .filter(|&o| o != 0)
+    {
+        if pages.len() == MAX_PAGES || !visited.insert(offset) {
+            break;
+        }
+        let Some(count) = tiff
+            .uint(offset, count_len)
+            .and_then(|c| usize::try_from(c).ok())
+        else {
+            break;
+        };
+        // Classic TIFF counts are 16 bit; larger BigTIFF counts are corrupt
+        let count = count.min(0xffff);
+        let mut page = json!({
+            "index": pages.len(),
+            "kind": "page",
+            "width": null,
+            "height": null,
+            "subfile_type": 0,
+            "has_manifest_store": false,
+        });
+        for i in 0..count {
+            let entry = offset + count_len + i * entry_len;
+            let Some(tag) = tiff.uint(entry, 2) else {
+                break;
+            };
+            match tag as u16 {
+                TIFF_IMAGE_WIDTH => page["width"] = json!(tiff.entry_value(entry)),
+                TIFF_IMAGE_LENGTH => page["height"] = json!(tiff.entry_value(entry)),
+                TIFF_NEW_SUBFILE_TYPE => page["subfile_type"] = json!(tiff.entry_value(entry)),
+                TIFF_C2PA_TAG => page["has_manifest_store"] = json!(true),
+                _ => {}
+            }
+        }
+        pages.push(page);
+        next = tiff.uint(offset + count_len + count * entry_len, tiff.offset_len());
+    }
+    pages
+}
+
+/// The ISO BMFF boxes of `data`, as (type, payload)
+fn bmff_boxes(data: &[u8]) -> Vec<([u8; 4], &[u8])> {
+    let Some(mut source) = Source::new(Cursor::new(data)) else {
+        return Vec::new();
+    };
+    asset_info::bmff_boxes(&mut source, 0, data.len() as u64)
+        .into_iter()
+        .map(|(kind, body, end)| (kind, &data[body as usize..end as usize]))
+        .collect()
+}
+
+fn child<'a>(payload: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    bmff_boxes(payload)
+        .into_iter()
+        .find(|(k, _)| k == kind)
+        .map(|(_, p)| p)
+}
+
+fn be(bytes: Option<&[u8]>) -> Option<u32> {
+    bytes.map(|b| b.iter().fold(0, |n, &b| (n << 8) | u32::from(b)))
+}
+
+fn fourcc(bytes: Option<&[u8]>) -> Option<String> {
+    bytes.map(|b| String::from_utf8_lossy(b).into_owned())
+}
+
+/// The items of the file level `meta` box and the tracks of a HEIF file
+fn heif_items(data: &[u8]) -> Vec<Value> {
+    let top = bmff_boxes(data);
+    let mut items = Vec::new();
+
+    // `meta` and its children below are full boxes: version, then 3 flag bytes
+    if let Some(meta) = top
+        .iter()
+        .find(|(k, _)| k == b"meta")
+        .and_then(|(_, p)| p.get(4..))
+    {
+        let primary = child(meta, b"pitm").and_then(|pitm| match pitm.first()? {
+            0 => be(pitm.get(4..6)),
+            _ => be(pitm.get(4..8)),
+        });
+        if let Some(iinf) = child(meta, b"iinf") {
+            let entries = match iinf.first() {
+                Some(0) => iinf.get(6..),
+                _ => iinf.get(8..),
+            };
+            for (kind, infe) in bmff_boxes(entries.unwrap_or_default()) {
+                if &kind != b"infe" {
+                    continue;
+                }
+                let (id, item_type) = match infe.first() {
+                    Some(2) => (be(infe.get(4..6)), fourcc(infe.get(8..12))),
+                    Some(3) => (be(infe.get(4..8)), fourcc(infe.get(10..14))),
+                    _ => (be(infe.get(4..6)), None),
+                };
+                items.push(json!({
+                    "index": items.len(),
+                    "kind": "item",
+                    "id": id,
+                    "type": item_type,
+                    "primary": id.is_some() && id == primary,
+                }));
+            }
+        }
+    }
+
+    if let Some(moov) = top.iter().find(|(k, _)| k == b"moov").map(|(_, p)| *p) {
+        for (kind, trak) in bmff_boxes(moov) {
+            if &kind != b"trak" {
+                continue;
+            }
+            let id = child(trak, b"tkhd").and_then(|tkhd| match tkhd.first()? {
+                0 => be(tkhd.get(12..16)),
+                _ => be(tkhd.get(20..24)),
+            });
+            let handler = child(trak, b"mdia")
+                .and_then(|mdia| child(mdia, b"hdlr"))
+                .and_then(|hdlr| fourcc(hdlr.get(8..12)));
+            items.push(json!({
+                "index": items.len(),
+                "kind": "track",
+                "id": id,
+                "handler": handler,
+            }));
+        }
+    }
+    items
+}
+
+/// The hard binding of the active manifest, from its validation results, as
+/// (assertion label, whether it matched)
+fn hard_binding(result: &Value) -> Option<(String, bool)> {
+    let active = result.get("validation_results")?.get("activeManifest")?;
+    let binding = |status: &Value| {
+        let code = status.get("code")?.as_str()?;
+        let url = status.get("url")?.as_str()?;
+        let label = url.rsplit('/').next()?;
+        HARD_BINDING_CODES
+            .iter()
+            .any(|prefix| code.starts_with(prefix))
+            .then(|| (label.to_owned(), code.ends_with(".match")))
+    };
+    let statuses = |key: &str| {
+        active
+            .get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(binding)
+            .collect::<Vec<_>>()
+    };
+    let failures = statuses("failure");
+    match failures.into_iter().next() {
+        Some((label, _)) => Some((label, false)),
+        None => statuses("success").into_iter().next(),
+    }
+}
+
+/// List the pages or images of a multi-image asset with their C2PA binding
+///
+/// The manifest store of a TIFF or HEIF file binds the whole file, so every
+/// page or item is covered by the active manifest when its hard binding
+/// matches. Supported are TIFF (pages of the main IFD chain) and HEIF, HEIC and
+/// AVIF (items of the file level `meta` box, then the tracks of image sequences).
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data: "image/tiff", "image/heic", "image/heif"
+///         or "image/avif"
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A dict with "result" (the C2PA data as returned by read_c2pa_from_bytes, or
+///     None), "binding" (the active manifest's hard binding assertion "label" and
+///     whether it is "valid", or None), "active_item" (the index of the page
+///     holding the manifest store, or of the primary HEIF item) and "items": one
+///     dict per page or item with "index", "kind" ("page", "item" or "track"),
+///     "bound" (whether the active manifest's binding covers it) and, for pages,
+///     "width", "height", "subfile_type" and "has_manifest_store", for items "id",
+///     "type" and "primary", and for tracks "id" and "handler"
+///
+/// Raises:
+///     ValueError: If the MIME type is not a multi-image format
+///     C2paError: If there is an error reading or parsing the C2PA data
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, allow_threads=None))]
+pub fn read_c2pa_items(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<PyObject> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let list = || match mime_type {
+        "image/tiff" | "tiff" | "tif" => Some(tiff_pages(data)),
+        "image/heic" | "image/heif" | "image/avif" | "heic" | "heif" | "avif" => {
+            Some(heif_items(data))
+        }
+        _ => None,
+    };
+    let items = if allow_threads {
+        py.allow_threads(list)
+    } else {
+        list()
+    };
+    let Some(mut items) = items else {
+        return Err(PyValueError::new_err(format!(
+            "{mime_type} is not a multi-image format"
+        )));
+    };
+
+    let result = read_value(py, data, mime_type, allow_threads)?;
+    let binding = result.as_ref().and_then(hard_binding);
+    let bound = binding.as_ref().is_some_and(|(_, valid)| *valid);
+    for item in &mut items {
+        item["bound"] = json!(bound);
+    }
+    let active_item = result.as_ref().and_then(|_| {
+        items
+            .iter()
+            .find(|item| {
+                item["has_manifest_store"] == json!(true) || item["primary"] == json!(true)
+            })
+            .map(|item| item["index"].clone())
+    });
+
+    value_to_py(
+        py,
+        &json!({
+            "result": result,
+            "binding": binding.map(|(label, valid)| json!({ "label": label, "valid": valid })),
+            "active_item": active_item,
+            "items": items,
+        }),
+    )
+}
@@ -0,0 +1,493 @@
+//! Tolerant JUMBF helpers
+//!
+//! c2pa-rs rejects an asset outright when its container cannot be parsed, which
+//! is what happens to uploads cut off in the middle of the manifest store. The
+//! functions here locate the raw manifest store bytes in JPEG and PNG files
+//! without requiring the rest of the file to be intact, and walk JUMBF boxes
+//! without trusting their declared lengths.
+
+use crate::id3::scan_mp3;
+
+/// First four bytes of the C2PA JUMBF description box UUID
+const C2PA_MARKER: &[u8; 4] = b"c2pa";
+
+const JPEG_APP11: u8 = 0xEB;
+const JPEG_SOS: u8 = 0xDA;
+const JPEG_EOI: u8 = 0xD9;
+const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+/// A box found while walking JUMBF data
+pub struct JumbfBox<'a> {
+    /// Four character box type, e.g. `jumb` or `jumd`
+    pub box_type: [u8; 4],
+    /// Header plus payload, clipped to the available data
+    pub raw: &'a [u8],
+    /// Payload, clipped to the available data
+    pub payload: &'a [u8],
+    /// Length the box header declares
+    pub declared_len: u64,
+}
+
+impl JumbfBox<'_> {
+    /// Whether the declared length is fully present in the data
+    pub fn is_complete(&self) -> bool {
+        self.raw.len() as u64 == self.declared_len
+    }
+
+    /// Whether this is a superbox described with the C2PA UUID
+    pub fn is_c2pa(&self) -> bool {
+        self.payload.len() >= 16 && &self.payload[8..12] == C2PA_MARKER
+    }
+}
+
+/// Split `data` into consecutive boxes
+///
+/// The last box is returned even when it is cut short; check
+/// [`JumbfBox::is_complete`] before relying on its contents.
+pub fn parse_boxes(data: &[u8]) -> Vec<JumbfBox<'_>> {
+    let mut boxes = Vec::new();
+    let mut pos = 0usize;
+    while data.len() - pos >= 8 {
+        let lbox = u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]);
+        let box_type = [data[pos + 4], data[pos + 5], data[pos + 6], data[pos + 7]];
+        let (header_len, declared_len) = match lbox {
+            0 => (8usize, (data.len() - pos) as u64),
+            1 => {
+                if data.len() - pos < 16 {
+                    break;
+                }
+                let mut xl = [0u8; 8];
+                xl.copy_from_slice(&data[pos + 8..pos + 16]);
+                (16usize, u64::from_be_bytes(xl))
+            }
+            len => (8usize, len as u64),
+        };
+        if declared_len < header_len as u64 {
+            break;
+        }
+        let end = pos
+            .saturating_add(usize::try_from(declared_len).unwrap_or(usize::MAX))
+            .min(data.len());
+        boxes.push(JumbfBox {
+            box_type,
+            raw: &data[pos..end],
+            payload: &data[pos + header_len..end],
+            declared_len,
+        });
+        pos = end;
+    }
+    boxes
+}
+
+/// Locate the C2PA manifest store in a JPEG, PNG or MP3 file without parsing
+/// the whole container
+///
+/// Returns the (possibly truncated) JUMBF bytes, or `None` for other formats or
+/// when no manifest store is present.
+pub fn scan_manifest_store(data: &[u8], mime_type: &str) -> Option<Vec<u8>> {
+    match mime_type {
+        "image/jpeg" | "image/jpg" | "jpg" | "jpeg" => scan_jpeg(data),
+        "image/png" | "png" => scan_png(data),
+        "audio/mpeg" | "audio/mp3" | "mp3" => scan_mp3(data),
+        _ => None,
+    }
+}
+
+/// A marker segment found while walking a JPEG file
+pub struct JpegSegment {
+    /// Marker code, e.g. 0xEB for APP11
+    pub marker: u8,
+    /// Offset of the 0xFF byte that starts the marker
+    pub offset: usize,
+    /// Segment length as declared after the marker (0 for standalone markers)
+    pub length: usize,
+    /// Number of 0xFF fill bytes before the marker
+    pub fill_bytes: usize,
+    /// Number of stray non-marker bytes skipped before the marker
+    pub skipped_bytes: usize,
+    /// Whether the segment runs past the end of the data
+    pub truncated: bool,
+    /// JPEG XT box instance and packet sequence for APP11 "JP" segments
+    pub jpeg_xt: Option<(u16, u32)>,
+    /// Whether the segment belongs to the C2PA manifest store
+    pub c2pa: bool,
+}
+
+/// Walk the marker segments of a JPEG file up to the start of scan
+///
+/// Fill bytes and stray padding between segments are skipped rather than
+/// treated as errors. APP11 segments carrying the C2PA manifest store are
+/// flagged as such; a packet that repeats an earlier sequence number is not.
+pub fn jpeg_segments(data: &[u8]) -> Vec<JpegSegment> {
+    let mut segments = Vec::new();
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return segments;
+    }
+    segments.push(JpegSegment {
+        marker: 0xD8,
+        offset: 0,
+        length: 0,
+        fill_bytes: 0,
+        skipped_bytes: 0,
+        truncated: false,
+        jpeg_xt: None,
+        c2pa: false,
+    });
+    let mut store_en: Option<u16> = None;
+    let mut last_seq = 0u32;
+    let mut pos = 2usize;
+    let mut skipped_bytes = 0usize;
+    while pos + 1 < data.len() {
+        if data[pos] != 0xFF {
+            pos += 1;
+            skipped_bytes += 1;
+            continue;
+        }
+        // Any number of 0xFF fill bytes may precede a marker
+        let mut fill_bytes = 0usize;
+        while pos + 1 < data.len() && data[pos + 1] == 0xFF {
+            pos += 1;
+            fill_bytes += 1;
+        }
+        if pos + 1 >= data.len() {
+            break;
+        }
+        let marker = data[pos + 1];
+        let mut segment = JpegSegment {
+            marker,
+            offset: pos,
+            length: 0,
+            fill_bytes,
+            skipped_bytes,
+            truncated: false,
+            jpeg_xt: None,
+            c2pa: false,
+        };
+        skipped_bytes = 0;
+        if marker == 0x01 || (0xD0..=0xD8).contains(&marker) || marker == JPEG_EOI {
+            segments.push(segment);
+            if marker == JPEG_EOI {
+                break;
+            }
+            pos += 2;
+            continue;
+        }
+        if pos + 4 > data.len() {
+            segment.truncated = true;
+            segments.push(segment);
+            break;
+        }
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let start = pos + 4;
+        let end = (pos + 2 + length).min(data.len());
+        segment.length = length;
+        segment.truncated = pos + 2 + length > data.len();
+
+        if marker == JPEG_APP11 && end > start && end - start > 16 && &data[start..start + 2] == b"JP" {
+            let body = &data[start..end];
+            let en = u16::from_be_bytes([body[2], body[3]]);
+            let seq = u32::from_be_bytes([body[4], body[5], body[6], body[7]]);
+            segment.jpeg_xt = Some((en, seq));
+            match store_en {
+                Some(store) if store == en && seq > last_seq => {
+                    segment.c2pa = true;
+                    last_seq = seq;
+                }
+                None if body.len() > 28 && &body[24..28] == C2PA_MARKER => {
+                    segment.c2pa = true;
+                    store_en = Some(en);
+                    last_seq = seq;
+                }
+                _ => {}
+            }
+        }
+
+        segments.push(segment);
+        if marker == JPEG_SOS {
+            break;
+        }
+        pos = start.saturating_add(length.saturating_sub(2));
+    }
+    segments
+}
+
+fn scan_jpeg(data: &[u8]) -> Option<Vec<u8>> {
+    let mut store: Option<Vec<u8>> = None;
+    for segment in jpeg_segments(data).iter().filter(|s| s.c2pa) {
+        let start = segment.offset + 4;
+        let end = (segment.offset + 2 + segment.length).min(data.len());
+        let body = &data[start..end];
+        match store.as_mut() {
+            // Continuation packets repeat LBox and TBox, skip them
+            Some(buffer) => buffer.extend_from_slice(&body[16..]),
+            None => store = Some(body[8..].to_vec()),
+        }
+    }
+    store
+}
+
+fn scan_png(data: &[u8]) -> Option<Vec<u8>> {
+    if !data.starts_with(PNG_SIGNATURE) {
+        return None;
+    }
+    let mut pos = PNG_SIGNATURE.len();
+    while pos + 8 <= data.len() {
+        let len =
+            u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+        let chunk_type = &data[pos + 4..pos + 8];
+        let start = pos + 8;
+        let end = start.saturating_add(len).min(data.len());
+        if chunk_type == b"caBX" {
+            return Some(data[start..end].to_vec());
+        }
+        if chunk_type == b"IEND" {
+            break;
+        }
+        // Skip payload and CRC
+        pos = start.saturating_add(len).saturating_add(4);
+    }
+    None
+}
+
+/// Label of a superbox, from its description box
+fn superbox_label<'a>(superbox: &JumbfBox<'a>) -> Option<&'a str> {
+    let description = parse_boxes(superbox.payload).into_iter().next()?;
+    if &description.box_type != b"jumd" {
+        return None;
+    }
+    // UUID, then a toggles byte whose bit 1 says a label follows
+    let (&toggles, rest) = description.payload.get(16..)?.split_first()?;
+    if toggles & 0x02 == 0 {
+        return None;
+    }
+    let end = rest.iter().position(|&b| b == 0)?;
+    std::str::from_utf8(&rest[..end]).ok()
+}
+
+/// The signatures of the manifests in a manifest store, as (manifest label,
+/// COSE_Sign1 bytes) pairs in store order
+pub fn manifest_signatures(store: &[u8]) -> Vec<(&str, &[u8])> {
+    let Some(outer) = parse_boxes(store).into_iter().next().filter(JumbfBox::is_c2pa) else {
+        return Vec::new();
+    };
+    let mut signatures = Vec::new();
+    for manifest in parse_boxes(outer.payload).iter().skip(1) {
+        let Some(label) = superbox_label(manifest) else {
+            continue;
+        };
+        let signature = parse_boxes(manifest.payload)
+            .into_iter()
+            .skip(1)
+            .find(|child| superbox_label(child) == Some("c2pa.signature"))
+            .and_then(|signature| parse_boxes(signature.payload).into_iter().nth(1));
+        if let Some(content) = signature {
+            signatures.push((label, content.payload));
+        }
+    }
+    signatures
+}
+
+/// `store` with the signature of its active manifest, the last one, replaced
+/// by `signature`
+///
+/// The boxes holding the signature grow or shrink with it. Returns `None` if
+/// the store has no active signature or uses other than plain 32-bit box
+/// lengths around it.
+pub fn with_active_signature(store: &[u8], signature: &[u8]) -> Option<Vec<u8>> {
+    let outer = parse_boxes(store).into_iter().next().filter(JumbfBox::is_c2pa)?;
+    let manifest = parse_boxes(outer.payload).into_iter().skip(1).last()?;
+    let superbox = parse_boxes(manifest.payload)
+        .into_iter()
+        .skip(1)
+        .find(|child| superbox_label(child) == Some("c2pa.signature"))?;
+    let content = parse_boxes(superbox.payload).into_iter().nth(1)?;
+
+    let offset = |raw: &[u8]| raw.as_ptr() as usize - store.as_ptr() as usize;
+    let start = offset(content.payload);
+    let end = start + content.payload.len();
+    let mut replaced = Vec::with_capacity(store.len() - content.payload.len() + signature.len());
+    replaced.extend_from_slice(&store[..start]);
+    replaced.extend_from_slice(signature);
+    replaced.extend_from_slice(&store[end..]);
+    // The headers all come before the signature, so they stay in place
+    for holder in [&outer, &manifest, &superbox, &content] {
+        let lbox = u32::from_be_bytes(holder.raw[..4].try_into().ok()?);
+        if !holder.is_complete() || u64::from(lbox) != holder.declared_len {
+            return None;
+        }
+        let len = (holder.declared_len as usize + signature.len())
+            .checked_sub(content.payload.len())
+            .and_then(|len| u32::try_from(len).ok())?;
+        let at = offset(holder.raw);
+        replaced[at..at + 4].copy_from_slice(&len.to_be_bytes());
+    }
+    Some(replaced)
+}
+
+/// The claims of the manifests in a manifest store, as (manifest label, claim
+/// box label) pairs in store order: "c2pa.claim" for claims of version 1,
+/// "c2pa.claim.v2" for version 2
+pub fn manifest_claims(store: &[u8]) -> Vec<(&str, &str)> {
+    let Some(outer) = parse_boxes(store).into_iter().next().filter(JumbfBox::is_c2pa) else {
+        return Vec::new();
+    };
+    parse_boxes(outer.payload)
+        .iter()
+        .skip(1)
+        .filter_map(|manifest| {
+            let claim = parse_boxes(manifest.payload)
+                .iter()
+                .skip(1)
+                .filter_map(superbox_label)
+                .find(|label| label.starts_with("c2pa.claim"))?;
+            Some((superbox_label(manifest)?, claim))
+        })
+        .collect()
+}
+
+/// The claims of the manifests in a manifest store, as (manifest label, claim
+/// CBOR bytes) pairs in store order: the bytes the claim signature covers
+pub fn manifest_claim_bytes(store: &[u8]) -> Vec<(&str, &[u8])> {
+    let Some(outer) = parse_boxes(store).into_iter().next().filter(JumbfBox::is_c2pa) else {
+        return Vec::new();
+    };
+    parse_boxes(outer.payload)
+        .iter()
+        .skip(1)
+        .filter_map(|manifest| {
+            let claim = parse_boxes(manifest.payload)
+                .into_iter()
+                .skip(1)
+                .find(|child| superbox_label(child).is_some_and(|l| l.starts_with("c2pa.claim")))?;
+            let content = parse_boxes(claim.payload).into_iter().nth(1)?;
+            Some((superbox_label(manifest)?, content.payload))
+        })
+        .collect()
+}
+
+/// The assertions of a manifest superbox as (assertion label, content bytes)
+fn assertions<'a>(manifest: &JumbfBox<'a>) -> Vec<(&'a str, &'a [u8])> {
+    let Some(assertion_store) = parse_boxes(manifest.payload)
+        .into_iter()
+        .skip(1)
+        .find(|child| superbox_label(child) == Some("c2pa.assertions"))
+    else {
+        return Vec::new();
+    };
+    parse_boxes(assertion_store.payload)
+        .iter()
+        .skip(1)
+        .filter_map(|assertion| {
+            let content = parse_boxes(assertion.payload).into_iter().nth(1)?;
+            Some((superbox_label(assertion)?, content.payload))
+        })
+        .collect()
+}
+
+/// The assertions of every manifest of a manifest store, as (manifest label,
+/// assertion label, content bytes) in store order
+pub fn manifest_assertions(store: &[u8]) -> Vec<(&str, &str, &[u8])> {
+    let Some(outer) = parse_boxes(store).into_iter().next().filter(JumbfBox::is_c2pa) else {
+        return Vec::new();
+    };
+    parse_boxes(outer.payload)
+        .iter()
+        .skip(1)
+        .filter_map(|manifest| Some((superbox_label(manifest)?, assertions(manifest))))
+        .flat_map(|(label, assertions)| {
+            assertions
+                .into_iter()
+                .map(move |(assertion, content)| (label, assertion, content))
+        })
+        .collect()
+}
+
+/// The assertions of the active manifest of a manifest store, the last one,
+/// as (manifest label, assertion label, content bytes) in store order
+pub fn active_manifest_assertions(store: &[u8]) -> Vec<(&str, &str, &[u8])> {
+    let Some(outer) = parse_boxes(store).into_iter().next().filter(JumbfBox::is_c2pa) else {
+        return Vec::new();
+    };
+    let Some(manifest) = parse_boxes(outer.payload).into_iter().skip(1).last() else {
+        return Vec::new();
+    };
+    let Some(label) = superbox_label(&manifest) else {
+        return Vec::new();
+    };
+    assertions(&manifest)
+        .into_iter()
+        .map(|(assertion, content)| (label, assertion, content))
+        .collect()
+}
+
+/// The claim thumbnail of the active manifest of a manifest store, the last
+/// one, as (media type, image bytes), if fully present
+pub fn active_claim_thumbnail(store: &[u8]) -> Option<(&str, &[u8])> {
+    let outer = parse_boxes(store).into_iter().next().filter(JumbfBox::is_c2pa)?;
+    let manifest = parse_boxes(outer.payload).into_iter().skip(1).last()?;
+    let assertion_store = parse_boxes(manifest.payload)
+        .into_iter()
+        .skip(1)
+        .find(|child| superbox_label(child) == Some("c2pa.assertions"))?;
+    let thumbnail = parse_boxes(assertion_store.payload).into_iter().skip(1).find(|assertion| {
+        superbox_label(assertion).is_some_and(|l| l.starts_with("c2pa.thumbnail.claim"))
+    })?;
+    // An embedded file: a file description box, then the file data box
+    let boxes = parse_boxes(thumbnail.payload);
+    let description = boxes.iter().find(|child| &child.box_type == b"bfdb")?;
+    let data = boxes.iter().find(|child| &child.box_type == b"bidb" && child.is_complete())?;
+    // A toggles byte, then the media type up to its NUL
+    let media_type = description.payload.get(1..)?;
+    let end = media_type.iter().position(|&b| b == 0).unwrap_or(media_type.len());
+    Some((std::str::from_utf8(&media_type[..end]).ok()?, data.payload))
+}
+
+/// Whether `store` is a complete C2PA manifest store superbox
+pub fn is_complete_store(store: &[u8]) -> bool {
+    parse_boxes(store)
+        .first()
+        .is_some_and(|outer| outer.is_c2pa() && outer.is_complete())
+}
+
+/// Outcome of [`salvage_manifest_store`]
+pub struct Salvaged {
+    /// Manifest store rebuilt from the manifests that are fully present
+    pub store: Vec<u8>,
+    /// Number of manifests kept
+    pub kept: usize,
+}
+
+/// Rebuild a truncated manifest store from its complete manifests
+///
+/// Returns `None` when `store` is not a C2PA manifest store or is not
+/// truncated.
+pub fn salvage_manifest_store(store: &[u8]) -> Option<Salvaged> {
+    let outer = parse_boxes(store).into_iter().next()?;
+    if !outer.is_c2pa() || outer.is_complete() {
+        return None;
+    }
+
+    let children = parse_boxes(outer.payload);
+    let mut body = Vec::new();
+    let mut kept = 0;
+    for (index, child) in children.iter().enumerate() {
+        if index == 0 {
+            if &child.box_type != b"jumd" || !child.is_complete() {
+                return None;
+            }
+            body.extend_from_slice(child.raw);
+        } else if child.is_complete() {
+            body.extend_from_slice(child.raw);
+            kept += 1;
+        }
+    }
+
+    let mut rebuilt = Vec::with_capacity(body.len() + 8);
+    rebuilt.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+    rebuilt.extend_from_slice(b"jumb");
+    rebuilt.extend_from_slice(&body);
+    Some(Salvaged {
+        store: rebuilt,
+        kept,
+    })
+}
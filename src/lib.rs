@@ -1,23 +1,199 @@
 //TODO: Check better way to handle mime
 
 use pyo3::prelude::*;
-use pyo3::exceptions::PyRuntimeError;
+
+mod actions;
+use actions::{describe_actions, set_action_translations};
+
+mod archive;
+use archive::read_c2pa_from_zip;
+
+mod assertion_metadata;
+use assertion_metadata::get_assertion_metadata;
+
+#[cfg(feature = "asyncio")]
+mod async_reader;
+#[cfg(feature = "asyncio")]
+use async_reader::read_c2pa_from_bytes_async;
+
+mod asset_info;
+use asset_info::get_asset_info;
+
+mod audit;
+use audit::set_audit_callback;
+
+mod batch;
+use batch::read_c2pa_batch;
+
+mod buffer;
+
+mod build_info;
+use build_info::{can_embed, can_sidecar, capabilities, supported_mime_types};
+
+mod cache;
+use cache::{
+    clear_shared_cache, disable_shared_cache, enable_shared_cache, get_cache_stats,
+    get_shared_cache_stats,
+};
 
 mod c2pa_reader;
 use c2pa_reader::{
     read_c2pa_from_bytes,
+    read_c2pa_from_file,
+    read_c2pa_from_stream,
+    read_c2pa_json_from_bytes,
+    read_c2pa_encoded_from_bytes,
+    verify_manifest_bytes,
+    extract_manifest_store,
 };
 
-mod utils;
-use utils::{
-    convert_to_gray_keep_c2pa
+mod chunks;
+
+mod constants;
+use constants::get_c2pa_constants;
+mod coverage;
+use coverage::get_hash_coverage;
+
+mod datetimes;
+mod deadline;
+use deadline::{get_abandoned_parses, set_max_abandoned_parses, TimeBudgetExceeded};
+mod decoders;
+use decoders::register_assertion_decoder;
+mod definition;
+use definition::{get_manifest_definition_schema, validate_manifest_definition};
+mod detailed;
+mod digest;
+use digest::read_c2pa_digest;
+mod email;
+use email::read_c2pa_from_email;
+
+mod embed;
+use embed::{embed_jumbf, extract_jumbf, relocate_manifest, remove_c2pa, strip_c2pa};
+
+mod errors;
+use errors::{
+    c2pa_error, C2paError, IoError, ManifestNotFoundError, SigningError, UnsupportedFormatError,
+    ValidationError,
+};
+mod external;
+use external::set_resource_resolver;
+
+mod fragments;
+use fragments::read_c2pa_fragmented;
+
+mod gil;
+use gil::{get_gil_release_threshold, set_gil_release_threshold};
+
+mod http;
+use http::{configure_http, fetch_url, get_http_config};
+mod id3;
+mod ingredients;
+use ingredients::flatten_ingredients;
+mod items;
+use items::read_c2pa_items;
+mod jumbf;
+mod lifecycle;
+use lifecycle::shutdown;
+#[cfg(feature = "pdf")]
+mod pdf;
+#[cfg(feature = "pdf")]
+use pdf::read_c2pa_from_pdf_images;
+#[cfg(feature = "memory-stats")]
+mod memory;
+#[cfg(feature = "memory-stats")]
+use memory::measure_memory;
+mod merge;
+use merge::read_c2pa_merged;
+mod mime;
+use mime::detect_mime_from_bytes;
+mod proto;
+use proto::to_protobuf;
+mod reader;
+use reader::Reader;
+#[cfg(feature = "signing")]
+mod receipt;
+#[cfg(feature = "signing")]
+use receipt::{set_receipt_signer, verify_receipt};
+mod remote;
+use remote::set_remote_manifest_fetcher;
+mod repair;
+use repair::repair_c2pa;
+
+mod reputation;
+use reputation::set_signer_reputation;
+mod revocation;
+use revocation::{clear_ocsp_cache, get_ocsp_cache_stats, set_ocsp_cache};
+mod revalidation;
+use revalidation::revalidate;
+mod resources;
+use resources::{
+    get_claim_thumbnail_fast, get_generator_icons, read_c2pa_resources, set_resource_limits,
+};
+mod sarif;
+use sarif::to_sarif;
+mod scan;
+use scan::scan_to_file;
+mod schema;
+mod serialize;
+mod settings;
+mod signature_verifier;
+use signature_verifier::set_signature_verifier;
+mod signatures;
+use signatures::{get_manifest_signers, read_signing_info};
+#[cfg(feature = "signing")]
+mod signing;
+#[cfg(feature = "signing")]
+use signing::{
+    add_ingredient_from_url, create_ingredient_from_bytes, sign_and_embed, sign_with_manifest_file,
+    CallbackSigner,
 };
 
+#[cfg(feature = "transforms")]
+mod similarity;
+#[cfg(feature = "transforms")]
+use similarity::compare_ingredient_thumbnails;
+
+mod soft_binding;
+use soft_binding::set_soft_binding_resolver;
+mod stream;
+#[cfg(feature = "signing")]
+mod test_assets;
+#[cfg(feature = "signing")]
+use test_assets::make_signed_test_asset;
+
+#[cfg(feature = "transforms")]
+mod thumbnail;
+#[cfg(feature = "transforms")]
+use thumbnail::get_thumbnail_array;
+
+#[cfg(feature = "transforms")]
+mod transform;
+#[cfg(feature = "transforms")]
+use transform::{convert_to_gray_keep_c2pa, transform_keep_c2pa};
+
+mod transparency;
+use transparency::set_transparency_log_verifier;
+
+mod trust;
+use trust::{enable_trust_checks, refresh_trust_list, set_allowed_list, set_trust_anchors};
+
+mod utils;
+use utils::list_jpeg_segments;
+
+mod validation;
+use validation::validate_c2pa_from_bytes;
+
+mod versions;
+use versions::set_version_limits;
+
+mod xmp;
+use xmp::{read_xmp, read_xmp_provenance, set_remote_reference};
+
 #[pyfunction]
 pub fn load_c2pa_settings(settings_json: &str) -> PyResult<()> {
-    match c2pa::settings::load_settings_from_str(settings_json, "json") {
+    match settings::load(settings_json) {
         Ok(_) => Ok(()),
-        Err(e) => Err(PyRuntimeError::new_err(format!("Error loading C2PA settings: {}", e)))
+        Err(e) => Err(c2pa_error("Error loading C2PA settings", &e))
     }
 }
 
@@ -28,7 +204,122 @@ pub fn load_c2pa_settings(settings_json: &str) -> PyResult<()> {
 #[pymodule]
 fn fast_c2pa_core(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(read_c2pa_from_bytes, m)?)?;
-    m.add_function(wrap_pyfunction!(load_c2pa_settings, m)?)?; 
-    m.add_function(wrap_pyfunction!(convert_to_gray_keep_c2pa, m)?)?; 
+    m.add_function(wrap_pyfunction!(read_c2pa_from_file, m)?)?;
+    m.add_function(wrap_pyfunction!(read_c2pa_from_stream, m)?)?;
+    m.add_function(wrap_pyfunction!(read_c2pa_fragmented, m)?)?;
+    #[cfg(feature = "asyncio")]
+    m.add_function(wrap_pyfunction!(read_c2pa_from_bytes_async, m)?)?;
+    m.add_function(wrap_pyfunction!(read_c2pa_json_from_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(read_c2pa_encoded_from_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_manifest_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(revalidate, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_c2pa_from_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_manifest_store, m)?)?;
+    m.add_function(wrap_pyfunction!(read_c2pa_merged, m)?)?;
+    #[cfg(feature = "signing")]
+    m.add_function(wrap_pyfunction!(sign_and_embed, m)?)?;
+    #[cfg(feature = "signing")]
+    m.add_function(wrap_pyfunction!(create_ingredient_from_bytes, m)?)?;
+    #[cfg(feature = "signing")]
+    m.add_function(wrap_pyfunction!(add_ingredient_from_url, m)?)?;
+    #[cfg(feature = "signing")]
+    m.add_function(wrap_pyfunction!(sign_with_manifest_file, m)?)?;
+    #[cfg(feature = "signing")]
+    m.add_class::<CallbackSigner>()?;
+    #[cfg(feature = "signing")]
+    m.add_function(wrap_pyfunction!(make_signed_test_asset, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_manifest_definition, m)?)?;
+    m.add_function(wrap_pyfunction!(get_manifest_definition_schema, m)?)?;
+    m.add_function(wrap_pyfunction!(get_c2pa_constants, m)?)?;
+    m.add_class::<Reader>()?;
+    m.add("C2paReader", m.getattr("Reader")?)?;
+    m.add_function(wrap_pyfunction!(read_c2pa_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(read_c2pa_from_zip, m)?)?;
+    m.add_function(wrap_pyfunction!(read_c2pa_from_email, m)?)?;
+    m.add_function(wrap_pyfunction!(read_c2pa_items, m)?)?;
+    m.add_function(wrap_pyfunction!(read_c2pa_digest, m)?)?;
+    m.add_function(wrap_pyfunction!(scan_to_file, m)?)?;
+    m.add_function(wrap_pyfunction!(get_hash_coverage, m)?)?;
+    m.add_function(wrap_pyfunction!(get_asset_info, m)?)?;
+    m.add_function(wrap_pyfunction!(get_manifest_signers, m)?)?;
+    m.add_function(wrap_pyfunction!(read_signing_info, m)?)?;
+    m.add_function(wrap_pyfunction!(get_assertion_metadata, m)?)?;
+    m.add_function(wrap_pyfunction!(describe_actions, m)?)?;
+    m.add_function(wrap_pyfunction!(set_action_translations, m)?)?;
+    #[cfg(feature = "pdf")]
+    m.add_function(wrap_pyfunction!(read_c2pa_from_pdf_images, m)?)?;
+    m.add_function(wrap_pyfunction!(get_generator_icons, m)?)?;
+    m.add_function(wrap_pyfunction!(read_c2pa_resources, m)?)?;
+    m.add_function(wrap_pyfunction!(get_claim_thumbnail_fast, m)?)?;
+    m.add_function(wrap_pyfunction!(set_resource_limits, m)?)?;
+    #[cfg(feature = "transforms")]
+    m.add_function(wrap_pyfunction!(get_thumbnail_array, m)?)?;
+    #[cfg(feature = "transforms")]
+    m.add_function(wrap_pyfunction!(compare_ingredient_thumbnails, m)?)?;
+    m.add_function(wrap_pyfunction!(set_soft_binding_resolver, m)?)?;
+    m.add_function(wrap_pyfunction!(set_remote_manifest_fetcher, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_mime_from_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_jumbf, m)?)?;
+    m.add_function(wrap_pyfunction!(embed_jumbf, m)?)?;
+    m.add_function(wrap_pyfunction!(strip_c2pa, m)?)?;
+    m.add_function(wrap_pyfunction!(remove_c2pa, m)?)?;
+    m.add_function(wrap_pyfunction!(relocate_manifest, m)?)?;
+    m.add_function(wrap_pyfunction!(register_assertion_decoder, m)?)?;
+    m.add_function(wrap_pyfunction!(set_resource_resolver, m)?)?;
+    m.add_function(wrap_pyfunction!(set_transparency_log_verifier, m)?)?;
+    m.add_function(wrap_pyfunction!(set_trust_anchors, m)?)?;
+    m.add_function(wrap_pyfunction!(set_allowed_list, m)?)?;
+    m.add_function(wrap_pyfunction!(enable_trust_checks, m)?)?;
+    m.add_function(wrap_pyfunction!(refresh_trust_list, m)?)?;
+    m.add_function(wrap_pyfunction!(set_signature_verifier, m)?)?;
+    m.add_function(wrap_pyfunction!(set_audit_callback, m)?)?;
+    #[cfg(feature = "signing")]
+    m.add_function(wrap_pyfunction!(set_receipt_signer, m)?)?;
+    #[cfg(feature = "signing")]
+    m.add_function(wrap_pyfunction!(verify_receipt, m)?)?;
+    m.add_function(wrap_pyfunction!(set_signer_reputation, m)?)?;
+    m.add_function(wrap_pyfunction!(set_version_limits, m)?)?;
+    m.add_function(wrap_pyfunction!(to_protobuf, m)?)?;
+    m.add_function(wrap_pyfunction!(to_sarif, m)?)?;
+    m.add_function(wrap_pyfunction!(flatten_ingredients, m)?)?;
+    m.add_function(wrap_pyfunction!(enable_shared_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(disable_shared_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_shared_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(get_shared_cache_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(get_cache_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(set_ocsp_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_ocsp_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(get_ocsp_cache_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(shutdown, m)?)?;
+    #[cfg(feature = "memory-stats")]
+    m.add_function(wrap_pyfunction!(measure_memory, m)?)?;
+    m.add_function(wrap_pyfunction!(capabilities, m)?)?;
+    m.add("TimeBudgetExceeded", m.py().get_type::<TimeBudgetExceeded>())?;
+    m.add_function(wrap_pyfunction!(set_max_abandoned_parses, m)?)?;
+    m.add_function(wrap_pyfunction!(get_abandoned_parses, m)?)?;
+    m.add("C2paError", m.py().get_type::<C2paError>())?;
+    m.add("UnsupportedFormatError", m.py().get_type::<UnsupportedFormatError>())?;
+    m.add("ManifestNotFoundError", m.py().get_type::<ManifestNotFoundError>())?;
+    m.add("ValidationError", m.py().get_type::<ValidationError>())?;
+    m.add("SigningError", m.py().get_type::<SigningError>())?;
+    m.add("IoError", m.py().get_type::<IoError>())?;
+    m.add_function(wrap_pyfunction!(can_embed, m)?)?;
+    m.add_function(wrap_pyfunction!(can_sidecar, m)?)?;
+    m.add_function(wrap_pyfunction!(supported_mime_types, m)?)?;
+    m.add_function(wrap_pyfunction!(load_c2pa_settings, m)?)?;
+    m.add_function(wrap_pyfunction!(set_gil_release_threshold, m)?)?;
+    m.add_function(wrap_pyfunction!(get_gil_release_threshold, m)?)?;
+    m.add_function(wrap_pyfunction!(configure_http, m)?)?;
+    m.add_function(wrap_pyfunction!(get_http_config, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_url, m)?)?;
+    #[cfg(feature = "transforms")]
+    m.add_function(wrap_pyfunction!(convert_to_gray_keep_c2pa, m)?)?;
+    #[cfg(feature = "transforms")]
+    m.add_function(wrap_pyfunction!(transform_keep_c2pa, m)?)?;
+    m.add_function(wrap_pyfunction!(list_jpeg_segments, m)?)?;
+    m.add_function(wrap_pyfunction!(read_xmp_provenance, m)?)?;
+    m.add_function(wrap_pyfunction!(read_xmp, m)?)?;
+    m.add_function(wrap_pyfunction!(set_remote_reference, m)?)?;
+    m.add_function(wrap_pyfunction!(repair_c2pa, m)?)?;
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,112 @@
+//! Shutting the library down before exit or fork
+//!
+//! Some work outlives the call that started it: parses abandoned when their
+//! deadline ran out, reads awaited on the tokio runtime, retries of remote
+//! downloads. A process that forks while one of them holds a lock, or exits
+//! while the shared cache has unwritten pages, is left in a bad state, so
+//! embedding applications call shutdown first. Work that runs on threads of
+//! its own registers as a worker, and downloads check the shutdown epoch
+//! they started in between attempts.
+
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+use pyo3::prelude::*;
+
+use crate::cache;
+use crate::http;
+
+static WORKERS: Mutex<usize> = Mutex::new(0);
+static FINISHED: Condvar = Condvar::new();
+
+/// Number of shutdowns so far: work started before the latest is cancelled
+static EPOCH: Mutex<u64> = Mutex::new(0);
+static CANCELLED: Condvar = Condvar::new();
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Background work shutdown waits for, until the guard is dropped
+pub(crate) struct Worker(());
+
+impl Worker {
+    pub(crate) fn start() -> Self {
+        *lock(&WORKERS) += 1;
+        Worker(())
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        *lock(&WORKERS) -= 1;
+        FINISHED.notify_all();
+    }
+}
+
+/// The shutdown epoch, taken when work that can be cancelled starts
+pub(crate) fn epoch() -> u64 {
+    *lock(&EPOCH)
+}
+
+/// Whether shutdown was called since `since` was taken
+pub(crate) fn cancelled(since: u64) -> bool {
+    epoch() != since
+}
+
+/// Sleep for `delay`, or until shutdown cancels the work started at `since`
+pub(crate) fn sleep(delay: Duration, since: u64) {
+    let epoch = lock(&EPOCH);
+    let _ = CANCELLED.wait_timeout_while(epoch, delay, |epoch| *epoch == since);
+}
+
+/// Wait for the workers to finish, at most `timeout`, and whether they did
+fn drain(timeout: Option<Duration>) -> bool {
+    let workers = lock(&WORKERS);
+    let workers = match timeout {
+        None => FINISHED
+            .wait_while(workers, |workers| *workers > 0)
+            .unwrap_or_else(|e| e.into_inner()),
+        Some(timeout) => {
+            FINISHED
+                .wait_timeout_while(workers, timeout, |workers| *workers > 0)
+                .unwrap_or_else(|e| e.into_inner())
+                .0
+        }
+    };
+    *workers == 0
+}
+
+/// Stop the library's background work, before the process exits or forks
+///
+/// Remote downloads in progress are cancelled: they fail with an IoError
+/// before their next attempt or redirect, a request already sent running
+/// until it completes or times out. Then the background work is waited for:
+/// parses abandoned by deadline_ms, awaited reads of read_c2pa_from_bytes_async
+/// and read_c2pa_batch calls of other threads. Last, the shared cache is
+/// written to its file and closed in this process, and the idle connections
+/// of the HTTP client are dropped.
+///
+/// The library can be used again afterwards, with the shared cache disabled
+/// until enable_shared_cache is called again. Reads started meanwhile are not
+/// cancelled.
+///
+/// Args:
+///     timeout_ms: Longest wait for the background work in milliseconds
+///         (default: None, until it is done)
+///
+/// Returns:
+///     True if all background work finished, False if the timeout ran out first
+///
+/// Raises:
+///     OSError: If the shared cache cannot be written to its file
+#[pyfunction]
+#[pyo3(signature = (timeout_ms=None))]
+pub fn shutdown(py: Python, timeout_ms: Option<u64>) -> PyResult<bool> {
+    *lock(&EPOCH) += 1;
+    CANCELLED.notify_all();
+    let drained = py.allow_threads(|| drain(timeout_ms.map(Duration::from_millis)));
+    cache::close()?;
+    http::reset();
+    Ok(drained)
+}
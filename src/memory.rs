@@ -0,0 +1,180 @@
+//! Memory use of individual calls
+//!
+//! Built with the "memory-stats" cargo feature, the module's allocations go
+//! through a counting wrapper around the system allocator. Each measured call
+//! has counters of its own, charged by the thread it runs on and by the
+//! threads doing part of its work (the parse of a read with deadline_ms, the
+//! pool of read_c2pa_batch), so the peak of one call is not mixed with work
+//! done by other calls at the same time. Only allocations made by the Rust
+//! code are counted; Python objects are allocated by the interpreter.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::ptr;
+use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+
+struct Counting;
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// The counters of a measured call
+#[derive(Default)]
+struct Counters {
+    /// Bytes allocated and not yet freed during the call; frees of memory
+    /// allocated before it can make it negative
+    current: AtomicIsize,
+    peak: AtomicIsize,
+    allocated: AtomicUsize,
+    allocations: AtomicUsize,
+    /// The call measuring this one, charged too
+    outer: Option<Arc<Counters>>,
+}
+
+thread_local! {
+    /// The counters of the innermost call measured on this thread, kept alive
+    /// by the `Entered` that set them. A raw pointer, so that the allocator
+    /// neither borrows nor drops anything.
+    static MEASURED: Cell<*const Counters> = const { Cell::new(ptr::null()) };
+}
+
+fn record(delta: isize, allocated: usize) {
+    // Counting stops while a thread is being torn down
+    let _ = MEASURED.try_with(|measured| {
+        // SAFETY: the pointer is null or comes from an Arc held by the
+        // Entered in scope on this thread
+        let mut counters = unsafe { measured.get().as_ref() };
+        while let Some(call) = counters {
+            let now = call.current.fetch_add(delta, Ordering::Relaxed) + delta;
+            call.peak.fetch_max(now, Ordering::Relaxed);
+            if allocated > 0 {
+                call.allocated.fetch_add(allocated, Ordering::Relaxed);
+                call.allocations.fetch_add(1, Ordering::Relaxed);
+            }
+            counters = call.outer.as_deref();
+        }
+    });
+}
+
+/// The call being measured on a thread, to charge other threads doing part
+/// of its work to it
+#[derive(Clone)]
+pub(crate) struct Measured(Option<Arc<Counters>>);
+
+impl Measured {
+    /// The innermost call measured on this thread, if any
+    pub(crate) fn current() -> Measured {
+        let current = MEASURED.get();
+        if current.is_null() {
+            return Measured(None);
+        }
+        // SAFETY: as in record, the pointer comes from a live Arc
+        Measured(Some(unsafe {
+            Arc::increment_strong_count(current);
+            Arc::from_raw(current)
+        }))
+    }
+
+    /// Charge this thread's allocations to the call until the guard is dropped
+    pub(crate) fn enter(&self) -> Entered {
+        let previous = MEASURED.replace(self.0.as_ref().map_or(ptr::null(), Arc::as_ptr));
+        Entered {
+            _counters: self.0.clone(),
+            previous,
+        }
+    }
+}
+
+/// Restores the call measured before `Measured::enter` when dropped
+pub(crate) struct Entered {
+    _counters: Option<Arc<Counters>>,
+    previous: *const Counters,
+}
+
+impl Drop for Entered {
+    fn drop(&mut self) {
+        MEASURED.set(self.previous);
+    }
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record(layout.size() as isize, layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record(layout.size() as isize, layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record(-(layout.size() as isize), 0);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            record(
+                new_size as isize - layout.size() as isize,
+                new_size.saturating_sub(layout.size()),
+            );
+        }
+        new
+    }
+}
+
+/// Call a function and report the memory the module allocated meanwhile
+///
+/// Wrap a read or verify call to see how much memory it needs, e.g. to size
+/// containers for validating large videos. Work done on other threads for the
+/// call is counted: the parse of a read given deadline_ms, and the pool of
+/// read_c2pa_batch. Calls that return before their work is done are not:
+/// read_c2pa_from_bytes_async returns a coroutine and read_c2pa_many a
+/// generator, whose reads run later on other threads; measure the awaited or
+/// consumed reads instead. Calls can be nested.
+///
+/// Args:
+///     func: The function to call
+///     *args, **kwargs: Its arguments
+///
+/// Returns:
+///     A tuple of the function's return value and a dict with "peak_bytes" (the
+///     most memory held at once during the call, beyond what was held before),
+///     "allocated_bytes" (the total allocated) and "allocations" (their count)
+///
+/// Raises:
+///     Whatever the function raises
+#[pyfunction]
+#[pyo3(signature = (func, *args, **kwargs))]
+pub fn measure_memory(
+    py: Python,
+    func: &Bound<'_, PyAny>,
+    args: &Bound<'_, PyTuple>,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<(PyObject, PyObject)> {
+    let counters = Arc::new(Counters {
+        outer: Measured::current().0,
+        ..Counters::default()
+    });
+    let entered = Measured(Some(counters.clone())).enter();
+    let result = func.call(args, kwargs);
+    drop(entered);
+
+    let stats = PyDict::new(py);
+    stats.set_item("peak_bytes", counters.peak.load(Ordering::Relaxed))?;
+    stats.set_item("allocated_bytes", counters.allocated.load(Ordering::Relaxed))?;
+    stats.set_item("allocations", counters.allocations.load(Ordering::Relaxed))?;
+    Ok((result?.unbind(), stats.into_any().unbind()))
+}
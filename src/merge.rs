@@ -0,0 +1,185 @@
+//! Unified view of the embedded and sidecar manifest stores of an asset
+//!
+//! An asset can have its manifest store embedded and another one next to it,
+//! as a sidecar file or at the remote URL its XMP points to. Usually one
+//! extends the other, e.g. a sidecar written after an edit, but they can also
+//! disagree. Both are read and merged, and the disagreements are reported
+//! instead of one store being silently preferred.
+
+use pyo3::prelude::*;
+use serde_json::{json, Map, Value};
+
+use crate::c2pa_reader::{read_reader, reader_to_value, value_to_py, verify_value};
+use crate::gil;
+use crate::http::fetch_reported;
+use crate::xmp::scan_provenance;
+
+fn conflict(code: &str, label: Option<&str>, explanation: String) -> Value {
+    let mut conflict = json!({ "code": code, "explanation": explanation });
+    if let Some(label) = label {
+        conflict["label"] = json!(label);
+    }
+    conflict
+}
+
+fn active_label(store: &Value) -> Option<&str> {
+    store.get("active_manifest").and_then(Value::as_str)
+}
+
+fn manifests(store: &Value) -> Option<&Map<String, Value>> {
+    store.get("manifests").and_then(Value::as_object)
+}
+
+fn contains(store: &Value, label: Option<&str>) -> bool {
+    label.is_some_and(|label| manifests(store).is_some_and(|m| m.contains_key(label)))
+}
+
+/// Summary of one source in the merged view
+fn source(store: &Value, url: Option<&str>) -> Value {
+    let mut source = json!({
+        "active_manifest": store.get("active_manifest"),
+        "validation_state": store.get("validation_state"),
+        "validation_results": store.get("validation_results"),
+    });
+    if let Some(url) = url {
+        source["url"] = json!(url);
+    }
+    source
+}
+
+/// Merge the embedded and sidecar manifest stores
+///
+/// The active manifest is the one of the store that contains the other's
+/// active manifest, i.e. the more recent one; when neither does, the stores
+/// conflict and the embedded one wins. Manifests present in both stores with
+/// different contents are reported, as are different validation states for
+/// the same active manifest.
+fn merge_stores(
+    embedded: Option<&Value>,
+    sidecar: Option<&Value>,
+    sidecar_url: Option<&str>,
+) -> Value {
+    let mut conflicts = Vec::new();
+    let (primary, secondary, primary_is_embedded) = match (embedded, sidecar) {
+        (Some(embedded), Some(sidecar)) => {
+            let (embedded_active, sidecar_active) = (active_label(embedded), active_label(sidecar));
+            if embedded_active == sidecar_active {
+                if embedded.get("validation_state") != sidecar.get("validation_state") {
+                    conflicts.push(conflict(
+                        "merge.validationState.mismatch",
+                        embedded_active,
+                        "the embedded and sidecar stores validate differently".to_owned(),
+                    ));
+                }
+                (embedded, Some(sidecar), true)
+            } else if contains(sidecar, embedded_active) {
+                (sidecar, Some(embedded), false)
+            } else if contains(embedded, sidecar_active) {
+                (embedded, Some(sidecar), true)
+            } else {
+                conflicts.push(conflict(
+                    "merge.activeManifest.mismatch",
+                    None,
+                    format!(
+                        "active manifest {} of the embedded store is unrelated to {} of the sidecar store",
+                        embedded_active.unwrap_or("none"),
+                        sidecar_active.unwrap_or("none"),
+                    ),
+                ));
+                (embedded, Some(sidecar), true)
+            }
+        }
+        (Some(embedded), None) => (embedded, None, true),
+        (None, Some(sidecar)) => (sidecar, None, false),
+        (None, None) => return Value::Null,
+    };
+
+    let mut merged = manifests(primary).cloned().unwrap_or_default();
+    for (label, manifest) in secondary.and_then(manifests).into_iter().flatten() {
+        match merged.get(label) {
+            Some(existing) if existing != manifest => conflicts.push(conflict(
+                "merge.manifest.mismatch",
+                Some(label),
+                format!("manifest {label} differs between the embedded and sidecar stores"),
+            )),
+            Some(_) => {}
+            None => {
+                merged.insert(label.clone(), manifest.clone());
+            }
+        }
+    }
+
+    json!({
+        "active_manifest": primary.get("active_manifest"),
+        "manifests": merged,
+        "validation_state": primary.get("validation_state"),
+        "is_embedded": primary_is_embedded,
+        "sources": {
+            "embedded": embedded.map(|store| source(store, None)),
+            "sidecar": sidecar.map(|store| source(store, sidecar_url)),
+        },
+        "conflicts": conflicts,
+    })
+}
+
+/// Read the embedded and sidecar manifest stores of an asset as one
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     sidecar: The sidecar manifest store as bytes (default: None)
+///     fetch_remote: Without a sidecar, download the manifest store the
+///         asset's XMP points to, with the settings of configure_http (default: False)
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     None if neither store exists. Otherwise a dict with the merged
+///     "active_manifest", "manifests" (from both stores by label),
+///     "validation_state" and "is_embedded" (whether the active manifest came
+///     from the embedded store), "remote_url" (the XMP reference), "sources"
+///     ("embedded" and "sidecar": their own "active_manifest",
+///     "validation_state" and "validation_results", or None) and "conflicts"
+///     (a list of dicts with "code", "explanation" and, where it applies, "label").
+///     A downloaded store adds "fetch_attempts", as returned by fetch_url.
+///
+/// Raises:
+///     C2paError: If either store cannot be parsed, or the download fails
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, sidecar=None, fetch_remote=false, allow_threads=None))]
+pub fn read_c2pa_merged(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    sidecar: Option<Vec<u8>>,
+    fetch_remote: bool,
+    allow_threads: Option<bool>,
+) -> PyResult<Option<PyObject>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let embedded = read_reader(py, data, mime_type, allow_threads)?
+        .map(reader_to_value)
+        .transpose()?;
+
+    let remote_url = scan_provenance(data).provenance;
+    let (sidecar, sidecar_url, attempts) = match (sidecar, remote_url.as_deref()) {
+        (Some(sidecar), _) => (Some(sidecar), None, None),
+        (None, Some(url)) if fetch_remote => {
+            let (manifest, attempts) = py.allow_threads(|| fetch_reported(url))?;
+            (Some(manifest), Some(url), Some(attempts))
+        }
+        (None, _) => (None, None, None),
+    };
+    let sidecar = sidecar
+        .map(|manifest| verify_value(py, &manifest, data, mime_type, allow_threads))
+        .transpose()?;
+
+    let mut merged = merge_stores(embedded.as_ref(), sidecar.as_ref(), sidecar_url);
+    if merged.is_null() {
+        return Ok(None);
+    }
+    merged["remote_url"] = json!(remote_url);
+    if let Some(attempts) = attempts {
+        merged["fetch_attempts"] = attempts;
+    }
+    value_to_py(py, &merged).map(Some)
+}
@@ -0,0 +1,106 @@
+//! MIME types from the content of assets
+//!
+//! File extensions and upload metadata are often wrong, and c2pa-rs picks
+//! the parser from the MIME type it is given. The first bytes of an asset
+//! tell its format more reliably.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Bytes of the start of an asset enough to tell its format
+pub(crate) const HEAD_LEN: usize = 256;
+
+/// Brands of the ISO base media file format naming a HEIF image
+const HEIC_BRANDS: [&[u8; 4]; 8] = [
+    b"heic", b"heix", b"heim", b"heis", b"hevc", b"hevx", b"hevm", b"hevs",
+];
+/// Brands of HEIF images that do not tell the codec, refined by the
+/// compatible brands
+const HEIF_BRANDS: [&[u8; 4]; 2] = [b"mif1", b"msf1"];
+
+/// The MIME type of an ISO base media file, from the brands of its ftyp box
+fn bmff_type(data: &[u8]) -> Option<&'static str> {
+    let size = u32::from_be_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    let major = data.get(8..12)?;
+    let compatible = data.get(16..size.clamp(16, data.len())).unwrap_or_default();
+    let has = |brand: &[u8; 4]| compatible.chunks_exact(4).any(|b| b == brand);
+    let mime_type = match major {
+        b"avif" | b"avis" => "image/avif",
+        brand if HEIC_BRANDS.iter().any(|b| *b == brand) => "image/heic",
+        brand if HEIF_BRANDS.iter().any(|b| *b == brand) => {
+            if has(b"avif") || has(b"avis") {
+                "image/avif"
+            } else {
+                "image/heic"
+            }
+        }
+        b"qt  " => "video/quicktime",
+        b"M4A " | b"M4B " => "audio/mp4",
+        _ => "video/mp4",
+    };
+    Some(mime_type)
+}
+
+/// Whether `data` starts with an MPEG audio frame header
+fn is_mpeg_frame(data: &[u8]) -> bool {
+    // 11 sync bits, then a version other than the reserved 01 and a layer
+    // other than the reserved 00
+    matches!(data, [0xff, second, ..]
+        if second & 0xe0 == 0xe0 && second & 0x18 != 0x08 && second & 0x06 != 0)
+}
+
+/// The MIME type of `data` from its magic bytes, if it is a format c2pa-rs
+/// reads
+pub(crate) fn sniff(data: &[u8]) -> Option<&'static str> {
+    let mime_type = match data {
+        [0xff, 0xd8, 0xff, ..] => "image/jpeg",
+        [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', ..] => "image/png",
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => "image/gif",
+        [b'I', b'I', b'*', 0, ..] | [b'M', b'M', 0, b'*', ..] => "image/tiff",
+        [b'%', b'P', b'D', b'F', b'-', ..] => "application/pdf",
+        [b'R', b'I', b'F', b'F', _, _, _, _, rest @ ..] => match rest.get(..4)? {
+            b"WEBP" => "image/webp",
+            b"WAVE" => "audio/wav",
+            b"AVI " => "video/avi",
+            _ => return None,
+        },
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => return bmff_type(data),
+        [b'I', b'D', b'3', ..] => "audio/mpeg",
+        data if is_mpeg_frame(data) => "audio/mpeg",
+        _ => return None,
+    };
+    Some(mime_type)
+}
+
+/// `mime_type`, else the MIME type of the asset given as `chunks`
+pub(crate) fn resolve(mime_type: Option<&str>, chunks: &[&[u8]]) -> PyResult<String> {
+    if let Some(mime_type) = mime_type {
+        return Ok(mime_type.to_owned());
+    }
+    let head: Vec<u8> = chunks
+        .iter()
+        .flat_map(|chunk| chunk.iter())
+        .take(HEAD_LEN)
+        .copied()
+        .collect();
+    sniff(&head).map(str::to_owned).ok_or_else(|| {
+        PyValueError::new_err("Cannot detect the MIME type of the data, pass mime_type")
+    })
+}
+
+/// Detect the MIME type of an asset from its first bytes
+///
+/// Recognizes JPEG, PNG, GIF, WebP, TIFF (including DNG), HEIC, AVIF, MP4,
+/// M4A, QuickTime, PDF, WAV, AVI and MP3.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object), or at least its first
+///         few dozen bytes
+///
+/// Returns:
+///     The MIME type, e.g. "image/jpeg", or None if the format is not recognized
+#[pyfunction]
+#[pyo3(signature = (data))]
+pub fn detect_mime_from_bytes(data: &[u8]) -> Option<&'static str> {
+    sniff(data)
+}
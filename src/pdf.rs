@@ -0,0 +1,193 @@
+//! Reading manifests from images embedded in PDF documents
+//!
+//! A PDF can carry its own manifest store, read like any other asset, but the
+//! photos placed in it keep the manifests they had before they were embedded.
+//! JPEG and JPEG 2000 images are stored in the document as complete files, so
+//! their C2PA data can be read back directly.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use c2pa::jumbf_io::get_supported_types;
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::Value;
+
+use crate::c2pa_reader::{read_contained, set_contained_result};
+use crate::errors::C2paError;
+use crate::gil;
+
+/// Outcome of reading one image XObject
+struct Image {
+    id: ObjectId,
+    pages: Vec<u32>,
+    mime_type: Option<&'static str>,
+    size: usize,
+    result: Result<Option<Value>, String>,
+}
+
+/// Media type of an image stream stored as a complete file
+fn image_mime_type(filter: &str) -> Option<&'static str> {
+    match filter {
+        "DCTDecode" => Some("image/jpeg"),
+        "JPXDecode" => Some("image/jp2"),
+        _ => None,
+    }
+}
+
+fn is_image(stream: &Stream) -> bool {
+    stream
+        .dict
+        .get(b"Subtype")
+        .and_then(Object::as_name_str)
+        .ok()
+        == Some("Image")
+}
+
+/// Collect the image XObjects used by `resources`, including those drawn by
+/// the form XObjects it uses
+fn collect_images(
+    doc: &Document,
+    resources: &Dictionary,
+    images: &mut BTreeSet<ObjectId>,
+    visited: &mut BTreeSet<ObjectId>,
+) {
+    let Ok((_, xobjects)) = resources
+        .get(b"XObject")
+        .and_then(|xobjects| doc.dereference(xobjects))
+    else {
+        return;
+    };
+    let Ok(xobjects) = xobjects.as_dict() else {
+        return;
+    };
+    for (_, xobject) in xobjects.iter() {
+        let Ok(id) = xobject.as_reference() else {
+            continue;
+        };
+        if !visited.insert(id) {
+            continue;
+        }
+        let Ok(object) = doc.get_object(id) else {
+            continue;
+        };
+        let Ok(stream) = object.as_stream() else {
+            continue;
+        };
+        if is_image(stream) {
+            images.insert(id);
+        } else if let Ok((_, Object::Dictionary(form_resources))) = stream
+            .dict
+            .get(b"Resources")
+            .and_then(|resources| doc.dereference(resources))
+        {
+            collect_images(doc, form_resources, images, visited);
+        }
+    }
+}
+
+fn read_image(id: ObjectId, stream: &Stream, pages: Vec<u32>) -> Image {
+    let filters = stream.filters().unwrap_or_default();
+    let mime_type = match filters.as_slice() {
+        [filter] => image_mime_type(filter),
+        _ => None,
+    };
+
+    let result = match mime_type {
+        Some(mime_type) if get_supported_types().iter().any(|t| t == mime_type) => {
+            read_contained(&stream.content, mime_type)
+        }
+        // Pixel data re-encoded by the PDF writer carries no manifest
+        _ => Ok(None),
+    };
+    Image {
+        id,
+        pages,
+        mime_type,
+        size: stream.content.len(),
+        result,
+    }
+}
+
+fn scan_pdf(data: &[u8]) -> Result<Vec<Image>, String> {
+    let doc = Document::load_mem(data).map_err(|e| format!("Error reading PDF: {e}"))?;
+
+    let mut pages: BTreeMap<ObjectId, Vec<u32>> = BTreeMap::new();
+    for (number, page_id) in doc.get_pages() {
+        let (inline, inherited) = doc.get_page_resources(page_id);
+        let mut images = BTreeSet::new();
+        let mut visited = BTreeSet::new();
+        let resources = inline.into_iter().chain(
+            inherited
+                .into_iter()
+                .filter_map(|id| doc.get_dictionary(id).ok()),
+        );
+        for resources in resources {
+            collect_images(&doc, resources, &mut images, &mut visited);
+        }
+        for id in images {
+            pages.entry(id).or_default().push(number);
+        }
+    }
+
+    // Images only reachable from annotations or not used at all are still
+    // reported, without pages
+    Ok(doc
+        .objects
+        .iter()
+        .filter_map(|(&id, object)| object.as_stream().ok().map(|stream| (id, stream)))
+        .filter(|(_, stream)| is_image(stream))
+        .map(|(id, stream)| read_image(id, stream, pages.remove(&id).unwrap_or_default()))
+        .collect())
+}
+
+/// Read C2PA metadata from the images embedded in a PDF
+///
+/// This looks at the image objects of the document; use read_c2pa_from_bytes
+/// with "application/pdf" for the document's own manifest. Only JPEG and
+/// JPEG 2000 images keep their original bytes in a PDF, other images are
+/// listed with no result.
+///
+/// Args:
+///     data: Binary data of the PDF (bytes-like object)
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A list with one dict per image object, in object number order, with
+///     "object" (the object number and generation, e.g. "12 0"), "pages" (the
+///     1-based numbers of the pages that draw it, empty if none do),
+///     "mime_type" (None if the image is not stored as a JPEG or JPEG 2000
+///     file), "size" (stored size in bytes), "result" (the C2PA data as
+///     returned by read_c2pa_from_bytes, or None) and "error" (a message, or None)
+///
+/// Raises:
+///     C2paError: If the data is not a readable PDF
+#[pyfunction]
+#[pyo3(signature = (data, allow_threads=None))]
+pub fn read_c2pa_from_pdf_images(
+    py: Python,
+    data: &[u8],
+    allow_threads: Option<bool>,
+) -> PyResult<Vec<PyObject>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let images = if allow_threads {
+        py.allow_threads(|| scan_pdf(data))
+    } else {
+        scan_pdf(data)
+    }
+    .map_err(C2paError::new_err)?;
+
+    images
+        .into_iter()
+        .map(|image| {
+            let entry = PyDict::new(py);
+            entry.set_item("object", format!("{} {}", image.id.0, image.id.1))?;
+            entry.set_item("pages", image.pages)?;
+            entry.set_item("mime_type", image.mime_type)?;
+            entry.set_item("size", image.size)?;
+            set_contained_result(py, &entry, image.result)?;
+            Ok(entry.into_any().unbind())
+        })
+        .collect()
+}
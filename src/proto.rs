@@ -0,0 +1,382 @@
+//! Protobuf encoding of manifest results
+//!
+//! The message types below mirror `fast_c2pa_python/fast_c2pa.proto` and must
+//! be kept in sync with it. Results are converted from the same JSON value that
+//! backs the dict output, so every output format describes the same data.
+
+use std::collections::BTreeMap;
+
+use prost::Message;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use serde_json::{Map, Value};
+
+use crate::c2pa_reader::py_to_value;
+use crate::serialize::sort_keys;
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ManifestStore {
+    #[prost(string, tag = "1")]
+    pub active_manifest: String,
+    #[prost(btree_map = "string, message", tag = "2")]
+    pub manifests: BTreeMap<String, Manifest>,
+    #[prost(message, repeated, tag = "3")]
+    pub validation_status: Vec<ValidationStatus>,
+    #[prost(message, optional, tag = "4")]
+    pub validation_results: Option<ValidationResults>,
+    #[prost(string, tag = "5")]
+    pub validation_state: String,
+    #[prost(message, repeated, tag = "6")]
+    pub warnings: Vec<ValidationStatus>,
+    #[prost(bool, tag = "7")]
+    pub is_embedded: bool,
+    #[prost(string, tag = "8")]
+    pub remote_url: String,
+    #[prost(string, tag = "15")]
+    pub extra_json: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Manifest {
+    #[prost(string, tag = "1")]
+    pub label: String,
+    #[prost(string, tag = "2")]
+    pub claim_generator: String,
+    #[prost(message, repeated, tag = "3")]
+    pub claim_generator_info: Vec<ClaimGeneratorInfo>,
+    #[prost(string, tag = "4")]
+    pub title: String,
+    #[prost(string, tag = "5")]
+    pub format: String,
+    #[prost(string, tag = "6")]
+    pub instance_id: String,
+    #[prost(message, optional, tag = "7")]
+    pub thumbnail: Option<ResourceRef>,
+    #[prost(message, repeated, tag = "8")]
+    pub ingredients: Vec<Ingredient>,
+    #[prost(message, repeated, tag = "9")]
+    pub assertions: Vec<Assertion>,
+    #[prost(message, optional, tag = "10")]
+    pub signature_info: Option<SignatureInfo>,
+    #[prost(string, repeated, tag = "11")]
+    pub redactions: Vec<String>,
+    #[prost(string, tag = "15")]
+    pub extra_json: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ClaimGeneratorInfo {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub version: String,
+    #[prost(string, tag = "15")]
+    pub extra_json: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ResourceRef {
+    #[prost(string, tag = "1")]
+    pub format: String,
+    #[prost(string, tag = "2")]
+    pub identifier: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Ingredient {
+    #[prost(string, tag = "1")]
+    pub title: String,
+    #[prost(string, tag = "2")]
+    pub format: String,
+    #[prost(string, tag = "3")]
+    pub instance_id: String,
+    #[prost(string, tag = "4")]
+    pub document_id: String,
+    #[prost(string, tag = "5")]
+    pub relationship: String,
+    #[prost(string, tag = "6")]
+    pub active_manifest: String,
+    #[prost(string, tag = "7")]
+    pub label: String,
+    #[prost(message, optional, tag = "8")]
+    pub thumbnail: Option<ResourceRef>,
+    #[prost(message, optional, tag = "9")]
+    pub manifest_data: Option<ResourceRef>,
+    #[prost(message, repeated, tag = "10")]
+    pub validation_status: Vec<ValidationStatus>,
+    #[prost(message, optional, tag = "11")]
+    pub validation_results: Option<ValidationResults>,
+    #[prost(string, tag = "15")]
+    pub extra_json: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Assertion {
+    #[prost(string, tag = "1")]
+    pub label: String,
+    #[prost(string, tag = "2")]
+    pub data_json: String,
+    #[prost(uint32, tag = "3")]
+    pub instance: u32,
+    #[prost(string, tag = "4")]
+    pub kind: String,
+    #[prost(string, tag = "15")]
+    pub extra_json: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct SignatureInfo {
+    #[prost(string, tag = "1")]
+    pub alg: String,
+    #[prost(string, tag = "2")]
+    pub issuer: String,
+    #[prost(string, tag = "3")]
+    pub cert_serial_number: String,
+    #[prost(string, tag = "4")]
+    pub time: String,
+    #[prost(bool, optional, tag = "5")]
+    pub revocation_status: Option<bool>,
+    #[prost(string, tag = "15")]
+    pub extra_json: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ValidationStatus {
+    #[prost(string, tag = "1")]
+    pub code: String,
+    #[prost(string, tag = "2")]
+    pub url: String,
+    #[prost(string, tag = "3")]
+    pub explanation: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct StatusCodes {
+    #[prost(message, repeated, tag = "1")]
+    pub success: Vec<ValidationStatus>,
+    #[prost(message, repeated, tag = "2")]
+    pub informational: Vec<ValidationStatus>,
+    #[prost(message, repeated, tag = "3")]
+    pub failure: Vec<ValidationStatus>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct IngredientDelta {
+    #[prost(string, tag = "1")]
+    pub ingredient_assertion_uri: String,
+    #[prost(message, optional, tag = "2")]
+    pub validation_deltas: Option<StatusCodes>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ValidationResults {
+    #[prost(message, optional, tag = "1")]
+    pub active_manifest: Option<StatusCodes>,
+    #[prost(message, repeated, tag = "2")]
+    pub ingredient_deltas: Vec<IngredientDelta>,
+}
+
+/// Fields of a JSON object, consumed as they are mapped to message fields
+///
+/// Whatever is left over is kept as `extra_json` so data the schema does not
+/// model yet is not dropped.
+struct Fields(Map<String, Value>);
+
+impl Fields {
+    fn new(value: &Value) -> Self {
+        Fields(value.as_object().cloned().unwrap_or_default())
+    }
+
+    fn take(&mut self, key: &str) -> Option<Value> {
+        self.0.remove(key).filter(|v| !v.is_null())
+    }
+
+    fn string(&mut self, key: &str) -> String {
+        match self.take(key) {
+            Some(Value::String(s)) => s,
+            Some(other) => other.to_string(),
+            None => String::new(),
+        }
+    }
+
+    fn list<T>(&mut self, key: &str, convert: impl Fn(&Value) -> T) -> Vec<T> {
+        match self.take(key) {
+            Some(Value::Array(items)) => items.iter().map(convert).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn message<T>(&mut self, key: &str, convert: impl Fn(&Value) -> T) -> Option<T> {
+        self.take(key).map(|v| convert(&v))
+    }
+
+    fn extra_json(self) -> String {
+        if self.0.is_empty() {
+            String::new()
+        } else {
+            Value::Object(self.0).to_string()
+        }
+    }
+}
+
+fn resource_ref(value: &Value) -> ResourceRef {
+    let mut f = Fields::new(value);
+    ResourceRef {
+        format: f.string("format"),
+        identifier: f.string("identifier"),
+    }
+}
+
+fn validation_status(value: &Value) -> ValidationStatus {
+    let mut f = Fields::new(value);
+    ValidationStatus {
+        code: f.string("code"),
+        url: f.string("url"),
+        explanation: f.string("explanation"),
+    }
+}
+
+fn status_codes(value: &Value) -> StatusCodes {
+    let mut f = Fields::new(value);
+    StatusCodes {
+        success: f.list("success", validation_status),
+        informational: f.list("informational", validation_status),
+        failure: f.list("failure", validation_status),
+    }
+}
+
+fn validation_results(value: &Value) -> ValidationResults {
+    let mut f = Fields::new(value);
+    ValidationResults {
+        active_manifest: f.message("activeManifest", status_codes),
+        ingredient_deltas: f.list("ingredientDeltas", |delta| {
+            let mut d = Fields::new(delta);
+            IngredientDelta {
+                ingredient_assertion_uri: d.string("ingredientAssertionURI"),
+                validation_deltas: d.message("validationDeltas", status_codes),
+            }
+        }),
+    }
+}
+
+fn claim_generator_info(value: &Value) -> ClaimGeneratorInfo {
+    let mut f = Fields::new(value);
+    ClaimGeneratorInfo {
+        name: f.string("name"),
+        version: f.string("version"),
+        extra_json: f.extra_json(),
+    }
+}
+
+fn ingredient(value: &Value) -> Ingredient {
+    let mut f = Fields::new(value);
+    Ingredient {
+        title: f.string("title"),
+        format: f.string("format"),
+        instance_id: f.string("instance_id"),
+        document_id: f.string("document_id"),
+        relationship: f.string("relationship"),
+        active_manifest: f.string("active_manifest"),
+        label: f.string("label"),
+        thumbnail: f.message("thumbnail", resource_ref),
+        manifest_data: f.message("manifest_data", resource_ref),
+        validation_status: f.list("validation_status", validation_status),
+        validation_results: f.message("validation_results", validation_results),
+        extra_json: f.extra_json(),
+    }
+}
+
+fn assertion(value: &Value) -> Assertion {
+    let mut f = Fields::new(value);
+    Assertion {
+        label: f.string("label"),
+        data_json: f.take("data").map(|d| d.to_string()).unwrap_or_default(),
+        instance: f
+            .take("instance")
+            .and_then(|i| i.as_u64())
+            .and_then(|i| u32::try_from(i).ok())
+            .unwrap_or_default(),
+        kind: f.string("kind"),
+        extra_json: f.extra_json(),
+    }
+}
+
+fn signature_info(value: &Value) -> SignatureInfo {
+    let mut f = Fields::new(value);
+    SignatureInfo {
+        alg: f.string("alg"),
+        issuer: f.string("issuer"),
+        cert_serial_number: f.string("cert_serial_number"),
+        time: f.string("time"),
+        revocation_status: f.take("revocation_status").and_then(|v| v.as_bool()),
+        extra_json: f.extra_json(),
+    }
+}
+
+fn manifest(value: &Value) -> Manifest {
+    let mut f = Fields::new(value);
+    Manifest {
+        label: f.string("label"),
+        claim_generator: f.string("claim_generator"),
+        claim_generator_info: f.list("claim_generator_info", claim_generator_info),
+        title: f.string("title"),
+        format: f.string("format"),
+        instance_id: f.string("instance_id"),
+        thumbnail: f.message("thumbnail", resource_ref),
+        ingredients: f.list("ingredients", ingredient),
+        assertions: f.list("assertions", assertion),
+        signature_info: f.message("signature_info", signature_info),
+        redactions: f.list("redactions", |r| r.as_str().unwrap_or_default().to_owned()),
+        extra_json: f.extra_json(),
+    }
+}
+
+fn manifest_store(value: &Value) -> ManifestStore {
+    let mut f = Fields::new(value);
+    let manifests = match f.take("manifests") {
+        Some(Value::Object(manifests)) => manifests
+            .iter()
+            .map(|(label, m)| (label.clone(), manifest(m)))
+            .collect(),
+        _ => BTreeMap::new(),
+    };
+    ManifestStore {
+        active_manifest: f.string("active_manifest"),
+        manifests,
+        validation_status: f.list("validation_status", validation_status),
+        validation_results: f.message("validation_results", validation_results),
+        validation_state: f.string("validation_state"),
+        warnings: f.list("warnings", validation_status),
+        is_embedded: f.take("is_embedded").and_then(|v| v.as_bool()).unwrap_or_default(),
+        remote_url: f.string("remote_url"),
+        extra_json: f.extra_json(),
+    }
+}
+
+/// Encode a manifest store result as a `fast_c2pa.v1.ManifestStore` message
+///
+/// Keys inside the embedded JSON strings are sorted, so the same manifest store
+/// always encodes to the same bytes.
+pub fn encode_manifest_store(value: &Value) -> Vec<u8> {
+    manifest_store(&sort_keys(value)).encode_to_vec()
+}
+
+/// Encode a result dict as a protobuf `fast_c2pa.v1.ManifestStore` message
+///
+/// The schema ships with the package as `fast_c2pa.proto`; generate stubs
+/// from it with `protoc` to decode the message in other services.
+///
+/// Args:
+///     result: Dict returned by read_c2pa_from_bytes
+///
+/// Returns:
+///     The serialized message as bytes
+///
+/// Raises:
+///     TypeError: If the result contains values that are not JSON serializable
+#[pyfunction]
+pub fn to_protobuf(py: Python, result: &Bound<'_, PyAny>) -> PyResult<Py<PyBytes>> {
+    let value = py_to_value(py, result)?;
+    let encoded = py.allow_threads(|| encode_manifest_store(&value));
+    Ok(PyBytes::new(py, &encoded).unbind())
+}
@@ -0,0 +1,447 @@
+//! Parsed manifest stores that can be shared between threads
+//!
+//! The read functions return a fresh dict per call, and a dict handed to
+//! several threads can be mutated under them. A `Reader` holds the parsed
+//! result immutably instead: it is frozen, so any number of Python threads can
+//! query one instance at the same time, each getting its own copy of the part
+//! it asks for. Callers needing several views of one asset parse it once; only
+//! the part asked for is converted to Python objects, and the raw manifest
+//! store is only extracted again if its thumbnail is asked for.
+//!
+//! Readers are stored as JSON with `to_json` and rehydrated with `from_json`.
+//! The JSON holds the result with the version of its layout and of the
+//! libraries that produced it, so later versions of the module can still
+//! read what earlier ones stored.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use base64::Engine;
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyIterator, PyList, PyString};
+use serde_json::{json, Map, Value};
+
+use crate::buffer::Buffer;
+use crate::c2pa_reader::{embedded_store, file_mime_type, map_file, read_value, value_to_py};
+use crate::errors::ManifestNotFoundError;
+use crate::gil;
+use crate::jumbf::active_claim_thumbnail;
+use crate::mime;
+use crate::resources::Budget;
+
+/// The "format" of the JSON of `Reader.to_json`
+const JSON_FORMAT: &str = "fast_c2pa.reader";
+
+/// Version of the JSON of `Reader.to_json`, raised when its layout changes
+const JSON_VERSION: u64 = 1;
+
+/// Where the asset of a `Reader` was read from, to extract its manifest store
+/// again when needed
+enum Source {
+    Data(Py<PyAny>),
+    File(PathBuf),
+    /// Rehydrated from JSON, with the manifest store only if it was stored
+    Json,
+}
+
+/// A parsed manifest store, safe to share between threads
+///
+/// Create one with `Reader.from_bytes`, or `C2paReader(source)` for a path or
+/// bytes. Every method returns new Python objects, so changing what one
+/// caller gets does not affect the others.
+#[pyclass(frozen, module = "fast_c2pa_core")]
+pub struct Reader {
+    value: Value,
+    mime_type: String,
+    source: Source,
+    store: OnceLock<Option<Vec<u8>>>,
+}
+
+impl Reader {
+    fn manifest_map(&self) -> Option<&Map<String, Value>> {
+        self.value.get("manifests").and_then(Value::as_object)
+    }
+
+    /// The manifest labelled `label`, None for labels that are not strings,
+    /// as a dict lookup would miss them
+    fn manifest_for_key(&self, label: &Bound<'_, PyAny>) -> Option<&Value> {
+        let label = label.downcast::<PyString>().ok()?.to_str().ok()?;
+        self.manifest_map()?.get(label)
+    }
+
+    fn manifest_value(&self, label: Option<&str>) -> Option<&Value> {
+        let label = label.or(self.active_label())?;
+        self.manifest_map().and_then(|m| m.get(label))
+    }
+
+    /// The manifest store embedded in the asset, extracted on first use
+    fn store(&self, py: Python) -> PyResult<Option<&[u8]>> {
+        if let Some(store) = self.store.get() {
+            return Ok(store.as_deref());
+        }
+        let extract = |data: &[u8]| {
+            if gil::release(None, data.len()) {
+                py.allow_threads(|| embedded_store(data, &self.mime_type))
+            } else {
+                embedded_store(data, &self.mime_type)
+            }
+        };
+        let store = match &self.source {
+            Source::Data(data) => extract(Buffer::new(data.bind(py), "data")?.as_bytes()),
+            Source::File(path) => extract(&map_file(path)?),
+            Source::Json => None,
+        };
+        Ok(self.store.get_or_init(|| store).as_deref())
+    }
+}
+
+#[pymethods]
+impl Reader {
+    /// Parse the manifest store of an asset
+    ///
+    /// Args:
+    ///     data: Binary data of the file (bytes-like object)
+    ///     mime_type: MIME type of the data (e.g., "image/jpeg")
+    ///     allow_threads: Whether to release the Python GIL during processing (default: None,
+    ///         only for payloads of at least get_gil_release_threshold() bytes)
+    ///
+    /// Returns:
+    ///     A Reader, or None if no C2PA metadata is present
+    ///
+    /// Raises:
+    ///     C2paError: If there is an error reading or parsing the C2PA data
+    #[staticmethod]
+    #[pyo3(signature = (data, mime_type, allow_threads=None))]
+    fn from_bytes(
+        py: Python,
+        data: &Bound<'_, PyAny>,
+        mime_type: &str,
+        allow_threads: Option<bool>,
+    ) -> PyResult<Option<Reader>> {
+        let buffer = Buffer::new(data, "data")?;
+        let bytes = buffer.as_bytes();
+        let allow_threads = gil::release(allow_threads, bytes.len());
+        Ok(read_value(py, bytes, mime_type, allow_threads)?.map(|value| Reader {
+            value,
+            mime_type: mime_type.to_owned(),
+            source: Source::Data(data.clone().unbind()),
+            store: OnceLock::new(),
+        }))
+    }
+
+    /// Parse the manifest store of an asset given as a path or as bytes
+    ///
+    /// Available as C2paReader. The asset is parsed once; the methods then
+    /// convert only the part they return.
+    ///
+    /// Args:
+    ///     source: Path of the file (str or os.PathLike), memory-mapped as by
+    ///         read_c2pa_from_file, or its binary data (bytes-like object)
+    ///     mime_type: MIME type of the asset (default: None, detected from its
+    ///         content, else from the extension of the path)
+    ///     allow_threads: Whether to release the Python GIL during processing (default: None,
+    ///         only for payloads of at least get_gil_release_threshold() bytes)
+    ///
+    /// Raises:
+    ///     ManifestNotFoundError: If no C2PA metadata is present
+    ///     OSError: If the file cannot be opened or mapped
+    ///     TypeError: If source is neither a path nor bytes-like
+    ///     ValueError: If mime_type is None and the format of the data is not recognized
+    ///     C2paError: If there is an error reading or parsing the C2PA data
+    #[new]
+    #[pyo3(signature = (source, mime_type=None, allow_threads=None))]
+    fn new(
+        py: Python,
+        source: &Bound<'_, PyAny>,
+        mime_type: Option<&str>,
+        allow_threads: Option<bool>,
+    ) -> PyResult<Reader> {
+        let reader = if source.is_instance_of::<PyString>() || source.hasattr("__fspath__")? {
+            let path: PathBuf = source.extract()?;
+            let map = map_file(&path)?;
+            let mime_type = match mime_type {
+                Some(mime_type) => mime_type.to_owned(),
+                None => file_mime_type(&map, &path),
+            };
+            let allow_threads = gil::release(allow_threads, map.len());
+            read_value(py, &map, &mime_type, allow_threads)?.map(|value| Reader {
+                value,
+                mime_type,
+                source: Source::File(path),
+                store: OnceLock::new(),
+            })
+        } else {
+            let buffer = Buffer::new(source, "source")?;
+            let mime_type = mime::resolve(mime_type, &[buffer.as_bytes()])?;
+            Self::from_bytes(py, source, &mime_type, allow_threads)?
+        };
+        reader.ok_or_else(|| ManifestNotFoundError::new_err("No C2PA data found in the asset"))
+    }
+
+    /// Rehydrate a Reader stored with to_json
+    ///
+    /// JSON written by any earlier version of the module is accepted, as is a
+    /// bare result of read_c2pa_from_bytes or read_c2pa_json_from_bytes. The
+    /// Reader has no asset to extract the manifest store from, so
+    /// thumbnail_bytes returns None unless the store was included.
+    ///
+    /// Args:
+    ///     json: The JSON, as a str or bytes
+    ///
+    /// Returns:
+    ///     The Reader, with the same result as the one stored
+    ///
+    /// Raises:
+    ///     ValueError: If json is not the JSON of a Reader, or was written by
+    ///         a newer version of the module in a layout this one cannot read
+    #[staticmethod]
+    fn from_json(json: &Bound<'_, PyAny>) -> PyResult<Reader> {
+        let text = match json.downcast::<PyBytes>() {
+            Ok(bytes) => std::str::from_utf8(bytes.as_bytes())
+                .map_err(|e| PyValueError::new_err(format!("Invalid Reader JSON: {e}")))?,
+            Err(_) => json.downcast::<PyString>()?.to_str()?,
+        };
+        let mut stored: Value = serde_json::from_str(text)
+            .map_err(|e| PyValueError::new_err(format!("Invalid Reader JSON: {e}")))?;
+        if stored.get("format").is_none() && stored.get("manifests").is_some() {
+            return Ok(Reader {
+                value: stored,
+                mime_type: String::new(),
+                source: Source::Json,
+                store: OnceLock::new(),
+            });
+        }
+        if stored["format"] != JSON_FORMAT {
+            return Err(PyValueError::new_err(
+                "Invalid Reader JSON: not written by Reader.to_json",
+            ));
+        }
+        let version = &stored["format_version"];
+        let readable = version
+            .as_u64()
+            .is_some_and(|version| version <= JSON_VERSION);
+        if !readable {
+            return Err(PyValueError::new_err(format!(
+                "Reader JSON of format_version {version} needs a newer fast_c2pa_python, \
+                 this one reads up to {JSON_VERSION}"
+            )));
+        }
+        let store = match stored["manifest_store"].as_str() {
+            Some(store) => Some(
+                base64::engine::general_purpose::STANDARD
+                    .decode(store)
+                    .map_err(|e| PyValueError::new_err(format!("Invalid Reader JSON: {e}")))?,
+            ),
+            None => None,
+        };
+        Ok(Reader {
+            value: stored["result"].take(),
+            mime_type: stored["mime_type"].as_str().unwrap_or_default().to_owned(),
+            source: Source::Json,
+            store: store.map_or_else(OnceLock::new, |store| OnceLock::from(Some(store))),
+        })
+    }
+
+    /// Label of the active manifest
+    #[getter]
+    fn active_label(&self) -> Option<&str> {
+        self.value.get("active_manifest").and_then(Value::as_str)
+    }
+
+    /// Validation state of the manifest store, e.g. "Valid"
+    #[getter]
+    fn validation_state(&self) -> Option<&str> {
+        self.value.get("validation_state").and_then(Value::as_str)
+    }
+
+    /// Labels of all manifests in the store
+    fn labels(&self) -> Vec<&str> {
+        self.manifest_map()
+            .into_iter()
+            .flat_map(|m| m.keys().map(String::as_str))
+            .collect()
+    }
+
+    /// Get one manifest as a dict
+    ///
+    /// Args:
+    ///     label: Label of the manifest (default: None, the active manifest)
+    ///
+    /// Returns:
+    ///     The manifest, or None if the store has no manifest with that label
+    #[pyo3(signature = (label=None))]
+    fn manifest(&self, py: Python, label: Option<&str>) -> PyResult<Option<PyObject>> {
+        self.manifest_value(label)
+            .map(|manifest| value_to_py(py, manifest))
+            .transpose()
+    }
+
+    /// Get the active manifest as a dict, or None if the store has none
+    fn active_manifest(&self, py: Python) -> PyResult<Option<PyObject>> {
+        self.manifest(py, None)
+    }
+
+    /// Get all manifests as a dict of label to manifest
+    fn manifests(&self, py: Python) -> PyResult<PyObject> {
+        match self.value.get("manifests") {
+            Some(manifests) => value_to_py(py, manifests),
+            None => Ok(PyDict::new(py).into_any().unbind()),
+        }
+    }
+
+    /// Get the validation status codes of the manifest store, as a list of
+    /// dicts with "code", "url" and "explanation"; empty when nothing failed
+    fn validation_status(&self, py: Python) -> PyResult<PyObject> {
+        match self.value.get("validation_status") {
+            Some(status) if !status.is_null() => value_to_py(py, status),
+            _ => Ok(PyList::empty(py).into_any().unbind()),
+        }
+    }
+
+    /// Get the data of an assertion
+    ///
+    /// Args:
+    ///     label: Label of the assertion (e.g., "c2pa.actions.v2")
+    ///     manifest_label: Label of the manifest (default: None, the active manifest)
+    ///
+    /// Returns:
+    ///     The data of the first assertion with that label, or None if the
+    ///     manifest has none
+    #[pyo3(signature = (label, manifest_label=None))]
+    fn get_assertion(
+        &self,
+        py: Python,
+        label: &str,
+        manifest_label: Option<&str>,
+    ) -> PyResult<Option<PyObject>> {
+        self.manifest_value(manifest_label)
+            .and_then(|manifest| manifest.get("assertions"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .find(|assertion| assertion.get("label").and_then(Value::as_str) == Some(label))
+            .map(|assertion| value_to_py(py, &assertion["data"]))
+            .transpose()
+    }
+
+    /// Get the claim thumbnail of the active manifest as image bytes
+    ///
+    /// Its MIME type is the "format" of the manifest's "thumbnail". The
+    /// manifest store is extracted from the asset again on the first call, so
+    /// the file or buffer the reader was created from must not have changed.
+    ///
+    /// Returns:
+    ///     The thumbnail, or None if the active manifest has no claim thumbnail
+    ///     stored in the asset, or the reader was rehydrated from JSON without
+    ///     the manifest store
+    ///
+    /// Raises:
+    ///     ValueError: If the thumbnail is over the limits set by set_resource_limits
+    ///     OSError: If the file the reader was created from cannot be read
+    fn thumbnail_bytes(&self, py: Python) -> PyResult<Option<Py<PyBytes>>> {
+        let Some((_, bytes)) = self.store(py)?.and_then(active_claim_thumbnail) else {
+            return Ok(None);
+        };
+        if let Some(limit) = Budget::new().admit(bytes.len()) {
+            return Err(PyValueError::new_err(format!(
+                "thumbnail of {} bytes is over the {limit} resource limit",
+                bytes.len()
+            )));
+        }
+        Ok(Some(PyBytes::new(py, bytes).unbind()))
+    }
+
+    /// Get the whole result, as read_c2pa_from_bytes returns it
+    fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        value_to_py(py, &self.value)
+    }
+
+    /// Get the Reader as a JSON string, to store and rehydrate with from_json
+    ///
+    /// The JSON is an object with "format" ("fast_c2pa.reader"),
+    /// "format_version", "library_version" and "c2pa_version" (the versions of
+    /// this module and of c2pa-rs that read the asset), "mime_type", "result"
+    /// (the whole result, as read_c2pa_from_bytes returns it) and
+    /// "manifest_store" (in base64, or None).
+    ///
+    /// Args:
+    ///     include_store: Include the manifest store, so thumbnail_bytes works
+    ///         on the rehydrated Reader (default: False). It is extracted from
+    ///         the asset again.
+    ///
+    /// Raises:
+    ///     OSError: If include_store is True and the file the reader was
+    ///         created from cannot be read
+    #[pyo3(signature = (include_store=false))]
+    fn to_json(&self, py: Python, include_store: bool) -> PyResult<String> {
+        let store = if include_store {
+            self.store(py)?
+                .map(|store| base64::engine::general_purpose::STANDARD.encode(store))
+        } else {
+            None
+        };
+        let stored = json!({
+            "format": JSON_FORMAT,
+            "format_version": JSON_VERSION,
+            "library_version": env!("CARGO_PKG_VERSION"),
+            "c2pa_version": c2pa::VERSION,
+            "mime_type": self.mime_type,
+            "result": self.value,
+            "manifest_store": store,
+        });
+        Ok(stored.to_string())
+    }
+
+    /// Labels of all manifests in the store, as dict.keys() of manifests() would
+    /// give them; iterating over the Reader gives the same labels
+    fn keys(&self) -> Vec<&str> {
+        self.labels()
+    }
+
+    /// Get all manifests as a list of dicts, in the order of keys()
+    fn values(&self, py: Python) -> PyResult<Vec<PyObject>> {
+        self.manifest_map()
+            .into_iter()
+            .flat_map(Map::values)
+            .map(|manifest| value_to_py(py, manifest))
+            .collect()
+    }
+
+    /// Get all manifests as a list of (label, manifest) tuples, in the order of keys()
+    fn items(&self, py: Python) -> PyResult<Vec<(&str, PyObject)>> {
+        self.manifest_map()
+            .into_iter()
+            .flatten()
+            .map(|(label, manifest)| Ok((label.as_str(), value_to_py(py, manifest)?)))
+            .collect()
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        PyList::new(py, self.labels())?.try_iter()
+    }
+
+    fn __getitem__(&self, py: Python, label: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        match self.manifest_for_key(label) {
+            Some(manifest) => value_to_py(py, manifest),
+            None => Err(PyKeyError::new_err(label.clone().unbind())),
+        }
+    }
+
+    fn __contains__(&self, label: &Bound<'_, PyAny>) -> bool {
+        self.manifest_for_key(label).is_some()
+    }
+
+    fn __len__(&self) -> usize {
+        self.manifest_map().map_or(0, |m| m.len())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Reader(active_label={:?}, validation_state={:?}, manifests={})",
+            self.active_label().unwrap_or_default(),
+            self.validation_state().unwrap_or_default(),
+            self.__len__()
+        )
+    }
+}
@@ -0,0 +1,209 @@
+//! Processing receipts
+//!
+//! Compliance teams archive evidence of the checks performed on content, not
+//! only their outcome. A receipt records what was read (the hash of the input),
+//! what came out (a digest of the result), with which library versions and
+//! when, and is signed with a key of the caller's, so that it can be verified
+//! long after the read with verify_receipt.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
+
+use base64::Engine;
+use c2pa::{Signer, SigningAlg};
+use c2pa_crypto::raw_signature::validator_for_signing_alg;
+use chrono::{DateTime, SecondsFormat, Utc};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use x509_parser::parse_x509_certificate;
+use x509_parser::pem::Pem;
+
+use crate::c2pa_reader::py_to_value;
+use crate::errors::signing_error;
+use crate::reputation::hex;
+use crate::serialize::to_canonical_json;
+use crate::signing::{signer_config, SignerConfig};
+
+/// Version of the receipt payload layout
+const RECEIPT_VERSION: u32 = 1;
+
+static SIGNER: Mutex<Option<Arc<SignerConfig>>> = Mutex::new(None);
+
+/// When a read started, by the wall clock for the receipt and by a monotonic
+/// clock for its duration
+pub(crate) struct Started {
+    time: SystemTime,
+    instant: Instant,
+}
+
+impl Started {
+    pub(crate) fn now() -> Self {
+        Started {
+            time: SystemTime::now(),
+            instant: Instant::now(),
+        }
+    }
+}
+
+fn rfc3339(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// The signer set with set_receipt_signer
+pub(crate) fn receipt_signer() -> PyResult<Arc<SignerConfig>> {
+    SIGNER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .ok_or_else(|| PyValueError::new_err("receipt=True needs set_receipt_signer first"))
+}
+
+/// The signed receipt of a read of `chunks` that gave `value`
+///
+/// Does not touch Python state, so it can run with the GIL released.
+fn signed_receipt(
+    config: &SignerConfig,
+    chunks: &[&[u8]],
+    mime_type: &str,
+    value: &Value,
+    started: &Started,
+) -> PyResult<Value> {
+    let mut input = Sha256::new();
+    for chunk in chunks {
+        input.update(chunk);
+    }
+    let result = to_canonical_json(value)
+        .map_err(|e| PyValueError::new_err(format!("Error serializing result: {e}")))?;
+    let payload = json!({
+        "version": RECEIPT_VERSION,
+        "input": {
+            "sha256": hex(&input.finalize()),
+            "size": chunks.iter().map(|chunk| chunk.len()).sum::<usize>(),
+            "mime_type": mime_type,
+        },
+        "result": {
+            "sha256": hex(&Sha256::digest(result.as_bytes())),
+            "active_manifest": value.get("active_manifest"),
+            "validation_state": value.get("validation_state"),
+        },
+        "libraries": {
+            "fast_c2pa_python": env!("CARGO_PKG_VERSION"),
+            "c2pa": c2pa::VERSION,
+        },
+        "started_at": rfc3339(started.time),
+        "finished_at": rfc3339(SystemTime::now()),
+        "duration_ms": started.instant.elapsed().as_secs_f64() * 1000.0,
+    });
+    let payload = to_canonical_json(&payload)
+        .map_err(|e| PyValueError::new_err(format!("Error serializing receipt: {e}")))?;
+    let signature = config
+        .signer()
+        .and_then(|signer| signer.sign(payload.as_bytes()))
+        .map_err(|e| signing_error("Error signing receipt", &e))?;
+    Ok(json!({
+        "payload": payload,
+        "alg": config.alg.to_string(),
+        "signature": base64::engine::general_purpose::STANDARD.encode(signature),
+        "certificate_chain": String::from_utf8_lossy(&config.sign_cert),
+    }))
+}
+
+/// Add the signed receipt of a read of `chunks` to its result `value`, as
+/// "receipt"
+pub(crate) fn add_receipt(
+    py: Python,
+    value: &mut Value,
+    chunks: &[&[u8]],
+    mime_type: &str,
+    config: &SignerConfig,
+    started: &Started,
+    allow_threads: bool,
+) -> PyResult<()> {
+    let receipt = if allow_threads {
+        py.allow_threads(|| signed_receipt(config, chunks, mime_type, value, started))
+    } else {
+        signed_receipt(config, chunks, mime_type, value, started)
+    }?;
+    value["receipt"] = receipt;
+    Ok(())
+}
+
+/// Set the certificate and key processing receipts are signed with
+///
+/// Reads called with receipt=True then add a "receipt" to their result. The
+/// key is the caller's own: receipts attest that this deployment performed
+/// the checks, independently of who signed the content.
+///
+/// Args:
+///     signer_config: A dict with "alg", "sign_cert" and "private_key" as for
+///         sign_and_embed ("ta_url" is not used), a CallbackSigner, or None to
+///         stop signing receipts
+///
+/// Raises:
+///     ValueError: If signer_config is invalid, or the key does not load
+///     TypeError: If sign_cert or private_key is not str or bytes
+#[pyfunction]
+#[pyo3(signature = (signer_config))]
+pub fn set_receipt_signer(signer_config: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
+    let config = match signer_config {
+        Some(config) => {
+            let config = self::signer_config(config)?;
+            config
+                .signer()
+                .map_err(|e| PyValueError::new_err(format!("Invalid signer_config: {e}")))?;
+            Some(Arc::new(config))
+        }
+        None => None,
+    };
+    *SIGNER.lock().unwrap_or_else(|e| e.into_inner()) = config;
+    Ok(())
+}
+
+/// A string field of a receipt
+fn field<'a>(receipt: &'a Value, key: &str) -> PyResult<&'a str> {
+    receipt[key]
+        .as_str()
+        .ok_or_else(|| PyValueError::new_err(format!("receipt has no {key:?}")))
+}
+
+/// Check the signature of a processing receipt
+///
+/// Only the signature is checked, with the public key of the first certificate
+/// of its "certificate_chain": compare that certificate with your own to know
+/// the receipt was issued by your deployment. json.loads(receipt["payload"])
+/// gives the recorded "input" (hash, size and MIME type), "result" (digest of
+/// the result without its "receipt", active manifest and validation state),
+/// "libraries" versions, "started_at", "finished_at" and "duration_ms".
+///
+/// Args:
+///     receipt: The "receipt" of a result read with receipt=True
+///
+/// Returns:
+///     True if the payload was signed by the key of the certificate
+///
+/// Raises:
+///     ValueError: If the receipt is malformed, or its algorithm is not supported
+#[pyfunction]
+pub fn verify_receipt(py: Python, receipt: &Bound<'_, PyAny>) -> PyResult<bool> {
+    let receipt = py_to_value(py, receipt)?;
+    let payload = field(&receipt, "payload")?;
+    let alg: SigningAlg = field(&receipt, "alg")?
+        .parse()
+        .map_err(|_| PyValueError::new_err("receipt has an unknown \"alg\""))?;
+    let signature = base64::engine::general_purpose::STANDARD
+        .decode(field(&receipt, "signature")?)
+        .map_err(|e| PyValueError::new_err(format!("Invalid receipt signature: {e}")))?;
+    let pem = Pem::iter_from_buffer(field(&receipt, "certificate_chain")?.as_bytes())
+        .find_map(Result::ok)
+        .ok_or_else(|| PyValueError::new_err("receipt has no PEM certificate"))?;
+    let (_, certificate) = parse_x509_certificate(&pem.contents)
+        .map_err(|e| PyValueError::new_err(format!("Invalid receipt certificate: {e}")))?;
+    let validator = validator_for_signing_alg(alg)
+        .ok_or_else(|| PyValueError::new_err(format!("Unsupported receipt alg {alg}")))?;
+    let public_key = certificate.tbs_certificate.subject_pki.raw;
+    Ok(validator
+        .validate(&signature, payload.as_bytes(), public_key)
+        .is_ok())
+}
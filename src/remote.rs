@@ -0,0 +1,141 @@
+//! Reading assets whose manifest store is kept at a remote URL
+//!
+//! Such assets carry no manifest store, only a pointer to it: the
+//! dcterms:provenance URL of their XMP, or the X-C2PA-Manifest header of the
+//! response they were downloaded with. When asked to, the reader fetches the
+//! store from there, with the shared HTTP client or a registered fetcher, and
+//! validates it against the asset.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use serde_json::Value;
+
+use crate::c2pa_reader::{run_hooks, set_source, verify_value};
+use crate::deadline;
+use crate::http::{configured_timeout, fetch_reported_within};
+use crate::xmp::scan_provenance;
+
+static FETCHER: Mutex<Option<PyObject>> = Mutex::new(None);
+
+/// Where and how to fetch the remote manifest store of a read
+pub(crate) struct RemoteOptions {
+    /// The URL to fetch, e.g. from an X-C2PA-Manifest header, else the
+    /// reference in the asset's XMP
+    pub url: Option<String>,
+    /// Timeout of each request, else the one given to configure_http
+    pub timeout_ms: Option<u64>,
+}
+
+impl RemoteOptions {
+    /// The options of a read, `None` when it does not fetch remote manifests
+    pub(crate) fn new(fetch: bool, url: Option<String>, timeout_ms: Option<u64>) -> Option<Self> {
+        fetch.then_some(RemoteOptions { url, timeout_ms })
+    }
+}
+
+/// Download the manifest store at `url` with the registered fetcher, else the
+/// shared HTTP client, with the "fetch_attempts" of the latter
+fn fetch(
+    py: Python,
+    url: &str,
+    timeout_ms: Option<u64>,
+) -> PyResult<Option<(Vec<u8>, Option<Value>)>> {
+    let fetcher = FETCHER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|fetcher| fetcher.clone_ref(py));
+    let Some(fetcher) = fetcher else {
+        let timeout = timeout_ms.map(Duration::from_millis);
+        let (manifest, attempts) = py.allow_threads(|| fetch_reported_within(url, timeout))?;
+        return Ok(Some((manifest, Some(attempts))));
+    };
+    let found = fetcher.call1(py, (url, timeout_ms))?;
+    let found = found.bind(py);
+    if found.is_none() {
+        Ok(None)
+    } else if let Ok(manifest) = found.extract::<Vec<u8>>() {
+        Ok(Some((manifest, None)))
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "remote manifest fetcher must return bytes or None, not {}",
+            found.get_type().name()?
+        )))
+    }
+}
+
+/// Fetch and validate the remote manifest store of an asset with no embedded
+/// one, then run the read hooks on it
+///
+/// Returns `None` when the asset has no reference or the fetcher found
+/// nothing. The result has "is_embedded" False, "remote_url" set to the URL
+/// fetched and, when the shared client downloaded it, "fetch_attempts".
+pub(crate) fn read_remote(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    options: &RemoteOptions,
+    allow_threads: bool,
+) -> PyResult<Option<Value>> {
+    let url = match &options.url {
+        Some(url) => url.clone(),
+        None => match scan_provenance(data).provenance {
+            Some(url) => url,
+            None => return Ok(None),
+        },
+    };
+    // Downloads in a read with a budget stop when it runs out
+    deadline::check(py, "fetching the remote manifest", None)?;
+    let timeout_ms = match deadline::remaining() {
+        Some(left) => {
+            let timeout = options
+                .timeout_ms
+                .map_or_else(configured_timeout, Duration::from_millis);
+            Some(timeout.min(left).as_millis().max(1) as u64)
+        }
+        None => options.timeout_ms,
+    };
+    let Some((manifest, attempts)) = fetch(py, &url, timeout_ms)? else {
+        return Ok(None);
+    };
+
+    let mut value = verify_value(py, &manifest, data, mime_type, allow_threads)?;
+    set_source(&mut value, false, Some(url));
+    if let (Some(map), Some(attempts)) = (value.as_object_mut(), attempts) {
+        map.insert("fetch_attempts".to_owned(), attempts);
+    }
+    run_hooks(py, &mut value, "read", allow_threads, || None)?;
+    Ok(Some(value))
+}
+
+/// Register a callback that downloads remote manifest stores
+///
+/// Reads with fetch_remote_manifests=True download the remote manifest store
+/// of an asset with `fetcher(url, timeout_ms)` instead of the shared HTTP
+/// client, for instance to go through an authenticated client, a local
+/// mirror or a cache. timeout_ms is the remote_timeout_ms of the read, or
+/// None. The fetcher returns the manifest store as bytes, or None if there is
+/// none at the URL; exceptions it raises propagate to the caller of the read
+/// function.
+///
+/// Args:
+///     fetcher: A callable, or None to download with the shared HTTP client again
+///
+/// Raises:
+///     TypeError: If fetcher is not callable
+#[pyfunction]
+#[pyo3(signature = (fetcher))]
+pub fn set_remote_manifest_fetcher(fetcher: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+    if let Some(fetcher) = &fetcher {
+        if !fetcher.is_callable() {
+            return Err(PyTypeError::new_err(
+                "remote manifest fetcher must be callable",
+            ));
+        }
+    }
+    *FETCHER.lock().unwrap_or_else(|e| e.into_inner()) = fetcher.map(Bound::unbind);
+    Ok(())
+}
@@ -0,0 +1,359 @@
+//! Repair of structural damage left by early C2PA tools
+//!
+//! Some tools wrote manifest stores with superbox lengths that do not match
+//! their contents, embedded the XMP packet more than once, or pointed the XMP
+//! at sidecar manifests that were later deleted. Superbox lengths are rebuilt
+//! from the content boxes they hold, which the claim hashes cover and which
+//! are kept byte for byte.
+
+use std::path::{Path, PathBuf};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use serde_json::{json, Value};
+
+use crate::c2pa_reader::value_to_py;
+use crate::gil;
+use crate::jumbf::scan_manifest_store;
+use crate::xmp::{find_provenance, without_provenance, Container};
+
+/// A JUMBF box of a manifest store as rebuilt
+enum Node<'a> {
+    /// A content box, kept byte for byte
+    Content(&'a [u8]),
+    Superbox(Superbox<'a>),
+}
+
+struct Superbox<'a> {
+    /// The length its header declares
+    declared: u64,
+    /// Nesting level in the store: 0 for the store, 1 for manifests, 2 for
+    /// the stores of a manifest, its claim and signature, 3 for assertions
+    level: u8,
+    label: String,
+    /// Description box first, then the boxes it contains
+    children: Vec<Node<'a>>,
+}
+
+/// The nesting level of a superbox from its description box type
+///
+/// Other types are the boxes of a store (assertions, data boxes or
+/// credentials), inside the store opened last.
+fn level(kind: &[u8; 4]) -> u8 {
+    match kind {
+        b"c2pa" => 0,
+        b"c2ma" | b"c2um" => 1,
+        b"c2as" | b"c2cl" | b"c2cs" | b"c2db" | b"c2vc" => 2,
+        _ => 3,
+    }
+}
+
+impl Superbox<'_> {
+    fn len(&self) -> u64 {
+        8 + self.children.iter().map(Node::len).sum::<u64>()
+    }
+}
+
+impl Node<'_> {
+    fn len(&self) -> u64 {
+        match self {
+            Node::Content(raw) => raw.len() as u64,
+            Node::Superbox(superbox) => superbox.len(),
+        }
+    }
+}
+
+/// The type and label of a superbox from its description box
+fn description(jumd: &[u8]) -> ([u8; 4], String) {
+    let payload = &jumd[8.min(jumd.len())..];
+    let mut kind = [0; 4];
+    if let Some(uuid) = payload.get(..4) {
+        kind.copy_from_slice(uuid);
+    }
+    // UUID, then a toggles byte whose bit 1 says a label follows
+    let label = match payload.get(16..).and_then(<[u8]>::split_first) {
+        Some((toggles, rest)) if toggles & 0x02 != 0 => {
+            let end = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+            String::from_utf8_lossy(&rest[..end]).into_owned()
+        }
+        _ => String::new(),
+    };
+    (kind, label)
+}
+
+/// Rebuild the box tree of a manifest store, ignoring superbox lengths
+///
+/// Content boxes are read with their declared lengths; each superbox is
+/// placed by the type of its description box, which says where it belongs in
+/// the store whatever its header claims.
+fn parse_store(store: &[u8]) -> Result<Superbox<'_>, String> {
+    let mut stack: Vec<Superbox> = Vec::new();
+    let mut pos = 0;
+    while let Some(header) = store.get(pos..pos + 8) {
+        let lbox = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+        let (header_len, declared) = match lbox {
+            0 => (8, (store.len() - pos) as u64),
+            1 => {
+                let xl = store
+                    .get(pos + 8..pos + 16)
+                    .ok_or_else(|| format!("box at offset {pos} is cut short"))?;
+                (16, u64::from_be_bytes(xl.try_into().unwrap_or_default()))
+            }
+            len => (8, u64::from(len)),
+        };
+        if &header[4..8] == b"jumb" {
+            let jumd = store
+                .get(pos + header_len..)
+                .filter(|rest| rest.get(4..8) == Some(b"jumd"))
+                .ok_or_else(|| format!("superbox at offset {pos} has no description box"))?;
+            let (kind, label) = description(jumd);
+            let level = level(&kind);
+            while stack.len() > 1 && stack.last().is_some_and(|open| open.level >= level) {
+                close(&mut stack);
+            }
+            if level == 0 && !stack.is_empty() {
+                return Err(format!("second manifest store at offset {pos}"));
+            }
+            stack.push(Superbox {
+                declared,
+                level,
+                label,
+                children: Vec::new(),
+            });
+            pos += header_len;
+            continue;
+        }
+        let end = usize::try_from(declared)
+            .ok()
+            .and_then(|len| pos.checked_add(len))
+            .filter(|&end| end <= store.len() && end >= pos + header_len)
+            .ok_or_else(|| {
+                format!("box at offset {pos} runs past the end of the manifest store")
+            })?;
+        let Some(parent) = stack.last_mut() else {
+            return Err(format!("box at offset {pos} is outside the manifest store"));
+        };
+        parent.children.push(Node::Content(&store[pos..end]));
+        pos = end;
+    }
+    if pos != store.len() && store[pos..].iter().any(|&b| b != 0) {
+        return Err(format!("unexpected bytes at offset {pos}"));
+    }
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+    let outer = stack.pop().ok_or("no manifest store")?;
+    if outer.level != 0 {
+        return Err("not a C2PA manifest store".to_owned());
+    }
+    if outer.len() > u64::from(u32::MAX) {
+        return Err("manifest store is over 4 GiB".to_owned());
+    }
+    Ok(outer)
+}
+
+/// Move the innermost open superbox into its parent
+fn close(stack: &mut Vec<Superbox>) {
+    if let Some(superbox) = stack.pop() {
+        if let Some(parent) = stack.last_mut() {
+            parent.children.push(Node::Superbox(superbox));
+        }
+    }
+}
+
+/// Write a superbox with the lengths of its contents, recording the ones
+/// that differ from what it declared
+fn write(superbox: &Superbox, path: &str, out: &mut Vec<u8>, changes: &mut Vec<Value>) {
+    let path = if path.is_empty() {
+        superbox.label.clone()
+    } else {
+        format!("{path}/{}", superbox.label)
+    };
+    let len = superbox.len();
+    if len != superbox.declared {
+        changes.push(json!({
+            "code": "jumbf.boxLength",
+            "label": path,
+            "explanation": format!(
+                "set the length of superbox {path} from {} to {len}",
+                superbox.declared
+            ),
+        }));
+    }
+    // The whole store was checked to fit in 32-bit lengths
+    out.extend_from_slice(&(len as u32).to_be_bytes());
+    out.extend_from_slice(b"jumb");
+    for child in &superbox.children {
+        match child {
+            Node::Content(raw) => out.extend_from_slice(raw),
+            Node::Superbox(child) => write(child, &path, out, changes),
+        }
+    }
+}
+
+/// The manifest store of `data` with its superbox lengths fixed, if any were
+/// wrong
+fn repair_store(
+    data: &[u8],
+    mime_type: &str,
+    changes: &mut Vec<Value>,
+) -> Result<Option<Vec<u8>>, String> {
+    let Some(store) = scan_manifest_store(data, mime_type) else {
+        return Ok(None);
+    };
+    let outer =
+        parse_store(&store).map_err(|e| format!("cannot rebuild the manifest store: {e}"))?;
+    let mut fixed = Vec::with_capacity(store.len());
+    let found = changes.len();
+    write(&outer, "", &mut fixed, changes);
+    if changes.len() == found {
+        return Ok(None);
+    }
+    // Keep the padding after the store, so it takes the same space
+    fixed.resize(fixed.len().max(store.len()), 0);
+    c2pa::jumbf_io::save_jumbf_to_memory(mime_type, data, &fixed)
+        .map(Some)
+        .map_err(|e| format!("cannot write the manifest store: {e}"))
+}
+
+/// The sidecar file a provenance pointer names, if it is not a URL
+fn sidecar_path(pointer: &str, sidecar_dir: Option<&Path>) -> Option<PathBuf> {
+    let path = match pointer.strip_prefix("file://") {
+        Some(path) => Path::new(path),
+        None if pointer.contains("://") => return None,
+        None => Path::new(pointer),
+    };
+    if path.is_absolute() {
+        Some(path.to_owned())
+    } else {
+        sidecar_dir.map(|dir| dir.join(path))
+    }
+}
+
+/// `data` with its duplicate XMP packets removed and its provenance pointer
+/// dropped if it names a missing sidecar
+fn repair_xmp(
+    data: Vec<u8>,
+    container: Container,
+    sidecar_dir: Option<&Path>,
+    changes: &mut Vec<Value>,
+) -> Result<Vec<u8>, String> {
+    let Some((slots, _)) = container.slots(&data) else {
+        return Ok(data);
+    };
+    let Some((first, duplicates)) = slots.split_first() else {
+        return Ok(data);
+    };
+    let pointer = first.xmp.as_deref().and_then(find_provenance);
+    let mut replacement = None;
+    if let (Some(pointer), Some(xmp)) = (&pointer, &first.xmp) {
+        let missing = sidecar_path(pointer, sidecar_dir).filter(|path| !path.exists());
+        if let Some(path) = missing {
+            let xmp = without_provenance(xmp).ok_or("cannot remove the provenance pointer")?;
+            replacement = Some(container.encode(&xmp)?);
+            changes.push(json!({
+                "code": "xmp.staleReference",
+                "label": null,
+                "explanation": format!(
+                    "removed the provenance pointer to the missing sidecar {}",
+                    path.display()
+                ),
+            }));
+        }
+    }
+    for duplicate in duplicates {
+        let other = duplicate.xmp.as_deref().and_then(find_provenance);
+        let explanation = match other {
+            Some(other) if Some(&other) != pointer.as_ref() => format!(
+                "removed the duplicate XMP packet at offset {}, with provenance pointer {other}",
+                duplicate.range.start
+            ),
+            _ => format!(
+                "removed the duplicate XMP packet at offset {}",
+                duplicate.range.start
+            ),
+        };
+        changes.push(json!({ "code": "xmp.duplicate", "label": null, "explanation": explanation }));
+    }
+    if replacement.is_none() && duplicates.is_empty() {
+        return Ok(data);
+    }
+
+    let mut repaired = Vec::with_capacity(data.len());
+    let mut pos = 0;
+    for (index, slot) in slots.iter().enumerate() {
+        repaired.extend_from_slice(&data[pos..slot.range.start]);
+        let kept = match &replacement {
+            Some(replacement) if index == 0 => replacement.as_slice(),
+            _ if index == 0 => &data[slot.range.clone()],
+            _ => &[],
+        };
+        repaired.extend_from_slice(kept);
+        pos = slot.range.end;
+    }
+    repaired.extend_from_slice(&data[pos..]);
+    Ok(repaired)
+}
+
+/// Repair the recoverable structural damage of an asset
+///
+/// Fixes manifest store superboxes whose declared lengths do not match their
+/// contents, removes XMP packets stored more than once (the first is kept) and
+/// removes a provenance pointer to a sidecar manifest file that does not exist.
+/// Only the headers of superboxes are rewritten, never the signed boxes they
+/// contain. XMP changes do alter bytes covered by the hash binding of an
+/// embedded manifest: read the repaired asset to check its validation state.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data, "image/jpeg" or "image/png"
+///     sidecar_dir: Directory relative sidecar pointers are resolved against
+///         (default: None, only absolute paths and file:// URLs are checked)
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A dict with "data" (the repaired asset as bytes, the same bytes when
+///     nothing changed) and "changes" (what was changed, as dicts with "code":
+///     "jumbf.boxLength", "xmp.duplicate" or "xmp.staleReference", "label"
+///     (the path of labels of the superbox, or None) and "explanation")
+///
+/// Raises:
+///     ValueError: If the format is not supported or the damage cannot be repaired
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, sidecar_dir=None, allow_threads=None))]
+pub fn repair_c2pa(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    sidecar_dir: Option<PathBuf>,
+    allow_threads: Option<bool>,
+) -> PyResult<PyObject> {
+    let Some(container) = Container::from_mime_type(mime_type) else {
+        return Err(PyValueError::new_err(format!(
+            "Cannot repair {mime_type} assets, only JPEG and PNG are supported"
+        )));
+    };
+    let allow_threads = gil::release(allow_threads, data.len());
+    let repair = || {
+        let mut changes = Vec::new();
+        let repaired = match repair_store(data, mime_type, &mut changes)? {
+            Some(repaired) => repaired,
+            None => data.to_vec(),
+        };
+        let repaired = repair_xmp(repaired, container, sidecar_dir.as_deref(), &mut changes)?;
+        Ok::<_, String>((repaired, changes))
+    };
+    let (repaired, changes) = if allow_threads {
+        py.allow_threads(repair)
+    } else {
+        repair()
+    }
+    .map_err(|e| PyValueError::new_err(format!("Cannot repair asset: {e}")))?;
+
+    let result = PyDict::new(py);
+    result.set_item("data", PyBytes::new(py, &repaired))?;
+    result.set_item("changes", value_to_py(py, &Value::Array(changes))?)?;
+    Ok(result.into_any().unbind())
+}
@@ -0,0 +1,141 @@
+//! Signer reputation
+//!
+//! Moderation rules often depend on who signed a manifest: a newsroom whose
+//! certificate is on file is treated differently from an unknown signer. A
+//! reputation map, keyed by the SHA-256 of a certificate, annotates the signer
+//! of each manifest so rules can branch on it without a second pass.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use c2pa_crypto::cose::cert_chain_from_sign1;
+use coset::{CoseSign1, TaggedCborSerializable};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::c2pa_reader::py_to_value;
+use crate::jumbf::manifest_signatures;
+
+static REPUTATION: Mutex<Option<HashMap<String, Value>>> = Mutex::new(None);
+
+/// Whether a reputation map is set
+pub(crate) fn has_reputation() -> bool {
+    REPUTATION
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some()
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The certificate chain of a COSE_Sign1 signature, signing certificate first
+pub(crate) fn cert_chain(signature: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let sign1 = CoseSign1::from_tagged_slice(signature).ok()?;
+    cert_chain_from_sign1(&sign1).ok()
+}
+
+/// Annotate each manifest of `store` with the reputation of its signer
+///
+/// The signing certificate is looked up first, then the certificates that
+/// issued it, so an entry for an organization's CA covers all its signers.
+pub(crate) fn annotate_signers(value: &mut Value, store: &[u8]) {
+    let reputation = REPUTATION.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(reputation) = reputation.as_ref() else {
+        return;
+    };
+    for (label, signature) in manifest_signatures(store) {
+        let Some(manifest) = value.get_mut("manifests").and_then(|m| m.get_mut(label)) else {
+            continue;
+        };
+        let Some(chain) = cert_chain(signature) else {
+            continue;
+        };
+        let hashes: Vec<String> = chain
+            .iter()
+            .map(|cert| hex(&Sha256::digest(cert)))
+            .collect();
+        let known = hashes
+            .iter()
+            .enumerate()
+            .find_map(|(index, hash)| reputation.get(hash).map(|entry| (index, hash, entry)));
+        manifest["signer_reputation"] = match known {
+            Some((index, hash, entry)) => {
+                let mut annotation = json!({
+                    "known": true,
+                    "cert_sha256": hash,
+                    "chain_index": index,
+                });
+                if let (Some(annotation), Some(entry)) =
+                    (annotation.as_object_mut(), entry.as_object())
+                {
+                    for (key, value) in entry {
+                        annotation.entry(key).or_insert_with(|| value.clone());
+                    }
+                }
+                annotation
+            }
+            None => json!({
+                "known": false,
+                "cert_sha256": hashes.first(),
+            }),
+        };
+    }
+}
+
+/// Set the reputation map used to annotate the signer of each manifest
+///
+/// Keys are SHA-256 fingerprints of DER certificates, in hex with or without
+/// colons; values are JSON serializable dicts such as {"label": "Newsroom",
+/// "score": 0.9}. Each manifest of the stores read by read_c2pa_from_file,
+/// read_c2pa_from_bytes, read_c2pa_json_from_bytes, read_c2pa_encoded_from_bytes,
+/// verify_manifest_bytes and Reader.from_bytes then gets a "signer_reputation"
+/// dict: "known" tells whether the signing certificate or one of its issuers is
+/// in the map, "cert_sha256" is the fingerprint that matched (the signing
+/// certificate's for unknown signers) and, for known signers, "chain_index" is
+/// the position of the matched certificate in the chain (0 for the signing
+/// certificate) next to the items of the map entry.
+///
+/// The map is only a lookup: it does not make a signer trusted. Manifest stores
+/// recovered by the soft binding resolver are not annotated.
+///
+/// Args:
+///     reputation: A dict of fingerprint to entry dict, or None to remove the map
+///
+/// Raises:
+///     TypeError: If reputation is not a dict of str to dict
+///     ValueError: If a key is not a SHA-256 fingerprint
+#[pyfunction]
+#[pyo3(signature = (reputation))]
+pub fn set_signer_reputation(py: Python, reputation: Option<&Bound<'_, PyDict>>) -> PyResult<()> {
+    let reputation = match reputation {
+        None => None,
+        Some(reputation) => {
+            let mut map = HashMap::new();
+            for (fingerprint, entry) in reputation.iter() {
+                let fingerprint: String = fingerprint
+                    .extract()
+                    .map_err(|_| PyTypeError::new_err("reputation keys must be str"))?;
+                let hash = fingerprint.replace(':', "").to_ascii_lowercase();
+                if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    return Err(PyValueError::new_err(format!(
+                        "{fingerprint} is not a SHA-256 fingerprint"
+                    )));
+                }
+                if !entry.is_instance_of::<PyDict>() {
+                    return Err(PyTypeError::new_err(format!(
+                        "reputation entry for {fingerprint} must be a dict"
+                    )));
+                }
+                map.insert(hash, py_to_value(py, &entry)?);
+            }
+            Some(map)
+        }
+    };
+    *REPUTATION.lock().unwrap_or_else(|e| e.into_inner()) = reputation;
+    Ok(())
+}
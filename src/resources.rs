@@ -0,0 +1,397 @@
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::sync::Mutex;
+
+use c2pa::Reader;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use serde_json::Value;
+
+use crate::c2pa_reader::{embedded_store, read_reader};
+use crate::gil;
+use crate::jumbf::active_claim_thumbnail;
+
+/// Byte limits on the resources a call returns, None for no limit
+#[derive(Clone, Copy)]
+struct ResourceLimits {
+    resource: Option<usize>,
+    total: Option<usize>,
+}
+
+static LIMITS: Mutex<ResourceLimits> = Mutex::new(ResourceLimits {
+    resource: None,
+    total: None,
+});
+
+/// The resource bytes one call may still return
+pub(crate) struct Budget {
+    limits: ResourceLimits,
+    used: usize,
+}
+
+impl Budget {
+    pub(crate) fn new() -> Self {
+        Budget {
+            limits: *LIMITS.lock().unwrap_or_else(|e| e.into_inner()),
+            used: 0,
+        }
+    }
+
+    /// The limit a resource of `len` bytes is over, or None after counting it
+    pub(crate) fn admit(&mut self, len: usize) -> Option<&'static str> {
+        if self.limits.resource.is_some_and(|max| len > max) {
+            return Some("max_resource_bytes");
+        }
+        if self.limits.total.is_some_and(|max| self.used + len > max) {
+            return Some("max_total_bytes");
+        }
+        self.used += len;
+        None
+    }
+
+    /// The size of the largest resource that can still be admitted
+    fn allowance(&self) -> Option<usize> {
+        let total = self.limits.total.map(|max| max.saturating_sub(self.used));
+        match (self.limits.resource, total) {
+            (Some(resource), Some(total)) => Some(resource.min(total)),
+            (resource, total) => resource.or(total),
+        }
+    }
+}
+
+/// A buffer refusing writes past `limit`, so an oversized resource is never
+/// copied in full
+struct Capped {
+    buffer: Cursor<Vec<u8>>,
+    limit: Option<usize>,
+    /// The size reached by the refused write, at most the resource size
+    refused: Option<usize>,
+}
+
+impl Write for Capped {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.buffer.position() as usize + buf.len();
+        if self.limit.is_some_and(|limit| len > limit) {
+            self.refused = Some(len);
+            return Err(io::Error::other("resource over the byte limit"));
+        }
+        self.buffer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.buffer.flush()
+    }
+}
+
+impl Read for Capped {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.buffer.read(buf)
+    }
+}
+
+impl Seek for Capped {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.buffer.seek(pos)
+    }
+}
+
+/// A resource loaded within the byte limits
+pub(crate) enum Resource {
+    Loaded(Vec<u8>),
+    /// Not stored in the manifest store, e.g. a remote icon
+    Missing,
+    /// Over the limit named
+    Skipped(&'static str),
+}
+
+/// Load the bytes of a resource referenced from a manifest, counting them
+/// against `budget`
+pub(crate) fn resource_bytes(reader: &Reader, identifier: &str, budget: &mut Budget) -> Resource {
+    let mut stream = Capped {
+        buffer: Cursor::new(Vec::new()),
+        limit: budget.allowance(),
+        refused: None,
+    };
+    let loaded = reader.resource_to_stream(identifier, &mut stream);
+    let len = match (loaded, stream.refused) {
+        (_, Some(len)) => len,
+        (Ok(_), None) => {
+            let bytes = stream.buffer.into_inner();
+            return match budget.admit(bytes.len()) {
+                Some(limit) => Resource::Skipped(limit),
+                None => Resource::Loaded(bytes),
+            };
+        }
+        (Err(_), None) => return Resource::Missing,
+    };
+    // Over the allowance, so over one of the limits
+    Resource::Skipped(budget.admit(len).unwrap_or("max_total_bytes"))
+}
+
+/// Labels of the manifests in `reader`, the active manifest first
+pub(crate) fn manifest_labels(reader: &Reader) -> Vec<String> {
+    let active = reader.active_label();
+    let mut labels: Vec<String> = reader
+        .manifests()
+        .keys()
+        .filter(|label| Some(label.as_str()) != active)
+        .cloned()
+        .collect();
+    labels.sort();
+    if let Some(active) = active {
+        labels.insert(0, active.to_owned());
+    }
+    labels
+}
+
+/// Get the icons of the tools that created each manifest
+///
+/// Claim generator info entries can reference an icon, typically the logo of
+/// the app or service that signed the manifest, for display next to the
+/// credentials.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A list of dicts with "manifest" (label), "name" and "version" of the claim
+///     generator, "format" (MIME type of the icon), "identifier", "data" (the
+///     icon as bytes, or None if it is not stored in the asset or is over the
+///     resource limits) and "skipped" (the limit the icon is over,
+///     "max_resource_bytes" or "max_total_bytes", else None). The active
+///     manifest comes first. Empty if there is no C2PA metadata or no icon.
+///
+/// Raises:
+///     C2paError: If there is an error reading or parsing the C2PA data
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, allow_threads=None))]
+pub fn get_generator_icons(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<Vec<PyObject>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let Some(reader) = read_reader(py, data, mime_type, allow_threads)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut budget = Budget::new();
+    let mut icons = Vec::new();
+    for label in manifest_labels(&reader) {
+        let Some(manifest) = reader.get_manifest(&label) else {
+            continue;
+        };
+        for info in manifest.claim_generator_info.iter().flatten() {
+            // A resource reference once resolved by the reader, otherwise a
+            // hashed URI pointing outside the manifest store
+            let Some(icon) = info.icon.as_ref().and_then(|i| serde_json::to_value(i).ok()) else {
+                continue;
+            };
+            let format = icon.get("format").and_then(Value::as_str);
+            let Some(identifier) = icon
+                .get("identifier")
+                .or_else(|| icon.get("url"))
+                .and_then(Value::as_str)
+            else {
+                continue;
+            };
+            let resource = match format {
+                Some(_) => resource_bytes(&reader, identifier, &mut budget),
+                None => Resource::Missing,
+            };
+            let (bytes, skipped) = match resource {
+                Resource::Loaded(bytes) => (Some(bytes), None),
+                Resource::Missing => (None, None),
+                Resource::Skipped(limit) => (None, Some(limit)),
+            };
+
+            let item = PyDict::new(py);
+            item.set_item("manifest", &label)?;
+            item.set_item("name", &info.name)?;
+            item.set_item("version", &info.version)?;
+            item.set_item("format", format)?;
+            item.set_item("identifier", identifier)?;
+            item.set_item("data", bytes.map(|b| PyBytes::new(py, &b)))?;
+            item.set_item("skipped", skipped)?;
+            icons.push(item.into_any().unbind());
+        }
+    }
+    Ok(icons)
+}
+
+/// The resource references in a manifest's JSON: the {"format", "identifier"}
+/// objects of thumbnails, ingredient thumbnails, icons and the like
+pub(crate) fn resource_refs<'a>(value: &'a Value, refs: &mut Vec<(&'a str, &'a str)>) {
+    match value {
+        Value::Object(map) => {
+            if let (Some(Value::String(format)), Some(Value::String(identifier))) =
+                (map.get("format"), map.get("identifier"))
+            {
+                refs.push((identifier, format));
+            }
+            map.values().for_each(|value| resource_refs(value, refs));
+        }
+        Value::Array(items) => items.iter().for_each(|value| resource_refs(value, refs)),
+        _ => {}
+    }
+}
+
+/// The URI of a resource of the manifest `label` from the manifest store root,
+/// for identifiers relative to the manifest such as
+/// "self#jumbf=c2pa.assertions/c2pa.thumbnail.ingredient"
+fn absolute_uri(label: &str, identifier: &str) -> String {
+    match identifier.strip_prefix("self#jumbf=") {
+        Some(path) if !path.starts_with('/') => format!("self#jumbf=/c2pa/{label}/{path}"),
+        _ => identifier.to_owned(),
+    }
+}
+
+/// Get the resources the manifests reference, such as thumbnails and icons
+///
+/// Read results only reference resources by identifier; this returns their
+/// bytes. Identifiers relative to a manifest are made absolute with its label,
+/// so the resources of different manifests do not collide.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A dict mapping resource URIs to dicts with "manifest" (label of the
+///     manifest referencing it), "identifier" (as in the read result), "format"
+///     (MIME type), "data" (the resource as bytes, or None if it is over the
+///     resource limits) and "skipped" (the limit it is over, "max_resource_bytes"
+///     or "max_total_bytes", else None). Resources of the active manifest come
+///     first; resources not stored in the asset are left out. Empty if there is
+///     no C2PA metadata.
+///
+/// Raises:
+///     C2paError: If there is an error reading or parsing the C2PA data
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, allow_threads=None))]
+pub fn read_c2pa_resources(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<PyObject> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let resources = PyDict::new(py);
+    let Some(reader) = read_reader(py, data, mime_type, allow_threads)? else {
+        return Ok(resources.into_any().unbind());
+    };
+
+    let mut budget = Budget::new();
+    for label in manifest_labels(&reader) {
+        let Some(manifest) = reader
+            .get_manifest(&label)
+            .and_then(|manifest| serde_json::to_value(manifest).ok())
+        else {
+            continue;
+        };
+        let mut refs = Vec::new();
+        resource_refs(&manifest, &mut refs);
+        for (identifier, format) in refs {
+            let uri = absolute_uri(&label, identifier);
+            if resources.contains(&uri)? {
+                continue;
+            }
+            // Identifiers the reader resolves against the active manifest are
+            // looked up as they are, including the ones it does not parse
+            let lookup = if reader.active_label() == Some(label.as_str()) {
+                identifier
+            } else {
+                &uri
+            };
+            let (bytes, skipped) = match resource_bytes(&reader, lookup, &mut budget) {
+                Resource::Loaded(bytes) => (Some(bytes), None),
+                Resource::Missing => continue,
+                Resource::Skipped(limit) => (None, Some(limit)),
+            };
+            let item = PyDict::new(py);
+            item.set_item("manifest", &label)?;
+            item.set_item("identifier", identifier)?;
+            item.set_item("format", format)?;
+            item.set_item("data", bytes.map(|b| PyBytes::new(py, &b)))?;
+            item.set_item("skipped", skipped)?;
+            resources.set_item(uri, item)?;
+        }
+    }
+    Ok(resources.into_any().unbind())
+}
+
+/// Get the claim thumbnail of the active manifest as stored, without
+/// validating anything
+///
+/// Only the JUMBF boxes leading to the thumbnail are walked: the manifest
+/// store is neither deserialized nor checked against the asset, so the
+/// thumbnail may belong to credentials that do not validate. Meant for
+/// showing the thumbnail quickly, such as a badge in a moderation UI, not for
+/// trusting it.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A (format, data) tuple of the MIME type of the thumbnail and its bytes, or
+///     None if there is no C2PA metadata or the active manifest has no claim
+///     thumbnail stored in the asset
+///
+/// Raises:
+///     ValueError: If the thumbnail is over the limits set by set_resource_limits
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, allow_threads=None))]
+pub fn get_claim_thumbnail_fast(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<Option<(String, Py<PyBytes>)>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let store = if allow_threads {
+        py.allow_threads(|| embedded_store(data, mime_type))
+    } else {
+        embedded_store(data, mime_type)
+    };
+    let Some((format, bytes)) = store.as_deref().and_then(active_claim_thumbnail) else {
+        return Ok(None);
+    };
+    if let Some(limit) = Budget::new().admit(bytes.len()) {
+        return Err(PyValueError::new_err(format!(
+            "thumbnail of {} bytes is over the {limit} resource limit",
+            bytes.len()
+        )));
+    }
+    Ok(Some((format.to_owned(), PyBytes::new(py, bytes).unbind())))
+}
+
+/// Limit the size of the resources returned by get_generator_icons,
+/// read_c2pa_resources, get_claim_thumbnail_fast and get_thumbnail_array, or
+/// compared by compare_ingredient_thumbnails
+///
+/// Resources stay in the manifest store until one of these functions asks for
+/// them: read results only reference them. The limits keep a multi-megabyte
+/// icon, or many of them, from being copied into Python. Call it without
+/// arguments to remove the limits.
+///
+/// Args:
+///     max_resource_bytes: Largest resource returned (default: None, no limit)
+///     max_total_bytes: Most resource bytes returned by one call (default: None,
+///         no limit)
+#[pyfunction]
+#[pyo3(signature = (max_resource_bytes=None, max_total_bytes=None))]
+pub fn set_resource_limits(max_resource_bytes: Option<usize>, max_total_bytes: Option<usize>) {
+    *LIMITS.lock().unwrap_or_else(|e| e.into_inner()) = ResourceLimits {
+        resource: max_resource_bytes,
+        total: max_total_bytes,
+    };
+}
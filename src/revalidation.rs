@@ -0,0 +1,239 @@
+//! Re-checking the hard bindings of a parsed manifest store
+//!
+//! A manifest store parsed once does not change, but the asset it is bound to
+//! may be edited, re-encoded or corrupted in storage. Re-running only the hash
+//! bindings of the active manifest against the current bytes tells whether the
+//! content is still the one signed, without the signature and trust checks of
+//! a full read. The hashes come from the detailed report of the earlier read.
+
+use std::io::Cursor;
+
+use base64::Engine;
+use c2pa::assertions::{BmffHash, DataHash};
+use c2pa::jumbf_io::get_supported_types;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use serde_json::{json, Map, Value};
+
+use crate::buffer::Buffer;
+use crate::c2pa_reader::{py_to_value, value_to_py};
+use crate::errors::c2pa_error;
+use crate::gil;
+
+/// Fields of the hard binding assertions holding bytes, which the detailed
+/// report gives in base64
+const BYTE_FIELDS: [&str; 6] = ["hash", "pad", "pad2", "value", "initHash", "flags"];
+
+/// The bytes fields of a hard binding assertion of the detailed report back as
+/// arrays of bytes, as the assertion structs of c2pa-rs deserialize them
+fn decode_bytes(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                let decoded = match field {
+                    Value::String(text) if BYTE_FIELDS.contains(&key.as_str()) => {
+                        base64::engine::general_purpose::STANDARD.decode(text).ok()
+                    }
+                    _ => None,
+                };
+                match decoded {
+                    Some(bytes) => *field = json!(bytes),
+                    None => decode_bytes(field),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(decode_bytes),
+        _ => {}
+    }
+}
+
+/// The detailed report of `store`: the result of a read with detailed=True or
+/// its "detailed" report
+fn detailed_report(store: &Value) -> Option<&Value> {
+    let report = match store.get("detailed") {
+        Some(report) if report.is_object() => report,
+        _ => store,
+    };
+    let manifests = report.get("manifests")?.as_object()?;
+    manifests
+        .values()
+        .all(|manifest| manifest.get("assertion_store").is_some())
+        .then_some(report)
+}
+
+/// The hard binding assertions of the active manifest of `report`, as (label,
+/// assertion data) pairs, with the hash algorithm of its claim
+fn hard_bindings(report: &Value) -> (Vec<(&str, &Value)>, &str) {
+    let manifest = report["active_manifest"]
+        .as_str()
+        .map_or(&Value::Null, |label| &report["manifests"][label]);
+    let alg = manifest["claim"]["alg"].as_str().unwrap_or("sha256");
+    let Some(assertions) = manifest["assertion_store"].as_object() else {
+        return (Vec::new(), alg);
+    };
+    let bindings = assertions
+        .iter()
+        .filter(|(label, _)| label.starts_with("c2pa.hash."))
+        .map(|(label, data)| (label.as_str(), data))
+        .collect();
+    (bindings, alg)
+}
+
+/// The version of a BMFF hash assertion from its label, such as 2 for
+/// "c2pa.hash.bmff.v2"
+fn bmff_version(label: &str) -> usize {
+    label
+        .split(".v")
+        .nth(1)
+        .and_then(|version| version.split("__").next()?.parse().ok())
+        .unwrap_or(1)
+}
+
+/// A BMFF hash assertion of the detailed report
+///
+/// The version of the assertion, which changes how its exclusions apply, is
+/// not part of its data: assertions of version 2 and later are rebuilt with
+/// the version c2pa-rs creates.
+fn bmff_hash(label: &str, assertion: Value, alg: &str) -> serde_json::Result<BmffHash> {
+    let merkle = assertion.get("merkle").cloned();
+    let mut parsed: BmffHash = serde_json::from_value(assertion)?;
+    if bmff_version(label) < 2 {
+        return Ok(parsed);
+    }
+    let name = parsed.name().cloned().unwrap_or_default();
+    let alg = parsed.alg().cloned().unwrap_or_else(|| alg.to_owned());
+    let mut hash = BmffHash::new(&name, &alg, None);
+    *hash.exclusions_mut() = std::mem::take(parsed.exclusions_mut());
+    if let Some(value) = parsed.hash() {
+        hash.set_hash(value.clone());
+    }
+    if let Some(merkle) = merkle.filter(|merkle| !merkle.is_null()) {
+        hash.set_merkle(serde_json::from_value(merkle)?);
+    }
+    Ok(hash)
+}
+
+/// Check one hard binding against `data`, as a binding entry of the result,
+/// with `alg` the hash algorithm of the claim for assertions that name none
+///
+/// Does not touch Python state, so it can run with the GIL released.
+fn check_binding(label: &str, assertion: &Value, alg: &str, data: &[u8]) -> Value {
+    let mut assertion = assertion.clone();
+    decode_bytes(&mut assertion);
+    let kind = label.trim_start_matches("c2pa.hash.");
+    let (name, result) = if kind.starts_with("data") {
+        let result = serde_json::from_value::<DataHash>(assertion)
+            .map_err(c2pa::Error::JsonError)
+            .and_then(|hash| hash.verify_in_memory_hash(data, Some(alg)));
+        ("dataHash", result)
+    } else if kind.starts_with("bmff") {
+        let result = bmff_hash(label, assertion, alg)
+            .map_err(c2pa::Error::JsonError)
+            .and_then(|hash| hash.verify_stream_hash(&mut Cursor::new(data), Some(alg)));
+        ("bmffHash", result)
+    } else {
+        return json!({
+            "label": label,
+            "code": null,
+            "explanation": "only data and BMFF hashes can be rechecked from the report",
+        });
+    };
+    match result {
+        Ok(()) => json!({
+            "label": label,
+            "code": format!("assertion.{name}.match"),
+            "explanation": null,
+        }),
+        Err(e) => json!({
+            "label": label,
+            "code": format!("assertion.{name}.mismatch"),
+            "explanation": e.to_string(),
+        }),
+    }
+}
+
+/// Re-check only the hash bindings of a previously read manifest store
+/// against an asset
+///
+/// The data and BMFF hashes of the active manifest are recomputed over `data`,
+/// which may have changed since the store was read. Signatures, certificates
+/// and trust are not checked again: use it to recheck content integrity, and
+/// read_c2pa_from_bytes for a full validation.
+///
+/// Args:
+///     manifest_store_json: The result of read_c2pa_from_bytes or
+///         read_c2pa_from_file with detailed=True, or its "detailed" report, as
+///         a dict or a JSON string
+///     data: Binary data of the asset (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg"), one c2pa-rs
+///         reads assets of
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A dict with "active_manifest", "validation_state" ("Valid" if every
+///     binding checked matched, "Invalid" if one did not, None if none could be
+///     checked) and "bindings": one dict per hard binding with its "label",
+///     "code" (e.g. "assertion.dataHash.match" or "assertion.bmffHash.mismatch",
+///     None for box hashes, which the report does not hold enough to check) and
+///     "explanation"
+///
+/// Raises:
+///     ValueError: If manifest_store_json is not valid JSON, or has no detailed
+///         report
+///     TypeError: If data is not bytes-like, or not C-contiguous
+///     UnsupportedFormatError: If c2pa-rs does not read assets of mime_type
+#[pyfunction]
+#[pyo3(signature = (manifest_store_json, data, mime_type, allow_threads=None))]
+pub fn revalidate(
+    py: Python,
+    manifest_store_json: &Bound<'_, PyAny>,
+    data: &Bound<'_, PyAny>,
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<PyObject> {
+    let store: Value = match manifest_store_json.downcast::<PyString>() {
+        Ok(text) => serde_json::from_str(text.to_str()?)
+            .map_err(|e| PyValueError::new_err(format!("Invalid manifest_store_json: {e}")))?,
+        Err(_) => py_to_value(py, manifest_store_json)?,
+    };
+    let report = detailed_report(&store).ok_or_else(|| {
+        PyValueError::new_err("manifest_store_json has no detailed report: read with detailed=True")
+    })?;
+    let mime_type = mime_type.to_ascii_lowercase();
+    if !get_supported_types().contains(&mime_type) {
+        return Err(c2pa_error(
+            "Error revalidating",
+            &c2pa::Error::UnsupportedType,
+        ));
+    }
+    let buffer = Buffer::new(data, "data")?;
+    let data = buffer.as_bytes();
+    let (bindings, alg) = hard_bindings(report);
+    let check = || -> Vec<Value> {
+        bindings
+            .iter()
+            .map(|(label, assertion)| check_binding(label, assertion, alg, data))
+            .collect()
+    };
+    let results = if gil::release(allow_threads, data.len()) {
+        py.allow_threads(check)
+    } else {
+        check()
+    };
+
+    let codes: Vec<&str> = results.iter().filter_map(|r| r["code"].as_str()).collect();
+    let state = if codes.is_empty() {
+        None
+    } else if codes.iter().all(|code| code.ends_with(".match")) {
+        Some("Valid")
+    } else {
+        Some("Invalid")
+    };
+    let mut result = Map::new();
+    result.insert("active_manifest".into(), report["active_manifest"].clone());
+    result.insert("validation_state".into(), json!(state));
+    result.insert("bindings".into(), Value::Array(results));
+    value_to_py(py, &Value::Object(result))
+}
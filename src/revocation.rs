@@ -0,0 +1,330 @@
+//! OCSP responses shared between reads
+//!
+//! With the verify.ocsp_fetch setting, c2pa-rs asks the OCSP responder of a
+//! signing certificate for its revocation status on every read, so a burst of
+//! assets from one signer repeats the same request thousands of times. The
+//! responses are fetched here instead, cached by certificate chain for a
+//! configurable time, and stapled to the signature of the active manifest
+//! before c2pa-rs validates it. c2pa-rs takes a stapled response in place of
+//! fetching one and reports its revocation checks as usual.
+//!
+//! Trust decisions are not cached: c2pa-rs builds the certificate chain and
+//! checks it against the trust lists inside the signature validation of every
+//! read, and takes no decision made earlier.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use c2pa_crypto::cose::cert_chain_from_sign1;
+use coset::cbor::Value as Cbor;
+use coset::{CoseSign1, Label, TaggedCborSerializable};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::Value;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use x509_parser::extensions::{GeneralName, ParsedExtension};
+use x509_parser::oid_registry::OID_PKIX_ACCESS_DESCRIPTOR_OCSP;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+use crate::http::fetch_reported;
+use crate::jumbf::{manifest_signatures, with_active_signature};
+use crate::settings;
+
+/// COSE headers of the RFC 3161 time stamps of a signature
+const TIME_STAMP_HEADERS: [&str; 2] = ["sigTst2", "sigTst"];
+/// DER AlgorithmIdentifier of SHA-1, with the NULL parameters many
+/// responders expect
+const SHA1_ALGORITHM: &[u8] = &[
+    0x30, 0x09, 0x06, 0x05, 0x2B, 0x0E, 0x03, 0x02, 0x1A, 0x05, 0x00,
+];
+
+type Key = [u8; 32];
+
+struct Entry {
+    /// The DER OCSP response, None if none could be fetched
+    response: Option<Arc<Vec<u8>>>,
+    /// When the response was fetched, None until it first is
+    fetched: Option<Instant>,
+    /// Held while the response is fetched, so concurrent reads of the same
+    /// signer wait for it rather than fetching it too
+    fetching: Arc<Mutex<()>>,
+}
+
+struct OcspCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: HashMap<Key, Entry>,
+}
+
+static CACHE: LazyLock<Mutex<OcspCache>> = LazyLock::new(|| {
+    Mutex::new(OcspCache {
+        ttl: Duration::from_secs(300),
+        max_entries: 1024,
+        entries: HashMap::new(),
+    })
+});
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+
+fn cache() -> std::sync::MutexGuard<'static, OcspCache> {
+    CACHE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl OcspCache {
+    fn fresh(&self, key: &Key) -> Option<&Entry> {
+        self.entries.get(key).filter(|entry| self.is_fresh(entry))
+    }
+
+    fn is_fresh(&self, entry: &Entry) -> bool {
+        entry
+            .fetched
+            .is_some_and(|fetched| fetched.elapsed() < self.ttl)
+    }
+
+    /// Make room for one more entry, dropping expired ones first and then the
+    /// oldest; entries being fetched are kept
+    fn evict(&mut self) {
+        let ttl = self.ttl;
+        self.entries
+            .retain(|_, entry| entry.fetched.is_none_or(|fetched| fetched.elapsed() < ttl));
+        while self.entries.len() >= self.max_entries.max(1) {
+            let oldest = self
+                .entries
+                .iter()
+                .filter_map(|(key, entry)| Some((key, entry.fetched?)))
+                .min_by_key(|(_, fetched)| *fetched)
+                .map(|(key, _)| *key);
+            match oldest {
+                Some(key) => self.entries.remove(&key),
+                None => break,
+            };
+        }
+    }
+}
+
+/// Hash of a certificate chain, each certificate prefixed by its length
+fn chain_key(chain: &[Vec<u8>]) -> Key {
+    let mut hasher = Sha256::new();
+    for cert in chain {
+        hasher.update((cert.len() as u64).to_be_bytes());
+        hasher.update(cert);
+    }
+    hasher.finalize().into()
+}
+
+/// A DER element of `tag` holding `content`
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut element = vec![tag];
+    match content.len() {
+        len @ 0..=0x7F => element.push(len as u8),
+        len => {
+            let bytes = len.to_be_bytes();
+            let skip = bytes.iter().take_while(|&&b| b == 0).count();
+            element.push(0x80 | (bytes.len() - skip) as u8);
+            element.extend_from_slice(&bytes[skip..]);
+        }
+    }
+    element.extend_from_slice(content);
+    element
+}
+
+/// The OCSP request for the status of `cert` issued by `issuer`, and the
+/// responders named by `cert`, as c2pa-rs builds and sends them
+fn ocsp_request(cert: &[u8], issuer: &[u8]) -> Option<(Vec<u8>, Vec<String>)> {
+    let (_, cert) = X509Certificate::from_der(cert).ok()?;
+    let (_, issuer) = X509Certificate::from_der(issuer).ok()?;
+    let mut responders = Vec::new();
+    for extension in cert.extensions() {
+        if let ParsedExtension::AuthorityInfoAccess(aia) = extension.parsed_extension() {
+            for access in &aia.accessdescs {
+                if let GeneralName::URI(uri) = access.access_location {
+                    if access.access_method == OID_PKIX_ACCESS_DESCRIPTOR_OCSP {
+                        responders.push(uri.to_owned());
+                    }
+                }
+            }
+        }
+    }
+    if responders.is_empty() {
+        return None;
+    }
+
+    let name_hash = Sha1::digest(issuer.subject().as_raw());
+    let key_hash = Sha1::digest(&issuer.public_key().subject_public_key.data);
+    let mut cert_id = SHA1_ALGORITHM.to_vec();
+    cert_id.extend(der(0x04, &name_hash));
+    cert_id.extend(der(0x04, &key_hash));
+    cert_id.extend(der(0x02, cert.tbs_certificate.raw_serial()));
+    // The CertID in a Request, in the requestList of the TBSRequest of an
+    // OCSPRequest, all sequences
+    let request = (0..4).fold(der(0x30, &cert_id), |inner, _| der(0x30, &inner));
+    Some((request, responders))
+}
+
+/// Ask the responders of the signing certificate of `chain` for its status
+fn fetch(chain: &[Vec<u8>]) -> Option<Vec<u8>> {
+    let (request, responders) = ocsp_request(chain.first()?, chain.get(1)?)?;
+    let encoded = STANDARD.encode(request);
+    responders.iter().find_map(|responder| {
+        let url = url::Url::parse(responder).ok()?.join(&encoded).ok()?;
+        fetch_reported(url.as_str()).ok().map(|(body, _)| body)
+    })
+}
+
+/// The cached response for `chain`, fetched if it is not cached or expired
+fn response(chain: &[Vec<u8>]) -> Option<Arc<Vec<u8>>> {
+    let key = chain_key(chain);
+    let fetching = {
+        let mut cache = cache();
+        if let Some(entry) = cache.fresh(&key) {
+            HITS.fetch_add(1, Ordering::Relaxed);
+            return entry.response.clone();
+        }
+        match cache.entries.get(&key) {
+            Some(entry) => entry.fetching.clone(),
+            None => {
+                cache.evict();
+                let fetching = Arc::new(Mutex::new(()));
+                cache.entries.insert(
+                    key,
+                    Entry {
+                        response: None,
+                        fetched: None,
+                        fetching: fetching.clone(),
+                    },
+                );
+                fetching
+            }
+        }
+    };
+    let _fetching = fetching.lock().unwrap_or_else(|e| e.into_inner());
+    // Another read may have fetched it while this one waited
+    if let Some(entry) = cache().fresh(&key) {
+        HITS.fetch_add(1, Ordering::Relaxed);
+        return entry.response.clone();
+    }
+    MISSES.fetch_add(1, Ordering::Relaxed);
+    let response = fetch(chain).map(Arc::new);
+    if let Some(entry) = cache().entries.get_mut(&key) {
+        entry.response = response.clone();
+        entry.fetched = Some(Instant::now());
+    }
+    response
+}
+
+/// Whether `sign1` has a time stamp to check a stapled response at
+fn has_time_stamp(sign1: &CoseSign1) -> bool {
+    sign1.unprotected.rest.iter().any(|(label, _)| {
+        TIME_STAMP_HEADERS
+            .iter()
+            .any(|name| *label == Label::Text((*name).to_owned()))
+    })
+}
+
+/// `signature` with `response` in its rVals header, or None if it has a
+/// response already
+fn stapled_signature(sign1: &mut CoseSign1, response: &[u8]) -> Option<Vec<u8>> {
+    let headers = &mut sign1.unprotected.rest;
+    let r_vals = Label::Text("rVals".to_owned());
+    let ocsp_vals = (
+        Cbor::Text("ocspVals".to_owned()),
+        Cbor::Array(vec![Cbor::Bytes(response.to_vec())]),
+    );
+    match headers.iter_mut().find(|(label, _)| *label == r_vals) {
+        Some((_, Cbor::Map(values))) => {
+            if values
+                .iter()
+                .any(|(name, _)| *name == Cbor::Text("ocspVals".to_owned()))
+            {
+                return None;
+            }
+            values.push(ocsp_vals);
+        }
+        Some(_) => return None,
+        None => headers.push((r_vals, Cbor::Map(vec![ocsp_vals]))),
+    }
+    sign1.clone().to_tagged_vec().ok()
+}
+
+/// `store` with a cached OCSP response stapled to its active signature
+///
+/// c2pa-rs only checks a stapled response against the time stamp of the
+/// signature, and fetches one itself for signatures without a time stamp,
+/// so those are left as they are, as are signatures stapled when signed.
+/// Only the active manifest is stapled: the ingredient manifests of a store
+/// can be referenced by hashes of their signatures.
+pub(crate) fn staple(store: &[u8]) -> Cow<'_, [u8]> {
+    let enabled =
+        !cache().ttl.is_zero() && settings::value("/verify/ocsp_fetch") == Some(Value::Bool(true));
+    let stapled = enabled
+        .then(|| {
+            let (_, signature) = manifest_signatures(store).pop()?;
+            let mut sign1 = CoseSign1::from_tagged_slice(signature).ok()?;
+            if !has_time_stamp(&sign1) {
+                return None;
+            }
+            let chain = cert_chain_from_sign1(&sign1).ok()?;
+            let response = response(&chain)?;
+            let signature = stapled_signature(&mut sign1, &response)?;
+            with_active_signature(store, &signature)
+        })
+        .flatten();
+    match stapled {
+        Some(stapled) => Cow::Owned(stapled),
+        None => Cow::Borrowed(store),
+    }
+}
+
+/// Cache the OCSP responses of signing certificates between reads
+///
+/// Applies once OCSP fetching is enabled with
+/// load_c2pa_settings('{"verify": {"ocsp_fetch": true}}'). The response for a
+/// certificate chain is fetched once and reused by the reads of the assets it
+/// signed until it expires, so the revocation of a certificate is noticed
+/// within ttl_seconds. The cache is on by default. Only the responses are
+/// cached: the certificate chain is still built and checked against the trust
+/// lists on every read.
+///
+/// Args:
+///     ttl_seconds: How long a response is reused, or 0 to let c2pa-rs fetch
+///         one on every read (default: 300)
+///     max_entries: Most certificate chains cached; the oldest responses are
+///         dropped first (default: 1024)
+#[pyfunction]
+#[pyo3(signature = (ttl_seconds=300, max_entries=1024))]
+pub fn set_ocsp_cache(ttl_seconds: u64, max_entries: usize) {
+    let mut cache = cache();
+    cache.ttl = Duration::from_secs(ttl_seconds);
+    cache.max_entries = max_entries;
+    cache.evict();
+}
+
+/// Drop every cached OCSP response, to check revocation again on the next read
+#[pyfunction]
+pub fn clear_ocsp_cache() {
+    cache().entries.clear();
+}
+
+/// Get the state of the OCSP response cache
+///
+/// Returns:
+///     A dict with "ttl_seconds", "max_entries", "entries" (the certificate
+///     chains cached, expired or not), "hits" (reads that reused a response)
+///     and "misses" (responses fetched)
+#[pyfunction]
+pub fn get_ocsp_cache_stats(py: Python) -> PyResult<PyObject> {
+    let cache = cache();
+    let stats = PyDict::new(py);
+    stats.set_item("ttl_seconds", cache.ttl.as_secs())?;
+    stats.set_item("max_entries", cache.max_entries)?;
+    stats.set_item("entries", cache.entries.len())?;
+    stats.set_item("hits", HITS.load(Ordering::Relaxed))?;
+    stats.set_item("misses", MISSES.load(Ordering::Relaxed))?;
+    Ok(stats.into_any().unbind())
+}
@@ -0,0 +1,232 @@
+//! Validation findings as SARIF
+//!
+//! Security teams collect findings from code scanners and audits in SARIF
+//! 2.1.0 logs and already have dashboards and triage built on them. Each
+//! validation status code of a result becomes a SARIF result, its code the
+//! rule, so provenance checks land in the same place as other findings.
+
+use std::collections::BTreeMap;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::{json, Map, Value};
+
+use crate::c2pa_reader::py_to_value;
+use crate::errors::C2paError;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const INFORMATION_URI: &str = "https://github.com/Sightengine/fast_c2pa_python";
+
+/// A validation status entry of a result, with its SARIF level and kind
+pub(crate) struct Finding<'a> {
+    pub(crate) status: &'a Value,
+    level: &'static str,
+    kind: &'static str,
+    /// The list the status is in: "success", "informational" or "failure" of
+    /// the validation results, or "warning" for the library's own warnings
+    pub(crate) outcome: &'static str,
+    /// URI of the ingredient the status is about, for ingredient deltas
+    pub(crate) ingredient: Option<&'a str>,
+}
+
+impl Finding<'_> {
+    /// Label of the manifest the status is about, if it tells
+    pub(crate) fn manifest(&self) -> Option<&str> {
+        self.status
+            .get("label")
+            .and_then(Value::as_str)
+            .or_else(|| {
+                let url = self.status.get("url").and_then(Value::as_str)?;
+                manifest_label(url)
+            })
+    }
+}
+
+/// The status lists of a validation results object: (key, level, kind). SARIF
+/// only gives a level to problems, results of kind "fail"
+const STATUS_LISTS: [(&str, &str, &str); 3] = [
+    ("failure", "error", "fail"),
+    ("informational", "note", "fail"),
+    ("success", "none", "pass"),
+];
+
+fn status_findings<'a>(
+    statuses: &'a Value,
+    ingredient: Option<&'a str>,
+    include_success: bool,
+    findings: &mut Vec<Finding<'a>>,
+) {
+    for (key, level, kind) in STATUS_LISTS {
+        if key == "success" && !include_success {
+            continue;
+        }
+        let list = statuses.get(key).and_then(Value::as_array);
+        findings.extend(list.into_iter().flatten().map(|status| Finding {
+            status,
+            level,
+            kind,
+            outcome: key,
+            ingredient,
+        }));
+    }
+}
+
+/// The findings of a read result: its validation statuses, those of its
+/// ingredients, and the library's own "warnings"
+pub(crate) fn findings(result: &Value, include_success: bool) -> Vec<Finding<'_>> {
+    let mut findings = Vec::new();
+    match result.get("validation_results") {
+        Some(results) => {
+            if let Some(active) = results.get("activeManifest") {
+                status_findings(active, None, include_success, &mut findings);
+            }
+            let deltas = results.get("ingredientDeltas").and_then(Value::as_array);
+            for delta in deltas.into_iter().flatten() {
+                let uri = delta.get("uri").and_then(Value::as_str);
+                if let Some(statuses) = delta.get("validationDeltas") {
+                    status_findings(statuses, uri, include_success, &mut findings);
+                }
+            }
+        }
+        // Layouts without validation_results list the failures only
+        None => {
+            let list = result.get("validation_status").and_then(Value::as_array);
+            findings.extend(list.into_iter().flatten().map(|status| Finding {
+                status,
+                level: "error",
+                kind: "fail",
+                outcome: "failure",
+                ingredient: None,
+            }));
+        }
+    }
+    let warnings = result.get("warnings").and_then(Value::as_array);
+    findings.extend(warnings.into_iter().flatten().map(|status| Finding {
+        status,
+        level: "warning",
+        kind: "fail",
+        outcome: "warning",
+        ingredient: None,
+    }));
+    findings
+}
+
+/// The manifest label in a JUMBF URI such as "self#jumbf=/c2pa/<label>/c2pa.signature"
+fn manifest_label(url: &str) -> Option<&str> {
+    let path = url.split_once("jumbf=")?.1;
+    path.strip_prefix("/c2pa/")?.split('/').next()
+}
+
+/// Build a SARIF log of the findings of `results`, in artifact order
+fn sarif_log(results: &[(String, Value)], include_success: bool) -> Value {
+    let mut rules: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut ordered_rules = Vec::new();
+    let mut sarif_results = Vec::new();
+    for (index, (uri, result)) in results.iter().enumerate() {
+        for finding in findings(result, include_success) {
+            let status = finding.status;
+            let code = status
+                .get("code")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown");
+            let rule_index = *rules.entry(code).or_insert_with(|| {
+                ordered_rules.push(json!({ "id": code }));
+                ordered_rules.len() - 1
+            });
+            let url = status.get("url").and_then(Value::as_str);
+            let explanation = status
+                .get("explanation")
+                .and_then(Value::as_str)
+                .unwrap_or(code);
+
+            let mut properties = Map::new();
+            properties.insert("manifest".into(), json!(finding.manifest()));
+            if let Some(ingredient) = finding.ingredient {
+                properties.insert("ingredient".into(), json!(ingredient));
+            }
+            properties.insert(
+                "validation_state".into(),
+                result
+                    .get("validation_state")
+                    .cloned()
+                    .unwrap_or(Value::Null),
+            );
+
+            let mut location = json!({
+                "physicalLocation": {
+                    "artifactLocation": { "uri": uri, "index": index },
+                },
+            });
+            if let Some(url) = url {
+                location["logicalLocations"] =
+                    json!([{ "fullyQualifiedName": url, "kind": "element" }]);
+            }
+            sarif_results.push(json!({
+                "ruleId": code,
+                "ruleIndex": rule_index,
+                "kind": finding.kind,
+                "level": finding.level,
+                "message": { "text": explanation },
+                "locations": [location],
+                "properties": properties,
+            }));
+        }
+    }
+
+    let artifacts: Vec<Value> = results
+        .iter()
+        .map(|(uri, _)| json!({ "location": { "uri": uri } }))
+        .collect();
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "fast-c2pa-python",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": INFORMATION_URI,
+                    "rules": ordered_rules,
+                },
+            },
+            "artifacts": artifacts,
+            "results": sarif_results,
+        }],
+    })
+}
+
+/// Render the validation findings of read results as a SARIF 2.1.0 log
+///
+/// Each validation status code becomes a result whose rule is the code:
+/// failures are errors, informational codes notes and the library's own
+/// "warnings" (truncation, version limits, transparency log) warnings. The
+/// statuses of ingredients carry the ingredient URI in their properties, and
+/// the JUMBF URI a status refers to is its logical location.
+///
+/// Args:
+///     results: A dict mapping each asset's URI or path to its result from
+///         read_c2pa_from_bytes, or None for assets without C2PA metadata (listed
+///         as artifacts without findings)
+///     include_success: Also report success codes, as results of kind "pass"
+///         (default: False)
+///
+/// Returns:
+///     The SARIF log as a JSON string
+///
+/// Raises:
+///     TypeError: If a result contains values that are not JSON serializable
+#[pyfunction]
+#[pyo3(signature = (results, include_success=false))]
+pub fn to_sarif(
+    py: Python,
+    results: &Bound<'_, PyDict>,
+    include_success: bool,
+) -> PyResult<String> {
+    let results = results
+        .iter()
+        .map(|(uri, result)| Ok((uri.str()?.to_string(), py_to_value(py, &result)?)))
+        .collect::<PyResult<Vec<_>>>()?;
+    let log = py.allow_threads(|| sarif_log(&results, include_success));
+    serde_json::to_string(&log)
+        .map_err(|e| C2paError::new_err(format!("Error serializing SARIF log: {e}")))
+}
@@ -0,0 +1,421 @@
+//! Bulk scans written straight to disk
+//!
+//! Audits of millions of files keep one record per asset, and building the
+//! records as Python dicts only to serialize them again costs more than the
+//! reads. The scan here reads each file, serializes its record and appends it
+//! to the output in Rust; Python only hands over the paths.
+//!
+//! Archives are scanned again as they grow, so a scan can keep an index of the
+//! files it read: their modification time, content hash and result. The next
+//! scan reads only the files whose time changed, and parses only those whose
+//! content did.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::c2pa_reader::{file_mime_type, read_store, read_value};
+use crate::digest::{digest_fields, field_json};
+use crate::gil;
+use crate::reputation::hex;
+
+/// Version of the index layout, in its header line
+const INDEX_VERSION: u64 = 1;
+
+enum Format {
+    JsonLines,
+    Csv,
+}
+
+/// Quote a CSV field when it holds a separator, quote or line break (RFC 4180)
+fn csv_field(value: &Value) -> String {
+    let text = match value {
+        Value::Null => return String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+fn csv_line(values: impl IntoIterator<Item = Value>) -> String {
+    let fields: Vec<String> = values.into_iter().map(|value| csv_field(&value)).collect();
+    fields.join(",") + "\r\n"
+}
+
+/// Read one asset, through read_value for full results or read_store for
+/// digests, as read_c2pa_digest does
+fn read_path(
+    py: Python,
+    path: &Path,
+    mime_type: Option<&str>,
+    digest: bool,
+) -> PyResult<Option<Value>> {
+    let data = py.allow_threads(|| std::fs::read(path))?;
+    parse_asset(py, path, &data, mime_type, digest)
+}
+
+fn parse_asset(
+    py: Python,
+    path: &Path,
+    data: &[u8],
+    mime_type: Option<&str>,
+    digest: bool,
+) -> PyResult<Option<Value>> {
+    let mime_type = match mime_type {
+        Some(mime_type) => mime_type.to_owned(),
+        None => file_mime_type(data, path),
+    };
+    let allow_threads = gil::release(None, data.len());
+    if digest {
+        read_store(py, data, &mime_type, allow_threads)
+    } else {
+        read_value(py, data, &mime_type, allow_threads)
+    }
+}
+
+/// A file as a scan with an index read it
+struct Indexed {
+    mtime: u64,
+    sha256: String,
+    result: Option<Value>,
+    error: Option<String>,
+}
+
+impl Indexed {
+    fn line(&self, path: &str) -> String {
+        json!({
+            "path": path,
+            "mtime": self.mtime,
+            "sha256": self.sha256,
+            "result": self.result,
+            "error": self.error,
+        })
+        .to_string()
+            + "\n"
+    }
+}
+
+/// How a file changed since the scan that wrote the index
+#[derive(Clone, Copy)]
+enum Change {
+    New,
+    Modified,
+    Unchanged,
+}
+
+impl Change {
+    fn name(self) -> &'static str {
+        match self {
+            Change::New => "new",
+            Change::Modified => "modified",
+            Change::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// The entries of the index at `path`, empty if there is none yet, and
+/// whether their results can be reused. Results of full scans and of digest
+/// scans differ, so an index written by the other kind of scan only tells
+/// which files changed.
+fn load_index(path: &Path, digest: bool) -> PyResult<(HashMap<String, Indexed>, bool)> {
+    let invalid = |reason: &str| {
+        PyValueError::new_err(format!("Invalid scan index {}: {reason}", path.display()))
+    };
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((HashMap::new(), true)),
+        Err(e) => return Err(e.into()),
+    };
+    let mut lines = BufReader::new(file).lines();
+    let header: Value = match lines.next() {
+        Some(line) => serde_json::from_str(&line?).map_err(|e| invalid(&e.to_string()))?,
+        None => return Ok((HashMap::new(), true)),
+    };
+    if header["index_version"] != INDEX_VERSION {
+        return Err(invalid("not written by scan_to_file"));
+    }
+    let mut entries = HashMap::new();
+    for line in lines {
+        let entry: Value = serde_json::from_str(&line?).map_err(|e| invalid(&e.to_string()))?;
+        let (Some(path), Some(mtime), Some(sha256)) = (
+            entry["path"].as_str(),
+            entry["mtime"].as_u64(),
+            entry["sha256"].as_str(),
+        ) else {
+            return Err(invalid("entry without path, mtime or sha256"));
+        };
+        let result = Some(entry["result"].clone()).filter(|result| !result.is_null());
+        let error = entry["error"].as_str().map(str::to_owned);
+        let indexed = Indexed {
+            mtime,
+            sha256: sha256.to_owned(),
+            result,
+            error,
+        };
+        entries.insert(path.to_owned(), indexed);
+    }
+    Ok((entries, header["digest"] == digest))
+}
+
+/// Read one asset unless the index has it with the same modification time,
+/// and parse it unless the index has its content
+fn read_indexed(
+    py: Python,
+    path: &Path,
+    mime_type: Option<&str>,
+    digest: bool,
+    previous: Option<Indexed>,
+    reuse: bool,
+) -> PyResult<(Change, Indexed)> {
+    let modified = py.allow_threads(|| std::fs::metadata(path)?.modified())?;
+    let mtime = modified
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_nanos() as u64);
+    let previous = match previous {
+        Some(previous) if reuse && previous.mtime == mtime => {
+            return Ok((Change::Unchanged, previous))
+        }
+        previous => previous,
+    };
+
+    let data = py.allow_threads(|| std::fs::read(path))?;
+    let sha256 = hex(&Sha256::digest(&data));
+    match previous {
+        Some(previous) if previous.sha256 == sha256 && reuse => {
+            Ok((Change::Unchanged, Indexed { mtime, ..previous }))
+        }
+        previous => {
+            let change = match previous {
+                None => Change::New,
+                Some(previous) if previous.sha256 == sha256 => Change::Unchanged,
+                Some(_) => Change::Modified,
+            };
+            let (result, error) = match parse_asset(py, path, &data, mime_type, digest) {
+                Ok(result) => (result, None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            let indexed = Indexed {
+                mtime,
+                sha256,
+                result,
+                error,
+            };
+            Ok((change, indexed))
+        }
+    }
+}
+
+/// The index a scan writes, replacing the previous one once it is complete
+struct IndexWriter {
+    path: PathBuf,
+    partial: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl IndexWriter {
+    fn create(path: PathBuf, digest: bool) -> PyResult<Self> {
+        let mut partial = path.clone().into_os_string();
+        partial.push(".partial");
+        let partial = PathBuf::from(partial);
+        let mut writer = BufWriter::new(File::create(&partial)?);
+        let header = json!({ "index_version": INDEX_VERSION, "digest": digest });
+        writer.write_all((header.to_string() + "\n").as_bytes())?;
+        Ok(Self {
+            path,
+            partial,
+            writer,
+        })
+    }
+
+    fn finish(mut self) -> PyResult<()> {
+        self.writer.flush()?;
+        std::fs::rename(&self.partial, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Read the C2PA data of many files and write one record per file to `output`
+///
+/// The paths are taken from the iterable one at a time and each record is
+/// written as soon as its file is read, so memory stays flat however many
+/// files are scanned. Errors reading a file are recorded in its "error" field
+/// and the scan goes on.
+///
+/// JSON Lines records are {"path", "result", "error"}, "result" being what
+/// read_c2pa_from_bytes returns, or {"path", <fields>..., "error"} with
+/// `fields`. CSV files have a header row and a column per field; CSV records
+/// are always digests.
+///
+/// With `index`, the scan keeps the modification time, SHA-256 hash and result
+/// of every file it read in that file, and the next scan with the same index
+/// takes the results of files whose time or content did not change from it
+/// instead of reading them again. Records then have a "change" field (a
+/// column before "error" in CSV files): "new", "modified" or "unchanged" since
+/// the scan that wrote the index. Files are known by their path as given, and
+/// results by the trust settings they were read with: delete the index when
+/// the settings change. Files that cannot be read are left out of the index and
+/// retried next time.
+///
+/// Args:
+///     paths: An iterable of file paths (str or os.PathLike)
+///     output: Path of the file to write, replaced if it exists
+///     format: "jsonl" or "csv" (default: "jsonl")
+///     fields: Digest fields of each record, as for read_c2pa_digest (default: None,
+///         the full result for "jsonl", and "signer", "generator" and "ai_flag"
+///         for "csv")
+///     mime_type: MIME type of every file (default: None, detected from each
+///         file's content, else from its extension, as by read_c2pa_from_file)
+///     index: Path of the index file, created if it does not exist (default:
+///         None, read every file)
+///
+/// Returns:
+///     A dict with the counts of "assets" scanned, "with_c2pa" and "errors",
+///     and with `index` the counts of "new", "modified" and "unchanged" files
+///     and of the files of the index that were "removed": not scanned this
+///     time, or no longer readable
+///
+/// Raises:
+///     ValueError: If format or a field is not one of the above, or index is
+///         not a scan index
+///     OSError: If the output or the index cannot be written
+#[pyfunction]
+#[pyo3(signature = (paths, output, format="jsonl", fields=None, mime_type=None, index=None))]
+pub fn scan_to_file(
+    py: Python,
+    paths: &Bound<'_, PyAny>,
+    output: PathBuf,
+    format: &str,
+    fields: Option<Vec<String>>,
+    mime_type: Option<&str>,
+    index: Option<PathBuf>,
+) -> PyResult<PyObject> {
+    let format = match format {
+        "jsonl" => Format::JsonLines,
+        "csv" => Format::Csv,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "format must be \"jsonl\" or \"csv\", not {format:?}"
+            )))
+        }
+    };
+    let fields = match (&format, fields) {
+        (Format::JsonLines, None) => None,
+        (_, fields) => Some(digest_fields(fields)?),
+    };
+
+    let digest = fields.is_some();
+    let (mut previous, reuse) = match &index {
+        Some(index) => load_index(index, digest)?,
+        None => (HashMap::new(), false),
+    };
+    let mut index = index
+        .map(|index| IndexWriter::create(index, digest))
+        .transpose()?;
+
+    let mut writer = BufWriter::new(File::create(&output)?);
+    if let (Format::Csv, Some(fields)) = (&format, &fields) {
+        let header = std::iter::once("path")
+            .chain(fields.iter().map(String::as_str))
+            .chain(index.as_ref().map(|_| "change"))
+            .chain(["error"])
+            .map(|name| json!(name));
+        writer.write_all(csv_line(header).as_bytes())?;
+    }
+
+    let (mut assets, mut with_c2pa, mut errors) = (0usize, 0usize, 0usize);
+    let (mut new, mut modified, mut unchanged, mut removed) = (0usize, 0usize, 0usize, 0usize);
+    for path in paths.try_iter()? {
+        let path: PathBuf = path?.extract()?;
+        assets += 1;
+        let (result, change, error) = match &mut index {
+            None => match read_path(py, &path, mime_type, digest) {
+                Ok(result) => (result, None, None),
+                Err(e) => (None, None, Some(e.to_string())),
+            },
+            Some(index) => {
+                let known = previous.remove(&*path.to_string_lossy());
+                let was_known = known.is_some();
+                match read_indexed(py, &path, mime_type, digest, known, reuse) {
+                    Ok((change, indexed)) => {
+                        let counter = match change {
+                            Change::New => &mut new,
+                            Change::Modified => &mut modified,
+                            Change::Unchanged => &mut unchanged,
+                        };
+                        *counter += 1;
+                        let line = indexed.line(&path.to_string_lossy());
+                        index.writer.write_all(line.as_bytes())?;
+                        (indexed.result, Some(change.name()), indexed.error)
+                    }
+                    Err(e) => {
+                        removed += usize::from(was_known);
+                        (None, None, Some(e.to_string()))
+                    }
+                }
+            }
+        };
+        with_c2pa += usize::from(result.is_some());
+        errors += usize::from(error.is_some());
+
+        let path = path.to_string_lossy();
+        // A failed read has no answers, not the answers of an asset without C2PA
+        let digest = |field: &String| match &error {
+            Some(_) => Value::Null,
+            None => field_json(field, result.as_ref()),
+        };
+        let line = match (&format, &fields) {
+            (Format::JsonLines, fields) => {
+                let mut record = Map::new();
+                record.insert("path".into(), json!(path));
+                match fields {
+                    None => {
+                        record.insert("result".into(), json!(result));
+                    }
+                    Some(fields) => {
+                        for field in fields {
+                            record.insert(field.clone(), digest(field));
+                        }
+                    }
+                }
+                if let Some(change) = change {
+                    record.insert("change".into(), json!(change));
+                }
+                record.insert("error".into(), json!(error));
+                Value::Object(record).to_string() + "\n"
+            }
+            (Format::Csv, fields) => csv_line(
+                std::iter::once(json!(path))
+                    .chain(fields.iter().flatten().map(digest))
+                    .chain(change.map(|change| json!(change)))
+                    .chain([json!(error)]),
+            ),
+        };
+        writer.write_all(line.as_bytes())?;
+    }
+    writer.flush()?;
+
+    let counts = PyDict::new(py);
+    counts.set_item("assets", assets)?;
+    counts.set_item("with_c2pa", with_c2pa)?;
+    counts.set_item("errors", errors)?;
+    if let Some(index) = index {
+        index.finish()?;
+        counts.set_item("new", new)?;
+        counts.set_item("modified", modified)?;
+        counts.set_item("unchanged", unchanged)?;
+        counts.set_item("removed", removed + previous.len())?;
+    }
+    Ok(counts.into_any().unbind())
+}
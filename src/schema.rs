@@ -0,0 +1,125 @@
+//! Versioned output schemas
+//!
+//! Results follow the layout c2pa-rs serializes its manifest stores to, which
+//! changes between releases: keys appear only when set, move around, or change
+//! spelling. Callers that ask for a schema version get a fixed layout instead,
+//! translated from whatever the linked c2pa-rs produces.
+//!
+//! Version 1 is the layout of c2pa-rs 0.49 with every documented key present,
+//! set to None (or an empty list) when the manifest store has no value for it.
+//! Keys added by the library's own hooks and options are kept as they are.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+use serde_json::{json, Map, Value};
+
+/// The schema versions that can be requested, oldest first
+pub const SCHEMA_VERSIONS: [u32; 1] = [1];
+
+/// Result keys of schema version 1, with their value when missing
+fn result_defaults() -> [(&'static str, Value); 6] {
+    [
+        ("active_manifest", Value::Null),
+        ("manifests", json!({})),
+        ("validation_state", Value::Null),
+        ("validation_status", json!([])),
+        ("validation_results", Value::Null),
+        ("remote_url", Value::Null),
+    ]
+}
+
+/// Manifest keys of schema version 1, with their value when missing
+fn manifest_defaults() -> [(&'static str, Value); 15] {
+    [
+        ("label", Value::Null),
+        ("vendor", Value::Null),
+        ("title", Value::Null),
+        ("format", Value::Null),
+        ("instance_id", Value::Null),
+        ("claim_generator", Value::Null),
+        ("claim_generator_info", json!([])),
+        ("metadata", json!([])),
+        ("thumbnail", Value::Null),
+        ("ingredients", json!([])),
+        ("assertions", json!([])),
+        ("redactions", json!([])),
+        ("credentials", json!([])),
+        ("signature_info", Value::Null),
+        ("resources", Value::Null),
+    ]
+}
+
+const SIGNATURE_INFO_KEYS: [&str; 4] = ["alg", "issuer", "cert_serial_number", "time"];
+
+fn fill(map: &mut Map<String, Value>, defaults: impl IntoIterator<Item = (&'static str, Value)>) {
+    for (key, default) in defaults {
+        let value = map.entry(key).or_insert(Value::Null);
+        if value.is_null() {
+            *value = default;
+        }
+    }
+}
+
+/// The claim generator string of a v2 claim, which only has claim_generator_info
+pub(crate) fn claim_generator(info: &Value) -> Option<String> {
+    let first = info.as_array()?.first()?;
+    let name = first.get("name")?.as_str()?;
+    Some(match first.get("version").and_then(Value::as_str) {
+        Some(version) => format!("{name}/{version}"),
+        None => name.to_owned(),
+    })
+}
+
+/// Signing algorithms as c2pa-rs 0.49 spells them ("Ps256", "Ed25519")
+fn normalize_alg(alg: &str) -> String {
+    let mut chars = alg.chars();
+    match chars.next() {
+        Some(first) => {
+            first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+        }
+        None => String::new(),
+    }
+}
+
+fn translate_manifest_v1(manifest: &mut Map<String, Value>) {
+    fill(manifest, manifest_defaults());
+    if manifest["claim_generator"].is_null() {
+        manifest["claim_generator"] = json!(claim_generator(&manifest["claim_generator_info"]));
+    }
+    if let Some(info) = manifest["signature_info"].as_object_mut() {
+        for key in SIGNATURE_INFO_KEYS {
+            info.entry(key).or_insert(Value::Null);
+        }
+        if let Some(alg) = info["alg"].as_str() {
+            info["alg"] = json!(normalize_alg(alg));
+        }
+    }
+}
+
+/// Check that `schema_version` can be requested, before reading anything
+pub fn check_version(schema_version: Option<u32>) -> PyResult<()> {
+    match schema_version {
+        Some(version) if !SCHEMA_VERSIONS.contains(&version) => Err(PyValueError::new_err(format!(
+            "Unsupported schema version {version}, expected one of {SCHEMA_VERSIONS:?}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Translate a result to the layout of `schema_version`, checked with
+/// [`check_version`]
+///
+/// Without a version the result is left in the layout of the linked c2pa-rs.
+/// With one, the result carries it as "schema_version".
+pub fn translate(value: &mut Value, schema_version: Option<u32>) {
+    let (Some(version), Some(result)) = (schema_version, value.as_object_mut()) else {
+        return;
+    };
+    fill(result, result_defaults());
+    if let Some(manifests) = result["manifests"].as_object_mut() {
+        for manifest in manifests.values_mut().filter_map(Value::as_object_mut) {
+            translate_manifest_v1(manifest);
+        }
+    }
+    result.insert("schema_version".to_owned(), json!(version));
+}
@@ -0,0 +1,61 @@
+use serde_json::{Map, Value};
+
+/// Return a copy of `value` with the keys of every object sorted
+///
+/// serde_json keeps insertion order in this build (another dependency enables
+/// `preserve_order`), and c2pa-rs fills several maps from a `HashMap`, so the
+/// natural key order can change from one run to the next.
+pub fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let mut sorted = Map::with_capacity(entries.len());
+            for (key, item) in entries {
+                sorted.insert(key.clone(), sort_keys(item));
+            }
+            Value::Object(sorted)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sort_keys).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Serialize `value` as compact JSON with sorted keys
+///
+/// Numbers are written by serde_json's shortest round-trip formatter, which is
+/// stable across platforms.
+pub fn to_canonical_json(value: &Value) -> serde_json::Result<String> {
+    serde_json::to_string(&sort_keys(value))
+}
+
+/// Encode `value` in the requested wire format, optionally compressed
+///
+/// `format` is "json", "msgpack" or "protobuf"; `compress` is `None` or "zstd".
+pub fn encode(
+    value: &Value,
+    format: &str,
+    compress: Option<&str>,
+    canonical: bool,
+) -> Result<Vec<u8>, String> {
+    let sorted;
+    let value = if canonical {
+        sorted = sort_keys(value);
+        &sorted
+    } else {
+        value
+    };
+
+    let encoded = match format {
+        "json" => serde_json::to_vec(value).map_err(|e| e.to_string())?,
+        "msgpack" => rmp_serde::to_vec_named(value).map_err(|e| e.to_string())?,
+        "protobuf" => crate::proto::encode_manifest_store(value),
+        other => return Err(format!("Unsupported output format: {other}")),
+    };
+
+    match compress {
+        None => Ok(encoded),
+        Some("zstd") => zstd::encode_all(encoded.as_slice(), 0).map_err(|e| e.to_string()),
+        Some(other) => Err(format!("Unsupported compression: {other}")),
+    }
+}
@@ -0,0 +1,96 @@
+//! C2PA settings shared by all threads
+//!
+//! c2pa-rs keeps its settings per thread, so trust settings loaded on the main
+//! thread would not apply to reads from worker threads such as those of
+//! read_c2pa_many. Each load merges into the settings of the calling thread, so
+//! the loads are merged here the same way, and the result is applied to a thread
+//! before it reads whenever it changed since that thread last read.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::serialize::to_canonical_json;
+
+/// Bumped on each load, so threads can tell their settings are stale
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+/// All settings loaded so far, merged
+static SETTINGS: Mutex<Value> = Mutex::new(Value::Null);
+/// Hash of the merged settings, part of every key of the shared cache since
+/// trust settings change validation results
+static DIGEST: LazyLock<Mutex<[u8; 32]>> =
+    LazyLock::new(|| Mutex::new(Sha256::digest("{}").into()));
+/// Reads that found their thread's settings current, or had to apply them
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static APPLIED: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Load settings on this thread and record them for the other threads
+pub fn load(settings_json: &str) -> c2pa::Result<()> {
+    let mut settings = SETTINGS.lock().unwrap_or_else(|e| e.into_inner());
+    c2pa::settings::load_settings_from_str(settings_json, "json")?;
+    if let Ok(Value::Object(loaded)) = serde_json::from_str(settings_json) {
+        match &mut *settings {
+            Value::Object(merged) => merge(merged, loaded),
+            _ => *settings = Value::Object(loaded),
+        }
+    }
+    if let Ok(merged) = to_canonical_json(&settings) {
+        *DIGEST.lock().unwrap_or_else(|e| e.into_inner()) = Sha256::digest(merged).into();
+    }
+    let generation = GENERATION.fetch_add(1, Ordering::AcqRel) + 1;
+    APPLIED.with(|applied| applied.set(generation));
+    Ok(())
+}
+
+/// Hash of all settings loaded so far, merged
+pub fn digest() -> [u8; 32] {
+    *DIGEST.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The setting at `pointer` (e.g. "/verify/ocsp_fetch") of the settings
+/// loaded so far, if it was loaded
+pub fn value(pointer: &str) -> Option<Value> {
+    let settings = SETTINGS.lock().unwrap_or_else(|e| e.into_inner());
+    settings.pointer(pointer).cloned()
+}
+
+/// Apply the settings loaded so far to this thread, if it does not have them yet
+pub fn sync() {
+    if GENERATION.load(Ordering::Acquire) == APPLIED.with(Cell::get) {
+        HITS.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+    MISSES.fetch_add(1, Ordering::Relaxed);
+    let settings = SETTINGS.lock().unwrap_or_else(|e| e.into_inner());
+    // Every part of these settings loaded before, so loading them cannot fail
+    let _ = c2pa::settings::load_settings_from_str(&settings.to_string(), "json");
+    APPLIED.with(|applied| applied.set(GENERATION.load(Ordering::Acquire)));
+}
+
+/// Hits and misses of the per-thread settings, and the loads that evicted them
+pub fn stats() -> (u64, u64, u64) {
+    (
+        HITS.load(Ordering::Relaxed),
+        MISSES.load(Ordering::Relaxed),
+        GENERATION.load(Ordering::Relaxed),
+    )
+}
+
+/// Merge tables key by key, as c2pa-rs does when loading settings
+fn merge(into: &mut Map<String, Value>, from: Map<String, Value>) {
+    for (key, value) in from {
+        match (into.get_mut(&key), value) {
+            (Some(Value::Object(into)), Value::Object(from)) => merge(into, from),
+            (_, value) => {
+                into.insert(key, value);
+            }
+        }
+    }
+}
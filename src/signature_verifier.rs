@@ -0,0 +1,245 @@
+//! Claim signatures verified by a Python callback
+//!
+//! Regulated deployments may only accept signatures checked by certified
+//! cryptography, a FIPS validated module or a remote verification service.
+//! The library still parses the manifest store and checks every hash; each
+//! claim signature is handed to a Python callback, whose verdict replaces the
+//! one of c2pa-rs.
+
+use std::sync::Mutex;
+
+use c2pa_crypto::cose::signing_alg_from_sign1;
+use coset::{CoseSign1, TaggedCborSerializable};
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes};
+use serde_json::{json, Value};
+
+use crate::jumbf::{manifest_claim_bytes, manifest_signatures};
+use crate::reputation::cert_chain;
+
+static VERIFIER: Mutex<Option<PyObject>> = Mutex::new(None);
+
+const VALIDATED: &str = "claimSignature.validated";
+const MISMATCH: &str = "claimSignature.mismatch";
+
+/// Whether a signature verifier is registered
+pub(crate) fn has_signature_verifier() -> bool {
+    VERIFIER.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+}
+
+/// Whether `status` is c2pa-rs's verdict on the claim signature of `label`
+fn is_verdict(status: &Value, label: &str) -> bool {
+    let code = status.get("code").and_then(Value::as_str);
+    let url = status
+        .get("url")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    matches!(code, Some(VALIDATED | MISMATCH))
+        && url
+            .strip_prefix("self#jumbf=/c2pa/")
+            .and_then(|url| url.split('/').next())
+            == Some(label)
+}
+
+/// The validation state of the results, derived as c2pa-rs does: Invalid with
+/// any failure, Trusted with a trusted signing credential, Valid otherwise
+fn validation_state(value: &Value) -> &'static str {
+    let results = &value["validation_results"];
+    let statuses = |pointer: &str| {
+        results
+            .pointer(pointer)
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    };
+    let ingredient_failures = results
+        .get("ingredientDeltas")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .any(|delta| {
+            delta
+                .pointer("/validationDeltas/failure")
+                .and_then(Value::as_array)
+                .is_some_and(|failures| !failures.is_empty())
+        });
+    if !statuses("/activeManifest/failure").is_empty() || ingredient_failures {
+        "Invalid"
+    } else if statuses("/activeManifest/success").iter().any(|status| {
+        status.get("code").and_then(Value::as_str) == Some("signingCredential.trusted")
+    }) {
+        "Trusted"
+    } else {
+        "Valid"
+    }
+}
+
+/// Put the verdict of the callback on the active manifest's signature in place
+/// of the one of c2pa-rs, and derive the validation state again as c2pa-rs does
+fn replace_verdict(value: &mut Value, label: &str, valid: bool) {
+    let url = format!("self#jumbf=/c2pa/{label}/c2pa.signature");
+    let status = if valid {
+        json!({"code": VALIDATED, "url": url, "explanation": "claim signature valid (verified by callback)"})
+    } else {
+        json!({"code": MISMATCH, "url": url, "explanation": "claim signature invalid (verified by callback)"})
+    };
+    if let Some(statuses) = value
+        .get_mut("validation_status")
+        .and_then(Value::as_array_mut)
+    {
+        statuses.retain(|status| !is_verdict(status, label));
+        if !valid {
+            statuses.push(status.clone());
+        }
+    }
+    let Some(active) = value
+        .get_mut("validation_results")
+        .and_then(|results| results.get_mut("activeManifest"))
+        .and_then(Value::as_object_mut)
+    else {
+        return;
+    };
+    for list in ["success", "failure"] {
+        if let Some(statuses) = active.get_mut(list).and_then(Value::as_array_mut) {
+            statuses.retain(|status| !is_verdict(status, label));
+        }
+    }
+    let list = if valid { "success" } else { "failure" };
+    match active.get_mut(list).and_then(Value::as_array_mut) {
+        Some(statuses) => statuses.push(status),
+        None => {
+            active.insert(list.to_owned(), json!([status]));
+        }
+    }
+
+    value["validation_state"] = json!(validation_state(value));
+}
+
+/// Verify the claim signatures of the manifests in `store` with the
+/// registered verifier
+///
+/// Each answer is stored in the manifest's "signature_verification" item. The
+/// answer for the active manifest replaces c2pa-rs's in the validation results,
+/// and the other manifests with an invalid signature are reported in "warnings".
+pub(crate) fn verify_signatures(py: Python, value: &mut Value, store: &[u8]) -> PyResult<()> {
+    let Some(verifier) = VERIFIER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|verifier| verifier.clone_ref(py))
+    else {
+        return Ok(());
+    };
+
+    let claims = manifest_claim_bytes(store);
+    let active_label = value
+        .get("active_manifest")
+        .and_then(Value::as_str)
+        .map(str::to_owned);
+    let mut warnings = Vec::new();
+    for (label, signature) in manifest_signatures(store) {
+        if value.get("manifests").and_then(|m| m.get(label)).is_none() {
+            continue;
+        }
+        let Some(&(_, claim)) = claims.iter().find(|(claim_label, _)| *claim_label == label) else {
+            continue;
+        };
+        let Ok(mut sign1) = CoseSign1::from_tagged_slice(signature) else {
+            continue;
+        };
+        let alg = signing_alg_from_sign1(&sign1)
+            .ok()
+            .map(|alg| alg.to_string());
+        // The claim is the detached payload of the signature, with no external data
+        sign1.payload = Some(claim.to_vec());
+        let tbs_data = sign1.tbs_data(&[]);
+        let certificates: Vec<_> = cert_chain(signature)
+            .unwrap_or_default()
+            .iter()
+            .map(|cert| PyBytes::new(py, cert))
+            .collect();
+
+        let answer = verifier.call1(
+            py,
+            (
+                label,
+                alg.as_deref(),
+                PyBytes::new(py, &tbs_data),
+                PyBytes::new(py, &sign1.signature),
+                certificates,
+            ),
+        )?;
+        let answer = answer.bind(py);
+        if answer.is_none() {
+            continue;
+        }
+        if !answer.is_instance_of::<PyBool>() {
+            return Err(PyTypeError::new_err(
+                "signature verifier must return True, False or None",
+            ));
+        }
+        let valid: bool = answer.extract()?;
+        value["manifests"][label]["signature_verification"] =
+            json!({"verified_by": "callback", "valid": valid});
+        if active_label.as_deref() == Some(label) {
+            replace_verdict(value, label, valid);
+        } else if !valid {
+            warnings.push(json!({
+                "code": MISMATCH,
+                "label": label,
+                "explanation": format!("the signature verifier rejected the claim signature of {label}"),
+            }));
+        }
+    }
+
+    if !warnings.is_empty() {
+        match value.get_mut("warnings").and_then(Value::as_array_mut) {
+            Some(existing) => existing.extend(warnings),
+            None => value["warnings"] = json!(warnings),
+        }
+    }
+    Ok(())
+}
+
+/// Register a callback that verifies claim signatures in place of the library
+///
+/// For each manifest of the stores read by read_c2pa_from_file,
+/// read_c2pa_from_bytes, read_c2pa_json_from_bytes, read_c2pa_encoded_from_bytes,
+/// verify_manifest_bytes and Reader.from_bytes, `verifier(label, alg, tbs_data,
+/// signature, certificates)` is called with the manifest label, the signing
+/// algorithm (e.g. "es256", or None if unknown), the bytes the signature is
+/// computed over (the COSE Sig_structure of the claim), the raw signature bytes
+/// and the DER certificate chain as a list of bytes, signing certificate first.
+///
+/// It returns True for a valid signature, False for an invalid one, or None to
+/// keep the verdict of the library. The answer is added to the manifest as
+/// "signature_verification": {"verified_by": "callback", "valid": bool}. For
+/// the active manifest it replaces the "claimSignature.validated" or
+/// "claimSignature.mismatch" status of the validation results, and the
+/// validation_state is derived again; other manifests whose signature is
+/// rejected are reported in the result's "warnings" with the code
+/// "claimSignature.mismatch".
+///
+/// Parsing, hash checks and certificate trust stay with the library. c2pa-rs
+/// still checks the signatures on its own as it validates, only its verdict is
+/// replaced. Manifest stores recovered by the soft binding resolver are not
+/// checked. Exceptions raised by the verifier propagate to the caller of the
+/// read function.
+///
+/// Args:
+///     verifier: A callable, or None to remove the registered verifier
+///
+/// Raises:
+///     TypeError: If verifier is not callable
+#[pyfunction]
+#[pyo3(signature = (verifier))]
+pub fn set_signature_verifier(verifier: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+    if let Some(verifier) = &verifier {
+        if !verifier.is_callable() {
+            return Err(PyTypeError::new_err("signature verifier must be callable"));
+        }
+    }
+    *VERIFIER.lock().unwrap_or_else(|e| e.into_inner()) = verifier.map(Bound::unbind);
+    Ok(())
+}
@@ -0,0 +1,442 @@
+//! Every signature on a manifest, signer by signer
+//!
+//! signature_info only describes the claim signer, but a claim signature can
+//! carry RFC 3161 time stamps countersigned by a time stamp authority (the
+//! sigTst and sigTst2 COSE headers). Reporting each signer separately lets
+//! callers check who vouched for the signing time as well as who signed.
+//! The claim signer's certificate chain can also be inspected certificate by
+//! certificate, for audits of the CAs behind incoming content.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use c2pa_crypto::cose::signing_alg_from_sign1;
+use chrono::{DateTime, SecondsFormat};
+use coset::cbor::Value as Cbor;
+use coset::{CoseSign1, Label, TaggedCborSerializable};
+use pyo3::prelude::*;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use x509_parser::pem::Pem;
+use x509_parser::prelude::{ASN1Time, FromDer, X509Certificate, X509Name};
+
+use crate::c2pa_reader::{embedded_store, read_reader, value_to_py};
+use crate::errors::ManifestNotFoundError;
+use crate::gil;
+use crate::jumbf::manifest_signatures;
+use crate::reputation::{cert_chain, hex};
+
+/// Time stamp headers of a COSE signature, newest layout first
+const TIME_STAMP_HEADERS: [&str; 2] = ["sigTst2", "sigTst"];
+
+/// Split a DER element into (tag, content, rest)
+fn der(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, data) = data.split_first()?;
+    let (&first, mut data) = data.split_first()?;
+    let len = if first < 0x80 {
+        first as usize
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > std::mem::size_of::<usize>() || data.len() < count {
+            return None;
+        }
+        let (bytes, rest) = data.split_at(count);
+        data = rest;
+        bytes.iter().fold(0, |len, &b| (len << 8) | b as usize)
+    };
+    (data.len() >= len).then(|| (tag, &data[..len], &data[len..]))
+}
+
+/// The DER elements of a constructed element's content
+fn der_elements(mut data: &[u8]) -> Vec<(u8, &[u8], &[u8])> {
+    let mut elements = Vec::new();
+    while let Some((tag, content, rest)) = der(data) {
+        let whole = &data[..data.len() - rest.len()];
+        elements.push((tag, content, whole));
+        data = rest;
+    }
+    elements
+}
+
+/// The certificates of a time stamp token, or of a time stamp response
+/// wrapping one
+///
+/// TimeStampToken is a CMS ContentInfo holding SignedData, whose optional
+/// `[0] IMPLICIT` field lists the certificates.
+fn token_certificates(token: &[u8]) -> Vec<Vec<u8>> {
+    let Some((0x30, content, _)) = der(token) else {
+        return Vec::new();
+    };
+    let elements = der_elements(content);
+    let content_info = match elements.as_slice() {
+        [(0x06, ..), ..] => content,
+        // TimeStampResp: status, then the token
+        [(0x30, ..), (0x30, token, _), ..] => token,
+        _ => return Vec::new(),
+    };
+    let Some((0xa0, explicit, _)) = der_elements(content_info).into_iter().nth(1) else {
+        return Vec::new();
+    };
+    let Some((0x30, signed_data, _)) = der(explicit) else {
+        return Vec::new();
+    };
+    der_elements(signed_data)
+        .into_iter()
+        .find(|(tag, ..)| *tag == 0xa0)
+        .map(|(_, certificates, _)| {
+            der_elements(certificates)
+                .into_iter()
+                .filter(|(tag, ..)| *tag == 0x30)
+                .map(|(.., whole)| whole.to_vec())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Order `certs` signing certificate first, each followed by its issuer
+///
+/// Time stamp tokens list their certificates in any order; the signing
+/// certificate is the one that issued none of the others.
+fn order_chain(certs: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    let names: Vec<Option<(Vec<u8>, Vec<u8>)>> = certs
+        .iter()
+        .map(|der| {
+            let (_, cert) = X509Certificate::from_der(der).ok()?;
+            Some((
+                cert.subject().as_raw().to_vec(),
+                cert.issuer().as_raw().to_vec(),
+            ))
+        })
+        .collect();
+    let issues_another = |index: usize| {
+        names[index].as_ref().is_some_and(|(subject, _)| {
+            names.iter().enumerate().any(|(other, name)| {
+                other != index
+                    && name.as_ref().is_some_and(|(other_subject, issuer)| {
+                        issuer == subject && other_subject != subject
+                    })
+            })
+        })
+    };
+    let Some(mut current) = (0..certs.len()).find(|&index| !issues_another(index)) else {
+        return certs;
+    };
+    let mut order = vec![current];
+    while let Some((_, issuer)) = &names[current] {
+        let next = (0..certs.len()).find(|index| {
+            !order.contains(index)
+                && names[*index]
+                    .as_ref()
+                    .is_some_and(|(subject, _)| subject == issuer)
+        });
+        let Some(next) = next else {
+            break;
+        };
+        order.push(next);
+        current = next;
+    }
+    let rest: Vec<usize> = (0..certs.len())
+        .filter(|index| !order.contains(index))
+        .collect();
+    order.extend(rest);
+    let mut certs: Vec<Option<Vec<u8>>> = certs.into_iter().map(Some).collect();
+    order
+        .into_iter()
+        .filter_map(|index| certs[index].take())
+        .collect()
+}
+
+/// The signer fields of a certificate chain, signing certificate first
+fn signer(role: &str, chain: &[Vec<u8>]) -> Map<String, Value> {
+    let cert = chain
+        .first()
+        .and_then(|der| X509Certificate::from_der(der).ok())
+        .map(|(_, cert)| cert);
+    let common_name = cert.as_ref().and_then(|cert| {
+        cert.subject()
+            .iter_common_name()
+            .next()
+            .and_then(|cn| cn.as_str().ok())
+            .map(str::to_owned)
+    });
+    let organization = |name: &X509Name| {
+        name.iter_organization()
+            .next()
+            .and_then(|o| o.as_str().ok())
+            .map(str::to_owned)
+    };
+    let mut signer = Map::new();
+    signer.insert("role".into(), json!(role));
+    signer.insert("common_name".into(), json!(common_name));
+    signer.insert(
+        "organization".into(),
+        json!(cert.as_ref().and_then(|cert| organization(cert.subject()))),
+    );
+    signer.insert(
+        "issuer_organization".into(),
+        json!(cert.as_ref().and_then(|cert| organization(cert.issuer()))),
+    );
+    signer.insert(
+        "cert_serial_number".into(),
+        json!(cert.as_ref().map(|cert| cert.serial.to_string())),
+    );
+    signer.insert(
+        "cert_sha256".into(),
+        json!(chain.first().map(|der| hex(&Sha256::digest(der)))),
+    );
+    signer.insert(
+        "chain_sha256".into(),
+        json!(chain
+            .iter()
+            .map(|der| hex(&Sha256::digest(der)))
+            .collect::<Vec<_>>()),
+    );
+    signer
+}
+
+fn field<'a>(value: &'a Cbor, key: &str) -> Option<&'a Cbor> {
+    value
+        .as_map()?
+        .iter()
+        .find(|(k, _)| k.as_text() == Some(key))
+        .map(|(_, v)| v)
+}
+
+/// The time stamp tokens of a time stamp header: {"tstTokens": [{"val": token}]}
+fn header_tokens(header: &Cbor) -> Vec<&[u8]> {
+    field(header, "tstTokens")
+        .and_then(Cbor::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|token| field(token, "val")?.as_bytes().map(Vec::as_slice))
+        .collect()
+}
+
+/// The signers of one COSE_Sign1 claim signature
+fn signers(signature: &[u8]) -> Vec<Value> {
+    let Ok(sign1) = CoseSign1::from_tagged_slice(signature) else {
+        return Vec::new();
+    };
+    let mut claim = signer("claim", &cert_chain(signature).unwrap_or_default());
+    claim.insert(
+        "alg".into(),
+        json!(signing_alg_from_sign1(&sign1)
+            .ok()
+            .map(|alg| format!("{alg:?}"))),
+    );
+    let mut signers = vec![Value::Object(claim)];
+    for name in TIME_STAMP_HEADERS {
+        let label = Label::Text(name.to_owned());
+        let Some((_, header)) = sign1.unprotected.rest.iter().find(|(l, _)| *l == label) else {
+            continue;
+        };
+        for token in header_tokens(header) {
+            let mut time_stamp = signer("time_stamp", &order_chain(token_certificates(token)));
+            time_stamp.insert("header".into(), json!(name));
+            signers.push(Value::Object(time_stamp));
+        }
+    }
+    signers
+}
+
+/// Get every signer of each manifest, the claim signer and time stamp authorities
+///
+/// Each manifest's claim signature is reported first, then the RFC 3161 time
+/// stamps countersigning it, from the sigTst2 and sigTst COSE headers, each with
+/// the certificates of its own authority. The signatures are only read, not
+/// validated: use read_c2pa_from_bytes for the validation results.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     None if no manifest store is embedded, else a dict with "active_manifest"
+///     and "manifests", mapping each manifest label to a list of signer dicts:
+///     "role" ("claim" or "time_stamp"), "common_name", "organization" (of the
+///     signing certificate, what signature_info calls "issuer"),
+///     "issuer_organization" (of the certificate that issued it),
+///     "cert_serial_number", "cert_sha256" and "chain_sha256" (fingerprints,
+///     signing certificate first). Claim signers also have "alg", time stamp
+///     signers "header" (the COSE header holding the token).
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, allow_threads=None))]
+pub fn get_manifest_signers(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<Option<PyObject>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let read = || {
+        let store = embedded_store(data, mime_type)?;
+        let signatures = manifest_signatures(&store);
+        let active = signatures.last().map(|(label, _)| *label);
+        let manifests: Map<String, Value> = signatures
+            .iter()
+            .map(|(label, signature)| (label.to_string(), json!(signers(signature))))
+            .collect();
+        Some(json!({
+            "active_manifest": active,
+            "manifests": manifests,
+        }))
+    };
+    let signers = if allow_threads {
+        py.allow_threads(read)
+    } else {
+        read()
+    };
+    signers.map(|signers| value_to_py(py, &signers)).transpose()
+}
+
+/// The name of a certificate signature algorithm OID, or the OID itself
+fn signature_algorithm_name(oid: &str) -> String {
+    let name = match oid {
+        "1.2.840.10045.4.3.2" => "ecdsa-with-SHA256",
+        "1.2.840.10045.4.3.3" => "ecdsa-with-SHA384",
+        "1.2.840.10045.4.3.4" => "ecdsa-with-SHA512",
+        "1.2.840.113549.1.1.5" => "sha1WithRSAEncryption",
+        "1.2.840.113549.1.1.10" => "RSASSA-PSS",
+        "1.2.840.113549.1.1.11" => "sha256WithRSAEncryption",
+        "1.2.840.113549.1.1.12" => "sha384WithRSAEncryption",
+        "1.2.840.113549.1.1.13" => "sha512WithRSAEncryption",
+        "1.3.101.112" => "Ed25519",
+        oid => oid,
+    };
+    name.to_owned()
+}
+
+/// An ASN.1 time as an RFC 3339 UTC timestamp
+fn rfc3339(time: ASN1Time) -> Option<String> {
+    DateTime::from_timestamp(time.timestamp(), 0)
+        .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+/// A PEM certificate of the DER `der`
+fn pem_certificate(der: &[u8]) -> String {
+    let encoded = STANDARD.encode(der);
+    let mut pem = String::from("-----BEGIN CERTIFICATE-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(&String::from_utf8_lossy(line));
+        pem.push('\n');
+    }
+    pem.push_str("-----END CERTIFICATE-----\n");
+    pem
+}
+
+/// The fields of a certificate of a chain
+fn certificate(der: &[u8]) -> Value {
+    let Ok((_, cert)) = X509Certificate::from_der(der) else {
+        return json!({
+            "sha256": hex(&Sha256::digest(der)),
+            "pem": pem_certificate(der),
+        });
+    };
+    let common_name = cert
+        .subject()
+        .iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .map(str::to_owned);
+    let organization = cert
+        .subject()
+        .iter_organization()
+        .next()
+        .and_then(|o| o.as_str().ok())
+        .map(str::to_owned);
+    let validity = cert.validity();
+    json!({
+        "subject": cert.subject().to_string(),
+        "issuer": cert.issuer().to_string(),
+        "common_name": common_name,
+        "organization": organization,
+        "serial_number": cert.serial.to_string(),
+        "not_before": rfc3339(validity.not_before),
+        "not_after": rfc3339(validity.not_after),
+        "signature_algorithm":
+            signature_algorithm_name(&cert.signature_algorithm.algorithm.to_id_string()),
+        "is_ca": cert.is_ca(),
+        "sha256": hex(&Sha256::digest(der)),
+        "pem": pem_certificate(der),
+    })
+}
+
+/// Get who signed a manifest: the signing certificate chain, issuer, serial
+/// number, validity window, signature algorithm and time stamp
+///
+/// The fields of signature_info, with the certificate chain it leaves out
+/// parsed certificate by certificate, so the CAs that signed incoming content
+/// can be audited without parsing PEM. The manifest store is validated as by
+/// read_c2pa_from_bytes; "time" is only set for signatures with a time stamp.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     manifest_label: Label of the manifest whose signer to return, such as the
+///         manifest of an ingredient (default: None, the active manifest)
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     None if no manifest store is embedded, else a dict with "manifest" (the
+///     manifest label), "alg" (the signing algorithm, e.g. "Es256"), "issuer"
+///     (the organization of the signing certificate), "common_name",
+///     "cert_serial_number", "time" (the time stamp, RFC 3339, or None),
+///     "not_before" and "not_after" (the validity window of the signing
+///     certificate, RFC 3339) and "chain", the certificates as dicts, signing
+///     certificate first: "subject", "issuer", "common_name", "organization",
+///     "serial_number", "not_before", "not_after", "signature_algorithm",
+///     "is_ca", "sha256" and "pem".
+///
+/// Raises:
+///     ManifestNotFoundError: If the store has no manifest with manifest_label,
+///         or the manifest is not signed
+///     C2paError: If there is an error reading the manifest store
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, manifest_label=None, allow_threads=None))]
+pub fn read_signing_info(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    manifest_label: Option<&str>,
+    allow_threads: Option<bool>,
+) -> PyResult<Option<PyObject>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let Some(reader) = read_reader(py, data, mime_type, allow_threads)? else {
+        return Ok(None);
+    };
+    let label = match manifest_label {
+        Some(label) => Some(label.to_owned()),
+        None => reader.active_label().map(str::to_owned),
+    };
+    let info = label
+        .as_deref()
+        .and_then(|label| reader.get_manifest(label))
+        .and_then(|manifest| manifest.signature_info());
+    let (Some(label), Some(info)) = (label, info) else {
+        return Err(ManifestNotFoundError::new_err(match manifest_label {
+            Some(label) => format!("No signed manifest with label {label:?}"),
+            None => "No signed active manifest".to_owned(),
+        }));
+    };
+
+    let chain: Vec<Value> = Pem::iter_from_buffer(info.cert_chain().as_bytes())
+        .filter_map(Result::ok)
+        .map(|pem| certificate(&pem.contents))
+        .collect();
+    let signing = chain.first();
+    let signing_field = |key: &str| signing.and_then(|cert| cert.get(key)).cloned();
+    let signing_info = json!({
+        "manifest": label,
+        "alg": info.alg.map(|alg| serde_json::to_value(alg).unwrap_or_default()),
+        "issuer": info.issuer,
+        "common_name": signing_field("common_name"),
+        "cert_serial_number": info.cert_serial_number,
+        "time": info.time,
+        "not_before": signing_field("not_before"),
+        "not_after": signing_field("not_after"),
+        "chain": chain,
+    });
+    value_to_py(py, &signing_info).map(Some)
+}
@@ -0,0 +1,883 @@
+//! Creating and signing manifests
+//!
+//! The rest of the crate reads what others signed. Pipelines that produce
+//! assets also need to attach their own Content Credentials, so a manifest
+//! definition can be signed with a local certificate and key and embedded in
+//! the asset with c2pa-rs's Builder. Parent assets and components are
+//! recorded as ingredients built from their bytes, with their resources handed
+//! back to the Builder when the new manifest is signed.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use c2pa::assertions::Relationship;
+use c2pa::{create_signer, format_from_path, Builder, Ingredient, Signer, SigningAlg};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyString};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use url::Url;
+use x509_parser::pem::Pem;
+
+use crate::buffer::Buffer;
+use crate::c2pa_reader::{os_error, value_to_py};
+use crate::errors::{c2pa_error, signing_error, C2paError, SigningError};
+use crate::gil;
+use crate::http::fetch_reported;
+use crate::jumbf::{manifest_claim_bytes, manifest_signatures};
+use crate::mime::sniff;
+use crate::reputation::hex;
+use crate::resources::resource_refs;
+use crate::settings;
+
+thread_local! {
+    /// The exception raised by the last signing callback that failed on this
+    /// thread, to chain to the SigningError of the signing
+    static CALLBACK_ERROR: RefCell<Option<PyErr>> = const { RefCell::new(None) };
+}
+
+/// The private key of a signer
+pub(crate) enum SigningKey {
+    /// A PEM private key
+    Pem(Vec<u8>),
+    /// The callable of a CallbackSigner, which holds the key itself
+    Callback(Arc<Py<PyAny>>),
+}
+
+/// The certificate chain and private key to sign with
+pub(crate) struct SignerConfig {
+    pub(crate) alg: SigningAlg,
+    pub(crate) sign_cert: Vec<u8>,
+    pub(crate) key: SigningKey,
+    pub(crate) ta_url: Option<String>,
+}
+
+impl SignerConfig {
+    /// The c2pa-rs signer of the config
+    pub(crate) fn signer(&self) -> c2pa::Result<Box<dyn Signer>> {
+        match &self.key {
+            SigningKey::Pem(private_key) => create_signer::from_keys(
+                &self.sign_cert,
+                private_key,
+                self.alg,
+                self.ta_url.clone(),
+            ),
+            SigningKey::Callback(callback) => {
+                CALLBACK_ERROR.with_borrow_mut(|error| *error = None);
+                let callback = callback.clone();
+                let signer = c2pa::CallbackSigner::new(
+                    move |_, data| sign_with_callback(&callback, data),
+                    self.alg,
+                    self.sign_cert.clone(),
+                );
+                Ok(Box::new(match &self.ta_url {
+                    Some(url) => signer.set_tsa_url(url),
+                    None => signer,
+                }))
+            }
+        }
+    }
+}
+
+/// The signature of `data` by the callable of a CallbackSigner
+fn sign_with_callback(callback: &Py<PyAny>, data: &[u8]) -> c2pa::Result<Vec<u8>> {
+    Python::with_gil(|py| {
+        let signature = callback
+            .call1(py, (PyBytes::new(py, data),))
+            .and_then(|signature| {
+                let signature = Buffer::new(signature.bind(py), "signature")?;
+                Ok(signature.as_bytes().to_vec())
+            });
+        signature.map_err(|e| {
+            let message = format!("signing callback failed: {e}");
+            CALLBACK_ERROR.with_borrow_mut(|error| *error = Some(e));
+            c2pa::Error::OtherError(message.into())
+        })
+    })
+}
+
+/// The exception of the signing callback that failed last on this thread, if
+/// it was not taken yet
+pub(crate) fn take_callback_error() -> Option<PyErr> {
+    CALLBACK_ERROR.with_borrow_mut(Option::take)
+}
+
+/// A signer whose signatures are made by a Python callable
+///
+/// The private key stays wherever the callable reaches it, such as in AWS KMS,
+/// GCP KMS or an HSM: the callable is given the bytes to sign and returns their
+/// signature, which is embedded with the certificate chain. Pass it as the
+/// signer_config of sign_and_embed, sign_with_manifest_file, set_receipt_signer
+/// or make_signed_test_asset.
+///
+/// The callable may be called from any thread, with the GIL held. ECDSA
+/// signatures may be DER encoded, as KMS services return them, or the raw r||s
+/// of COSE; RSA signatures use PSS padding with the hash of the algorithm.
+///
+/// Args:
+///     callback: A callable taking the bytes to sign and returning their
+///         signature as a bytes-like object
+///     alg: The signing algorithm of the key: es256, es384, es512, ps256,
+///         ps384, ps512 or ed25519
+///     sign_cert: The PEM certificate chain, signing certificate first, as str
+///         or bytes
+///     ta_url: An RFC 3161 time stamp authority (default: None)
+///
+/// Raises:
+///     TypeError: If callback is not callable, or sign_cert is not str or bytes
+///     ValueError: If alg is unknown, or sign_cert holds no PEM certificate
+#[pyclass(frozen, module = "fast_c2pa_core")]
+pub struct CallbackSigner {
+    callback: Arc<Py<PyAny>>,
+    alg: SigningAlg,
+    sign_cert: Vec<u8>,
+    ta_url: Option<String>,
+}
+
+#[pymethods]
+impl CallbackSigner {
+    #[new]
+    #[pyo3(signature = (callback, alg, sign_cert, ta_url=None))]
+    fn new(
+        callback: &Bound<'_, PyAny>,
+        alg: &str,
+        sign_cert: &Bound<'_, PyAny>,
+        ta_url: Option<String>,
+    ) -> PyResult<Self> {
+        if !callback.is_callable() {
+            return Err(PyTypeError::new_err(format!(
+                "callback must be callable, not {}",
+                callback.get_type().name()?
+            )));
+        }
+        let sign_cert = text_or_bytes(sign_cert, "sign_cert")?;
+        let mut certificates = Pem::iter_from_buffer(&sign_cert).map_while(Result::ok);
+        if !certificates.any(|pem| pem.label == "CERTIFICATE") {
+            return Err(PyValueError::new_err(
+                "sign_cert must be a PEM certificate chain",
+            ));
+        }
+        Ok(CallbackSigner {
+            callback: Arc::new(callback.clone().unbind()),
+            alg: parse_alg(alg)?,
+            sign_cert,
+            ta_url,
+        })
+    }
+
+    /// The signing algorithm, such as "es256"
+    #[getter]
+    fn alg(&self) -> String {
+        self.alg.to_string()
+    }
+
+    /// The PEM certificate chain, signing certificate first
+    #[getter]
+    fn sign_cert(&self) -> String {
+        String::from_utf8_lossy(&self.sign_cert).into_owned()
+    }
+
+    /// The time stamp authority, or None
+    #[getter]
+    fn ta_url(&self) -> Option<String> {
+        self.ta_url.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CallbackSigner(alg={:?})", self.alg.to_string())
+    }
+}
+
+/// A PEM argument `what`, as str or bytes
+fn text_or_bytes(value: &Bound<'_, PyAny>, what: &str) -> PyResult<Vec<u8>> {
+    if let Ok(text) = value.downcast::<PyString>() {
+        return Ok(text.to_str()?.as_bytes().to_vec());
+    }
+    match value.downcast::<PyBytes>() {
+        Ok(bytes) => Ok(bytes.as_bytes().to_vec()),
+        Err(_) => Err(PyTypeError::new_err(format!(
+            "{what} must be str or bytes, not {}",
+            value.get_type().name()?
+        ))),
+    }
+}
+
+/// A PEM item of the signer config, as str or bytes
+fn pem(config: &Bound<'_, PyDict>, key: &str) -> PyResult<Vec<u8>> {
+    let Some(value) = config.get_item(key)? else {
+        return Err(PyValueError::new_err(format!(
+            "signer_config needs {key:?}"
+        )));
+    };
+    text_or_bytes(&value, &format!("signer_config {key:?}"))
+}
+
+fn parse_alg(alg: &str) -> PyResult<SigningAlg> {
+    alg.to_ascii_lowercase()
+        .parse()
+        .map_err(|_| PyValueError::new_err(format!("Invalid signer_config: unknown alg {alg:?}")))
+}
+
+/// The signer of a signer_config argument: a dict of PEM keys, or a
+/// CallbackSigner
+pub(crate) fn signer_config(config: &Bound<'_, PyAny>) -> PyResult<SignerConfig> {
+    if let Ok(signer) = config.downcast::<CallbackSigner>() {
+        let signer = signer.get();
+        return Ok(SignerConfig {
+            alg: signer.alg,
+            sign_cert: signer.sign_cert.clone(),
+            key: SigningKey::Callback(signer.callback.clone()),
+            ta_url: signer.ta_url.clone(),
+        });
+    }
+    let Ok(config) = config.downcast::<PyDict>() else {
+        return Err(PyTypeError::new_err(format!(
+            "signer_config must be a dict or a CallbackSigner, not {}",
+            config.get_type().name()?
+        )));
+    };
+    let alg: String = match config.get_item("alg")? {
+        Some(alg) => alg.extract()?,
+        None => return Err(PyValueError::new_err("signer_config needs \"alg\"")),
+    };
+    let ta_url = match config.get_item("ta_url")? {
+        Some(url) => url.extract()?,
+        None => None,
+    };
+    Ok(SignerConfig {
+        alg: parse_alg(&alg)?,
+        sign_cert: pem(config, "sign_cert")?,
+        key: SigningKey::Pem(pem(config, "private_key")?),
+        ta_url,
+    })
+}
+
+/// Sign a manifest definition and embed it in the asset
+///
+/// The manifest store is built from the definition by c2pa-rs, with its hard
+/// binding to the asset, signed and embedded, replacing any manifest store the
+/// asset had. Read the result with read_c2pa_from_bytes to check it.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     manifest_json: The manifest definition as a JSON string, e.g.
+///         {"claim_generator_info": [{"name": "my-app"}], "title": "photo.jpg",
+///         "assertions": [...]}, as taken by c2pa-rs's Builder
+///     signer_config: A dict with "alg" (es256, es384, es512, ps256, ps384, ps512
+///         or ed25519), "sign_cert" (the PEM certificate chain, signing
+///         certificate first), "private_key" (the PEM private key), both as str
+///         or bytes, and optionally "ta_url" (an RFC 3161 time stamp authority),
+///         or a CallbackSigner for keys that are not at hand
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///     resources: A dict mapping the identifiers the definition references, such
+///         as the thumbnails and manifest_data of ingredients made with
+///         create_ingredient_from_bytes, to their bytes (default: None)
+///     deterministic: Sign the same asset and definition into the same bytes, for
+///         golden-file tests (default: False). The manifest label, unless the
+///         definition has one, and the instance ID of the claim are derived
+///         from the hash of the asset and definition instead of being random,
+///         and no time stamp is requested. The signer is called twice. ES256
+///         and the other ECDSA and RSA-PSS signatures are randomized, so only
+///         ed25519, or a CallbackSigner that signs deterministically, make the
+///         whole asset byte-stable.
+///
+/// Returns:
+///     The signed asset as bytes
+///
+/// Raises:
+///     ValueError: If the manifest definition or signer_config is invalid
+///     TypeError: If signer_config is neither a dict nor a CallbackSigner,
+///         sign_cert or private_key is not str or bytes, or a resource is not
+///         bytes
+///     UnsupportedFormatError: If the format of the asset cannot be signed
+///     SigningError: If the asset cannot be signed, with the exception of the
+///         callback of a CallbackSigner that raised as its __cause__
+#[pyfunction]
+#[pyo3(signature = (
+    data, mime_type, manifest_json, signer_config, allow_threads=None, resources=None,
+    deterministic=false
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn sign_and_embed(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    manifest_json: &str,
+    signer_config: &Bound<'_, PyAny>,
+    allow_threads: Option<bool>,
+    resources: Option<&Bound<'_, PyDict>>,
+    deterministic: bool,
+) -> PyResult<Py<PyBytes>> {
+    let mut config = self::signer_config(signer_config)?;
+    let mut builder = Builder::from_json(manifest_json)
+        .map_err(|e| PyValueError::new_err(format!("Invalid manifest definition: {e}")))?;
+    for (identifier, resource) in resources.into_iter().flatten() {
+        let identifier: String = identifier.extract()?;
+        let resource = resource.downcast::<PyBytes>().map_err(|_| {
+            PyTypeError::new_err(format!("resource {identifier:?} must be bytes"))
+        })?;
+        builder
+            .add_resource(&identifier, Cursor::new(resource.as_bytes()))
+            .map_err(|e| PyValueError::new_err(format!("Invalid resource {identifier:?}: {e}")))?;
+    }
+    if deterministic {
+        config.ta_url = None;
+    }
+    let mut sign = || {
+        if deterministic {
+            sign_deterministic(&mut builder, &config, mime_type, data, manifest_json)
+        } else {
+            sign_builder(&mut builder, &config, mime_type, data)
+        }
+    };
+    let allow_threads = gil::release(allow_threads, data.len());
+    let signed = if allow_threads {
+        py.allow_threads(sign)
+    } else {
+        sign()
+    }?;
+    Ok(PyBytes::new(py, &signed).unbind())
+}
+
+/// Sign the manifest of `builder` into the asset `data` as sign_builder does,
+/// with the identifiers c2pa-rs makes random derived from the asset and the
+/// manifest definition `manifest_json`
+fn sign_deterministic(
+    builder: &mut Builder,
+    config: &SignerConfig,
+    mime_type: &str,
+    data: &[u8],
+    manifest_json: &str,
+) -> PyResult<Vec<u8>> {
+    let seed = Sha256::new()
+        .chain_update(data)
+        .chain_update(manifest_json)
+        .finalize();
+    builder
+        .definition
+        .label
+        .get_or_insert_with(|| format!("urn:uuid:{}", derived_uuid(&seed, "label")));
+    let signed = sign_builder(builder, config, mime_type, data)?;
+    let instance_id = derived_uuid(&seed, "instance_id");
+    pin_instance_id(&signed, mime_type, config, &instance_id)
+}
+
+/// A version 4 UUID made from the hash of `seed` and `purpose` rather than
+/// randomly, for deterministic signing
+fn derived_uuid(seed: &[u8], purpose: &str) -> String {
+    let mut bytes: [u8; 16] = Sha256::new()
+        .chain_update(seed)
+        .chain_update(purpose)
+        .finalize()[..16]
+        .try_into()
+        .unwrap_or_default();
+    bytes[6] = bytes[6] & 0x0f | 0x40;
+    bytes[8] = bytes[8] & 0x3f | 0x80;
+    let hex = hex(&bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// The asset `signed` with the claim of its active manifest given the
+/// instance ID "xmp:iid:`uuid`" and signed again
+///
+/// c2pa-rs's Builder always gives a new claim a random instance ID. The ID is
+/// as long as the one it replaces, and so is the new signature, so the
+/// manifest store keeps its size and the hard binding, which excludes the
+/// store, still holds.
+fn pin_instance_id(
+    signed: &[u8],
+    mime_type: &str,
+    config: &SignerConfig,
+    uuid: &str,
+) -> PyResult<Vec<u8>> {
+    /// The CBOR of the claim's "instanceID" key and the head of its value, a
+    /// text of "xmp:iid:" and a UUID
+    const INSTANCE_ID: &[u8] = b"\x6ainstanceID\x78\x2cxmp:iid:";
+
+    let error = |e: c2pa::Error| signing_error("Error signing asset deterministically", &e);
+    let mut store = c2pa::jumbf_io::load_jumbf_from_memory(mime_type, signed).map_err(error)?;
+    let offset = |part: &[u8]| part.as_ptr() as usize - store.as_ptr() as usize;
+    let (Some((_, claim)), Some((_, signature))) = (
+        manifest_claim_bytes(&store).last().copied(),
+        manifest_signatures(&store).last().copied(),
+    ) else {
+        return Err(SigningError::new_err(
+            "Error signing asset deterministically: the signed manifest has no claim",
+        ));
+    };
+    let claim = offset(claim)..offset(claim) + claim.len();
+    let signature = offset(signature)..offset(signature) + signature.len();
+    let Some(at) = memchr::memmem::find(&store[claim.clone()], INSTANCE_ID) else {
+        return Err(SigningError::new_err(
+            "Error signing asset deterministically: the claim has no instance ID",
+        ));
+    };
+    let id = claim.start + at + INSTANCE_ID.len();
+    store[id..id + uuid.len()].copy_from_slice(uuid.as_bytes());
+
+    let signer = config
+        .signer()
+        .map_err(|e| PyValueError::new_err(format!("Invalid signer_config: {e}")))?;
+    let resigned = c2pa::cose_sign::sign_claim(&store[claim], signer.as_ref(), signature.len())
+        .map_err(error)?;
+    if resigned.len() != signature.len() {
+        return Err(SigningError::new_err(
+            "Error signing asset deterministically: the signature changed size",
+        ));
+    }
+    store[signature].copy_from_slice(&resigned);
+    c2pa::jumbf_io::save_jumbf_to_memory(mime_type, signed, &store).map_err(error)
+}
+
+/// Sign the manifest of `builder` into the asset `data`, returning the signed
+/// asset
+pub(crate) fn sign_builder(
+    builder: &mut Builder,
+    config: &SignerConfig,
+    mime_type: &str,
+    data: &[u8],
+) -> PyResult<Vec<u8>> {
+    settings::sync();
+    let signer = config
+        .signer()
+        .map_err(|e| PyValueError::new_err(format!("Invalid signer_config: {e}")))?;
+    let mut signed = Cursor::new(Vec::new());
+    builder
+        .sign(
+            signer.as_ref(),
+            mime_type,
+            &mut Cursor::new(data),
+            &mut signed,
+        )
+        .map_err(|e| signing_error("Error signing asset", &e))?;
+    Ok(signed.into_inner())
+}
+
+/// The relationship of an ingredient to the asset it goes into
+fn relationship(relationship: &str) -> PyResult<Relationship> {
+    serde_json::from_value(json!(relationship)).map_err(|_| {
+        PyValueError::new_err(format!(
+            "Unknown relationship {relationship:?}, expected \"parentOf\", \"componentOf\" \
+             or \"inputTo\""
+        ))
+    })
+}
+
+/// An ingredient of the asset `data`, with the hash of its bytes and, when
+/// its manifest has no claim thumbnail, one made from the image
+pub(crate) fn new_ingredient(
+    data: &[u8],
+    mime_type: &str,
+    title: Option<&str>,
+    relationship: Relationship,
+) -> c2pa::Result<Ingredient> {
+    let mut ingredient = Ingredient::from_memory(mime_type, data)?;
+    ingredient.set_relationship(relationship).set_hash(hex(&Sha256::digest(data)));
+    if let Some(title) = title {
+        ingredient.set_title(title);
+    }
+    #[cfg(feature = "transforms")]
+    if ingredient.thumbnail_ref().is_none() {
+        if let Some((format, thumbnail)) = crate::thumbnail::make_thumbnail(data) {
+            ingredient.set_thumbnail(format, thumbnail)?;
+        }
+    }
+    Ok(ingredient)
+}
+
+/// Build an ingredient from an asset, to record it in a new manifest
+///
+/// The asset's manifest store, if it has one, is validated and kept as the
+/// ingredient's manifest_data, so the provenance chain carries on through the
+/// new manifest. Give the result's "ingredient" to the "ingredients" of a
+/// manifest definition and its "resources" to sign_and_embed.
+///
+/// Args:
+///     data: Binary data of the asset (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     title: Title of the ingredient, such as the file name of the asset
+///         (default: None, "untitled")
+///     relationship: "parentOf" for the asset the new one was edited from,
+///         "componentOf" for assets composed into it or "inputTo" for inputs of
+///         a generative process (default: "componentOf")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A dict with "ingredient" (the ingredient definition, with "hash" the
+///     hex SHA-256 of the data, "validation_status" and "validation_results" of
+///     its manifest store, and "thumbnail" and "manifest_data" referencing
+///     resources by identifier) and "resources" (a dict mapping those
+///     identifiers to their bytes). The thumbnail is the claim thumbnail of the
+///     asset's manifest; without one, builds with image transforms make a JPEG
+///     of at most 1024 pixels from images, or a PNG for transparent ones.
+///
+/// Raises:
+///     ValueError: If relationship is unknown
+///     C2paError: If the ingredient cannot be built
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, title=None, relationship="componentOf", allow_threads=None))]
+pub fn create_ingredient_from_bytes(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    title: Option<&str>,
+    relationship: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<PyObject> {
+    let relationship = self::relationship(relationship)?;
+    let allow_threads = gil::release(allow_threads, data.len());
+    let build = || {
+        settings::sync();
+        new_ingredient(data, mime_type, title, relationship)
+    };
+    let ingredient = if allow_threads {
+        py.allow_threads(build)
+    } else {
+        build()
+    }
+    .map_err(|e| c2pa_error("Error creating ingredient", &e))?;
+
+    let definition = ingredient_definition(&ingredient)?;
+    let result = PyDict::new(py);
+    result.set_item("ingredient", value_to_py(py, &definition)?)?;
+    result.set_item("resources", ingredient_resources(py, &ingredient)?)?;
+    Ok(result.into_any().unbind())
+}
+
+/// The definition of an ingredient, as the "ingredients" of a manifest
+/// definition take it
+fn ingredient_definition(ingredient: &Ingredient) -> PyResult<Value> {
+    serde_json::to_value(ingredient)
+        .map_err(|e| C2paError::new_err(format!("Error serializing ingredient: {e}")))
+}
+
+/// The resources of an ingredient, as a dict of identifier to bytes
+fn ingredient_resources<'py>(
+    py: Python<'py>,
+    ingredient: &Ingredient,
+) -> PyResult<Bound<'py, PyDict>> {
+    let resources = PyDict::new(py);
+    for (identifier, resource) in ingredient.resources().resources() {
+        resources.set_item(identifier, PyBytes::new(py, resource))?;
+    }
+    Ok(resources)
+}
+
+/// The title an ingredient at `url` gets by default: the last segment of its
+/// path, else its host
+fn url_title(url: &Url) -> String {
+    url.path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| !segment.is_empty())
+        .or(url.host_str())
+        .unwrap_or("untitled")
+        .to_owned()
+}
+
+/// Add an ingredient at a URL, such as a parent asset on a CDN, to a manifest
+/// definition
+///
+/// By default the asset is downloaded with the shared HTTP client, so the
+/// configure_http limits and host restrictions apply, and recorded like one
+/// of create_ingredient_from_bytes: with the hex SHA-256 of the bytes, its
+/// thumbnail and its manifest store. With download=False the ingredient only
+/// references the asset, by title and format, and has no hash. Either way the
+/// URL is recorded as the ingredient's informational_URI.
+///
+/// Args:
+///     manifest_json: The manifest definition as a JSON string, as taken by
+///         sign_and_embed
+///     url: http or https URL of the asset
+///     relationship: "parentOf", "componentOf" or "inputTo", as for
+///         create_ingredient_from_bytes (default: "componentOf")
+///     title: Title of the ingredient (default: None, the file name of the URL)
+///     mime_type: MIME type of the asset (default: None, detected from the
+///         downloaded bytes, else from the extension of the URL; required
+///         without download when the URL has no known extension)
+///     download: Download the asset, to record its hash and manifest store
+///         (default: True)
+///
+/// Returns:
+///     A dict with "manifest_json" (the definition with the ingredient added
+///     to its "ingredients") and "resources" (the ingredient's resources by
+///     identifier, to pass to sign_and_embed with those of other ingredients)
+///
+/// Raises:
+///     ValueError: If the manifest definition, URL or relationship is invalid,
+///         or the format of the asset cannot be determined
+///     PermissionError: If the URL, or a redirect, is not allowed by configure_http
+///     IoError: If the download fails
+///     C2paError: If the ingredient cannot be built
+#[pyfunction]
+#[pyo3(signature = (
+    manifest_json, url, relationship="componentOf", title=None, mime_type=None, download=true
+))]
+pub fn add_ingredient_from_url(
+    py: Python,
+    manifest_json: &str,
+    url: &str,
+    relationship: &str,
+    title: Option<&str>,
+    mime_type: Option<&str>,
+    download: bool,
+) -> PyResult<PyObject> {
+    let Value::Object(mut definition) = serde_json::from_str(manifest_json)
+        .map_err(|e| PyValueError::new_err(format!("Invalid manifest definition: {e}")))?
+    else {
+        return Err(PyValueError::new_err(
+            "Invalid manifest definition: expected a JSON object",
+        ));
+    };
+    let relationship = self::relationship(relationship)?;
+    let parsed = Url::parse(url).map_err(|e| PyValueError::new_err(format!("Invalid URL: {e}")))?;
+    let title = title.map_or_else(|| url_title(&parsed), str::to_owned);
+    let from_extension = || format_from_path(Path::new(parsed.path()));
+    let unknown_format = || {
+        PyValueError::new_err(format!(
+            "Cannot determine the format of ingredient {url:?}; pass mime_type"
+        ))
+    };
+
+    let mut ingredient = if download {
+        let data = py.allow_threads(|| fetch_reported(url))?.0;
+        let mime_type = mime_type
+            .map(str::to_owned)
+            .or_else(|| sniff(&data).map(str::to_owned))
+            .or_else(from_extension)
+            .ok_or_else(unknown_format)?;
+        py.allow_threads(|| {
+            settings::sync();
+            new_ingredient(&data, &mime_type, Some(&title), relationship)
+        })
+        .map_err(|e| c2pa_error("Error creating ingredient", &e))?
+    } else {
+        let mime_type = mime_type
+            .map(str::to_owned)
+            .or_else(from_extension)
+            .ok_or_else(unknown_format)?;
+        let mut ingredient = Ingredient::new_v2(title, mime_type);
+        ingredient.set_relationship(relationship);
+        ingredient
+    };
+    ingredient.set_informational_uri(url);
+
+    let Value::Array(ingredients) = definition
+        .entry("ingredients")
+        .or_insert_with(|| Value::Array(Vec::new()))
+    else {
+        return Err(PyValueError::new_err(
+            "Invalid manifest definition: \"ingredients\" must be a list",
+        ));
+    };
+    ingredients.push(ingredient_definition(&ingredient)?);
+    let result = PyDict::new(py);
+    result.set_item("manifest_json", Value::Object(definition).to_string())?;
+    result.set_item("resources", ingredient_resources(py, &ingredient)?)?;
+    Ok(result.into_any().unbind())
+}
+
+/// The keys of c2patool manifest definition files that configure c2patool
+/// rather than describe the manifest
+const C2PATOOL_KEYS: [&str; 5] = ["alg", "private_key", "sign_cert", "ta_url", "ingredient_paths"];
+
+/// A file `path` of a manifest definition file, relative to the directory
+/// `base` of the file
+fn read_relative(base: &Path, path: &str) -> PyResult<(PathBuf, Vec<u8>)> {
+    let path = base.join(path);
+    let data = fs::read(&path).map_err(|e| os_error(e, &path))?;
+    Ok((path, data))
+}
+
+/// The MIME type of the ingredient file `path`, from its magic bytes, else its
+/// extension
+fn ingredient_mime_type(path: &Path, data: &[u8]) -> PyResult<String> {
+    sniff(data)
+        .map(str::to_owned)
+        .or_else(|| format_from_path(path))
+        .ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Cannot determine the format of ingredient {:?}",
+                path.display()
+            ))
+        })
+}
+
+/// The signer of a manifest definition file: its "alg", defaulting to es256 as
+/// in c2patool, and its "sign_cert" and "private_key" files
+fn file_signer_config(
+    base: &Path,
+    definition: &Map<String, Value>,
+) -> PyResult<SignerConfig> {
+    let text = |key: &str| definition.get(key).and_then(Value::as_str);
+    let (Some(sign_cert), Some(private_key)) = (text("sign_cert"), text("private_key")) else {
+        return Err(PyValueError::new_err(
+            "No signer: pass signer_config, or set \"sign_cert\" and \"private_key\" in the \
+             manifest definition file",
+        ));
+    };
+    let alg = text("alg").unwrap_or("es256");
+    Ok(SignerConfig {
+        alg: alg.to_ascii_lowercase().parse().map_err(|_| {
+            PyValueError::new_err(format!("Invalid manifest definition: unknown alg {alg:?}"))
+        })?,
+        sign_cert: read_relative(base, sign_cert)?.1,
+        key: SigningKey::Pem(read_relative(base, private_key)?.1),
+        ta_url: text("ta_url").map(str::to_owned),
+    })
+}
+
+/// The claim_generator_info of a c2patool "claim_generator" string such as
+/// "my-app/1.0", superseded by claim_generator_info and ignored by the Builder
+fn claim_generator_info(claim_generator: &str) -> Option<Value> {
+    let product = claim_generator.split_whitespace().next()?;
+    Some(match product.split_once('/') {
+        Some((name, version)) => json!([{"name": name, "version": version}]),
+        None => json!([{"name": product}]),
+    })
+}
+
+/// Sign a c2patool manifest definition file and embed it in the asset
+///
+/// Takes the manifest definition files of c2patool scripts as they are:
+/// "sign_cert" and "private_key" paths with "alg" and "ta_url" as the signer,
+/// "ingredient_paths" as componentOf ingredients, a "claim_generator" string
+/// as its claim_generator_info, and resource identifiers such as the
+/// "thumbnail" of the manifest or of ingredients as files. Paths are relative
+/// to the directory of the manifest definition file, as in c2patool.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     manifest_path: Path to the manifest definition JSON file
+///     signer_config: A dict or CallbackSigner as taken by sign_and_embed, to
+///         sign with instead of the signer of the file (default: None)
+///     parent: Path to the asset the new one was edited from, recorded as its
+///         parentOf ingredient, as with c2patool --parent (default: None)
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     The signed asset as bytes
+///
+/// Raises:
+///     OSError: If the manifest definition file or a file it names cannot be read
+///     ValueError: If the manifest definition is invalid, or neither it nor
+///         signer_config gives a signer
+///     UnsupportedFormatError: If the format of the asset cannot be signed
+///     SigningError: If the asset cannot be signed
+#[pyfunction]
+#[pyo3(signature = (
+    data, mime_type, manifest_path, signer_config=None, parent=None, allow_threads=None
+))]
+pub fn sign_with_manifest_file(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    manifest_path: PathBuf,
+    signer_config: Option<&Bound<'_, PyAny>>,
+    parent: Option<PathBuf>,
+    allow_threads: Option<bool>,
+) -> PyResult<Py<PyBytes>> {
+    let text = fs::read_to_string(&manifest_path).map_err(|e| os_error(e, &manifest_path))?;
+    let Value::Object(mut definition) = serde_json::from_str(&text)
+        .map_err(|e| PyValueError::new_err(format!("Invalid manifest definition: {e}")))?
+    else {
+        return Err(PyValueError::new_err(
+            "Invalid manifest definition: expected a JSON object",
+        ));
+    };
+    let base = manifest_path.parent().unwrap_or(Path::new("")).to_path_buf();
+    let config = match signer_config {
+        Some(config) => self::signer_config(config)?,
+        None => file_signer_config(&base, &definition)?,
+    };
+
+    let mut ingredients = Vec::new();
+    if let Some(parent) = parent {
+        let data = fs::read(&parent).map_err(|e| os_error(e, &parent))?;
+        ingredients.push((parent, data, Relationship::ParentOf));
+    }
+    for path in definition
+        .get("ingredient_paths")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let Some(path) = path.as_str() else {
+            return Err(PyValueError::new_err(
+                "Invalid manifest definition: ingredient_paths must be strings",
+            ));
+        };
+        let (path, data) = read_relative(&base, path)?;
+        ingredients.push((path, data, Relationship::ComponentOf));
+    }
+    for key in C2PATOOL_KEYS {
+        definition.remove(key);
+    }
+    if !definition.contains_key("claim_generator_info") {
+        if let Some(info) = definition
+            .get("claim_generator")
+            .and_then(Value::as_str)
+            .and_then(claim_generator_info)
+        {
+            definition.insert("claim_generator_info".to_owned(), info);
+        }
+    }
+
+    let definition = Value::Object(definition);
+    let mut builder = Builder::from_json(&definition.to_string())
+        .map_err(|e| PyValueError::new_err(format!("Invalid manifest definition: {e}")))?;
+    let mut refs = Vec::new();
+    resource_refs(&definition, &mut refs);
+    let mut added = HashSet::new();
+    for (identifier, _) in refs {
+        let path = base.join(identifier);
+        if identifier.starts_with("self#jumbf=")
+            || identifier.contains("://")
+            || !path.is_file()
+            || !added.insert(identifier)
+        {
+            continue;
+        }
+        let resource = fs::read(&path).map_err(|e| os_error(e, &path))?;
+        builder
+            .add_resource(identifier, Cursor::new(resource))
+            .map_err(|e| PyValueError::new_err(format!("Invalid resource {identifier:?}: {e}")))?;
+    }
+
+    let size = data.len() + ingredients.iter().map(|(_, data, _)| data.len()).sum::<usize>();
+    let allow_threads = gil::release(allow_threads, size);
+    let mut sign = || {
+        settings::sync();
+        for (path, data, relationship) in &ingredients {
+            let mime_type = ingredient_mime_type(path, data)?;
+            let title = path.file_name().map(|name| name.to_string_lossy());
+            let ingredient =
+                new_ingredient(data, &mime_type, title.as_deref(), relationship.clone())
+                    .map_err(|e| c2pa_error("Error creating ingredient", &e))?;
+            builder.add_ingredient(ingredient);
+        }
+        sign_builder(&mut builder, &config, mime_type, data)
+    };
+    let signed = if allow_threads {
+        py.allow_threads(sign)
+    } else {
+        sign()
+    }?;
+    Ok(PyBytes::new(py, &signed).unbind())
+}
@@ -0,0 +1,163 @@
+//! Visual consistency of an asset with its ingredients
+//!
+//! Re-encoding, resizing or color conversion change every hash of an asset
+//! while leaving its content alone. A perceptual hash (pHash) of the asset
+//! and of the thumbnail each ingredient was recorded with tells whether they
+//! still look alike, a quick signal when the hard bindings cannot.
+
+use std::f64::consts::PI;
+
+use image::imageops::FilterType;
+use image::DynamicImage;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::c2pa_reader::read_reader;
+use crate::errors::C2paError;
+use crate::gil;
+use crate::resources::Budget;
+
+/// Side of the grayscale image the DCT is computed over
+const SIZE: usize = 32;
+/// Side of the block of low frequencies kept, one bit each
+const LOW: usize = 8;
+
+/// The 64-bit pHash of an image: the low frequencies of the DCT of its 32x32
+/// grayscale reduction, each set when above their median (the scheme of
+/// imagehash's phash)
+fn phash(image: &DynamicImage) -> u64 {
+    let pixels = image
+        .grayscale()
+        .resize_exact(SIZE as u32, SIZE as u32, FilterType::Triangle)
+        .to_luma8();
+    let cosines: Vec<[f64; SIZE]> = (0..LOW)
+        .map(|u| {
+            std::array::from_fn(|x| ((2 * x + 1) as f64 * u as f64 * PI / (2 * SIZE) as f64).cos())
+        })
+        .collect();
+
+    // DCT of the rows, then of the columns, for the low frequencies alone
+    let mut rows = [[0.0; LOW]; SIZE];
+    for (y, row) in rows.iter_mut().enumerate() {
+        for (u, coefficient) in row.iter_mut().enumerate() {
+            *coefficient = (0..SIZE)
+                .map(|x| f64::from(pixels.get_pixel(x as u32, y as u32)[0]) * cosines[u][x])
+                .sum();
+        }
+    }
+    let mut low = Vec::with_capacity(LOW * LOW);
+    for cosine in &cosines {
+        for u in 0..LOW {
+            low.push((0..SIZE).map(|y| rows[y][u] * cosine[y]).sum::<f64>());
+        }
+    }
+
+    let mut sorted = low.clone();
+    sorted.sort_by(f64::total_cmp);
+    let median = (sorted[LOW * LOW / 2 - 1] + sorted[LOW * LOW / 2]) / 2.0;
+    low.iter().fold(0, |hash, &coefficient| {
+        (hash << 1) | u64::from(coefficient > median)
+    })
+}
+
+/// Compare the thumbnails of the active manifest's ingredients with the asset
+///
+/// Each ingredient thumbnail stored in the asset is decoded and its pHash
+/// compared with the pHash of the asset itself. The distance is the number of
+/// the 64 hash bits that differ: up to about 10 for the same picture after
+/// re-encoding, resizing or a color change, around 32 for unrelated images.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object), an image format the
+///         build can decode (JPEG, PNG, GIF, WebP, TIFF, ...)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A list of dicts, one per ingredient with a thumbnail, with "label" (its
+///     assertion label), "title", "relationship" ("parentOf", "componentOf" or
+///     "inputTo"), "format" (of the thumbnail), "distance" (0 to 64) and
+///     "similarity" (1 - distance / 64), both None when the thumbnail is not
+///     compared, "skipped" (the limit of set_resource_limits it is over, else
+///     None) and "error" (why it cannot be decoded, else None). Empty if there
+///     is no C2PA metadata or no ingredient thumbnail.
+///
+/// Raises:
+///     C2paError: If there is an error reading the C2PA data or decoding the asset
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, allow_threads=None))]
+pub fn compare_ingredient_thumbnails(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<Vec<PyObject>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let Some(reader) = read_reader(py, data, mime_type, allow_threads)? else {
+        return Ok(Vec::new());
+    };
+    let Some(manifest) = reader.active_manifest() else {
+        return Ok(Vec::new());
+    };
+    let mut budget = Budget::new();
+    let thumbnails: Vec<_> = manifest
+        .ingredients()
+        .iter()
+        .filter_map(|ingredient| {
+            let (format, bytes) = ingredient.thumbnail()?;
+            let skipped = budget.admit(bytes.len());
+            Some((ingredient, format, bytes, skipped))
+        })
+        .collect();
+    if thumbnails.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let compare = || {
+        let asset = image::load_from_memory(data)
+            .map_err(|e| C2paError::new_err(format!("Error decoding asset: {e}")))?;
+        let asset_hash = phash(&asset);
+        let distances: Vec<Option<Result<u32, String>>> = thumbnails
+            .iter()
+            .map(|(_, _, bytes, skipped)| {
+                skipped.is_none().then(|| {
+                    let thumbnail = image::load_from_memory(bytes)
+                        .map_err(|e| format!("thumbnail cannot be decoded: {e}"))?;
+                    Ok((phash(&thumbnail) ^ asset_hash).count_ones())
+                })
+            })
+            .collect();
+        Ok::<_, PyErr>(distances)
+    };
+    let distances = if allow_threads {
+        py.allow_threads(compare)
+    } else {
+        compare()
+    }?;
+
+    let mut comparisons = Vec::with_capacity(thumbnails.len());
+    for ((ingredient, format, _, skipped), distance) in thumbnails.iter().zip(distances) {
+        let item = PyDict::new(py);
+        item.set_item("label", ingredient.label())?;
+        item.set_item("title", ingredient.title())?;
+        item.set_item(
+            "relationship",
+            serde_json::to_value(ingredient.relationship())
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_owned)),
+        )?;
+        item.set_item("format", format)?;
+        let (distance, error) = match distance {
+            Some(Ok(distance)) => (Some(distance), None),
+            Some(Err(error)) => (None, Some(error)),
+            None => (None, None),
+        };
+        item.set_item("distance", distance)?;
+        item.set_item("similarity", distance.map(|d| 1.0 - f64::from(d) / 64.0))?;
+        item.set_item("skipped", skipped)?;
+        item.set_item("error", error)?;
+        comparisons.push(item.into_any().unbind());
+    }
+    Ok(comparisons)
+}
@@ -0,0 +1,116 @@
+//! Recovering stripped manifests through soft bindings
+//!
+//! Durable Content Credentials pair the manifest with a watermark or a
+//! fingerprint of the content, so the manifest can be found again after the
+//! asset has been stripped of its metadata. Decoding watermarks and querying
+//! manifest repositories is left to a Python callback; the library calls it
+//! for assets without an embedded manifest and validates what it returns.
+
+use std::sync::Mutex;
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+use serde_json::{json, Value};
+
+use crate::c2pa_reader::verify_value;
+use crate::http::fetch_reported;
+
+static RESOLVER: Mutex<Option<PyObject>> = Mutex::new(None);
+
+/// Whether a resolver is registered
+pub(crate) fn has_resolver() -> bool {
+    RESOLVER.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+}
+
+/// What the resolver found for an asset
+enum Resolved {
+    Manifest(Vec<u8>),
+    Url(String),
+}
+
+fn call_resolver(py: Python, data: &[u8], mime_type: &str) -> PyResult<Option<Resolved>> {
+    let resolver = match RESOLVER.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(resolver) => resolver.clone_ref(py),
+        None => return Ok(None),
+    };
+    let found = resolver.call1(py, (PyBytes::new(py, data), mime_type))?;
+    let found = found.bind(py);
+    if found.is_none() {
+        Ok(None)
+    } else if let Ok(url) = found.downcast::<PyString>() {
+        Ok(Some(Resolved::Url(url.to_str()?.to_owned())))
+    } else if let Ok(manifest) = found.extract::<Vec<u8>>() {
+        Ok(Some(Resolved::Manifest(manifest)))
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "soft binding resolver must return bytes, a URL or None, not {}",
+            found.get_type().name()?
+        )))
+    }
+}
+
+/// Recover the manifest store of an asset with no embedded manifest
+///
+/// Returns `None` when no resolver is registered or the resolver found
+/// nothing. Recovered results carry a "soft_binding" item with the URL the
+/// manifest store was fetched from, if any, and downloaded ones the
+/// "fetch_attempts" of the download.
+pub(crate) fn resolve_soft_binding(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: bool,
+) -> PyResult<Option<Value>> {
+    let (manifest, url, attempts) = match call_resolver(py, data, mime_type)? {
+        None => return Ok(None),
+        Some(Resolved::Manifest(manifest)) => (manifest, None, None),
+        Some(Resolved::Url(url)) => {
+            let (manifest, attempts) = py.allow_threads(|| fetch_reported(&url))?;
+            (manifest, Some(url), Some(attempts))
+        }
+    };
+
+    let mut value = verify_value(py, &manifest, data, mime_type, allow_threads)?;
+    if let Some(map) = value.as_object_mut() {
+        map.insert("soft_binding".to_owned(), json!({ "url": url }));
+        if let Some(attempts) = attempts {
+            map.insert("fetch_attempts".to_owned(), attempts);
+        }
+    }
+    Ok(Some(value))
+}
+
+/// Register a callback that recovers manifests of stripped assets
+///
+/// When an asset has no embedded manifest, read_c2pa_from_file,
+/// read_c2pa_from_bytes, read_c2pa_json_from_bytes and
+/// read_c2pa_encoded_from_bytes call `resolver(data, mime_type)`. It can look up a watermark or fingerprint of
+/// the content and return the manifest store as bytes, a URL to download it
+/// from (with the settings of configure_http), or None if nothing is found. The
+/// recovered manifest store is validated against the asset and the result
+/// has "is_embedded" set to False and a "soft_binding" dict with the "url" it
+/// came from, or None. Downloaded stores add "fetch_attempts", as returned by
+/// fetch_url.
+///
+/// Exceptions raised by the resolver propagate to the caller of the read
+/// function.
+///
+/// Args:
+///     resolver: A callable, or None to remove the registered resolver
+///
+/// Raises:
+///     TypeError: If resolver is not callable
+#[pyfunction]
+#[pyo3(signature = (resolver))]
+pub fn set_soft_binding_resolver(resolver: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+    if let Some(resolver) = &resolver {
+        if !resolver.is_callable() {
+            return Err(PyTypeError::new_err(
+                "soft binding resolver must be callable",
+            ));
+        }
+    }
+    *RESOLVER.lock().unwrap_or_else(|e| e.into_inner()) = resolver.map(Bound::unbind);
+    Ok(())
+}
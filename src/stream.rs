@@ -0,0 +1,178 @@
+//! Reading assets from and writing output to Python file objects
+//!
+//! The JSON of a large manifest store can run to many megabytes. Serializing
+//! it straight into a file object, a chunk at a time, keeps the output from
+//! ever being held in memory as one string. Likewise an asset read through a
+//! file object's read() and seek() is never held in memory as a whole.
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+
+/// An io::Write handing its output to a Python file object's write() in
+/// chunks of about `chunk_size` bytes
+///
+/// Text files (io.TextIOBase) get str chunks, cut at character boundaries;
+/// other objects get bytes.
+pub(crate) struct PyWriter<'py> {
+    output: Bound<'py, PyAny>,
+    text: bool,
+    chunk_size: usize,
+    buffer: Vec<u8>,
+    written: usize,
+    /// The exception raised by write(), returned by `finish`
+    error: Option<PyErr>,
+}
+
+impl<'py> PyWriter<'py> {
+    pub(crate) fn new(output: Bound<'py, PyAny>, chunk_size: usize) -> PyResult<Self> {
+        let py = output.py();
+        let text = output.is_instance(&py.import("io")?.getattr("TextIOBase")?)?;
+        Ok(PyWriter {
+            output,
+            text,
+            chunk_size: chunk_size.max(1),
+            buffer: Vec::with_capacity(chunk_size.max(1)),
+            written: 0,
+            error: None,
+        })
+    }
+
+    /// Write the buffered bytes, keeping back the start of a character cut
+    /// off at the end of a text chunk
+    fn write_chunk(&mut self) -> PyResult<()> {
+        let py = self.output.py();
+        let (chunk, len) = if self.text {
+            let text = match std::str::from_utf8(&self.buffer) {
+                Ok(text) => text,
+                Err(e) => std::str::from_utf8(&self.buffer[..e.valid_up_to()]).unwrap_or_default(),
+            };
+            (PyString::new(py, text).into_any(), text.len())
+        } else {
+            (PyBytes::new(py, &self.buffer).into_any(), self.buffer.len())
+        };
+        if len == 0 {
+            return Ok(());
+        }
+        self.output.call_method1("write", (chunk,))?;
+        self.written += len;
+        self.buffer.drain(..len);
+        Ok(())
+    }
+
+    /// Write what is left and return the number of bytes written, or the
+    /// exception raised by the file object
+    pub(crate) fn finish(mut self) -> PyResult<usize> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        self.write_chunk()?;
+        Ok(self.written)
+    }
+}
+
+impl Write for PyWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= self.chunk_size {
+            if let Err(e) = self.write_chunk() {
+                self.error = Some(e);
+                return Err(io::Error::other("write() of the output failed"));
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// An io::Read and io::Seek over a binary Python file object's read() and
+/// seek()
+///
+/// Every call takes the GIL, so the reader can be used with it released.
+pub(crate) struct PyReader {
+    input: Py<PyAny>,
+    /// The exception raised by the file object, returned by `check`
+    error: Option<PyErr>,
+}
+
+impl PyReader {
+    pub(crate) fn new(input: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let seekable = match input.getattr("seekable") {
+            Ok(seekable) => seekable.call0()?.is_truthy()?,
+            Err(_) => input.hasattr("seek")?,
+        };
+        if !input.hasattr("read")? || !seekable {
+            return Err(PyTypeError::new_err(format!(
+                "file_obj must be a seekable binary file object, not {}",
+                input.get_type().name()?
+            )));
+        }
+        Ok(PyReader {
+            input: input.clone().unbind(),
+            error: None,
+        })
+    }
+
+    /// Keep the exception raised by the file object for `check`
+    fn fail(&mut self, error: PyErr, method: &str) -> io::Error {
+        self.error = Some(error);
+        io::Error::other(format!("{method}() of the input failed"))
+    }
+
+    /// The exception raised by the file object, if any, else `result` with
+    /// I/O errors raised as OSError
+    pub(crate) fn check<T>(&mut self, result: io::Result<T>) -> PyResult<T> {
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => result.map_err(PyErr::from),
+        }
+    }
+}
+
+impl Read for PyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let result = Python::with_gil(|py| {
+            let chunk = self.input.bind(py).call_method1("read", (buf.len(),))?;
+            let chunk = match chunk.downcast::<PyBytes>() {
+                Ok(chunk) => chunk.as_bytes(),
+                Err(_) => {
+                    return Err(PyTypeError::new_err(format!(
+                        "read() must return bytes, not {}",
+                        chunk.get_type().name()?
+                    )))
+                }
+            };
+            // A read() returning more than asked for is cut short
+            let len = chunk.len().min(buf.len());
+            buf[..len].copy_from_slice(&chunk[..len]);
+            Ok(len)
+        });
+        result.map_err(|e| self.fail(e, "read"))
+    }
+}
+
+impl Seek for PyReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (offset, whence) = match pos {
+            SeekFrom::Start(offset) => (offset as i64, 0),
+            SeekFrom::Current(offset) => (offset, 1),
+            SeekFrom::End(offset) => (offset, 2),
+        };
+        let result = Python::with_gil(|py| {
+            let input = self.input.bind(py);
+            let position = input.call_method1("seek", (offset, whence))?;
+            // seek() of some file-like objects returns None
+            if position.is_none() {
+                input.call_method0("tell")?.extract::<u64>()
+            } else {
+                position.extract::<u64>()
+            }
+        });
+        result.map_err(|e| self.fail(e, "seek"))
+    }
+}
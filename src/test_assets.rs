@@ -0,0 +1,406 @@
+//! Signed test assets made in memory
+//!
+//! Projects that consume Content Credentials need signed samples for their
+//! integration tests, and committing binary fixtures for each format makes
+//! them stale and opaque. make_signed_test_asset draws a small asset of the
+//! format and signs it with the development certificate of c2pa-rs, which is
+//! bundled for the purpose and trusted by nothing.
+
+use c2pa::assertions::Relationship;
+use c2pa::{Builder, SigningAlg};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyString};
+use serde_json::{json, Value};
+
+use crate::c2pa_reader::py_to_value;
+use crate::errors::{c2pa_error, UnsupportedFormatError};
+use crate::mime::sniff;
+use crate::signing::{new_ingredient, sign_builder, signer_config, SignerConfig, SigningKey};
+
+/// The c2pa-rs development signing certificate chain and its key, the ones the
+/// tests sign with
+const DEV_CERT: &[u8] = include_bytes!("../tests/sign_cert/es256.pub");
+const DEV_KEY: &[u8] = include_bytes!("../tests/sign_cert/es256.priv");
+
+/// The formats test assets can be made in, by extension, with their MIME type
+const FORMATS: &[(&str, &str)] = &[
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("png", "image/png"),
+    ("gif", "image/gif"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("wav", "audio/wav"),
+    ("mp3", "audio/mpeg"),
+    ("pdf", "application/pdf"),
+];
+
+/// The options make_signed_test_asset takes
+const OPTIONS: &[&str] = &[
+    "width",
+    "height",
+    "title",
+    "assertions",
+    "manifest_json",
+    "parent",
+    "signer_config",
+];
+
+/// The MIME type and extension of a format given either way
+fn format(format: &str) -> PyResult<(&'static str, &'static str)> {
+    let format = format.trim_start_matches('.').to_ascii_lowercase();
+    FORMATS
+        .iter()
+        .find(|(ext, mime_type)| *ext == format || *mime_type == format)
+        .map(|(ext, mime_type)| (*mime_type, *ext))
+        .ok_or_else(|| {
+            UnsupportedFormatError::new_err(format!("Cannot make test assets of format {format:?}"))
+        })
+}
+
+/// An option of make_signed_test_asset, if given
+fn option<'py, T: FromPyObject<'py>>(
+    options: Option<&Bound<'py, PyDict>>,
+    key: &str,
+) -> PyResult<Option<T>> {
+    let Some(value) = options
+        .map(|options| options.get_item(key))
+        .transpose()?
+        .flatten()
+    else {
+        return Ok(None);
+    };
+    if value.is_none() {
+        return Ok(None);
+    }
+    value.extract().map(Some).map_err(|e| {
+        PyTypeError::new_err(format!("Invalid option {key:?}: {}", e.value(value.py())))
+    })
+}
+
+/// A WAV of a tenth of a second of a 440 Hz triangle wave, 8 kHz 16-bit mono
+fn wav() -> Vec<u8> {
+    const RATE: u32 = 8000;
+    let samples: Vec<i16> = (0..RATE / 10)
+        .map(|i| {
+            let phase = (i * 440 % RATE) as i32 * 4 - 2 * RATE as i32;
+            ((phase.abs() - RATE as i32) * 4) as i16
+        })
+        .collect();
+    let size = samples.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + size as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + size).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, one channel, the byte rate, block alignment and bits of a sample
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&RATE.to_le_bytes());
+    wav.extend_from_slice(&(RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&size.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+/// An MP3 of a tenth of a second of silence: four MPEG-1 Layer III frames of
+/// 128 kbit/s at 44.1 kHz, whose zeroed side information decodes to nothing
+fn mp3() -> Vec<u8> {
+    const FRAME_LEN: usize = 417;
+    let mut frame = vec![0; FRAME_LEN];
+    frame[..4].copy_from_slice(&[0xff, 0xfb, 0x90, 0x64]);
+    frame.repeat(4)
+}
+
+/// An SVG of a gradient the size of the image assets
+fn svg(width: u32, height: u32) -> Vec<u8> {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\">\
+         <defs><linearGradient id=\"g\"><stop offset=\"0\" stop-color=\"#0080ff\"/>\
+         <stop offset=\"1\" stop-color=\"#ff8000\"/></linearGradient></defs>\
+         <rect width=\"100%\" height=\"100%\" fill=\"url(#g)\"/></svg>\n"
+    )
+    .into_bytes()
+}
+
+/// An image of a gradient, encoded with the `image` crate
+#[cfg(feature = "transforms")]
+fn image(mime_type: &str, width: u32, height: u32) -> PyResult<Vec<u8>> {
+    use std::io::Cursor;
+
+    use image::{DynamicImage, ImageFormat, RgbImage};
+
+    let format = ImageFormat::from_mime_type(mime_type).ok_or_else(|| {
+        UnsupportedFormatError::new_err(format!("Cannot encode images of type {mime_type}"))
+    })?;
+    let gradient = RgbImage::from_fn(width, height, |x, y| {
+        image::Rgb([
+            (x * 255 / width.max(1)) as u8,
+            (y * 255 / height.max(1)) as u8,
+            128,
+        ])
+    });
+    let image = match format {
+        ImageFormat::Gif => DynamicImage::ImageRgba8(DynamicImage::from(gradient).to_rgba8()),
+        _ => DynamicImage::ImageRgb8(gradient),
+    };
+    let mut encoded = Cursor::new(Vec::new());
+    image
+        .write_to(&mut encoded, format)
+        .map_err(|e| PyValueError::new_err(format!("Error encoding test image: {e}")))?;
+    Ok(encoded.into_inner())
+}
+
+#[cfg(not(feature = "transforms"))]
+fn image(mime_type: &str, _width: u32, _height: u32) -> PyResult<Vec<u8>> {
+    Err(UnsupportedFormatError::new_err(format!(
+        "Test assets of type {mime_type} need the 'transforms' cargo feature"
+    )))
+}
+
+/// A one-page PDF of a rectangle the size of the image assets, with `manifest`
+/// as its C2PA associated file, and the offset of the manifest bytes
+#[cfg(feature = "pdf")]
+fn pdf_document(manifest: &[u8], width: u32, height: u32) -> (Vec<u8>, usize) {
+    let content = format!("0 0.5 1 rg 0 0 {width} {height} re f");
+    let store_header = format!(
+        "<< /Type /EmbeddedFile /Subtype /application#2Fx-c2pa-manifest-store /Length {} >>\n\
+         stream\n",
+        manifest.len()
+    );
+    let objects = [
+        b"<< /Type /Catalog /Pages 2 0 R /AF [4 0 R] >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!("<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {width} {height}] /Contents 6 0 R >>")
+            .into_bytes(),
+        b"<< /Type /Filespec /F (manifest.c2pa) /UF (manifest.c2pa) \
+          /AFRelationship /C2PA_Manifest /EF << /F 5 0 R >> >>"
+            .to_vec(),
+        [store_header.as_bytes(), manifest, b"\nendstream"].concat(),
+        format!(
+            "<< /Length {} >>\nstream\n{content}\nendstream",
+            content.len()
+        )
+        .into_bytes(),
+    ];
+    let mut pdf = b"%PDF-1.7\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (number, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", number + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+    let manifest_offset = offsets[4] + "5 0 obj\n".len() + store_header.len();
+    let xref = pdf.len();
+    pdf.extend_from_slice(
+        format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+    );
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .as_bytes(),
+    );
+    (pdf, manifest_offset)
+}
+
+/// A signed PDF of the manifest of `builder`
+///
+/// c2pa-rs reads manifests from PDFs but cannot write them, so a placeholder
+/// manifest is embedded in the document, the document hashed without it, and
+/// the signed manifest, of the same size, written over the placeholder.
+#[cfg(feature = "pdf")]
+fn signed_pdf(
+    builder: &mut Builder,
+    config: &SignerConfig,
+    width: u32,
+    height: u32,
+) -> PyResult<Vec<u8>> {
+    use std::io::Cursor;
+
+    use c2pa::assertions::DataHash;
+    use c2pa::HashRange;
+
+    use crate::errors::{signing_error, SigningError};
+    use crate::settings;
+
+    const PDF: &str = "application/pdf";
+
+    settings::sync();
+    let signer = config
+        .signer()
+        .map_err(|e| PyValueError::new_err(format!("Invalid signer_config: {e}")))?;
+    let placeholder = builder
+        .data_hashed_placeholder(signer.reserve_size(), PDF)
+        .map_err(|e| signing_error("Error signing asset", &e))?;
+    let (mut pdf, offset) = pdf_document(&placeholder, width, height);
+    let mut data_hash = DataHash::new("jumbf manifest", "sha256");
+    data_hash.add_exclusion(HashRange::new(offset, placeholder.len()));
+    let manifest = data_hash
+        .gen_hash_from_stream(&mut Cursor::new(&pdf))
+        .and_then(|()| builder.sign_data_hashed_embeddable(signer.as_ref(), &data_hash, PDF))
+        .map_err(|e| signing_error("Error signing asset", &e))?;
+    if manifest.len() != placeholder.len() {
+        return Err(SigningError::new_err(
+            "Error signing asset: the manifest outgrew its placeholder",
+        ));
+    }
+    pdf[offset..offset + manifest.len()].copy_from_slice(&manifest);
+    Ok(pdf)
+}
+
+#[cfg(not(feature = "pdf"))]
+fn signed_pdf(
+    _builder: &mut Builder,
+    _config: &SignerConfig,
+    _width: u32,
+    _height: u32,
+) -> PyResult<Vec<u8>> {
+    Err(UnsupportedFormatError::new_err(
+        "Test assets of type application/pdf need the 'pdf' cargo feature",
+    ))
+}
+
+/// The manifest definition of a test asset when none is given
+fn default_definition(title: &str, mime_type: &str, parent: bool, assertions: Vec<Value>) -> Value {
+    let action = if parent {
+        json!({"action": "c2pa.opened"})
+    } else {
+        json!({
+            "action": "c2pa.created",
+            "digitalSourceType": "http://cv.iptc.org/newscodes/digitalsourcetype/digitalCapture",
+        })
+    };
+    let mut all = vec![json!({"label": "c2pa.actions", "data": {"actions": [action]}})];
+    all.extend(assertions);
+    json!({
+        "claim_generator_info": [{
+            "name": "fast_c2pa_python test asset",
+            "version": env!("CARGO_PKG_VERSION"),
+        }],
+        "title": title,
+        "format": mime_type,
+        "assertions": all,
+    })
+}
+
+/// Make a small signed asset for tests, without a fixture file
+///
+/// The asset is a gradient image, a one-page PDF, a short tone for WAV or
+/// silence for MP3, signed with the
+/// c2pa-rs development certificate bundled in the library. That certificate
+/// is in no trust list, so with enable_trust_checks(True) reads report
+/// signingCredential.untrusted; give "signer_config" to sign with a test
+/// certificate of your own trust configuration instead.
+///
+/// Args:
+///     format: Extension or MIME type of the asset: jpeg, png, gif, tiff and
+///         webp (builds with the "transforms" cargo feature), pdf (builds with
+///         the "pdf" cargo feature), svg, wav or mp3
+///     options: A dict of any of "width" and "height" of images in pixels
+///         (default: 64 and 48), "title" (default: "test_asset.<ext>"),
+///         "assertions" (a list of assertion definitions added after the
+///         c2pa.actions of the default manifest), "manifest_json" (a manifest
+///         definition as str or dict replacing the default one), "parent" (the
+///         bytes of an asset recorded as parentOf ingredient, to make
+///         provenance chains) and "signer_config" (as for sign_and_embed)
+///         (default: None)
+///
+/// Returns:
+///     The signed asset as bytes
+///
+/// Raises:
+///     ValueError: If an option is unknown or invalid
+///     TypeError: If an option has the wrong type
+///     UnsupportedFormatError: If test assets of the format cannot be made
+///     SigningError: If the asset cannot be signed
+#[pyfunction]
+#[pyo3(signature = (format, options=None))]
+pub fn make_signed_test_asset(
+    py: Python,
+    format: &str,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Py<PyBytes>> {
+    for key in options.into_iter().flat_map(|options| options.keys()) {
+        let key: String = key.extract()?;
+        if !OPTIONS.contains(&key.as_str()) {
+            return Err(PyValueError::new_err(format!("Unknown option {key:?}")));
+        }
+    }
+    let (mime_type, ext) = self::format(format)?;
+    let width: u32 = option(options, "width")?.unwrap_or(64);
+    let height: u32 = option(options, "height")?.unwrap_or(48);
+    if width == 0 || height == 0 || width > 4096 || height > 4096 {
+        return Err(PyValueError::new_err(
+            "width and height must be between 1 and 4096",
+        ));
+    }
+    let title: String = option(options, "title")?.unwrap_or_else(|| format!("test_asset.{ext}"));
+    let parent: Option<Vec<u8>> = option(options, "parent")?;
+    let config = match option::<Bound<PyAny>>(options, "signer_config")? {
+        Some(config) => signer_config(&config)?,
+        None => SignerConfig {
+            alg: SigningAlg::Es256,
+            sign_cert: DEV_CERT.to_vec(),
+            key: SigningKey::Pem(DEV_KEY.to_vec()),
+            ta_url: None,
+        },
+    };
+    let definition = match option::<Bound<PyAny>>(options, "manifest_json")? {
+        Some(definition) => match definition.downcast::<PyString>() {
+            Ok(text) => text.to_str()?.to_owned(),
+            Err(_) => py_to_value(py, &definition)?.to_string(),
+        },
+        None => {
+            let assertions = match option::<Bound<PyAny>>(options, "assertions")? {
+                Some(assertions) => match py_to_value(py, &assertions)? {
+                    Value::Array(assertions) => assertions,
+                    _ => {
+                        return Err(PyTypeError::new_err(
+                            "Invalid option \"assertions\": expected a list",
+                        ))
+                    }
+                },
+                None => Vec::new(),
+            };
+            default_definition(&title, mime_type, parent.is_some(), assertions).to_string()
+        }
+    };
+
+    let mut builder = Builder::from_json(&definition)
+        .map_err(|e| PyValueError::new_err(format!("Invalid manifest definition: {e}")))?;
+    if let Some(parent) = &parent {
+        let parent_type = sniff(parent).ok_or_else(|| {
+            UnsupportedFormatError::new_err("Cannot tell the format of the parent asset")
+        })?;
+        let ingredient = new_ingredient(parent, parent_type, None, Relationship::ParentOf)
+            .map_err(|e| c2pa_error("Error creating ingredient", &e))?;
+        builder.add_ingredient(ingredient);
+    }
+    let signed = match mime_type {
+        "application/pdf" => signed_pdf(&mut builder, &config, width, height)?,
+        "audio/wav" => sign_builder(&mut builder, &config, mime_type, &wav())?,
+        "audio/mpeg" => sign_builder(&mut builder, &config, mime_type, &mp3())?,
+        "image/svg+xml" => sign_builder(&mut builder, &config, mime_type, &svg(width, height))?,
+        _ => sign_builder(
+            &mut builder,
+            &config,
+            mime_type,
+            &image(mime_type, width, height)?,
+        )?,
+    };
+    Ok(PyBytes::new(py, &signed).unbind())
+}
@@ -0,0 +1,130 @@
+//! The claim thumbnail as pixels
+//!
+//! Models scoring an asset against its signed thumbnail want the pixels, not
+//! the JPEG or PNG stored in the manifest. Decoding here saves a Pillow round
+//! trip and hands numpy one buffer to wrap. Ingredients of new manifests get
+//! their thumbnails made here too.
+
+#[cfg(feature = "signing")]
+use std::io::Cursor;
+
+use image::GenericImageView;
+#[cfg(feature = "signing")]
+use image::{DynamicImage, ImageFormat};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyByteArray;
+
+use crate::c2pa_reader::read_reader;
+use crate::errors::C2paError;
+use crate::gil;
+use crate::resources::Budget;
+
+/// A decoded thumbnail: height, width, channels and the interleaved 8-bit samples
+struct Pixels {
+    height: u32,
+    width: u32,
+    channels: u8,
+    samples: Vec<u8>,
+}
+
+/// Decode `bytes` to RGB, or RGBA when the image has an alpha channel
+fn decode(bytes: &[u8]) -> Result<Pixels, image::ImageError> {
+    let image = image::load_from_memory(bytes)?;
+    let (width, height) = image.dimensions();
+    let (channels, samples) = if image.color().has_alpha() {
+        (4, image.to_rgba8().into_raw())
+    } else {
+        (3, image.to_rgb8().into_raw())
+    };
+    Ok(Pixels {
+        height,
+        width,
+        channels,
+        samples,
+    })
+}
+
+/// Longest side of the thumbnails made for ingredients, as c2pa-rs makes them
+#[cfg(feature = "signing")]
+const THUMBNAIL_SIZE: u32 = 1024;
+
+/// A thumbnail of the image `data`, at most THUMBNAIL_SIZE pixels on its
+/// longest side, as (format, bytes): a JPEG, or a PNG when the image has
+/// transparent pixels. None if the image cannot be decoded.
+#[cfg(feature = "signing")]
+pub(crate) fn make_thumbnail(data: &[u8]) -> Option<(&'static str, Vec<u8>)> {
+    let mut image = image::load_from_memory(data).ok()?;
+    if image.width().max(image.height()) > THUMBNAIL_SIZE {
+        image = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+    }
+    let mut out = Cursor::new(Vec::new());
+    let rgba = image.color().has_alpha().then(|| image.to_rgba8());
+    let format = if let Some(rgba) = rgba.filter(|rgba| rgba.pixels().any(|p| p[3] < u8::MAX)) {
+        DynamicImage::ImageRgba8(rgba).write_to(&mut out, ImageFormat::Png).ok()?;
+        "image/png"
+    } else {
+        DynamicImage::ImageRgb8(image.to_rgb8()).write_to(&mut out, ImageFormat::Jpeg).ok()?;
+        "image/jpeg"
+    };
+    Some((format, out.into_inner()))
+}
+
+/// Get the claim thumbnail of the active manifest as a numpy array
+///
+/// The thumbnail is decoded to 8-bit samples, an HxWx3 RGB array, or HxWx4
+/// RGBA when it has an alpha channel. Grayscale thumbnails are expanded to RGB
+/// so models always get the same layout. Requires numpy, imported on the first
+/// call that finds a thumbnail.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     A writable numpy.ndarray of dtype uint8 with shape (height, width,
+///     channels), or None if there is no C2PA metadata or the active manifest
+///     has no thumbnail stored in the asset
+///
+/// Raises:
+///     C2paError: If there is an error reading the C2PA data or decoding the thumbnail
+///     ValueError: If the thumbnail is over the limits set by set_resource_limits
+///     ImportError: If numpy is not installed
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, allow_threads=None))]
+pub fn get_thumbnail_array(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<Option<PyObject>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let Some(reader) = read_reader(py, data, mime_type, allow_threads)? else {
+        return Ok(None);
+    };
+    let Some((_, bytes)) = reader.active_manifest().and_then(|m| m.thumbnail()) else {
+        return Ok(None);
+    };
+    if let Some(limit) = Budget::new().admit(bytes.len()) {
+        return Err(PyValueError::new_err(format!(
+            "thumbnail of {} bytes is over the {limit} resource limit",
+            bytes.len()
+        )));
+    }
+    let pixels = if allow_threads {
+        py.allow_threads(|| decode(&bytes))
+    } else {
+        decode(&bytes)
+    }
+    .map_err(|e| C2paError::new_err(format!("Error decoding thumbnail: {e}")))?;
+
+    let numpy = py.import("numpy")?;
+    // A bytearray keeps the array writable, unlike a view of bytes
+    let buffer = PyByteArray::new(py, &pixels.samples);
+    let array = numpy
+        .call_method1("frombuffer", (buffer, "uint8"))?
+        .call_method1("reshape", ((pixels.height, pixels.width, pixels.channels),))?;
+    Ok(Some(array.unbind()))
+}
@@ -0,0 +1,566 @@
+//! Image transforms that keep the C2PA data
+//!
+//! Resizing or re-encoding an asset with the image crate drops its manifest
+//! store. The pipeline here takes the store out first, applies the operations
+//! and embeds the store again in the output, in whatever format it ends up.
+//! The data hash still describes the original asset, so the result reads
+//! with a hash mismatch until it is signed again.
+
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use c2pa::jumbf_io::{load_jumbf_from_stream, save_jumbf_to_memory};
+use c2pa::Error;
+use image::codecs::avif::AvifEncoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::{
+    ColorType, DynamicImage, ExtendedColorType, GenericImageView, ImageDecoder, ImageEncoder,
+    ImageFormat, ImageReader, ImageResult,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use serde_json::{json, Value};
+
+use crate::c2pa_reader::value_to_py;
+use crate::errors::{c2pa_error, C2paError};
+use crate::gil;
+use crate::jumbf::jpeg_segments;
+use crate::mime;
+
+/// One operation of a transform pipeline
+enum Op {
+    Resize {
+        width: Option<u32>,
+        height: Option<u32>,
+        exact: bool,
+        filter: FilterType,
+    },
+    Crop {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    Grayscale,
+    Rotate(u32),
+}
+
+/// The operations of a pipeline and the encoding of its output
+struct Pipeline {
+    ops: Vec<Op>,
+    format: Option<ImageFormat>,
+    quality: Option<u8>,
+}
+
+/// The image format of a MIME type or file extension
+fn image_format(mime_type: &str) -> Option<ImageFormat> {
+    ImageFormat::from_mime_type(mime_type).or_else(|| ImageFormat::from_extension(mime_type))
+}
+
+fn invalid(index: usize, message: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(format!("Invalid transform op {index}: {message}"))
+}
+
+/// The value of `key` of an op, if given
+fn field<'py, T: FromPyObject<'py>>(
+    op: &Bound<'py, PyDict>,
+    index: usize,
+    key: &str,
+) -> PyResult<Option<T>> {
+    match op.get_item(key)? {
+        Some(value) if !value.is_none() => value
+            .extract()
+            .map(Some)
+            .map_err(|e| invalid(index, format!("{key:?}: {e}"))),
+        _ => Ok(None),
+    }
+}
+
+fn required<'py, T: FromPyObject<'py>>(
+    op: &Bound<'py, PyDict>,
+    index: usize,
+    key: &str,
+) -> PyResult<T> {
+    field(op, index, key)?.ok_or_else(|| invalid(index, format!("needs {key:?}")))
+}
+
+fn filter(name: &str) -> Option<FilterType> {
+    Some(match name {
+        "nearest" => FilterType::Nearest,
+        "triangle" | "bilinear" => FilterType::Triangle,
+        "catmullrom" | "bicubic" => FilterType::CatmullRom,
+        "gaussian" => FilterType::Gaussian,
+        "lanczos3" => FilterType::Lanczos3,
+        _ => return None,
+    })
+}
+
+fn pipeline(ops: &[Bound<'_, PyDict>]) -> PyResult<Pipeline> {
+    let mut pipeline = Pipeline {
+        ops: Vec::new(),
+        format: None,
+        quality: None,
+    };
+    for (index, op) in ops.iter().enumerate() {
+        let name: String = required(op, index, "op")?;
+        match name.as_str() {
+            "resize" => {
+                let width = field(op, index, "width")?;
+                let height = field(op, index, "height")?;
+                if width.is_none() && height.is_none() || width == Some(0) || height == Some(0) {
+                    return Err(invalid(index, "resize needs a non-zero width or height"));
+                }
+                let name: String = field(op, index, "filter")?.unwrap_or("lanczos3".to_owned());
+                let filter = filter(&name)
+                    .ok_or_else(|| invalid(index, format!("unknown filter {name:?}")))?;
+                pipeline.ops.push(Op::Resize {
+                    width,
+                    height,
+                    exact: field(op, index, "exact")?.unwrap_or(false),
+                    filter,
+                });
+            }
+            "crop" => {
+                let (width, height) = (
+                    required(op, index, "width")?,
+                    required(op, index, "height")?,
+                );
+                if width == 0 || height == 0 {
+                    return Err(invalid(index, "crop needs a non-zero width and height"));
+                }
+                pipeline.ops.push(Op::Crop {
+                    x: field(op, index, "x")?.unwrap_or(0),
+                    y: field(op, index, "y")?.unwrap_or(0),
+                    width,
+                    height,
+                });
+            }
+            "grayscale" => pipeline.ops.push(Op::Grayscale),
+            "rotate" => {
+                let degrees: i64 = required(op, index, "degrees")?;
+                if degrees % 90 != 0 {
+                    return Err(invalid(index, "rotate takes multiples of 90 degrees"));
+                }
+                pipeline
+                    .ops
+                    .push(Op::Rotate(degrees.rem_euclid(360) as u32));
+            }
+            "format" => {
+                let mime_type: String = required(op, index, "mime_type")?;
+                let format = image_format(&mime_type).filter(|f| f.writing_enabled());
+                pipeline.format = Some(
+                    format.ok_or_else(|| invalid(index, format!("cannot encode {mime_type:?}")))?,
+                );
+            }
+            "quality" => {
+                let quality: u8 = required(op, index, "quality")?;
+                if !(1..=100).contains(&quality) {
+                    return Err(invalid(index, "quality is from 1 to 100"));
+                }
+                pipeline.quality = Some(quality);
+            }
+            _ => return Err(invalid(index, format!("unknown op {name:?}"))),
+        }
+    }
+    Ok(pipeline)
+}
+
+fn apply(image: DynamicImage, op: &Op) -> PyResult<DynamicImage> {
+    let (w, h) = image.dimensions();
+    Ok(match *op {
+        Op::Resize {
+            width,
+            height,
+            exact,
+            filter,
+        } => match (width, height) {
+            (Some(width), Some(height)) if exact => image.resize_exact(width, height, filter),
+            (Some(width), Some(height)) => image.resize(width, height, filter),
+            // One side given: the other keeps the aspect ratio
+            (Some(width), None) => {
+                let height = (u64::from(h) * u64::from(width) / u64::from(w)).max(1) as u32;
+                image.resize_exact(width, height, filter)
+            }
+            (None, Some(height)) => {
+                let width = (u64::from(w) * u64::from(height) / u64::from(h)).max(1) as u32;
+                image.resize_exact(width, height, filter)
+            }
+            (None, None) => image,
+        },
+        Op::Crop {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            if u64::from(x) + u64::from(width) > u64::from(w)
+                || u64::from(y) + u64::from(height) > u64::from(h)
+            {
+                return Err(PyValueError::new_err(format!(
+                    "crop of {width}x{height} at ({x}, {y}) is outside the {w}x{h} image"
+                )));
+            }
+            image.crop_imm(x, y, width, height)
+        }
+        Op::Grayscale => image.grayscale(),
+        Op::Rotate(90) => image.rotate90(),
+        Op::Rotate(180) => image.rotate180(),
+        Op::Rotate(270) => image.rotate270(),
+        Op::Rotate(_) => image,
+    })
+}
+
+/// The action of the c2pa.actions assertion describing `op`
+fn action(op: &Op) -> Value {
+    match op {
+        Op::Resize { .. } => json!({ "action": "c2pa.resized" }),
+        Op::Crop { .. } => json!({ "action": "c2pa.cropped" }),
+        Op::Grayscale => json!({
+            "action": "c2pa.color_adjustments",
+            "description": "Converted to grayscale",
+        }),
+        Op::Rotate(_) => json!({ "action": "c2pa.orientation" }),
+    }
+}
+
+/// A decoded input, with the colour information DynamicImage leaves out
+struct Decoded {
+    image: DynamicImage,
+    icc_profile: Option<Vec<u8>>,
+    cmyk: bool,
+}
+
+/// The number of components of a JPEG frame, from its SOF segment
+fn jpeg_components(data: &[u8]) -> Option<u8> {
+    jpeg_segments(data)
+        .iter()
+        .find(|s| matches!(s.marker, 0xC0..=0xCF) && !matches!(s.marker, 0xC4 | 0xC8 | 0xCC))
+        .and_then(|s| data.get(s.offset + 9).copied())
+}
+
+fn decode(data: &[u8], format: ImageFormat) -> ImageResult<Decoded> {
+    let mut decoder = ImageReader::with_format(Cursor::new(data), format).into_decoder()?;
+    let icc_profile = decoder.icc_profile()?;
+    // The JPEG decoder reports CMYK frames as the RGB it converts them to
+    let cmyk = decoder.original_color_type() == ExtendedColorType::Cmyk8
+        || format == ImageFormat::Jpeg && jpeg_components(data) == Some(4);
+    Ok(Decoded {
+        image: DynamicImage::from_decoder(decoder)?,
+        icc_profile,
+        cmyk,
+    })
+}
+
+/// Bits per sample of `color`
+fn bits(color: ColorType) -> u16 {
+    u16::from(color.bytes_per_pixel() / color.channel_count()) * 8
+}
+
+/// The most bits per sample `format` stores
+fn max_bits(format: ImageFormat) -> u16 {
+    match format {
+        ImageFormat::Png | ImageFormat::Tiff => 16,
+        ImageFormat::OpenExr => 32,
+        _ => 8,
+    }
+}
+
+/// `image` with its samples reduced to 8 or, for `bits` over 8, 16 bits
+fn reduce(image: DynamicImage, bits: u16) -> DynamicImage {
+    let color = image.color();
+    match (bits > 8, color.has_color(), color.has_alpha()) {
+        (false, false, false) => DynamicImage::ImageLuma8(image.to_luma8()),
+        (false, false, true) => DynamicImage::ImageLumaA8(image.to_luma_alpha8()),
+        (false, true, false) => DynamicImage::ImageRgb8(image.to_rgb8()),
+        (false, true, true) => DynamicImage::ImageRgba8(image.to_rgba8()),
+        (true, false, false) => DynamicImage::ImageLuma16(image.to_luma16()),
+        (true, false, true) => DynamicImage::ImageLumaA16(image.to_luma_alpha16()),
+        (true, true, false) => DynamicImage::ImageRgb16(image.to_rgb16()),
+        (true, true, true) => DynamicImage::ImageRgba16(image.to_rgba16()),
+    }
+}
+
+/// `encoder` with `icc_profile` set, for the encoders that take one
+fn with_icc(mut encoder: impl ImageEncoder, icc_profile: Option<Vec<u8>>) -> impl ImageEncoder {
+    if let Some(icc_profile) = icc_profile {
+        // Only fails for encoders without ICC support, which never get a profile
+        let _ = encoder.set_icc_profile(icc_profile);
+    }
+    encoder
+}
+
+fn encode(
+    image: DynamicImage,
+    format: ImageFormat,
+    quality: Option<u8>,
+    icc_profile: Option<Vec<u8>>,
+) -> ImageResult<Vec<u8>> {
+    // JPEG has no alpha channel
+    let image = match (format, image.color()) {
+        (ImageFormat::Jpeg, ColorType::La8) => DynamicImage::ImageLuma8(image.to_luma8()),
+        (ImageFormat::Jpeg, ColorType::Rgba8) => DynamicImage::ImageRgb8(image.to_rgb8()),
+        _ => image,
+    };
+    let mut encoded = Cursor::new(Vec::new());
+    match format {
+        ImageFormat::Jpeg => {
+            let encoder = JpegEncoder::new_with_quality(&mut encoded, quality.unwrap_or(75));
+            image.write_with_encoder(with_icc(encoder, icc_profile))?
+        }
+        ImageFormat::Png => {
+            image.write_with_encoder(with_icc(PngEncoder::new(&mut encoded), icc_profile))?
+        }
+        ImageFormat::WebP => image.write_with_encoder(with_icc(
+            WebPEncoder::new_lossless(&mut encoded),
+            icc_profile,
+        ))?,
+        ImageFormat::Avif => match quality {
+            Some(quality) => image.write_with_encoder(AvifEncoder::new_with_speed_quality(
+                &mut encoded,
+                4,
+                quality,
+            ))?,
+            None => image.write_to(&mut encoded, format)?,
+        },
+        _ => image.write_to(&mut encoded, format)?,
+    }
+    Ok(encoded.into_inner())
+}
+
+/// How a transform was run
+struct Options<'a> {
+    input_format: ImageFormat,
+    mime_type: &'a str,
+    output_format: ImageFormat,
+    allow_color_conversion: bool,
+}
+
+/// Decode `data`, run `pipeline` on it and encode the result with the
+/// manifest store of `data`, if it has one, together with the actions taken
+fn transform(
+    data: &[u8],
+    options: &Options,
+    pipeline: &Pipeline,
+) -> PyResult<(Vec<u8>, Vec<Value>)> {
+    let jumbf = match load_jumbf_from_stream(options.mime_type, &mut Cursor::new(data)) {
+        Ok(jumbf) => Some(jumbf),
+        Err(Error::JumbfNotFound | Error::UnsupportedType) => None,
+        Err(e) => return Err(c2pa_error("Failed to load JUMBF", &e)),
+    };
+    let Decoded {
+        mut image,
+        mut icc_profile,
+        cmyk,
+    } = decode(data, options.input_format)
+        .map_err(|e| C2paError::new_err(format!("Failed to decode input: {e}")))?;
+    let output_mime = options.output_format.to_mime_type();
+
+    // Colour the output cannot keep as it is, checked before any work is done
+    let mut conversions = Vec::new();
+    if cmyk {
+        // The image crate converts CMYK samples without colour management,
+        // and the profile describes the CMYK samples
+        conversions.push("Converted from CMYK to RGB".to_owned());
+        icc_profile = None;
+    }
+    let (sample_bits, max_bits) = (bits(image.color()), max_bits(options.output_format));
+    if sample_bits > max_bits {
+        conversions.push(format!(
+            "Reduced {sample_bits}-bit samples to {max_bits} bits for {output_mime}"
+        ));
+        image = reduce(image, max_bits);
+    }
+    let keeps_icc = matches!(
+        options.output_format,
+        ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP
+    );
+    if icc_profile.is_some() && !keeps_icc {
+        conversions.push(format!(
+            "Removed the ICC profile, which {output_mime} cannot carry"
+        ));
+        icc_profile = None;
+    }
+    if !conversions.is_empty() && !options.allow_color_conversion {
+        return Err(PyValueError::new_err(format!(
+            "The output cannot keep the colour of the input: {}; \
+             pass allow_color_conversion=True to accept",
+            conversions.join("; ")
+        )));
+    }
+
+    let mut actions: Vec<Value> = conversions
+        .into_iter()
+        .map(|c| json!({ "action": "c2pa.color_adjustments", "description": c }))
+        .collect();
+    for op in &pipeline.ops {
+        image = apply(image, op)?;
+        actions.push(action(op));
+    }
+    if options.output_format != options.input_format || pipeline.quality.is_some() {
+        actions.push(json!({
+            "action": "c2pa.transcoded",
+            "description": format!("Encoded as {output_mime}"),
+        }));
+    }
+    let encoded = encode(image, options.output_format, pipeline.quality, icc_profile)
+        .map_err(|e| C2paError::new_err(format!("Failed to encode output: {e}")))?;
+    let Some(jumbf) = jumbf else {
+        return Ok((encoded, actions));
+    };
+    let embedded = save_jumbf_to_memory(output_mime, &encoded, &jumbf).map_err(|e| match e {
+        Error::UnsupportedType => {
+            PyValueError::new_err(format!("Cannot embed C2PA data in {output_mime}"))
+        }
+        e => c2pa_error("Failed to save output with jumbf", &e),
+    })?;
+    Ok((embedded, actions))
+}
+
+/// Transform an image, keeping its C2PA data
+///
+/// The operations run in order on the decoded image, which is then encoded
+/// and has the manifest store of the input embedded again. Assets without a
+/// manifest store are transformed all the same. The data hash of the store
+/// still describes the input, so sign the result again with the input as its
+/// ingredient to have it validate.
+///
+/// ICC profiles are kept in JPEG, PNG and WebP output, and 16-bit samples in
+/// PNG and TIFF output. Transforms that would change the colour of the input
+/// otherwise, by converting CMYK to RGB, reducing the sample depth or dropping
+/// the ICC profile, raise ValueError unless allow_color_conversion is set.
+///
+/// Args:
+///     input: The image, as bytes or a path (str or os.PathLike)
+///     output: The path to write the result to, or None to return it as bytes
+///     ops: A list of dicts with an "op" key, one of
+///         {"op": "resize", "width": w, "height": h}: fit within w x h, keeping the
+///             aspect ratio; with one side only, scale to it; "exact": True stretches
+///             to w x h; "filter": "nearest", "triangle", "catmullrom", "gaussian"
+///             or "lanczos3" (default)
+///         {"op": "crop", "x": x, "y": y, "width": w, "height": h}
+///         {"op": "grayscale"}
+///         {"op": "rotate", "degrees": d}: clockwise, by a multiple of 90
+///         {"op": "format", "mime_type": m}: encode the output as m (default: the
+///             format of output's extension, else of the input)
+///         {"op": "quality", "quality": q}: JPEG or AVIF quality from 1 to 100
+///     mime_type: MIME type of the input (default: None, detected from its content,
+///         else from its extension)
+///     allow_color_conversion: Convert the colour of the input when the output
+///         cannot keep it, instead of raising ValueError (default: False)
+///     with_actions: Also return the actions taken (default: False)
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     The transformed image as bytes, or None when written to output. With
+///     with_actions a (result, actions) tuple, actions being the entries of a
+///     c2pa.actions assertion for the transform, colour conversions included,
+///     to record when signing the result again
+///
+/// Raises:
+///     ValueError: If an op is invalid, a format cannot be decoded or encoded, the
+///         colour would change without allow_color_conversion, or the manifest store
+///         cannot be embedded in the output format
+///     C2paError: If the input cannot be decoded or the output encoded
+///     OSError: If input or output cannot be read or written
+#[pyfunction]
+#[pyo3(signature = (
+    input, output, ops, mime_type=None, allow_color_conversion=false, with_actions=false,
+    allow_threads=None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn transform_keep_c2pa(
+    py: Python,
+    input: &Bound<'_, PyAny>,
+    output: Option<PathBuf>,
+    ops: Vec<Bound<'_, PyDict>>,
+    mime_type: Option<&str>,
+    allow_color_conversion: bool,
+    with_actions: bool,
+    allow_threads: Option<bool>,
+) -> PyResult<PyObject> {
+    let pipeline = pipeline(&ops)?;
+    let (data, input_path) = match input.downcast::<PyBytes>() {
+        Ok(bytes) => (bytes.as_bytes().to_vec(), None),
+        Err(_) => {
+            let path: PathBuf = input.extract()?;
+            (std::fs::read(&path)?, Some(path))
+        }
+    };
+    let mime_type = match mime_type {
+        Some(mime_type) => mime_type.to_owned(),
+        None => mime::sniff(&data)
+            .map(str::to_owned)
+            .or_else(|| input_path.as_deref().and_then(c2pa::format_from_path))
+            .ok_or_else(|| {
+                PyValueError::new_err("Cannot detect the MIME type of the input, pass mime_type")
+            })?,
+    };
+    let input_format = image_format(&mime_type)
+        .filter(|f| f.reading_enabled())
+        .ok_or_else(|| PyValueError::new_err(format!("Cannot decode {mime_type}")))?;
+    let output_format = pipeline
+        .format
+        .or_else(|| {
+            output
+                .as_deref()
+                .and_then(|p| ImageFormat::from_path(p).ok())
+        })
+        .filter(|f| f.writing_enabled())
+        .unwrap_or(input_format);
+    if pipeline.quality.is_some() && !matches!(output_format, ImageFormat::Jpeg | ImageFormat::Avif)
+    {
+        return Err(PyValueError::new_err(format!(
+            "quality applies to JPEG and AVIF output only, not {}",
+            output_format.to_mime_type()
+        )));
+    }
+
+    let options = Options {
+        input_format,
+        mime_type: &mime_type,
+        output_format,
+        allow_color_conversion,
+    };
+    let run = || transform(&data, &options, &pipeline);
+    let (transformed, actions) = if gil::release(allow_threads, data.len()) {
+        py.allow_threads(run)
+    } else {
+        run()
+    }?;
+    let result = match output {
+        Some(path) => {
+            std::fs::write(path, transformed)?;
+            py.None()
+        }
+        None => PyBytes::new(py, &transformed).into_any().unbind(),
+    };
+    if !with_actions {
+        return Ok(result);
+    }
+    let actions = value_to_py(py, &Value::Array(actions))?;
+    Ok((result, actions).into_pyobject(py)?.into_any().unbind())
+}
+
+/// Convert an image file to grayscale, keeping its C2PA data
+///
+/// Same as transform_keep_c2pa(input_path, output_path, [{"op": "grayscale"}],
+/// format).
+#[pyfunction]
+pub fn convert_to_gray_keep_c2pa(
+    py: Python,
+    input_path: &str,
+    output_path: &str,
+    format: &str,
+) -> PyResult<()> {
+    let ops = vec![PyDict::new(py)];
+    ops[0].set_item("op", "grayscale")?;
+    let input = input_path.into_pyobject(py)?.into_any();
+    let output = Some(output_path.into());
+    transform_keep_c2pa(py, &input, output, ops, Some(format), false, false, None)?;
+    Ok(())
+}
@@ -0,0 +1,115 @@
+//! Checking manifest signatures against a transparency log
+//!
+//! Organizations archiving provenance can record every signature they accept
+//! in an append-only log (Sigstore Rekor, Trillian or an in-house audit log).
+//! Querying the log is left to a Python callback; the library hands it each
+//! manifest's signature and puts the answer in the result.
+
+use std::sync::Mutex;
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes, PyDict};
+use serde_json::{json, Value};
+
+use crate::c2pa_reader::{py_to_value, value_to_py};
+use crate::jumbf::manifest_signatures;
+
+static VERIFIER: Mutex<Option<PyObject>> = Mutex::new(None);
+
+/// Whether a transparency log verifier is registered
+pub(crate) fn has_verifier() -> bool {
+    VERIFIER.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+}
+
+/// Check the signatures of the manifests in `store` with the registered
+/// verifier
+///
+/// The answer for each manifest is stored in its "transparency_log" item, and
+/// manifests the log does not include are reported in "warnings".
+pub(crate) fn check_transparency_log(py: Python, value: &mut Value, store: &[u8]) -> PyResult<()> {
+    let Some(verifier) = VERIFIER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|verifier| verifier.clone_ref(py))
+    else {
+        return Ok(());
+    };
+
+    let mut warnings = Vec::new();
+    for (label, signature) in manifest_signatures(store) {
+        let Some(manifest) = value.get_mut("manifests").and_then(|m| m.get_mut(label)) else {
+            continue;
+        };
+        let signature_info =
+            value_to_py(py, manifest.get("signature_info").unwrap_or(&Value::Null))?;
+        let answer = verifier.call1(py, (label, PyBytes::new(py, signature), signature_info))?;
+        let answer = answer.bind(py);
+        if answer.is_none() {
+            continue;
+        }
+        let included = answer
+            .downcast::<PyDict>()
+            .ok()
+            .and_then(|answer| answer.get_item("included").ok().flatten())
+            .filter(|included| included.is_instance_of::<PyBool>());
+        let Some(included) = included else {
+            return Err(PyTypeError::new_err(
+                "transparency log verifier must return a dict with a bool \"included\", or None",
+            ));
+        };
+        if !included.extract::<bool>()? {
+            warnings.push(json!({
+                "code": "transparencyLog.notIncluded",
+                "label": label,
+                "explanation": format!("the signature of {label} is not in the transparency log"),
+            }));
+        }
+        manifest["transparency_log"] = py_to_value(py, answer)?;
+    }
+
+    if !warnings.is_empty() {
+        match value.get_mut("warnings").and_then(Value::as_array_mut) {
+            Some(existing) => existing.extend(warnings),
+            None => value["warnings"] = json!(warnings),
+        }
+    }
+    Ok(())
+}
+
+/// Register a callback that checks manifest signatures against a transparency log
+///
+/// For each manifest of the stores read by read_c2pa_from_file,
+/// read_c2pa_from_bytes, read_c2pa_json_from_bytes, read_c2pa_encoded_from_bytes,
+/// verify_manifest_bytes and Reader.from_bytes, `verifier(label, signature,
+/// signature_info)` is called with the manifest label, its COSE_Sign1 signature
+/// as bytes and its "signature_info" dict. It returns None to skip the manifest,
+/// or a JSON serializable dict with a bool "included" and whatever else
+/// describes the inclusion proof (log index, log ID, checkpoint, ...). The dict
+/// is added to the manifest as "transparency_log", and manifests that are not
+/// included are reported in the result's "warnings" with the code
+/// "transparencyLog.notIncluded".
+///
+/// Manifest stores recovered by the soft binding resolver are not checked.
+/// Exceptions raised by the verifier propagate to the caller of the read
+/// function.
+///
+/// Args:
+///     verifier: A callable, or None to remove the registered verifier
+///
+/// Raises:
+///     TypeError: If verifier is not callable
+#[pyfunction]
+#[pyo3(signature = (verifier))]
+pub fn set_transparency_log_verifier(verifier: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+    if let Some(verifier) = &verifier {
+        if !verifier.is_callable() {
+            return Err(PyTypeError::new_err(
+                "transparency log verifier must be callable",
+            ));
+        }
+    }
+    *VERIFIER.lock().unwrap_or_else(|e| e.into_inner()) = verifier.map(Bound::unbind);
+    Ok(())
+}
@@ -0,0 +1,214 @@
+//! Trust list configuration
+//!
+//! Trust is configured through the "trust" and "verify" tables of the C2PA
+//! settings, which take whole PEM bundles embedded in JSON. These helpers load
+//! one list at a time, check it holds certificates before c2pa-rs gets it, and
+//! can download the official Content Credentials trust list at startup and
+//! again whenever it should be refreshed.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::{json, Map, Value};
+use x509_parser::pem::Pem;
+
+use crate::errors::c2pa_error;
+use crate::http::fetch_reported;
+use crate::settings;
+
+/// Trust anchors of the official Content Credentials trust list
+const TRUST_ANCHORS_URL: &str = "https://contentcredentials.org/trust/anchors.pem";
+/// SHA-256 hashes of the end-entity certificates allowed on their own
+const ALLOWED_LIST_URL: &str = "https://contentcredentials.org/trust/allowed.sha256.txt";
+/// Extended key usages accepted for signing certificates
+const TRUST_CONFIG_URL: &str = "https://contentcredentials.org/trust/store.cfg";
+
+/// Load `settings` as load_c2pa_settings does
+fn load(settings: &Value) -> PyResult<()> {
+    settings::load(&settings.to_string())
+        .map_err(|e| c2pa_error("Error loading trust settings", &e))
+}
+
+/// The number of certificates in a PEM bundle
+fn certificate_count(pem_bundle: &str) -> usize {
+    Pem::iter_from_buffer(pem_bundle.as_bytes())
+        .filter(|pem| pem.as_ref().is_ok_and(|pem| pem.label == "CERTIFICATE"))
+        .count()
+}
+
+/// The number of entries of an allowed list: certificates, and base64
+/// certificate hashes outside of them
+fn allowed_count(allowed_list: &str) -> usize {
+    let mut hashes = 0;
+    let mut inside_certificate = false;
+    for line in allowed_list.lines().map(str::trim) {
+        if line.starts_with("-----BEGIN") {
+            inside_certificate = true;
+        } else if line.starts_with("-----END") {
+            inside_certificate = false;
+        } else if !inside_certificate && !line.is_empty() && !line.starts_with('#') {
+            hashes += 1;
+        }
+    }
+    certificate_count(allowed_list) + hashes
+}
+
+fn checked_anchor_count(pem_bundle: &str) -> PyResult<usize> {
+    match certificate_count(pem_bundle) {
+        0 => Err(PyValueError::new_err(
+            "trust anchors have no PEM certificates",
+        )),
+        count => Ok(count),
+    }
+}
+
+fn checked_allowed_count(allowed_list: &str) -> PyResult<usize> {
+    match allowed_count(allowed_list) {
+        0 => Err(PyValueError::new_err(
+            "allowed list has no PEM certificates or certificate hashes",
+        )),
+        count => Ok(count),
+    }
+}
+
+/// Trust the CA certificates of a PEM bundle as anchors for signing certificates
+///
+/// Replaces the trust anchors loaded before. Signing certificates are only
+/// checked against them once trust checks are enabled with enable_trust_checks.
+///
+/// Args:
+///     pem_bundle: The CA certificates, PEM encoded
+///
+/// Returns:
+///     The number of certificates loaded
+///
+/// Raises:
+///     ValueError: If the bundle has no PEM certificates
+///     C2paError: If c2pa-rs rejects the bundle
+#[pyfunction]
+pub fn set_trust_anchors(pem_bundle: &str) -> PyResult<usize> {
+    let count = checked_anchor_count(pem_bundle)?;
+    load(&json!({"trust": {"trust_anchors": pem_bundle}}))?;
+    Ok(count)
+}
+
+/// Trust end-entity signing certificates on their own, whoever issued them
+///
+/// Replaces the allowed list loaded before. Entries are PEM certificates or
+/// base64 SHA-256 hashes of certificates, one per line, as in the allowed list
+/// of the official trust list.
+///
+/// Args:
+///     pem_bundle: The certificates or certificate hashes
+///
+/// Returns:
+///     The number of entries loaded
+///
+/// Raises:
+///     ValueError: If the list has no certificates or hashes
+///     C2paError: If c2pa-rs rejects the list
+#[pyfunction]
+pub fn set_allowed_list(pem_bundle: &str) -> PyResult<usize> {
+    let count = checked_allowed_count(pem_bundle)?;
+    load(&json!({"trust": {"allowed_list": pem_bundle}}))?;
+    Ok(count)
+}
+
+/// Check signing certificates against the trust anchors and allowed list
+///
+/// With trust checks, manifests signed by certificates that do not chain to an
+/// anchor and are not allowed get the signingCredential.untrusted failure.
+///
+/// Args:
+///     enabled: Whether to check trust (default: True)
+#[pyfunction]
+#[pyo3(signature = (enabled=true))]
+pub fn enable_trust_checks(enabled: bool) -> PyResult<()> {
+    load(&json!({"verify": {"verify_trust": enabled}}))
+}
+
+/// Download a trust list and load it, by default the official Content
+/// Credentials trust list
+///
+/// Call it at startup and again to pick up changes to the list. Every list is
+/// downloaded with the shared HTTP client before any is loaded, so a failed
+/// download leaves the lists loaded before in place.
+///
+/// Args:
+///     trust_anchors_url: URL of the PEM trust anchors, or None to keep the
+///         loaded ones (default: the official anchors.pem)
+///     allowed_list_url: URL of the allowed list, or None to keep the loaded
+///         one (default: the official allowed.sha256.txt)
+///     trust_config_url: URL of the extended key usages accepted for signing
+///         certificates, one OID per line, or None to keep the loaded ones
+///         (default: the official store.cfg)
+///     enable_trust_checks: Whether to enable trust checks once loaded
+///         (default: True)
+///
+/// Returns:
+///     A dict with the number of entries loaded: "trust_anchors",
+///     "allowed_list" and "trust_config", None for the lists not downloaded
+///
+/// Raises:
+///     ValueError: If a downloaded list has no entries
+///     PermissionError: If a URL is not allowed by the HTTP settings
+///     IoError: If a download fails
+///     C2paError: If c2pa-rs rejects a list
+#[pyfunction]
+#[pyo3(signature = (
+    trust_anchors_url=Some(TRUST_ANCHORS_URL),
+    allowed_list_url=Some(ALLOWED_LIST_URL),
+    trust_config_url=Some(TRUST_CONFIG_URL),
+    enable_trust_checks=true,
+))]
+pub fn refresh_trust_list(
+    py: Python,
+    trust_anchors_url: Option<&str>,
+    allowed_list_url: Option<&str>,
+    trust_config_url: Option<&str>,
+    enable_trust_checks: bool,
+) -> PyResult<PyObject> {
+    let fetch = |url: Option<&str>| -> PyResult<Option<String>> {
+        let Some(url) = url else {
+            return Ok(None);
+        };
+        let (body, _) = py.allow_threads(|| fetch_reported(url))?;
+        Ok(Some(String::from_utf8_lossy(&body).into_owned()))
+    };
+    let trust_anchors = fetch(trust_anchors_url)?;
+    let allowed_list = fetch(allowed_list_url)?;
+    let trust_config = fetch(trust_config_url)?;
+
+    let counts = PyDict::new(py);
+    let mut trust = Map::new();
+    if let Some(pem_bundle) = trust_anchors {
+        counts.set_item("trust_anchors", checked_anchor_count(&pem_bundle)?)?;
+        trust.insert("trust_anchors".into(), pem_bundle.into());
+    } else {
+        counts.set_item("trust_anchors", py.None())?;
+    }
+    if let Some(list) = allowed_list {
+        counts.set_item("allowed_list", checked_allowed_count(&list)?)?;
+        trust.insert("allowed_list".into(), list.into());
+    } else {
+        counts.set_item("allowed_list", py.None())?;
+    }
+    if let Some(config) = trust_config {
+        let oids = config
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with("//"))
+            .count();
+        counts.set_item("trust_config", oids)?;
+        trust.insert("trust_config".into(), config.into());
+    } else {
+        counts.set_item("trust_config", py.None())?;
+    }
+
+    let mut settings = json!({"trust": trust});
+    if enable_trust_checks {
+        settings["verify"] = json!({"verify_trust": true});
+    }
+    load(&settings)?;
+    Ok(counts.into_any().unbind())
+}
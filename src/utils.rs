@@ -1,42 +1,57 @@
-use std::io::Cursor;
-use c2pa::jumbf_io::{load_jumbf_from_stream, save_jumbf_to_stream};
 use pyo3::prelude::*;
 use pyo3::pyfunction;
-use pyo3::exceptions::PyRuntimeError;
+use pyo3::types::PyDict;
+
+use crate::jumbf::jpeg_segments;
+
+fn jpeg_marker_name(marker: u8) -> String {
+    match marker {
+        0xD8 => "SOI".to_owned(),
+        0xD9 => "EOI".to_owned(),
+        0xDA => "SOS".to_owned(),
+        0xDB => "DQT".to_owned(),
+        0xC4 => "DHT".to_owned(),
+        0xDD => "DRI".to_owned(),
+        0xFE => "COM".to_owned(),
+        0xC0..=0xCF => format!("SOF{}", marker - 0xC0),
+        0xD0..=0xD7 => format!("RST{}", marker - 0xD0),
+        0xE0..=0xEF => format!("APP{}", marker - 0xE0),
+        _ => format!("0x{marker:02X}"),
+    }
+}
 
+/// List the marker segments of a JPEG file
+///
+/// Diagnostics for assets whose manifest store is split across many APP11
+/// segments: every segment up to the start of scan is reported, including fill
+/// bytes and stray padding that were skipped to reach it.
+///
+/// Args:
+///     data: Binary data of the JPEG file (bytes-like object)
+///
+/// Returns:
+///     A list of dicts with "marker", "name", "offset", "length", "fill_bytes",
+///     "skipped_bytes", "truncated" and "c2pa" keys. JPEG XT (APP11 "JP")
+///     segments also carry "box_instance" and "sequence".
 #[pyfunction]
-pub fn convert_to_gray_keep_c2pa(
-    input_path: &str, 
-    output_path: &str,
-    format: &str,
-) -> PyResult<()> {
-    // 1. Read and extract JUMBF (C2PA data)
-    let mut source = std::fs::File::open(input_path)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to open file: {}", e)))?;
-
-    let jumbf = load_jumbf_from_stream(format, &mut source)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to load JUMBF: {}", e)))?;
-
-    // 2. Convert to grayscale
-    let input_img = image::open(input_path)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to open input path: {}", e)))?;
-
-    let output_img = input_img.grayscale();
-    output_img.save(output_path)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to save output file: {}", e)))?;
-
-
-    // 3. Write back JUMBF
-    let image = std::fs::read(output_path)?;
-    let mut dest = std::fs::File::create(output_path)?;
-    save_jumbf_to_stream(
-        format,
-        &mut Cursor::new(image),
-        &mut dest,
-        &jumbf
-    )
-    .map_err(|e| PyRuntimeError::new_err(format!("Failed to save output with jumbf: {}", e)))?;
-
-    Ok(())
+pub fn list_jpeg_segments(py: Python, data: &[u8]) -> PyResult<Vec<PyObject>> {
+    jpeg_segments(data)
+        .into_iter()
+        .map(|segment| {
+            let item = PyDict::new(py);
+            item.set_item("marker", segment.marker)?;
+            item.set_item("name", jpeg_marker_name(segment.marker))?;
+            item.set_item("offset", segment.offset)?;
+            item.set_item("length", segment.length)?;
+            item.set_item("fill_bytes", segment.fill_bytes)?;
+            item.set_item("skipped_bytes", segment.skipped_bytes)?;
+            item.set_item("truncated", segment.truncated)?;
+            item.set_item("c2pa", segment.c2pa)?;
+            if let Some((box_instance, sequence)) = segment.jpeg_xt {
+                item.set_item("box_instance", box_instance)?;
+                item.set_item("sequence", sequence)?;
+            }
+            Ok(item.into_any().unbind())
+        })
+        .collect()
 }
-
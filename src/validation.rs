@@ -0,0 +1,225 @@
+//! Validation outcome of an asset
+//!
+//! Most callers only need the verdict and why: the validation state, the
+//! status codes behind it and which ingredients failed. These are taken from
+//! the validation results of a read, with the library's own checks applied,
+//! so callers do not have to walk the nested report.
+
+use c2pa::validation_status::{
+    ASSERTION_BMFFHASH_MATCH, ASSERTION_BMFFHASH_MISMATCH, ASSERTION_BOXHASH_MATCH,
+    ASSERTION_BOXHASH_MISMATCH, ASSERTION_BOXHASH_UNKNOWN, ASSERTION_DATAHASH_MATCH,
+    ASSERTION_DATAHASH_MISMATCH, HARD_BINDINGS_MISSING,
+};
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+
+use crate::c2pa_reader::{read_value, value_to_py};
+use crate::gil;
+use crate::sarif::findings;
+
+/// The failure codes recorded for an ingredient when it was added, as
+/// validation results (claims v2) or a status list (claims v1)
+fn recorded_failures(ingredient: &Value) -> Vec<&Value> {
+    let results = ingredient.get("validation_results");
+    let active = results
+        .and_then(|results| results.pointer("/activeManifest/failure"))
+        .and_then(Value::as_array);
+    let deltas = results
+        .and_then(|results| results.get("ingredientDeltas"))
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|delta| delta.pointer("/validationDeltas/failure")?.as_array());
+    let status = ingredient
+        .get("validation_status")
+        .and_then(Value::as_array);
+    active
+        .into_iter()
+        .chain(deltas)
+        .chain(status)
+        .flatten()
+        .filter_map(|status| status.get("code"))
+        .collect()
+}
+
+/// The ingredients with failures: those recorded in the ingredient, the
+/// ingredient deltas of the validation results and the library's warnings
+/// about the ingredient's manifest
+fn failed_ingredients(value: &Value) -> Vec<Value> {
+    let deltas: Vec<(&str, &Value)> = value
+        .pointer("/validation_results/ingredientDeltas")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|delta| {
+            let failures = delta.pointer("/validationDeltas/failure")?;
+            Some((delta.get("uri")?.as_str()?, failures))
+        })
+        .collect();
+    let warnings = value.get("warnings").and_then(Value::as_array);
+
+    let mut failed = Vec::new();
+    let manifests = value.get("manifests").and_then(Value::as_object);
+    for (label, manifest) in manifests.into_iter().flatten() {
+        let ingredients = manifest.get("ingredients").and_then(Value::as_array);
+        for ingredient in ingredients.into_iter().flatten() {
+            let assertion = ingredient.get("label").and_then(Value::as_str);
+            let ingredient_manifest = ingredient.get("active_manifest").and_then(Value::as_str);
+            let mut codes = recorded_failures(ingredient);
+            if let Some(assertion) = assertion {
+                let uri = format!("self#jumbf=/c2pa/{label}/c2pa.assertions/{assertion}");
+                for (_, failures) in deltas.iter().filter(|(delta_uri, _)| *delta_uri == uri) {
+                    let failures = failures.as_array().into_iter().flatten();
+                    codes.extend(failures.filter_map(|status| status.get("code")));
+                }
+            }
+            if let Some(ingredient_manifest) = ingredient_manifest {
+                let about = warnings.into_iter().flatten().filter(|warning| {
+                    warning.get("label").and_then(Value::as_str) == Some(ingredient_manifest)
+                });
+                codes.extend(about.filter_map(|warning| warning.get("code")));
+            }
+            if codes.is_empty() {
+                continue;
+            }
+            let mut unique: Vec<&Value> = Vec::new();
+            for code in codes {
+                if !unique.contains(&code) {
+                    unique.push(code);
+                }
+            }
+            failed.push(json!({
+                "manifest": label,
+                "label": assertion,
+                "title": ingredient.get("title"),
+                "active_manifest": ingredient_manifest,
+                "codes": unique,
+            }));
+        }
+    }
+    failed
+}
+
+/// Codes of hard binding checks that passed
+const BINDING_MATCHES: [&str; 3] = [
+    ASSERTION_DATAHASH_MATCH,
+    ASSERTION_BMFFHASH_MATCH,
+    ASSERTION_BOXHASH_MATCH,
+];
+
+/// Codes of hard binding checks that failed
+const BINDING_MISMATCHES: [&str; 5] = [
+    ASSERTION_DATAHASH_MISMATCH,
+    ASSERTION_BMFFHASH_MISMATCH,
+    ASSERTION_BOXHASH_MISMATCH,
+    ASSERTION_BOXHASH_UNKNOWN,
+    HARD_BINDINGS_MISSING,
+];
+
+/// Whether the hard bindings of the active manifest of a read result matched
+/// the asset, with the labels of the hash assertions that did not
+///
+/// c2pa-rs checks the bindings against the bytes it read; this picks their
+/// outcome out of the validation results of the active manifest.
+pub(crate) fn hash_bindings(value: &Value) -> Value {
+    let statuses = |outcome: &str| {
+        value
+            .pointer(&format!("/validation_results/activeManifest/{outcome}"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|status| Some((status["code"].as_str()?, status)))
+    };
+    let matched = statuses("success").any(|(code, _)| BINDING_MATCHES.contains(&code));
+    let failed: Vec<&Value> = statuses("failure")
+        .filter(|(code, _)| BINDING_MISMATCHES.contains(code))
+        .map(|(_, status)| status)
+        .collect();
+    let mut failures: Vec<&str> = Vec::new();
+    for status in &failed {
+        let label = status["url"]
+            .as_str()
+            .and_then(|url| url.rsplit_once("c2pa.assertions/"))
+            .map(|(_, label)| label);
+        if let Some(label) = label.filter(|label| !failures.contains(label)) {
+            failures.push(label);
+        }
+    }
+    json!({
+        "is_intact": matched && failed.is_empty(),
+        "failures": failures,
+    })
+}
+
+/// The validation outcome of a read result
+fn validation(value: &Value) -> Value {
+    let statuses: Vec<Value> = findings(value, true)
+        .iter()
+        .map(|finding| {
+            json!({
+                "code": finding.status.get("code"),
+                "outcome": finding.outcome,
+                "manifest": finding.manifest(),
+                "ingredient": finding.ingredient,
+                "url": finding.status.get("url"),
+                "explanation": finding.status.get("explanation"),
+            })
+        })
+        .collect();
+    let mut failures: Vec<&Value> = Vec::new();
+    for status in &statuses {
+        if status["outcome"] == "failure" && !failures.contains(&&status["code"]) {
+            failures.push(&status["code"]);
+        }
+    }
+    json!({
+        "validation_state": value.get("validation_state"),
+        "active_manifest": value.get("active_manifest"),
+        "failures": failures,
+        "statuses": statuses,
+        "failed_ingredients": failed_ingredients(value),
+    })
+}
+
+/// Validate the C2PA data of an asset
+///
+/// The same validation as read_c2pa_from_bytes, with the registered hooks
+/// (signature verifier, version limits, ...), returned as the outcome alone.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     None if no C2PA metadata is present, else a dict with:
+///     - "validation_state": "Trusted", "Valid" or "Invalid"
+///     - "active_manifest": label of the active manifest
+///     - "failures": the distinct failure codes, e.g. ["assertion.dataHash.mismatch"]
+///     - "statuses": every validation status as a dict with "code", "outcome"
+///       ("success", "informational", "failure", or "warning" for the library's
+///       own checks), "manifest", "ingredient" (URI of the ingredient assertion
+///       for ingredient deltas, else None), "url" and "explanation"
+///     - "failed_ingredients": the ingredients with failures, as dicts with
+///       "manifest" (label of the manifest listing it), "label" (its assertion
+///       label), "title", "active_manifest" (label of its own manifest, if any)
+///       and "codes" (the failure codes recorded when it was added, of the
+///       ingredient deltas, and of the library's warnings about its manifest)
+///
+/// Raises:
+///     C2paError: If there is an error reading or parsing the C2PA data
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, allow_threads=None))]
+pub fn validate_c2pa_from_bytes(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<Option<PyObject>> {
+    let allow_threads = gil::release(allow_threads, data.len());
+    let Some(value) = read_value(py, data, mime_type, allow_threads)? else {
+        return Ok(None);
+    };
+    value_to_py(py, &validation(&value)).map(Some)
+}
@@ -0,0 +1,147 @@
+//! Limits on the claim and assertion versions a result may use
+//!
+//! New versions of the specification bring new claim and assertion layouts,
+//! which c2pa-rs may accept before a validator has reviewed what they mean.
+//! Conservative validators set the versions they have reviewed; manifests
+//! using newer ones are reported, and with "reject" the result is invalid.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+
+use crate::jumbf::{manifest_assertions, manifest_claims};
+
+/// Key of max_assertion_versions applying to the assertions it does not list
+const ANY_ASSERTION: &str = "*";
+
+struct VersionLimits {
+    claim: Option<u32>,
+    assertions: HashMap<String, u32>,
+    reject: bool,
+}
+
+static LIMITS: Mutex<Option<VersionLimits>> = Mutex::new(None);
+
+/// Whether version limits are set
+pub(crate) fn has_limits() -> bool {
+    LIMITS.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+}
+
+/// Split a versioned label such as "c2pa.actions.v2__1" into ("c2pa.actions", 2);
+/// labels without a version suffix are version 1
+fn split_version(label: &str) -> (&str, u32) {
+    let label = label.split("__").next().unwrap_or(label);
+    match label.rsplit_once(".v") {
+        Some((base, version)) if !version.is_empty() => match version.parse() {
+            Ok(version) => (base, version),
+            Err(_) => (label, 1),
+        },
+        _ => (label, 1),
+    }
+}
+
+/// Report the manifests of `store` using versions newer than the limits
+///
+/// Each such claim or assertion gets an entry in the result's "warnings"; when
+/// the limits reject them, the validation_state becomes "Invalid" as well.
+pub(crate) fn check_versions(value: &mut Value, store: &[u8]) {
+    let limits = LIMITS.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(limits) = limits.as_ref() else {
+        return;
+    };
+    let mut warnings = Vec::new();
+    if let Some(max) = limits.claim {
+        for (manifest, claim) in manifest_claims(store) {
+            let (_, version) = split_version(claim);
+            if version > max {
+                warnings.push(json!({
+                    "code": "claim.versionNotAllowed",
+                    "label": manifest,
+                    "explanation": format!(
+                        "{manifest} uses claim version {version}, newer than {max}"
+                    ),
+                }));
+            }
+        }
+    }
+    for (manifest, label, _) in manifest_assertions(store) {
+        let (base, version) = split_version(label);
+        let max = limits
+            .assertions
+            .get(base)
+            .or_else(|| limits.assertions.get(ANY_ASSERTION));
+        if let Some(&max) = max.filter(|&&max| version > max) {
+            warnings.push(json!({
+                "code": "assertion.versionNotAllowed",
+                "label": manifest,
+                "explanation": format!(
+                    "{manifest} uses {label}, version {version} of {base}, newer than {max}"
+                ),
+            }));
+        }
+    }
+
+    if warnings.is_empty() {
+        return;
+    }
+    if limits.reject {
+        value["validation_state"] = json!("Invalid");
+    }
+    match value.get_mut("warnings").and_then(Value::as_array_mut) {
+        Some(existing) => existing.extend(warnings),
+        None => value["warnings"] = json!(warnings),
+    }
+}
+
+/// Limit the claim and assertion versions the manifests read may use
+///
+/// Applies to the stores read by read_c2pa_from_file, read_c2pa_from_bytes,
+/// read_c2pa_json_from_bytes, read_c2pa_encoded_from_bytes,
+/// verify_manifest_bytes and Reader.from_bytes. Every manifest of a store is
+/// checked, ingredients included. Each claim or assertion newer than allowed is
+/// reported in the result's "warnings" with the code "claim.versionNotAllowed"
+/// or "assertion.versionNotAllowed" and the manifest "label". Manifest stores
+/// recovered by the soft binding resolver are not checked. Call it without
+/// arguments to remove the limits.
+///
+/// Args:
+///     max_claim_version: Newest claim version allowed, e.g. 1 for "c2pa.claim"
+///         only, 2 to allow "c2pa.claim.v2" (default: None, any)
+///     max_assertion_versions: Newest version allowed per assertion label, without
+///         its version suffix, e.g. {"c2pa.actions": 2, "c2pa.ingredient": 3}.
+///         Labels without a suffix are version 1; the key "*" applies to the
+///         labels not listed (default: None, any)
+///     action: "reject" to also set validation_state to "Invalid", or "warn" to
+///         only report them (default: "reject")
+///
+/// Raises:
+///     ValueError: If action is not "reject" or "warn"
+#[pyfunction]
+#[pyo3(signature = (max_claim_version=None, max_assertion_versions=None, action="reject"))]
+pub fn set_version_limits(
+    max_claim_version: Option<u32>,
+    max_assertion_versions: Option<HashMap<String, u32>>,
+    action: &str,
+) -> PyResult<()> {
+    let reject = match action {
+        "reject" => true,
+        "warn" => false,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "action must be \"reject\" or \"warn\", not {action:?}"
+            )))
+        }
+    };
+    let limits = (max_claim_version.is_some() || max_assertion_versions.is_some()).then(|| {
+        VersionLimits {
+            claim: max_claim_version,
+            assertions: max_assertion_versions.unwrap_or_default(),
+            reject,
+        }
+    });
+    *LIMITS.lock().unwrap_or_else(|e| e.into_inner()) = limits;
+    Ok(())
+}
@@ -0,0 +1,826 @@
+//! Tolerant XMP scanning for provenance pointers
+//!
+//! A remote manifest is referenced from the asset's XMP through the
+//! `dcterms:provenance` property. Real-world XMP is frequently not what the
+//! spec describes: packets start with a byte order mark, are stored as UTF-16,
+//! use a different namespace prefix or are cut short. This module searches the
+//! raw asset bytes for XMP packets without requiring the container to parse and
+//! reports what it found, including problems, instead of silently giving up.
+//!
+//! The XMP of JPEG and PNG files can also be rewritten here, to point an asset
+//! at a remote manifest without going through signing.
+
+use std::io::{Cursor, Read, Seek};
+use std::ops::Range;
+
+use memchr::memmem;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use crate::asset_info::{be_u16, be_u32, bmff_boxes, riff_chunks, tiff_header, Source};
+use crate::gil;
+use crate::jumbf::jpeg_segments;
+use crate::mime;
+
+const DCTERMS_NS: &str = "http://purl.org/dc/terms/";
+const XMPMETA_OPEN: &str = "<x:xmpmeta";
+const XMPMETA_CLOSE: &str = "</x:xmpmeta>";
+const XPACKET_OPEN: &str = "<?xpacket begin";
+const XPACKET_END: &str = "<?xpacket end";
+const ENCODINGS: [&str; 3] = ["utf-8", "utf-16le", "utf-16be"];
+/// Namespace header of the APP1 segment holding a JPEG's XMP
+const JPEG_XMP_HEADER: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+/// Keyword of the iTXt chunk holding a PNG's XMP
+const PNG_XMP_KEYWORD: &[u8] = b"XML:com.adobe.xmp";
+const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+/// ID of the RIFF chunk holding the XMP of WebP, WAV and AVI files
+const RIFF_XMP_CHUNK: &[u8; 4] = b"XMP ";
+/// Extended type of the `uuid` box holding the XMP of ISO base media files
+const BMFF_XMP_UUID: [u8; 16] = [
+    0xBE, 0x7A, 0xCF, 0xCB, 0x97, 0xA9, 0x42, 0xE8, 0x9C, 0x71, 0x99, 0x94, 0x91, 0xE3, 0xAF, 0xAC,
+];
+/// TIFF tag of the XMP packet
+const TIFF_XMP_TAG: u16 = 700;
+const JPEG_APP0: u8 = 0xE0;
+const JPEG_APP1: u8 = 0xE1;
+
+/// An XMP packet found in the raw asset bytes
+pub struct XmpPacket {
+    /// Offset of the packet in the asset
+    pub offset: usize,
+    /// Text encoding the packet was stored in
+    pub encoding: &'static str,
+    /// Whether the packet text starts with a byte order mark
+    pub bom: bool,
+    /// Decoded packet text
+    pub text: String,
+    /// Problems found while extracting the packet
+    pub warnings: Vec<String>,
+}
+
+/// What a scan for a provenance pointer found
+pub struct ProvenanceReport {
+    /// Every XMP packet found in the asset
+    pub packets: Vec<XmpPacket>,
+    /// The first provenance URL found
+    pub provenance: Option<String>,
+    /// Problems found while scanning, across all packets
+    pub warnings: Vec<String>,
+}
+
+fn encode(text: &str, encoding: &str) -> Vec<u8> {
+    match encoding {
+        "utf-16le" => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+        "utf-16be" => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+        _ => text.as_bytes().to_vec(),
+    }
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if from >= haystack.len() || needle.is_empty() {
+        return None;
+    }
+    memmem::find(&haystack[from..], needle).map(|pos| pos + from)
+}
+
+fn decode(bytes: &[u8], encoding: &'static str) -> String {
+    match encoding {
+        "utf-8" => String::from_utf8_lossy(bytes).into_owned(),
+        _ => {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| {
+                    if encoding == "utf-16le" {
+                        u16::from_le_bytes([pair[0], pair[1]])
+                    } else {
+                        u16::from_be_bytes([pair[0], pair[1]])
+                    }
+                })
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+    }
+}
+
+/// Find the XMP packets stored anywhere in `data`
+///
+/// Packets are located by their `<x:xmpmeta` element (or `<?xpacket` wrapper)
+/// in UTF-8, UTF-16LE and UTF-16BE. A packet without a closing tag is still
+/// returned, up to the next packet or the end of the data.
+pub fn find_xmp_packets(data: &[u8]) -> Vec<XmpPacket> {
+    let mut packets = Vec::new();
+    for encoding in ENCODINGS {
+        let open = encode(XMPMETA_OPEN, encoding);
+        let close = encode(XMPMETA_CLOSE, encoding);
+        let xpacket = encode(XPACKET_OPEN, encoding);
+        let xpacket_end = encode(XPACKET_END, encoding);
+        let bom_bytes = encode("\u{feff}", encoding);
+        let gt = encode(">", encoding);
+        let unit = if encoding == "utf-8" { 1 } else { 2 };
+        let mut from = 0;
+        while let Some(start) = find_bytes(data, &open, from) {
+            let mut warnings = Vec::new();
+
+            // Include the <?xpacket wrapper when it directly precedes the packet,
+            // otherwise a byte order mark in front of the packet
+            let lookback = start.saturating_sub(256 * unit);
+            let begin = match find_bytes(&data[lookback..start], &xpacket, 0) {
+                Some(pos) => pos + lookback,
+                None if data[..start].ends_with(&bom_bytes) => start - bom_bytes.len(),
+                None => start,
+            };
+            let wrapped = find_bytes(&data[begin..start], &xpacket, 0).is_some();
+
+            let next_open = find_bytes(data, &open, start + open.len()).unwrap_or(data.len());
+            let mut end = match find_bytes(&data[..next_open], &close, start) {
+                Some(pos) => pos + close.len(),
+                None => {
+                    warnings.push("XMP packet is not terminated by </x:xmpmeta>".to_owned());
+                    next_open
+                }
+            };
+            if wrapped {
+                // Padding whitespace commonly sits between the packet and its trailer
+                let window = end.saturating_add(4096 * unit).min(next_open);
+                match find_bytes(&data[..window], &xpacket_end, end) {
+                    Some(pos) => {
+                        let trailer_end = find_bytes(&data[..window], &gt, pos)
+                            .map(|gt_pos| gt_pos + gt.len())
+                            .unwrap_or(window);
+                        end = trailer_end.min(window);
+                    }
+                    None => warnings
+                        .push("XMP packet wrapper has no <?xpacket end?> trailer".to_owned()),
+                }
+            }
+
+            let mut bytes = &data[begin..end];
+            if encoding != "utf-8" && bytes.len() % 2 == 1 {
+                bytes = &bytes[..bytes.len() - 1];
+            }
+            let mut text = decode(bytes, encoding);
+            let mut bom = false;
+
+            // The xpacket begin attribute normally holds a BOM; a BOM in front
+            // of the packet text itself is also common.
+            if let Some(stripped) = text.strip_prefix('\u{feff}') {
+                text = stripped.to_owned();
+                bom = true;
+            }
+            if text.starts_with("<?xpacket begin=\"\u{feff}\"")
+                || text.starts_with("<?xpacket begin='\u{feff}'")
+            {
+                bom = true;
+            }
+            if text.contains('\u{fffd}') {
+                warnings.push(format!(
+                    "XMP packet contains bytes that are not valid {encoding}"
+                ));
+            }
+
+            packets.push(XmpPacket {
+                offset: begin,
+                encoding,
+                bom,
+                text,
+                warnings,
+            });
+            from = end.max(start + open.len());
+        }
+    }
+    packets.sort_by_key(|packet| packet.offset);
+    packets
+}
+
+/// Namespace prefixes bound to the Dublin Core terms namespace in `xmp`
+fn dcterms_prefixes(xmp: &str) -> Vec<String> {
+    let mut prefixes = Vec::new();
+    let mut rest = xmp;
+    while let Some(pos) = rest.find("xmlns:") {
+        let after = &rest[pos + 6..];
+        let Some(eq) = after.find('=') else { break };
+        let prefix = after[..eq].trim();
+        let value = after[eq + 1..].trim_start();
+        let quote = value.chars().next();
+        if let Some(q @ ('"' | '\'')) = quote {
+            if let Some(close) = value[1..].find(q) {
+                if value[1..1 + close].trim() == DCTERMS_NS && !prefix.is_empty() {
+                    prefixes.push(prefix.to_owned());
+                }
+            }
+        }
+        rest = after;
+    }
+    if !prefixes.iter().any(|p| p == "dcterms") {
+        // Tolerate packets that use the conventional prefix without declaring it
+        prefixes.push("dcterms".to_owned());
+    }
+    prefixes
+}
+
+fn quoted_value(text: &str) -> Option<&str> {
+    let quote = text.chars().next()?;
+    if quote != '"' && quote != '\'' {
+        return None;
+    }
+    let close = text[1..].find(quote)?;
+    Some(&text[1..1 + close])
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&amp;", "&")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+}
+
+/// Find the `dcterms:provenance` value in an XMP packet
+///
+/// Both the attribute form (`dcterms:provenance="..."`) and the element form
+/// (`<dcterms:provenance>...</dcterms:provenance>`, optionally with an
+/// `rdf:resource` attribute) are recognised, under any prefix bound to the
+/// Dublin Core terms namespace.
+pub fn find_provenance(xmp: &str) -> Option<String> {
+    provenance_value(xmp).map(unescape)
+}
+
+/// The `dcterms:provenance` value in an XMP packet as stored, still escaped,
+/// as a slice of `xmp`
+fn provenance_value(xmp: &str) -> Option<&str> {
+    for prefix in dcterms_prefixes(xmp) {
+        let name = format!("{prefix}:provenance");
+        let mut from = 0;
+        while let Some(pos) = xmp[from..].find(&name).map(|p| p + from) {
+            let after = &xmp[pos + name.len()..];
+            from = pos + name.len();
+
+            let preceded_by_lt = xmp[..pos].ends_with('<');
+            if !preceded_by_lt {
+                // Attribute form
+                let trimmed = after.trim_start();
+                if let Some(value) = trimmed
+                    .strip_prefix('=')
+                    .map(str::trim_start)
+                    .and_then(quoted_value)
+                {
+                    let value = value.trim();
+                    if !value.is_empty() {
+                        return Some(value);
+                    }
+                }
+                continue;
+            }
+
+            // Element form
+            let Some(tag_end) = after.find('>') else {
+                continue;
+            };
+            let attributes = &after[..tag_end];
+            if let Some(res) = attributes.find("resource") {
+                let value = attributes[res + 8..].trim_start();
+                if let Some(value) = value
+                    .strip_prefix('=')
+                    .map(str::trim_start)
+                    .and_then(quoted_value)
+                {
+                    return Some(value.trim());
+                }
+            }
+            let body = &after[tag_end + 1..];
+            let body_end = body.find('<').unwrap_or(body.len());
+            let value = body[..body_end].trim();
+            if !value.is_empty() {
+                return Some(value);
+            }
+        }
+    }
+    None
+}
+
+/// Search `data` for XMP packets and the provenance pointer they carry
+pub fn scan_provenance(data: &[u8]) -> ProvenanceReport {
+    let packets = find_xmp_packets(data);
+    let mut provenance: Option<String> = None;
+    let mut warnings = Vec::new();
+
+    for packet in &packets {
+        warnings.extend(packet.warnings.iter().cloned());
+        if let Some(found) = find_provenance(&packet.text) {
+            match &provenance {
+                None => provenance = Some(found),
+                Some(existing) if *existing != found => warnings.push(format!(
+                    "conflicting provenance pointers: {existing} and {found}"
+                )),
+                _ => {}
+            }
+        }
+    }
+    if packets.len() > 1 {
+        warnings.push(format!("found {} XMP packets", packets.len()));
+    }
+
+    ProvenanceReport {
+        packets,
+        provenance,
+        warnings,
+    }
+}
+
+/// The XMP packet stored where the asset's format keeps it
+///
+/// Only the container structure is walked: JPEG segments, PNG chunks, the
+/// first TIFF directory, RIFF chunks and top-level ISO base media boxes. This
+/// costs a few small reads whatever the size of the asset. Returns None for
+/// formats without a known XMP location, and Some(None) for assets of a known
+/// format without XMP.
+pub(crate) fn container_xmp(reader: impl Read + Seek, mime_type: &str) -> Option<Option<String>> {
+    let mut source = Source::new(reader)?;
+    let head = source.read(0, 16).unwrap_or_default();
+    let xmp = match mime::sniff(&head).unwrap_or(mime_type) {
+        "image/jpeg" => jpeg_xmp(&mut source),
+        "image/png" => png_xmp(&mut source),
+        "image/tiff" => tiff_xmp(&mut source),
+        "image/webp" | "audio/wav" | "video/avi" => riff_xmp(&mut source),
+        "image/heic" | "image/heif" | "image/avif" | "video/mp4" | "video/quicktime"
+        | "audio/mp4" => bmff_xmp(&mut source),
+        _ => return None,
+    };
+    Some(xmp)
+}
+
+fn jpeg_xmp<R: Read + Seek>(source: &mut Source<R>) -> Option<String> {
+    let mut pos = 2;
+    loop {
+        let header = source.read(pos, 4)?;
+        if header[0] != 0xff {
+            return None;
+        }
+        match header[1] {
+            0xff => {
+                pos += 1;
+                continue;
+            }
+            0x01 | 0xd0..=0xd7 => {
+                pos += 2;
+                continue;
+            }
+            // The XMP segment comes before the image data
+            0xd9 | 0xda => return None,
+            _ => {}
+        }
+        let length = usize::from(be_u16(&header, 2)?);
+        let body = pos + 4;
+        if header[1] == JPEG_APP1
+            && length >= 2 + JPEG_XMP_HEADER.len()
+            && source.read(body, JPEG_XMP_HEADER.len()).as_deref() == Some(JPEG_XMP_HEADER)
+        {
+            let xmp_len = length - 2 - JPEG_XMP_HEADER.len();
+            let xmp = source.read(body + JPEG_XMP_HEADER.len() as u64, xmp_len)?;
+            return Some(String::from_utf8_lossy(&xmp).into_owned());
+        }
+        pos += 2 + length as u64;
+    }
+}
+
+fn png_xmp<R: Read + Seek>(source: &mut Source<R>) -> Option<String> {
+    let mut pos = PNG_SIGNATURE.len() as u64;
+    while let Some(header) = source.read(pos, 8) {
+        let len = be_u32(&header, 0)?;
+        let body = pos + 8;
+        match &header[4..8] {
+            b"iTXt" => {
+                let xmp = source
+                    .read(body, len as usize)
+                    .filter(|chunk| chunk.starts_with(PNG_XMP_KEYWORD))
+                    .and_then(|chunk| png_itxt_xmp(&chunk));
+                if xmp.is_some() {
+                    return xmp;
+                }
+            }
+            b"IEND" => break,
+            _ => {}
+        }
+        pos = body + u64::from(len) + 4;
+    }
+    None
+}
+
+fn tiff_xmp<R: Read + Seek>(source: &mut Source<R>) -> Option<String> {
+    let (endian, offset) = tiff_header(&source.read(0, 8)?)?;
+    let count = endian.u16(&source.read(offset.into(), 2)?, 0)?;
+    let entries = source.read(u64::from(offset) + 2, usize::from(count) * 12)?;
+    let entry = entries
+        .chunks_exact(12)
+        .find(|entry| endian.u16(entry, 0) == Some(TIFF_XMP_TAG))?;
+    // A BYTE or UNDEFINED array, stored elsewhere unless it fits in 4 bytes
+    let len = endian.u32(entry, 4)? as usize;
+    let xmp = match len {
+        0..=4 => entry[8..8 + len].to_vec(),
+        _ => source.read(endian.u32(entry, 8)?.into(), len)?,
+    };
+    Some(String::from_utf8_lossy(&xmp).into_owned())
+}
+
+fn riff_xmp<R: Read + Seek>(source: &mut Source<R>) -> Option<String> {
+    let len = source.len;
+    let (_, body, size) = riff_chunks(source, 12, len)
+        .into_iter()
+        .find(|(id, _, _)| id == RIFF_XMP_CHUNK)?;
+    let xmp = source.read(body, size as usize)?;
+    Some(String::from_utf8_lossy(&xmp).into_owned())
+}
+
+fn bmff_xmp<R: Read + Seek>(source: &mut Source<R>) -> Option<String> {
+    let len = source.len;
+    for (kind, body, end) in bmff_boxes(source, 0, len) {
+        if &kind != b"uuid" || end < body + 16 {
+            continue;
+        }
+        if source.read(body, 16).as_deref() == Some(&BMFF_XMP_UUID[..]) {
+            let xmp = source.read(body + 16, (end - body - 16) as usize)?;
+            return Some(String::from_utf8_lossy(&xmp).into_owned());
+        }
+    }
+    None
+}
+
+/// The remote manifest reference in the XMP of an asset read for its manifest
+/// store
+///
+/// When the manifest store was embedded, only the XMP where the format stores
+/// it is looked at, as by container_xmp, so reads do not search every byte of
+/// large assets for a pointer that is rarely there. Otherwise the raw bytes
+/// are searched as by scan_provenance, which also finds damaged XMP.
+pub(crate) fn remote_reference(data: &[u8], mime_type: &str, is_embedded: bool) -> Option<String> {
+    if !is_embedded {
+        return scan_provenance(data).provenance;
+    }
+    container_xmp(Cursor::new(data), mime_type)
+        .flatten()
+        .as_deref()
+        .and_then(find_provenance)
+}
+
+/// Find the remote manifest URL referenced from an asset's XMP
+///
+/// The raw bytes are searched directly, so this works even when the container
+/// is damaged. XMP stored with a byte order mark, as UTF-16, under an unusual
+/// namespace prefix or without a closing tag is still examined, and problems
+/// are reported rather than ignored.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///
+/// Returns:
+///     A dict with "provenance" (the URL, or None), "xmp_found", "packets" (a list
+///     of dicts with "offset", "encoding" and "bom") and "warnings"
+#[pyfunction]
+pub fn read_xmp_provenance(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let report = py.allow_threads(|| scan_provenance(data));
+
+    let result = PyDict::new(py);
+    result.set_item("provenance", report.provenance)?;
+    result.set_item("xmp_found", !report.packets.is_empty())?;
+    let packets = report
+        .packets
+        .iter()
+        .map(|packet| {
+            let item = PyDict::new(py);
+            item.set_item("offset", packet.offset)?;
+            item.set_item("encoding", packet.encoding)?;
+            item.set_item("bom", packet.bom)?;
+            Ok(item)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    result.set_item("packets", packets)?;
+    result.set_item("warnings", report.warnings)?;
+    Ok(result.into_any().unbind())
+}
+
+/// Containers whose XMP can be rewritten
+#[derive(Clone, Copy)]
+pub(crate) enum Container {
+    Jpeg,
+    Png,
+}
+
+impl Container {
+    pub(crate) fn from_mime_type(mime_type: &str) -> Option<Self> {
+        match mime_type {
+            "image/jpeg" | "image/jpg" | "jpg" | "jpeg" => Some(Container::Jpeg),
+            "image/png" | "png" => Some(Container::Png),
+            _ => None,
+        }
+    }
+
+    /// Every segment or chunk storing XMP in `data`, in file order, and where
+    /// one would be inserted, or None if `data` is not of this format
+    pub(crate) fn slots(self, data: &[u8]) -> Option<(Vec<XmpSlot>, usize)> {
+        match self {
+            Container::Jpeg => jpeg_slots(data),
+            Container::Png => png_slots(data),
+        }
+    }
+
+    /// Where the XMP of `data` is stored, or None if `data` is not of this format
+    fn slot(self, data: &[u8]) -> Option<XmpSlot> {
+        let (slots, insert_at) = self.slots(data)?;
+        Some(slots.into_iter().next().unwrap_or(XmpSlot {
+            range: insert_at..insert_at,
+            xmp: None,
+        }))
+    }
+
+    /// The segment or chunk storing `xmp`
+    pub(crate) fn encode(self, xmp: &str) -> Result<Vec<u8>, String> {
+        match self {
+            Container::Jpeg => {
+                let length = 2 + JPEG_XMP_HEADER.len() + xmp.len();
+                let length = u16::try_from(length).map_err(|_| {
+                    format!(
+                        "XMP packet of {} bytes does not fit in a JPEG segment",
+                        xmp.len()
+                    )
+                })?;
+                let mut segment = vec![0xFF, JPEG_APP1];
+                segment.extend_from_slice(&length.to_be_bytes());
+                segment.extend_from_slice(JPEG_XMP_HEADER);
+                segment.extend_from_slice(xmp.as_bytes());
+                Ok(segment)
+            }
+            Container::Png => {
+                // Uncompressed, with empty language tag and translated keyword
+                let mut body = b"iTXt".to_vec();
+                body.extend_from_slice(PNG_XMP_KEYWORD);
+                body.extend_from_slice(&[0, 0, 0, 0, 0]);
+                body.extend_from_slice(xmp.as_bytes());
+                let length = u32::try_from(body.len() - 4).map_err(|_| {
+                    format!(
+                        "XMP packet of {} bytes does not fit in a PNG chunk",
+                        xmp.len()
+                    )
+                })?;
+                let mut chunk = length.to_be_bytes().to_vec();
+                chunk.extend_from_slice(&body);
+                chunk.extend_from_slice(&crc32fast::hash(&body).to_be_bytes());
+                Ok(chunk)
+            }
+        }
+    }
+}
+
+/// Where an asset stores its XMP
+pub(crate) struct XmpSlot {
+    /// The segment or chunk holding the XMP, or an empty range where one
+    /// would be inserted
+    pub range: Range<usize>,
+    /// The XMP packet, if any
+    pub xmp: Option<String>,
+}
+
+/// The XMP packet of the body of a PNG iTXt chunk with the XMP keyword, if
+/// stored uncompressed
+fn png_itxt_xmp(body: &[u8]) -> Option<String> {
+    // Keyword, compression flag and method, language tag, translated keyword
+    match body.get(PNG_XMP_KEYWORD.len()..)? {
+        [0, 0, _, rest @ ..] => {
+            let mut fields = rest.splitn(3, |&b| b == 0);
+            fields
+                .nth(2)
+                .map(|text| String::from_utf8_lossy(text).into_owned())
+        }
+        _ => None,
+    }
+}
+
+/// The APP1 XMP segments of a JPEG, and the position after SOI and any JFIF
+/// APP0 segment
+fn jpeg_slots(data: &[u8]) -> Option<(Vec<XmpSlot>, usize)> {
+    let segments = jpeg_segments(data);
+    segments.first()?;
+    let mut slots = Vec::new();
+    let mut insert_at = 2;
+    for segment in &segments {
+        let end = (segment.offset + 2 + segment.length).min(data.len());
+        if segment.marker == JPEG_APP0 && segment.offset == insert_at {
+            insert_at = end;
+        }
+        let body = &data[(segment.offset + 4).min(end)..end];
+        if segment.marker == JPEG_APP1 && body.starts_with(JPEG_XMP_HEADER) {
+            let xmp = String::from_utf8_lossy(&body[JPEG_XMP_HEADER.len()..]).into_owned();
+            slots.push(XmpSlot {
+                range: segment.offset..end,
+                xmp: Some(xmp),
+            });
+        }
+    }
+    Some((slots, insert_at))
+}
+
+/// The iTXt XMP chunks of a PNG, and the position after IHDR
+fn png_slots(data: &[u8]) -> Option<(Vec<XmpSlot>, usize)> {
+    if !data.starts_with(PNG_SIGNATURE) {
+        return None;
+    }
+    let mut slots = Vec::new();
+    let mut insert_at = None;
+    let mut pos = PNG_SIGNATURE.len();
+    while pos + 8 <= data.len() {
+        let len =
+            u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+        let chunk_type = &data[pos + 4..pos + 8];
+        let start = pos + 8;
+        let end = start.saturating_add(len).saturating_add(4).min(data.len());
+        let body = &data[start..start.saturating_add(len).min(data.len())];
+        insert_at.get_or_insert(end);
+        if chunk_type == b"iTXt" && body.starts_with(PNG_XMP_KEYWORD) {
+            slots.push(XmpSlot {
+                range: pos..end,
+                xmp: png_itxt_xmp(body),
+            });
+        }
+        if chunk_type == b"IEND" {
+            break;
+        }
+        pos = end;
+    }
+    Some((slots, insert_at?))
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// `xmp` with its provenance pointer set to `url`, or a new packet holding
+/// only the pointer
+fn with_provenance(xmp: Option<&str>, url: &str) -> Result<String, String> {
+    let url = escape(url);
+    let Some(xmp) = xmp else {
+        return Ok(format!(
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\
+             <x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\
+             <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\
+             <rdf:Description rdf:about=\"\" xmlns:dcterms=\"{DCTERMS_NS}\" dcterms:provenance=\"{url}\"/>\
+             </rdf:RDF></x:xmpmeta><?xpacket end=\"w\"?>"
+        ));
+    };
+    if let Some(value) = provenance_value(xmp) {
+        let start = value.as_ptr() as usize - xmp.as_ptr() as usize;
+        return Ok(format!(
+            "{}{url}{}",
+            &xmp[..start],
+            &xmp[start + value.len()..]
+        ));
+    }
+
+    let prefix = &dcterms_prefixes(xmp)[0];
+    let property = format!("{prefix}:provenance=\"{url}\"");
+    if let Some(pos) = xmp.find("<rdf:Description") {
+        let at = pos + "<rdf:Description".len();
+        let tag_end = xmp[at..].find('>').map_or(xmp.len(), |end| at + end);
+        let declared = xmp[at..tag_end].contains(&format!("xmlns:{prefix}="));
+        let declaration = if declared {
+            String::new()
+        } else {
+            format!(" xmlns:{prefix}=\"{DCTERMS_NS}\"")
+        };
+        return Ok(format!(
+            "{}{declaration} {property}{}",
+            &xmp[..at],
+            &xmp[at..]
+        ));
+    }
+    let Some(pos) = xmp.find("<rdf:RDF") else {
+        return Err("XMP packet has no rdf:RDF element".to_owned());
+    };
+    let Some(tag_end) = xmp[pos..].find('>').map(|end| pos + end + 1) else {
+        return Err("XMP packet has no rdf:RDF element".to_owned());
+    };
+    Ok(format!(
+        "{}<rdf:Description rdf:about=\"\" xmlns:{prefix}=\"{DCTERMS_NS}\" {property}/>{}",
+        &xmp[..tag_end],
+        &xmp[tag_end..]
+    ))
+}
+
+/// `xmp` without its provenance pointer, or None if it has none
+pub(crate) fn without_provenance(xmp: &str) -> Option<String> {
+    let value = provenance_value(xmp)?;
+    let start = value.as_ptr() as usize - xmp.as_ptr() as usize;
+    let end = start + value.len();
+    let name = xmp[..start].rfind(":provenance")?;
+    let property = xmp[..name].rfind(|c: char| c.is_whitespace() || c == '<')?;
+    let after = if xmp[property..].starts_with('<') {
+        // Element form: up to the end of the element
+        let tag_end = end + xmp[end..].find('>')? + 1;
+        let closing = format!("</{}>", &xmp[property + 1..name + ":provenance".len()]);
+        let rest = &xmp[tag_end..];
+        match rest.trim_start().strip_prefix(&closing) {
+            Some(after) if !xmp[property..tag_end].ends_with("/>") => xmp.len() - after.len(),
+            _ => tag_end,
+        }
+    } else {
+        // Attribute form: past the closing quote
+        end + 1
+    };
+    Some(format!("{}{}", &xmp[..property], &xmp[after..]))
+}
+
+/// Read the XMP packet of an asset
+///
+/// For JPEG and PNG the packet is taken from its APP1 segment or iTXt chunk;
+/// for other formats the raw bytes are searched for one, as by
+/// read_xmp_provenance.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data (e.g., "image/jpeg")
+///
+/// Returns:
+///     The XMP packet as a string, or None if the asset has none
+#[pyfunction]
+pub fn read_xmp(py: Python, data: &[u8], mime_type: &str) -> Option<String> {
+    let container = Container::from_mime_type(mime_type);
+    py.allow_threads(
+        || match container.and_then(|container| container.slot(data)) {
+            Some(slot) => slot.xmp,
+            None => find_xmp_packets(data)
+                .into_iter()
+                .next()
+                .map(|packet| packet.text),
+        },
+    )
+}
+
+/// `data` with the provenance pointer of its XMP set to `url`
+pub(crate) fn with_remote_reference(
+    data: &[u8],
+    container: Container,
+    mime_type: &str,
+    url: &str,
+) -> Result<Vec<u8>, String> {
+    let slot = container
+        .slot(data)
+        .ok_or_else(|| format!("Data is not a valid {mime_type} asset"))?;
+    let xmp = with_provenance(slot.xmp.as_deref(), url)?;
+    let encoded = container.encode(&xmp)?;
+    let mut updated = Vec::with_capacity(data.len() + encoded.len());
+    updated.extend_from_slice(&data[..slot.range.start]);
+    updated.extend_from_slice(&encoded);
+    updated.extend_from_slice(&data[slot.range.end..]);
+    Ok(updated)
+}
+
+/// Point an asset at a remote manifest through its XMP
+///
+/// Sets the `dcterms:provenance` property of the asset's XMP to `url`, adding
+/// the property, or an XMP packet, as needed; the rest of the XMP is kept.
+/// Changing the XMP of an asset with an embedded manifest invalidates its hard
+/// binding, so set the reference before signing, or on assets whose manifest
+/// is only stored remotely.
+///
+/// Args:
+///     data: Binary data of the file (bytes-like object)
+///     mime_type: MIME type of the data, "image/jpeg" or "image/png"
+///     url: URL of the remote manifest store
+///     allow_threads: Whether to release the Python GIL during processing (default: None,
+///         only for payloads of at least get_gil_release_threshold() bytes)
+///
+/// Returns:
+///     The asset with the reference, as bytes
+///
+/// Raises:
+///     ValueError: If the format is not supported, the data is not of that
+///         format, or its XMP cannot be updated
+#[pyfunction]
+#[pyo3(signature = (data, mime_type, url, allow_threads=None))]
+pub fn set_remote_reference(
+    py: Python,
+    data: &[u8],
+    mime_type: &str,
+    url: &str,
+    allow_threads: Option<bool>,
+) -> PyResult<Py<PyBytes>> {
+    let Some(container) = Container::from_mime_type(mime_type) else {
+        return Err(PyValueError::new_err(format!(
+            "Cannot write XMP to {mime_type} assets, only JPEG and PNG are supported"
+        )));
+    };
+    let allow_threads = gil::release(allow_threads, data.len());
+    let update = || with_remote_reference(data, container, mime_type, url);
+    let updated = if allow_threads {
+        py.allow_threads(update)
+    } else {
+        update()
+    }
+    .map_err(|e| PyValueError::new_err(format!("Cannot set the remote reference: {e}")))?;
+    Ok(PyBytes::new(py, &updated).unbind())
+}
@@ -0,0 +1,57 @@
+"""
+Fixtures shared by the test modules.
+"""
+
+import struct
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import enable_trust_checks, make_signed_test_asset, read_c2pa_from_bytes
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+@pytest.fixture
+def read_image():
+    """Read a file of tests/test_images by name, skipping the test if it is missing"""
+
+    def read(name):
+        path = TEST_IMAGES_DIR / name
+        if not path.exists():
+            pytest.skip(f"Test image not found: {path}")
+        return path.read_bytes()
+
+    return read
+
+
+@pytest.fixture
+def split_manifest():
+    """Split a PNG into (manifest store, asset without the caBX chunk)"""
+
+    def split(data):
+        pos = 8
+        while pos < len(data):
+            length, chunk_type = struct.unpack(">I4s", data[pos:pos + 8])
+            end = pos + 12 + length
+            if chunk_type == b"caBX":
+                return data[pos + 8:pos + 8 + length], data[:pos] + data[end:]
+            pos = end
+        raise AssertionError("no caBX chunk")
+
+    return split
+
+
+@pytest.fixture
+def untrusted_valid():
+    """Trust checks off, so assets signed with the development certificate read as Valid,
+    then restored
+
+    There is no getter for the setting, so a fresh signed asset tells whether
+    trust checks were on: only then is it anything but Valid.
+    """
+    probe = make_signed_test_asset("png")
+    was_enabled = read_c2pa_from_bytes(probe, "image/png")["validation_state"] != "Valid"
+    enable_trust_checks(False)
+    yield
+    enable_trust_checks(was_enabled)
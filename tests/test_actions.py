@@ -0,0 +1,67 @@
+"""
+Tests for describing the actions of a manifest in words.
+
+C.jpg records a single c2pa.created action in a c2pa.actions assertion,
+chatgpt_image.png a c2pa.opened action in c2pa.actions.v2.
+"""
+
+import pytest
+
+from fast_c2pa_python import describe_actions, set_action_translations
+
+
+@pytest.fixture(autouse=True)
+def no_translations():
+    yield
+    for locale in ("en", "fr", "pt-br"):
+        set_action_translations(locale, None)
+
+
+def test_describe_actions(read_image):
+    actions = describe_actions(read_image("C.jpg"), "image/jpeg")
+
+    assert len(actions) == 1
+    action = actions[0]
+    assert action["action"] == "c2pa.created"
+    assert action["description"] == "The asset was first created."
+    assert action["assertion"] == "c2pa.actions"
+    assert action["data"]["softwareAgent"] == "Make Test Images 0.33.1"
+
+
+def test_describe_actions_v2(read_image):
+    actions = describe_actions(read_image("chatgpt_image.png"), "image/png")
+
+    assert [a["action"] for a in actions] == ["c2pa.opened"]
+    assert actions[0]["assertion"] == "c2pa.actions.v2"
+    assert actions[0]["description"] == "An existing asset was opened."
+
+
+def test_describe_actions_locale_fallback(read_image):
+    data = read_image("C.jpg")
+    set_action_translations("fr", {"c2pa.created": "Le contenu a été créé."})
+    set_action_translations("pt_BR", {"c2pa.opened": "Um arquivo foi aberto."})
+
+    assert describe_actions(data, "image/jpeg", locale="fr-CA")[0]["description"] == (
+        "Le contenu a été créé."
+    )
+    # Not in the pt-BR table, nor in a pt one: English
+    assert describe_actions(data, "image/jpeg", locale="pt-br")[0]["description"] == (
+        "The asset was first created."
+    )
+
+    set_action_translations("fr", None)
+    assert describe_actions(data, "image/jpeg", locale="fr")[0]["description"] == (
+        "The asset was first created."
+    )
+
+
+def test_describe_actions_english_override(read_image):
+    set_action_translations("en", {"c2pa.created": "Made"})
+
+    assert describe_actions(read_image("C.jpg"), "image/jpeg")[0]["description"] == "Made"
+
+
+def test_describe_actions_errors():
+    assert describe_actions(b"not an image", "image/jpeg") is None
+    with pytest.raises(TypeError):
+        set_action_translations("fr", {"c2pa.created": 1})
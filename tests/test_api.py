@@ -5,13 +5,15 @@ These tests verify that our implementation correctly reads C2PA metadata
 and maintains compatibility with expected output formats.
 """
 
+import base64
+import io
 import os
 import pytest
 import json
 import mimetypes
 from pathlib import Path
 
-from fast_c2pa_python import read_c2pa_from_bytes, read_c2pa_from_file, get_mime_type, setup_trust_verification
+from fast_c2pa_python import read_c2pa_from_bytes, read_c2pa_from_file, read_c2pa_json_from_bytes, read_c2pa_encoded_from_bytes, get_mime_type, set_remote_reference, setup_trust_verification, enable_trust_checks
 
 # Test images - both JPEG and PNG formats
 TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
@@ -89,6 +91,12 @@ def test_read_c2pa_from_bytes(setup_test_image_bytes):
     if "signature_info" in active_manifest:
         assert "issuer" in active_manifest["signature_info"]
 
+@pytest.fixture
+def restores_trust():
+    """Turn trust checks off again after a test that configures them."""
+    yield
+    enable_trust_checks(False)
+
 @pytest.mark.parametrize("test_image", TEST_IMAGES)
 @pytest.mark.order(1)  # Run this test early before any trust setup
 def test_validation_state_without_trust(test_image):
@@ -119,7 +127,7 @@ def test_validation_state_without_trust(test_image):
 
 @pytest.mark.parametrize("test_image", TEST_IMAGES)
 @pytest.mark.order(2)  # Run after the without-trust test
-def test_validation_state_with_trust(test_image):
+def test_validation_state_with_trust(test_image, restores_trust):
     """Test that validation_state is 'Trusted' when trust settings are configured."""
     if not os.path.exists(test_image):
         pytest.skip(f"Test image not found: {test_image}")
@@ -158,7 +166,7 @@ def test_validation_state_with_trust(test_image):
 
 @pytest.mark.parametrize("test_image", TEST_IMAGES) 
 @pytest.mark.order(3)  # Run after trust is configured
-def test_trust_enables_additional_validation(test_image):
+def test_trust_enables_additional_validation(test_image, restores_trust):
     """Test that trust settings add the signingCredential.trusted validation result."""
     if not os.path.exists(test_image):
         pytest.skip(f"Test image not found: {test_image}")
@@ -380,4 +388,241 @@ def test_read_c2pa_from_file_empty_mime(test_image):
     # Both should work the same
     assert type(result_empty) == type(result_auto)
     if result_empty is not None and result_auto is not None:
-        assert result_empty.keys() == result_auto.keys() 
\ No newline at end of file
+        assert result_empty.keys() == result_auto.keys()
+
+def test_read_c2pa_json_canonical(setup_test_image_bytes):
+    """Test that canonical JSON output is sorted, compact and stable."""
+    image_bytes, mime_type, test_image = setup_test_image_bytes
+
+    first = read_c2pa_json_from_bytes(image_bytes, mime_type, canonical=True)
+    second = read_c2pa_json_from_bytes(image_bytes, mime_type, canonical=True)
+    assert first is not None
+    assert first == second
+
+    parsed = json.loads(first)
+    assert first == json.dumps(parsed, sort_keys=True, separators=(",", ":"), ensure_ascii=False)
+
+    # Same content as the default (pretty-printed) output
+    assert parsed == json.loads(read_c2pa_json_from_bytes(image_bytes, mime_type))
+
+def test_read_c2pa_json_streamed(setup_test_image_bytes):
+    """Test that JSON streamed to a file object matches the returned string."""
+    image_bytes, mime_type, test_image = setup_test_image_bytes
+    expected = read_c2pa_json_from_bytes(image_bytes, mime_type)
+
+    class Recorder(io.StringIO):
+        chunks = []
+
+        def write(self, chunk):
+            self.chunks.append(chunk)
+            return super().write(chunk)
+
+    text = Recorder()
+    written = read_c2pa_json_from_bytes(image_bytes, mime_type, output=text, chunk_size=1000)
+    # Key order may differ between reads, as c2pa-rs fills some maps from a HashMap
+    assert json.loads(text.getvalue()) == json.loads(expected)
+    assert written == len(text.getvalue().encode("utf-8"))
+    assert len(text.chunks) > 1
+    assert all(isinstance(chunk, str) for chunk in text.chunks)
+
+    binary = io.BytesIO()
+    read_c2pa_json_from_bytes(image_bytes, mime_type, canonical=True, output=binary, chunk_size=1)
+    assert binary.getvalue().decode("utf-8") == read_c2pa_json_from_bytes(image_bytes, mime_type, canonical=True)
+
+    assert read_c2pa_json_from_bytes(b"no jumbf data", "image/jpeg", output=io.StringIO()) is None
+    with pytest.raises(io.UnsupportedOperation):
+        read_c2pa_json_from_bytes(image_bytes, mime_type, output=io.BufferedReader(io.BytesIO()))
+
+def test_dict_matches_json(setup_test_image_bytes):
+    """Test that the dict result is what json.loads gives for the JSON result."""
+    image_bytes, mime_type, test_image = setup_test_image_bytes
+
+    result = read_c2pa_from_bytes(image_bytes, mime_type)
+    expected = json.loads(read_c2pa_json_from_bytes(image_bytes, mime_type))
+    assert result == expected
+    # Same types too: 1 == 1.0 == True would hide int/float/bool mix-ups
+    assert json.dumps(result, sort_keys=True) == json.dumps(expected, sort_keys=True)
+    assert list(result) == list(expected)
+
+def test_read_c2pa_json_no_jumbf():
+    """Test that JSON output returns None when there is no C2PA data."""
+    assert read_c2pa_json_from_bytes(b"no jumbf data", "image/jpeg", canonical=True) is None
+
+def test_read_c2pa_encoded_json(setup_test_image_bytes):
+    """Test that encoded JSON bytes match the JSON string output."""
+    image_bytes, mime_type, test_image = setup_test_image_bytes
+
+    encoded = read_c2pa_encoded_from_bytes(image_bytes, mime_type, format="json", canonical=True)
+    assert isinstance(encoded, bytes)
+    assert encoded.decode("utf-8") == read_c2pa_json_from_bytes(image_bytes, mime_type, canonical=True)
+
+def test_read_c2pa_encoded_msgpack(setup_test_image_bytes):
+    """Test msgpack output, with and without zstd compression."""
+    msgpack = pytest.importorskip("msgpack")
+    image_bytes, mime_type, test_image = setup_test_image_bytes
+    expected = read_c2pa_from_bytes(image_bytes, mime_type)
+
+    encoded = read_c2pa_encoded_from_bytes(image_bytes, mime_type)
+    assert msgpack.unpackb(encoded) == expected
+
+    zstandard = pytest.importorskip("zstandard")
+    compressed = read_c2pa_encoded_from_bytes(image_bytes, mime_type, compress="zstd")
+    assert msgpack.unpackb(zstandard.ZstdDecompressor().decompress(compressed)) == expected
+
+def test_read_c2pa_encoded_invalid_format(setup_test_image_bytes):
+    """Test that unknown formats and compressions raise ValueError."""
+    image_bytes, mime_type, test_image = setup_test_image_bytes
+
+    with pytest.raises(ValueError):
+        read_c2pa_encoded_from_bytes(image_bytes, mime_type, format="xml")
+    with pytest.raises(ValueError):
+        read_c2pa_encoded_from_bytes(image_bytes, mime_type, compress="lz4")
+
+def test_lenient_truncated_manifest(setup_test_image_bytes):
+    """Test that lenient mode reports assets cut off inside the manifest store."""
+    image_bytes, mime_type, test_image = setup_test_image_bytes
+
+    # Cut the file a short way into the manifest store
+    truncated = image_bytes[:image_bytes.find(b"jumb") + 2000]
+
+    result = read_c2pa_from_bytes(truncated, mime_type, lenient=True)
+    assert result is not None
+    assert result["validation_state"] == "Invalid"
+    assert isinstance(result["manifests"], dict)
+    assert [w["code"] for w in result["warnings"]] == ["asset.truncated"]
+
+def test_lenient_complete_asset(setup_test_image_bytes):
+    """Test that lenient mode does not change results for intact assets."""
+    image_bytes, mime_type, test_image = setup_test_image_bytes
+
+    result = read_c2pa_from_bytes(image_bytes, mime_type, lenient=True)
+    assert result == read_c2pa_from_bytes(image_bytes, mime_type)
+    assert "warnings" not in result
+    assert read_c2pa_from_bytes(b"no jumbf data", "image/jpeg", lenient=True) is None
+
+def test_manifest_source(setup_test_image_bytes):
+    """Test that results report how the manifest store was obtained."""
+    image_bytes, mime_type, test_image = setup_test_image_bytes
+
+    result = read_c2pa_from_bytes(image_bytes, mime_type)
+    assert result["is_embedded"] is True
+    assert result["remote_url"] is None
+
+def test_manifest_source_remote_url():
+    """Test that a remote manifest reference in the XMP is reported."""
+    with open(TEST_IMAGES[1], "rb") as f:
+        image_bytes = f.read()
+
+    url = "https://example.com/manifests/firefly.c2pa"
+    xmp = ('<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">'
+           f'<rdf:Description rdf:about="" xmlns:dcterms="http://purl.org/dc/terms/" dcterms:provenance="{url}"/>'
+           '</rdf:RDF></x:xmpmeta>').encode()
+    body = b"http://ns.adobe.com/xap/1.0/\x00" + xmp
+    app1 = b"\xff\xe1" + (len(body) + 2).to_bytes(2, "big") + body
+
+    result = read_c2pa_from_bytes(image_bytes[:2] + app1 + image_bytes[2:], "image/jpeg")
+    assert result["is_embedded"] is True
+    assert result["remote_url"] == url
+
+def test_manifest_source_remote_url_location():
+    """Test that only the XMP where the format stores it is read for embedded manifests."""
+    url = "https://example.com/manifests/chatgpt.c2pa"
+    with open(TEST_IMAGES[0], "rb") as f:
+        png = set_remote_reference(f.read(), "image/png", url)
+    assert read_c2pa_from_bytes(png, "image/png")["remote_url"] == url
+
+    # A packet in a comment segment is not the XMP of the JPEG
+    with open(TEST_IMAGES[1], "rb") as f:
+        image_bytes = f.read()
+    xmp = ('<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">'
+           f'<rdf:Description rdf:about="" xmlns:dcterms="http://purl.org/dc/terms/" dcterms:provenance="{url}"/>'
+           '</rdf:RDF></x:xmpmeta>').encode()
+    com = b"\xff\xfe" + (len(xmp) + 2).to_bytes(2, "big") + xmp
+    result = read_c2pa_from_bytes(image_bytes[:2] + com + image_bytes[2:], "image/jpeg")
+    assert result["is_embedded"] is True
+    assert result["remote_url"] is None
+
+def test_max_ingredient_depth():
+    """Test that manifests deeper than max_ingredient_depth are left out."""
+    with open(TEST_IMAGES[0], "rb") as f:
+        image_bytes = f.read()
+
+    full = read_c2pa_from_bytes(image_bytes, "image/png")
+    assert len(full["manifests"]) == 2
+    assert read_c2pa_from_bytes(image_bytes, "image/png", max_ingredient_depth=1) == full
+
+    limited = read_c2pa_from_bytes(image_bytes, "image/png", max_ingredient_depth=0)
+    assert list(limited["manifests"]) == [full["active_manifest"]]
+    assert [w["code"] for w in limited["warnings"]] == ["ingredients.depthLimited"]
+    assert json.loads(read_c2pa_json_from_bytes(image_bytes, "image/png", max_ingredient_depth=0)) == limited
+
+def test_skip_ingredient_thumbnails():
+    """Test that ingredient thumbnails can be left out."""
+    with open(TEST_IMAGES[0], "rb") as f:
+        image_bytes = f.read()
+
+    result = read_c2pa_from_bytes(image_bytes, "image/png", ingredient_thumbnails=False)
+    ingredients = [i for m in result["manifests"].values() for i in m.get("ingredients", [])]
+    assert ingredients
+    assert all("thumbnail" not in i for i in ingredients)
+
+def test_parse_datetimes():
+    """Test that signing times can be returned as UTC datetimes."""
+    from datetime import datetime, timezone
+
+    result = read_c2pa_from_file(TEST_IMAGES[1])
+    parsed = read_c2pa_from_file(TEST_IMAGES[1], parse_datetimes=True)
+    label = result["active_manifest"]
+    text = result["manifests"][label]["signature_info"]["time"]
+    time = parsed["manifests"][label]["signature_info"]["time"]
+
+    assert isinstance(text, str)
+    assert isinstance(time, datetime)
+    assert time.utcoffset().total_seconds() == 0
+    assert time == datetime.fromisoformat(text).astimezone(timezone.utc)
+
+def test_read_file_memory_mapped():
+    """Test that files read by path match the same bytes read in memory."""
+    import fast_c2pa_core
+
+    for path in TEST_IMAGES:
+        expected = read_c2pa_from_bytes(Path(path).read_bytes(), get_mime_type(path))
+        assert read_c2pa_from_file(path) == expected
+        # The MIME type defaults to the one of the extension in Rust as well
+        assert fast_c2pa_core.read_c2pa_from_file(Path(path)) == expected
+    assert read_c2pa_from_file(TEST_IMAGE_NOT_C2PA) is None
+
+    with pytest.raises(FileNotFoundError):
+        read_c2pa_from_file(str(TEST_IMAGES_DIR / "missing.jpg"))
+
+def test_detailed_report():
+    """Test that detailed=True adds the low-level report of the manifest store."""
+    image_bytes = Path(TEST_IMAGES[0]).read_bytes()
+    result = read_c2pa_from_bytes(image_bytes, "image/png", detailed=True)
+    summary = read_c2pa_from_bytes(image_bytes, "image/png")
+    assert "detailed" not in summary
+    assert {k: v for k, v in result.items() if k != "detailed"} == summary
+
+    detailed = result["detailed"]
+    assert detailed["active_manifest"] == summary["active_manifest"]
+    assert detailed["manifests"].keys() == summary["manifests"].keys()
+    manifest = detailed["manifests"][detailed["active_manifest"]]
+    assert manifest["claim"]["signature"].endswith("/c2pa.signature")
+    assert "c2pa.hash.data" in manifest["assertion_store"]
+    assert isinstance(manifest["assertion_store"]["c2pa.hash.data"]["hash"], str)
+    # The raw COSE_Sign1 signature, base64 encoded: a CBOR tag 18
+    assert base64.b64decode(manifest["signature"]["cose_sign1"])[0] == 0xD2
+
+    # The same report for chunks and for memory-mapped files
+    chunks = [image_bytes[:1000], image_bytes[1000:]]
+    assert read_c2pa_from_bytes(chunks, "image/png", detailed=True)["detailed"] == detailed
+    assert read_c2pa_from_file(TEST_IMAGES[0], detailed=True)["detailed"] == detailed
+    assert read_c2pa_from_bytes(Path(TEST_IMAGE_NOT_C2PA).read_bytes(), "image/png",
+                                detailed=True) is None
+
+def test_detailed_report_bmff_hash():
+    """Test that the hashes of BMFF hash assertions are base64 in the detailed report."""
+    result = read_c2pa_from_file(str(TEST_IMAGES_DIR / "sequence.heic"), detailed=True)
+    detailed = result["detailed"]
+    assertions = detailed["manifests"][detailed["active_manifest"]]["assertion_store"]
+    assert isinstance(assertions["c2pa.hash.bmff.v2"]["hash"], str)
@@ -0,0 +1,163 @@
+"""
+Tests for metadata attached to individual assertions.
+
+C.jpg carries no assertion metadata, so the tests add assertions to its active
+manifest. The signature no longer matches, which does not matter here: the
+metadata is read without validation.
+"""
+
+import struct
+from pathlib import Path
+
+from fast_c2pa_python import get_assertion_metadata, read_c2pa_from_bytes
+
+TEST_IMAGE = Path(__file__).parent / "test_images" / "C.jpg"
+CBOR_UUID = bytes.fromhex("63626f7200110010800000aa00389b71")
+JSON_UUID = bytes.fromhex("6a736f6e00110010800000aa00389b71")
+
+
+def cbor(value):
+    """Encode the subset of CBOR the tests need."""
+    def head(major, n):
+        if n < 24:
+            return bytes([major << 5 | n])
+        for info, fmt in ((24, ">B"), (25, ">H"), (26, ">I"), (27, ">Q")):
+            if n < 1 << (8 * struct.calcsize(fmt)):
+                return bytes([major << 5 | info]) + struct.pack(fmt, n)
+
+    if isinstance(value, bool):
+        return b"\xf5" if value else b"\xf4"
+    if isinstance(value, int):
+        return head(0, value) if value >= 0 else head(1, -1 - value)
+    if isinstance(value, bytes):
+        return head(2, len(value)) + value
+    if isinstance(value, str):
+        return head(3, len(value.encode())) + value.encode()
+    if isinstance(value, list):
+        return head(4, len(value)) + b"".join(cbor(v) for v in value)
+    return head(5, len(value)) + b"".join(cbor(k) + cbor(v) for k, v in value.items())
+
+
+def box(box_type, payload):
+    return struct.pack(">I", len(payload) + 8) + box_type + payload
+
+
+def boxes(data):
+    pos = 0
+    while pos < len(data):
+        length = struct.unpack(">I", data[pos:pos + 4])[0]
+        yield data[pos + 4:pos + 8], data[pos + 8:pos + length]
+        pos += length
+
+
+def superbox(uuid, label, children):
+    return box(b"jumb", box(b"jumd", uuid + b"\x03" + label.encode() + b"\x00") + b"".join(children))
+
+
+def label_of(payload):
+    _, description = next(boxes(payload))
+    return description[17:description.index(b"\x00", 17)].decode()
+
+
+def add_assertions(store, added):
+    """Append assertion superboxes to the last manifest of a manifest store."""
+    (_, outer), = boxes(store)
+    manifests = list(boxes(outer))
+    kind, manifest = manifests[-1]
+    children = []
+    for child_type, child in boxes(manifest):
+        if child_type == b"jumb" and label_of(child) == "c2pa.assertions":
+            child += b"".join(added)
+        children.append(box(child_type, child))
+    manifests[-1] = (kind, b"".join(children))
+    return box(b"jumb", b"".join(box(t, p) for t, p in manifests))
+
+
+def with_store(data, store):
+    """Replace the APP11 manifest store of a JPEG."""
+    out, pos, inserted = [data[:2]], 2, False
+    while data[pos + 1] != 0xDA:
+        length = struct.unpack(">H", data[pos + 2:pos + 4])[0]
+        segment = data[pos:pos + 2 + length]
+        if segment[1] == 0xEB:
+            if not inserted:
+                for seq, start in enumerate(range(0, len(store), 60000), 1):
+                    payload = store[start:start + 60000]
+                    if start:
+                        payload = store[:8] + payload
+                    body = b"JP\x02\x11" + struct.pack(">I", seq) + payload
+                    out.append(b"\xff\xeb" + struct.pack(">H", len(body) + 2) + body)
+                inserted = True
+        else:
+            out.append(segment)
+        pos += 2 + length
+    return b"".join(out) + data[pos:]
+
+
+def extract_store(data):
+    store, pos = b"", 2
+    while data[pos + 1] != 0xDA:
+        length = struct.unpack(">H", data[pos + 2:pos + 4])[0]
+        if data[pos + 1] == 0xEB and data[pos + 4:pos + 6] == b"JP":
+            body = data[pos + 4:pos + 2 + length]
+            store += body[16:] if store else body[8:]
+        pos += 2 + length
+    return store
+
+
+REVIEW = {"dateTime": "2024-05-01T10:00:00Z", "reviewRatings": [{"value": 4, "explanation": "checked"}]}
+
+
+def signed_with(*assertions):
+    data = TEST_IMAGE.read_bytes()
+    return with_store(data, add_assertions(extract_store(data), assertions))
+
+
+def test_no_assertion_metadata():
+    data = TEST_IMAGE.read_bytes()
+    result = get_assertion_metadata(data, "image/jpeg")
+    assert result["active_manifest"] == read_c2pa_from_bytes(data, "image/jpeg")["active_manifest"]
+    assert all(entries == [] for entries in result["manifests"].values())
+
+
+def test_metadata_assertion_references_assertion():
+    metadata = dict(REVIEW, reference={"url": "self#jumbf=c2pa.assertions/c2pa.thumbnail.claim.jpeg",
+                                       "hash": b"\x00" * 32})
+    data = signed_with(superbox(CBOR_UUID, "c2pa.assertion.metadata", [box(b"cbor", cbor(metadata))]))
+
+    result = get_assertion_metadata(data, "image/jpeg")
+    (entry,) = result["manifests"][result["active_manifest"]]
+    assert entry == {
+        "assertion": "c2pa.thumbnail.claim.jpeg",
+        "stored_in": "c2pa.assertion.metadata",
+        "metadata": REVIEW,
+    }
+
+
+def test_embedded_metadata():
+    data = signed_with(
+        superbox(CBOR_UUID, "com.example.rated", [box(b"cbor", cbor({"score": 7, "metadata": REVIEW}))]),
+        superbox(JSON_UUID, "com.example.notes", [box(b"json", b'{"metadata": {"dataSource": {"type": "human"}}}')]),
+    )
+    result = get_assertion_metadata(data, "image/jpeg")
+    entries = result["manifests"][result["active_manifest"]]
+    assert entries == [
+        {"assertion": "com.example.rated", "stored_in": "com.example.rated", "metadata": REVIEW},
+        {"assertion": "com.example.notes", "stored_in": "com.example.notes",
+         "metadata": {"dataSource": {"type": "human"}}},
+    ]
+
+
+def test_metadata_without_reference():
+    data = signed_with(
+        superbox(CBOR_UUID, "c2pa.assertion.metadata__1", [box(b"cbor", cbor({"dateTime": "2024-05-01T10:00:00Z"}))]),
+    )
+    result = get_assertion_metadata(data, "image/jpeg")
+    (entry,) = result["manifests"][result["active_manifest"]]
+    assert entry["assertion"] is None
+    assert entry["stored_in"] == "c2pa.assertion.metadata__1"
+
+
+def test_no_manifest():
+    data = (TEST_IMAGE.parent / "screenshot_noc2pa.png").read_bytes()
+    assert get_assertion_metadata(data, "image/png") is None
@@ -0,0 +1,96 @@
+"""
+Tests for the asset info of read results: dimensions, orientation, color space
+and duration, from the headers of the asset.
+"""
+
+import io
+import struct
+import wave
+
+import pytest
+
+from fast_c2pa_python import get_asset_info, read_c2pa_from_bytes, read_c2pa_from_stream
+
+
+def bmff_box(kind, *children, full=None):
+    body = b"".join(children)
+    if full is not None:
+        body = struct.pack(">I", full) + body
+    return struct.pack(">I4s", 8 + len(body), kind) + body
+
+
+def with_exif_orientation(jpeg, orientation):
+    tiff = b"II*\x00" + struct.pack("<I", 8) + struct.pack("<H", 1)
+    tiff += struct.pack("<HHIHH", 0x0112, 3, 1, orientation, 0) + struct.pack("<I", 0)
+    body = b"Exif\x00\x00" + tiff
+    return jpeg[:2] + b"\xff\xe1" + struct.pack(">H", len(body) + 2) + body + jpeg[2:]
+
+
+def test_in_read_results(read_image):
+    data = read_image("C.jpg")
+    info = {"width": 2048, "height": 1365, "orientation": None, "color_space": "RGB",
+            "duration": None}
+    assert get_asset_info(data) == info
+    assert read_c2pa_from_bytes(data, "image/jpeg")["asset_info"] == info
+    assert read_c2pa_from_bytes([data[:100], data[100:]], "image/jpeg")["asset_info"] == info
+    assert read_c2pa_from_stream(io.BytesIO(data))["asset_info"] == info
+
+
+def test_images(read_image):
+    assert get_asset_info(read_image("chatgpt_image_gray.png"))["color_space"] == "Gray"
+    rotated = get_asset_info(with_exif_orientation(read_image("icon_test.jpg"), 6))
+    assert (rotated["width"], rotated["height"], rotated["orientation"]) == (64, 48, 6)
+    gif = b"GIF89a" + struct.pack("<HH", 320, 200) + b"\x00\x00\x00;"
+    assert get_asset_info(gif)["width"] == 320
+
+
+def test_heif_rotation():
+    ispe = bmff_box(b"ispe", struct.pack(">II", 4032, 3024), full=0)
+    irot = bmff_box(b"irot", b"\x01")
+    ipma = bmff_box(b"ipma", struct.pack(">IHB", 1, 1, 2) + b"\x81\x82", full=0)
+    meta = bmff_box(
+        b"meta",
+        bmff_box(b"pitm", struct.pack(">H", 1), full=0),
+        bmff_box(b"iprp", bmff_box(b"ipco", ispe, irot), ipma),
+        full=0,
+    )
+    heic = bmff_box(b"ftyp", b"heic\x00\x00\x00\x00mif1heic") + meta
+    info = get_asset_info(heic)
+    assert (info["width"], info["height"], info["orientation"]) == (4032, 3024, 8)
+
+
+def test_video_duration_and_rotation():
+    mvhd = bmff_box(b"mvhd", struct.pack(">IIII", 0, 0, 1000, 2500), full=0)
+    matrix = struct.pack(">9i", 0, 0x10000, 0, -0x10000, 0, 0, 0, 0, 0x40000000)
+    tkhd = bmff_box(b"tkhd", bytes(20) + bytes(16) + matrix
+                    + struct.pack(">II", 1920 << 16, 1080 << 16), full=0)
+    hdlr = bmff_box(b"hdlr", struct.pack(">I4s", 0, b"vide") + bytes(13), full=0)
+    trak = bmff_box(b"trak", tkhd, bmff_box(b"mdia", hdlr))
+    mp4 = (bmff_box(b"ftyp", b"isom\x00\x00\x02\x00isommp41") + bmff_box(b"mdat", bytes(64))
+           + bmff_box(b"moov", mvhd, trak))
+    assert get_asset_info(mp4, "video/mp4") == {
+        "width": 1920, "height": 1080, "orientation": 6, "color_space": None, "duration": 2.5,
+    }
+
+
+def test_large_size_box_past_its_parent():
+    # A 64-bit size header whose body would start after the end of moov
+    mp4 = b"\0\0\0\x12moov" + b"\0\0\0\x01mvhd" + (16).to_bytes(8, "big") + b"\0" * 32
+    assert set(get_asset_info(mp4, "video/mp4").values()) == {None}
+
+def test_audio_duration():
+    buffer = io.BytesIO()
+    with wave.open(buffer, "wb") as audio:
+        audio.setnchannels(1)
+        audio.setsampwidth(2)
+        audio.setframerate(8000)
+        audio.writeframes(bytes(2 * 12000))
+    assert get_asset_info(buffer.getvalue())["duration"] == 1.5
+
+
+def test_nothing_to_tell(read_image):
+    info = get_asset_info(read_image("id3_priv.mp3"), "audio/mpeg")
+    assert set(info.values()) == {None}
+    assert set(get_asset_info(b"\xff\xd8\xff\xe0", "image/jpeg").values()) == {None}
+    with pytest.raises(ValueError):
+        get_asset_info(b"not an asset")
@@ -0,0 +1,42 @@
+"""
+Tests for awaitable reads with read_c2pa_from_bytes_async.
+"""
+
+import asyncio
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, read_c2pa_from_bytes_async
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+JPEG = (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+PNG = (TEST_IMAGES_DIR / "chatgpt_image.png").read_bytes()
+NO_C2PA = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+
+
+def test_read_async():
+    async def read():
+        return await asyncio.gather(
+            read_c2pa_from_bytes_async(JPEG, "image/jpeg"),
+            read_c2pa_from_bytes_async([PNG[:1000], PNG[1000:]], "image/png", max_ingredient_depth=0),
+            read_c2pa_from_bytes_async(NO_C2PA, "image/png"),
+        )
+
+    jpeg, png, no_c2pa = asyncio.run(read())
+    # Validation results may differ in OCSP responses fetched meanwhile
+    assert jpeg["manifests"] == read_c2pa_from_bytes(JPEG, "image/jpeg")["manifests"]
+    assert list(png["manifests"]) == [png["active_manifest"]]
+    assert no_c2pa is None
+
+
+def test_read_async_errors():
+    async def read(data, **options):
+        return await read_c2pa_from_bytes_async(data, "image/jpeg", **options)
+
+    # Raised when awaited
+    with pytest.raises(ValueError):
+        asyncio.run(read(JPEG, schema_version=999))
+    # Raised by the call
+    with pytest.raises(TypeError):
+        asyncio.run(read("not bytes"))
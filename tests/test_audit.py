@@ -0,0 +1,78 @@
+"""
+Tests for the audit events of validation decisions.
+"""
+
+import json
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    extract_manifest_store,
+    read_c2pa_from_bytes,
+    set_audit_callback,
+    set_version_limits,
+    verify_manifest_bytes,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+PNG = (TEST_IMAGES_DIR / "chatgpt_image.png").read_bytes()
+
+
+@pytest.fixture
+def events():
+    events = []
+    set_audit_callback(events.append)
+    yield events
+    set_audit_callback(None)
+    set_version_limits()
+
+
+def test_steps_then_decision(events):
+    result = read_c2pa_from_bytes(PNG, "image/png")
+    *steps, decision = events
+
+    statuses = result["validation_results"]["activeManifest"]
+    expected = sorted(s["code"] for key in ("success", "informational", "failure")
+                      for s in statuses.get(key, []))
+    assert sorted(step["code"] for step in steps if step["ingredient"] is None) == expected
+    assert {step["event"] for step in steps} == {"validation.step"}
+    data_hash = next(step for step in steps if step["code"] == "assertion.dataHash.match")
+    assert data_hash["step"] == "assertion"
+    assert data_hash["outcome"] == "success"
+    assert data_hash["manifest"] == result["active_manifest"]
+
+    assert decision["event"] == "validation.decision"
+    assert decision["validation_state"] == result["validation_state"]
+    assert decision["active_manifest"] == result["active_manifest"]
+    assert decision["failures"] == sum(step["outcome"] == "failure" for step in steps)
+    assert len({event["read_id"] for event in events}) == 1
+    assert {event["operation"] for event in events} == {"read"}
+    json.dumps(events)
+
+
+def test_library_checks_and_verify(events):
+    set_version_limits(max_assertion_versions={"c2pa.actions": 1}, action="warn")
+    read_c2pa_from_bytes(PNG, "image/png")
+    warnings = [event for event in events if event.get("outcome") == "warning"]
+    assert {event["code"] for event in warnings} == {"assertion.versionNotAllowed"}
+
+    events.clear()
+    set_version_limits()
+    verify_manifest_bytes(extract_manifest_store(PNG, "image/png"), PNG, "image/png")
+    assert events[-1]["event"] == "validation.decision"
+    assert {event["operation"] for event in events} == {"verify"}
+
+
+def test_callback_errors_propagate(events):
+    def fail(event):
+        raise OSError("audit log unavailable")
+
+    set_audit_callback(fail)
+    with pytest.raises(OSError, match="audit log"):
+        read_c2pa_from_bytes(PNG, "image/png")
+
+    set_audit_callback(None)
+    assert read_c2pa_from_bytes(PNG, "image/png") is not None
+    with pytest.raises(TypeError):
+        set_audit_callback("not callable")
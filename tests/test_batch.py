@@ -0,0 +1,58 @@
+"""
+Tests for reading many assets in parallel with read_c2pa_batch.
+"""
+
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_batch, read_c2pa_from_bytes, set_audit_callback
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+JPEG = TEST_IMAGES_DIR / "adobe_firefly_image.jpg"
+PNG = TEST_IMAGES_DIR / "chatgpt_image.png"
+NO_C2PA = TEST_IMAGES_DIR / "screenshot_noc2pa.png"
+
+
+def test_results_in_item_order():
+    data = JPEG.read_bytes()
+    expected = read_c2pa_from_bytes(data, "image/jpeg")
+    items = [(data, "image/jpeg"), str(PNG), NO_C2PA, JPEG] * 4
+
+    results = read_c2pa_batch(items, max_workers=3)
+    assert len(results) == len(items)
+    for i in range(0, len(items), 4):
+        jpeg, png, no_c2pa, path = results[i:i + 4]
+        # Validation results may differ in OCSP responses fetched meanwhile
+        assert jpeg["manifests"] == path["manifests"] == expected["manifests"]
+        assert png["active_manifest"] == read_c2pa_from_bytes(PNG.read_bytes(), "image/png")["active_manifest"]
+        assert no_c2pa is None
+    assert read_c2pa_batch([]) == []
+
+
+def test_errors_returned_per_item():
+    results = read_c2pa_batch([
+        "/nonexistent/image.jpg",
+        (b"\xff\xd8\xff\xeb" + b"\x00" * 64, "image/jpeg"),
+        (JPEG.read_bytes(), "image/jpeg"),
+    ])
+    missing, _, ok = results
+    assert isinstance(missing, FileNotFoundError)
+    assert missing.filename == "/nonexistent/image.jpg"
+    assert isinstance(ok, dict)
+
+    with pytest.raises(TypeError, match="batch items"):
+        read_c2pa_batch([42])
+    with pytest.raises(ValueError, match="max_workers"):
+        read_c2pa_batch([JPEG], max_workers=0)
+
+
+def test_hooks_run_for_each_result():
+    events = []
+    set_audit_callback(events.append)
+    try:
+        read_c2pa_batch([JPEG, PNG, NO_C2PA])
+    finally:
+        set_audit_callback(None)
+    decisions = [event for event in events if event["event"] == "validation.decision"]
+    assert len(decisions) == 2
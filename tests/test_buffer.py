@@ -0,0 +1,60 @@
+"""
+Tests for reading assets from objects with the buffer protocol.
+"""
+
+import array
+import mmap
+import tempfile
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes
+
+
+@pytest.mark.parametrize("allow_threads", [False, True])
+def test_bytearray_and_memoryview(read_image, allow_threads):
+    data = read_image("adobe_firefly_image.jpg")
+    expected = read_c2pa_from_bytes(data, "image/jpeg")
+    assert read_c2pa_from_bytes(bytearray(data), "image/jpeg", allow_threads) == expected
+    padded = b"padding" + data + b"padding"
+    view = memoryview(padded)[7:-7]
+    assert read_c2pa_from_bytes(view, allow_threads=allow_threads) == expected
+
+
+def test_mmap(read_image):
+    data = read_image("chatgpt_image.png")
+    with tempfile.TemporaryFile() as file:
+        file.write(data)
+        file.flush()
+        with mmap.mmap(file.fileno(), 0, access=mmap.ACCESS_READ) as mapped:
+            result = read_c2pa_from_bytes(mapped, "image/png", allow_threads=True)
+            assert result == read_c2pa_from_bytes(data, "image/png")
+            # The buffer export ends with the call
+            mapped.close()
+
+
+def test_numpy_array(read_image):
+    np = pytest.importorskip("numpy")
+    data = read_image("adobe_firefly_image.jpg")
+    pixels = np.frombuffer(data, dtype=np.uint8).copy()
+    assert read_c2pa_from_bytes(pixels, "image/jpeg") == read_c2pa_from_bytes(data, "image/jpeg")
+
+
+def test_chunks_of_buffers(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    view = memoryview(data)
+    chunks = [view[:1000], bytearray(data[1000:5000]), data[5000:]]
+    result = read_c2pa_from_bytes(chunks, "image/jpeg")
+    assert result["active_manifest"] == read_c2pa_from_bytes(data, "image/jpeg")["active_manifest"]
+
+
+def test_unsupported_buffers(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    with pytest.raises(TypeError, match="bytes-like"):
+        read_c2pa_from_bytes("not bytes", "image/jpeg")
+    with pytest.raises(TypeError, match="bytes-like"):
+        read_c2pa_from_bytes(array.array("f", [1.0, 2.0]), "image/jpeg")
+    with pytest.raises(TypeError, match="C-contiguous"):
+        read_c2pa_from_bytes(memoryview(data)[::2], "image/jpeg")
+    with pytest.raises(TypeError, match="data chunks"):
+        read_c2pa_from_bytes([data[:10], 1], "image/jpeg")
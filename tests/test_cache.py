@@ -0,0 +1,216 @@
+"""
+Tests for the shared result cache and the cache counters.
+"""
+
+import base64
+import json
+import os
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    clear_shared_cache,
+    disable_shared_cache,
+    enable_shared_cache,
+    get_cache_stats,
+    get_shared_cache_stats,
+    load_c2pa_settings,
+    read_c2pa_from_bytes,
+    read_c2pa_json_from_bytes,
+)
+
+TEST_DIR = Path(__file__).parent
+TEST_IMAGES_DIR = TEST_DIR / "test_images"
+# The roots of the c2pa-rs test certificates, and trust anchors without them
+TEST_ROOTS = (TEST_DIR / "sign_cert" / "test_cert_root_bundle.pem").read_text()
+OTHER_ANCHORS = (TEST_DIR / "tmp_cert" / "anchors.pem").read_text()
+
+
+@pytest.fixture
+def asset():
+    return (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+
+
+@pytest.fixture
+def cache_path(tmp_path):
+    yield tmp_path / "results.cache"
+    disable_shared_cache()
+
+
+def test_disabled_by_default():
+    assert get_shared_cache_stats() is None
+
+
+def test_second_read_hits(asset, cache_path):
+    expected = read_c2pa_from_bytes(asset, "image/jpeg")
+    enable_shared_cache(str(cache_path), size_mb=4)
+
+    assert read_c2pa_from_bytes(asset, "image/jpeg") == expected
+    assert read_c2pa_json_from_bytes(asset, "image/jpeg") is not None
+    stats = get_shared_cache_stats()
+    assert (stats["hits"], stats["misses"], stats["stores"]) == (1, 1, 1)
+    assert str(stats["path"]) == str(cache_path)
+    assert stats["max_entry_bytes"] == 256 * 1024
+
+    # Assets without a manifest store are not cached
+    no_c2pa = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    assert read_c2pa_from_bytes(no_c2pa, "image/png") is None
+    assert get_shared_cache_stats()["stores"] == 1
+
+
+def test_shared_between_processes(asset, cache_path):
+    enable_shared_cache(str(cache_path), size_mb=4)
+
+    pid = os.fork()
+    if pid == 0:
+        read_c2pa_from_bytes(asset, "image/jpeg")
+        os._exit(0 if get_shared_cache_stats()["stores"] == 1 else 1)
+    _, status = os.waitpid(pid, 0)
+    assert os.waitstatus_to_exitcode(status) == 0
+
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    stats = get_shared_cache_stats()
+    assert (stats["hits"], stats["stores"]) == (1, 0)
+
+
+def test_created_once_by_concurrent_processes(asset, cache_path):
+    # Workers opening a missing cache at once must all map the same file
+    start_r, start_w = os.pipe()
+    ready_r, ready_w = os.pipe()
+    read_r, read_w = os.pipe()
+    children = []
+    for _ in range(4):
+        pid = os.fork()
+        if pid == 0:
+            os.read(start_r, 1)
+            enable_shared_cache(str(cache_path), size_mb=4)
+            os.write(ready_w, b".")
+            os.read(read_r, 1)
+            read_c2pa_from_bytes(asset, "image/jpeg")
+            os._exit(0 if get_shared_cache_stats()["hits"] == 1 else 1)
+        children.append(pid)
+    os.write(start_w, b"." * len(children))
+    for _ in children:
+        os.read(ready_r, 1)
+
+    enable_shared_cache(str(cache_path), size_mb=4)
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    assert get_shared_cache_stats()["stores"] == 1
+    os.write(read_w, b"." * len(children))
+    for pid in children:
+        _, status = os.waitpid(pid, 0)
+        assert os.waitstatus_to_exitcode(status) == 0
+    for fd in (start_r, start_w, ready_r, ready_w, read_r, read_w):
+        os.close(fd)
+
+
+def test_reopen_keeps_results(asset, cache_path):
+    enable_shared_cache(str(cache_path), size_mb=4)
+    read_c2pa_from_bytes(asset, "image/jpeg")
+
+    enable_shared_cache(str(cache_path), size_mb=4)
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    assert get_shared_cache_stats()["hits"] == 1
+
+    # Different sizes recreate the file
+    enable_shared_cache(str(cache_path), size_mb=8)
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    assert get_shared_cache_stats()["hits"] == 0
+
+
+def test_clear(asset, cache_path):
+    enable_shared_cache(str(cache_path), size_mb=4)
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    clear_shared_cache()
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    stats = get_shared_cache_stats()
+    assert (stats["hits"], stats["stores"]) == (0, 2)
+
+
+def test_expired_and_oversized_entries(asset, cache_path):
+    enable_shared_cache(str(cache_path), size_mb=4, ttl_seconds=0)
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    assert get_shared_cache_stats()["hits"] == 0
+
+    enable_shared_cache(str(cache_path), size_mb=4, max_entry_kb=1)
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    assert get_shared_cache_stats()["stores"] == 0
+
+
+def test_settings_change_misses(asset, cache_path):
+    enable_shared_cache(str(cache_path), size_mb=4)
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    # Settings that change nothing keep the stored results
+    load_c2pa_settings("{}")
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    assert get_shared_cache_stats()["hits"] == 1
+    try:
+        load_c2pa_settings(json.dumps({"core": {"max_memory_usage": 2**40 + os.getpid()}}))
+        read_c2pa_from_bytes(asset, "image/jpeg")
+        assert get_shared_cache_stats()["hits"] == 1
+    finally:
+        load_c2pa_settings(json.dumps({"core": {"max_memory_usage": None}}))
+
+
+def test_trust_anchors_change_misses(cache_path):
+    # Each load merges into the settings before it, so the key must follow the
+    # merged settings rather than the last load
+    data = (TEST_IMAGES_DIR / "multipage.tif").read_bytes()
+
+    def trust(anchors):
+        load_c2pa_settings(json.dumps({
+            "trust": {"trust_anchors": anchors, "allowed_list": base64.b64encode(b"none").decode()},
+        }))
+        load_c2pa_settings(json.dumps({"verify": {"verify_trust": True}}))
+        return read_c2pa_from_bytes(data, "image/tiff")["validation_state"]
+
+    enable_shared_cache(str(cache_path), size_mb=4)
+    try:
+        assert trust(TEST_ROOTS) == "Trusted"
+        assert trust(OTHER_ANCHORS) == "Invalid"
+        assert trust(TEST_ROOTS) == "Trusted"
+        assert get_shared_cache_stats()["hits"] == 1
+    finally:
+        load_c2pa_settings(json.dumps({
+            "trust": {"trust_anchors": None, "allowed_list": None},
+            "verify": {"verify_trust": False},
+        }))
+
+
+def test_invalid_sizes(cache_path):
+    with pytest.raises(ValueError):
+        enable_shared_cache(str(cache_path), size_mb=1, max_entry_kb=2048)
+    with pytest.raises(ValueError):
+        enable_shared_cache(str(cache_path), max_entry_kb=0)
+
+
+def test_evictions(asset, cache_path):
+    # A single slot, so the second asset replaces the first
+    enable_shared_cache(str(cache_path), size_mb=1, max_entry_kb=1000)
+    other = (TEST_IMAGES_DIR / "C.jpg").read_bytes()
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    read_c2pa_from_bytes(other, "image/jpeg")
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    stats = get_shared_cache_stats()
+    assert stats["slots"] == 1
+    assert (stats["hits"], stats["misses"], stats["stores"], stats["evictions"]) == (1, 3, 3, 2)
+
+
+def test_every_cache(asset, cache_path):
+    assert get_cache_stats()["results"] is None
+    enable_shared_cache(str(cache_path), size_mb=4)
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    assert get_cache_stats()["results"] == get_shared_cache_stats()
+
+    before = get_cache_stats()["settings"]
+    load_c2pa_settings("{}")
+    clear_shared_cache()
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    settings = get_cache_stats()["settings"]
+    assert settings["evictions"] == before["evictions"] + 1
+    # The loading thread keeps its copy current
+    assert settings["hits"] == before["hits"] + 1
+    assert settings["misses"] == before["misses"]
@@ -0,0 +1,68 @@
+"""
+Tests for build capability introspection.
+"""
+
+from fast_c2pa_python import (
+    can_embed,
+    can_sidecar,
+    capabilities,
+    convert_to_gray_keep_c2pa,
+    measure_memory,
+    read_c2pa_from_bytes_async,
+    read_c2pa_from_pdf_images,
+    sign_and_embed,
+    supported_mime_types,
+)
+
+
+def available(func, *args):
+    try:
+        func(*args)
+    except NotImplementedError:
+        return False
+    except Exception:
+        return True
+    return True
+
+
+def test_report():
+    caps = capabilities()
+    assert set(caps) == {
+        "version", "c2pa_version", "features", "signing_algorithms", "hash_algorithms", "formats",
+        "schema_versions",
+    }
+    assert caps["c2pa_version"].count(".") == 2
+    assert {"es256", "ps256", "ed25519"} <= set(caps["signing_algorithms"])
+    assert "sha256" in caps["hash_algorithms"]
+    assert {"image/jpeg", "image/png", "video/mp4"} <= set(caps["formats"])
+    assert 1 in caps["schema_versions"]
+
+
+def test_features_match_build():
+    features = capabilities()["features"]
+    assert features["file_io"] and features["remote_fetch"]
+    assert features["signing"] == available(sign_and_embed, b"", "image/jpeg", "{}", {})
+    assert features["pdf"] == available(read_c2pa_from_pdf_images, b"")
+    assert features["asyncio"] == available(read_c2pa_from_bytes_async, b"", "image/jpeg")
+    assert features["transforms"] == available(convert_to_gray_keep_c2pa, "/nonexistent", "/nonexistent", "png")
+    assert features["memory_stats"] == available(measure_memory, len, b"")
+
+
+def test_writer_formats():
+    assert can_embed("image/jpeg") and can_embed("JPG") and can_embed("video/mp4")
+    assert not can_embed("application/pdf")
+    assert not can_embed("image/x-sony-arw")
+    assert not can_embed("application/c2pa")
+    assert not can_embed("image/x-unknown")
+    formats = capabilities()["formats"]
+    assert [can_sidecar(format) for format in formats] == [can_embed(format) for format in formats]
+
+
+def test_supported_mime_types():
+    formats = supported_mime_types()
+    assert {"image/jpeg", "application/pdf", "audio/wav", "application/c2pa"} <= set(formats["read"])
+    assert formats["read"] == sorted(formats["read"])
+    assert set(formats["embed"]) < set(formats["read"])
+    assert "image/jpeg" in formats["embed"] and "application/pdf" not in formats["embed"]
+    assert all(can_embed(format) for format in formats["embed"])
+    assert not any(can_embed(format) for format in set(formats["read"]) - set(formats["embed"]))
@@ -0,0 +1,66 @@
+"""
+Tests for reading assets given as a list of chunks.
+"""
+
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+URL = "https://example.com/manifests/firefly.c2pa"
+
+
+def split(data, size):
+    return [data[i:i + size] for i in range(0, len(data), size)]
+
+
+def same_result(chunked, whole):
+    keys = ("active_manifest", "manifests", "validation_state", "is_embedded", "remote_url")
+    return all(chunked[key] == whole[key] for key in keys)
+
+
+@pytest.mark.parametrize("size", [1, 4096, 65536])
+def test_chunks_match_bytes(size):
+    data = (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+    whole = read_c2pa_from_bytes(data, "image/jpeg")
+    assert same_result(read_c2pa_from_bytes(split(data, size), "image/jpeg"), whole)
+
+
+def test_tuple_and_empty_chunks():
+    data = (TEST_IMAGES_DIR / "multipage.tif").read_bytes()
+    whole = read_c2pa_from_bytes(data, "image/tiff")
+    chunks = (b"", data[:100], b"", data[100:], b"")
+    assert same_result(read_c2pa_from_bytes(chunks, "image/tiff"), whole)
+
+
+def test_remote_url_split_across_chunks():
+    data = (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+    xmp = ('<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">'
+           f'<rdf:Description rdf:about="" xmlns:dcterms="http://purl.org/dc/terms/" dcterms:provenance="{URL}"/>'
+           '</rdf:RDF></x:xmpmeta>').encode()
+    body = b"http://ns.adobe.com/xap/1.0/\x00" + xmp
+    app1 = b"\xff\xe1" + (len(body) + 2).to_bytes(2, "big") + body
+    data = data[:2] + app1 + data[2:]
+
+    assert read_c2pa_from_bytes(split(data, 5), "image/jpeg")["remote_url"] == URL
+
+
+def test_scanner_fallback():
+    """Stores c2pa-rs does not find are still recovered from the chunks."""
+    data = (TEST_IMAGES_DIR / "id3_stacked.mp3").read_bytes()
+    whole = read_c2pa_from_bytes(data, "audio/mpeg")
+    assert whole is not None
+    assert same_result(read_c2pa_from_bytes(split(data, 1), "audio/mpeg"), whole)
+
+
+def test_no_manifest():
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    assert read_c2pa_from_bytes(split(data, 1000), "image/png") is None
+    assert read_c2pa_from_bytes([], "image/png") is None
+
+
+def test_chunks_must_be_bytes():
+    with pytest.raises(TypeError):
+        read_c2pa_from_bytes([b"\xff\xd8", "not bytes"], "image/jpeg")
@@ -0,0 +1,101 @@
+"""
+Tests for the command line interface and reading from standard input.
+"""
+
+import io
+import json
+import os
+import subprocess
+import sys
+from pathlib import Path
+
+
+from fast_c2pa_python import read_c2pa_from_bytes, read_c2pa_from_file, strip_c2pa
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+CERT_DIR = Path(__file__).parent / "sign_cert"
+
+
+def cli(*args, stdin=b""):
+    """Run fast-c2pa in a child process, as in a shell pipeline"""
+    env = {**os.environ, "PYTHONPATH": os.pathsep.join(sys.path)}
+    return subprocess.run([sys.executable, "-m", "fast_c2pa_python", *args], input=stdin,
+                          capture_output=True, env=env, timeout=60)
+
+
+def test_read_from_stdin(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    run = cli("read", stdin=data)
+    assert run.returncode == 0
+    expected = read_c2pa_from_bytes(data, "image/jpeg")
+    assert json.loads(run.stdout)["active_manifest"] == expected["active_manifest"]
+
+    run = cli("read", "-", stdin=read_image("screenshot_noc2pa.png"))
+    assert (run.returncode, run.stdout) == (1, b"")
+
+
+def test_strip_pipeline(read_image):
+    data = read_image("chatgpt_image.png")
+    stripped = cli("strip", stdin=data)
+    assert stripped.returncode == 0
+    assert stripped.stdout == strip_c2pa(data, "image/png")
+    assert read_c2pa_from_bytes(stripped.stdout, "image/png") is None
+    assert cli("read", stdin=stripped.stdout).returncode == 1
+
+
+def test_extract_and_embed(read_image, tmp_path):
+    store = tmp_path / "store.c2pa"
+    assert cli("extract", str(TEST_IMAGES_DIR / "chatgpt_image.png"), "-o", str(store)).returncode == 0
+
+    embedded = cli("embed", "--jumbf", str(store), stdin=read_image("screenshot_noc2pa.png"))
+    assert embedded.returncode == 0
+    assert read_c2pa_from_bytes(embedded.stdout, "image/png")["active_manifest"]
+
+
+def test_sign_to_stdout(read_image, tmp_path):
+    manifest = tmp_path / "manifest.json"
+    manifest.write_text(json.dumps({"title": "piped.png", "assertions": []}))
+    run = cli("sign", "--manifest", str(manifest), "--cert", str(CERT_DIR / "es256.pub"),
+              "--key", str(CERT_DIR / "es256.priv"), stdin=read_image("screenshot_noc2pa.png"))
+    assert run.returncode == 0, run.stderr
+    result = read_c2pa_from_bytes(run.stdout, "image/png")
+    assert result["manifests"][result["active_manifest"]]["title"] == "piped.png"
+
+
+def test_sign_with_c2patool_manifest(read_image, tmp_path):
+    manifest = tmp_path / "manifest.json"
+    manifest.write_text(json.dumps({
+        "title": "piped.png",
+        "sign_cert": str(CERT_DIR / "es256.pub"),
+        "private_key": str(CERT_DIR / "es256.priv"),
+    }))
+    run = cli("sign", "--manifest", str(manifest), "--parent", str(TEST_IMAGES_DIR / "C.jpg"),
+              stdin=read_image("screenshot_noc2pa.png"))
+    assert run.returncode == 0, run.stderr
+    result = read_c2pa_from_bytes(run.stdout, "image/png")
+    (parent,) = result["manifests"][result["active_manifest"]]["ingredients"]
+    assert parent["relationship"] == "parentOf"
+    run = cli("sign", "--manifest", str(manifest), "--cert", str(CERT_DIR / "es256.pub"),
+              stdin=read_image("screenshot_noc2pa.png"))
+    assert run.returncode == 2
+    assert b"--key" in run.stderr
+
+
+def test_errors():
+    run = cli("strip", stdin=b"not an asset")
+    assert run.returncode == 2
+    assert b"--mime-type" in run.stderr
+    run = cli("strip", "--mime-type", "application/x-unknown", stdin=b"not an asset")
+    assert run.returncode == 2
+    assert b"Unsupported format" in run.stderr
+
+
+def test_read_c2pa_from_file_stdin(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    stdin = sys.stdin
+    sys.stdin = io.TextIOWrapper(io.BytesIO(data))
+    try:
+        result = read_c2pa_from_file("-")
+    finally:
+        sys.stdin = stdin
+    assert result == read_c2pa_from_bytes(data, "image/jpeg")
@@ -0,0 +1,51 @@
+"""
+Tests for the enums of the standard strings of C2PA manifests.
+"""
+
+from pathlib import Path
+
+from fast_c2pa_python import (
+    SUCCESS_CODES,
+    Action,
+    AssertionLabel,
+    Relationship,
+    ValidationCode,
+    ValidationState,
+    get_c2pa_constants,
+    make_signed_test_asset,
+    read_c2pa_from_bytes,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+def test_members_are_strings():
+    assert AssertionLabel.DATA_HASH == "c2pa.hash.data"
+    assert AssertionLabel.BMFF_HASH == "c2pa.hash.bmff"
+    assert Action.CROPPED == "c2pa.cropped"
+    assert Action.EDITED_METADATA == "c2pa.edited.metadata"
+    assert Relationship.PARENT_OF == "parentOf"
+    assert ValidationState.TRUSTED == "Trusted"
+    assert ValidationCode("signingCredential.untrusted") is ValidationCode.SIGNING_CREDENTIAL_UNTRUSTED
+    assert f"{ValidationCode.ASSERTION_DATAHASH_MISMATCH}" == "assertion.dataHash.mismatch"
+
+    assert ValidationCode.CLAIM_SIGNATURE_VALIDATED in SUCCESS_CODES
+    assert ValidationCode.SIGNING_CREDENTIAL_UNTRUSTED not in SUCCESS_CODES
+    assert set(get_c2pa_constants()["relationships"].values()) == {"parentOf", "componentOf", "inputTo"}
+
+
+def test_results_use_the_enums():
+    parent = make_signed_test_asset("png")
+    result = read_c2pa_from_bytes(make_signed_test_asset("jpeg", {"parent": parent}), "image/jpeg")
+    assert ValidationState(result["validation_state"])
+    manifest = result["manifests"][result["active_manifest"]]
+    assert manifest["assertions"][0]["label"] == AssertionLabel.ACTIONS
+    assert manifest["assertions"][0]["data"]["actions"][0]["action"] == Action.OPENED
+    assert manifest["ingredients"][0]["relationship"] == Relationship.PARENT_OF
+
+    data = (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+    results = read_c2pa_from_bytes(data, "image/jpeg")["validation_results"]["activeManifest"]
+    for status in results["success"]:
+        assert ValidationCode(status["code"]) in SUCCESS_CODES
+    for status in results["failure"]:
+        assert ValidationCode(status["code"]) not in SUCCESS_CODES
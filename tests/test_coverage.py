@@ -0,0 +1,57 @@
+"""
+Tests for the byte ranges covered by data hash bindings.
+"""
+
+from fast_c2pa_python import get_hash_coverage, read_c2pa_from_bytes
+
+
+def test_jpeg_exclusion(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    coverage = get_hash_coverage(data, "image/jpeg")
+
+    assert coverage["active_manifest"] == read_c2pa_from_bytes(data, "image/jpeg")["active_manifest"]
+    assert coverage["size"] == len(data)
+    (binding,) = coverage["bindings"]
+    assert binding["label"] == "c2pa.hash.data"
+    assert binding["alg"] == "sha256"
+
+    # The manifest store sits in the APP11 segments after the JFIF header
+    (exclusion,) = binding["exclusions"]
+    assert data[exclusion["start"]:exclusion["start"] + 2] == b"\xff\xeb"
+    assert binding["covered"] == [
+        {"start": 0, "length": exclusion["start"]},
+        {"start": exclusion["start"] + exclusion["length"], "length": len(data) - exclusion["start"] - exclusion["length"]},
+    ]
+    assert binding["covered_bytes"] == len(data) - exclusion["length"]
+
+
+def test_ranges_partition_the_file(read_image):
+    data = read_image("multipage.tif")
+    (binding,) = get_hash_coverage(data, "image/tiff")["bindings"]
+
+    ranges = sorted(binding["exclusions"] + binding["covered"], key=lambda r: r["start"])
+    pos = 0
+    for r in ranges:
+        assert r["start"] == pos
+        pos += r["length"]
+    assert pos == len(data)
+
+
+def test_trailing_bytes_are_covered(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    (signed,) = get_hash_coverage(data, "image/jpeg")["bindings"]
+    (appended,) = get_hash_coverage(data + b"trailing", "image/jpeg")["bindings"]
+
+    assert appended["exclusions"] == signed["exclusions"]
+    assert appended["covered"][-1]["start"] + appended["covered"][-1]["length"] == len(data) + 8
+    assert appended["covered_bytes"] == signed["covered_bytes"] + 8
+
+
+def test_no_data_hash(read_image):
+    coverage = get_hash_coverage(read_image("sequence.heic"), "image/heic")
+    assert coverage["active_manifest"]
+    assert coverage["bindings"] == []
+
+
+def test_no_manifest(read_image):
+    assert get_hash_coverage(read_image("screenshot_noc2pa.png"), "image/png") is None
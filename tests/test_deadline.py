@@ -0,0 +1,127 @@
+"""
+Tests for processing time budgets (deadline_ms).
+"""
+
+import time
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    TimeBudgetExceeded,
+    get_abandoned_parses,
+    read_c2pa_from_bytes,
+    read_c2pa_from_file,
+    register_assertion_decoder,
+    set_max_abandoned_parses,
+    set_remote_manifest_fetcher,
+    verify_manifest_bytes,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+def test_within_budget(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    expected = read_c2pa_from_bytes(data, "image/jpeg")
+    assert read_c2pa_from_bytes(data, "image/jpeg", deadline_ms=60_000) == expected
+    assert read_c2pa_from_bytes([data[:1000], data[1000:]], "image/jpeg", deadline_ms=60_000) == expected
+
+
+def test_budget_exceeded_while_parsing(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    with pytest.raises(TimeBudgetExceeded) as excinfo:
+        read_c2pa_from_bytes(data, "image/jpeg", deadline_ms=0)
+    assert isinstance(excinfo.value, TimeoutError)
+    assert excinfo.value.partial is None
+    # The abandoned parse does not affect later reads
+    assert read_c2pa_from_bytes(data, "image/jpeg")["active_manifest"]
+
+
+def wait_for_abandoned_parses():
+    deadline = time.monotonic() + 60
+    while get_abandoned_parses()["running"] and time.monotonic() < deadline:
+        time.sleep(0.05)
+    return get_abandoned_parses()["running"]
+
+
+def test_shared_inputs(read_image):
+    """Buffers and memory-mapped files are handed to the bounded parse."""
+    path = TEST_IMAGES_DIR / "adobe_firefly_image.jpg"
+    data = read_image("adobe_firefly_image.jpg")
+    expected = read_c2pa_from_bytes(data, "image/jpeg")
+    assert read_c2pa_from_bytes(memoryview(bytearray(data)), "image/jpeg", deadline_ms=60_000) == expected
+    assert read_c2pa_from_file(str(path), "image/jpeg", deadline_ms=60_000) == expected
+
+    with pytest.raises(TimeBudgetExceeded):
+        read_c2pa_from_bytes(bytearray(data), "image/jpeg", deadline_ms=0)
+    assert wait_for_abandoned_parses() == 0
+
+
+def test_abandoned_parses_limit(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    assert get_abandoned_parses()["limit"] == 16
+    set_max_abandoned_parses(0)
+    try:
+        with pytest.raises(TimeBudgetExceeded, match="still running") as excinfo:
+            read_c2pa_from_bytes(data, "image/jpeg", deadline_ms=60_000)
+        assert excinfo.value.partial is None
+        # Reads without a budget are not limited
+        assert read_c2pa_from_bytes(data, "image/jpeg")["active_manifest"]
+    finally:
+        set_max_abandoned_parses(16)
+    assert read_c2pa_from_bytes(data, "image/jpeg", deadline_ms=60_000)["active_manifest"]
+
+
+@pytest.fixture
+def slow_decoder():
+    calls = []
+
+    def decode(data):
+        # Once is enough to run out of the budget
+        if not calls:
+            time.sleep(1.2)
+        calls.append(data)
+        return {"count": len(data["actions"])}
+
+    register_assertion_decoder("c2pa.actions", decode)
+    yield
+    register_assertion_decoder("c2pa.actions", None)
+
+
+def test_partial_result(read_image, slow_decoder):
+    data = read_image("adobe_firefly_image.jpg")
+    with pytest.raises(TimeBudgetExceeded) as excinfo:
+        read_c2pa_from_bytes(data, "image/jpeg", deadline_ms=1000)
+    partial = excinfo.value.partial
+    assert partial["active_manifest"]
+    assert partial["warnings"][-1]["code"] == "deadline.exceeded"
+    assert "resource resolver" in str(excinfo.value)
+
+
+def test_remote_timeout_within_budget(read_image, split_manifest):
+    manifest, stripped = split_manifest(read_image("chatgpt_image.png"))
+    timeouts = []
+
+    def fetcher(url, timeout_ms):
+        timeouts.append(timeout_ms)
+        return manifest
+
+    set_remote_manifest_fetcher(fetcher)
+    try:
+        result = read_c2pa_from_bytes(stripped, "image/png", fetch_remote_manifests=True,
+                                      remote_manifest_url="https://example.com/m.c2pa",
+                                      remote_timeout_ms=600_000, deadline_ms=10_000)
+    finally:
+        set_remote_manifest_fetcher(None)
+    assert result["is_embedded"] is False
+    assert 0 < timeouts[0] <= 10_000
+
+
+def test_verify_manifest_bytes(read_image, split_manifest):
+    data = read_image("chatgpt_image.png")
+    manifest, stripped = split_manifest(data)
+    expected = verify_manifest_bytes(manifest, stripped, "image/png")
+    assert verify_manifest_bytes(manifest, stripped, "image/png", deadline_ms=60_000) == expected
+    with pytest.raises(TimeBudgetExceeded):
+        verify_manifest_bytes(manifest, stripped, "image/png", deadline_ms=0)
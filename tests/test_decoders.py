@@ -0,0 +1,71 @@
+"""
+Tests for custom assertion decoders.
+"""
+
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    Reader,
+    read_c2pa_from_bytes,
+    read_c2pa_json_from_bytes,
+    register_assertion_decoder,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+@pytest.fixture
+def asset():
+    yield (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+    register_assertion_decoder("c2pa.actions", None)
+
+
+def actions_assertions(result):
+    return [
+        assertion
+        for manifest in result["manifests"].values()
+        for assertion in manifest["assertions"]
+        if assertion["label"] == "c2pa.actions"
+    ]
+
+
+def test_decoded_result_added(asset):
+    calls = []
+
+    def decode(data):
+        calls.append(data)
+        return {"count": len(data["actions"])}
+
+    register_assertion_decoder("c2pa.actions", decode)
+    result = read_c2pa_from_bytes(asset, "image/jpeg")
+    assertions = actions_assertions(result)
+    assert assertions and len(calls) == len(assertions)
+    for assertion in assertions:
+        assert assertion["decoded"] == {"count": len(assertion["data"]["actions"])}
+
+    assert '"decoded"' in read_c2pa_json_from_bytes(asset, "image/jpeg")
+    reader = Reader.from_bytes(asset, "image/jpeg")
+    assert actions_assertions(reader.to_dict())[0]["decoded"]
+
+
+def test_none_and_unregistered(asset):
+    register_assertion_decoder("c2pa.actions", lambda data: None)
+    assert all("decoded" not in a for a in actions_assertions(read_c2pa_from_bytes(asset, "image/jpeg")))
+
+    register_assertion_decoder("c2pa.actions", lambda data: 1)
+    register_assertion_decoder("c2pa.actions", None)
+    assert all("decoded" not in a for a in actions_assertions(read_c2pa_from_bytes(asset, "image/jpeg")))
+
+
+def test_decoder_errors(asset):
+    def fail(data):
+        raise ValueError("cannot decode")
+
+    register_assertion_decoder("c2pa.actions", fail)
+    with pytest.raises(ValueError):
+        read_c2pa_from_bytes(asset, "image/jpeg")
+
+    with pytest.raises(TypeError):
+        register_assertion_decoder("c2pa.actions", "not callable")
@@ -0,0 +1,55 @@
+"""
+Tests for the scalar digest of a manifest store.
+"""
+
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_digest, read_c2pa_from_bytes
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+ALL_FIELDS = ["signer", "generator", "ai_flag", "validation_state", "active_manifest", "signed_at", "has_c2pa"]
+
+
+def test_default_fields():
+    data = (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+    digest = read_c2pa_digest(data, "image/jpeg")
+    assert digest == {
+        "signer": "Adobe Inc.",
+        "generator": "Adobe_Firefly adobe_c2pa/0.12.4 c2pa-rs/0.32.7",
+        "ai_flag": True,
+    }
+
+
+def test_matches_full_result():
+    data = (TEST_IMAGES_DIR / "multipage.tif").read_bytes()
+    result = read_c2pa_from_bytes(data, "image/tiff")
+    active = result["manifests"][result["active_manifest"]]
+    digest = read_c2pa_digest(data, "image/tiff", fields=ALL_FIELDS)
+    assert list(digest) == ALL_FIELDS
+    assert digest["signer"] == active["signature_info"]["issuer"]
+    assert digest["generator"] == active["claim_generator"]
+    assert digest["active_manifest"] == result["active_manifest"]
+    assert digest["validation_state"] == result["validation_state"]
+    assert digest["signed_at"] == active["signature_info"].get("time")
+    assert digest["ai_flag"] is False and digest["has_c2pa"] is True
+
+
+def test_ai_flag_from_ingredient():
+    # The active manifest opens an ingredient created by a generative model
+    data = (TEST_IMAGES_DIR / "chatgpt_image.png").read_bytes()
+    digest = read_c2pa_digest(data, "image/png", fields=["generator", "ai_flag"])
+    assert digest == {"generator": "ChatGPT", "ai_flag": True}
+
+
+def test_without_c2pa():
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    digest = read_c2pa_digest(data, "image/png", fields=ALL_FIELDS)
+    assert digest.pop("has_c2pa") is False
+    assert set(digest.values()) == {None}
+
+
+def test_unknown_field():
+    with pytest.raises(ValueError):
+        read_c2pa_digest(b"", "image/jpeg", fields=["signer", "color"])
@@ -0,0 +1,80 @@
+"""
+Tests for reading the media attachments of raw MIME email messages.
+"""
+
+from email.message import EmailMessage
+from email.mime.application import MIMEApplication
+from email.mime.image import MIMEImage
+from email.mime.multipart import MIMEMultipart
+from email.mime.text import MIMEText
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, read_c2pa_from_email
+
+
+@pytest.fixture
+def reported_message(read_image):
+    forwarded = MIMEMultipart()
+    forwarded["Subject"] = "Original message"
+    forwarded.attach(MIMEText("See attached."))
+    forwarded.attach(MIMEImage(read_image("C.jpg"), "jpeg", name="C.jpg"))
+
+    message = MIMEMultipart()
+    message["Subject"] = "Abuse report"
+    message.attach(MIMEText("This image is fake.\n"))
+    firefly = MIMEImage(read_image("adobe_firefly_image.jpg"), "jpeg")
+    firefly.add_header("Content-Disposition", "attachment", filename=("utf-8", "", "café.jpg"))
+    message.attach(firefly)
+    # Mislabeled attachments are read as what their bytes are
+    message.attach(MIMEApplication(read_image("chatgpt_image.png"), Name="=?utf-8?B?aW1hZ2UuYmlu?="))
+    message.attach(MIMEImage(read_image("screenshot_noc2pa.png"), "png"))
+    forwarded_part = EmailMessage()
+    forwarded_part.set_content(forwarded)
+    message.attach(forwarded_part)
+    return message.as_bytes()
+
+
+def test_attachments(read_image, reported_message):
+    entries = read_c2pa_from_email(reported_message)
+
+    assert [entry["part"] for entry in entries] == ["2", "3", "4", "5.2"]
+    assert [entry["filename"] for entry in entries] == ["café.jpg", "image.bin", None, "C.jpg"]
+    assert [entry["mime_type"] for entry in entries] == [
+        "image/jpeg", "image/png", "image/png", "image/jpeg",
+    ]
+    assert entries[1]["content_type"] == "application/octet-stream"
+    assert entries[0]["size"] == len(read_image("adobe_firefly_image.jpg"))
+    assert entries[0]["result"] == read_c2pa_from_bytes(read_image("adobe_firefly_image.jpg"), "image/jpeg")
+    assert entries[2]["result"] is None
+    assert all(entry["error"] is None for entry in entries)
+    assert entries[3]["result"]["active_manifest"] is not None
+
+
+def test_quoted_printable_and_single_part(read_image):
+    message = MIMEImage(read_image("chatgpt_image.png"), "png")
+    entries = read_c2pa_from_email(bytearray(message.as_bytes()), allow_threads=True)
+    assert [(entry["part"], entry["mime_type"]) for entry in entries] == [("1", "image/png")]
+    assert entries[0]["result"] is not None
+
+    text = MIMEText("café =3D fine", "plain", "utf-8")
+    text.replace_header("Content-Transfer-Encoding", "quoted-printable")
+    assert read_c2pa_from_email(text.as_bytes()) == []
+
+
+def test_broken_attachment_reported(read_image):
+    message = MIMEMultipart()
+    data = read_image("adobe_firefly_image.jpg")
+    message.attach(MIMEImage(data[: len(data) // 3] + data[-1000:], "jpeg", name="cut.jpg"))
+    (entry,) = read_c2pa_from_email(message.as_bytes())
+    assert entry["filename"] == "cut.jpg"
+    assert (entry["result"] is None) != (entry["error"] is None)
+
+
+def test_unterminated_multipart(read_image):
+    message = MIMEMultipart(boundary="XYZ")
+    message.attach(MIMEImage(read_image("C.jpg"), "jpeg"))
+    raw = message.as_bytes()
+    cut = raw[: raw.rindex(b"--XYZ--")]
+    (entry,) = read_c2pa_from_email(cut)
+    assert entry["result"]["active_manifest"] is not None
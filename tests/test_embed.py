@@ -0,0 +1,132 @@
+"""
+Tests for extracting and embedding raw JUMBF manifest stores.
+"""
+
+import pytest
+
+from fast_c2pa_python import (
+    embed_jumbf,
+    extract_jumbf,
+    extract_manifest_store,
+    read_c2pa_from_bytes,
+    read_xmp,
+    read_xmp_provenance,
+    relocate_manifest,
+    remove_c2pa,
+    set_remote_reference,
+    verify_manifest_bytes,
+)
+
+
+def test_extract(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    jumbf = extract_jumbf(data, "image/jpeg")
+    assert jumbf == extract_manifest_store(data, "image/jpeg")
+    assert jumbf[4:8] == b"jumb"
+
+    assert extract_jumbf(read_image("screenshot_noc2pa.png"), "image/png") is None
+
+
+def test_embed_restores_stripped_asset(read_image, split_manifest):
+    data = read_image("chatgpt_image.png")
+    jumbf = extract_jumbf(data, "image/png")
+    _, stripped = split_manifest(data)
+    assert read_c2pa_from_bytes(stripped, "image/png") is None
+
+    restored = embed_jumbf(stripped, "image/png", jumbf)
+    assert extract_jumbf(restored, "image/png") == jumbf
+    expected = read_c2pa_from_bytes(data, "image/png")
+    assert read_c2pa_from_bytes(restored, "image/png")["active_manifest"] == expected["active_manifest"]
+
+
+def test_embed_replaces_store(read_image):
+    firefly = extract_jumbf(read_image("adobe_firefly_image.jpg"), "image/jpeg")
+    other = read_image("C.jpg")
+    replaced = embed_jumbf(other, "image/jpeg", firefly)
+    assert extract_jumbf(replaced, "image/jpeg") == firefly
+
+
+def test_errors(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    jumbf = extract_jumbf(data, "image/jpeg")
+    with pytest.raises(ValueError):
+        embed_jumbf(data, "image/jpeg", b"not a manifest store")
+    with pytest.raises(ValueError):
+        embed_jumbf(data, "application/x-unknown", jumbf)
+    with pytest.raises(ValueError):
+        extract_jumbf(data, "application/x-unknown")
+    with pytest.raises(RuntimeError):
+        extract_jumbf(b"\x89PNG\r\n\x1a\nnot a png", "image/png")
+
+
+@pytest.mark.parametrize("name,mime_type", [
+    ("adobe_firefly_image.jpg", "image/jpeg"),
+    ("chatgpt_image.png", "image/png"),
+])
+def test_remove_c2pa(read_image, name, mime_type):
+    url = "https://example.com/manifests/abc.c2pa"
+    data = set_remote_reference(read_image(name), mime_type, url)
+
+    removed = remove_c2pa(data, mime_type)
+    assert read_c2pa_from_bytes(removed, mime_type) is None
+    assert read_xmp_provenance(removed)["provenance"] is None
+    assert read_xmp(removed, mime_type) is not None
+
+    kept = remove_c2pa(data, mime_type, remove_remote_reference=False)
+    assert read_xmp_provenance(kept)["provenance"] == url
+
+
+def test_remove_c2pa_without_provenance(read_image):
+    data = read_image("screenshot_noc2pa.png")
+    assert remove_c2pa(data, "image/png") == data
+    with pytest.raises(ValueError):
+        remove_c2pa(data, "text/plain")
+
+
+def test_relocate_bmff_in_place(read_image, untrusted_valid):
+    data = read_image("sequence.heic")
+    moved = relocate_manifest(data, "image/heic", "sidecar")
+    assert len(moved["asset"]) == len(data)
+    assert read_c2pa_from_bytes(moved["asset"], "image/heic") is None
+    assert moved["manifest_store"] == extract_jumbf(data, "image/heic")
+    assert moved["remote_url"] is None
+    sidecar = verify_manifest_bytes(moved["manifest_store"], moved["asset"], "image/heic")
+    assert sidecar["validation_state"] == "Valid"
+
+    restored = relocate_manifest(
+        moved["asset"], "image/heic", "embedded", manifest_store=moved["manifest_store"]
+    )
+    assert restored["asset"] == data
+
+    # The pointer would go in an XMP box the BMFF hash covers
+    with pytest.raises(ValueError):
+        relocate_manifest(data, "image/heic", "remote", url="https://example.com/a.c2pa")
+
+
+def test_relocate_data_hash(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    with pytest.raises(ValueError, match="data hash"):
+        relocate_manifest(data, "image/jpeg", "sidecar")
+    assert relocate_manifest(data, "image/jpeg", "embedded")["asset"] == data
+
+
+def test_relocate_between_sidecar_and_remote(read_image):
+    url = "https://example.com/manifests/abc.c2pa"
+    data = read_image("adobe_firefly_image.jpg")
+    store = extract_jumbf(data, "image/jpeg")
+    asset = set_remote_reference(remove_c2pa(data, "image/jpeg"), "image/jpeg", url)
+
+    remote = relocate_manifest(asset, "image/jpeg", "remote", url=url, manifest_store=store)
+    assert remote == {"asset": asset, "manifest_store": store, "remote_url": url}
+    sidecar = relocate_manifest(asset, "image/jpeg", "sidecar", manifest_store=store)
+    assert sidecar == remote
+
+
+def test_relocate_errors(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    with pytest.raises(ValueError):
+        relocate_manifest(data, "image/jpeg", "elsewhere")
+    with pytest.raises(ValueError):
+        relocate_manifest(data, "image/jpeg", "remote")
+    with pytest.raises(ValueError):
+        relocate_manifest(read_image("screenshot_noc2pa.png"), "image/png", "embedded")
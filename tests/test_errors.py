@@ -0,0 +1,62 @@
+"""
+Tests for the exception hierarchy raised for c2pa-rs failures.
+"""
+
+import json
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    C2paError,
+    IoError,
+    ManifestNotFoundError,
+    SigningError,
+    UnsupportedFormatError,
+    ValidationError,
+    extract_jumbf,
+    sign_and_embed,
+    verify_manifest_bytes,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+CERT_DIR = Path(__file__).parent / "sign_cert"
+
+SIGNER = {
+    "alg": "es256",
+    "sign_cert": (CERT_DIR / "es256.pub").read_bytes(),
+    "private_key": (CERT_DIR / "es256.priv").read_text(),
+}
+
+MANIFEST = json.dumps({"title": "signed.png", "assertions": []})
+
+
+@pytest.mark.parametrize("error", [
+    UnsupportedFormatError, ManifestNotFoundError, ValidationError, SigningError, IoError,
+])
+def test_hierarchy(error):
+    assert issubclass(error, C2paError)
+    assert issubclass(error, RuntimeError)
+    assert error.__module__ == "fast_c2pa_core"
+
+
+def test_unsupported_format():
+    with pytest.raises(UnsupportedFormatError, match="signing"):
+        sign_and_embed(b"plain text", "text/plain", MANIFEST, SIGNER)
+
+
+def test_signing_error():
+    with pytest.raises(SigningError, match="could not be parsed"):
+        sign_and_embed(b"\x89PNG\r\n\x1a\nbroken", "image/png", MANIFEST, SIGNER)
+
+
+def test_validation_error():
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    with pytest.raises(ValidationError, match="JUMBF"):
+        verify_manifest_bytes(b"not a manifest store", data, "image/png")
+
+
+def test_base_class_for_other_failures():
+    with pytest.raises(C2paError) as info:
+        extract_jumbf(b"\x89PNG\r\n\x1a\nnot a png", "image/png")
+    assert type(info.value) is C2paError
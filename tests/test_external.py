@@ -0,0 +1,66 @@
+"""
+Tests for resolving data referenced from outside the asset.
+
+remote_ref.jpg has a "com.example.cloud-data" assertion referencing
+https://data.example.com/ingredient.bin by the SHA-256 hash of PAYLOAD.
+"""
+
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, set_resource_resolver
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+URL = "https://data.example.com/ingredient.bin"
+PAYLOAD = b"remote ingredient data"
+
+
+@pytest.fixture
+def asset():
+    yield (TEST_IMAGES_DIR / "remote_ref.jpg").read_bytes()
+    set_resource_resolver(None)
+
+
+def location(result):
+    manifest = result["manifests"][result["active_manifest"]]
+    (assertion,) = [a for a in manifest["assertions"] if a["label"] == "com.example.cloud-data"]
+    return assertion["data"]["location"]
+
+
+def test_matching_data(asset):
+    urls = []
+
+    def resolve(url):
+        urls.append(url)
+        return PAYLOAD
+
+    set_resource_resolver(resolve)
+    result = read_c2pa_from_bytes(asset, "image/jpeg")
+    assert urls == [URL]
+    assert location(result)["resolved"] == {"size": len(PAYLOAD), "code": "assertion.hashedURI.match"}
+    assert "warnings" not in result
+
+
+def test_mismatching_data(asset):
+    set_resource_resolver(lambda url: b"tampered")
+    result = read_c2pa_from_bytes(asset, "image/jpeg")
+    assert location(result)["resolved"]["code"] == "assertion.hashedURI.mismatch"
+    (warning,) = result["warnings"]
+    assert warning["code"] == "assertion.hashedURI.mismatch"
+    assert warning["url"] == URL
+
+
+def test_unresolved(asset):
+    assert "resolved" not in location(read_c2pa_from_bytes(asset, "image/jpeg"))
+    set_resource_resolver(lambda url: None)
+    assert "resolved" not in location(read_c2pa_from_bytes(asset, "image/jpeg"))
+
+
+def test_resolver_errors(asset):
+    set_resource_resolver(lambda url: 42)
+    with pytest.raises(TypeError):
+        read_c2pa_from_bytes(asset, "image/jpeg")
+
+    with pytest.raises(TypeError):
+        set_resource_resolver("not callable")
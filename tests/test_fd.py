@@ -0,0 +1,91 @@
+"""
+Tests for reading assets from open file descriptors and file objects.
+"""
+
+import io
+import os
+import socket
+import threading
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, read_c2pa_from_file, read_c2pa_many
+
+TEST_IMAGE = Path(__file__).parent / "test_images" / "multipage.tif"
+DATA = TEST_IMAGE.read_bytes()
+
+
+@pytest.fixture(scope="module")
+def expected():
+    return read_c2pa_from_bytes(DATA, "image/tiff")
+
+
+def test_raw_descriptor(expected):
+    fd = os.open(TEST_IMAGE, os.O_RDONLY)
+    try:
+        os.lseek(fd, 100, os.SEEK_SET)
+        assert read_c2pa_from_file(fd, "image/tiff") == expected
+        # Read from the start, offset left alone, descriptor still open
+        assert os.lseek(fd, 0, os.SEEK_CUR) == 100
+    finally:
+        os.close(fd)
+
+
+def test_file_object(expected):
+    with open(TEST_IMAGE, "rb") as f:
+        f.read(10)
+        assert read_c2pa_from_file(f, "image/tiff") == expected
+        assert f.tell() == 10
+
+
+def test_in_memory_stream(expected):
+    # io.BytesIO has fileno() but no descriptor; it is read as bytes instead
+    stream = io.BytesIO(DATA)
+    stream.seek(10)
+    assert read_c2pa_from_file(stream) == expected
+    assert stream.tell() == 10
+
+
+def test_pipe(expected):
+    read_end, write_end = os.pipe()
+
+    def write():
+        with os.fdopen(write_end, "wb") as w:
+            w.write(DATA)
+
+    writer = threading.Thread(target=write)
+    writer.start()
+    try:
+        assert read_c2pa_from_file(read_end, "image/tiff") == expected
+    finally:
+        writer.join()
+        os.close(read_end)
+
+
+def test_socket(expected):
+    receiver, sender = socket.socketpair()
+    with receiver, sender:
+        writer = threading.Thread(target=lambda: (sender.sendall(DATA), sender.shutdown(socket.SHUT_WR)))
+        writer.start()
+        assert read_c2pa_from_file(receiver, "image/tiff") == expected
+        writer.join()
+
+
+def test_mime_type_required():
+    with open(TEST_IMAGE, "rb") as f:
+        with pytest.raises(ValueError):
+            read_c2pa_from_file(f.fileno())
+
+
+def test_many(expected):
+    with open(TEST_IMAGE, "rb") as f:
+        ((item_id, result, error),) = read_c2pa_many([("fd", f.fileno(), "image/tiff")])
+    assert (item_id, error) == ("fd", None)
+    assert result["manifests"] == expected["manifests"]
+
+
+def test_many_in_memory_stream(expected):
+    ((item_id, result, error),) = read_c2pa_many([("stream", io.BytesIO(DATA))])
+    assert (item_id, error) == ("stream", None)
+    assert result["manifests"] == expected["manifests"]
@@ -0,0 +1,57 @@
+"""
+Tests for validating fragmented MP4 segment by segment against the init segment.
+"""
+
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, read_c2pa_fragmented
+
+FRAGMENTED_DIR = Path(__file__).parent / "test_images" / "fragmented"
+
+
+def load(name):
+    return (FRAGMENTED_DIR / name).read_bytes()
+
+
+@pytest.mark.parametrize("allow_threads", [False, True])
+def test_every_fragment_valid(allow_threads):
+    init = load("init.mp4")
+    fragments = [load("segment1.m4s"), memoryview(load("segment2.m4s"))]
+    result = read_c2pa_fragmented(init, fragments, allow_threads=allow_threads)
+
+    # The state depends on whether trust settings loaded by other tests trust
+    # the test certificate, but is the same for both fragments
+    assert [fragment["index"] for fragment in result["fragments"]] == [0, 1]
+    for fragment in result["fragments"]:
+        assert fragment["validation_state"] == result["validation_state"]
+        codes = [s["code"] for s in fragment["validation_results"]["activeManifest"]["success"]]
+        assert "assertion.bmffHash.match" in codes
+        failures = [s["code"] for s in fragment["validation_status"] or []]
+        assert "assertion.bmffHash.mismatch" not in failures
+    assert "validation_status" not in result
+    assert result["manifests"][result["active_manifest"]]["title"] == "init.mp4"
+
+    # Without its fragments the init segment does not validate
+    assert read_c2pa_from_bytes(init, "video/mp4")["validation_state"] == "Invalid"
+
+
+def test_tampered_fragment():
+    tampered = bytearray(load("segment2.m4s"))
+    tampered[-1] ^= 0xFF
+    result = read_c2pa_fragmented(load("init.mp4"), [load("segment1.m4s"), tampered])
+
+    assert result["validation_state"] == "Invalid"
+    first, second = result["fragments"]
+    assert "assertion.bmffHash.mismatch" not in [s["code"] for s in first["validation_status"] or []]
+    assert "assertion.bmffHash.mismatch" in [s["code"] for s in second["validation_status"]]
+
+
+def test_no_manifest_and_no_fragments():
+    unsigned = (Path(__file__).parent / "test_images" / "screenshot_noc2pa.png").read_bytes()
+    assert read_c2pa_fragmented(unsigned, [load("segment1.m4s")], "image/png") is None
+    with pytest.raises(ValueError):
+        read_c2pa_fragmented(load("init.mp4"), [])
+    with pytest.raises(TypeError, match="bytes-like"):
+        read_c2pa_fragmented(load("init.mp4"), ["segment1.m4s"])
@@ -0,0 +1,73 @@
+"""
+Tests for the automatic release of the GIL.
+
+With a long switch interval, a waiting thread only runs while the main thread is
+inside a read if the read releases the GIL.
+"""
+
+import sys
+import threading
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    get_gil_release_threshold,
+    read_c2pa_from_bytes,
+    set_gil_release_threshold,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+LARGE = (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+SMALL = (TEST_IMAGES_DIR / "multipage.tif").read_bytes()
+
+
+@pytest.fixture
+def threshold():
+    if not getattr(sys, "_is_gil_enabled", lambda: True)():
+        pytest.skip("free-threaded Python has no GIL to release")
+    default = get_gil_release_threshold()
+    interval = sys.getswitchinterval()
+    sys.setswitchinterval(100)
+    yield default
+    sys.setswitchinterval(interval)
+    set_gil_release_threshold(default)
+
+
+def released(data, mime_type, **kwargs):
+    """Whether reading `data` let another thread run"""
+    lock = threading.Lock()
+    lock.acquire()
+    ran = []
+    waiter = threading.Thread(target=lambda: lock.acquire() and ran.append(True))
+    waiter.start()
+    lock.release()
+    read_c2pa_from_bytes(data, mime_type, **kwargs)
+    during = bool(ran)
+    waiter.join()
+    return during
+
+
+def test_default_threshold(threshold):
+    assert len(SMALL) < threshold <= len(LARGE)
+    assert not released(SMALL, "image/tiff")
+    assert released(LARGE, "image/jpeg")
+
+
+def test_explicit_choice_wins(threshold):
+    assert not released(LARGE, "image/jpeg", allow_threads=False)
+    assert released(LARGE, "image/jpeg", allow_threads=True)
+
+
+def test_configured_threshold(threshold):
+    set_gil_release_threshold(0)
+    assert get_gil_release_threshold() == 0
+    assert released(SMALL, "image/tiff")
+
+    set_gil_release_threshold(len(LARGE) + 1)
+    assert not released(LARGE, "image/jpeg")
+
+
+def test_invalid_threshold():
+    with pytest.raises(OverflowError):
+        set_gil_release_threshold(-1)
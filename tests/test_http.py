@@ -0,0 +1,209 @@
+"""
+Tests for the shared HTTP client configuration, against a local server.
+"""
+
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from fast_c2pa_python import configure_http, fetch_url, get_http_config
+
+
+class Handler(BaseHTTPRequestHandler):
+    requests = []
+
+    def do_GET(self):
+        Handler.requests.append((self.path, self.headers.get("User-Agent")))
+        if self.path.endswith("/redirect"):
+            self.send_response(302)
+            self.send_header("Location", "/data")
+            self.end_headers()
+        elif self.path.endswith("/loop"):
+            self.send_response(302)
+            self.send_header("Location", "/loop")
+            self.end_headers()
+        elif self.path.endswith("/offsite"):
+            self.send_response(302)
+            self.send_header("Location", "http://elsewhere.invalid/data")
+            self.end_headers()
+        elif self.path.endswith("/flaky") and len(Handler.requests) < 3:
+            self.send_response(503)
+            self.end_headers()
+        elif self.path.endswith("/unavailable"):
+            self.send_response(503)
+            self.end_headers()
+        elif self.path.endswith(("/data", "/flaky")):
+            self.send_response(200)
+            self.end_headers()
+            self.wfile.write(b"payload")
+        else:
+            self.send_response(404)
+            self.end_headers()
+
+    def log_message(self, *args):
+        pass
+
+
+@pytest.fixture
+def server():
+    httpd = HTTPServer(("127.0.0.1", 0), Handler)
+    thread = threading.Thread(target=httpd.serve_forever, daemon=True)
+    thread.start()
+    Handler.requests = []
+    configure_http(block_private_ips=False)
+    yield f"http://127.0.0.1:{httpd.server_address[1]}"
+    httpd.shutdown()
+    configure_http()
+
+
+def test_defaults():
+    configure_http()
+    config = get_http_config()
+    assert config["proxy"] is None
+    assert config["max_redirects"] == 5
+    assert config["allowed_hosts"] is None
+    assert config["user_agent"].startswith("fast-c2pa-python/")
+    assert config["max_attempts"] == 1
+    assert config["retry_on"] == [429, 500, 502, 503, 504]
+    assert config["allowed_schemes"] == ["http", "https"]
+    assert config["block_private_ips"] is True
+
+
+def test_fetch_with_user_agent(server):
+    configure_http(user_agent="corp-scanner/1.0", block_private_ips=False)
+    assert fetch_url(f"{server}/data") == b"payload"
+    assert Handler.requests == [("/data", "corp-scanner/1.0")]
+
+
+def test_follows_redirects(server):
+    assert fetch_url(f"{server}/redirect") == b"payload"
+
+
+def test_max_redirects(server):
+    configure_http(max_redirects=3, block_private_ips=False)
+    with pytest.raises(RuntimeError, match="redirects"):
+        fetch_url(f"{server}/loop")
+    assert len(Handler.requests) == 4
+
+
+def test_error_status(server):
+    with pytest.raises(RuntimeError, match="404"):
+        fetch_url(f"{server}/missing")
+
+
+def test_allowed_hosts(server):
+    configure_http(allowed_hosts=["127.0.0.1"], block_private_ips=False)
+    assert fetch_url(f"{server}/data") == b"payload"
+
+    configure_http(allowed_hosts=["*.example.com"], block_private_ips=False)
+    with pytest.raises(PermissionError):
+        fetch_url(f"{server}/data")
+    assert Handler.requests == [("/data", Handler.requests[0][1])]
+
+
+def test_denied_hosts(server):
+    configure_http(allowed_hosts=["127.0.0.1"], denied_hosts=["127.0.0.1"], block_private_ips=False)
+    with pytest.raises(PermissionError, match="127.0.0.1"):
+        fetch_url(f"{server}/data")
+
+    configure_http(denied_hosts=["*.invalid"], block_private_ips=False)
+    with pytest.raises(PermissionError, match="elsewhere.invalid"):
+        fetch_url(f"{server}/offsite")
+
+
+def test_allowed_schemes(server):
+    configure_http(allowed_schemes=["https"], block_private_ips=False)
+    with pytest.raises(PermissionError, match="scheme"):
+        fetch_url(f"{server}/data")
+    assert Handler.requests == []
+
+    with pytest.raises(ValueError, match="ftp"):
+        configure_http(allowed_schemes=["ftp"])
+
+
+def test_block_private_ips(server):
+    # Blocked by default
+    configure_http()
+    with pytest.raises(PermissionError, match="private address"):
+        fetch_url(f"{server}/data")
+    with pytest.raises(PermissionError, match="private address"):
+        fetch_url("http://[::1]/data")
+    assert Handler.requests == []
+
+
+@pytest.mark.parametrize("host", [
+    "0.1.2.3",              # this network, 0.0.0.0/8
+    "192.0.0.8",            # IETF protocol assignments, 192.0.0.0/24
+    "198.19.0.1",           # benchmarking, 198.18.0.0/15
+    "224.0.0.251",          # multicast, 224.0.0.0/4
+    "240.0.0.1",            # reserved, 240.0.0.0/4
+    "[64:ff9b::a00:1]",     # NAT64 of 10.0.0.1
+    "[2002:c0a8:101::1]",   # 6to4 of 192.168.1.1
+    "[::7f00:1]",           # IPv4-compatible 127.0.0.1
+    "[2001:0:4136:e378:8000:63bf:f5ff:fffe]",  # Teredo of 10.0.0.1
+    "[ff02::1]",            # IPv6 multicast, ff00::/8
+])
+def test_block_reserved_ranges(host):
+    configure_http(block_private_ips=True)
+    try:
+        with pytest.raises(PermissionError, match="private address"):
+            fetch_url(f"http://{host}/data")
+    finally:
+        configure_http()
+
+
+def test_redirect_to_disallowed_host(server):
+    configure_http(allowed_hosts=["127.0.0.1"], block_private_ips=False)
+    with pytest.raises(PermissionError, match="elsewhere.invalid"):
+        fetch_url(f"{server}/offsite")
+
+
+def test_proxy(server):
+    """Requests for plain http URLs are sent to the proxy with the absolute URL."""
+    configure_http(proxy=server, block_private_ips=False)
+    assert fetch_url("http://manifests.invalid/data") == b"payload"
+    assert Handler.requests[0][0] == "http://manifests.invalid/data"
+
+
+def test_invalid_settings(tmp_path):
+    with pytest.raises(ValueError):
+        configure_http(ca_bundle=str(tmp_path / "missing.pem"))
+
+    empty = tmp_path / "empty.pem"
+    empty.write_text("")
+    with pytest.raises(ValueError, match="no certificates"):
+        configure_http(ca_bundle=str(empty))
+
+    with pytest.raises(ValueError):
+        fetch_url("ftp://example.com/file")
+
+    with pytest.raises(ValueError, match="max_attempts"):
+        configure_http(max_attempts=0)
+
+
+def test_retries_with_backoff(server):
+    configure_http(max_attempts=4, backoff_ms=5, jitter=False, block_private_ips=False)
+    body, attempts = fetch_url(f"{server}/flaky", with_attempts=True)
+    assert body == b"payload"
+    assert [a["status"] for a in attempts] == [503, 503, 200]
+    assert [a["delay_ms"] for a in attempts] == [5, 10, None]
+    assert attempts[0]["error"].startswith("HTTP 503")
+    assert attempts[-1]["error"] is None
+    assert all(a["url"] == f"{server}/flaky" for a in attempts)
+
+
+def test_retries_give_up(server):
+    configure_http(max_attempts=2, backoff_ms=1, block_private_ips=False)
+    with pytest.raises(RuntimeError, match="after 2 attempts"):
+        fetch_url(f"{server}/unavailable")
+    assert len(Handler.requests) == 2
+
+    # Statuses outside retry_on are not retried
+    with pytest.raises(RuntimeError, match="404"):
+        fetch_url(f"{server}/missing")
+    assert len(Handler.requests) == 3
+
+    configure_http(max_attempts=2, backoff_ms=1, retry_on=[404], block_private_ips=False)
+    with pytest.raises(RuntimeError, match="after 2 attempts"):
+        fetch_url(f"{server}/missing")
@@ -0,0 +1,50 @@
+"""
+Tests for flattening the ingredient tree of a result.
+"""
+
+from fast_c2pa_python import flatten_ingredients, read_c2pa_from_bytes
+
+
+def test_flatten_real_manifest(read_image):
+    result = read_c2pa_from_bytes(read_image("chatgpt_image.png"), "image/png")
+    ingredient = result["manifests"][result["active_manifest"]]["ingredients"][0]
+
+    flat = flatten_ingredients(result)
+    assert len(flat) == 1
+    entry = flat[0]
+    assert entry["parent_manifest"] == result["active_manifest"]
+    assert entry["depth"] == 1
+    assert entry["index"] == 0
+    assert entry["title"] == ingredient["title"]
+    assert entry["active_manifest"] == ingredient["active_manifest"]
+
+
+def test_flatten_nested_tree():
+    def manifest(*ingredients):
+        return {"ingredients": [{"title": t, "active_manifest": m} for t, m in ingredients]}
+
+    result = {
+        "active_manifest": "a",
+        "manifests": {
+            "a": manifest(("b.jpg", "b"), ("c.jpg", "c"), ("raw.dng", None)),
+            "b": manifest(("d.jpg", "d")),
+            # Shares d with b and points back to a
+            "c": manifest(("d.jpg", "d"), ("a.jpg", "a")),
+            "d": manifest(),
+            "orphan": manifest(("x.jpg", None)),
+        },
+    }
+    flat = flatten_ingredients(result)
+    assert [(e["parent_manifest"], e["depth"], e["index"], e["title"]) for e in flat] == [
+        ("a", 1, 0, "b.jpg"),
+        ("a", 1, 1, "c.jpg"),
+        ("a", 1, 2, "raw.dng"),
+        ("b", 2, 0, "d.jpg"),
+        ("c", 2, 0, "d.jpg"),
+        ("c", 2, 1, "a.jpg"),
+    ]
+
+
+def test_flatten_without_ingredients(read_image):
+    assert flatten_ingredients(read_c2pa_from_bytes(read_image("C.jpg"), "image/jpeg")) == []
+    assert flatten_ingredients(None) == []
@@ -0,0 +1,75 @@
+"""
+Tests for per-page and per-item reporting of multi-image assets.
+
+multipage.tif has two pages (8x8 and 4x2) with the manifest store in IFD0;
+sequence.heic has hvc1 items 1 and 2 (the primary) and an Exif item 3.
+"""
+
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, read_c2pa_items
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+def test_tiff_pages():
+    data = (TEST_IMAGES_DIR / "multipage.tif").read_bytes()
+    report = read_c2pa_items(data, "image/tiff")
+
+    assert report["result"] == read_c2pa_from_bytes(data, "image/tiff")
+    assert report["binding"] == {"label": "c2pa.hash.data", "valid": True}
+    assert report["active_item"] == 0
+    assert [(p["index"], p["kind"], p["width"], p["height"]) for p in report["items"]] == [
+        (0, "page", 8, 8),
+        (1, "page", 4, 2),
+    ]
+    assert [p["has_manifest_store"] for p in report["items"]] == [True, False]
+    assert all(p["bound"] for p in report["items"])
+
+
+def test_heif_items():
+    data = (TEST_IMAGES_DIR / "sequence.heic").read_bytes()
+    report = read_c2pa_items(data, "image/heic")
+
+    assert report["binding"]["label"].startswith("c2pa.hash.bmff")
+    assert report["binding"]["valid"]
+    assert [(i["id"], i["type"], i["primary"]) for i in report["items"]] == [
+        (1, "hvc1", False),
+        (2, "hvc1", True),
+        (3, "Exif", False),
+    ]
+    assert report["active_item"] == 1
+    assert all(i["bound"] and i["kind"] == "item" for i in report["items"])
+
+
+def test_tampered_page_not_bound():
+    data = bytearray((TEST_IMAGES_DIR / "multipage.tif").read_bytes())
+    # Change a pixel of the second page's strip, which is right before the store
+    strip = data.index(b"\x78" * 8)
+    data[strip] = 0
+    report = read_c2pa_items(bytes(data), "image/tiff")
+    assert report["binding"] == {"label": "c2pa.hash.data", "valid": False}
+    assert not any(p["bound"] for p in report["items"])
+
+
+def test_without_manifest():
+    data = (TEST_IMAGES_DIR / "multipage.tif").read_bytes()
+    # A two page TIFF with the C2PA tag renamed out of the way
+    stripped = data.replace(b"\x41\xcd\x07\x00", b"\x42\xcd\x07\x00", 1)
+    report = read_c2pa_items(stripped, "image/tiff")
+    assert report["result"] is None
+    assert report["binding"] is None and report["active_item"] is None
+    assert len(report["items"]) == 2
+
+
+def test_large_size_box_past_its_parent():
+    # Boxes are walked as get_asset_info walks them: pitm ends past meta
+    heic = b"\0\0\0\x14meta\0\0\0\0" + b"\0\0\0\x01pitm" + (16).to_bytes(8, "big") + b"\0" * 32
+    report = read_c2pa_items(heic, "image/heic")
+    assert report["result"] is None and report["items"] == []
+
+def test_unsupported_type():
+    with pytest.raises(ValueError):
+        read_c2pa_items(b"", "image/jpeg")
@@ -0,0 +1,150 @@
+"""
+Regression tests for the JPEG APP11 segment scanner.
+
+The layouts below were reduced from fuzzing runs: the manifest store of a real
+signed JPEG is re-split into many small APP11 packets, mixed with other APP
+segments, fill bytes and stray packets. The manifest must still be found, even
+though re-splitting invalidates the hard binding.
+"""
+
+import random
+import struct
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, list_jpeg_segments
+
+TEST_IMAGE = Path(__file__).parent / "test_images" / "C.jpg"
+
+APP11 = 0xEB
+SOS = 0xDA
+
+
+def split_segments(data):
+    """Split a well-formed JPEG into (marker, raw segment) pairs up to SOS."""
+    pos = 2
+    segments = []
+    while pos < len(data):
+        marker = data[pos + 1]
+        if marker == SOS:
+            segments.append((marker, data[pos:]))
+            break
+        length = struct.unpack(">H", data[pos + 2:pos + 4])[0]
+        segments.append((marker, data[pos:pos + 2 + length]))
+        pos += 2 + length
+    return segments
+
+
+def extract_store(data):
+    """Reassemble the manifest store from the APP11 segments."""
+    store = b""
+    for marker, segment in split_segments(data):
+        if marker == APP11 and segment[4:6] == b"JP":
+            body = segment[4:]
+            store += body[16:] if store else body[8:]
+    return store
+
+
+def app11(sequence, payload, box_instance=b"\x02\x11"):
+    body = b"JP" + box_instance + struct.pack(">I", sequence) + payload
+    return b"\xff\xeb" + struct.pack(">H", len(body) + 2) + body
+
+
+def rebuild(data, chunk_size, extra=lambda index: b""):
+    """Re-split the manifest store into chunk_size packets, calling extra after each."""
+    store = extract_store(data)
+    packets = []
+    for index, start in enumerate(range(0, len(store), chunk_size)):
+        chunk = store[start:start + chunk_size]
+        payload = chunk if index == 0 else store[:8] + chunk
+        packets.append(app11(index + 1, payload) + extra(index))
+
+    out = [b"\xff\xd8"]
+    inserted = False
+    for marker, segment in split_segments(data):
+        if marker == APP11:
+            if not inserted:
+                out.extend(packets)
+                inserted = True
+            continue
+        out.append(segment)
+    return b"".join(out)
+
+
+@pytest.fixture
+def jpeg_bytes():
+    if not TEST_IMAGE.exists():
+        pytest.skip(f"Test image not found: {TEST_IMAGE}")
+    return TEST_IMAGE.read_bytes()
+
+
+def assert_manifest_found(data):
+    result = read_c2pa_from_bytes(data, "image/jpeg")
+    assert result is not None
+    assert result["active_manifest"] in result["manifests"]
+
+
+def test_many_small_segments(jpeg_bytes):
+    """Manifest store split across hundreds of APP11 packets."""
+    assert_manifest_found(rebuild(jpeg_bytes, 97))
+
+
+def test_interleaved_app_segments(jpeg_bytes):
+    """Other APP segments between the APP11 packets."""
+    def comment(index):
+        text = b"camera-notes-%03d" % index
+        return b"\xff\xe4" + struct.pack(">H", len(text) + 2) + text
+
+    assert_manifest_found(rebuild(jpeg_bytes, 500, comment))
+
+
+def test_fill_bytes_before_markers(jpeg_bytes):
+    """Runs of 0xFF fill bytes before each marker."""
+    data = rebuild(jpeg_bytes, 1000, lambda index: b"\xff" * (index % 5))
+    assert_manifest_found(data)
+
+    segments = list_jpeg_segments(data)
+    assert any(s["fill_bytes"] > 0 for s in segments)
+
+
+def test_stray_packet_with_same_box_instance(jpeg_bytes):
+    """A stray packet repeating the store's box instance and sequence number."""
+    stray = app11(1, b"\x00" * 24)
+    data = rebuild(jpeg_bytes, 4000, lambda index: stray if index == 0 else b"")
+    assert_manifest_found(data)
+
+    c2pa_segments = [s for s in list_jpeg_segments(data) if s["c2pa"]]
+    assert [s["sequence"] for s in c2pa_segments] == list(range(1, len(c2pa_segments) + 1))
+
+
+def test_list_jpeg_segments(jpeg_bytes):
+    """Diagnostics list the segments of the original file."""
+    segments = list_jpeg_segments(jpeg_bytes)
+    assert segments[0]["name"] == "SOI"
+    assert segments[-1]["name"] == "SOS"
+
+    c2pa_segments = [s for s in segments if s["c2pa"]]
+    assert c2pa_segments
+    assert all(s["name"] == "APP11" and s["box_instance"] == c2pa_segments[0]["box_instance"]
+               for s in c2pa_segments)
+    assert len(extract_store(jpeg_bytes)) > 0
+
+
+def test_list_jpeg_segments_not_jpeg():
+    assert list_jpeg_segments(b"not a jpeg") == []
+
+
+@pytest.mark.parametrize("seed", range(20))
+def test_random_corruption_does_not_crash(jpeg_bytes, seed):
+    """Random byte flips in the header area either parse or raise cleanly."""
+    rng = random.Random(seed)
+    data = bytearray(jpeg_bytes[:60000])
+    for _ in range(8):
+        data[rng.randrange(2, len(data))] = rng.randrange(256)
+
+    list_jpeg_segments(bytes(data))
+    try:
+        read_c2pa_from_bytes(bytes(data), "image/jpeg", lenient=True)
+    except RuntimeError:
+        pass
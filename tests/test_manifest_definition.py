@@ -0,0 +1,77 @@
+"""
+Tests for linting manifest definitions against the bundled JSON Schema.
+"""
+
+import json
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    create_ingredient_from_bytes,
+    get_manifest_definition_schema,
+    validate_manifest_definition,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+DEFINITION = {
+    "claim_generator_info": [{"name": "test-suite", "version": "1.0"}],
+    "title": "signed.png",
+    "assertions": [
+        {"label": "c2pa.actions", "data": {"actions": [{"action": "c2pa.created"}]}},
+    ],
+}
+
+
+def test_valid_definitions():
+    assert validate_manifest_definition(json.dumps(DEFINITION)) == []
+    assert validate_manifest_definition(DEFINITION) == []
+    assert validate_manifest_definition({}) == []
+
+    data = (TEST_IMAGES_DIR / "C.jpg").read_bytes()
+    ingredient = create_ingredient_from_bytes(data, "image/jpeg", relationship="parentOf")
+    assert validate_manifest_definition({**DEFINITION, "ingredients": [ingredient["ingredient"]]}) == []
+
+
+def test_every_problem_reported():
+    problems = validate_manifest_definition({
+        "claim_generator_info": [],
+        "titel": "typo.png",
+        "claim_version": 3,
+        "assertions": [{"label": ""}, {"label": "org.example", "data": 1, "kind": "Yaml"}],
+        "ingredients": [{"relationship": "childOf", "thumbnail": {"format": "image/jpeg"}}],
+    })
+    assert {problem["path"]: problem["message"] for problem in problems} == {
+        "/claim_generator_info": "expected at least 1 item(s), found 0",
+        "/titel": 'unknown key "titel"',
+        "/claim_version": "3 is more than the maximum of 2",
+        "/assertions/0": 'missing required key "data"',
+        "/assertions/0/label": "expected at least 1 character(s)",
+        "/assertions/1/kind": '"Yaml" is not one of "Cbor", "Json"',
+        "/ingredients/0/relationship": '"childOf" is not one of "parentOf", "componentOf", "inputTo"',
+        "/ingredients/0/thumbnail": 'missing required key "identifier"',
+    }
+
+
+def test_wrong_types():
+    problems = validate_manifest_definition({"title": 1, "assertions": {}})
+    assert sorted(problems, key=lambda problem: problem["path"]) == [
+        {"path": "/assertions", "message": "expected array, not object"},
+        {"path": "/title", "message": "expected string, not integer"},
+    ]
+
+
+def test_invalid_json():
+    (problem,) = validate_manifest_definition("{not json")
+    assert problem["path"] == ""
+    assert problem["message"].startswith("invalid JSON")
+    with pytest.raises(TypeError):
+        validate_manifest_definition(["not", "a", "definition"])
+
+
+def test_bundled_schema():
+    schema = get_manifest_definition_schema()
+    assert schema["title"] == "ManifestDefinition"
+    assert set(DEFINITION) <= set(schema["properties"])
+    assert "AssertionDefinition" in schema["definitions"]
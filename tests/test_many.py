@@ -0,0 +1,106 @@
+"""
+Tests for reading a stream of assets with read_c2pa_many.
+"""
+
+import threading
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    enable_trust_checks,
+    read_c2pa_from_bytes,
+    read_c2pa_many,
+    setup_trust_verification,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+JPEG = TEST_IMAGES_DIR / "adobe_firefly_image.jpg"
+NO_C2PA = TEST_IMAGES_DIR / "screenshot_noc2pa.png"
+TRUST_DIR = Path(__file__).parent / "tmp_cert"
+
+
+def test_mixed_sources():
+    data = JPEG.read_bytes()
+    expected = read_c2pa_from_bytes(data, "image/jpeg")
+    items = [
+        ("bytes", data),
+        ("path", str(JPEG)),
+        ("pathlib", NO_C2PA),
+        ("typed", data, "image/jpeg"),
+    ]
+    results = {item_id: (result, error) for item_id, result, error in read_c2pa_many(items, mime_type="image/jpeg")}
+
+    assert set(results) == {"bytes", "path", "pathlib", "typed"}
+    for item_id in ("bytes", "path", "typed"):
+        result, error = results[item_id]
+        assert error is None
+        # Validation results may differ in OCSP responses fetched meanwhile
+        assert result["manifests"] == expected["manifests"]
+        assert result["validation_state"] == expected["validation_state"]
+    assert results["pathlib"] == (None, None)
+
+
+def test_errors_reported_per_item():
+    items = [
+        ("missing", "/nonexistent/image.jpg"),
+        ("untyped", b"data"),
+        ("ok", JPEG),
+        ("detected", JPEG.read_bytes()),
+    ]
+    results = {item_id: (result, error) for item_id, result, error in read_c2pa_many(items)}
+    assert results["missing"][0] is None and "nonexistent" in results["missing"][1]
+    assert "mime_type" in results["untyped"][1]
+    assert results["ok"][1] is None and results["ok"][0] is not None
+    assert results["detected"][1] is None and results["detected"][0] is not None
+
+
+def test_backpressure():
+    data = JPEG.read_bytes()
+    taken = 0
+    lock = threading.Lock()
+
+    def source():
+        nonlocal taken
+        for i in range(50):
+            with lock:
+                taken += 1
+            yield i, data
+
+    stream = read_c2pa_many(source(), mime_type="image/jpeg", max_in_flight=4)
+    first = next(stream)
+    assert first[2] is None
+    # Only the items in flight have been taken from the iterator
+    assert taken <= 4
+    assert len(list(stream)) == 49
+    assert taken == 50
+
+
+def test_options_forwarded():
+    items = [("json", JPEG.read_bytes())]
+    ((_, result, error),) = read_c2pa_many(items, mime_type="image/jpeg", max_ingredient_depth=0)
+    assert error is None
+    assert list(result["manifests"]) == [result["active_manifest"]]
+
+
+def test_invalid_max_in_flight():
+    with pytest.raises(ValueError):
+        list(read_c2pa_many([], max_in_flight=0))
+
+
+@pytest.fixture
+def trusted():
+    """Trust checks against the test trust settings, turned off again afterwards"""
+    if not (TRUST_DIR / "anchors.pem").exists():
+        pytest.skip("trust settings files not found")
+    setup_trust_verification(
+        str(TRUST_DIR / "anchors.pem"), str(TRUST_DIR / "allowed.pem"), str(TRUST_DIR / "store.cfg")
+    )
+    yield
+    enable_trust_checks(False)
+
+
+def test_settings_apply_to_workers(trusted):
+    ((_, result, error),) = read_c2pa_many([("trusted", JPEG)])
+    assert error is None
+    assert result["validation_state"] == "Trusted"
@@ -0,0 +1,59 @@
+"""
+Tests for per-call memory statistics (the "memory-stats" cargo feature).
+"""
+
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import measure_memory, read_c2pa_batch, read_c2pa_from_bytes
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+@pytest.fixture
+def asset():
+    return (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+
+
+def measure(*args, **kwargs):
+    try:
+        return measure_memory(*args, **kwargs)
+    except NotImplementedError:
+        pytest.skip("built without the memory-stats feature")
+
+
+def test_reports_read(asset):
+    result, stats = measure(read_c2pa_from_bytes, asset, "image/jpeg", allow_threads=False)
+    assert result == read_c2pa_from_bytes(asset, "image/jpeg")
+    assert 0 < stats["peak_bytes"] <= stats["allocated_bytes"]
+    assert stats["allocations"] > 0
+
+
+def test_released_gil_counts(asset):
+    _, stats = measure(read_c2pa_from_bytes, asset, "image/jpeg", allow_threads=True)
+    assert stats["peak_bytes"] > 0
+
+
+def test_worker_threads_count(asset):
+    # The parse of a read with a budget runs on a thread of its own, the
+    # reads of a batch on a pool
+    _, plain = measure(read_c2pa_from_bytes, asset, "image/jpeg")
+    _, bounded = measure(read_c2pa_from_bytes, asset, "image/jpeg", deadline_ms=60_000)
+    _, batch = measure(read_c2pa_batch, [(asset, "image/jpeg")])
+    for stats in (bounded, batch):
+        assert stats["peak_bytes"] > plain["peak_bytes"] // 2
+        assert stats["allocations"] > plain["allocations"] // 2
+
+def test_nested(asset):
+    (_, inner), outer = measure(measure, read_c2pa_from_bytes, asset, "image/jpeg")
+    assert inner["peak_bytes"] <= outer["peak_bytes"]
+    assert inner["allocations"] <= outer["allocations"]
+
+
+def test_exception_propagates():
+    def fail():
+        raise ValueError("boom")
+
+    with pytest.raises(ValueError):
+        measure(fail)
@@ -0,0 +1,112 @@
+"""
+Tests for merging embedded and sidecar manifest stores.
+
+chatgpt_image.png provides a manifest store to use as a sidecar, and C.jpg an
+unrelated embedded store.
+"""
+
+import struct
+import threading
+import zlib
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from fast_c2pa_python import configure_http, read_c2pa_from_bytes, read_c2pa_merged
+
+
+def with_xmp_provenance(png, url):
+    """Add an iTXt XMP chunk pointing to a remote manifest after IHDR."""
+    xmp = ('<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">'
+           f'<rdf:Description rdf:about="" xmlns:dcterms="http://purl.org/dc/terms/" dcterms:provenance="{url}"/>'
+           '</rdf:RDF></x:xmpmeta>').encode()
+    body = b"iTXt" + b"XML:com.adobe.xmp\x00\x00\x00\x00\x00" + xmp
+    chunk = struct.pack(">I", len(body) - 4) + body + struct.pack(">I", zlib.crc32(body))
+    return png[:33] + chunk + png[33:]
+
+
+@pytest.fixture
+def png(read_image):
+    return read_image("chatgpt_image.png")
+
+
+def test_same_store(split_manifest, png):
+    manifest, _ = split_manifest(png)
+    embedded = read_c2pa_from_bytes(png, "image/png")
+
+    merged = read_c2pa_merged(png, "image/png", sidecar=manifest)
+    assert merged["conflicts"] == []
+    assert merged["active_manifest"] == embedded["active_manifest"]
+    assert merged["manifests"] == embedded["manifests"]
+    assert merged["validation_state"] == embedded["validation_state"]
+    assert merged["is_embedded"] is True
+    assert merged["sources"]["embedded"]["active_manifest"] == embedded["active_manifest"]
+    assert merged["sources"]["sidecar"]["active_manifest"] == embedded["active_manifest"]
+
+
+def test_sidecar_only(split_manifest, png):
+    manifest, stripped = split_manifest(png)
+    merged = read_c2pa_merged(stripped, "image/png", sidecar=manifest)
+    assert merged["is_embedded"] is False
+    assert merged["sources"]["embedded"] is None
+    assert merged["conflicts"] == []
+    assert merged["active_manifest"] == read_c2pa_from_bytes(png, "image/png")["active_manifest"]
+
+
+def test_embedded_only(read_image):
+    data = read_image("C.jpg")
+    merged = read_c2pa_merged(data, "image/jpeg")
+    assert merged["sources"]["sidecar"] is None
+    assert merged["manifests"] == read_c2pa_from_bytes(data, "image/jpeg")["manifests"]
+
+
+def test_unrelated_stores_conflict(read_image, split_manifest, png):
+    manifest, _ = split_manifest(png)
+    data = read_image("C.jpg")
+    embedded = read_c2pa_from_bytes(data, "image/jpeg")
+
+    merged = read_c2pa_merged(data, "image/jpeg", sidecar=manifest)
+    assert [c["code"] for c in merged["conflicts"]] == ["merge.activeManifest.mismatch"]
+    # The embedded store wins, the sidecar manifests are still listed
+    assert merged["active_manifest"] == embedded["active_manifest"]
+    assert merged["is_embedded"] is True
+    sidecar = read_c2pa_from_bytes(png, "image/png")
+    assert set(merged["manifests"]) == set(embedded["manifests"]) | set(sidecar["manifests"])
+
+
+def test_no_stores(read_image):
+    assert read_c2pa_merged(read_image("screenshot_noc2pa.png"), "image/png") is None
+
+
+def test_fetch_remote(split_manifest, png):
+    manifest, stripped = split_manifest(png)
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self):
+            self.send_response(200)
+            self.end_headers()
+            self.wfile.write(manifest)
+
+        def log_message(self, *args):
+            pass
+
+    httpd = HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=httpd.serve_forever, daemon=True).start()
+    url = f"http://127.0.0.1:{httpd.server_address[1]}/manifest.c2pa"
+    asset = with_xmp_provenance(stripped, url)
+    try:
+        configure_http(proxy=None, block_private_ips=False)
+        assert read_c2pa_merged(asset, "image/png") is None
+        merged = read_c2pa_merged(asset, "image/png", fetch_remote=True)
+    finally:
+        httpd.shutdown()
+        configure_http()
+    assert merged["remote_url"] == url
+    assert merged["sources"]["sidecar"]["url"] == url
+    assert [attempt["status"] for attempt in merged["fetch_attempts"]] == [200]
+    assert merged["active_manifest"] == read_c2pa_from_bytes(png, "image/png")["active_manifest"]
+
+
+def test_invalid_sidecar(png):
+    with pytest.raises(RuntimeError):
+        read_c2pa_merged(png, "image/png", sidecar=b"not a manifest store")
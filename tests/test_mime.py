@@ -0,0 +1,70 @@
+"""
+Tests for detecting MIME types from the content of assets.
+"""
+
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import detect_mime_from_bytes, read_c2pa_from_bytes, read_c2pa_from_file
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+@pytest.mark.parametrize("name, mime_type", [
+    ("adobe_firefly_image.jpg", "image/jpeg"),
+    ("chatgpt_image.png", "image/png"),
+    ("multipage.tif", "image/tiff"),
+    ("sequence.heic", "image/heic"),
+    ("id3_priv.mp3", "audio/mpeg"),
+])
+def test_detect_test_images(name, mime_type):
+    path = TEST_IMAGES_DIR / name
+    if not path.exists():
+        pytest.skip(f"Test image not found: {path}")
+    assert detect_mime_from_bytes(path.read_bytes()) == mime_type
+
+
+@pytest.mark.parametrize("head, mime_type", [
+    (b"GIF89a\x01\x00\x01\x00", "image/gif"),
+    (b"RIFF\x24\x00\x00\x00WEBPVP8 ", "image/webp"),
+    (b"RIFF\x24\x00\x00\x00WAVEfmt ", "audio/wav"),
+    (b"RIFF\x24\x00\x00\x00AVI LIST", "video/avi"),
+    (b"MM\x00*\x00\x00\x00\x08", "image/tiff"),
+    (b"%PDF-1.7\n", "application/pdf"),
+    (b"\x00\x00\x00\x1cftypavif\x00\x00\x00\x00avifmif1", "image/avif"),
+    (b"\x00\x00\x00\x18ftypmif1\x00\x00\x00\x00mif1avif", "image/avif"),
+    (b"\x00\x00\x00\x18ftypmif1\x00\x00\x00\x00mif1heic", "image/heic"),
+    (b"\x00\x00\x00\x18ftypisom\x00\x00\x02\x00isomiso2", "video/mp4"),
+    (b"\x00\x00\x00\x14ftypqt  \x00\x00\x00\x00qt  ", "video/quicktime"),
+    (b"\x00\x00\x00\x18ftypM4A \x00\x00\x00\x00M4A mp42", "audio/mp4"),
+    (b"\xff\xfb\x90\x64\x00", "audio/mpeg"),
+    (b"not an asset", None),
+    (b"\xff\xe9\x90\x64", None),
+    (b"", None),
+])
+def test_detect_magic_bytes(head, mime_type):
+    assert detect_mime_from_bytes(head) == mime_type
+
+
+def test_read_without_mime_type():
+    path = TEST_IMAGES_DIR / "adobe_firefly_image.jpg"
+    if not path.exists():
+        pytest.skip(f"Test image not found: {path}")
+    data = path.read_bytes()
+    expected = read_c2pa_from_bytes(data, "image/jpeg")
+    assert read_c2pa_from_bytes(data) == expected
+    assert read_c2pa_from_bytes([data[:3], data[3:]], mime_type=None) == expected
+
+    with pytest.raises(ValueError):
+        read_c2pa_from_bytes(b"not an asset")
+
+
+def test_read_file_with_wrong_extension(tmp_path):
+    path = TEST_IMAGES_DIR / "chatgpt_image.png"
+    if not path.exists():
+        pytest.skip(f"Test image not found: {path}")
+    misnamed = tmp_path / "chatgpt_image.jpg"
+    misnamed.write_bytes(path.read_bytes())
+    expected = read_c2pa_from_file(path)
+    assert read_c2pa_from_file(misnamed)["active_manifest"] == expected["active_manifest"]
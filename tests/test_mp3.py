@@ -0,0 +1,106 @@
+"""
+Tests for reading manifest stores from the ID3 tags of MP3 files.
+
+id3_stacked.mp3 has an ID3v2.3 tag (title and artist) followed by an ID3v2.4
+tag with the manifest store in a GEOB frame; id3_priv.mp3 has a single
+ID3v2.3 tag with the manifest store in a PRIV frame. Both are signed with the
+c2pa-rs test certificate. The other layouts are built from their manifest
+store; moving the store invalidates the data hash, but it is still found.
+"""
+
+import struct
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, read_c2pa_from_file, verify_manifest_bytes
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+GEOB_HEADER = b"\x00application/x-c2pa-manifest-store\x00c2pa\x00c2pa manifest store\x00"
+AUDIO = b"\xff\xfb\x90\x64" + bytes(413)
+
+
+def manifest_store(data):
+    start = data.find(b"jumb") - 4
+    return data[start:start + struct.unpack(">I", data[start:start + 4])[0]]
+
+
+def syncsafe(n):
+    return bytes((n >> shift) & 0x7F for shift in (21, 14, 7, 0))
+
+
+def unsynchronise(data):
+    return data.replace(b"\xff", b"\xff\x00")
+
+
+def tag(version, frames, flags=0, footer=False):
+    body = b"".join(frames)
+    header = syncsafe(len(body))
+    out = b"ID3" + bytes([version, 0, flags]) + header + body
+    if footer:
+        out += b"3DI" + bytes([version, 0, flags]) + header
+    return out
+
+
+def frame(version, frame_id, content, flags=0):
+    size = syncsafe(len(content)) if version == 4 else struct.pack(">I", len(content))
+    return frame_id + size + struct.pack(">H", flags) + content
+
+
+@pytest.mark.parametrize("name", ["id3_stacked.mp3", "id3_priv.mp3"])
+def test_signed_assets(read_image, name):
+    data = read_image(name)
+    result = read_c2pa_from_bytes(data, "audio/mpeg")
+    assert result is not None
+    manifest = result["manifests"][result["active_manifest"]]
+    assert manifest["title"] == name
+
+    # Same validation as with the manifest store supplied directly
+    expected = verify_manifest_bytes(manifest_store(data), data, "audio/mpeg")
+    assert result["validation_state"] == expected["validation_state"]
+    assert result["validation_results"] == expected["validation_results"]
+    assert read_c2pa_from_file(str(TEST_IMAGES_DIR / name)) == result
+
+
+def test_unsynchronised_v23_tag(read_image):
+    store = manifest_store(read_image("id3_stacked.mp3"))
+    body = unsynchronise(frame(3, b"GEOB", GEOB_HEADER + store))
+    data = tag(3, [body], flags=0x80) + AUDIO
+
+    result = read_c2pa_from_bytes(data, "audio/mpeg")
+    assert result["manifests"][result["active_manifest"]]["title"] == "id3_stacked.mp3"
+
+
+def test_unsynchronised_v24_frame_with_length_indicator(read_image):
+    store = manifest_store(read_image("id3_priv.mp3"))
+    content = struct.pack(">I", len(store) + 5) + unsynchronise(b"c2pa\x00" + store)
+    data = tag(4, [frame(4, b"PRIV", content, flags=0x0003)]) + AUDIO
+
+    result = read_c2pa_from_bytes(data, "audio/mpeg")
+    assert result["manifests"][result["active_manifest"]]["title"] == "id3_priv.mp3"
+
+
+def test_appended_tag(read_image):
+    """An ID3v2.4 tag at the end of the file, before an ID3v1 tag."""
+    store = manifest_store(read_image("id3_stacked.mp3"))
+    appended = tag(4, [frame(4, b"GEOB", GEOB_HEADER + store)], flags=0x10, footer=True)
+    id3v1 = b"TAG" + bytes(125)
+    data = tag(3, [frame(3, b"TIT2", b"\x00Episode 42")]) + AUDIO + appended + id3v1
+
+    result = read_c2pa_from_bytes(data, "audio/mpeg")
+    assert result["manifests"][result["active_manifest"]]["title"] == "id3_stacked.mp3"
+
+
+def test_extended_header_and_padding(read_image):
+    store = manifest_store(read_image("id3_stacked.mp3"))
+    extended = syncsafe(6) + b"\x01\x00"
+    frames = [frame(4, b"TIT2", b"\x03Episode 42"), frame(4, b"GEOB", GEOB_HEADER + store), bytes(256)]
+    data = tag(4, [extended] + frames, flags=0x40) + AUDIO
+
+    assert read_c2pa_from_bytes(data, "audio/mpeg") is not None
+
+
+def test_mp3_without_manifest():
+    data = tag(3, [frame(3, b"TIT2", b"\x00Episode 42"), frame(3, b"PRIV", b"com.example\x00data")]) + AUDIO
+    assert read_c2pa_from_bytes(data, "audio/mpeg") is None
+    assert read_c2pa_from_bytes(AUDIO, "audio/mpeg") is None
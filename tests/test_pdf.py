@@ -0,0 +1,124 @@
+"""
+Tests for reading manifests of PDF documents and of the images embedded in them.
+
+The documents are built on the fly: page 1 draws C.jpg, page 2 draws
+icon_test.jpg through a form XObject, and a raw (FlateDecode) image is left
+unused.
+"""
+
+import zlib
+
+import pytest
+
+from fast_c2pa_python import (
+    UnsupportedFormatError,
+    get_mime_type,
+    make_signed_test_asset,
+    read_c2pa_from_bytes,
+    read_c2pa_from_file,
+    read_c2pa_from_pdf_images,
+)
+
+
+def stream(dictionary, content):
+    return b"<< %s /Length %d >>\nstream\n%s\nendstream" % (dictionary, len(content), content)
+
+
+def build_pdf(objects):
+    """Serialize numbered objects (1-based) with a valid cross-reference table."""
+    out = bytearray(b"%PDF-1.7\n")
+    offsets = []
+    for number, body in enumerate(objects, start=1):
+        offsets.append(len(out))
+        out += b"%d 0 obj\n%s\nendobj\n" % (number, body)
+    xref = len(out)
+    out += b"xref\n0 %d\n0000000000 65535 f \n" % (len(objects) + 1)
+    for offset in offsets:
+        out += b"%010d 00000 n \n" % offset
+    out += b"trailer\n<< /Size %d /Root 1 0 R >>\nstartxref\n%d\n%%%%EOF\n" % (len(objects) + 1, xref)
+    return bytes(out)
+
+
+def jpeg_xobject(data):
+    return stream(b"/Type /XObject /Subtype /Image /Width 1 /Height 1 /ColorSpace /DeviceRGB "
+                  b"/BitsPerComponent 8 /Filter /DCTDecode", data)
+
+
+@pytest.fixture
+def pdf(read_image):
+    raw = zlib.compress(b"\xff\x00\x00")
+    return build_pdf([
+        b"<< /Type /Catalog /Pages 2 0 R >>",
+        b"<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+        b"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 10 10] "
+        b"/Resources << /XObject << /Im1 5 0 R >> >> /Contents 8 0 R >>",
+        b"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 10 10] "
+        b"/Resources << /XObject << /Fm1 6 0 R >> >> /Contents 9 0 R >>",
+        jpeg_xobject(read_image("C.jpg")),
+        stream(b"/Type /XObject /Subtype /Form /BBox [0 0 1 1] "
+               b"/Resources << /XObject << /Im2 7 0 R >> >>", b"/Im2 Do"),
+        jpeg_xobject(read_image("icon_test.jpg")),
+        stream(b"", b"q 10 0 0 10 0 0 cm /Im1 Do Q"),
+        stream(b"", b"q 10 0 0 10 0 0 cm /Fm1 Do Q"),
+        stream(b"/Type /XObject /Subtype /Image /Width 1 /Height 1 /ColorSpace /DeviceRGB "
+               b"/BitsPerComponent 8 /Filter /FlateDecode", raw),
+    ])
+
+
+def test_embedded_images(read_image, pdf):
+    images = read_c2pa_from_pdf_images(pdf)
+    assert [(i["object"], i["pages"], i["mime_type"]) for i in images] == [
+        ("5 0", [1], "image/jpeg"),
+        ("7 0", [2], "image/jpeg"),
+        ("10 0", [], None),
+    ]
+
+    c = read_c2pa_from_bytes(read_image("C.jpg"), "image/jpeg")
+    icon = read_c2pa_from_bytes(read_image("icon_test.jpg"), "image/jpeg")
+    assert images[0]["result"] == c
+    assert images[0]["size"] == len(read_image("C.jpg"))
+    assert images[1]["result"] == icon
+    assert images[2]["result"] is None
+    assert all(i["error"] is None for i in images)
+
+
+def test_shared_image(pdf):
+    """An image drawn on several pages reports each of them."""
+    shared = pdf.replace(b"/XObject << /Fm1 6 0 R >>", b"/XObject << /Im1 5 0 R >>")
+    images = read_c2pa_from_pdf_images(shared, allow_threads=False)
+    assert images[0]["pages"] == [1, 2]
+    assert images[1]["pages"] == []
+
+
+def test_document_without_manifest(pdf):
+    assert read_c2pa_from_bytes(pdf, "application/pdf") is None
+
+
+def test_invalid_pdf():
+    with pytest.raises(RuntimeError):
+        read_c2pa_from_pdf_images(b"not a pdf")
+
+
+def test_signed_document(untrusted_valid, tmp_path):
+    data = make_signed_test_asset("pdf", {"title": "report.pdf"})
+    result = read_c2pa_from_bytes(data, "application/pdf")
+    assert result["validation_state"] == "Valid"
+    assert result["manifests"][result["active_manifest"]]["title"] == "report.pdf"
+
+    path = tmp_path / "report.pdf"
+    path.write_bytes(data)
+    assert get_mime_type(str(path)) == "application/pdf"
+    assert read_c2pa_from_file(str(path)) == result
+
+    tampered = data.replace(b"0 0.5 1 rg", b"1 0.5 0 rg")
+    assert read_c2pa_from_bytes(tampered, "application/pdf")["validation_state"] == "Invalid"
+
+
+def test_document_mime_types():
+    assert get_mime_type("scan.DNG") == "image/x-adobe-dng"
+    assert get_mime_type("manifest.c2pa") == "application/c2pa"
+
+
+def test_unsupported_format():
+    with pytest.raises(UnsupportedFormatError, match="application/pdf"):
+        read_c2pa_from_bytes(b"hello", "text/plain")
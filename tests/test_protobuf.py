@@ -0,0 +1,98 @@
+"""
+Tests for the protobuf encoder.
+
+The messages are decoded with a minimal wire-format reader so the tests do not
+need generated stubs.
+"""
+
+import json
+from pathlib import Path
+
+import pytest
+
+import fast_c2pa_python
+from fast_c2pa_python import read_c2pa_from_bytes, read_c2pa_encoded_from_bytes, to_protobuf
+
+TEST_IMAGES = Path(__file__).parent / "test_images"
+
+
+def read_varint(data, pos):
+    result = shift = 0
+    while True:
+        byte = data[pos]
+        pos += 1
+        result |= (byte & 0x7F) << shift
+        if not byte & 0x80:
+            return result, pos
+        shift += 7
+
+
+def decode_fields(data):
+    """Decode a message into {field_number: [values]}, length-delimited values as bytes."""
+    fields = {}
+    pos = 0
+    while pos < len(data):
+        key, pos = read_varint(data, pos)
+        number, wire_type = key >> 3, key & 7
+        if wire_type == 0:
+            value, pos = read_varint(data, pos)
+        elif wire_type == 2:
+            length, pos = read_varint(data, pos)
+            value = data[pos:pos + length]
+            pos += length
+        else:
+            raise AssertionError(f"unexpected wire type {wire_type}")
+        fields.setdefault(number, []).append(value)
+    return fields
+
+
+def text(fields, number):
+    return fields[number][0].decode("utf-8") if number in fields else ""
+
+
+@pytest.fixture(params=["adobe_firefly_image.jpg", "chatgpt_image.png"])
+def result(request):
+    path = TEST_IMAGES / request.param
+    if not path.exists():
+        pytest.skip(f"Test image not found: {path}")
+    mime_type = "image/jpeg" if path.suffix == ".jpg" else "image/png"
+    data = path.read_bytes()
+    return data, mime_type, read_c2pa_from_bytes(data, mime_type)
+
+
+def test_encoded_matches_to_protobuf(result):
+    data, mime_type, expected = result
+    encoded = read_c2pa_encoded_from_bytes(data, mime_type, format="protobuf")
+    assert encoded == to_protobuf(expected)
+
+
+def test_manifest_store_fields(result):
+    _, _, expected = result
+    store = decode_fields(to_protobuf(expected))
+
+    assert text(store, 1) == expected["active_manifest"]
+    assert text(store, 5) == expected["validation_state"]
+
+    manifests = {}
+    for entry in store[2]:
+        entry_fields = decode_fields(entry)
+        manifests[text(entry_fields, 1)] = decode_fields(entry_fields[2][0])
+    assert set(manifests) == set(expected["manifests"])
+
+    active = manifests[expected["active_manifest"]]
+    expected_active = expected["manifests"][expected["active_manifest"]]
+    assert text(active, 1) == expected_active["label"]
+    assertions = [decode_fields(a) for a in active.get(9, [])]
+    assert [text(a, 1) for a in assertions] == [a["label"] for a in expected_active["assertions"]]
+    assert [json.loads(text(a, 2)) for a in assertions] == [a["data"] for a in expected_active["assertions"]]
+
+
+def test_unmodelled_fields_kept_as_json():
+    store = decode_fields(to_protobuf({"active_manifest": "urn:a", "custom": {"x": 1}}))
+    assert text(store, 1) == "urn:a"
+    assert json.loads(text(store, 15)) == {"custom": {"x": 1}}
+
+
+def test_schema_ships_with_package():
+    schema = Path(fast_c2pa_python.__file__).parent / "fast_c2pa.proto"
+    assert "message ManifestStore" in schema.read_text()
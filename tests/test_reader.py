@@ -0,0 +1,155 @@
+"""
+Tests for Reader objects shared between threads.
+"""
+
+import json
+from concurrent.futures import ThreadPoolExecutor
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import Reader, read_c2pa_from_bytes, read_c2pa_json_from_bytes
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+@pytest.fixture
+def asset():
+    return (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+
+
+def test_from_bytes(asset):
+    expected = read_c2pa_from_bytes(asset, "image/jpeg")
+    reader = Reader.from_bytes(asset, "image/jpeg")
+
+    assert reader.to_dict() == expected
+    assert json.loads(reader.to_json())["result"] == expected
+    assert reader.active_label == expected["active_manifest"]
+    assert reader.validation_state == expected["validation_state"]
+    assert reader.labels() == list(expected["manifests"])
+    assert len(reader) == len(expected["manifests"])
+    assert reader.manifest() == expected["manifests"][expected["active_manifest"]]
+    assert reader.active_label in reader
+    assert reader[reader.active_label] == reader.manifest(reader.active_label)
+    assert "Reader(" in repr(reader)
+
+
+def test_mapping_protocol(asset):
+    expected = read_c2pa_from_bytes(asset, "image/jpeg")["manifests"]
+    reader = Reader.from_bytes(asset, "image/jpeg")
+
+    assert list(reader) == reader.keys() == list(expected)
+    assert reader.values() == list(expected.values())
+    assert reader.items() == list(expected.items())
+    assert {label: reader[label] for label in reader} == expected
+    assert dict(reader.items()) == reader.manifests()
+
+
+def test_unknown_label(asset):
+    reader = Reader.from_bytes(asset, "image/jpeg")
+    assert "urn:uuid:missing" not in reader
+    assert None not in reader
+    assert 1 not in reader
+    assert reader.manifest("urn:uuid:missing") is None
+    with pytest.raises(KeyError):
+        reader["urn:uuid:missing"]
+    for key in (None, 1, b"urn:uuid:missing"):
+        with pytest.raises(KeyError):
+            reader[key]
+
+
+def test_no_manifest():
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    assert Reader.from_bytes(data, "image/png") is None
+
+
+def test_results_are_copies(asset):
+    reader = Reader.from_bytes(asset, "image/jpeg")
+    reader.manifest()["title"] = "changed"
+    reader.to_dict().clear()
+    assert reader.manifest()["title"] != "changed"
+    assert reader.labels()
+
+
+def test_shared_between_threads(asset):
+    reader = Reader.from_bytes(asset, "image/jpeg")
+    expected = reader.to_dict()
+
+    def query(_):
+        return reader.to_dict(), reader.manifest(), reader.validation_state
+
+    with ThreadPoolExecutor(max_workers=8) as pool:
+        results = list(pool.map(query, range(64)))
+    for result, manifest, state in results:
+        assert result == expected
+        assert manifest == expected["manifests"][expected["active_manifest"]]
+        assert state == expected["validation_state"]
+
+
+def test_c2pa_reader_from_path_and_bytes(asset):
+    from fast_c2pa_python import C2paReader, get_claim_thumbnail_fast
+
+    path = TEST_IMAGES_DIR / "adobe_firefly_image.jpg"
+    expected = read_c2pa_from_bytes(asset, "image/jpeg")
+    for reader in (C2paReader(path), C2paReader(str(path)), C2paReader(asset),
+                   C2paReader(bytearray(asset), "image/jpeg")):
+        assert reader.to_dict() == expected
+        assert reader.active_manifest() == expected["manifests"][expected["active_manifest"]]
+        assert reader.manifests() == expected["manifests"]
+        assert reader.validation_status() == expected.get("validation_status", [])
+        assert reader.thumbnail_bytes() == get_claim_thumbnail_fast(asset, "image/jpeg")[1]
+    assert C2paReader is Reader
+
+
+def test_c2pa_reader_get_assertion(asset):
+    from fast_c2pa_python import C2paReader
+
+    reader = C2paReader(asset, "image/jpeg")
+    manifest = reader.active_manifest()
+    assertion = manifest["assertions"][0]
+    assert reader.get_assertion(assertion["label"]) == assertion["data"]
+    assert reader.get_assertion(assertion["label"], reader.active_label) == assertion["data"]
+    assert reader.get_assertion("com.example.missing") is None
+    assert reader.get_assertion(assertion["label"], "urn:uuid:missing") is None
+
+
+def test_c2pa_reader_no_manifest():
+    from fast_c2pa_python import C2paReader, ManifestNotFoundError
+
+    with pytest.raises(ManifestNotFoundError):
+        C2paReader(TEST_IMAGES_DIR / "screenshot_noc2pa.png")
+    with pytest.raises(TypeError):
+        C2paReader(42)
+    with pytest.raises(FileNotFoundError):
+        C2paReader(TEST_IMAGES_DIR / "missing.jpg")
+
+
+def test_json_round_trip(asset):
+    reader = Reader.from_bytes(asset, "image/jpeg")
+    stored = json.loads(reader.to_json())
+    assert (stored["format"], stored["format_version"]) == ("fast_c2pa.reader", 1)
+    assert stored["mime_type"] == "image/jpeg" and stored["manifest_store"] is None
+
+    rehydrated = Reader.from_json(reader.to_json())
+    assert rehydrated.to_dict() == reader.to_dict()
+    assert rehydrated.to_json() == reader.to_json()
+    assert (rehydrated.active_label, rehydrated.validation_state) == (reader.active_label, reader.validation_state)
+    assert rehydrated.thumbnail_bytes() is None
+
+    with_store = Reader.from_json(reader.to_json(include_store=True).encode())
+    assert with_store.thumbnail_bytes() == reader.thumbnail_bytes()
+    assert with_store.to_json(include_store=True) == reader.to_json(include_store=True)
+
+    # Bare results, as stored before results had a format
+    bare = Reader.from_json(read_c2pa_json_from_bytes(asset, "image/jpeg"))
+    assert bare.to_dict() == reader.to_dict()
+
+
+def test_json_versions(asset):
+    stored = json.loads(Reader.from_bytes(asset, "image/jpeg").to_json())
+    with pytest.raises(ValueError, match="newer"):
+        Reader.from_json(json.dumps({**stored, "format_version": 2}))
+    with pytest.raises(ValueError, match="Reader JSON"):
+        Reader.from_json(json.dumps({"format": "other"}))
+    with pytest.raises(ValueError, match="Reader JSON"):
+        Reader.from_json("{")
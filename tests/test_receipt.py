@@ -0,0 +1,91 @@
+"""
+Tests for signed processing receipts.
+"""
+
+import hashlib
+import json
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    read_c2pa_from_bytes,
+    read_c2pa_from_file,
+    set_receipt_signer,
+    verify_receipt,
+)
+
+TEST_DIR = Path(__file__).parent
+IMAGE = TEST_DIR / "test_images" / "C.jpg"
+SIGNER = {
+    "alg": "es256",
+    "sign_cert": (TEST_DIR / "sign_cert" / "es256.pub").read_bytes(),
+    "private_key": (TEST_DIR / "sign_cert" / "es256.priv").read_text(),
+}
+
+
+@pytest.fixture
+def signer():
+    set_receipt_signer(SIGNER)
+    yield
+    set_receipt_signer(None)
+
+
+def canonical(value):
+    return json.dumps(value, sort_keys=True, separators=(",", ":"), ensure_ascii=False)
+
+
+def test_receipt(signer):
+    data = IMAGE.read_bytes()
+    result = read_c2pa_from_bytes(data, "image/jpeg", receipt=True)
+    receipt = result.pop("receipt")
+    assert receipt["alg"] == "es256"
+    assert receipt["certificate_chain"] == SIGNER["sign_cert"].decode()
+    assert verify_receipt(receipt)
+
+    payload = json.loads(receipt["payload"])
+    assert payload["version"] == 1
+    assert payload["input"] == {
+        "sha256": hashlib.sha256(data).hexdigest(),
+        "size": len(data),
+        "mime_type": "image/jpeg",
+    }
+    assert payload["result"] == {
+        "sha256": hashlib.sha256(canonical(result).encode()).hexdigest(),
+        "active_manifest": result["active_manifest"],
+        "validation_state": result["validation_state"],
+    }
+    assert set(payload["libraries"]) == {"fast_c2pa_python", "c2pa"}
+    assert payload["started_at"] <= payload["finished_at"]
+    assert payload["duration_ms"] >= 0
+
+    # Chunks and memory-mapped files hash the same input
+    chunks = [data[:1000], data[1000:]]
+    for other in (
+        read_c2pa_from_bytes(chunks, "image/jpeg", receipt=True),
+        read_c2pa_from_file(str(IMAGE), receipt=True),
+    ):
+        assert json.loads(other["receipt"]["payload"])["input"] == payload["input"]
+    assert "receipt" not in read_c2pa_from_bytes(data, "image/jpeg")
+
+
+def test_tampered_receipt(signer):
+    receipt = read_c2pa_from_file(str(IMAGE), receipt=True)["receipt"]
+    payload = json.loads(receipt["payload"])
+    payload["result"]["validation_state"] = "Trusted"
+    assert not verify_receipt(dict(receipt, payload=canonical(payload)))
+
+    with pytest.raises(ValueError, match="signature"):
+        verify_receipt(dict(receipt, signature=None))
+    with pytest.raises(ValueError, match="certificate"):
+        verify_receipt(dict(receipt, certificate_chain="not a certificate"))
+
+
+def test_receipt_without_signer():
+    set_receipt_signer(None)
+    with pytest.raises(ValueError, match="set_receipt_signer"):
+        read_c2pa_from_file(str(IMAGE), receipt=True)
+    with pytest.raises(ValueError, match="private_key"):
+        set_receipt_signer({"alg": "es256", "sign_cert": SIGNER["sign_cert"]})
+    with pytest.raises(ValueError, match="signer_config"):
+        set_receipt_signer({**SIGNER, "private_key": "not a key"})
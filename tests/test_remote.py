@@ -0,0 +1,143 @@
+"""
+Tests for fetching remote manifest stores.
+
+The remote asset is chatgpt_image.png without its caBX chunk, its manifest
+store served by a fetcher callback or a local HTTP server.
+"""
+
+import threading
+import time
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from fast_c2pa_python import (
+    configure_http,
+    read_c2pa_from_bytes,
+    read_c2pa_from_file,
+    set_remote_manifest_fetcher,
+    set_remote_reference,
+    verify_manifest_bytes,
+)
+
+URL = "https://manifests.example.com/chatgpt_image.c2pa"
+
+
+@pytest.fixture
+def png(read_image, split_manifest):
+    yield split_manifest(read_image("chatgpt_image.png"))
+    set_remote_manifest_fetcher(None)
+
+
+def test_fetch_from_given_url(png, tmp_path):
+    manifest, stripped = png
+    calls = []
+
+    def fetcher(url, timeout_ms):
+        calls.append((url, timeout_ms))
+        return manifest
+
+    set_remote_manifest_fetcher(fetcher)
+    result = read_c2pa_from_bytes(stripped, "image/png", fetch_remote_manifests=True,
+                                  remote_manifest_url=URL, remote_timeout_ms=500)
+    assert calls == [(URL, 500)]
+    expected = verify_manifest_bytes(manifest, stripped, "image/png")
+    assert result == {**expected, "remote_url": URL}
+    assert result["is_embedded"] is False
+
+    path = tmp_path / "stripped.png"
+    path.write_bytes(stripped)
+    assert read_c2pa_from_file(path, fetch_remote_manifests=True, remote_manifest_url=URL) == result
+    assert read_c2pa_from_bytes([stripped[:100], stripped[100:]], "image/png",
+                                fetch_remote_manifests=True, remote_manifest_url=URL) == result
+
+
+def test_fetch_from_xmp_reference(png):
+    manifest, stripped = png
+    pointed = set_remote_reference(stripped, "image/png", URL)
+    calls = []
+
+    def fetcher(url, timeout_ms):
+        calls.append((url, timeout_ms))
+        return manifest
+
+    set_remote_manifest_fetcher(fetcher)
+    assert read_c2pa_from_bytes(pointed, "image/png") is None
+    assert calls == []
+
+    result = read_c2pa_from_bytes(pointed, "image/png", fetch_remote_manifests=True)
+    assert calls == [(URL, None)]
+    assert result["is_embedded"] is False
+    assert result["remote_url"] == URL
+    assert result["active_manifest"] == verify_manifest_bytes(manifest, pointed, "image/png")["active_manifest"]
+
+
+def test_embedded_manifests_are_not_fetched(read_image, png):
+    set_remote_manifest_fetcher(lambda url, timeout_ms: pytest.fail("fetcher called"))
+    original = read_image("chatgpt_image.png")
+    result = read_c2pa_from_bytes(original, "image/png", fetch_remote_manifests=True,
+                                  remote_manifest_url=URL)
+    assert result["is_embedded"] is True
+
+
+def test_fetch_with_http_client(png):
+    manifest, stripped = png
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self):
+            if self.path == "/slow":
+                # The client has given up by the time this answers
+                time.sleep(1)
+                return
+            self.send_response(200)
+            self.end_headers()
+            self.wfile.write(manifest)
+
+        def log_message(self, *args):
+            pass
+
+    httpd = HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=httpd.serve_forever, daemon=True).start()
+    base = f"http://127.0.0.1:{httpd.server_address[1]}"
+    try:
+        configure_http(proxy=None, block_private_ips=False)
+        result = read_c2pa_from_bytes(stripped, "image/png", fetch_remote_manifests=True,
+                                      remote_manifest_url=f"{base}/manifest")
+        with pytest.raises(RuntimeError):
+            read_c2pa_from_bytes(stripped, "image/png", fetch_remote_manifests=True,
+                                 remote_manifest_url=f"{base}/slow", remote_timeout_ms=100)
+    finally:
+        httpd.shutdown()
+        configure_http()
+    assert result["remote_url"] == f"{base}/manifest"
+    assert [attempt["status"] for attempt in result["fetch_attempts"]] == [200]
+
+
+def test_fetcher_errors(png):
+    _, stripped = png
+    assert read_c2pa_from_bytes(stripped, "image/png", fetch_remote_manifests=True) is None
+
+    set_remote_manifest_fetcher(lambda url, timeout_ms: None)
+    assert read_c2pa_from_bytes(stripped, "image/png", fetch_remote_manifests=True,
+                                remote_manifest_url=URL) is None
+
+    def failing(url, timeout_ms):
+        raise ConnectionError("manifest repository unavailable")
+
+    set_remote_manifest_fetcher(failing)
+    with pytest.raises(ConnectionError):
+        read_c2pa_from_bytes(stripped, "image/png", fetch_remote_manifests=True,
+                             remote_manifest_url=URL)
+
+    set_remote_manifest_fetcher(lambda url, timeout_ms: 42)
+    with pytest.raises(TypeError):
+        read_c2pa_from_bytes(stripped, "image/png", fetch_remote_manifests=True,
+                             remote_manifest_url=URL)
+
+    set_remote_manifest_fetcher(lambda url, timeout_ms: b"not a manifest store")
+    with pytest.raises(RuntimeError):
+        read_c2pa_from_bytes(stripped, "image/png", fetch_remote_manifests=True,
+                             remote_manifest_url=URL)
+
+    with pytest.raises(TypeError):
+        set_remote_manifest_fetcher("not callable")
@@ -0,0 +1,102 @@
+"""
+Tests for repairing structural damage with repair_c2pa.
+
+The damaged assets are made from the test images: a superbox length is
+changed, or XMP packets are added.
+"""
+
+import struct
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, read_xmp, read_xmp_provenance, repair_c2pa
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+JPEG = (TEST_IMAGES_DIR / "icon_test.jpg").read_bytes()
+PNG = (TEST_IMAGES_DIR / "chatgpt_image_gray.png").read_bytes()
+
+
+def with_superbox_length(data, uuid, delta):
+    """`data` with the length of the first superbox described by `uuid` changed"""
+    pos = data.index(uuid) - 16
+    assert data[pos + 4:pos + 8] == b"jumb"
+    (length,) = struct.unpack(">I", data[pos:pos + 4])
+    return data[:pos] + struct.pack(">I", length + delta) + data[pos + 4:]
+
+
+def xmp_segment(provenance):
+    xmp = (
+        '<x:xmpmeta xmlns:x="adobe:ns:meta/">'
+        '<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">'
+        '<rdf:Description rdf:about="" xmlns:dcterms="http://purl.org/dc/terms/"'
+        f' dcterms:title="x" dcterms:provenance="{provenance}"/>'
+        '</rdf:RDF></x:xmpmeta>'
+    ).encode()
+    body = b"http://ns.adobe.com/xap/1.0/\x00" + xmp
+    return b"\xff\xe1" + struct.pack(">H", len(body) + 2) + body
+
+
+def test_nothing_to_repair():
+    for data, mime_type in ((JPEG, "image/jpeg"), (PNG, "image/png")):
+        assert repair_c2pa(data, mime_type) == {"data": data, "changes": []}
+
+
+@pytest.mark.parametrize("data,mime_type,uuid,label", [
+    (JPEG, "image/jpeg", b"c2ma", ""),
+    (JPEG, "image/jpeg", b"c2db", "/c2pa.databoxes"),
+    (PNG, "image/png", b"c2cs", "/c2pa.signature"),
+])
+def test_superbox_lengths(data, mime_type, uuid, label):
+    damaged = with_superbox_length(data, uuid, 100)
+    repaired = repair_c2pa(damaged, mime_type, allow_threads=True)
+    assert repaired["data"] == data
+    (change,) = repaired["changes"]
+    assert change["code"] == "jumbf.boxLength"
+    assert change["label"].startswith("c2pa/urn:")
+    assert change["label"].endswith(label) and change["label"].count("/") == 1 + label.count("/")
+    declared, actual = change["explanation"].split()[-3::2]
+    assert int(declared) == int(actual) + 100
+
+
+def test_duplicate_xmp():
+    first, second = xmp_segment("https://example.com/a.c2pa"), xmp_segment("https://example.com/b.c2pa")
+    damaged = JPEG[:2] + first + second + JPEG[2:]
+
+    repaired = repair_c2pa(damaged, "image/jpeg")
+    assert repaired["data"] == JPEG[:2] + first + JPEG[2:]
+    (change,) = repaired["changes"]
+    assert change["code"] == "xmp.duplicate"
+    assert "https://example.com/b.c2pa" in change["explanation"]
+    assert read_xmp_provenance(repaired["data"])["warnings"] == []
+
+
+def test_stale_sidecar_reference(tmp_path):
+    (tmp_path / "kept.c2pa").write_bytes(b"")
+    for pointer in ("kept.c2pa", "https://example.com/gone.c2pa"):
+        data = JPEG[:2] + xmp_segment(pointer) + JPEG[2:]
+        assert repair_c2pa(data, "image/jpeg", sidecar_dir=tmp_path)["changes"] == []
+
+    data = JPEG[:2] + xmp_segment("gone.c2pa") + JPEG[2:]
+    # Relative pointers are only checked against a sidecar directory
+    assert repair_c2pa(data, "image/jpeg")["changes"] == []
+    repaired = repair_c2pa(data, "image/jpeg", sidecar_dir=tmp_path)
+    (change,) = repaired["changes"]
+    assert change["code"] == "xmp.staleReference"
+    xmp = read_xmp(repaired["data"], "image/jpeg")
+    assert "provenance" not in xmp
+    assert 'dcterms:title="x"' in xmp
+    assert read_c2pa_from_bytes(repaired["data"], "image/jpeg")["manifests"] == \
+        read_c2pa_from_bytes(JPEG, "image/jpeg")["manifests"]
+
+
+def test_unsupported():
+    with pytest.raises(ValueError, match="only JPEG and PNG"):
+        repair_c2pa(b"data", "video/mp4")
+    # A content box running past the end of the store cannot be placed
+    description = JPEG.index(b"c2cl") - 8
+    (length,) = struct.unpack(">I", JPEG[description:description + 4])
+    pos = description + length
+    damaged = JPEG[:pos] + b"\xff\xff\xff\xf0" + JPEG[pos + 4:]
+    with pytest.raises(ValueError, match="runs past the end"):
+        repair_c2pa(damaged, "image/jpeg")
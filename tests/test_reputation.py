@@ -0,0 +1,62 @@
+"""
+Tests for signer reputation annotations.
+
+multipage.tif is signed with the c2pa-rs es256 test certificate, issued by the
+intermediate CA fingerprinted below.
+"""
+
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, set_signer_reputation
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+TEST_SIGNER = "6fb5eddb353a82fa8720b1d54a4925eaa20e128b10cc4b3fa4d3e9e920c04001"
+TEST_CA = "3c4b61503a62b9afdf593e9e01a1fbe91afa800b6f18cf51f9836a5b65f7376d"
+
+
+@pytest.fixture
+def asset():
+    yield (TEST_IMAGES_DIR / "multipage.tif").read_bytes()
+    set_signer_reputation(None)
+
+
+def signers(result):
+    return [m["signer_reputation"] for m in result["manifests"].values()]
+
+
+def test_unknown_signer(asset):
+    set_signer_reputation({})
+    result = read_c2pa_from_bytes(asset, "image/tiff")
+    assert signers(result) == [{"known": False, "cert_sha256": TEST_SIGNER}]
+
+
+def test_known_signer(asset):
+    set_signer_reputation({TEST_SIGNER.upper(): {"label": "newsroom", "score": 0.9}})
+    result = read_c2pa_from_bytes(asset, "image/tiff")
+    assert signers(result) == [{
+        "known": True, "cert_sha256": TEST_SIGNER, "chain_index": 0, "label": "newsroom", "score": 0.9,
+    }]
+
+
+def test_known_issuer(asset):
+    fingerprint = ":".join(TEST_CA[i:i + 2] for i in range(0, 64, 2))
+    set_signer_reputation({fingerprint: {"label": "test CA"}})
+    (signer,) = signers(read_c2pa_from_bytes(asset, "image/tiff"))
+    assert signer["known"] and signer["chain_index"] == 1
+    assert signer["cert_sha256"] == TEST_CA and signer["label"] == "test CA"
+
+
+def test_without_map(asset):
+    result = read_c2pa_from_bytes(asset, "image/tiff")
+    assert all("signer_reputation" not in m for m in result["manifests"].values())
+
+
+def test_invalid_maps():
+    with pytest.raises(ValueError):
+        set_signer_reputation({"not a fingerprint": {}})
+    with pytest.raises(TypeError):
+        set_signer_reputation({TEST_SIGNER: "newsroom"})
+    with pytest.raises(TypeError):
+        set_signer_reputation([TEST_SIGNER])
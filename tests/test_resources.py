@@ -0,0 +1,176 @@
+"""
+Tests for extracting resources referenced from manifests.
+
+icon_test.jpg is signed with the c2pa-rs test certificate and carries a claim
+generator icon (a 16x16 PNG). C.jpg has a 1024x683 claim thumbnail.
+chatgpt_image_gray.png was converted to grayscale after signing; its ingredient
+thumbnail is of the same picture.
+"""
+
+import pytest
+
+from fast_c2pa_python import (
+    compare_ingredient_thumbnails,
+    get_claim_thumbnail_fast,
+    get_generator_icons,
+    get_thumbnail_array,
+    read_c2pa_from_bytes,
+    read_c2pa_resources,
+    set_resource_limits,
+)
+
+
+def test_generator_icons(read_image):
+    data = read_image("icon_test.jpg")
+    icons = get_generator_icons(data, "image/jpeg")
+
+    assert len(icons) == 1
+    icon = icons[0]
+    assert icon["manifest"] == read_c2pa_from_bytes(data, "image/jpeg")["active_manifest"]
+    assert icon["name"] == "fast-c2pa-python tests"
+    assert icon["version"] == "1.0"
+    assert icon["format"] == "image/png"
+    assert icon["data"].startswith(b"\x89PNG\r\n\x1a\n")
+    assert icon["skipped"] is None
+
+
+def test_generator_icons_without_icons(read_image):
+    assert get_generator_icons(read_image("C.jpg"), "image/jpeg") == []
+
+
+def test_generator_icons_without_c2pa(read_image):
+    assert get_generator_icons(read_image("screenshot_noc2pa.png"), "image/png") == []
+
+
+def test_thumbnail_array(read_image):
+    np = pytest.importorskip("numpy")
+    pixels = get_thumbnail_array(read_image("C.jpg"), "image/jpeg")
+
+    assert isinstance(pixels, np.ndarray)
+    assert pixels.dtype == np.uint8
+    assert pixels.shape == (683, 1024, 3)
+    assert pixels.flags.writeable
+
+
+def test_thumbnail_array_without_thumbnail(read_image):
+    assert get_thumbnail_array(read_image("icon_test.jpg"), "image/jpeg") is None
+    assert get_thumbnail_array(b"not an image", "image/jpeg") is None
+
+
+def test_claim_thumbnail_fast(read_image):
+    data = read_image("C.jpg")
+    format, thumbnail = get_claim_thumbnail_fast(data, "image/jpeg")
+
+    (resource,) = read_c2pa_resources(data, "image/jpeg").values()
+    assert format == resource["format"] == "image/jpeg"
+    assert thumbnail == resource["data"]
+
+
+def test_claim_thumbnail_fast_without_thumbnail(read_image):
+    assert get_claim_thumbnail_fast(read_image("icon_test.jpg"), "image/jpeg") is None
+    assert get_claim_thumbnail_fast(read_image("screenshot_noc2pa.png"), "image/png") is None
+    assert get_claim_thumbnail_fast(b"not an image", "image/jpeg") is None
+
+
+def test_compare_ingredient_thumbnails(read_image):
+    data = read_image("chatgpt_image_gray.png")
+    result = read_c2pa_from_bytes(data, "image/png")
+    ingredient = result["manifests"][result["active_manifest"]]["ingredients"][0]
+
+    (comparison,) = compare_ingredient_thumbnails(data, "image/png", allow_threads=True)
+    assert comparison["label"] == ingredient["label"]
+    assert comparison["title"] == ingredient["title"]
+    assert comparison["relationship"] == ingredient["relationship"]
+    assert comparison["format"] == "image/jpeg"
+    assert comparison["distance"] <= 10
+    assert comparison["similarity"] == 1 - comparison["distance"] / 64
+    assert comparison["skipped"] is None
+    assert comparison["error"] is None
+
+
+def test_compare_ingredient_thumbnails_without_thumbnail(read_image):
+    assert compare_ingredient_thumbnails(read_image("adobe_firefly_image.jpg"), "image/jpeg") == []
+    assert compare_ingredient_thumbnails(read_image("screenshot_noc2pa.png"), "image/png") == []
+
+
+def test_read_resources(read_image):
+    data = read_image("C.jpg")
+    result = read_c2pa_from_bytes(data, "image/jpeg")
+    thumbnail = result["manifests"][result["active_manifest"]]["thumbnail"]
+
+    resources = read_c2pa_resources(data, "image/jpeg")
+    assert list(resources) == [thumbnail["identifier"]]
+    resource = resources[thumbnail["identifier"]]
+    assert resource["manifest"] == result["active_manifest"]
+    assert resource["format"] == thumbnail["format"] == "image/jpeg"
+    assert resource["data"].startswith(b"\xff\xd8")
+    assert resource["skipped"] is None
+
+    icon = get_generator_icons(read_image("icon_test.jpg"), "image/jpeg")[0]
+    resources = read_c2pa_resources(read_image("icon_test.jpg"), "image/jpeg")
+    assert [r["data"] for r in resources.values()] == [icon["data"]]
+    assert read_c2pa_resources(read_image("screenshot_noc2pa.png"), "image/png") == {}
+
+
+def test_read_ingredient_resources(read_image):
+    data = read_image("chatgpt_image.png")
+    result = read_c2pa_from_bytes(data, "image/png")
+    label = result["active_manifest"]
+    thumbnail = result["manifests"][label]["ingredients"][0]["thumbnail"]
+
+    resources = read_c2pa_resources(data, "image/png")
+    # The relative identifier is made absolute with the manifest's label
+    path = thumbnail["identifier"].removeprefix("self#jumbf=")
+    resource = resources[f"self#jumbf=/c2pa/{label}/{path}"]
+    assert resource["identifier"] == thumbnail["identifier"]
+    assert resource["data"].startswith(b"\xff\xd8")
+
+
+@pytest.fixture
+def resource_limits():
+    yield set_resource_limits
+    set_resource_limits()
+
+
+def test_generator_icons_resource_limits(read_image, resource_limits):
+    data = read_image("icon_test.jpg")
+    size = len(get_generator_icons(data, "image/jpeg")[0]["data"])
+
+    resource_limits(max_resource_bytes=size - 1)
+    icon = get_generator_icons(data, "image/jpeg")[0]
+    assert icon["data"] is None
+    assert icon["skipped"] == "max_resource_bytes"
+    assert icon["format"] == "image/png"
+
+    resource_limits(max_total_bytes=size - 1)
+    assert get_generator_icons(data, "image/jpeg")[0]["skipped"] == "max_total_bytes"
+
+    resource_limits(max_resource_bytes=size, max_total_bytes=size)
+    assert len(get_generator_icons(data, "image/jpeg")[0]["data"]) == size
+
+
+def test_thumbnail_array_resource_limit(read_image, resource_limits):
+    resource_limits(max_resource_bytes=1000)
+    with pytest.raises(ValueError):
+        get_thumbnail_array(read_image("C.jpg"), "image/jpeg")
+
+
+def test_claim_thumbnail_fast_resource_limit(read_image, resource_limits):
+    resource_limits(max_resource_bytes=1000)
+    with pytest.raises(ValueError, match="max_resource_bytes"):
+        get_claim_thumbnail_fast(read_image("C.jpg"), "image/jpeg")
+
+
+def test_compare_ingredient_thumbnails_resource_limit(read_image, resource_limits):
+    resource_limits(max_resource_bytes=100)
+    (comparison,) = compare_ingredient_thumbnails(read_image("chatgpt_image_gray.png"), "image/png")
+    assert comparison["distance"] is None
+    assert comparison["similarity"] is None
+    assert comparison["skipped"] == "max_resource_bytes"
+
+
+def test_read_resources_limits(read_image, resource_limits):
+    resource_limits(max_resource_bytes=1000)
+    (resource,) = read_c2pa_resources(read_image("C.jpg"), "image/jpeg").values()
+    assert resource["data"] is None
+    assert resource["skipped"] == "max_resource_bytes"
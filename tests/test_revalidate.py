@@ -0,0 +1,66 @@
+"""
+Tests for re-checking the hard bindings of a previously read manifest store.
+"""
+
+import json
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    UnsupportedFormatError,
+    read_c2pa_from_bytes,
+    revalidate,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+def edited(data, offset):
+    changed = bytearray(data)
+    changed[offset] ^= 0xFF
+    return bytes(changed)
+
+
+def test_data_hash():
+    data = (TEST_IMAGES_DIR / "C.jpg").read_bytes()
+    result = read_c2pa_from_bytes(data, "image/jpeg", detailed=True)
+
+    checked = revalidate(result, data, "image/jpeg")
+    assert checked == {
+        "active_manifest": result["active_manifest"],
+        "validation_state": "Valid",
+        "bindings": [
+            {"label": "c2pa.hash.data", "code": "assertion.dataHash.match", "explanation": None}
+        ],
+    }
+    # The detailed report alone, as a JSON string, works as well
+    assert revalidate(json.dumps(result["detailed"]), data, "image/jpeg") == checked
+
+    changed = revalidate(result, edited(data, len(data) - 10), "image/jpeg")
+    assert changed["validation_state"] == "Invalid"
+    assert changed["bindings"][0]["code"] == "assertion.dataHash.mismatch"
+    assert changed["bindings"][0]["explanation"]
+
+
+def test_bmff_hash():
+    data = (TEST_IMAGES_DIR / "sequence.heic").read_bytes()
+    result = read_c2pa_from_bytes(data, "image/heic", detailed=True)
+
+    checked = revalidate(result, data, "image/heic")
+    assert checked["validation_state"] == "Valid"
+    assert [b["code"] for b in checked["bindings"]] == ["assertion.bmffHash.match"]
+
+    changed = revalidate(result, edited(data, len(data) - 10), "image/heic")
+    assert [b["code"] for b in changed["bindings"]] == ["assertion.bmffHash.mismatch"]
+
+
+def test_needs_detailed_report():
+    data = (TEST_IMAGES_DIR / "C.jpg").read_bytes()
+    result = read_c2pa_from_bytes(data, "image/jpeg")
+    with pytest.raises(ValueError, match="detailed=True"):
+        revalidate(result, data, "image/jpeg")
+    with pytest.raises(ValueError, match="manifest_store_json"):
+        revalidate("not json", data, "image/jpeg")
+    with pytest.raises(UnsupportedFormatError):
+        revalidate(read_c2pa_from_bytes(data, "image/jpeg", detailed=True), data, "text/plain")
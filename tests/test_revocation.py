@@ -0,0 +1,112 @@
+"""
+Tests for the OCSP response cache, against a local proxy standing in for the
+responders.
+"""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    clear_ocsp_cache,
+    configure_http,
+    get_cache_stats,
+    get_ocsp_cache_stats,
+    load_c2pa_settings,
+    read_c2pa_from_bytes,
+    set_ocsp_cache,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+class Responder(BaseHTTPRequestHandler):
+    requests = []
+
+    def do_GET(self):
+        Responder.requests.append(self.path)
+        self.send_response(200)
+        self.send_header("Content-Type", "application/ocsp-response")
+        self.end_headers()
+        self.wfile.write(b"\x30\x03\x0a\x01\x06")  # unauthorized
+
+    def log_message(self, *args):
+        pass
+
+
+@pytest.fixture
+def asset():
+    # Time stamped, with an OCSP responder in its signing certificate
+    return (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+
+
+@pytest.fixture
+def responder():
+    httpd = HTTPServer(("127.0.0.1", 0), Responder)
+    thread = threading.Thread(target=httpd.serve_forever, daemon=True)
+    thread.start()
+    Responder.requests = []
+    configure_http(proxy=f"http://127.0.0.1:{httpd.server_address[1]}", block_private_ips=False)
+    clear_ocsp_cache()
+    load_c2pa_settings(json.dumps({"verify": {"ocsp_fetch": True}}))
+    yield Responder.requests
+    load_c2pa_settings(json.dumps({"verify": {"ocsp_fetch": False}}))
+    set_ocsp_cache()
+    clear_ocsp_cache()
+    httpd.shutdown()
+    configure_http()
+
+
+def test_defaults():
+    stats = get_ocsp_cache_stats()
+    assert (stats["ttl_seconds"], stats["max_entries"]) == (300, 1024)
+    assert get_cache_stats()["ocsp"] == stats
+
+
+def test_fetched_once(asset, responder):
+    before = get_ocsp_cache_stats()
+    first = read_c2pa_from_bytes(asset, "image/jpeg")
+    second = read_c2pa_from_bytes(asset, "image/jpeg")
+    assert first == second
+    assert len(responder) == 1
+    assert responder[0].startswith("http://pki-ocsp.symauth.com/")
+    stats = get_ocsp_cache_stats()
+    assert stats["entries"] == 1
+    assert stats["misses"] == before["misses"] + 1
+    assert stats["hits"] == before["hits"] + 1
+
+
+def test_clear_fetches_again(asset, responder):
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    clear_ocsp_cache()
+    assert get_ocsp_cache_stats()["entries"] == 0
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    assert len(responder) == 2
+
+
+def test_expired(asset, responder):
+    set_ocsp_cache(ttl_seconds=0)
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    # c2pa-rs fetches the response itself, outside the cache
+    assert responder == []
+    assert get_ocsp_cache_stats()["entries"] == 0
+
+
+def test_max_entries(asset, responder):
+    set_ocsp_cache(max_entries=1)
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    # Another signer, whose certificate names no responder
+    read_c2pa_from_bytes((TEST_IMAGES_DIR / "C.jpg").read_bytes(), "image/jpeg")
+    assert get_ocsp_cache_stats()["entries"] == 1
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    assert len(responder) == 2
+
+
+def test_fetching_disabled(asset, responder):
+    load_c2pa_settings(json.dumps({"verify": {"ocsp_fetch": False}}))
+    read_c2pa_from_bytes(asset, "image/jpeg")
+    assert responder == []
+    assert get_ocsp_cache_stats()["entries"] == 0
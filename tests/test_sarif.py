@@ -0,0 +1,76 @@
+"""
+Tests for rendering validation findings as SARIF.
+
+chatgpt_image.png is signed with an expired certificate, which c2pa-rs reports
+as a signingCredential.expired failure of the active manifest; C.jpg validates
+without failures.
+"""
+
+import json
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, to_sarif
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+def read_result(name, mime_type):
+    path = TEST_IMAGES_DIR / name
+    if not path.exists():
+        pytest.skip(f"Test image not found: {path}")
+    return read_c2pa_from_bytes(path.read_bytes(), mime_type)
+
+
+def test_sarif_log():
+    result = read_result("chatgpt_image.png", "image/png")
+    log = json.loads(to_sarif({"images/chatgpt_image.png": result}))
+
+    assert log["version"] == "2.1.0"
+    run = log["runs"][0]
+    assert run["tool"]["driver"]["name"] == "fast-c2pa-python"
+    assert run["artifacts"] == [{"location": {"uri": "images/chatgpt_image.png"}}]
+
+    failures = result["validation_results"]["activeManifest"]["failure"]
+    assert [r["ruleId"] for r in run["results"]] == [f["code"] for f in failures]
+    index = [f["code"] for f in failures].index("signingCredential.expired")
+    finding = run["results"][index]
+    assert finding["level"] == "error"
+    assert run["tool"]["driver"]["rules"][finding["ruleIndex"]]["id"] == finding["ruleId"]
+    location = finding["locations"][0]
+    assert location["physicalLocation"]["artifactLocation"]["uri"] == "images/chatgpt_image.png"
+    assert location["logicalLocations"][0]["fullyQualifiedName"] == failures[index]["url"]
+    assert finding["properties"]["manifest"] == result["active_manifest"]
+    assert finding["properties"]["validation_state"] == result["validation_state"]
+
+
+def test_sarif_success_and_missing():
+    result = read_result("C.jpg", "image/jpeg")
+    log = json.loads(to_sarif({"C.jpg": result, "plain.jpg": None}))
+    run = log["runs"][0]
+
+    assert [a["location"]["uri"] for a in run["artifacts"]] == ["C.jpg", "plain.jpg"]
+    assert all(r["level"] != "none" for r in run["results"])
+
+    log = json.loads(to_sarif({"C.jpg": result}, include_success=True))
+    passes = [r for r in log["runs"][0]["results"] if r["kind"] == "pass"]
+    successes = result["validation_results"]["activeManifest"]["success"]
+    assert len(passes) == len(successes)
+    assert all(r["level"] == "none" for r in passes)
+
+
+def test_sarif_warnings():
+    result = {
+        "validation_state": "Invalid",
+        "warnings": [{
+            "code": "asset.truncated",
+            "explanation": "asset is truncated inside the manifest store",
+        }],
+    }
+    finding = json.loads(to_sarif({"cut.jpg": result}))["runs"][0]["results"][0]
+
+    assert finding["ruleId"] == "asset.truncated"
+    assert finding["level"] == "warning"
+    assert finding["message"]["text"] == "asset is truncated inside the manifest store"
+    assert "logicalLocations" not in finding["locations"][0]
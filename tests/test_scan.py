@@ -0,0 +1,152 @@
+"""
+Tests for bulk scans written to a file.
+"""
+
+import csv
+import json
+import os
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_digest, read_c2pa_from_bytes, scan_to_file
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+IMAGES = ["C.jpg", "adobe_firefly_image.jpg", "screenshot_noc2pa.png"]
+
+
+def image_paths():
+    paths = [TEST_IMAGES_DIR / name for name in IMAGES]
+    for path in paths:
+        if not path.exists():
+            pytest.skip(f"Test image not found: {path}")
+    return paths
+
+
+def test_scan_jsonl(tmp_path):
+    paths = image_paths()
+    output = tmp_path / "scan.jsonl"
+
+    counts = scan_to_file(paths, output)
+
+    assert counts == {"assets": 3, "with_c2pa": 2, "errors": 0}
+    records = [json.loads(line) for line in output.read_text().splitlines()]
+    assert [r["path"] for r in records] == [str(p) for p in paths]
+    expected = read_c2pa_from_bytes(paths[0].read_bytes(), "image/jpeg")
+    assert records[0]["result"]["active_manifest"] == expected["active_manifest"]
+    assert records[0]["result"]["validation_state"] == expected["validation_state"]
+    assert records[2]["result"] is None
+    assert all(r["error"] is None for r in records)
+
+
+def test_scan_jsonl_digest(tmp_path):
+    paths = image_paths()
+    output = tmp_path / "scan.jsonl"
+
+    scan_to_file((str(p) for p in paths), output, fields=["signer", "ai_flag", "has_c2pa"])
+
+    records = [json.loads(line) for line in output.read_text().splitlines()]
+    assert list(records[0]) == ["path", "signer", "ai_flag", "has_c2pa", "error"]
+    for path, record in zip(paths, records):
+        mime_type = "image/png" if path.suffix == ".png" else "image/jpeg"
+        digest = read_c2pa_digest(path.read_bytes(), mime_type, ["signer", "ai_flag", "has_c2pa"])
+        assert {k: record[k] for k in digest} == digest
+
+
+def test_scan_csv(tmp_path):
+    paths = image_paths()
+    output = tmp_path / "scan.csv"
+
+    scan_to_file(paths, output, format="csv")
+
+    with open(output, newline="") as f:
+        rows = list(csv.reader(f))
+    assert rows[0] == ["path", "signer", "generator", "ai_flag", "error"]
+    assert len(rows) == 4
+    assert rows[2][0] == str(paths[1])
+    assert rows[2][3] == "true"
+    assert rows[3][1:] == ["", "", "", ""]
+
+
+def test_scan_detects_mime_types(tmp_path):
+    # Content wins over a wrong or missing extension, as with read_c2pa_from_file
+    data = (TEST_IMAGES_DIR / "C.jpg").read_bytes()
+    paths = [tmp_path / "C.png", tmp_path / "C"]
+    for path in paths:
+        path.write_bytes(data)
+    output = tmp_path / "scan.jsonl"
+
+    assert scan_to_file(paths, output) == {"assets": 2, "with_c2pa": 2, "errors": 0}
+    expected = read_c2pa_from_bytes(data, "image/jpeg")
+    for line in output.read_text().splitlines():
+        record = json.loads(line)
+        assert record["error"] is None
+        assert record["result"]["active_manifest"] == expected["active_manifest"]
+
+
+def test_scan_records_errors(tmp_path):
+    output = tmp_path / "scan.jsonl"
+
+    counts = scan_to_file([tmp_path / "missing.jpg"], output, fields=["has_c2pa"])
+
+    assert counts == {"assets": 1, "with_c2pa": 0, "errors": 1}
+    record = json.loads(output.read_text())
+    assert record["has_c2pa"] is None
+    assert "FileNotFoundError" in record["error"]
+
+
+def test_scan_invalid_arguments(tmp_path):
+    with pytest.raises(ValueError):
+        scan_to_file([], tmp_path / "scan.xml", format="xml")
+    with pytest.raises(ValueError):
+        scan_to_file([], tmp_path / "scan.csv", format="csv", fields=["colour"])
+
+
+def test_scan_index(tmp_path):
+    archive = tmp_path / "archive"
+    archive.mkdir()
+    for name in IMAGES:
+        (archive / name).write_bytes((TEST_IMAGES_DIR / name).read_bytes())
+    image_paths()
+    output, index = tmp_path / "scan.jsonl", tmp_path / "scan.index"
+
+    counts = scan_to_file(sorted(archive.iterdir()), output, index=index)
+    assert counts == {
+        "assets": 3, "with_c2pa": 2, "errors": 0, "new": 3, "modified": 0, "unchanged": 0, "removed": 0,
+    }
+    first = [json.loads(line) for line in output.read_text().splitlines()]
+    assert [r["change"] for r in first] == ["new"] * 3
+
+    # Touched but not changed, changed, removed and added
+    c, firefly, screenshot = sorted(archive.iterdir())
+    os.utime(c, ns=(0, 10**9))
+    firefly.write_bytes((TEST_IMAGES_DIR / "C.jpg").read_bytes())
+    screenshot.unlink()
+    (archive / "copy.jpg").write_bytes(c.read_bytes())
+
+    counts = scan_to_file(sorted(archive.iterdir()), output, index=index)
+    assert counts == {
+        "assets": 3, "with_c2pa": 3, "errors": 0, "new": 1, "modified": 1, "unchanged": 1, "removed": 1,
+    }
+    second = {r["path"]: r for r in map(json.loads, output.read_text().splitlines())}
+    assert second[str(c)]["change"] == "unchanged"
+    assert second[str(c)]["result"] == first[0]["result"]
+    assert second[str(firefly)]["change"] == "modified"
+    assert second[str(firefly)]["result"]["active_manifest"] == first[0]["result"]["active_manifest"]
+    assert second[str(archive / "copy.jpg")]["change"] == "new"
+
+    # A digest scan with the index of a full scan still reports the changes
+    csv_output = tmp_path / "scan.csv"
+    counts = scan_to_file(sorted(archive.iterdir()), csv_output, format="csv", index=index)
+    assert (counts["unchanged"], counts["removed"]) == (3, 0)
+    with open(csv_output, newline="") as f:
+        rows = list(csv.reader(f))
+    assert rows[0] == ["path", "signer", "generator", "ai_flag", "change", "error"]
+    assert [row[4] for row in rows[1:]] == ["unchanged"] * 3
+
+
+def test_scan_invalid_index(tmp_path):
+    index = tmp_path / "scan.index"
+    index.write_text("not an index\n")
+    with pytest.raises(ValueError, match="scan index"):
+        scan_to_file([], tmp_path / "scan.jsonl", index=index)
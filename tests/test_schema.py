@@ -0,0 +1,76 @@
+"""
+Tests for versioned output schemas.
+"""
+
+import json
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    read_c2pa_encoded_from_bytes,
+    read_c2pa_from_bytes,
+    read_c2pa_from_file,
+    read_c2pa_json_from_bytes,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+MANIFEST_KEYS = {
+    "label", "vendor", "title", "format", "instance_id", "claim_generator", "claim_generator_info",
+    "metadata", "thumbnail", "ingredients", "assertions", "redactions", "credentials",
+    "signature_info", "resources",
+}
+
+
+@pytest.mark.parametrize("name", ["adobe_firefly_image.jpg", "chatgpt_image.png"])
+def test_v1_layout(name):
+    result = read_c2pa_from_file(str(TEST_IMAGES_DIR / name), schema_version=1)
+    assert result["schema_version"] == 1
+    assert {
+        "active_manifest", "manifests", "validation_state", "validation_status", "validation_results",
+        "is_embedded", "remote_url",
+    } <= set(result)
+    for manifest in result["manifests"].values():
+        assert MANIFEST_KEYS <= set(manifest)
+        assert set(manifest["signature_info"]) >= {"alg", "issuer", "cert_serial_number", "time"}
+        assert isinstance(manifest["redactions"], list)
+
+
+def test_v1_claim_generator():
+    # A v2 claim only has claim_generator_info
+    data = (TEST_IMAGES_DIR / "chatgpt_image.png").read_bytes()
+    raw = read_c2pa_from_bytes(data, "image/png")
+    result = read_c2pa_from_bytes(data, "image/png", schema_version=1)
+    active = result["active_manifest"]
+    assert "claim_generator" not in raw["manifests"][active]
+    info = raw["manifests"][active]["claim_generator_info"][0]
+    assert result["manifests"][active]["claim_generator"].startswith(info["name"])
+
+
+def test_values_kept():
+    data = (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+    raw = read_c2pa_from_bytes(data, "image/jpeg")
+    result = read_c2pa_from_bytes(data, "image/jpeg", schema_version=1)
+    for label, manifest in raw["manifests"].items():
+        for key, value in manifest.items():
+            assert result["manifests"][label][key] == value
+    assert read_c2pa_from_bytes(data, "image/jpeg", schema_version=None) == raw
+
+
+def test_serialized_outputs():
+    data = (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+    expected = read_c2pa_from_bytes(data, "image/jpeg", schema_version=1)
+    as_json = json.loads(read_c2pa_json_from_bytes(data, "image/jpeg", schema_version=1))
+    encoded = json.loads(read_c2pa_encoded_from_bytes(data, "image/jpeg", format="json", schema_version=1))
+    assert as_json["schema_version"] == encoded["schema_version"] == 1
+    active = expected["active_manifest"]
+    assert set(as_json["manifests"][active]) == set(encoded["manifests"][active]) == set(
+        expected["manifests"][active]
+    )
+
+
+def test_unsupported_version():
+    with pytest.raises(ValueError):
+        read_c2pa_from_bytes(b"", "image/jpeg", schema_version=99)
+    with pytest.raises(ValueError):
+        read_c2pa_json_from_bytes(b"", "image/jpeg", schema_version=0)
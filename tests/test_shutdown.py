@@ -0,0 +1,72 @@
+"""
+Tests for shutting down the library's background work.
+"""
+
+import socket
+import threading
+import time
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    IoError,
+    configure_http,
+    enable_shared_cache,
+    fetch_url,
+    get_shared_cache_stats,
+    read_c2pa_from_bytes,
+    shutdown,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+@pytest.fixture
+def closed_port():
+    """A local port nothing listens on, so connections are refused at once"""
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        port = s.getsockname()[1]
+    yield port
+    configure_http()
+
+
+def test_shutdown_closes_the_cache(tmp_path):
+    asset = (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+    cache_path = tmp_path / "results.cache"
+    enable_shared_cache(str(cache_path), size_mb=4)
+    expected = read_c2pa_from_bytes(asset, "image/jpeg")
+
+    assert shutdown(timeout_ms=1000) is True
+    assert get_shared_cache_stats() is None
+    assert cache_path.exists()
+
+    # Usable again afterwards, with the results of the closed cache kept
+    assert read_c2pa_from_bytes(asset, "image/jpeg") == expected
+    enable_shared_cache(str(cache_path), size_mb=4)
+    assert read_c2pa_from_bytes(asset, "image/jpeg") == expected
+    assert get_shared_cache_stats()["hits"] == 1
+    shutdown()
+
+
+def test_shutdown_cancels_retries(closed_port):
+    configure_http(max_attempts=5, backoff_ms=30000, jitter=False, block_private_ips=False)
+    errors = []
+
+    def fetch():
+        try:
+            fetch_url(f"http://127.0.0.1:{closed_port}/manifest.c2pa")
+        except IoError as e:
+            errors.append(e)
+
+    thread = threading.Thread(target=fetch)
+    start = time.monotonic()
+    thread.start()
+    time.sleep(0.2)
+    assert shutdown(timeout_ms=1000) is True
+    thread.join(5)
+
+    assert not thread.is_alive()
+    assert time.monotonic() - start < 5
+    assert "cancelled by shutdown" in str(errors[0])
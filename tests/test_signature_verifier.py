@@ -0,0 +1,103 @@
+"""
+Tests for claim signatures verified by a Python callback.
+"""
+
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    extract_manifest_store,
+    read_c2pa_from_bytes,
+    set_signature_verifier,
+    verify_manifest_bytes,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+JPG = (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+PNG = (TEST_IMAGES_DIR / "chatgpt_image.png").read_bytes()
+
+
+@pytest.fixture
+def verifier():
+    yield set_signature_verifier
+    set_signature_verifier(None)
+
+
+def statuses(result, key):
+    return result["validation_results"]["activeManifest"][key]
+
+
+def test_verifier_receives_signature(verifier):
+    pytest.importorskip("cryptography")
+    from cryptography import x509
+    from cryptography.hazmat.primitives import hashes
+    from cryptography.hazmat.primitives.asymmetric import padding
+
+    calls = []
+
+    def verify(label, alg, tbs_data, signature, certificates):
+        calls.append((label, alg))
+        key = x509.load_der_x509_certificate(certificates[0]).public_key()
+        key.verify(signature, tbs_data, padding.PSS(padding.MGF1(hashes.SHA256()), 32),
+                   hashes.SHA256())
+        return True
+
+    verifier(verify)
+    result = read_c2pa_from_bytes(JPG, "image/jpeg")
+    label = result["active_manifest"]
+    assert calls == [(label, "ps256")]
+    assert result["manifests"][label]["signature_verification"] == {
+        "verified_by": "callback", "valid": True,
+    }
+    validated = [s for s in statuses(result, "success") if s["code"] == "claimSignature.validated"]
+    assert len(validated) == 1
+    assert "callback" in validated[0]["explanation"]
+    assert result["validation_state"] == read_c2pa_from_bytes(JPG, "image/jpeg")["validation_state"]
+
+
+def test_rejection_replaces_verdict(verifier):
+    verifier(lambda *args: False)
+    result = read_c2pa_from_bytes(JPG, "image/jpeg")
+    label = result["active_manifest"]
+    assert result["validation_state"] == "Invalid"
+    assert "claimSignature.validated" not in {s["code"] for s in statuses(result, "success")}
+    mismatch = [s for s in statuses(result, "failure") if s["code"] == "claimSignature.mismatch"]
+    assert [s["url"] for s in mismatch] == [f"self#jumbf=/c2pa/{label}/c2pa.signature"]
+    assert result["manifests"][label]["signature_verification"]["valid"] is False
+
+    manifest_bytes = extract_manifest_store(JPG, "image/jpeg")
+    verified = verify_manifest_bytes(manifest_bytes, JPG, "image/jpeg")
+    assert verified["validation_state"] == "Invalid"
+
+
+def test_ingredient_rejection_warns(verifier):
+    baseline = read_c2pa_from_bytes(PNG, "image/png")
+    active = baseline["active_manifest"]
+    verifier(lambda label, *args: None if label == active else False)
+
+    result = read_c2pa_from_bytes(PNG, "image/png")
+    assert result["validation_results"] == baseline["validation_results"]
+    assert "signature_verification" not in result["manifests"][active]
+    ingredient = next(label for label in result["manifests"] if label != active)
+    assert result["warnings"] == [{
+        "code": "claimSignature.mismatch",
+        "label": ingredient,
+        "explanation": f"the signature verifier rejected the claim signature of {ingredient}",
+    }]
+
+
+def test_invalid_verifiers(verifier):
+    with pytest.raises(TypeError):
+        verifier("not callable")
+
+    verifier(lambda *args: "yes")
+    with pytest.raises(TypeError, match="True, False or None"):
+        read_c2pa_from_bytes(JPG, "image/jpeg")
+
+    def unavailable(*args):
+        raise ConnectionError("verification service unavailable")
+
+    verifier(unavailable)
+    with pytest.raises(ConnectionError, match="verification service"):
+        read_c2pa_from_bytes(JPG, "image/jpeg")
@@ -0,0 +1,107 @@
+"""
+Tests for reporting every signer of a manifest and its certificate chain.
+"""
+
+import hashlib
+import ssl
+
+import pytest
+
+from fast_c2pa_python import (
+    ManifestNotFoundError,
+    get_manifest_signers,
+    read_c2pa_from_bytes,
+    read_signing_info,
+)
+
+
+# The c2pa-rs es256 test signing certificate and its intermediate
+LEAF_SHA256 = "6fb5eddb353a82fa8720b1d54a4925eaa20e128b10cc4b3fa4d3e9e920c04001"
+INTERMEDIATE_SHA256 = "3c4b61503a62b9afdf593e9e01a1fbe91afa800b6f18cf51f9836a5b65f7376d"
+
+
+def test_claim_signer_matches_signature_info(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    signers = get_manifest_signers(data, "image/jpeg")
+    metadata = read_c2pa_from_bytes(data, "image/jpeg")
+
+    assert signers["active_manifest"] == metadata["active_manifest"]
+    assert signers["manifests"].keys() == metadata["manifests"].keys()
+    claim = signers["manifests"][signers["active_manifest"]][0]
+    info = metadata["manifests"][metadata["active_manifest"]]["signature_info"]
+    assert claim["role"] == "claim"
+    assert claim["alg"] == info["alg"]
+    assert claim["organization"] == info["issuer"]
+    assert claim["cert_serial_number"] == info["cert_serial_number"]
+
+
+def test_time_stamp_authority(read_image):
+    signers = get_manifest_signers(read_image("adobe_firefly_image.jpg"), "image/jpeg")
+    claim, time_stamp = signers["manifests"][signers["active_manifest"]]
+
+    assert time_stamp["role"] == "time_stamp"
+    assert time_stamp["header"] == "sigTst"
+    assert time_stamp["issuer_organization"] == "DigiCert, Inc."
+    assert time_stamp["cert_sha256"] == time_stamp["chain_sha256"][0]
+    assert time_stamp["cert_sha256"] != claim["cert_sha256"]
+    assert "alg" not in time_stamp
+
+
+def test_chain_order(read_image):
+    signers = get_manifest_signers(read_image("multipage.tif"), "image/tiff")
+    (claim,) = signers["manifests"][signers["active_manifest"]]
+    assert claim["cert_sha256"] == LEAF_SHA256
+    assert claim["chain_sha256"] == [LEAF_SHA256, INTERMEDIATE_SHA256]
+    assert claim["issuer_organization"] != claim["organization"]
+
+
+def test_every_manifest(read_image):
+    signers = get_manifest_signers(read_image("chatgpt_image.png"), "image/png")
+    assert len(signers["manifests"]) == 2
+    for manifest_signers in signers["manifests"].values():
+        assert [s["role"] for s in manifest_signers] == ["claim"]
+
+
+def test_no_manifest(read_image):
+    assert get_manifest_signers(read_image("screenshot_noc2pa.png"), "image/png") is None
+
+
+def test_signing_info_matches_signature_info(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    info = read_signing_info(data, "image/jpeg")
+    metadata = read_c2pa_from_bytes(data, "image/jpeg")
+    signature_info = metadata["manifests"][metadata["active_manifest"]]["signature_info"]
+
+    assert info["manifest"] == metadata["active_manifest"]
+    for key in ("alg", "issuer", "cert_serial_number"):
+        assert info[key] == signature_info[key]
+    assert info["time"] is not None
+    assert info["common_name"] == "Adobe Firefly C2PA"
+    assert info["chain"][0]["signature_algorithm"] == "sha256WithRSAEncryption"
+
+
+def test_signing_certificate_chain(read_image):
+    info = read_signing_info(read_image("multipage.tif"), "image/tiff")
+    leaf, intermediate = info["chain"]
+
+    assert [leaf["sha256"], intermediate["sha256"]] == [LEAF_SHA256, INTERMEDIATE_SHA256]
+    assert leaf["issuer"] == intermediate["subject"]
+    assert (leaf["is_ca"], intermediate["is_ca"]) == (False, True)
+    assert (info["not_before"], info["not_after"]) == (leaf["not_before"], leaf["not_after"])
+    assert leaf["not_after"] == "2030-08-26T18:46:40Z"
+    assert leaf["serial_number"] == info["cert_serial_number"]
+    assert leaf["signature_algorithm"] == "ecdsa-with-SHA256"
+    assert hashlib.sha256(ssl.PEM_cert_to_DER_cert(leaf["pem"])).hexdigest() == LEAF_SHA256
+
+
+def test_signing_info_of_ingredient_manifest(read_image):
+    data = read_image("chatgpt_image.png")
+    metadata = read_c2pa_from_bytes(data, "image/png")
+    (label,) = set(metadata["manifests"]) - {metadata["active_manifest"]}
+    assert read_signing_info(data, "image/png", manifest_label=label)["manifest"] == label
+    with pytest.raises(ManifestNotFoundError):
+        read_signing_info(data, "image/png", manifest_label="urn:uuid:unknown")
+
+
+def test_signing_info_without_manifest(read_image):
+    assert read_signing_info(read_image("screenshot_noc2pa.png"), "image/png") is None
@@ -0,0 +1,452 @@
+"""
+Tests for signing manifests and embedding them in assets.
+
+The es256 and ed25519 certificate chains and keys are the test fixtures of c2pa-rs.
+"""
+
+import hashlib
+import io
+import json
+import shutil
+import struct
+import threading
+import wave
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from pathlib import Path
+
+import pytest
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec
+
+from fast_c2pa_python import (
+    CallbackSigner,
+    IoError,
+    SigningError,
+    add_ingredient_from_url,
+    capabilities,
+    configure_http,
+    create_ingredient_from_bytes,
+    read_c2pa_from_bytes,
+    sign_and_embed,
+    sign_with_manifest_file,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+CERT_DIR = Path(__file__).parent / "sign_cert"
+
+SIGNER = {
+    "alg": "es256",
+    "sign_cert": (CERT_DIR / "es256.pub").read_bytes(),
+    "private_key": (CERT_DIR / "es256.priv").read_text(),
+}
+
+ED25519_SIGNER = {
+    "alg": "ed25519",
+    "sign_cert": (CERT_DIR / "ed25519.pub").read_text(),
+    "private_key": (CERT_DIR / "ed25519.priv").read_text(),
+}
+
+MANIFEST = json.dumps({
+    "claim_generator_info": [{"name": "test-suite", "version": "1.0"}],
+    "title": "signed.png",
+    "assertions": [
+        {"label": "c2pa.actions", "data": {"actions": [{"action": "c2pa.created"}]}},
+    ],
+})
+
+# Four silent MPEG-1 Layer III frames
+MP3_AUDIO = (b"\xff\xfb\x90\x64" + bytes(413)) * 4
+
+
+def test_sign_unsigned_asset():
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    assert read_c2pa_from_bytes(data, "image/png") is None
+
+    signed = sign_and_embed(data, "image/png", MANIFEST, SIGNER)
+    result = read_c2pa_from_bytes(signed, "image/png")
+    manifest = result["manifests"][result["active_manifest"]]
+    assert manifest["title"] == "signed.png"
+    assert manifest["claim_generator_info"][0]["name"] == "test-suite"
+    actions = [a for a in manifest["assertions"] if a["label"].startswith("c2pa.actions")]
+    assert actions[0]["data"]["actions"][0]["action"] == "c2pa.created"
+    # The hard binding covers the asset: no data hash failures
+    failures = result["validation_results"]["activeManifest"]["failure"]
+    assert not [f for f in failures if f["code"].startswith("assertion.dataHash")]
+
+
+def test_sign_replaces_manifest_store():
+    data = (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+    original = read_c2pa_from_bytes(data, "image/jpeg")
+
+    signed = sign_and_embed(
+        data, "image/jpeg", MANIFEST, {**SIGNER, "alg": "ES256"}, allow_threads=True
+    )
+    result = read_c2pa_from_bytes(signed, "image/jpeg")
+    assert result["active_manifest"] != original["active_manifest"]
+    assert list(result["manifests"]) == [result["active_manifest"]]
+
+
+def wav_tone():
+    out = io.BytesIO()
+    with wave.open(out, "wb") as w:
+        w.setnchannels(1)
+        w.setsampwidth(2)
+        w.setframerate(8000)
+        w.writeframes(struct.pack("<800h", *(i * 40 - 16000 for i in range(800))))
+    return out.getvalue()
+
+
+def mp3_with_title():
+    title = b"TIT2" + struct.pack(">IH", 6, 0) + b"\x00Tone\x00"
+    return b"ID3\x03\x00\x00" + bytes([0, 0, 0, len(title)]) + title + MP3_AUDIO
+
+
+@pytest.mark.parametrize("mime_type,make", [("audio/wav", wav_tone), ("audio/mpeg", mp3_with_title)])
+def test_sign_audio(mime_type, make):
+    # WAV stores the manifest in a RIFF chunk, MP3 in an ID3 GEOB frame; the
+    # samples, and the other ID3 frames, are kept
+    data = make()
+    signed = sign_and_embed(data, mime_type, MANIFEST, SIGNER)
+    result = read_c2pa_from_bytes(signed, mime_type)
+    assert result["validation_state"] == "Valid"
+    failures = result["validation_results"]["activeManifest"]["failure"]
+    assert not [f for f in failures if f["code"].startswith("assertion.dataHash")]
+    if mime_type == "audio/wav":
+        with wave.open(io.BytesIO(signed)) as signed_wav, wave.open(io.BytesIO(data)) as original:
+            assert signed_wav.readframes(800) == original.readframes(800)
+    else:
+        assert signed.startswith(b"ID3") and b"TIT2" in signed
+        assert signed.endswith(MP3_AUDIO)
+
+    resigned = read_c2pa_from_bytes(sign_and_embed(signed, mime_type, MANIFEST, SIGNER), mime_type)
+    assert resigned["active_manifest"] != result["active_manifest"]
+    assert list(resigned["manifests"]) == [resigned["active_manifest"]]
+
+
+def gif_frame(delay):
+    control = b"\x21\xf9\x04\x04" + struct.pack("<H", delay) + b"\x00\x00"
+    return control + b"\x2c" + struct.pack("<4H", 0, 0, 1, 1) + b"\x00\x02\x02\x44\x01\x00"
+
+
+def test_sign_gif():
+    # An animation with a loop count and a comment: the manifest store goes in
+    # one C2PA_GIF application extension, placed before the frames and
+    # replaced, not added to, when signing again
+    frames = gif_frame(10) + gif_frame(20) + b"\x3b"
+    data = (
+        b"GIF89a" + struct.pack("<HH", 1, 1) + b"\x80\x00\x00" + b"\x00\x00\x00\xff\xff\xff"
+        + b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00"
+        + b"\x21\xfe\x05hello\x00"
+        + frames
+    )
+    signed = sign_and_embed(data, "image/gif", MANIFEST, SIGNER)
+    result = read_c2pa_from_bytes(signed, "image/gif")
+    assert result["validation_state"] == "Valid"
+    assert result["validation_results"]["activeManifest"]["failure"] == []
+    assert signed.count(b"C2PA_GIF") == 1
+    assert signed.index(b"C2PA_GIF") < signed.index(frames)
+    assert b"NETSCAPE2.0" in signed and b"hello" in signed
+    assert signed.endswith(frames)
+
+    resigned = sign_and_embed(signed, "image/gif", MANIFEST, SIGNER)
+    assert resigned.count(b"C2PA_GIF") == 1
+    assert read_c2pa_from_bytes(resigned, "image/gif")["validation_state"] == "Valid"
+
+    # GIF87a has no extension blocks; the signed image is GIF89a
+    still = b"GIF87a" + data[6:19] + gif_frame(0)[8:] + b"\x3b"
+    signed = sign_and_embed(still, "image/gif", MANIFEST, SIGNER)
+    assert signed.startswith(b"GIF89a")
+    assert read_c2pa_from_bytes(signed, "image/gif")["validation_state"] == "Valid"
+
+
+def test_deterministic_signing():
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    # ed25519 signatures are deterministic, so the whole asset is; no time
+    # stamp authority is asked, or this one would fail the signing
+    signer = {**ED25519_SIGNER, "ta_url": "http://127.0.0.1:9"}
+    signed = sign_and_embed(data, "image/png", MANIFEST, signer, deterministic=True)
+    assert sign_and_embed(data, "image/png", MANIFEST, signer, deterministic=True) == signed
+    result = read_c2pa_from_bytes(signed, "image/png")
+    assert result["validation_state"] == "Valid"
+    assert result["validation_results"]["activeManifest"]["failure"] == []
+    manifest = result["manifests"][result["active_manifest"]]
+
+    # ES256 signatures are randomized, but the identifiers are still pinned
+    def identifiers(signed):
+        result = read_c2pa_from_bytes(signed, "image/png")
+        return result["active_manifest"], result["manifests"][result["active_manifest"]]["instance_id"]
+
+    assert identifiers(sign_and_embed(data, "image/png", MANIFEST, SIGNER, deterministic=True)) == (
+        result["active_manifest"],
+        manifest["instance_id"],
+    )
+    assert identifiers(sign_and_embed(data, "image/png", MANIFEST, SIGNER)) != identifiers(signed)
+    assert identifiers(sign_and_embed(data + b"\x00", "image/png", MANIFEST, SIGNER,
+                                      deterministic=True)) != identifiers(signed)
+
+    # A label of the definition is kept
+    labelled = json.dumps({**json.loads(MANIFEST), "label": "urn:uuid:00000000-0000-4000-8000-000000000001"})
+    signed = sign_and_embed(data, "image/png", labelled, ED25519_SIGNER, deterministic=True)
+    assert read_c2pa_from_bytes(signed, "image/png")["active_manifest"] == (
+        "urn:uuid:00000000-0000-4000-8000-000000000001"
+    )
+
+
+def test_invalid_arguments():
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    with pytest.raises(ValueError, match="manifest definition"):
+        sign_and_embed(data, "image/png", "not json", SIGNER)
+    with pytest.raises(ValueError, match="alg"):
+        sign_and_embed(data, "image/png", MANIFEST, {**SIGNER, "alg": "md5"})
+    with pytest.raises(ValueError, match="private_key"):
+        sign_and_embed(data, "image/png", MANIFEST, {"alg": "es256", "sign_cert": "x"})
+    with pytest.raises(TypeError, match="sign_cert"):
+        sign_and_embed(data, "image/png", MANIFEST, {**SIGNER, "sign_cert": 1})
+    with pytest.raises(ValueError, match="signer_config"):
+        sign_and_embed(data, "image/png", MANIFEST, {**SIGNER, "private_key": "not a key"})
+    with pytest.raises(RuntimeError, match="signing"):
+        sign_and_embed(b"plain text", "text/plain", MANIFEST, SIGNER)
+
+
+def test_callback_signer():
+    # The key stays with the callback, as it would in a KMS; DER signatures,
+    # as KMS services return them, are taken as they are
+    key = serialization.load_pem_private_key(SIGNER["private_key"].encode(), None)
+    signed_payloads = []
+
+    def sign(data):
+        signed_payloads.append(data)
+        return key.sign(data, ec.ECDSA(hashes.SHA256()))
+
+    signer = CallbackSigner(sign, "ES256", SIGNER["sign_cert"])
+    assert signer.alg == "es256"
+    assert signer.ta_url is None
+
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    signed = sign_and_embed(data, "image/png", MANIFEST, signer, allow_threads=True)
+    assert len(signed_payloads) == 1 and isinstance(signed_payloads[0], bytes)
+    result = read_c2pa_from_bytes(signed, "image/png")
+    codes = {status["code"] for status in result["validation_results"]["activeManifest"]["success"]}
+    assert "claimSignature.validated" in codes
+    info = result["manifests"][result["active_manifest"]]["signature_info"]
+    expected = read_c2pa_from_bytes(sign_and_embed(data, "image/png", MANIFEST, SIGNER), "image/png")
+    expected_info = expected["manifests"][expected["active_manifest"]]["signature_info"]
+    assert info["cert_serial_number"] == expected_info["cert_serial_number"]
+
+
+def test_callback_signer_errors():
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    with pytest.raises(TypeError, match="callable"):
+        CallbackSigner(b"not callable", "es256", SIGNER["sign_cert"])
+    with pytest.raises(ValueError, match="alg"):
+        CallbackSigner(bytes, "md5", SIGNER["sign_cert"])
+    with pytest.raises(ValueError, match="certificate"):
+        CallbackSigner(bytes, "es256", "not a certificate")
+    with pytest.raises(TypeError, match="signer_config"):
+        sign_and_embed(data, "image/png", MANIFEST, "es256")
+
+    def unavailable(data):
+        raise ConnectionError("KMS unavailable")
+
+    signer = CallbackSigner(unavailable, "es256", SIGNER["sign_cert"])
+    with pytest.raises(SigningError, match="KMS unavailable") as raised:
+        sign_and_embed(data, "image/png", MANIFEST, signer)
+    assert isinstance(raised.value.__cause__, ConnectionError)
+
+    signer = CallbackSigner(lambda data: "not bytes", "es256", SIGNER["sign_cert"])
+    with pytest.raises(SigningError, match="bytes-like") as raised:
+        sign_and_embed(data, "image/png", MANIFEST, signer)
+    assert isinstance(raised.value.__cause__, TypeError)
+
+
+def test_ingredient_provenance_chain():
+    parent = (TEST_IMAGES_DIR / "C.jpg").read_bytes()
+    created = create_ingredient_from_bytes(parent, "image/jpeg", title="C.jpg",
+                                           relationship="parentOf")
+    ingredient, resources = created["ingredient"], created["resources"]
+    assert ingredient["title"] == "C.jpg"
+    assert ingredient["relationship"] == "parentOf"
+    assert ingredient["hash"] == hashlib.sha256(parent).hexdigest()
+    assert ingredient["active_manifest"] == read_c2pa_from_bytes(parent, "image/jpeg")["active_manifest"]
+    assert ingredient["thumbnail"]["identifier"] in resources
+    assert ingredient["manifest_data"]["identifier"] in resources
+
+    definition = json.dumps({**json.loads(MANIFEST), "ingredients": [ingredient]})
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    signed = sign_and_embed(data, "image/png", definition, SIGNER, resources=resources)
+    result = read_c2pa_from_bytes(signed, "image/png")
+    (recorded,) = result["manifests"][result["active_manifest"]]["ingredients"]
+    assert recorded["title"] == "C.jpg"
+    assert recorded["active_manifest"] in result["manifests"]
+
+
+def test_ingredient_without_manifest_store():
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    created = create_ingredient_from_bytes(data, "image/png")
+    ingredient = created["ingredient"]
+    assert ingredient["relationship"] == "componentOf"
+    assert "manifest_data" not in ingredient
+    if capabilities()["features"]["transforms"]:
+        thumbnail = ingredient["thumbnail"]
+        assert thumbnail["format"] == "image/jpeg"
+        assert created["resources"][thumbnail["identifier"]].startswith(b"\xff\xd8")
+
+
+class AssetHandler(BaseHTTPRequestHandler):
+    requests = []
+
+    def do_GET(self):
+        AssetHandler.requests.append(self.path)
+        if self.path in ("/assets/C.jpg", "/blob"):
+            self.send_response(200)
+            self.end_headers()
+            self.wfile.write((TEST_IMAGES_DIR / "C.jpg").read_bytes())
+        else:
+            self.send_response(404)
+            self.end_headers()
+
+    def log_message(self, *args):
+        pass
+
+
+@pytest.fixture
+def cdn():
+    httpd = HTTPServer(("127.0.0.1", 0), AssetHandler)
+    threading.Thread(target=httpd.serve_forever, daemon=True).start()
+    AssetHandler.requests = []
+    configure_http(block_private_ips=False)
+    yield f"http://127.0.0.1:{httpd.server_address[1]}"
+    httpd.shutdown()
+    configure_http()
+
+
+def test_ingredient_from_url(cdn):
+    parent = (TEST_IMAGES_DIR / "C.jpg").read_bytes()
+    url = f"{cdn}/assets/C.jpg"
+    first = add_ingredient_from_url(MANIFEST, url, "parentOf")
+    (ingredient,) = json.loads(first["manifest_json"])["ingredients"]
+    assert ingredient["title"] == "C.jpg"
+    assert ingredient["format"] == "image/jpeg"
+    assert ingredient["relationship"] == "parentOf"
+    assert ingredient["informational_URI"] == url
+    assert ingredient["hash"] == hashlib.sha256(parent).hexdigest()
+    assert ingredient["active_manifest"] == read_c2pa_from_bytes(parent, "image/jpeg")["active_manifest"]
+    assert ingredient["manifest_data"]["identifier"] in first["resources"]
+
+    # The format is detected from the bytes when the URL has no extension
+    second = add_ingredient_from_url(first["manifest_json"], f"{cdn}/blob", title="blob.jpg")
+    assert [i["title"] for i in json.loads(second["manifest_json"])["ingredients"]] == ["C.jpg", "blob.jpg"]
+
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    resources = {**first["resources"], **second["resources"]}
+    signed = sign_and_embed(data, "image/png", second["manifest_json"], SIGNER, resources=resources)
+    result = read_c2pa_from_bytes(signed, "image/png")
+    recorded = result["manifests"][result["active_manifest"]]["ingredients"]
+    assert [i["title"] for i in recorded] == ["C.jpg", "blob.jpg"]
+    assert recorded[0]["active_manifest"] in result["manifests"]
+
+
+def test_ingredient_referenced_by_url(cdn):
+    url = f"{cdn}/assets/C.jpg"
+    added = add_ingredient_from_url(MANIFEST, url, "inputTo", download=False)
+    assert AssetHandler.requests == [] and added["resources"] == {}
+    (ingredient,) = json.loads(added["manifest_json"])["ingredients"]
+    assert (ingredient["title"], ingredient["format"]) == ("C.jpg", "image/jpeg")
+    assert ingredient["relationship"] == "inputTo"
+    assert ingredient["informational_URI"] == url
+    assert "hash" not in ingredient
+
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    signed = sign_and_embed(data, "image/png", added["manifest_json"], SIGNER)
+    result = read_c2pa_from_bytes(signed, "image/png")
+    (recorded,) = result["manifests"][result["active_manifest"]]["ingredients"]
+    assert recorded["title"] == "C.jpg"
+
+
+def test_ingredient_from_url_errors(cdn):
+    with pytest.raises(ValueError, match="mime_type"):
+        add_ingredient_from_url(MANIFEST, f"{cdn}/blob", download=False)
+    with pytest.raises(ValueError, match="manifest definition"):
+        add_ingredient_from_url("[]", f"{cdn}/assets/C.jpg")
+    with pytest.raises(ValueError, match="URL"):
+        add_ingredient_from_url(MANIFEST, "not a url")
+    with pytest.raises(ValueError, match="relationship"):
+        add_ingredient_from_url(MANIFEST, f"{cdn}/assets/C.jpg", "siblingOf")
+    with pytest.raises(IoError, match="404"):
+        add_ingredient_from_url(MANIFEST, f"{cdn}/missing.jpg")
+    configure_http(allowed_hosts=["cdn.example.com"])
+    with pytest.raises(PermissionError):
+        add_ingredient_from_url(MANIFEST, f"{cdn}/assets/C.jpg")
+
+
+def test_ingredient_invalid_arguments():
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    with pytest.raises(ValueError, match="relationship"):
+        create_ingredient_from_bytes(data, "image/png", relationship="childOf")
+    with pytest.raises(TypeError, match="thumb"):
+        sign_and_embed(data, "image/png", MANIFEST, SIGNER, resources={"thumb": "not bytes"})
+
+
+def c2patool_manifest(directory, **definition):
+    """A c2patool manifest definition file in directory, with its files"""
+    (directory / "keys").mkdir()
+    for name in ("es256.pub", "es256.priv"):
+        shutil.copy(CERT_DIR / name, directory / "keys" / name)
+    shutil.copy(TEST_IMAGES_DIR / "C.jpg", directory / "component.jpg")
+    path = directory / "manifest.json"
+    path.write_text(json.dumps({
+        "alg": "es256",
+        "sign_cert": "keys/es256.pub",
+        "private_key": "keys/es256.priv",
+        "claim_generator": "legacy-script/2.1 c2patool/0.9",
+        "title": "signed.png",
+        "ingredient_paths": ["component.jpg"],
+        **definition,
+    }))
+    return path
+
+
+def test_sign_with_manifest_file(tmp_path):
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    thumbnail = (TEST_IMAGES_DIR / "icon_test.jpg").read_bytes()
+    (tmp_path / "thumbnail.jpg").write_bytes(thumbnail)
+    path = c2patool_manifest(
+        tmp_path, thumbnail={"format": "image/jpeg", "identifier": "thumbnail.jpg"}
+    )
+
+    signed = sign_with_manifest_file(data, "image/png", path)
+    result = read_c2pa_from_bytes(signed, "image/png")
+    manifest = result["manifests"][result["active_manifest"]]
+    generator = manifest["claim_generator_info"][0]
+    assert (generator["name"], generator["version"]) == ("legacy-script", "2.1")
+    assert manifest["thumbnail"]["format"] == "image/jpeg"
+    (component,) = manifest["ingredients"]
+    assert component["title"] == "component.jpg"
+    assert component["relationship"] == "componentOf"
+    assert component["active_manifest"] in result["manifests"]
+
+
+def test_sign_with_manifest_file_parent(tmp_path):
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    path = c2patool_manifest(tmp_path, ingredient_paths=[])
+    signed = sign_with_manifest_file(
+        data, "image/png", str(path), signer_config=SIGNER,
+        parent=TEST_IMAGES_DIR / "adobe_firefly_image.jpg", allow_threads=True,
+    )
+    result = read_c2pa_from_bytes(signed, "image/png")
+    (parent,) = result["manifests"][result["active_manifest"]]["ingredients"]
+    assert parent["title"] == "adobe_firefly_image.jpg"
+    assert parent["relationship"] == "parentOf"
+
+
+def test_sign_with_manifest_file_errors(tmp_path):
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    path = tmp_path / "manifest.json"
+    path.write_text(json.dumps({"title": "signed.png"}))
+    with pytest.raises(ValueError, match="signer"):
+        sign_with_manifest_file(data, "image/png", path)
+    with pytest.raises(FileNotFoundError):
+        sign_with_manifest_file(data, "image/png", tmp_path / "missing.json", SIGNER)
+    path.write_text(json.dumps({"title": "signed.png", "ingredient_paths": ["missing.jpg"]}))
+    with pytest.raises(FileNotFoundError, match="missing.jpg"):
+        sign_with_manifest_file(data, "image/png", path, SIGNER)
@@ -0,0 +1,111 @@
+"""
+Tests for recovering stripped manifests through a soft binding resolver.
+
+The stripped asset is chatgpt_image.png without its caBX chunk; the resolver
+plays the part of a watermark lookup returning the original manifest store.
+"""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from fast_c2pa_python import (
+    configure_http,
+    read_c2pa_encoded_from_bytes,
+    read_c2pa_from_bytes,
+    read_c2pa_json_from_bytes,
+    set_soft_binding_resolver,
+    verify_manifest_bytes,
+)
+
+
+@pytest.fixture
+def png(read_image, split_manifest):
+    yield split_manifest(read_image("chatgpt_image.png"))
+    set_soft_binding_resolver(None)
+
+
+def test_resolver_returns_manifest(png):
+    manifest, stripped = png
+    calls = []
+
+    def resolver(data, mime_type):
+        calls.append((data, mime_type))
+        return manifest
+
+    set_soft_binding_resolver(resolver)
+    result = read_c2pa_from_bytes(stripped, "image/png")
+    assert calls == [(stripped, "image/png")]
+
+    expected = verify_manifest_bytes(manifest, stripped, "image/png")
+    assert result == {**expected, "soft_binding": {"url": None}}
+    assert result["is_embedded"] is False
+    assert json.loads(read_c2pa_json_from_bytes(stripped, "image/png")) == result
+    encoded = read_c2pa_encoded_from_bytes(stripped, "image/png", format="json", canonical=True)
+    assert encoded == read_c2pa_json_from_bytes(stripped, "image/png", canonical=True).encode()
+
+
+def test_resolver_returns_url(png):
+    manifest, stripped = png
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self):
+            self.send_response(200)
+            self.end_headers()
+            self.wfile.write(manifest)
+
+        def log_message(self, *args):
+            pass
+
+    httpd = HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=httpd.serve_forever, daemon=True).start()
+    url = f"http://127.0.0.1:{httpd.server_address[1]}/manifests/1.c2pa"
+    try:
+        configure_http(proxy=None, block_private_ips=False)
+        set_soft_binding_resolver(lambda data, mime_type: url)
+        result = read_c2pa_from_bytes(stripped, "image/png")
+    finally:
+        httpd.shutdown()
+        configure_http()
+    assert result["soft_binding"] == {"url": url}
+    assert result["active_manifest"] == verify_manifest_bytes(manifest, stripped, "image/png")["active_manifest"]
+
+
+def test_resolver_not_called_for_embedded_manifests(read_image, png):
+    manifest, stripped = png
+    set_soft_binding_resolver(lambda data, mime_type: pytest.fail("resolver called"))
+    original = read_image("chatgpt_image.png")
+    assert "soft_binding" not in read_c2pa_from_bytes(original, "image/png")
+
+
+def test_resolver_finds_nothing(png):
+    _, stripped = png
+    set_soft_binding_resolver(lambda data, mime_type: None)
+    assert read_c2pa_from_bytes(stripped, "image/png") is None
+
+    set_soft_binding_resolver(None)
+    assert read_c2pa_from_bytes(stripped, "image/png") is None
+
+
+def test_resolver_errors(png):
+    _, stripped = png
+
+    def failing(data, mime_type):
+        raise LookupError("watermark service unavailable")
+
+    set_soft_binding_resolver(failing)
+    with pytest.raises(LookupError):
+        read_c2pa_from_bytes(stripped, "image/png")
+
+    set_soft_binding_resolver(lambda data, mime_type: 42)
+    with pytest.raises(TypeError):
+        read_c2pa_from_bytes(stripped, "image/png")
+
+    set_soft_binding_resolver(lambda data, mime_type: b"not a manifest store")
+    with pytest.raises(RuntimeError):
+        read_c2pa_from_bytes(stripped, "image/png")
+
+    with pytest.raises(TypeError):
+        set_soft_binding_resolver("not callable")
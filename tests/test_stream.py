@@ -0,0 +1,79 @@
+"""
+Tests for reading assets from file-like objects.
+"""
+
+import io
+import mmap
+import tempfile
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, read_c2pa_from_stream
+
+
+class Body:
+    """A streaming body with only read, seek and tell, logging its reads"""
+
+    def __init__(self, data):
+        self.stream = io.BytesIO(data)
+        self.reads = []
+
+    def read(self, size=-1):
+        chunk = self.stream.read(size)
+        self.reads.append(len(chunk))
+        return chunk
+
+    def seek(self, offset, whence=0):
+        self.stream.seek(offset, whence)
+
+    def tell(self):
+        return self.stream.tell()
+
+
+class Failing(Body):
+    def read(self, size=-1):
+        if self.reads:
+            raise ConnectionResetError("connection lost")
+        return super().read(size)
+
+
+def test_bytes_io(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    stream = io.BytesIO(data)
+    stream.seek(100)
+    assert read_c2pa_from_stream(stream) == read_c2pa_from_bytes(data, "image/jpeg")
+    assert stream.tell() == 100
+
+
+def test_file_like_object(read_image):
+    data = read_image("chatgpt_image.png")
+    body = Body(data)
+    result = read_c2pa_from_stream(body, "image/png", allow_threads=True)
+    assert result["active_manifest"] == read_c2pa_from_bytes(data, "image/png")["active_manifest"]
+    assert max(body.reads) < len(data)
+
+
+def test_tempfile_and_mmap(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    expected = read_c2pa_from_bytes(data, "image/jpeg")
+    with tempfile.TemporaryFile() as file:
+        file.write(data)
+        assert read_c2pa_from_stream(file) == expected
+        file.flush()
+        with mmap.mmap(file.fileno(), 0, access=mmap.ACCESS_READ) as mapped:
+            assert read_c2pa_from_stream(mapped, "image/jpeg") == expected
+
+
+def test_without_manifest_store(read_image):
+    assert read_c2pa_from_stream(io.BytesIO(read_image("screenshot_noc2pa.png"))) is None
+
+
+def test_errors(read_image):
+    with pytest.raises(TypeError, match="seekable"):
+        read_c2pa_from_stream(b"not a stream")
+    with pytest.raises(TypeError, match="read"):
+        read_c2pa_from_stream(io.StringIO("text"), "image/jpeg")
+    with pytest.raises(ValueError, match="mime_type"):
+        read_c2pa_from_stream(io.BytesIO(b"not an asset"))
+    with pytest.raises(ConnectionResetError):
+        read_c2pa_from_stream(Failing(read_image("adobe_firefly_image.jpg")), "image/jpeg")
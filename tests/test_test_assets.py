@@ -0,0 +1,64 @@
+"""
+Tests for the signed test assets made in memory.
+"""
+
+import pytest
+
+from fast_c2pa_python import (
+    UnsupportedFormatError,
+    make_signed_test_asset,
+    read_c2pa_from_bytes,
+)
+
+FORMATS = [
+    ("jpeg", "image/jpeg"),
+    ("png", "image/png"),
+    ("gif", "image/gif"),
+    ("tiff", "image/tiff"),
+    ("webp", "image/webp"),
+    ("image/svg+xml", "image/svg+xml"),
+    ("wav", "audio/wav"),
+    ("mp3", "audio/mpeg"),
+    ("pdf", "application/pdf"),
+]
+
+
+@pytest.mark.parametrize("format,mime_type", FORMATS)
+def test_formats(untrusted_valid, format, mime_type):
+    data = make_signed_test_asset(format)
+    result = read_c2pa_from_bytes(data, mime_type)
+    assert result["validation_state"] == "Valid"
+    manifest = result["manifests"][result["active_manifest"]]
+    assert manifest["title"].startswith("test_asset.")
+    assert manifest["assertions"][0]["data"]["actions"][0]["action"] == "c2pa.created"
+
+
+def test_options_and_parent(untrusted_valid):
+    parent = make_signed_test_asset("png", {
+        "title": "parent.png",
+        "width": 8,
+        "height": 8,
+        "assertions": [{"label": "com.example.test", "data": {"answer": 42}}],
+    })
+    manifest = read_c2pa_from_bytes(parent, "image/png")
+    assert [a["label"] for a in manifest["manifests"][manifest["active_manifest"]]["assertions"]] == [
+        "c2pa.actions",
+        "com.example.test",
+    ]
+
+    child = read_c2pa_from_bytes(make_signed_test_asset("jpeg", {"parent": parent}), "image/jpeg")
+    assert child["validation_state"] == "Valid"
+    ingredient = child["manifests"][child["active_manifest"]]["ingredients"][0]
+    assert ingredient["relationship"] == "parentOf"
+    assert ingredient["active_manifest"] == manifest["active_manifest"]
+
+
+def test_errors():
+    with pytest.raises(UnsupportedFormatError):
+        make_signed_test_asset("bmp")
+    with pytest.raises(ValueError, match="colour"):
+        make_signed_test_asset("png", {"colour": "red"})
+    with pytest.raises(ValueError, match="width"):
+        make_signed_test_asset("png", {"width": 0})
+    with pytest.raises(TypeError, match="height"):
+        make_signed_test_asset("png", {"height": "tall"})
@@ -0,0 +1,182 @@
+"""
+Tests for image transforms that keep the C2PA data.
+"""
+
+import struct
+import zlib
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    convert_to_gray_keep_c2pa,
+    detect_mime_from_bytes,
+    read_c2pa_from_bytes,
+    read_c2pa_from_file,
+    transform_keep_c2pa,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+def png(width, height, bit_depth=8, color_type=2, icc_profile=None):
+    """A PNG of a gradient, optionally with an iCCP chunk"""
+    channels = {0: 1, 2: 3, 4: 2, 6: 4}[color_type]
+    row_len = width * channels * bit_depth // 8
+    rows = b"".join(b"\x00" + bytes((x * 7 + y * 13) % 256 for x in range(row_len))
+                    for y in range(height))
+
+    def chunk(kind, data):
+        return struct.pack(">I", len(data)) + kind + data + struct.pack(">I", zlib.crc32(kind + data))
+
+    ihdr = struct.pack(">IIBBBBB", width, height, bit_depth, color_type, 0, 0, 0)
+    out = b"\x89PNG\r\n\x1a\n" + chunk(b"IHDR", ihdr)
+    if icc_profile is not None:
+        out += chunk(b"iCCP", b"test\x00\x00" + zlib.compress(icc_profile))
+    return out + chunk(b"IDAT", zlib.compress(rows)) + chunk(b"IEND", b"")
+
+
+def cmyk_tiff(width, height):
+    """An uncompressed 8-bit CMYK TIFF"""
+    pixels = bytes((i * 11) % 256 for i in range(width * height * 4))
+    bits_offset = 8
+    data_offset = bits_offset + 8
+    ifd_offset = data_offset + len(pixels)
+    tags = [
+        (256, 3, 1, width), (257, 3, 1, height), (258, 3, 4, bits_offset), (259, 3, 1, 1),
+        (262, 3, 1, 5), (273, 4, 1, data_offset), (277, 3, 1, 4), (278, 3, 1, height),
+        (279, 4, 1, len(pixels)), (284, 3, 1, 1),
+    ]
+    ifd = struct.pack("<H", len(tags))
+    for tag, kind, count, value in tags:
+        packed = struct.pack("<H", value) + b"\x00\x00" if kind == 3 and count == 1 else struct.pack("<I", value)
+        ifd += struct.pack("<HHI", tag, kind, count) + packed
+    ifd += struct.pack("<I", 0)
+    return (b"II*\x00" + struct.pack("<I", ifd_offset) + struct.pack("<4H", 8, 8, 8, 8)
+            + pixels + ifd)
+
+
+def active_manifest(data):
+    return read_c2pa_from_bytes(data)["active_manifest"]
+
+
+def test_pipeline_in_memory(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    out = transform_keep_c2pa(data, None, [
+        {"op": "resize", "width": 256},
+        {"op": "rotate", "degrees": 90},
+        {"op": "quality", "quality": 80},
+    ])
+    assert detect_mime_from_bytes(out) == "image/jpeg"
+    assert len(out) < len(data)
+    result = read_c2pa_from_bytes(out, "image/jpeg")
+    assert result["active_manifest"] == active_manifest(data)
+    # The store still describes the original pixels
+    failures = result["validation_results"]["activeManifest"]["failure"]
+    assert "assertion.dataHash.mismatch" in [f["code"] for f in failures]
+
+
+def test_format_conversion(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    out = transform_keep_c2pa(data, None, [
+        {"op": "crop", "x": 10, "y": 10, "width": 100, "height": 50},
+        {"op": "grayscale"},
+        {"op": "format", "mime_type": "image/png"},
+    ])
+    assert detect_mime_from_bytes(out) == "image/png"
+    assert active_manifest(out) == active_manifest(data)
+
+
+def test_paths(tmp_path):
+    source = TEST_IMAGES_DIR / "chatgpt_image.png"
+    if not source.exists():
+        pytest.skip(f"Test image not found: {source}")
+    output = tmp_path / "small.jpg"
+    assert transform_keep_c2pa(source, output, [{"op": "resize", "width": 64, "height": 64}]) is None
+    result = read_c2pa_from_file(output)
+    assert result["active_manifest"] == read_c2pa_from_file(source)["active_manifest"]
+
+    gray = tmp_path / "gray.png"
+    convert_to_gray_keep_c2pa(str(source), str(gray), "png")
+    assert read_c2pa_from_file(gray)["active_manifest"] == result["active_manifest"]
+
+
+def test_without_manifest_store(read_image):
+    data = read_image("screenshot_noc2pa.png")
+    out = transform_keep_c2pa(data, None, [{"op": "resize", "width": 32}], mime_type="image/png")
+    assert detect_mime_from_bytes(out) == "image/png"
+    assert read_c2pa_from_bytes(out, "image/png") is None
+
+
+@pytest.mark.parametrize("ops", [
+    [{"op": "blur"}],
+    [{"kind": "grayscale"}],
+    [{"op": "rotate", "degrees": 45}],
+    [{"op": "resize"}],
+    [{"op": "resize", "width": 10, "filter": "sharp"}],
+    [{"op": "quality", "quality": 0}],
+    [{"op": "quality", "quality": 80}, {"op": "format", "mime_type": "image/png"}],
+    [{"op": "crop", "x": 5000, "width": 1, "height": 1}],
+    [{"op": "format", "mime_type": "image/bmp"}],
+    [{"op": "format", "mime_type": "application/pdf"}],
+])
+def test_invalid_ops(read_image, ops):
+    with pytest.raises(ValueError):
+        transform_keep_c2pa(read_image("adobe_firefly_image.jpg"), None, ops)
+
+
+def test_actions(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    out, actions = transform_keep_c2pa(data, None, [
+        {"op": "resize", "width": 64},
+        {"op": "grayscale"},
+        {"op": "format", "mime_type": "image/png"},
+    ], with_actions=True)
+    assert detect_mime_from_bytes(out) == "image/png"
+    assert [a["action"] for a in actions] == [
+        "c2pa.resized", "c2pa.color_adjustments", "c2pa.transcoded",
+    ]
+
+
+def test_icc_profile_kept():
+    icc_profile = b"test icc profile" * 8
+    data = png(16, 8, icc_profile=icc_profile)
+    for mime_type in ("image/jpeg", "image/png", "image/webp"):
+        out = transform_keep_c2pa(data, None, [{"op": "format", "mime_type": mime_type}])
+        assert detect_mime_from_bytes(out) == mime_type
+        if mime_type == "image/png":
+            assert b"iCCP" in out
+        else:
+            assert icc_profile in out
+
+    with pytest.raises(ValueError, match="ICC profile"):
+        transform_keep_c2pa(data, None, [{"op": "format", "mime_type": "image/tiff"}])
+    out, actions = transform_keep_c2pa(data, None, [{"op": "format", "mime_type": "image/tiff"}],
+                                       allow_color_conversion=True, with_actions=True)
+    assert detect_mime_from_bytes(out) == "image/tiff"
+    assert actions[0]["action"] == "c2pa.color_adjustments"
+    assert "ICC profile" in actions[0]["description"]
+
+
+def test_16_bit_samples():
+    data = png(16, 8, bit_depth=16)
+    out = transform_keep_c2pa(data, None, [{"op": "rotate", "degrees": 90}])
+    assert out[24] == 16  # IHDR bit depth
+    assert struct.unpack(">II", out[16:24]) == (8, 16)
+
+    with pytest.raises(ValueError, match="16-bit"):
+        transform_keep_c2pa(data, None, [{"op": "format", "mime_type": "image/jpeg"}])
+    out, actions = transform_keep_c2pa(data, None, [{"op": "format", "mime_type": "image/jpeg"}],
+                                       allow_color_conversion=True, with_actions=True)
+    assert detect_mime_from_bytes(out) == "image/jpeg"
+    assert "8 bits" in actions[0]["description"]
+
+
+def test_cmyk_input():
+    data = cmyk_tiff(4, 4)
+    with pytest.raises(ValueError, match="CMYK"):
+        transform_keep_c2pa(data, None, [{"op": "format", "mime_type": "image/png"}])
+    out, actions = transform_keep_c2pa(data, None, [{"op": "format", "mime_type": "image/png"}],
+                                       allow_color_conversion=True, with_actions=True)
+    assert out[25] == 2  # IHDR colour type: RGB
+    assert actions[0] == {"action": "c2pa.color_adjustments", "description": "Converted from CMYK to RGB"}
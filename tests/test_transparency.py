@@ -0,0 +1,86 @@
+"""
+Tests for the transparency log verification hook.
+"""
+
+import hashlib
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    read_c2pa_from_bytes,
+    set_transparency_log_verifier,
+    verify_manifest_bytes,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+@pytest.fixture
+def asset():
+    yield (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+    set_transparency_log_verifier(None)
+
+
+def test_every_manifest_checked(asset):
+    calls = []
+
+    def verifier(label, signature, signature_info):
+        calls.append((label, signature, signature_info))
+        return {"included": True, "log_index": len(calls), "entry": hashlib.sha256(signature).hexdigest()}
+
+    set_transparency_log_verifier(verifier)
+    result = read_c2pa_from_bytes(asset, "image/jpeg")
+
+    assert sorted(label for label, _, _ in calls) == sorted(result["manifests"])
+    for label, signature, signature_info in calls:
+        # COSE_Sign1 is CBOR tag 18, or an untagged four item array
+        assert signature[:1] in (b"\xd2", b"\x84")
+        assert signature_info == result["manifests"][label].get("signature_info")
+        log = result["manifests"][label]["transparency_log"]
+        assert log["included"] is True
+        assert log["entry"] == hashlib.sha256(signature).hexdigest()
+    assert "warnings" not in result
+
+
+def test_not_included(asset):
+    set_transparency_log_verifier(lambda label, signature, info: {"included": False})
+    result = read_c2pa_from_bytes(asset, "image/jpeg")
+    codes = {w["code"] for w in result["warnings"]}
+    assert codes == {"transparencyLog.notIncluded"}
+    assert {w["label"] for w in result["warnings"]} == set(result["manifests"])
+
+
+def test_skipped_manifests(asset):
+    set_transparency_log_verifier(lambda label, signature, info: None)
+    result = read_c2pa_from_bytes(asset, "image/jpeg")
+    assert all("transparency_log" not in m for m in result["manifests"].values())
+
+
+def test_sidecar_store():
+    png = (TEST_IMAGES_DIR / "chatgpt_image.png").read_bytes()
+    # Extract the caBX chunk as a sidecar store
+    pos = png.index(b"caBX")
+    length = int.from_bytes(png[pos - 4:pos], "big")
+    store = png[pos + 4:pos + 4 + length]
+
+    seen = []
+    set_transparency_log_verifier(lambda label, signature, info: seen.append(label) or {"included": True})
+    try:
+        result = verify_manifest_bytes(store, png, "image/png")
+    finally:
+        set_transparency_log_verifier(None)
+    assert sorted(seen) == sorted(result["manifests"])
+
+
+def test_invalid_answers(asset):
+    set_transparency_log_verifier(lambda label, signature, info: {"included": "yes"})
+    with pytest.raises(TypeError):
+        read_c2pa_from_bytes(asset, "image/jpeg")
+
+    set_transparency_log_verifier(lambda label, signature, info: True)
+    with pytest.raises(TypeError):
+        read_c2pa_from_bytes(asset, "image/jpeg")
+
+    with pytest.raises(TypeError):
+        set_transparency_log_verifier(42)
@@ -0,0 +1,147 @@
+"""
+Tests for the trust list configuration helpers.
+"""
+
+import base64
+import hashlib
+import ssl
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import (
+    IoError,
+    configure_http,
+    disable_shared_cache,
+    enable_shared_cache,
+    enable_trust_checks,
+    get_shared_cache_stats,
+    read_c2pa_from_bytes,
+    refresh_trust_list,
+    set_allowed_list,
+    set_trust_anchors,
+)
+
+TEST_DIR = Path(__file__).parent
+# The c2pa-rs es256 test signing certificate, followed by its intermediate
+CHAIN = (TEST_DIR / "sign_cert" / "es256.pub").read_text()
+LEAF = CHAIN[: CHAIN.index("-----END CERTIFICATE-----") + len("-----END CERTIFICATE-----\n")]
+LEAF_HASH = base64.b64encode(hashlib.sha256(ssl.PEM_cert_to_DER_cert(LEAF)).digest()).decode()
+# The roots of the c2pa-rs test certificates
+TEST_ROOTS = (TEST_DIR / "sign_cert" / "test_cert_root_bundle.pem").read_text()
+
+
+class Handler(BaseHTTPRequestHandler):
+    files = {}
+
+    def do_GET(self):
+        body = Handler.files.get(self.path)
+        self.send_response(200 if body is not None else 404)
+        self.end_headers()
+        if body is not None:
+            self.wfile.write(body.encode())
+
+    def log_message(self, *args):
+        pass
+
+
+@pytest.fixture
+def server():
+    httpd = HTTPServer(("127.0.0.1", 0), Handler)
+    thread = threading.Thread(target=httpd.serve_forever, daemon=True)
+    thread.start()
+    Handler.files = {"/anchors.pem": CHAIN, "/allowed.sha256.txt": LEAF_HASH + "\n"}
+    configure_http(block_private_ips=False)
+    yield f"http://127.0.0.1:{httpd.server_address[1]}"
+    httpd.shutdown()
+    configure_http()
+
+
+@pytest.fixture(autouse=True)
+def trust_checks_off():
+    yield
+    enable_trust_checks(False)
+
+
+def validation_state():
+    data = (TEST_DIR / "test_images" / "multipage.tif").read_bytes()
+    return read_c2pa_from_bytes(data, "image/tiff")["validation_state"]
+
+
+def test_allowed_list():
+    enable_trust_checks(False)
+    assert validation_state() == "Valid"
+    enable_trust_checks()
+    assert validation_state() == "Invalid"
+    assert set_allowed_list(LEAF) == 1
+    assert validation_state() == "Trusted"
+
+    # Each call replaces the list
+    assert set_allowed_list(base64.b64encode(b"another hash").decode()) == 1
+    assert validation_state() == "Invalid"
+
+    enable_trust_checks(False)
+    assert validation_state() == "Valid"
+
+
+def test_trust_anchors():
+    assert set_trust_anchors(CHAIN) == 2
+    with pytest.raises(ValueError, match="no PEM certificates"):
+        set_trust_anchors("not a certificate")
+    with pytest.raises(ValueError):
+        set_allowed_list("")
+
+
+def test_refresh_trust_list(server):
+    counts = refresh_trust_list(
+        trust_anchors_url=f"{server}/anchors.pem",
+        allowed_list_url=f"{server}/allowed.sha256.txt",
+        trust_config_url=None,
+    )
+    assert counts == {"trust_anchors": 2, "allowed_list": 1, "trust_config": None}
+    assert validation_state() == "Trusted"
+
+    # A failed download leaves the loaded lists in place
+    with pytest.raises(IoError):
+        refresh_trust_list(
+            trust_anchors_url=None,
+            allowed_list_url=f"{server}/missing.txt",
+            trust_config_url=None,
+        )
+    assert validation_state() == "Trusted"
+
+
+def test_refresh_without_enabling(server):
+    enable_trust_checks(False)
+    refresh_trust_list(
+        trust_anchors_url=None,
+        allowed_list_url=f"{server}/allowed.sha256.txt",
+        trust_config_url=None,
+        enable_trust_checks=False,
+    )
+    assert validation_state() == "Valid"
+
+
+def test_shared_cache_follows_trust_lists(tmp_path):
+    # Each helper loads one table of the settings, which merge into the
+    # settings before them; cached results must not outlive a change
+    enable_shared_cache(str(tmp_path / "results.cache"), size_mb=4)
+    try:
+        set_allowed_list(base64.b64encode(b"another hash").decode())
+        set_trust_anchors(TEST_ROOTS)
+        enable_trust_checks()
+        assert validation_state() == "Trusted"
+        set_trust_anchors(CHAIN)
+        enable_trust_checks()
+        assert validation_state() == "Invalid"
+        set_allowed_list(LEAF)
+        assert validation_state() == "Trusted"
+        enable_trust_checks(False)
+        assert validation_state() == "Valid"
+        stats = get_shared_cache_stats()
+        assert (stats["hits"], stats["stores"]) == (0, 4)
+    finally:
+        enable_trust_checks(False)
+        disable_shared_cache()
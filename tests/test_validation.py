@@ -0,0 +1,73 @@
+"""
+Tests for the validation outcome returned by validate_c2pa_from_bytes.
+
+chatgpt_image_gray.png was converted to grayscale after signing, so its data
+hash no longer matches.
+"""
+
+from pathlib import Path
+
+from fast_c2pa_python import (
+    read_c2pa_from_bytes,
+    read_c2pa_from_file,
+    set_signature_verifier,
+    validate_c2pa_from_bytes,
+)
+
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+JPG = (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+GRAY = (TEST_IMAGES_DIR / "chatgpt_image_gray.png").read_bytes()
+
+
+def test_validation_outcome():
+    result = read_c2pa_from_bytes(JPG, "image/jpeg")
+    validation = validate_c2pa_from_bytes(JPG, "image/jpeg")
+
+    assert validation["validation_state"] == result["validation_state"]
+    assert validation["active_manifest"] == result["active_manifest"]
+    statuses = result["validation_results"]["activeManifest"]
+    expected = sorted((s["code"], key) for key in ("success", "informational", "failure")
+                      for s in statuses[key])
+    assert sorted((s["code"], s["outcome"]) for s in validation["statuses"]) == expected
+    data_hash = next(s for s in validation["statuses"] if s["code"] == "assertion.dataHash.match")
+    assert data_hash["manifest"] == result["active_manifest"]
+    assert data_hash["ingredient"] is None
+    assert validation["failures"] == [s["code"] for s in statuses["failure"]]
+    assert validation["failed_ingredients"] == []
+
+    assert validate_c2pa_from_bytes(b"no jumbf data", "image/jpeg") is None
+
+
+def test_invalid_asset():
+    validation = validate_c2pa_from_bytes(GRAY, "image/png", allow_threads=True)
+    assert validation["validation_state"] == "Invalid"
+    assert "assertion.dataHash.mismatch" in validation["failures"]
+    assert len(validation["failures"]) == len(set(validation["failures"]))
+
+
+def test_hash_bindings():
+    assert "hash_bindings" not in read_c2pa_from_bytes(JPG, "image/jpeg")
+    intact = read_c2pa_from_bytes(JPG, "image/jpeg", verify_hash_bindings=True)
+    assert intact["hash_bindings"] == {"is_intact": True, "failures": []}
+
+    tampered = read_c2pa_from_file(TEST_IMAGES_DIR / "chatgpt_image_gray.png", verify_hash_bindings=True)
+    assert tampered["hash_bindings"] == {"is_intact": False, "failures": ["c2pa.hash.data"]}
+
+
+def test_failed_ingredients():
+    result = read_c2pa_from_bytes(GRAY, "image/png")
+    active = result["active_manifest"]
+    ingredient = result["manifests"][active]["ingredients"][0]
+
+    set_signature_verifier(lambda label, *args: False if label == ingredient["active_manifest"] else None)
+    try:
+        validation = validate_c2pa_from_bytes(GRAY, "image/png")
+    finally:
+        set_signature_verifier(None)
+    assert validation["failed_ingredients"] == [{
+        "manifest": active,
+        "label": ingredient["label"],
+        "title": ingredient["title"],
+        "active_manifest": ingredient["active_manifest"],
+        "codes": ["claimSignature.mismatch"],
+    }]
@@ -0,0 +1,84 @@
+"""
+Tests for validating manifest stores stored apart from their asset.
+"""
+
+import io
+
+import pytest
+
+from fast_c2pa_python import (
+    extract_manifest_store,
+    get_hash_coverage,
+    read_c2pa_from_bytes,
+    verify_manifest_bytes,
+)
+
+
+@pytest.fixture
+def png_bytes(read_image):
+    return read_image("chatgpt_image.png")
+
+
+def test_matches_embedded_result(split_manifest, png_bytes):
+    manifest, _ = split_manifest(png_bytes)
+    embedded = read_c2pa_from_bytes(png_bytes, "image/png")
+
+    result = verify_manifest_bytes(manifest, png_bytes, "image/png")
+    assert result["active_manifest"] == embedded["active_manifest"]
+    assert result["validation_state"] == embedded["validation_state"]
+    assert result["is_embedded"] is False
+
+
+def test_asset_without_manifest(split_manifest, png_bytes):
+    """The asset as stored by a CMS that keeps manifests in a database."""
+    manifest, stripped = split_manifest(png_bytes)
+    assert read_c2pa_from_bytes(stripped, "image/png") is None
+
+    embedded = read_c2pa_from_bytes(png_bytes, "image/png")
+    result = verify_manifest_bytes(manifest, stripped, "image/png")
+    assert result["active_manifest"] == embedded["active_manifest"]
+    assert result["validation_state"] == embedded["validation_state"]
+
+
+def test_file_like_asset(split_manifest, png_bytes):
+    manifest, stripped = split_manifest(png_bytes)
+    from_bytes = verify_manifest_bytes(manifest, stripped, "image/png")
+    from_stream = verify_manifest_bytes(manifest, io.BytesIO(stripped), "image/png")
+    assert from_stream == from_bytes
+
+
+def test_modified_asset_fails_validation(split_manifest, png_bytes):
+    manifest, stripped = split_manifest(png_bytes)
+    tampered = bytearray(stripped)
+    tampered[len(tampered) // 2] ^= 0xFF
+
+    result = verify_manifest_bytes(manifest, bytes(tampered), "image/png")
+    assert result["validation_state"] == "Invalid"
+    failures = result["validation_results"]["activeManifest"]["failure"]
+    assert any(f["code"] == "assertion.dataHash.mismatch" for f in failures)
+
+
+def test_invalid_manifest_bytes(png_bytes):
+    with pytest.raises(RuntimeError):
+        verify_manifest_bytes(b"not a manifest store", png_bytes, "image/png")
+
+
+def test_extracted_store_as_sidecar(read_image):
+    data = read_image("adobe_firefly_image.jpg")
+    store = extract_manifest_store(data, "image/jpeg")
+
+    embedded = read_c2pa_from_bytes(data, "image/jpeg")
+    sidecar = verify_manifest_bytes(store, data, "image/jpeg")
+    assert sidecar["manifests"] == embedded["manifests"]
+    assert sidecar["validation_state"] == embedded["validation_state"] != "Invalid"
+
+    # Removing the store from the asset moves the bytes the data hash covers
+    (binding,) = get_hash_coverage(data, "image/jpeg")["bindings"]
+    (exclusion,) = binding["exclusions"]
+    stripped = data[:exclusion["start"]] + data[exclusion["start"] + exclusion["length"]:]
+    assert verify_manifest_bytes(store, stripped, "image/jpeg")["validation_state"] == "Invalid"
+
+
+def test_extract_without_manifest(read_image):
+    data = read_image("screenshot_noc2pa.png")
+    assert extract_manifest_store(data, "image/png") is None
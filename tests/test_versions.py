@@ -0,0 +1,109 @@
+"""
+Tests for limiting the claim and assertion versions of the manifests read.
+
+C.jpg has a version 1 claim with unversioned assertions. Both manifests of
+chatgpt_image.png have "c2pa.claim.v2" claims; the active one has
+c2pa.ingredient.v3 and c2pa.actions.v2 assertions, its ingredient's manifest
+a c2pa.actions.v2 assertion.
+"""
+
+import pytest
+
+from fast_c2pa_python import (
+    extract_manifest_store,
+    make_signed_test_asset,
+    read_c2pa_from_bytes,
+    set_version_limits,
+    verify_manifest_bytes,
+)
+
+
+@pytest.fixture(autouse=True)
+def no_limits():
+    yield
+    set_version_limits()
+
+
+def codes(result):
+    return [(w["code"], w["label"]) for w in result.get("warnings", [])]
+
+
+def test_claim_version_limit(read_image):
+    data = read_image("chatgpt_image.png")
+    manifests = read_c2pa_from_bytes(data, "image/png")["manifests"]
+
+    set_version_limits(max_claim_version=1)
+    result = read_c2pa_from_bytes(data, "image/png")
+
+    assert result["validation_state"] == "Invalid"
+    assert sorted(codes(result)) == sorted(
+        ("claim.versionNotAllowed", label) for label in manifests
+    )
+
+
+def test_assertion_version_limits(read_image):
+    data = read_image("chatgpt_image.png")
+    active = read_c2pa_from_bytes(data, "image/png")["active_manifest"]
+
+    set_version_limits(max_assertion_versions={"c2pa.actions": 2, "c2pa.ingredient": 2})
+    warnings = read_c2pa_from_bytes(data, "image/png")["warnings"]
+
+    assert len(warnings) == 1
+    assert warnings[0]["code"] == "assertion.versionNotAllowed"
+    assert warnings[0]["label"] == active
+    assert "c2pa.ingredient.v3" in warnings[0]["explanation"]
+
+    set_version_limits(max_assertion_versions={"*": 1, "c2pa.ingredient": 3})
+    warnings = read_c2pa_from_bytes(data, "image/png")["warnings"]
+    assert [w["code"] for w in warnings] == ["assertion.versionNotAllowed"] * 2
+    assert all("c2pa.actions.v2" in w["explanation"] for w in warnings)
+
+
+def test_warn_keeps_validation_state(read_image):
+    data = read_image("chatgpt_image.png")
+    state = read_c2pa_from_bytes(data, "image/png")["validation_state"]
+
+    set_version_limits(max_claim_version=1, action="warn")
+    result = read_c2pa_from_bytes(data, "image/png")
+
+    assert result["validation_state"] == state
+    assert {code for code, _ in codes(result)} == {"claim.versionNotAllowed"}
+
+
+def test_within_limits(read_image):
+    data = read_image("C.jpg")
+    expected = read_c2pa_from_bytes(data, "image/jpeg")
+
+    set_version_limits(max_claim_version=1, max_assertion_versions={"*": 1})
+
+    assert read_c2pa_from_bytes(data, "image/jpeg") == expected
+
+
+def test_verify_manifest_bytes_checks_versions(read_image):
+    data = read_image("chatgpt_image.png")
+    store = extract_manifest_store(data, "image/png")
+
+    set_version_limits(max_claim_version=1)
+    result = verify_manifest_bytes(store, data, "image/png")
+
+    assert result["validation_state"] == "Invalid"
+    assert {code for code, _ in codes(result)} == {"claim.versionNotAllowed"}
+
+
+def test_lenient_truncated_read_checks_versions():
+    # Cut inside the active manifest, so only the parent's manifest is recovered
+    data = make_signed_test_asset("jpeg", {"parent": make_signed_test_asset("png")})
+    truncated = data[:data.rfind(b"c2ma") + 100]
+
+    set_version_limits(max_assertion_versions={"c2pa.actions": 0})
+    result = read_c2pa_from_bytes(truncated, "image/jpeg", lenient=True)
+
+    (recovered,) = result["manifests"]
+    truncated_warning, *warnings = result["warnings"]
+    assert truncated_warning["code"] == "asset.truncated"
+    assert ("assertion.versionNotAllowed", recovered) in codes({"warnings": warnings})
+
+
+def test_invalid_action():
+    with pytest.raises(ValueError):
+        set_version_limits(max_claim_version=1, action="ignore")
@@ -0,0 +1,164 @@
+"""
+Tests for the tolerant XMP provenance scanner and the XMP utilities.
+"""
+
+import struct
+from pathlib import Path
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, read_xmp, read_xmp_provenance, set_remote_reference
+
+URL = "https://example.com/manifests/abc.c2pa"
+TEST_IMAGES_DIR = Path(__file__).parent / "test_images"
+
+
+def xmp_packet(body, prefix="dcterms", wrap=True):
+    xmp = (
+        '<x:xmpmeta xmlns:x="adobe:ns:meta/">'
+        '<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">'
+        f'<rdf:Description rdf:about="" xmlns:{prefix}="http://purl.org/dc/terms/"'
+        f'{body}'
+        '</rdf:RDF></x:xmpmeta>'
+    )
+    if wrap:
+        xmp = '<?xpacket begin="﻿" id="W5M0MpCehiHzreSzNTczkc9d"?>' + xmp + '<?xpacket end="w"?>'
+    return xmp
+
+
+def jpeg_with_xmp(xmp_bytes):
+    body = b"http://ns.adobe.com/xap/1.0/\x00" + xmp_bytes
+    app1 = b"\xff\xe1" + struct.pack(">H", len(body) + 2) + body
+    return b"\xff\xd8" + app1 + b"\xff\xd9"
+
+
+def test_attribute_form():
+    data = jpeg_with_xmp(xmp_packet(f' dcterms:provenance="{URL}"/>').encode())
+    result = read_xmp_provenance(data)
+    assert result["xmp_found"]
+    assert result["provenance"] == URL
+    assert result["packets"][0]["encoding"] == "utf-8"
+    assert result["packets"][0]["bom"]
+    assert result["warnings"] == []
+
+
+def test_element_form_with_other_prefix():
+    body = f'><terms:provenance> {URL} </terms:provenance></rdf:Description>'
+    data = jpeg_with_xmp(xmp_packet(body, prefix="terms").encode())
+    assert read_xmp_provenance(data)["provenance"] == URL
+
+
+def test_element_form_with_resource():
+    body = f'><dcterms:provenance rdf:resource="{URL}"/></rdf:Description>'
+    data = jpeg_with_xmp(xmp_packet(body).encode())
+    assert read_xmp_provenance(data)["provenance"] == URL
+
+
+def test_escaped_url():
+    data = jpeg_with_xmp(xmp_packet(' dcterms:provenance="https://example.com/m?a=1&amp;b=2"/>').encode())
+    assert read_xmp_provenance(data)["provenance"] == "https://example.com/m?a=1&b=2"
+
+
+@pytest.mark.parametrize("encoding", ["utf-16-le", "utf-16-be"])
+def test_utf16_packet(encoding):
+    xmp = "﻿" + xmp_packet(f' dcterms:provenance="{URL}"/>', wrap=False)
+    data = jpeg_with_xmp(xmp.encode(encoding))
+    result = read_xmp_provenance(data)
+    assert result["provenance"] == URL
+    assert result["packets"][0]["encoding"] == encoding.replace("-le", "le").replace("-be", "be")
+    assert result["packets"][0]["bom"]
+
+
+def test_truncated_packet_reports_warning():
+    xmp = xmp_packet(f' dcterms:provenance="{URL}"/>')
+    data = jpeg_with_xmp(xmp[:xmp.index("</rdf:RDF>")].encode())
+    result = read_xmp_provenance(data)
+    assert result["provenance"] == URL
+    assert any("not terminated" in warning for warning in result["warnings"])
+
+
+def test_conflicting_packets_report_warning():
+    first = xmp_packet(f' dcterms:provenance="{URL}"/>').encode()
+    second = xmp_packet(' dcterms:provenance="https://example.org/other.c2pa"/>').encode()
+    result = read_xmp_provenance(jpeg_with_xmp(first) + jpeg_with_xmp(second))
+    assert result["provenance"] == URL
+    assert len(result["packets"]) == 2
+    assert any("conflicting" in warning for warning in result["warnings"])
+
+
+def test_no_xmp():
+    result = read_xmp_provenance(b"\xff\xd8\xff\xd9")
+    assert result == {"provenance": None, "xmp_found": False, "packets": [], "warnings": []}
+
+
+def test_xmp_without_provenance():
+    data = jpeg_with_xmp(xmp_packet(' dcterms:title="x"/>').encode())
+    result = read_xmp_provenance(data)
+    assert result["xmp_found"]
+    assert result["provenance"] is None
+
+
+def png_chunks(data):
+    pos, chunks = 8, []
+    while pos < len(data):
+        (length,) = struct.unpack(">I", data[pos:pos + 4])
+        chunks.append(data[pos + 4:pos + 8])
+        pos += 12 + length
+    return chunks
+
+
+def test_read_xmp():
+    xmp = xmp_packet(f' dcterms:provenance="{URL}"/>')
+    assert read_xmp(jpeg_with_xmp(xmp.encode()), "image/jpeg") == xmp
+    assert read_xmp(b"\xff\xd8\xff\xd9", "image/jpeg") is None
+    # Other formats are searched
+    assert read_xmp(b"\x00" * 16 + xmp.encode(), "image/webp") == xmp
+
+
+def test_set_remote_reference_jpeg():
+    data = (TEST_IMAGES_DIR / "adobe_firefly_image.jpg").read_bytes()
+    updated = set_remote_reference(data, "image/jpeg", URL)
+    assert read_xmp_provenance(updated)["provenance"] == URL
+    assert read_xmp(updated, "image/jpeg").startswith("<?xpacket")
+    result = read_c2pa_from_bytes(updated, "image/jpeg")
+    assert result["remote_url"] == URL
+    assert result["active_manifest"] == read_c2pa_from_bytes(data, "image/jpeg")["active_manifest"]
+
+    # The reference is replaced, not added again
+    other = "https://example.org/other.c2pa?a=1&b=2"
+    replaced = set_remote_reference(updated, "image/jpeg", other)
+    scan = read_xmp_provenance(replaced)
+    assert scan["provenance"] == other
+    assert len(scan["packets"]) == 1
+    assert scan["warnings"] == []
+    assert len(replaced) == len(updated) + len(other) - len(URL) + len("&amp;") - 1
+
+
+def test_set_remote_reference_keeps_xmp():
+    data = jpeg_with_xmp(xmp_packet(' dcterms:title="x"/>').encode())
+    xmp = read_xmp(set_remote_reference(data, "image/jpeg", URL), "image/jpeg")
+    assert 'dcterms:title="x"' in xmp
+    assert f'dcterms:provenance="{URL}"' in xmp
+
+    data = jpeg_with_xmp(xmp_packet(f'><terms:provenance>{URL}</terms:provenance></rdf:Description>',
+                                    prefix="terms").encode())
+    xmp = read_xmp(set_remote_reference(data, "image/jpeg", "https://example.org/m"), "image/jpeg")
+    assert "<terms:provenance>https://example.org/m</terms:provenance>" in xmp
+
+
+def test_set_remote_reference_png():
+    data = (TEST_IMAGES_DIR / "screenshot_noc2pa.png").read_bytes()
+    updated = set_remote_reference(data, "image/png", URL)
+    assert png_chunks(updated)[:2] == [b"IHDR", b"iTXt"]
+    assert png_chunks(updated)[2:] == png_chunks(data)[1:]
+    assert read_xmp_provenance(updated)["provenance"] == URL
+    replaced = set_remote_reference(updated, "image/png", URL + "2")
+    assert png_chunks(replaced) == png_chunks(updated)
+    assert read_xmp_provenance(replaced)["provenance"] == URL + "2"
+
+
+def test_set_remote_reference_errors():
+    with pytest.raises(ValueError, match="only JPEG and PNG"):
+        set_remote_reference(b"data", "video/mp4", URL)
+    with pytest.raises(ValueError, match="not a valid"):
+        set_remote_reference(b"not a png", "image/png", URL)
@@ -0,0 +1,70 @@
+"""
+Tests for reading manifests from assets inside ZIP archives.
+"""
+
+import io
+import zipfile
+
+import pytest
+
+from fast_c2pa_python import read_c2pa_from_bytes, read_c2pa_from_zip
+
+
+@pytest.fixture
+def bundle(read_image):
+    buffer = io.BytesIO()
+    with zipfile.ZipFile(buffer, "w", zipfile.ZIP_DEFLATED) as archive:
+        archive.writestr("images/", b"")
+        archive.writestr("images/C.jpg", read_image("C.jpg"))
+        archive.writestr("images/plain.png", read_image("screenshot_noc2pa.png"))
+        archive.writestr("images/broken.jpg", read_image("C.jpg")[:2000])
+        archive.writestr("notes.txt", b"not an image")
+        archive.writestr("icon.jpg", read_image("icon_test.jpg"), zipfile.ZIP_STORED)
+    return buffer.getvalue()
+
+
+def test_reads_supported_members(read_image, bundle):
+    members = read_c2pa_from_zip(bundle)
+    assert [m["name"] for m in members] == [
+        "images/C.jpg",
+        "images/plain.png",
+        "images/broken.jpg",
+        "icon.jpg",
+    ]
+
+    c = members[0]
+    assert c["mime_type"] == "image/jpeg"
+    assert c["size"] == len(read_image("C.jpg"))
+    assert c["error"] is None
+    assert c["result"] == read_c2pa_from_bytes(read_image("C.jpg"), "image/jpeg")
+
+    plain = members[1]
+    assert plain["result"] is None and plain["error"] is None
+    assert members[3]["result"] == read_c2pa_from_bytes(read_image("icon_test.jpg"), "image/jpeg")
+
+
+def test_member_errors_do_not_stop_scan(bundle):
+    broken = read_c2pa_from_zip(bundle)[2]
+    assert broken["result"] is None
+    # Either an error or no manifest at all, but never an exception
+    assert broken["error"] is None or broken["error"].startswith("Error")
+
+
+def test_member_pattern(bundle):
+    names = [m["name"] for m in read_c2pa_from_zip(bundle, member_pattern="images/*.jpg")]
+    assert names == ["images/C.jpg", "images/broken.jpg"]
+    assert read_c2pa_from_zip(bundle, member_pattern="*.txt") == []
+
+
+def test_path_input(bundle, tmp_path):
+    path = tmp_path / "bundle.zip"
+    path.write_bytes(bundle)
+    assert read_c2pa_from_zip(path) == read_c2pa_from_zip(bundle)
+    assert read_c2pa_from_zip(str(path), allow_threads=False) == read_c2pa_from_zip(bundle)
+
+
+def test_invalid_archive(tmp_path):
+    with pytest.raises(RuntimeError):
+        read_c2pa_from_zip(b"not a zip file")
+    with pytest.raises(OSError):
+        read_c2pa_from_zip(tmp_path / "missing.zip")